@@ -1,6 +1,6 @@
 use binrw::{binrw, BinRead};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 // stolen from offsetting, mostly
@@ -140,27 +140,19 @@ impl DrivenToWinZip {
         Ok(entries)
     }
 
-    pub fn try_zlib_deflate(compressed: &[u8], 
-        expected: usize, 
-        name: &str
-    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let mut decoder = flate2::read::ZlibDecoder::new(compressed);
-        let mut decompressed_data = Vec::new();
-
-        // Try zlib
-        if decoder.read_to_end(&mut decompressed_data).is_ok() && decompressed_data.len() == expected {
-            return Ok(decompressed_data);
-        }
+    // An entry's bytes exactly as stored and where they start
+    pub fn read_raw(
+        entry: &ZipDirEntry,
+        file: &mut File
+    ) -> Result<(u64, Vec<u8>), Box<dyn std::error::Error>> {
+        file.seek(SeekFrom::Start(entry.header_offset as u64))?;
+        let local_header = ZipLocalFileHeader::read(file)?;
+        let data_offset = entry.header_offset as u64 + 30 + local_header.file_name_length as u64 + local_header.extra_field_length as u64;
+        file.seek(SeekFrom::Start(data_offset))?;
 
-        // Try deflate if zlib fails
-        decompressed_data.clear();
-        let mut decoder = flate2::read::DeflateDecoder::new(compressed);
-        if decoder.read_to_end(&mut decompressed_data).is_ok() && decompressed_data.len() == expected {
-            println!("Successfully decompressed {}", name);
-            return Ok(decompressed_data);
-        } else {
-            return Err(format!("Failed to decompress {}", name).into());
-        }
+        let mut data = vec![0; entry.compressed_size as usize];
+        file.read_exact(&mut data)?;
+        Ok((data_offset, data))
     }
 
     pub fn extract_zip_file(
@@ -174,8 +166,25 @@ impl DrivenToWinZip {
         let mut compressed_data = vec![0; entry.compressed_size as usize];
         file.read_exact(&mut compressed_data)?;
 
-        let decompressed_data = Self::try_zlib_deflate(&compressed_data[..], entry.uncompressed_size as usize, &entry.file_name)?;
+        let decompressed_data = crate::gen::codecs::decompress(entry.compression_type, &compressed_data, entry.uncompressed_size as usize, &entry.file_name)?;
 
         Ok(decompressed_data)
     }
+
+    // Like extract_zip_file, but decompresses into `output` as it reads, for entries too big to hold
+    pub fn extract_zip_file_to(
+        entry: &ZipDirEntry,
+        file: &mut File,
+        output: &mut impl Write,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        file.seek(SeekFrom::Start(entry.header_offset as u64))?;
+
+        let _local_header = ZipLocalFileHeader::read(file)?;
+
+        let written = crate::gen::codecs::decompress_to(entry.compression_type, file.take(entry.compressed_size as u64), output, &entry.file_name)?;
+        if written != entry.uncompressed_size as u64 {
+            return Err(format!("{} came out as {} bytes, expected {}", entry.file_name, written, entry.uncompressed_size).into());
+        }
+        Ok(written)
+    }
 }
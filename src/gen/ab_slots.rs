@@ -0,0 +1,385 @@
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use super::paths::{display_name, long_path};
+use super::sniff::{self, FileKind};
+use super::storage_analyzer::format_size;
+
+// Read from each side for the byte and text comparisons
+const MAX_COMPARE_BYTES: u64 = 16 * 1024 * 1024;
+const BYTES_PER_ROW: usize = 16;
+// Line diffs larger than this (lines on one side times the other) mark the middle as one change
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    A,
+    B,
+}
+
+impl Slot {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Slot::A => "A",
+            Slot::B => "B",
+        }
+    }
+}
+
+// Which view a pinned pair opens in, from what both files are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareRoute {
+    Scenes,
+    // Archives or folders, entry by entry
+    Containers,
+    Textures,
+    Models,
+    Text,
+    Bytes,
+}
+
+pub fn route(a: &Path, b: &Path) -> CompareRoute {
+    if a.is_dir() || b.is_dir() {
+        return CompareRoute::Containers;
+    }
+    match (sniff::detect(a, &[]), sniff::detect(b, &[])) {
+        (Some(FileKind::Scene), Some(FileKind::Scene)) => CompareRoute::Scenes,
+        (Some(FileKind::Archive), Some(FileKind::Archive)) => CompareRoute::Containers,
+        (Some(FileKind::Texture), Some(FileKind::Texture)) => CompareRoute::Textures,
+        (Some(FileKind::ModelBuffer), Some(FileKind::ModelBuffer)) => CompareRoute::Models,
+        (Some(FileKind::Text), Some(FileKind::Text)) => CompareRoute::Text,
+        _ => CompareRoute::Bytes,
+    }
+}
+
+fn read_start(path: &Path) -> Result<(Vec<u8>, u64), String> {
+    let file = std::fs::File::open(long_path(path)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut data = Vec::new();
+    std::io::Read::read_to_end(&mut std::io::Read::take(file, MAX_COMPARE_BYTES), &mut data)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok((data, size))
+}
+
+pub struct ByteDiff {
+    a: Vec<u8>,
+    b: Vec<u8>,
+    size_a: u64,
+    size_b: u64,
+    // Rows with at least one differing byte, for jumping between them
+    changed_rows: Vec<usize>,
+    differing_bytes: usize,
+}
+
+impl ByteDiff {
+    pub fn load(a: &Path, b: &Path) -> Result<Self, String> {
+        let (a, size_a) = read_start(a)?;
+        let (b, size_b) = read_start(b)?;
+        let longest = a.len().max(b.len());
+        let differs = |i: usize| a.get(i) != b.get(i);
+        let differing_bytes = (0..longest).filter(|&i| differs(i)).count();
+        let changed_rows = (0..longest.div_ceil(BYTES_PER_ROW))
+            .filter(|row| (row * BYTES_PER_ROW..((row + 1) * BYTES_PER_ROW).min(longest)).any(differs))
+            .collect();
+        Ok(Self { a, b, size_a, size_b, changed_rows, differing_bytes })
+    }
+
+    fn rows(&self) -> usize {
+        self.a.len().max(self.b.len()).div_ceil(BYTES_PER_ROW)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LineChange {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+pub struct LineDiff {
+    a: Vec<String>,
+    b: Vec<String>,
+    rows: Vec<LineChange>,
+}
+
+// Common lines at both ends are skipped, and what's between is matched by longest common
+// subsequence while that stays small enough
+fn diff_lines(a: &[String], b: &[String]) -> Vec<LineChange> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (middle_a, middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut rows: Vec<LineChange> = (0..prefix).map(|i| LineChange::Same(i, i)).collect();
+    if (middle_a.len() + 1) * (middle_b.len() + 1) > MAX_DIFF_CELLS {
+        rows.extend((0..middle_a.len()).map(|i| LineChange::Removed(prefix + i)));
+        rows.extend((0..middle_b.len()).map(|i| LineChange::Added(prefix + i)));
+    } else {
+        // lengths[i][j]: longest common run of middle_a[i..] and middle_b[j..]
+        let width = middle_b.len() + 1;
+        let mut lengths = vec![0u32; (middle_a.len() + 1) * width];
+        for i in (0..middle_a.len()).rev() {
+            for j in (0..middle_b.len()).rev() {
+                lengths[i * width + j] = if middle_a[i] == middle_b[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < middle_a.len() || j < middle_b.len() {
+            if i < middle_a.len() && j < middle_b.len() && middle_a[i] == middle_b[j] {
+                rows.push(LineChange::Same(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < middle_b.len() && (i == middle_a.len() || lengths[i * width + j + 1] >= lengths[(i + 1) * width + j]) {
+                rows.push(LineChange::Added(prefix + j));
+                j += 1;
+            } else {
+                rows.push(LineChange::Removed(prefix + i));
+                i += 1;
+            }
+        }
+    }
+    let (start_a, start_b) = (a.len() - suffix, b.len() - suffix);
+    rows.extend((0..suffix).map(|i| LineChange::Same(start_a + i, start_b + i)));
+    rows
+}
+
+impl LineDiff {
+    pub fn load(a: &Path, b: &Path) -> Result<Self, String> {
+        let lines = |path: &Path| -> Result<Vec<String>, String> {
+            let (data, _) = read_start(path)?;
+            Ok(String::from_utf8_lossy(&data).lines().map(str::to_string).collect())
+        };
+        let (a, b) = (lines(a)?, lines(b)?);
+        let rows = diff_lines(&a, &b);
+        Ok(Self { a, b, rows })
+    }
+}
+
+// Totals of one model, filled in by the app, which owns the model loader
+#[derive(Debug, Clone, Default)]
+pub struct ModelSummary {
+    pub meshes: usize,
+    pub vertices: usize,
+    pub triangles: usize,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+}
+
+pub enum AbResult {
+    Bytes(ByteDiff),
+    Lines(LineDiff),
+    Models(ModelSummary, ModelSummary),
+    Error(String),
+}
+
+pub enum AbAction {
+    Compare,
+    Open(PathBuf),
+}
+
+// Two pinned files, A and B, that stay put while browsing. Files get pinned from the tree's
+// context menu or by dropping them (from the tree or the desktop) on a slot; Compare then opens
+// the view that suits both.
+#[derive(Default)]
+pub struct AbSlots {
+    pub a: Option<PathBuf>,
+    pub b: Option<PathBuf>,
+    // Byte, text and model comparisons; the others open their own views
+    pub result: Option<(PathBuf, PathBuf, AbResult)>,
+    jump_to_row: Option<usize>,
+    next_change: usize,
+}
+
+impl AbSlots {
+    pub fn pin(&mut self, slot: Slot, path: &Path) {
+        println!("Pinned {} as {}", path.display(), slot.label());
+        match slot {
+            Slot::A => self.a = Some(path.to_path_buf()),
+            Slot::B => self.b = Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn pinned(&self) -> Option<(PathBuf, PathBuf)> {
+        Some((self.a.clone()?, self.b.clone()?))
+    }
+
+    pub fn set_result(&mut self, a: &Path, b: &Path, result: AbResult) {
+        self.result = Some((a.to_path_buf(), b.to_path_buf(), result));
+        self.jump_to_row = None;
+        self.next_change = 0;
+    }
+
+    fn show_slot(&mut self, ui: &mut egui::Ui, slot: Slot, action: &mut Option<AbAction>) {
+        let pinned = match slot {
+            Slot::A => &mut self.a,
+            Slot::B => &mut self.b,
+        };
+        let frame = egui::Frame::group(ui.style()).inner_margin(4.0);
+        let (zone, dropped) = ui.dnd_drop_zone::<PathBuf, _>(frame, |ui| {
+            ui.set_width(ui.available_width().min(140.0));
+            ui.horizontal(|ui| {
+                ui.strong(slot.label());
+                match pinned.clone() {
+                    Some(path) => {
+                        let name = ui.add(egui::Label::new(display_name(&path)).truncate(true).sense(egui::Sense::click()));
+                        if name.on_hover_text(format!("{}\nClick to open", path.display())).clicked() {
+                            *action = Some(AbAction::Open(path));
+                        }
+                        if ui.small_button("✕").on_hover_text("Unpin").clicked() {
+                            *pinned = None;
+                        }
+                    }
+                    None => {
+                        ui.weak("Drop a file here");
+                    }
+                }
+            });
+        });
+        if let Some(path) = dropped {
+            *pinned = Some((*path).clone());
+        }
+        // Files dragged in from outside the app land on the slot under the pointer
+        if zone.response.contains_pointer() {
+            let dropped_files: Vec<PathBuf> = ui.ctx().input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+            if let Some(path) = dropped_files.into_iter().next() {
+                *pinned = Some(path);
+            }
+        }
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) -> Option<AbAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            self.show_slot(ui, Slot::A, &mut action);
+            if ui.small_button("⇄").on_hover_text("Swap A and B").clicked() {
+                std::mem::swap(&mut self.a, &mut self.b);
+            }
+            self.show_slot(ui, Slot::B, &mut action);
+            let ready = self.a.is_some() && self.b.is_some();
+            if ui.add_enabled(ready, egui::Button::new("Compare")).clicked() {
+                action = Some(AbAction::Compare);
+            }
+        });
+        action
+    }
+
+    // The byte, text or model comparison, for the app's window
+    pub fn show_result_ui(&mut self, ui: &mut egui::Ui) {
+        let Self { result, jump_to_row, next_change, .. } = self;
+        let Some((a, b, result)) = result else {
+            return;
+        };
+        ui.label(format!("A: {}", a.display()));
+        ui.label(format!("B: {}", b.display()));
+        ui.separator();
+        match result {
+            AbResult::Error(e) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            AbResult::Models(model_a, model_b) => Self::show_models(ui, model_a, model_b),
+            AbResult::Lines(diff) => Self::show_lines(ui, diff),
+            AbResult::Bytes(diff) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} vs {}; {} differing bytes in {} rows",
+                        format_size(diff.size_a),
+                        format_size(diff.size_b),
+                        diff.differing_bytes,
+                        diff.changed_rows.len()
+                    ));
+                    if ui.add_enabled(!diff.changed_rows.is_empty(), egui::Button::new("Next difference")).clicked() {
+                        *next_change %= diff.changed_rows.len();
+                        *jump_to_row = Some(diff.changed_rows[*next_change]);
+                        *next_change += 1;
+                    }
+                });
+                if diff.size_a.max(diff.size_b) > MAX_COMPARE_BYTES {
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("Only the first {} of each file are compared", format_size(MAX_COMPARE_BYTES)));
+                }
+                ui.separator();
+                Self::show_bytes(ui, diff, jump_to_row.take());
+            }
+        }
+    }
+
+    fn show_models(ui: &mut egui::Ui, a: &ModelSummary, b: &ModelSummary) {
+        let bounds = |m: &ModelSummary| {
+            let size: Vec<String> = (0..3).map(|i| format!("{:.3}", m.bounds_max[i] - m.bounds_min[i])).collect();
+            size.join(" × ")
+        };
+        let rows = [
+            ("Meshes", a.meshes.to_string(), b.meshes.to_string()),
+            ("Vertices", a.vertices.to_string(), b.vertices.to_string()),
+            ("Triangles", a.triangles.to_string(), b.triangles.to_string()),
+            ("Bounds", bounds(a), bounds(b)),
+        ];
+        egui::Grid::new("ab_models").striped(true).num_columns(3).show(ui, |ui| {
+            ui.strong("");
+            ui.strong("A");
+            ui.strong("B");
+            ui.end_row();
+            for (label, value_a, value_b) in rows {
+                ui.label(label);
+                if value_a == value_b {
+                    ui.label(&value_a);
+                    ui.label(&value_b);
+                } else {
+                    let changed = ui.visuals().warn_fg_color;
+                    ui.colored_label(changed, &value_a);
+                    ui.colored_label(changed, &value_b);
+                }
+                ui.end_row();
+            }
+        });
+    }
+
+    fn show_lines(ui: &mut egui::Ui, diff: &LineDiff) {
+        let added = diff.rows.iter().filter(|r| matches!(r, LineChange::Added(_))).count();
+        let removed = diff.rows.iter().filter(|r| matches!(r, LineChange::Removed(_))).count();
+        ui.label(format!("{} lines in A, {} in B; {} only in A, {} only in B", diff.a.len(), diff.b.len(), removed, added));
+        ui.separator();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both().id_source("ab_lines").auto_shrink([false, false]).show_rows(ui, row_height, diff.rows.len(), |ui, range| {
+            for row in &diff.rows[range] {
+                let (text, color) = match *row {
+                    LineChange::Same(i, j) => (format!("{:>5} {:>5}   {}", i + 1, j + 1, diff.a[i]), ui.visuals().text_color()),
+                    LineChange::Removed(i) => (format!("{:>5}       - {}", i + 1, diff.a[i]), egui::Color32::RED),
+                    LineChange::Added(j) => (format!("      {:>5} + {}", j + 1, diff.b[j]), egui::Color32::from_rgb(80, 170, 80)),
+                };
+                ui.label(egui::RichText::new(text).monospace().color(color));
+            }
+        });
+    }
+
+    fn show_bytes(ui: &mut egui::Ui, diff: &ByteDiff, jump_to_row: Option<usize>) {
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let mut scroll = egui::ScrollArea::both().id_source("ab_bytes").auto_shrink([false, false]);
+        if let Some(row) = jump_to_row {
+            scroll = scroll.vertical_scroll_offset(row as f32 * (row_height + ui.spacing().item_spacing.y));
+        }
+        let changed = egui::Color32::RED;
+        let hex = |data: &[u8], other: &[u8], start: usize, job: &mut egui::text::LayoutJob, normal: egui::Color32| {
+            for i in start..start + BYTES_PER_ROW {
+                let (text, color) = match data.get(i) {
+                    Some(byte) => (format!("{:02x} ", byte), if other.get(i) == Some(byte) { normal } else { changed }),
+                    None => ("   ".to_string(), normal),
+                };
+                job.append(&text, 0.0, egui::TextFormat::simple(egui::FontId::monospace(12.0), color));
+            }
+        };
+        scroll.show_rows(ui, row_height, diff.rows(), |ui, range| {
+            for row in range {
+                let start = row * BYTES_PER_ROW;
+                let normal = ui.visuals().text_color();
+                let mut job = egui::text::LayoutJob::default();
+                job.append(&format!("{:08x}  ", start), 0.0, egui::TextFormat::simple(egui::FontId::monospace(12.0), ui.visuals().weak_text_color()));
+                hex(&diff.a, &diff.b, start, &mut job, normal);
+                job.append(" │ ", 0.0, egui::TextFormat::simple(egui::FontId::monospace(12.0), ui.visuals().weak_text_color()));
+                hex(&diff.b, &diff.a, start, &mut job, normal);
+                ui.label(job);
+            }
+        });
+    }
+}
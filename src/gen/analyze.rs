@@ -0,0 +1,271 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+use super::signatures;
+use super::tasks::TaskContext;
+
+// The whole file is read into memory, so bigger files are refused
+const MAX_FILE_SIZE: u64 = 1 << 30;
+const MAX_HITS: usize = 10_000;
+// The entropy plot gets about this many points whatever the file size
+const TARGET_BLOCKS: usize = 2048;
+const MIN_BLOCK_SIZE: usize = 256;
+// Progress and cancellation are checked once per this many bytes
+const SCAN_STEP: usize = 1 << 20;
+// Streams inflating past this are reported without walking to their end
+const MAX_INFLATED: u64 = 256 << 20;
+// Same window the file sniffer reads
+const SNIFF_SIZE: usize = 4096;
+
+// A recognised header somewhere in the file. `length` runs to the end of the stream where the
+// format says where that is, otherwise up to the next hit.
+#[derive(Debug, Clone)]
+pub struct MagicHit {
+    pub offset: usize,
+    pub length: usize,
+    pub name: &'static str,
+    pub extension: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub path: PathBuf,
+    pub size: usize,
+    // What the signature database calls the whole file
+    pub format: Option<&'static str>,
+    pub block_size: usize,
+    // Bits per byte (0-8) for each block
+    pub entropy: Vec<f32>,
+    pub overall_entropy: f32,
+    pub hits: Vec<MagicHit>,
+    // More than MAX_HITS headers were found; the rest aren't listed
+    pub truncated: bool,
+}
+
+fn shannon_entropy(data: &[u8]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let len = data.len() as f32;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f32 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// Which wrapper a compressed stream has
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamFormat {
+    Zlib,
+    Deflate,
+}
+
+impl StreamFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StreamFormat::Zlib => "zlib",
+            StreamFormat::Deflate => "raw deflate",
+        }
+    }
+}
+
+// A stream that inflated cleanly. `compressed` is None for streams still going after
+// MAX_INFLATED bytes, which are reported without walking to their end.
+#[derive(Debug, Clone)]
+pub struct InflatedStream {
+    pub offset: usize,
+    pub format: StreamFormat,
+    pub compressed: Option<usize>,
+    pub inflated: u64,
+    // Start of the inflated data
+    pub preview: Vec<u8>,
+}
+
+// Inflates the stream at the start of `data`, keeping the first `keep` bytes of output (everything
+// for usize::MAX). None unless it's a stream that ends cleanly.
+fn inflate(data: &[u8], format: StreamFormat, keep: usize) -> Option<(Option<usize>, u64, Vec<u8>)> {
+    if format == StreamFormat::Zlib && !matches!(data, [0x78, 0x01 | 0x5e | 0x9c | 0xda, ..]) {
+        return None;
+    }
+    let mut inflater = flate2::Decompress::new(format == StreamFormat::Zlib);
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut kept = Vec::new();
+    loop {
+        let consumed = inflater.total_in() as usize;
+        let before = inflater.total_out();
+        let status = inflater
+            .decompress(&data[consumed..], &mut buffer, flate2::FlushDecompress::None)
+            .ok()?;
+        let produced = (inflater.total_out() - before) as usize;
+        let wanted = keep.saturating_sub(kept.len()).min(produced);
+        kept.extend_from_slice(&buffer[..wanted]);
+        match status {
+            flate2::Status::StreamEnd if inflater.total_out() > 0 => {
+                return Some((Some(inflater.total_in() as usize), inflater.total_out(), kept));
+            }
+            flate2::Status::StreamEnd => return None,
+            _ if keep != usize::MAX && inflater.total_out() > MAX_INFLATED => return Some((None, inflater.total_out(), kept)),
+            // Out of input before the end of the stream
+            _ if inflater.total_in() as usize == data.len() => return None,
+            _ if produced == 0 && inflater.total_in() as usize == consumed => return None,
+            _ => {}
+        }
+    }
+}
+
+// Name, extension and exact length (when the header gives it) of a format starting at `data`.
+// zlib needs inflating to be sure of, everything else comes from the signature database.
+fn identify(data: &[u8]) -> Option<(&'static str, &'static str, Option<usize>)> {
+    if let Some(signature) = signatures::database().identify_embedded(data) {
+        return Some((signature.name.as_str(), signature.extension(), signature.length(data)));
+    }
+    match data {
+        [0x78, ..] => inflate(data, StreamFormat::Zlib, 0).map(|(length, _, _)| ("zlib stream", "zlib", length)),
+        _ => None,
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let size = fs::metadata(long_path(path)).map_err(|e| e.to_string())?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(format!("{} is larger than the {} MB the analyzer reads", path.display(), MAX_FILE_SIZE >> 20));
+    }
+    fs::read(long_path(path)).map_err(|e| e.to_string())
+}
+
+pub fn analyze(path: &Path, task: &TaskContext) -> Result<Analysis, String> {
+    let data = read_file(path)?;
+
+    let block_size = (data.len() / TARGET_BLOCKS).max(MIN_BLOCK_SIZE).next_power_of_two();
+    let entropy = data.chunks(block_size).map(shannon_entropy).collect();
+    let overall_entropy = shannon_entropy(&data);
+    let format = signatures::database().identify(&data[..data.len().min(SNIFF_SIZE)]).map(|s| s.name.as_str());
+
+    task.set_total((data.len() + SCAN_STEP - 1) / SCAN_STEP);
+    let mut hits: Vec<MagicHit> = Vec::new();
+    let mut exact_lengths = Vec::new();
+    let mut truncated = false;
+    for offset in 0..data.len() {
+        if offset % SCAN_STEP == 0 {
+            if task.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            task.advance(format!("{} MB scanned", offset >> 20));
+        }
+        if let Some((name, extension, length)) = identify(&data[offset..]) {
+            if hits.len() == MAX_HITS {
+                truncated = true;
+                break;
+            }
+            hits.push(MagicHit { offset, length: 0, name, extension });
+            exact_lengths.push(length);
+        }
+    }
+
+    // Lengths the header didn't give run to the next hit
+    for index in 0..hits.len() {
+        let next = hits.get(index + 1).map_or(data.len(), |hit| hit.offset);
+        let offset = hits[index].offset;
+        hits[index].length = exact_lengths[index].map_or(next - offset, |length| length.min(data.len() - offset));
+    }
+
+    Ok(Analysis { path: path.to_path_buf(), size: data.len(), format, block_size, entropy, overall_entropy, hits, truncated })
+}
+
+// Copies `length` bytes at `offset` into `output`
+pub fn carve(path: &Path, offset: usize, length: usize, output: &Path) -> std::io::Result<()> {
+    let mut file = fs::File::open(long_path(path))?;
+    file.seek(SeekFrom::Start(offset as u64))?;
+    let mut region = Vec::with_capacity(length);
+    file.take(length as u64).read_to_end(&mut region)?;
+    let mut out = fs::File::create(long_path(output))?;
+    out.write_all(&region)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StreamSearch {
+    // Also try raw deflate at every offset, which is much slower than looking for zlib headers
+    pub raw_deflate: bool,
+    // Smaller streams are left out, raw deflate finds plenty of tiny false positives
+    pub min_inflated: u64,
+}
+
+impl Default for StreamSearch {
+    fn default() -> Self {
+        Self { raw_deflate: false, min_inflated: 64 }
+    }
+}
+
+const PREVIEW_BYTES: usize = 256;
+
+// Tries to inflate at every offset of the file. A stream that's found is skipped over, so the
+// results don't overlap.
+pub fn find_streams(path: &Path, search: StreamSearch, task: &TaskContext) -> Result<Vec<InflatedStream>, String> {
+    let data = read_file(path)?;
+    let mut formats = vec![StreamFormat::Zlib];
+    if search.raw_deflate {
+        formats.push(StreamFormat::Deflate);
+    }
+
+    task.set_total((data.len() + SCAN_STEP - 1) / SCAN_STEP);
+    let mut streams = Vec::new();
+    let mut next_step = 0;
+    let mut offset = 0;
+    while offset < data.len() {
+        while offset >= next_step {
+            if task.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            task.advance(format!("{} MB searched, {} streams", next_step >> 20, streams.len()));
+            next_step += SCAN_STEP;
+        }
+        let found = formats.iter().find_map(|&format| {
+            inflate(&data[offset..], format, PREVIEW_BYTES)
+                .filter(|&(_, inflated, _)| inflated >= search.min_inflated)
+                .map(|(compressed, inflated, preview)| InflatedStream { offset, format, compressed, inflated, preview })
+        });
+        match found {
+            Some(stream) => {
+                offset += stream.compressed.unwrap_or(1);
+                streams.push(stream);
+                if streams.len() == MAX_HITS {
+                    break;
+                }
+            }
+            None => offset += 1,
+        }
+    }
+    Ok(streams)
+}
+
+// Writes the inflated contents of each stream to its output. Returns how many were written.
+pub fn export_streams(path: &Path, streams: &[InflatedStream], outputs: &[PathBuf]) -> Result<usize, String> {
+    let data = read_file(path)?;
+    let mut exported = 0;
+    for (stream, output) in streams.iter().zip(outputs) {
+        let inflated = data.get(stream.offset..).and_then(|start| inflate(start, stream.format, usize::MAX));
+        let Some((_, _, inflated)) = inflated else {
+            eprintln!("Stream at 0x{:x} no longer inflates", stream.offset);
+            continue;
+        };
+        match fs::write(long_path(output), inflated) {
+            Ok(()) => exported += 1,
+            Err(e) => eprintln!("Failed to write {}: {}", output.display(), e),
+        }
+    }
+    Ok(exported)
+}
+
+// Extension for data starting with a known header, "bin" otherwise
+pub fn guess_extension(data: &[u8]) -> &'static str {
+    identify(data).map_or("bin", |(_, extension, _)| extension)
+}
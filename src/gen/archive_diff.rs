@@ -0,0 +1,293 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+use super::storage_analyzer::format_size;
+use super::tasks::TaskContext;
+
+// One file on either side of a comparison
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    pub name: String,
+    pub size: u64,
+    pub crc32: u32,
+    // Entries of archives inside a compared folder: the archive below the folder and the entry's
+    // name inside it. `name` is the two joined.
+    pub archive: Option<(String, String)>,
+    // Name hash the archive stores for the entry (MurmurHash3 in Disney Infinity archives)
+    pub name_hash: Option<u32>,
+}
+
+impl DiffEntry {
+    pub fn file(name: String, size: u64, crc32: u32) -> Self {
+        Self { name, size, crc32, archive: None, name_hash: None }
+    }
+
+    // The same asset wherever it's stored: the stored name hash, or the name inside the archive
+    fn identity(&self) -> Option<String> {
+        let (_, inner) = self.archive.as_ref()?;
+        Some(self.name_hash.map_or_else(|| inner.to_lowercase(), |hash| format!("{:08x}", hash)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    // Same contents under another name
+    Renamed,
+    // Same entry in another archive, possibly changed on the way
+    Moved,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 5] = [ChangeKind::Added, ChangeKind::Removed, ChangeKind::Changed, ChangeKind::Renamed, ChangeKind::Moved];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Changed => "Changed",
+            ChangeKind::Renamed => "Renamed",
+            ChangeKind::Moved => "Moved",
+        }
+    }
+
+    pub fn color(&self, ui: &egui::Ui) -> egui::Color32 {
+        match self {
+            ChangeKind::Added => egui::Color32::from_rgb(80, 170, 80),
+            ChangeKind::Removed => egui::Color32::RED,
+            ChangeKind::Changed => ui.visuals().warn_fg_color,
+            ChangeKind::Renamed | ChangeKind::Moved => ui.visuals().hyperlink_color,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffRow {
+    pub kind: ChangeKind,
+    pub old: Option<DiffEntry>,
+    pub new: Option<DiffEntry>,
+}
+
+impl DiffRow {
+    pub fn name(&self) -> &str {
+        self.new.as_ref().or(self.old.as_ref()).map_or("", |entry| entry.name.as_str())
+    }
+
+    pub fn size_delta(&self) -> i64 {
+        self.new.as_ref().map_or(0, |e| e.size as i64) - self.old.as_ref().map_or(0, |e| e.size as i64)
+    }
+}
+
+// Either side is an archive or a folder such as a game root
+#[derive(Debug, Clone)]
+pub struct ArchiveDiff {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub rows: Vec<DiffRow>,
+    pub unchanged: usize,
+}
+
+// Entries are matched by name first. What's left over on both sides is matched by size and CRC,
+// then archive entries by name hash, so moved files don't show as a removal and an addition.
+pub fn diff(old_path: &Path, new_path: &Path, old: Vec<DiffEntry>, new: Vec<DiffEntry>) -> ArchiveDiff {
+    let mut old_by_name: HashMap<String, DiffEntry> = old.into_iter().map(|e| (e.name.clone(), e)).collect();
+    let mut rows = Vec::new();
+    let mut added = Vec::new();
+    let mut unchanged = 0;
+    for entry in new {
+        match old_by_name.remove(&entry.name) {
+            Some(previous) if previous.size == entry.size && previous.crc32 == entry.crc32 => unchanged += 1,
+            Some(previous) => rows.push(DiffRow { kind: ChangeKind::Changed, old: Some(previous), new: Some(entry) }),
+            None => added.push(entry),
+        }
+    }
+
+    let mut removed_by_content: HashMap<(u64, u32), Vec<DiffEntry>> = HashMap::new();
+    for entry in old_by_name.into_values() {
+        removed_by_content.entry((entry.size, entry.crc32)).or_default().push(entry);
+    }
+    let mut still_added = Vec::new();
+    for entry in added {
+        let previous = removed_by_content.get_mut(&(entry.size, entry.crc32)).filter(|_| entry.size > 0).and_then(|list| list.pop());
+        match previous {
+            Some(previous) => rows.push(DiffRow { kind: ChangeKind::Renamed, old: Some(previous), new: Some(entry) }),
+            None => still_added.push(entry),
+        }
+    }
+
+    let mut removed_by_identity: HashMap<String, Vec<DiffEntry>> = HashMap::new();
+    let mut removed = Vec::new();
+    for entry in removed_by_content.into_values().flatten() {
+        match entry.identity() {
+            Some(identity) => removed_by_identity.entry(identity).or_default().push(entry),
+            None => removed.push(entry),
+        }
+    }
+    for entry in still_added {
+        let previous = entry.identity().and_then(|identity| removed_by_identity.get_mut(&identity)).and_then(|list| list.pop());
+        match previous {
+            Some(previous) => rows.push(DiffRow { kind: ChangeKind::Moved, old: Some(previous), new: Some(entry) }),
+            None => rows.push(DiffRow { kind: ChangeKind::Added, old: None, new: Some(entry) }),
+        }
+    }
+    for entry in removed.into_iter().chain(removed_by_identity.into_values().flatten()) {
+        rows.push(DiffRow { kind: ChangeKind::Removed, old: Some(entry), new: None });
+    }
+
+    rows.sort_by(|a, b| a.name().to_lowercase().cmp(&b.name().to_lowercase()));
+    ArchiveDiff { old: old_path.to_path_buf(), new: new_path.to_path_buf(), rows, unchanged }
+}
+
+// Every file below `root` with its CRC, named like archive entries. With `skip_archives` the
+// archives aren't hashed but returned with their names, for the caller to list their entries.
+pub fn list_folder(root: &Path, skip_archives: bool, task: &TaskContext) -> Result<(Vec<DiffEntry>, Vec<(PathBuf, String)>), String> {
+    let files: Vec<PathBuf> = walkdir::WalkDir::new(long_path(root))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut archives = Vec::new();
+    for path in files {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let relative = path.strip_prefix(long_path(root)).unwrap_or(&path);
+        let name = relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect::<Vec<_>>().join("/");
+        if skip_archives && name.to_lowercase().ends_with(".zip") {
+            archives.push((path, name));
+            continue;
+        }
+        task.set_message(name.clone());
+        let (size, crc32) = hash_file(&path).map_err(|e| format!("{}: {}", name, e))?;
+        entries.push(DiffEntry::file(name, size, crc32));
+    }
+    Ok((entries, archives))
+}
+
+// Size and CRC of a file, read in pieces
+pub fn hash_file(path: &Path) -> std::io::Result<(u64, u32)> {
+    let mut crc = flate2::Crc::new();
+    let mut size = 0;
+    let mut file = std::fs::File::open(long_path(path))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = std::io::Read::read(&mut file, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        crc.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, crc.sum()))
+}
+
+fn signed_size(delta: i64) -> String {
+    match delta {
+        0 => "0".to_string(),
+        d if d > 0 => format!("+{}", format_size(d as u64)),
+        d => format!("-{}", format_size(d.unsigned_abs())),
+    }
+}
+
+// What a click in the results asks the app to do
+pub enum DiffAction {
+    // `old` picks the side the entry is from
+    Open { old: bool, entry: DiffEntry },
+    // Both versions of a changed archive inside two folders
+    CompareArchives(String),
+    // Both versions of a changed OCT/BENT file, key by key
+    CompareScenes { old: DiffEntry, new: DiffEntry },
+}
+
+#[derive(Default)]
+pub struct DiffView {
+    filter: String,
+    hidden: Vec<ChangeKind>,
+}
+
+impl DiffView {
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, diff: &ArchiveDiff) -> Option<DiffAction> {
+        let count = |kind: ChangeKind| diff.rows.iter().filter(|row| row.kind == kind).count();
+        let total_delta: i64 = diff.rows.iter().map(DiffRow::size_delta).sum();
+        ui.label(format!(
+            "{} added, {} removed, {} changed, {} renamed, {} unchanged; size {}",
+            count(ChangeKind::Added),
+            count(ChangeKind::Removed),
+            count(ChangeKind::Changed),
+            count(ChangeKind::Renamed),
+            diff.unchanged,
+            signed_size(total_delta)
+        ));
+        ui.horizontal(|ui| {
+            for kind in ChangeKind::ALL {
+                let mut shown = !self.hidden.contains(&kind);
+                if ui.checkbox(&mut shown, kind.label()).changed() {
+                    self.hidden.retain(|k| *k != kind);
+                    if !shown {
+                        self.hidden.push(kind);
+                    }
+                }
+            }
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        let rows: Vec<&DiffRow> = diff.rows
+            .iter()
+            .filter(|row| !self.hidden.contains(&row.kind) && row.name().to_lowercase().contains(&filter))
+            .collect();
+        let folders = diff.old.is_dir() && diff.new.is_dir();
+        let mut action = None;
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical().id_source("diff_rows").auto_shrink([false, true]).show_rows(ui, row_height, rows.len(), |ui, range| {
+            egui::Grid::new("diff_grid").striped(true).num_columns(5).show(ui, |ui| {
+                for row in &rows[range] {
+                    ui.colored_label(row.kind.color(ui), row.kind.label());
+                    match (&row.old, &row.new, row.kind) {
+                        (Some(old), Some(_), ChangeKind::Renamed | ChangeKind::Moved) => ui.label(format!("{} → {}", old.name, row.name())),
+                        _ => ui.label(row.name()),
+                    };
+                    ui.label(match (&row.old, &row.new) {
+                        (Some(old), Some(new)) => format!("{} → {}", format_size(old.size), format_size(new.size)),
+                        (Some(old), None) => format_size(old.size),
+                        (None, Some(new)) => format_size(new.size),
+                        (None, None) => String::new(),
+                    });
+                    ui.label(signed_size(row.size_delta()));
+                    ui.horizontal(|ui| {
+                        if let Some(old) = &row.old {
+                            if ui.small_button("Open old").clicked() {
+                                action = Some(DiffAction::Open { old: true, entry: old.clone() });
+                            }
+                        }
+                        if let Some(new) = &row.new {
+                            if ui.small_button("Open new").clicked() {
+                                action = Some(DiffAction::Open { old: false, entry: new.clone() });
+                            }
+                        }
+                        let lower = row.name().to_lowercase();
+                        if folders && row.kind == ChangeKind::Changed && lower.ends_with(".zip") && ui.small_button("Compare").clicked() {
+                            action = Some(DiffAction::CompareArchives(row.name().to_string()));
+                        }
+                        let is_scene = lower.ends_with(".oct") || lower.ends_with(".bent");
+                        if let (Some(old), Some(new), true) = (&row.old, &row.new, is_scene) {
+                            if row.kind != ChangeKind::Renamed && ui.small_button("Compare keys").clicked() {
+                                action = Some(DiffAction::CompareScenes { old: old.clone(), new: new.clone() });
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+        action
+    }
+}
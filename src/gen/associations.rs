@@ -0,0 +1,157 @@
+use std::path::Path;
+
+// Game files double-clicked in the OS open in Tundra through its command-line path argument
+pub const EXTENSIONS: [&str; 5] = ["oct", "bent", "mtb", "tbody", "vbuf"];
+
+#[cfg(target_os = "windows")]
+const PROG_ID: &str = "Tundra.GameFile";
+
+#[cfg(target_os = "linux")]
+const DESKTOP_FILE: &str = "tundra.desktop";
+
+// `--workdir` is passed along because Tundra finds its config, icons and temp/ relative to the
+// folder it runs in, which isn't the file's folder when the OS launches it
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn launch_arguments(workdir: &Path) -> String {
+    format!("--workdir \"{}\"", workdir.display())
+}
+
+#[cfg(target_os = "windows")]
+pub fn register(executable: &Path, workdir: &Path) -> Result<String, String> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    // Per-user classes, so no elevation is needed
+    let classes = RegKey::predef(HKEY_CURRENT_USER).create_subkey("Software\\Classes").map_err(|e| e.to_string())?.0;
+    let (prog_id, _) = classes.create_subkey(PROG_ID).map_err(|e| e.to_string())?;
+    prog_id.set_value("", &"Tundra game file").map_err(|e| e.to_string())?;
+    let (icon, _) = prog_id.create_subkey("DefaultIcon").map_err(|e| e.to_string())?;
+    icon.set_value("", &format!("\"{}\",0", executable.display())).map_err(|e| e.to_string())?;
+    let (command, _) = prog_id.create_subkey("shell\\open\\command").map_err(|e| e.to_string())?;
+    command
+        .set_value("", &format!("\"{}\" {} \"%1\"", executable.display(), launch_arguments(workdir)))
+        .map_err(|e| e.to_string())?;
+
+    for extension in EXTENSIONS {
+        let (key, _) = classes.create_subkey(format!(".{}", extension)).map_err(|e| e.to_string())?;
+        key.set_value("", &PROG_ID).map_err(|e| e.to_string())?;
+        let (open_with, _) = key.create_subkey("OpenWithProgids").map_err(|e| e.to_string())?;
+        open_with.set_value(PROG_ID, &"").map_err(|e| e.to_string())?;
+    }
+    println!("Registered {} for {}", executable.display(), EXTENSIONS.join(", "));
+    Ok("Registered; Explorer may need a moment (or a sign-out) to pick up the change".to_string())
+}
+
+#[cfg(target_os = "windows")]
+pub fn unregister() -> Result<String, String> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    let classes = RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags("Software\\Classes", winreg::enums::KEY_ALL_ACCESS).map_err(|e| e.to_string())?;
+    for extension in EXTENSIONS {
+        let Ok(key) = classes.open_subkey_with_flags(format!(".{}", extension), winreg::enums::KEY_ALL_ACCESS) else {
+            continue;
+        };
+        // Leave extensions another program has taken over since
+        if key.get_value::<String, _>("").map_or(false, |handler| handler == PROG_ID) {
+            let _ = key.delete_value("");
+        }
+        if let Ok(open_with) = key.open_subkey_with_flags("OpenWithProgids", winreg::enums::KEY_ALL_ACCESS) {
+            let _ = open_with.delete_value(PROG_ID);
+        }
+    }
+    match classes.delete_subkey_all(PROG_ID) {
+        Ok(()) => Ok("Removed Tundra's file associations".to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok("Tundra wasn't registered".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn data_home() -> Result<std::path::PathBuf, String> {
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or_else(|| "Neither XDG_DATA_HOME nor HOME is set".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn mime_type(extension: &str) -> String {
+    format!("application/x-tundra-{}", extension)
+}
+
+// Runs a desktop database tool; they're missing on minimal installs, which only delays the change
+#[cfg(target_os = "linux")]
+fn run_tool(program: &str, args: &[&str]) {
+    match std::process::Command::new(program).args(args).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("{} exited with {}", program, status),
+        Err(e) => eprintln!("Couldn't run {}: {}", program, e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn register(executable: &Path, workdir: &Path) -> Result<String, String> {
+    let data = data_home()?;
+    let applications = data.join("applications");
+    let mime = data.join("mime");
+
+    // The games' files have no registered types, so each extension gets one of its own
+    let mut package = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<mime-info xmlns=\"http://www.freedesktop.org/standards/shared-mime-info\">\n");
+    for extension in EXTENSIONS {
+        package.push_str(&format!(
+            "  <mime-type type=\"{}\">\n    <comment>{} game file</comment>\n    <glob pattern=\"*.{}\"/>\n  </mime-type>\n",
+            mime_type(extension),
+            extension.to_uppercase(),
+            extension
+        ));
+    }
+    package.push_str("</mime-info>\n");
+    super::paths::write_creating_dirs(&mime.join("packages/tundra.xml"), package.as_bytes()).map_err(|e| e.to_string())?;
+
+    let types: Vec<String> = EXTENSIONS.iter().map(|e| mime_type(e)).collect();
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=Tundra\nComment=Browse and edit game assets\nExec=\"{}\" {} %F\nPath={}\nMimeType={};\nTerminal=false\nCategories=Development;Graphics;\n",
+        executable.display(),
+        launch_arguments(workdir),
+        workdir.display(),
+        types.join(";")
+    );
+    super::paths::write_creating_dirs(&applications.join(DESKTOP_FILE), entry.as_bytes()).map_err(|e| e.to_string())?;
+
+    run_tool("update-mime-database", &[&mime.to_string_lossy()]);
+    run_tool("update-desktop-database", &[&applications.to_string_lossy()]);
+    let mut args = vec!["default", DESKTOP_FILE];
+    args.extend(types.iter().map(String::as_str));
+    run_tool("xdg-mime", &args);
+    println!("Registered {} for {}", executable.display(), EXTENSIONS.join(", "));
+    Ok(format!("Registered {}", applications.join(DESKTOP_FILE).display()))
+}
+
+#[cfg(target_os = "linux")]
+pub fn unregister() -> Result<String, String> {
+    let data = data_home()?;
+    let mut removed = 0;
+    for file in [data.join("applications").join(DESKTOP_FILE), data.join("mime/packages/tundra.xml")] {
+        match std::fs::remove_file(&file) {
+            Ok(()) => removed += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("{}: {}", file.display(), e)),
+        }
+    }
+    run_tool("update-mime-database", &[&data.join("mime").to_string_lossy()]);
+    run_tool("update-desktop-database", &[&data.join("applications").to_string_lossy()]);
+    Ok(if removed > 0 { "Removed Tundra's file associations".to_string() } else { "Tundra wasn't registered".to_string() })
+}
+
+// Finder associations come from the app bundle's Info.plist, which a bare binary doesn't have
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn register(_executable: &Path, _workdir: &Path) -> Result<String, String> {
+    Err("Registering file types isn't supported on this OS".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn unregister() -> Result<String, String> {
+    Err("Registering file types isn't supported on this OS".to_string())
+}
@@ -0,0 +1,384 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::Path;
+use super::sniff::parse_magic;
+
+// User structure definitions, one per file, picked up at startup
+pub const TEMPLATE_DIR: &str = "binary_templates";
+pub const TEMPLATE_EXTENSION: &str = "struct";
+
+const MAX_FIELDS: usize = 20_000;
+const MAX_DEPTH: usize = 16;
+// Arrays longer than this are shown as a single field
+const MAX_INLINE_VALUES: usize = 16;
+
+// Structure definitions applied by the hex viewer. One statement per line, `#` starts a comment:
+//
+//   magic 54 45 58 42        # applied automatically to files starting with these bytes
+//   endian big               # little by default; can change between fields
+//   struct Entry {
+//       u8[8] id
+//       u32 flags
+//   }
+//   struct File {            # the last struct is the root
+//       char[4] tag
+//       u32 count
+//       Entry[count] entries # counts can name an earlier field of the same struct
+//       skip 4
+//   }
+//
+// Types: u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char, or any struct defined above.
+#[derive(Debug, Clone)]
+enum Count {
+    Fixed(usize),
+    Field(String),
+}
+
+#[derive(Debug, Clone)]
+enum FieldType {
+    Int { size: usize, signed: bool },
+    Float { size: usize },
+    Char,
+    Struct(String),
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+struct FieldDef {
+    name: String,
+    field_type: FieldType,
+    count: Option<Count>,
+    big_endian: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinaryTemplate {
+    pub name: String,
+    pub magic: Vec<u8>,
+    structs: Vec<(String, Vec<FieldDef>)>,
+}
+
+// A field found in the data; `depth` is how many structs it's nested in
+#[derive(Debug, Clone)]
+pub struct FieldValue {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub value: String,
+    pub depth: usize,
+}
+
+fn parse_type(text: &str, structs: &[(String, Vec<FieldDef>)]) -> Result<(FieldType, Option<Count>), String> {
+    let (base, count) = match text.split_once('[') {
+        Some((base, rest)) => {
+            let count = rest.strip_suffix(']').ok_or_else(|| format!("missing ] in {}", text))?;
+            let count = match count.parse() {
+                Ok(n) => Count::Fixed(n),
+                Err(_) => Count::Field(count.to_string()),
+            };
+            (base, Some(count))
+        }
+        None => (text, None),
+    };
+    let field_type = match base {
+        "u8" => FieldType::Int { size: 1, signed: false },
+        "u16" => FieldType::Int { size: 2, signed: false },
+        "u32" => FieldType::Int { size: 4, signed: false },
+        "u64" => FieldType::Int { size: 8, signed: false },
+        "i8" => FieldType::Int { size: 1, signed: true },
+        "i16" => FieldType::Int { size: 2, signed: true },
+        "i32" => FieldType::Int { size: 4, signed: true },
+        "i64" => FieldType::Int { size: 8, signed: true },
+        "f32" => FieldType::Float { size: 4 },
+        "f64" => FieldType::Float { size: 8 },
+        "char" => FieldType::Char,
+        name if structs.iter().any(|(s, _)| s == name) => FieldType::Struct(name.to_string()),
+        other => return Err(format!("unknown type {}", other)),
+    };
+    Ok((field_type, count))
+}
+
+impl BinaryTemplate {
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut magic = Vec::new();
+        let mut big_endian = false;
+        let mut structs: Vec<(String, Vec<FieldDef>)> = Vec::new();
+        let mut current: Option<(String, Vec<FieldDef>)> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {}", index + 1, message);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["endian", "big"] => big_endian = true,
+                ["endian", "little"] => big_endian = false,
+                ["magic", bytes @ ..] => {
+                    magic = parse_magic(&bytes.join(" ")).ok_or_else(|| error("magic isn't hex".to_string()))?;
+                }
+                ["struct", struct_name, "{"] => {
+                    if current.is_some() {
+                        return Err(error("structs can't be nested, close the previous one first".to_string()));
+                    }
+                    current = Some((struct_name.to_string(), Vec::new()));
+                }
+                ["}"] => {
+                    let finished = current.take().ok_or_else(|| error("} without a struct".to_string()))?;
+                    structs.push(finished);
+                }
+                ["skip", count] => {
+                    let fields = &mut current.as_mut().ok_or_else(|| error("skip outside a struct".to_string()))?.1;
+                    let count = match count.parse() {
+                        Ok(n) => Count::Fixed(n),
+                        Err(_) => Count::Field(count.to_string()),
+                    };
+                    fields.push(FieldDef { name: "skip".to_string(), field_type: FieldType::Skip, count: Some(count), big_endian });
+                }
+                [field_type, field_name] => {
+                    let (field_type, count) = parse_type(field_type, &structs).map_err(error)?;
+                    let fields = &mut current.as_mut().ok_or_else(|| error("field outside a struct".to_string()))?.1;
+                    fields.push(FieldDef { name: field_name.to_string(), field_type, count, big_endian });
+                }
+                _ => return Err(error(format!("expected `<type> <name>`, got `{}`", line))),
+            }
+        }
+
+        if let Some((struct_name, _)) = current {
+            return Err(format!("struct {} is never closed", struct_name));
+        }
+        if structs.is_empty() {
+            return Err("no structs defined".to_string());
+        }
+        Ok(Self { name: name.to_string(), magic, structs })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::parse(&name, &text)
+    }
+
+    // Fields laid over `data` from the root struct, plus the error that stopped the walk early
+    pub fn apply(&self, data: &[u8]) -> (Vec<FieldValue>, Option<String>) {
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        let (root, _) = self.structs.last().expect("parse rejects templates without structs");
+        let error = self.read_struct(root, data, &mut offset, "", 0, &mut fields).err();
+        (fields, error)
+    }
+
+    fn read_struct(&self, struct_name: &str, data: &[u8], offset: &mut usize, prefix: &str, depth: usize, out: &mut Vec<FieldValue>) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("{} nests deeper than {} structs", struct_name, MAX_DEPTH));
+        }
+        let (_, defs) = self.structs.iter().find(|(name, _)| name == struct_name)
+            .ok_or_else(|| format!("unknown struct {}", struct_name))?;
+        // Integer fields read so far, for counts that refer back to them
+        let mut locals: HashMap<&str, i64> = HashMap::new();
+
+        for def in defs {
+            if out.len() >= MAX_FIELDS {
+                return Err(format!("stopped after {} fields", MAX_FIELDS));
+            }
+            let name = format!("{}{}", prefix, def.name);
+            let count = match &def.count {
+                None => None,
+                Some(Count::Fixed(n)) => Some(*n),
+                Some(Count::Field(field)) => {
+                    let value = *locals.get(field.as_str()).ok_or_else(|| format!("{}: count field {} isn't an earlier integer", name, field))?;
+                    Some(usize::try_from(value).map_err(|_| format!("{}: count {} is negative", name, value))?)
+                }
+            };
+            let start = *offset;
+            let past_end = |size: usize| format!("{} at 0x{:x} runs past the end of the data", name, start.saturating_add(size).min(data.len()));
+
+            match &def.field_type {
+                FieldType::Skip => {
+                    let size = count.unwrap_or(0);
+                    slice(data, start, size).ok_or_else(|| past_end(size))?;
+                    *offset += size;
+                }
+                FieldType::Char => {
+                    let size = count.unwrap_or(1);
+                    let bytes = slice(data, start, size).ok_or_else(|| past_end(size))?;
+                    let text = String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string();
+                    out.push(FieldValue { name, offset: start, size, value: format!("\"{}\"", text), depth });
+                    *offset += size;
+                }
+                FieldType::Int { .. } | FieldType::Float { .. } => {
+                    let (size, signed, is_float) = match def.field_type {
+                        FieldType::Int { size, signed } => (size, signed, false),
+                        FieldType::Float { size } => (size, true, true),
+                        _ => unreachable!(),
+                    };
+                    let elements = count.unwrap_or(1);
+                    let total = size.checked_mul(elements).ok_or_else(|| past_end(usize::MAX))?;
+                    let bytes = slice(data, start, total).ok_or_else(|| past_end(total))?;
+                    let values: Vec<String> = bytes
+                        .chunks_exact(size)
+                        .take(MAX_INLINE_VALUES)
+                        .map(|chunk| {
+                            let raw = read_uint(chunk, def.big_endian);
+                            if is_float {
+                                format_float(raw, size)
+                            } else if signed {
+                                sign_extend(raw, size).to_string()
+                            } else if size == 1 && count.is_some() {
+                                format!("{:02x}", raw)
+                            } else {
+                                format!("{} (0x{:x})", raw, raw)
+                            }
+                        })
+                        .collect();
+                    if count.is_none() && !is_float {
+                        let raw = read_uint(bytes, def.big_endian);
+                        let value = if signed { sign_extend(raw, size) } else { raw as i64 };
+                        locals.insert(def.name.as_str(), value);
+                    }
+                    let mut value = match count {
+                        None => values.join(""),
+                        Some(_) if size == 1 && !is_float && !signed => values.join(" "),
+                        Some(_) => format!("[{}]", values.join(", ")),
+                    };
+                    if elements > MAX_INLINE_VALUES {
+                        value.push_str(&format!(" … ({} total)", elements));
+                    }
+                    out.push(FieldValue { name, offset: start, size: total, value, depth });
+                    *offset += total;
+                }
+                FieldType::Struct(child) => match count {
+                    None => self.read_struct(child, data, offset, &format!("{}.", name), depth + 1, out)?,
+                    Some(elements) if elements > data.len() - start => return Err(past_end(elements)),
+                    Some(elements) => {
+                        for i in 0..elements {
+                            self.read_struct(child, data, offset, &format!("{}[{}].", name, i), depth + 1, out)?;
+                        }
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn slice(data: &[u8], start: usize, size: usize) -> Option<&[u8]> {
+    data.get(start..start.checked_add(size)?)
+}
+
+fn read_uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let fold = |value: u64, &byte: &u8| (value << 8) | byte as u64;
+    if big_endian {
+        bytes.iter().fold(0, fold)
+    } else {
+        bytes.iter().rev().fold(0, fold)
+    }
+}
+
+fn sign_extend(raw: u64, size: usize) -> i64 {
+    let shift = 64 - size * 8;
+    ((raw << shift) as i64) >> shift
+}
+
+fn format_float(raw: u64, size: usize) -> String {
+    if size == 4 {
+        f32::from_bits(raw as u32).to_string()
+    } else {
+        f64::from_bits(raw).to_string()
+    }
+}
+
+// Every template in TEMPLATE_DIR, with the files that failed to parse
+pub fn load_all() -> (Vec<BinaryTemplate>, Vec<String>) {
+    let mut templates = Vec::new();
+    let mut errors = Vec::new();
+    let Ok(entries) = std::fs::read_dir(TEMPLATE_DIR) else {
+        return (templates, errors);
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map_or(false, |e| e.eq_ignore_ascii_case(TEMPLATE_EXTENSION)))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match BinaryTemplate::load(&path) {
+            Ok(template) => templates.push(template),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    (templates, errors)
+}
+
+// Which field covers each byte of `data`, for colouring the dump
+pub fn byte_fields(fields: &[FieldValue], len: usize) -> Vec<Option<usize>> {
+    let mut owners = vec![None; len];
+    for (index, field) in fields.iter().enumerate() {
+        let end = (field.offset + field.size).min(len);
+        for owner in &mut owners[field.offset.min(end)..end] {
+            *owner = Some(index);
+        }
+    }
+    owners
+}
+
+fn field_color(index: usize, dark: bool) -> egui::Color32 {
+    const HUES: [(u8, u8, u8); 6] = [(230, 80, 80), (80, 170, 230), (90, 200, 110), (230, 180, 60), (170, 110, 230), (60, 200, 190)];
+    let (r, g, b) = HUES[index % HUES.len()];
+    egui::Color32::from_rgba_unmultiplied(r, g, b, if dark { 70 } else { 90 })
+}
+
+// One 16 byte row of the dump with each byte tinted by the field it belongs to
+pub fn hex_row(ui: &egui::Ui, data: &[u8], row: usize, owners: &[Option<usize>]) -> egui::text::LayoutJob {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let dark = ui.visuals().dark_mode;
+    let plain = egui::TextFormat::simple(font.clone(), text_color);
+    let mut job = egui::text::LayoutJob::default();
+
+    let start = row * 16;
+    let end = (start + 16).min(data.len());
+    job.append(&format!("{:08x}  ", start), 0.0, plain.clone());
+    for i in start..start + 16 {
+        let mut format = plain.clone();
+        if let Some(Some(owner)) = owners.get(i) {
+            format.background = field_color(*owner, dark);
+        }
+        let text = if i < end { format!("{:02x}", data[i]) } else { "  ".to_string() };
+        job.append(&text, 0.0, format);
+        job.append(" ", 0.0, plain.clone());
+    }
+    job.append(" ", 0.0, plain.clone());
+    for i in start..end {
+        let mut format = plain.clone();
+        if let Some(Some(owner)) = owners.get(i) {
+            format.background = field_color(*owner, dark);
+        }
+        let byte = data[i];
+        let c = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+        job.append(&c.to_string(), 0.0, format);
+    }
+    job
+}
+
+pub fn show_fields(ui: &mut egui::Ui, fields: &[FieldValue]) {
+    let dark = ui.visuals().dark_mode;
+    egui::ScrollArea::vertical().id_source("template_fields").max_height(240.0).show(ui, |ui| {
+        egui::Grid::new("template_fields_grid").striped(true).num_columns(4).show(ui, |ui| {
+            ui.strong("Field");
+            ui.strong("Offset");
+            ui.strong("Size");
+            ui.strong("Value");
+            ui.end_row();
+            for (index, field) in fields.iter().enumerate() {
+                let name = egui::RichText::new(format!("{}{}", "  ".repeat(field.depth), field.name)).background_color(field_color(index, dark));
+                ui.label(name);
+                ui.monospace(format!("0x{:x}", field.offset));
+                ui.label(field.size.to_string());
+                ui.monospace(&field.value);
+                ui.end_row();
+            }
+        });
+    });
+}
@@ -0,0 +1,410 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::io::{BufRead, BufReader, Read, Seek, Write};
+use std::sync::OnceLock;
+
+// Maps extra ZIP method ids to codecs and loads codec libraries, e.g. for Oodle:
+// {"methods": {"95": "lz4"},
+//  "plugins": [{"name": "oodle", "library": "oo2core_9_win64.dll", "symbol": "OodleLZ_Decompress",
+//               "abi": "oodle", "methods": [64]}]}
+pub const CONFIG_PATH: &str = "tundra_codecs.json";
+
+pub trait Codec: Send + Sync {
+    fn name(&self) -> &str;
+    // `expected` is the uncompressed size the archive records for the entry
+    fn decompress(&self, data: &[u8], expected: usize) -> Result<Vec<u8>, String>;
+}
+
+// `expected` comes from the archive, so buffers start no bigger than this and grow as data arrives
+const PREALLOCATE_LIMIT: usize = 64 * 1024 * 1024;
+// Deflate can't do better than about 1032:1; codecs that need the whole output buffer up front
+// are held to that above the preallocation limit
+const MAX_RATIO: usize = 1032;
+
+fn output_buffer(expected: usize) -> Vec<u8> {
+    Vec::with_capacity(expected.min(PREALLOCATE_LIMIT))
+}
+
+// For codecs that write into a buffer of exactly the recorded size
+fn check_output_size(data: &[u8], expected: usize) -> Result<(), String> {
+    if expected > PREALLOCATE_LIMIT && expected / MAX_RATIO > data.len() {
+        return Err(format!("{} bytes from {} compressed is more than any codec manages, the size is likely corrupt", expected, data.len()));
+    }
+    Ok(())
+}
+
+fn check_size(data: Vec<u8>, expected: usize) -> Result<Vec<u8>, String> {
+    if data.len() == expected {
+        Ok(data)
+    } else {
+        Err(format!("came out as {} bytes, expected {}", data.len(), expected))
+    }
+}
+
+struct Stored;
+
+impl Codec for Stored {
+    fn name(&self) -> &str {
+        "stored"
+    }
+
+    fn decompress(&self, data: &[u8], _expected: usize) -> Result<Vec<u8>, String> {
+        Ok(data.to_vec())
+    }
+}
+
+// The game archives mostly hold zlib streams under the deflate method, so both are tried
+struct Deflate;
+
+impl Codec for Deflate {
+    fn name(&self) -> &str {
+        "deflate"
+    }
+
+    fn decompress(&self, data: &[u8], expected: usize) -> Result<Vec<u8>, String> {
+        let mut output = output_buffer(expected);
+        if flate2::read::ZlibDecoder::new(data).read_to_end(&mut output).is_ok() && output.len() == expected {
+            return Ok(output);
+        }
+        output.clear();
+        flate2::read::DeflateDecoder::new(data).read_to_end(&mut output).map_err(|e| e.to_string())?;
+        check_size(output, expected)
+    }
+}
+
+struct Lzma;
+
+impl Codec for Lzma {
+    fn name(&self) -> &str {
+        "lzma"
+    }
+
+    fn decompress(&self, data: &[u8], expected: usize) -> Result<Vec<u8>, String> {
+        use lzma_rs::decompress::{Options, UnpackedSize};
+        // ZIP stores a 4-byte version and properties size before the properties and no size field;
+        // anything else is taken as a plain .lzma stream
+        let (mut input, unpacked_size) = if data.len() > 9 && data[2..4] == [5, 0] {
+            (&data[4..], UnpackedSize::UseProvided(Some(expected as u64)))
+        } else {
+            (data, UnpackedSize::ReadHeaderButUseProvided(Some(expected as u64)))
+        };
+        let options = Options { unpacked_size, ..Default::default() };
+        let mut output = output_buffer(expected);
+        lzma_rs::lzma_decompress_with_options(&mut input, &mut output, &options).map_err(|e| e.to_string())?;
+        check_size(output, expected)
+    }
+}
+
+const LZ4_FRAME_MAGIC: [u8; 4] = [0x04, 0x22, 0x4D, 0x18];
+
+// LZ4 has no standard ZIP method id, so it's either mapped in the config or found by trying
+struct Lz4;
+
+impl Codec for Lz4 {
+    fn name(&self) -> &str {
+        "lz4"
+    }
+
+    fn decompress(&self, data: &[u8], expected: usize) -> Result<Vec<u8>, String> {
+        if data.starts_with(&LZ4_FRAME_MAGIC) {
+            let mut output = output_buffer(expected);
+            lz4_flex::frame::FrameDecoder::new(data).read_to_end(&mut output).map_err(|e| e.to_string())?;
+            return check_size(output, expected);
+        }
+        check_output_size(data, expected)?;
+        let output = lz4_flex::block::decompress(data, expected).map_err(|e| e.to_string())?;
+        check_size(output, expected)
+    }
+}
+
+// Both return the number of bytes written, or a negative/zero value on failure
+type SimpleDecompress = unsafe extern "C" fn(*const u8, usize, *mut u8, usize) -> isize;
+// OodleLZ_Decompress; only the buffers are passed, the rest is left at Oodle's defaults
+type OodleDecompress = unsafe extern "C" fn(
+    *const u8, isize, *mut u8, isize, i32, i32, i32, *mut u8, isize, *mut c_void, *mut c_void, *mut c_void, isize, i32,
+) -> isize;
+
+enum Entry {
+    Simple(SimpleDecompress),
+    Oodle(OodleDecompress),
+}
+
+struct Plugin {
+    name: String,
+    entry: Entry,
+    // Keeps the function pointer valid
+    _library: libloading::Library,
+}
+
+impl Codec for Plugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decompress(&self, data: &[u8], expected: usize) -> Result<Vec<u8>, String> {
+        check_output_size(data, expected)?;
+        let mut output = vec![0u8; expected];
+        let written = unsafe {
+            match self.entry {
+                Entry::Simple(decompress) => decompress(data.as_ptr(), data.len(), output.as_mut_ptr(), output.len()),
+                Entry::Oodle(decompress) => decompress(
+                    data.as_ptr(), data.len() as isize, output.as_mut_ptr(), output.len() as isize,
+                    // fuzz safe, no CRC check, quiet, no dictionary/callback/scratch memory, all thread phases
+                    1, 0, 0, std::ptr::null_mut(), 0, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut(), 0, 3,
+                ),
+            }
+        };
+        if written <= 0 && expected > 0 {
+            return Err(format!("{} returned {}", self.name, written));
+        }
+        output.truncate(written.max(0) as usize);
+        check_size(output, expected)
+    }
+}
+
+#[derive(Deserialize)]
+struct PluginConfig {
+    name: String,
+    library: String,
+    symbol: String,
+    // "simple" or "oodle"
+    #[serde(default = "default_abi")]
+    abi: String,
+    #[serde(default)]
+    methods: Vec<u16>,
+}
+
+fn default_abi() -> String {
+    "simple".to_string()
+}
+
+#[derive(Deserialize, Default)]
+struct Config {
+    #[serde(default)]
+    methods: HashMap<String, String>,
+    #[serde(default)]
+    plugins: Vec<PluginConfig>,
+}
+
+fn load_plugin(config: &PluginConfig) -> Result<Plugin, String> {
+    unsafe {
+        let library = libloading::Library::new(&config.library).map_err(|e| e.to_string())?;
+        let entry = match config.abi.as_str() {
+            "simple" => Entry::Simple(*library.get::<SimpleDecompress>(config.symbol.as_bytes()).map_err(|e| e.to_string())?),
+            "oodle" => Entry::Oodle(*library.get::<OodleDecompress>(config.symbol.as_bytes()).map_err(|e| e.to_string())?),
+            other => return Err(format!("unknown abi \"{}\", expected \"simple\" or \"oodle\"", other)),
+        };
+        Ok(Plugin { name: config.name.clone(), entry, _library: library })
+    }
+}
+
+struct Registry {
+    codecs: Vec<Box<dyn Codec>>,
+    // ZIP method id -> index into codecs
+    methods: HashMap<u16, usize>,
+}
+
+impl Registry {
+    fn load() -> Self {
+        let mut registry = Registry {
+            codecs: vec![Box::new(Stored), Box::new(Deflate), Box::new(Lzma), Box::new(Lz4)],
+            methods: HashMap::from([(0, 0), (8, 1), (14, 2)]),
+        };
+        let Ok(json) = std::fs::read_to_string(CONFIG_PATH) else {
+            return registry;
+        };
+        let config: Config = match serde_json::from_str(&json) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Ignoring {}: {}", CONFIG_PATH, e);
+                return registry;
+            }
+        };
+        for plugin in &config.plugins {
+            match load_plugin(plugin) {
+                Ok(codec) => {
+                    println!("Loaded codec {} from {}", plugin.name, plugin.library);
+                    registry.codecs.push(Box::new(codec));
+                    for method in &plugin.methods {
+                        registry.methods.insert(*method, registry.codecs.len() - 1);
+                    }
+                }
+                Err(e) => eprintln!("Failed to load codec {} from {}: {}", plugin.name, plugin.library, e),
+            }
+        }
+        for (method, name) in &config.methods {
+            let (Ok(method), Some(index)) = (method.parse::<u16>(), registry.codecs.iter().position(|codec| codec.name() == name)) else {
+                eprintln!("Ignoring codec mapping {} -> {} in {}", method, name, CONFIG_PATH);
+                continue;
+            };
+            registry.methods.insert(method, index);
+        }
+        registry
+    }
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::load)
+}
+
+// Name of the codec handling `method`, for listings
+pub fn codec_name(method: u16) -> Option<&'static str> {
+    let registry = registry();
+    registry.methods.get(&method).map(|index| registry.codecs[*index].name())
+}
+
+// Decompresses one archive entry. Methods without a codec are tried against each one in turn,
+// since the game archives don't always record the method they actually used.
+pub fn decompress(method: u16, data: &[u8], expected: usize, name: &str) -> Result<Vec<u8>, String> {
+    let registry = registry();
+    if let Some(index) = registry.methods.get(&method) {
+        let codec = &registry.codecs[*index];
+        return codec.decompress(data, expected).map_err(|e| format!("Failed to decompress {} ({}): {}", name, codec.name(), e));
+    }
+    for codec in registry.codecs.iter().skip(1) {
+        if let Ok(output) = codec.decompress(data, expected) {
+            return Ok(output);
+        }
+    }
+    Err(format!("Failed to decompress {}: compression method {} isn't supported; a codec for it can be set up in {}", name, method, CONFIG_PATH))
+}
+
+// Decompresses an entry straight into `output`, for entries too big to hold in memory. Only the
+// built-in stored and deflate codecs can stream; returns the number of bytes written.
+pub fn decompress_to(method: u16, input: impl Read, output: &mut impl Write, name: &str) -> Result<u64, String> {
+    let registry = registry();
+    let codec = registry.methods.get(&method).map(|index| registry.codecs[*index].name());
+    let mut input = BufReader::new(input);
+    let written = match codec {
+        Some("stored") => std::io::copy(&mut input, output),
+        Some("deflate") => {
+            // A zlib header's first byte says deflate and both bytes together are a multiple of 31
+            let header = input.fill_buf().map_err(|e| format!("{}: {}", name, e))?;
+            let is_zlib = header.len() >= 2 && header[0] & 0x0F == 8 && (u16::from(header[0]) << 8 | u16::from(header[1])) % 31 == 0;
+            if is_zlib {
+                std::io::copy(&mut flate2::bufread::ZlibDecoder::new(input), output)
+            } else {
+                std::io::copy(&mut flate2::bufread::DeflateDecoder::new(input), output)
+            }
+        }
+        _ => return Err(format!("{} is too big to decompress in memory and method {} can't be streamed", name, method)),
+    };
+    written.map_err(|e| format!("Failed to decompress {}: {}", name, e))
+}
+
+// The method id when the zip crate can't read the entry itself
+pub fn unsupported_method<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, index: usize) -> Option<u16> {
+    #[allow(deprecated)]
+    match archive.by_index_raw(index).ok()?.compression() {
+        zip::CompressionMethod::Unsupported(method) => Some(method),
+        _ => None,
+    }
+}
+
+// Reads a whole entry of a regular ZIP, going through the registry for methods the zip crate
+// doesn't know. Those are checked against the entry's CRC here.
+pub fn read_zip_entry<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, index: usize) -> Result<Vec<u8>, String> {
+    let Some(method) = unsupported_method(archive, index) else {
+        let mut file = archive.by_index(index).map_err(|e| e.to_string())?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(|e| format!("{}: {}", file.name(), e))?;
+        return Ok(contents);
+    };
+    let mut file = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+    let (name, size, crc) = (file.name().to_string(), file.size() as usize, file.crc32());
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).map_err(|e| format!("{}: {}", name, e))?;
+    let contents = decompress(method, &data, size, &name)?;
+    let mut hasher = flate2::Crc::new();
+    hasher.update(&contents);
+    if hasher.sum() != crc {
+        return Err(format!("{}: CRC doesn't match after decompressing", name));
+    }
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SAMPLE: &[u8] = b"Tundra test data, repeated so it compresses. Tundra test data, repeated so it compresses.";
+
+    #[test]
+    fn stored_returns_the_input() {
+        assert_eq!(Stored.decompress(SAMPLE, SAMPLE.len()).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn deflate_reads_zlib_and_raw_streams() {
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(SAMPLE).unwrap();
+        assert_eq!(Deflate.decompress(&zlib.finish().unwrap(), SAMPLE.len()).unwrap(), SAMPLE);
+
+        let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        raw.write_all(SAMPLE).unwrap();
+        assert_eq!(Deflate.decompress(&raw.finish().unwrap(), SAMPLE.len()).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn lz4_reads_blocks_and_frames() {
+        let block = lz4_flex::block::compress(SAMPLE);
+        assert_eq!(Lz4.decompress(&block, SAMPLE.len()).unwrap(), SAMPLE);
+
+        let mut frame = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        frame.write_all(SAMPLE).unwrap();
+        assert_eq!(Lz4.decompress(&frame.finish().unwrap(), SAMPLE.len()).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn wrong_size_is_an_error() {
+        let block = lz4_flex::block::compress(SAMPLE);
+        assert!(Lz4.decompress(&block, SAMPLE.len() - 1).is_err());
+
+        let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        raw.write_all(SAMPLE).unwrap();
+        assert!(Deflate.decompress(&raw.finish().unwrap(), SAMPLE.len() + 1).is_err());
+    }
+
+    #[test]
+    fn huge_recorded_sizes_are_refused_before_allocating() {
+        assert!(check_output_size(&[0; 16], usize::MAX).is_err());
+        assert!(Lz4.decompress(&[0; 16], usize::MAX).is_err());
+        assert!(check_output_size(&[0; 16], PREALLOCATE_LIMIT).is_ok());
+        // A big output is fine when the input is big enough to plausibly hold it
+        assert!(check_output_size(&vec![0; PREALLOCATE_LIMIT], PREALLOCATE_LIMIT * 4).is_ok());
+    }
+
+    #[test]
+    fn huge_recorded_sizes_dont_preallocate() {
+        assert!(output_buffer(usize::MAX).capacity() <= PREALLOCATE_LIMIT);
+        // Deflate reads until the stream ends, so a lying size only fails the size check
+        let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        raw.write_all(SAMPLE).unwrap();
+        assert!(Deflate.decompress(&raw.finish().unwrap(), usize::MAX).is_err());
+    }
+
+    #[test]
+    fn decompress_to_streams_stored_and_deflate() {
+        let mut output = Vec::new();
+        assert_eq!(decompress_to(0, SAMPLE, &mut output, "stored").unwrap(), SAMPLE.len() as u64);
+        assert_eq!(output, SAMPLE);
+
+        for zlib in [true, false] {
+            let compressed = if zlib {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(SAMPLE).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(SAMPLE).unwrap();
+                encoder.finish().unwrap()
+            };
+            let mut output = Vec::new();
+            decompress_to(8, compressed.as_slice(), &mut output, "deflate").unwrap();
+            assert_eq!(output, SAMPLE);
+        }
+    }
+}
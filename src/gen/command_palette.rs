@@ -0,0 +1,126 @@
+use eframe::egui;
+
+// Subsequence match: every query character must appear in order. Consecutive runs and
+// matches at word starts score higher, so "oct sch" ranks "OCT schema report" first.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..text.len()).find(|&i| text[i] == query_char)?;
+        score += 1;
+        if previous_match.map_or(false, |p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous_match = Some(found);
+        position = found + 1;
+    }
+
+    // Prefer shorter labels when the match is otherwise equal
+    Some(score * 100 - text.len() as i32)
+}
+
+pub struct PaletteItem<T> {
+    pub command: T,
+    pub label: &'static str,
+    pub enabled: bool,
+}
+
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    // Returns the command picked with Enter or a click
+    pub fn show<T: Copy>(&mut self, ctx: &egui::Context, items: &[PaletteItem<T>]) -> Option<T> {
+        if !self.open {
+            return None;
+        }
+
+        let mut matches: Vec<(i32, &PaletteItem<T>)> = items
+            .iter()
+            .filter_map(|item| fuzzy_score(&self.query, item.label).map(|score| (score, item)))
+            .collect();
+        // Stable sort keeps registration order for equal scores
+        matches.sort_by_key(|(score, item)| (!item.enabled, -score));
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down && self.selected + 1 < matches.len() {
+            self.selected += 1;
+        }
+
+        let mut picked = None;
+        if enter {
+            picked = matches.get(self.selected).filter(|(_, item)| item.enabled).map(|(_, item)| item.command);
+        }
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::Vec2::new(0.0, 40.0))
+            .fixed_size(egui::Vec2::new(420.0, 0.0))
+            .show(ctx, |ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Type a command...")
+                    .desired_width(f32::INFINITY));
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        if matches.is_empty() {
+                            ui.label("No matching commands");
+                        }
+                        for (index, (_, item)) in matches.iter().enumerate() {
+                            let row = ui.add_enabled(item.enabled, egui::SelectableLabel::new(index == self.selected, item.label));
+                            if index == self.selected && (up || down) {
+                                row.scroll_to_me(None);
+                            }
+                            if row.clicked() {
+                                picked = Some(item.command);
+                            }
+                        }
+                    });
+            });
+
+        if picked.is_some() || escape {
+            self.open = false;
+        }
+        picked
+    }
+}
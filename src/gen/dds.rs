@@ -0,0 +1,277 @@
+// Minimal DDS header parsing, enough to split cubemaps and texture arrays into single surfaces
+// that the image crate can decode on their own
+
+const HEADER_SIZE: usize = 128;
+const DX10_HEADER_SIZE: usize = 20;
+
+const CAPS2_CUBEMAP: u32 = 0x200;
+const CAPS2_CUBEMAP_ALL_FACES: u32 = 0xFC00;
+const DX10_MISC_TEXTURECUBE: u32 = 0x4;
+
+// DDS face order
+pub const CUBE_FACE_NAMES: [&str; 6] = ["+X", "-X", "+Y", "-Y", "+Z", "-Z"];
+
+#[derive(Debug, Clone)]
+pub struct DdsLayout {
+    pub width: u32,
+    pub height: u32,
+    pub mip_count: u32,
+    pub is_cubemap: bool,
+    pub array_size: u32,
+    data_offset: usize,
+    has_dx10: bool,
+    // Bytes per 4x4 block for compressed formats, or bytes per pixel otherwise
+    block_bytes: usize,
+    compressed: bool,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+impl DdsLayout {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(0..4)? != b"DDS " {
+            return None;
+        }
+
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let mip_count = read_u32(data, 28)?.max(1);
+        let pixel_flags = read_u32(data, 80)?;
+        let four_cc = data.get(84..88)?;
+        let bit_count = read_u32(data, 88)?;
+        let caps2 = read_u32(data, 112)?;
+
+        let has_dx10 = four_cc == b"DX10";
+        let (block_bytes, compressed, is_cubemap, array_size) = if has_dx10 {
+            let dxgi_format = read_u32(data, HEADER_SIZE)?;
+            let misc_flag = read_u32(data, HEADER_SIZE + 8)?;
+            let array_size = read_u32(data, HEADER_SIZE + 12)?.max(1);
+            let (block_bytes, compressed) = match dxgi_format {
+                70..=72 | 79..=81 => (8, true),
+                73..=78 | 82..=84 | 94..=99 => (16, true),
+                27..=32 | 87..=93 => (4, false),
+                _ => return None,
+            };
+            (block_bytes, compressed, misc_flag & DX10_MISC_TEXTURECUBE != 0, array_size)
+        } else {
+            // DDPF_FOURCC
+            let (block_bytes, compressed) = if pixel_flags & 0x4 != 0 {
+                match four_cc {
+                    b"DXT1" | b"ATI1" | b"BC4U" | b"BC4S" => (8, true),
+                    b"DXT2" | b"DXT3" | b"DXT4" | b"DXT5" | b"ATI2" | b"BC5U" | b"BC5S" => (16, true),
+                    _ => return None,
+                }
+            } else {
+                ((bit_count / 8).max(1) as usize, false)
+            };
+            let is_cubemap = caps2 & CAPS2_CUBEMAP != 0;
+            (block_bytes, compressed, is_cubemap, 1)
+        };
+
+        Some(Self {
+            width,
+            height,
+            mip_count,
+            is_cubemap,
+            array_size,
+            data_offset: HEADER_SIZE + if has_dx10 { DX10_HEADER_SIZE } else { 0 },
+            has_dx10,
+            block_bytes,
+            compressed,
+        })
+    }
+
+    pub fn surface_count(&self) -> usize {
+        self.array_size as usize * if self.is_cubemap { 6 } else { 1 }
+    }
+
+    fn mip_size(&self, level: u32) -> usize {
+        let width = (self.width >> level).max(1) as usize;
+        let height = (self.height >> level).max(1) as usize;
+        if self.compressed {
+            width.div_ceil(4) * height.div_ceil(4) * self.block_bytes
+        } else {
+            width * height * self.block_bytes
+        }
+    }
+
+    // Each surface stores its full mip chain before the next one starts
+    fn surface_stride(&self) -> usize {
+        (0..self.mip_count).map(|level| self.mip_size(level)).sum()
+    }
+
+    // Builds a standalone single-surface, single-mip DDS for one face or slice
+    pub fn extract_surface(&self, data: &[u8], index: usize) -> Option<Vec<u8>> {
+        if index >= self.surface_count() {
+            return None;
+        }
+
+        let start = self.data_offset + index * self.surface_stride();
+        let pixels = data.get(start..start + self.mip_size(0))?;
+
+        let mut header = data.get(0..self.data_offset)?.to_vec();
+        // Mip count of one, and drop the cubemap caps so it reads as a plain 2D texture
+        header[28..32].copy_from_slice(&1u32.to_le_bytes());
+        let caps2 = read_u32(&header, 112)? & !(CAPS2_CUBEMAP | CAPS2_CUBEMAP_ALL_FACES);
+        header[112..116].copy_from_slice(&caps2.to_le_bytes());
+        if self.has_dx10 {
+            let misc_flag = read_u32(&header, HEADER_SIZE + 8)? & !DX10_MISC_TEXTURECUBE;
+            header[HEADER_SIZE + 8..HEADER_SIZE + 12].copy_from_slice(&misc_flag.to_le_bytes());
+            header[HEADER_SIZE + 12..HEADER_SIZE + 16].copy_from_slice(&1u32.to_le_bytes());
+        }
+
+        header.extend_from_slice(pixels);
+        Some(header)
+    }
+
+    pub fn surface_label(&self, index: usize) -> String {
+        if self.is_cubemap {
+            let face = CUBE_FACE_NAMES[index % 6];
+            if self.array_size > 1 {
+                format!("Slice {} face {}", index / 6, face)
+            } else {
+                format!("Face {}", face)
+            }
+        } else {
+            format!("Slice {}", index)
+        }
+    }
+}
+
+fn to_rgb565(color: [u8; 3]) -> u16 {
+    ((color[0] as u16 >> 3) << 11) | ((color[1] as u16 >> 2) << 5) | (color[2] as u16 >> 3)
+}
+
+fn from_rgb565(value: u16) -> [i32; 3] {
+    let r = ((value >> 11) & 0x1F) as i32;
+    let g = ((value >> 5) & 0x3F) as i32;
+    let b = (value & 0x1F) as i32;
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+// Bounding box endpoints, good enough for round-tripping edited textures
+fn encode_color_block(pixels: &[[u8; 4]; 16], out: &mut Vec<u8>) {
+    let mut min = [255u8; 3];
+    let mut max = [0u8; 3];
+    for pixel in pixels {
+        for c in 0..3 {
+            min[c] = min[c].min(pixel[c]);
+            max[c] = max[c].max(pixel[c]);
+        }
+    }
+
+    let mut c0 = to_rgb565(max);
+    let mut c1 = to_rgb565(min);
+    // c0 > c1 selects the four color mode in DXT1
+    if c0 < c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+
+    let mut indices = 0u32;
+    if c0 != c1 {
+        let (a, b) = (from_rgb565(c0), from_rgb565(c1));
+        let palette = [
+            a,
+            b,
+            [(2 * a[0] + b[0]) / 3, (2 * a[1] + b[1]) / 3, (2 * a[2] + b[2]) / 3],
+            [(a[0] + 2 * b[0]) / 3, (a[1] + 2 * b[1]) / 3, (a[2] + 2 * b[2]) / 3],
+        ];
+        for (i, pixel) in pixels.iter().enumerate() {
+            let best = (0..4)
+                .min_by_key(|&p| (0..3).map(|c| (pixel[c] as i32 - palette[p][c]).pow(2)).sum::<i32>())
+                .unwrap_or(0);
+            indices |= (best as u32) << (i * 2);
+        }
+    }
+
+    out.extend_from_slice(&c0.to_le_bytes());
+    out.extend_from_slice(&c1.to_le_bytes());
+    out.extend_from_slice(&indices.to_le_bytes());
+}
+
+fn encode_alpha_block(pixels: &[[u8; 4]; 16], out: &mut Vec<u8>) {
+    let a0 = pixels.iter().map(|p| p[3]).max().unwrap_or(255);
+    let a1 = pixels.iter().map(|p| p[3]).min().unwrap_or(255);
+
+    let mut indices = 0u64;
+    if a0 != a1 {
+        // Eight value mode: a0, a1, then six interpolated steps
+        let (a, b) = (a0 as i32, a1 as i32);
+        let mut palette = [a, b, 0, 0, 0, 0, 0, 0];
+        for step in 1..7 {
+            palette[step + 1] = ((7 - step as i32) * a + step as i32 * b) / 7;
+        }
+        for (i, pixel) in pixels.iter().enumerate() {
+            let best = (0..8)
+                .min_by_key(|&p| (pixel[3] as i32 - palette[p]).abs())
+                .unwrap_or(0);
+            indices |= (best as u64) << (i * 3);
+        }
+    }
+
+    out.push(a0);
+    out.push(a1);
+    out.extend_from_slice(&indices.to_le_bytes()[..6]);
+}
+
+fn encode_blocks(width: u32, height: u32, rgba: &[u8], with_alpha: bool, out: &mut Vec<u8>) {
+    let (width, height) = (width as usize, height as usize);
+    for block_y in (0..height).step_by(4) {
+        for block_x in (0..width).step_by(4) {
+            // Edge blocks repeat the last row/column
+            let mut pixels = [[0u8; 4]; 16];
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let x = (block_x + i % 4).min(width - 1);
+                let y = (block_y + i / 4).min(height - 1);
+                let offset = (y * width + x) * 4;
+                pixel.copy_from_slice(&rgba[offset..offset + 4]);
+            }
+            if with_alpha {
+                encode_alpha_block(&pixels, out);
+            }
+            encode_color_block(&pixels, out);
+        }
+    }
+}
+
+// Encodes an RGBA image as a DXT1 or DXT5 DDS with `mip_count` levels (clamped to the full chain)
+pub fn encode_dxt(image: &image::RgbaImage, with_alpha: bool, mip_count: u32) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let full_chain = 32 - width.max(height).max(1).leading_zeros();
+    let mip_count = mip_count.clamp(1, full_chain);
+    let block_bytes = if with_alpha { 16 } else { 8 };
+
+    let mut out = Vec::new();
+    let mut header = [0u32; 31];
+    header[0] = 124;
+    // CAPS | HEIGHT | WIDTH | PIXELFORMAT | LINEARSIZE, plus MIPMAPCOUNT when there are mips
+    header[1] = 0x1 | 0x2 | 0x4 | 0x1000 | 0x80000 | if mip_count > 1 { 0x20000 } else { 0 };
+    header[2] = height;
+    header[3] = width;
+    header[4] = width.div_ceil(4) * height.div_ceil(4) * block_bytes;
+    header[6] = mip_count;
+    // Pixel format: size, DDPF_FOURCC, fourcc
+    header[18] = 32;
+    header[19] = 0x4;
+    header[20] = u32::from_le_bytes(if with_alpha { *b"DXT5" } else { *b"DXT1" });
+    // DDSCAPS_TEXTURE, plus COMPLEX | MIPMAP for mip chains
+    header[26] = 0x1000 | if mip_count > 1 { 0x8 | 0x400000 } else { 0 };
+
+    out.extend_from_slice(b"DDS ");
+    for value in header {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    let mut level = image.clone();
+    for index in 0..mip_count {
+        if index > 0 {
+            let (w, h) = level.dimensions();
+            level = image::imageops::resize(&level, (w / 2).max(1), (h / 2).max(1), image::imageops::FilterType::Triangle);
+        }
+        let (w, h) = level.dimensions();
+        encode_blocks(w, h, level.as_raw(), with_alpha, &mut out);
+    }
+    out
+}
@@ -0,0 +1,91 @@
+use eframe::egui;
+use super::tbody_viewer::TbodyTexture;
+
+struct DetachedTexture {
+    id: egui::ViewportId,
+    texture: TbodyTexture,
+    // Shown at 1:1 in a scroll area instead of fitted to the window
+    actual_size: bool,
+    on_top: bool,
+}
+
+// Textures popped out of the viewer into windows of their own, so a reference can stay up on a
+// second monitor while other files are browsed. They keep their copy of the texture, so loading
+// another file doesn't close them.
+//
+// Only textures can be popped out. The scene, text and model views stay in the main window: they
+// show the state of the main window rather than a copy of their own, the scene view edits the open
+// file in place, and the model view draws through the main window's renderer.
+#[derive(Default)]
+pub struct DetachedViewers {
+    textures: Vec<DetachedTexture>,
+    next_id: u64,
+}
+
+impl DetachedViewers {
+    pub fn open(&mut self, ctx: &egui::Context, texture: TbodyTexture) {
+        // Popping out the same file twice brings the open window forward instead
+        if let Some(open) = self.textures.iter().find(|d| d.texture.file_path == texture.file_path && d.texture.name == texture.name) {
+            ctx.send_viewport_cmd_to(open.id, egui::ViewportCommand::Focus);
+            return;
+        }
+        self.next_id += 1;
+        let id = egui::ViewportId::from_hash_of(("detached_texture", self.next_id));
+        self.textures.push(DetachedTexture { id, texture, actual_size: false, on_top: false });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.textures.retain_mut(|detached| {
+            let (width, height) = detached.texture.dimensions;
+            let builder = egui::ViewportBuilder::default()
+                .with_title(format!("{} - Tundra", detached.texture.name))
+                .with_inner_size([(width as f32).clamp(256.0, 1024.0), (height as f32).clamp(256.0, 1024.0) + 40.0]);
+            ctx.show_viewport_immediate(detached.id, builder, |ctx, class| {
+                // Backends without multiple windows draw it as an egui window inside the main one
+                if class == egui::ViewportClass::Embedded {
+                    let mut open = true;
+                    egui::Window::new(&detached.texture.name)
+                        .id(egui::Id::new(detached.id))
+                        .open(&mut open)
+                        .default_size([512.0, 512.0])
+                        .show(ctx, |ui| Self::show_texture(ui, detached, false));
+                    return open;
+                }
+                egui::CentralPanel::default().show(ctx, |ui| Self::show_texture(ui, detached, true));
+                !ctx.input(|i| i.viewport().close_requested())
+            })
+        });
+    }
+
+    fn show_texture(ui: &mut egui::Ui, detached: &mut DetachedTexture, own_window: bool) {
+        let texture = &detached.texture;
+        ui.horizontal(|ui| {
+            ui.strong(&texture.name);
+            ui.label(format!("{}x{}", texture.dimensions.0, texture.dimensions.1));
+            ui.checkbox(&mut detached.actual_size, "Actual size");
+            if own_window && ui.checkbox(&mut detached.on_top, "Always on top").changed() {
+                let level = if detached.on_top { egui::WindowLevel::AlwaysOnTop } else { egui::WindowLevel::Normal };
+                ui.ctx().send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+            }
+        })
+        .response
+        .on_hover_text(texture.file_path.display().to_string());
+        ui.separator();
+
+        let texture = &detached.texture;
+        if texture.show_cross {
+            let size = ui.available_width().min(ui.available_height() * 4.0 / 3.0);
+            texture.show_cube_cross(ui, size);
+        } else if let Some(handle) = texture.display_handle() {
+            if detached.actual_size {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    ui.add(egui::Image::new(handle).fit_to_original_size(1.0));
+                });
+            } else {
+                ui.add(egui::Image::new(handle).max_size(ui.available_size()).maintain_aspect_ratio(true));
+            }
+        } else {
+            ui.label("Failed to load texture");
+        }
+    }
+}
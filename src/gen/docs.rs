@@ -0,0 +1,246 @@
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use super::jobs;
+use super::mtb_reader::{MtbFile, MtbParams};
+use super::naming::to_uri;
+use super::paths::{long_path, write_creating_dirs};
+use super::read_scene::{ContainerData, Data};
+use super::scene_diff;
+use super::scene_export::reference_stem;
+use super::sniff::{self, FileKind};
+use super::storage_analyzer::format_size;
+use super::tasks::TaskContext;
+
+const THUMBNAIL_SIZE: u32 = 256;
+// Scene keys listed per page before the rest are summarised
+const MAX_SCENE_KEYS: usize = 40;
+// File types the games reference from scenes and materials
+const REFERENCE_EXTENSIONS: [&str; 7] = [".ibuf", ".vbuf", ".mtb", ".tbody", ".dds", ".oct", ".bent"];
+
+// One documented file and what the pages say about it
+struct Asset {
+    name: String,
+    path: PathBuf,
+    kind: Option<FileKind>,
+    size: u64,
+    // Markdown written by the kind's section, before the reference lists
+    body: String,
+    // Stems of files this one names, resolved against the documented files once all are read
+    references: Vec<String>,
+}
+
+fn page_name(name: &str) -> String {
+    format!("{}.md", name)
+}
+
+// Relative link from the page of `from` to the page of `to`
+fn link(from: &str, to: &str) -> String {
+    let depth = from.matches('/').count();
+    format!("{}{}", "../".repeat(depth), page_name(to)).replace(' ', "%20")
+}
+
+fn collect_strings(map: &IndexMap<String, ContainerData>, out: &mut Vec<String>) {
+    for value in map.values() {
+        let items: Vec<&Data> = match value {
+            ContainerData::Single(data) => vec![data],
+            ContainerData::Multiple(list) => list.iter().collect(),
+        };
+        for data in items {
+            match data {
+                Data::Container(children) => collect_strings(children, out),
+                Data::String(value) => out.push(value.clone()),
+                Data::StringVec(values) => out.extend(values.iter().cloned()),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn count_keys(map: &IndexMap<String, ContainerData>) -> usize {
+    map.values()
+        .map(|value| match value {
+            ContainerData::Single(Data::Container(children)) => 1 + count_keys(children),
+            ContainerData::Multiple(list) => list.iter().map(|d| if let Data::Container(c) = d { 1 + count_keys(c) } else { 1 }).sum(),
+            ContainerData::Single(_) => 1,
+        })
+        .sum()
+}
+
+fn scene_section(path: &Path, asset: &mut Asset) {
+    let scene = match scene_diff::load(path) {
+        Ok(scene) => scene,
+        Err(e) => {
+            asset.body.push_str(&format!("Couldn't parse the scene: {}\n\n", e));
+            return;
+        }
+    };
+    asset.body.push_str(&format!("## Scene\n\n{} keys in total.\n\n| Key | Value |\n|---|---|\n", count_keys(&scene)));
+    for (key, value) in scene.iter().take(MAX_SCENE_KEYS) {
+        let summary = match value {
+            ContainerData::Single(data) => scene_diff::format_value(data),
+            ContainerData::Multiple(list) => format!("{} items", list.len()),
+        };
+        asset.body.push_str(&format!("| `{}` | {} |\n", key, summary.replace('|', "\\|")));
+    }
+    if scene.len() > MAX_SCENE_KEYS {
+        asset.body.push_str(&format!("\n...and {} more top-level keys.\n", scene.len() - MAX_SCENE_KEYS));
+    }
+    asset.body.push('\n');
+
+    let mut strings = Vec::new();
+    collect_strings(&scene, &mut strings);
+    asset.references.extend(
+        strings.iter()
+            .filter(|s| REFERENCE_EXTENSIONS.iter().any(|ext| s.to_lowercase().ends_with(ext)))
+            .map(|s| reference_stem(s)),
+    );
+}
+
+fn material_section(path: &Path, params: &MtbParams, asset: &mut Asset) {
+    match MtbFile::load_from_file(&long_path(path), params) {
+        Ok(mtb) => {
+            asset.body.push_str(&format!("## Material\n\n{} textures.\n\n| Texture | File |\n|---|---|\n", mtb.textures.len()));
+            for texture in &mtb.textures {
+                asset.body.push_str(&format!("| {} | `{}` |\n", texture.name, texture.tbody_filename));
+                asset.references.push(reference_stem(&texture.tbody_filename));
+            }
+            asset.body.push('\n');
+        }
+        Err(e) => asset.body.push_str(&format!("Couldn't read the material: {}\n\n", e)),
+    }
+}
+
+// Thumbnail next to the page, shrunk to fit THUMBNAIL_SIZE
+fn texture_section(path: &Path, output: &Path, asset: &mut Asset) {
+    let thumbnail = std::fs::read(long_path(path))
+        .map_err(|e| e.to_string())
+        .and_then(|data| jobs::texture_png_with_size(&data).map_err(|e| e.to_string()))
+        .and_then(|(png, size)| {
+            let image = image::load_from_memory_with_format(&png, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+            Ok((image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE), size))
+        });
+    match thumbnail {
+        Ok((image, (width, height))) => {
+            let file_name = format!("{}.png", Path::new(&asset.name).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+            let thumbnail_path = output.join(format!("{}.png", asset.name));
+            let saved = thumbnail_path.parent()
+                .map_or(Ok(()), |parent| std::fs::create_dir_all(long_path(parent)))
+                .map_err(|e| e.to_string())
+                .and_then(|_| image.save_with_format(long_path(&thumbnail_path), image::ImageFormat::Png).map_err(|e| e.to_string()));
+            asset.body.push_str(&format!("## Texture\n\n{}x{}\n\n", width, height));
+            match saved {
+                Ok(()) => asset.body.push_str(&format!("![{}]({})\n\n", file_name, file_name.replace(' ', "%20"))),
+                Err(e) => asset.body.push_str(&format!("Couldn't write the thumbnail: {}\n\n", e)),
+            }
+        }
+        Err(e) => asset.body.push_str(&format!("Couldn't decode the texture: {}\n\n", e)),
+    }
+}
+
+fn write_page(asset: &Asset, references: &[&str], referenced_by: &[&str], output: &Path) -> std::io::Result<()> {
+    let mut page = format!("# {}\n\n[Index]({})\n\n", asset.name, link(&asset.name, "index"));
+    page.push_str("| | |\n|---|---|\n");
+    page.push_str(&format!("| Path | `{}` |\n", asset.name));
+    page.push_str(&format!("| Size | {} ({} bytes) |\n", format_size(asset.size), asset.size));
+    page.push_str(&format!("| Type | {} |\n\n", asset.kind.map_or("Unknown", |k| k.label())));
+    page.push_str(&asset.body);
+    for (title, names) in [("References", references), ("Referenced by", referenced_by)] {
+        if names.is_empty() {
+            continue;
+        }
+        page.push_str(&format!("## {}\n\n", title));
+        for name in names {
+            page.push_str(&format!("- [{}]({})\n", name, link(&asset.name, name)));
+        }
+        page.push('\n');
+    }
+    write_creating_dirs(&output.join(page_name(&asset.name)), page.as_bytes())
+}
+
+fn write_index(title: &str, assets: &[Asset], output: &Path) -> std::io::Result<()> {
+    let mut by_kind: BTreeMap<&str, usize> = BTreeMap::new();
+    for asset in assets {
+        *by_kind.entry(asset.kind.map_or("Unknown", |k| k.label())).or_default() += 1;
+    }
+    let mut index = format!("# {}\n\n{} files.\n\n| Type | Files |\n|---|---|\n", title, assets.len());
+    for (kind, count) in &by_kind {
+        index.push_str(&format!("| {} | {} |\n", kind, count));
+    }
+
+    let mut folders: BTreeMap<String, Vec<&Asset>> = BTreeMap::new();
+    for asset in assets {
+        let folder = asset.name.rsplit_once('/').map_or(String::new(), |(folder, _)| folder.to_string());
+        folders.entry(folder).or_default().push(asset);
+    }
+    for (folder, assets) in &folders {
+        index.push_str(&format!("\n## {}\n\n", if folder.is_empty() { "/" } else { folder }));
+        for asset in assets {
+            let file_name = asset.name.rsplit('/').next().unwrap_or(&asset.name);
+            index.push_str(&format!("- [{}]({}) — {}\n", file_name, page_name(&asset.name).replace(' ', "%20"), format_size(asset.size)));
+        }
+    }
+    write_creating_dirs(&output.join("index.md"), index.as_bytes())
+}
+
+// Writes a page per file below `root` into `output`, plus index.md. Scenes list their keys,
+// materials their textures, textures get a thumbnail, and references between the files become
+// links both ways.
+pub fn generate(root: &Path, title: &str, output: &Path, params: &MtbParams, task: &TaskContext) -> Result<String, String> {
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(long_path(root))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    // Writing into the documented folder mustn't document the docs
+    files.retain(|path| !path.starts_with(long_path(output)));
+    task.set_total(files.len());
+
+    let mut assets = Vec::new();
+    for path in files {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let name = to_uri(path.strip_prefix(long_path(root)).unwrap_or(&path));
+        task.advance(name.clone());
+        let kind = sniff::detect(&path, &[]);
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let mut asset = Asset { name, path: path.clone(), kind, size, body: String::new(), references: Vec::new() };
+        match kind {
+            Some(FileKind::Scene) => scene_section(&path, &mut asset),
+            Some(FileKind::Material) => material_section(&path, params, &mut asset),
+            Some(FileKind::Texture) => texture_section(&path, output, &mut asset),
+            _ => {}
+        }
+        assets.push(asset);
+    }
+
+    let mut by_stem: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, asset) in assets.iter().enumerate() {
+        by_stem.entry(reference_stem(&asset.path.to_string_lossy())).or_default().push(index);
+    }
+    let mut references: Vec<Vec<usize>> = vec![Vec::new(); assets.len()];
+    let mut referenced_by: Vec<Vec<usize>> = vec![Vec::new(); assets.len()];
+    for (index, asset) in assets.iter().enumerate() {
+        for stem in &asset.references {
+            for &target in by_stem.get(stem).into_iter().flatten() {
+                if target != index && !references[index].contains(&target) {
+                    references[index].push(target);
+                    referenced_by[target].push(index);
+                }
+            }
+        }
+    }
+
+    for (index, asset) in assets.iter().enumerate() {
+        let names = |list: &[usize]| list.iter().map(|&i| assets[i].name.as_str()).collect::<Vec<_>>();
+        write_page(asset, &names(&references[index]), &names(&referenced_by[index]), output)
+            .map_err(|e| format!("{}: {}", asset.name, e))?;
+    }
+    write_index(title, &assets, output).map_err(|e| e.to_string())?;
+    let links: usize = references.iter().map(Vec::len).sum();
+    println!("Wrote documentation for {} files to {}", assets.len(), output.display());
+    Ok(format!("{} pages, {} links → {}", assets.len(), links, output.join("index.md").display()))
+}
@@ -0,0 +1,92 @@
+use eframe::egui;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// Rows shown per report before the rest are summarised
+const MAX_SHOWN: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct PlannedWrite {
+    pub target: PathBuf,
+    pub source: String,
+    pub overwrites: bool,
+}
+
+// Files a batch operation would write, listed instead of written while dry run is on
+#[derive(Debug, Clone, Default)]
+pub struct DryRunReport {
+    pub title: String,
+    pub writes: Vec<PlannedWrite>,
+    pub errors: Vec<String>,
+}
+
+impl DryRunReport {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), ..Default::default() }
+    }
+
+    pub fn add(&mut self, target: PathBuf, source: impl Into<String>) {
+        let overwrites = target.exists();
+        self.writes.push(PlannedWrite { target, source: source.into(), overwrites });
+    }
+
+    pub fn overwrite_count(&self) -> usize {
+        self.writes.iter().filter(|w| w.overwrites).count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!("{} files would be written, {} of them overwriting", self.writes.len(), self.overwrite_count())
+    }
+}
+
+// Filled in by the UI and by tasks, newest last
+#[derive(Clone, Default)]
+pub struct DryRunResults(Arc<Mutex<Vec<DryRunReport>>>);
+
+impl DryRunResults {
+    pub fn push(&self, report: DryRunReport) {
+        println!("[dry run] {}: {}", report.title, report.summary());
+        self.0.lock().unwrap().push(report);
+    }
+
+    pub fn show_ui(&self, ui: &mut egui::Ui) {
+        let mut reports = self.0.lock().unwrap();
+        if reports.is_empty() {
+            ui.label("Nothing planned yet. With dry run on, batch operations and deploys are listed here instead of written.");
+            return;
+        }
+        if ui.button("Clear").clicked() {
+            reports.clear();
+            return;
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical().id_source("dry_run_scroll").show(ui, |ui| {
+            for (index, report) in reports.iter().enumerate().rev() {
+                egui::CollapsingHeader::new(format!("{} — {}", report.title, report.summary()))
+                    .id_source(("dry_run_report", index))
+                    .default_open(index + 1 == reports.len())
+                    .show(ui, |ui| {
+                        for error in &report.errors {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                        egui::Grid::new(("dry_run_grid", index)).striped(true).num_columns(3).show(ui, |ui| {
+                            for write in report.writes.iter().take(MAX_SHOWN) {
+                                if write.overwrites {
+                                    ui.colored_label(egui::Color32::YELLOW, "Overwrite");
+                                } else {
+                                    ui.label("New");
+                                }
+                                ui.label(write.target.display().to_string());
+                                ui.weak(&write.source);
+                                ui.end_row();
+                            }
+                        });
+                        if report.writes.len() > MAX_SHOWN {
+                            ui.weak(format!("...and {} more", report.writes.len() - MAX_SHOWN));
+                        }
+                    });
+            }
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+
+const PROBE_NAME: &str = ".tundra_write_probe";
+
+pub fn is_access_denied(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::PermissionDenied
+}
+
+// Whether `path` can be written without actually changing it: an existing file is opened for
+// writing, a new one is tried as a scratch file next to where it would go
+pub fn check_writable(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        return fs::OpenOptions::new().write(true).open(long_path(path)).map(|_| ());
+    }
+    let Some(parent) = path.parent().filter(|p| p.exists()) else {
+        return Ok(());
+    };
+    let probe = parent.join(PROBE_NAME);
+    fs::OpenOptions::new().write(true).create_new(true).open(long_path(&probe))?;
+    fs::remove_file(long_path(&probe))
+}
+
+// `net session` only succeeds from an elevated prompt
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("net")
+        .arg("session")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+#[cfg(not(windows))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+// Starts Tundra again elevated, in the same folder and with the same files; the UAC prompt comes
+// from PowerShell, so the caller should only exit once this returns Ok
+#[cfg(windows)]
+pub fn relaunch_as_admin() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let workdir = std::env::current_dir()?;
+    let mut args: Vec<String> = Vec::new();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        // Replaced below, elevated processes otherwise start in System32
+        if arg == "--workdir" {
+            iter.next();
+            continue;
+        }
+        args.push(arg);
+    }
+    args.push("--workdir".to_string());
+    args.push(workdir.display().to_string());
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let argument_list = args.iter().map(|arg| quote(&format!("\"{}\"", arg))).collect::<Vec<_>>().join(",");
+    let command = format!("Start-Process -FilePath {} -Verb RunAs -ArgumentList {}", quote(&exe.display().to_string()), argument_list);
+    let status = std::process::Command::new("powershell").args(["-NoProfile", "-Command", &command]).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        // Also what declining the UAC prompt looks like
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "Elevation was cancelled or failed"))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn relaunch_as_admin() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Relaunching as administrator is only available on Windows"))
+}
+
+pub enum AccessChoice {
+    Relaunch,
+    Workspace(PathBuf),
+    Cancel,
+}
+
+// `redirected` is where the edit lands in the workspace instead. Restarting is only offered
+// without unsaved changes, since they'd be lost.
+pub fn ask(path: &Path, redirected: &Path, has_unsaved_changes: bool) -> AccessChoice {
+    let elevatable = cfg!(windows) && !is_elevated();
+    let can_relaunch = elevatable && !has_unsaved_changes;
+    let mut why = if cfg!(windows) {
+        "Windows only lets administrators change files in protected folders like Program Files.".to_string()
+    } else {
+        "Your account isn't allowed to change files in this folder.".to_string()
+    };
+    if elevatable && has_unsaved_changes {
+        why.push_str("\nSave to the workspace first to restart as administrator without losing changes.");
+    }
+    let (options, buttons) = if can_relaunch {
+        ("Yes: restart Tundra as administrator\nNo: write to the workspace instead\nCancel: don't write anything", MessageButtons::YesNoCancel)
+    } else {
+        ("OK: write to the workspace instead\nCancel: don't write anything", MessageButtons::OkCancel)
+    };
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Access denied")
+        .set_description(format!("{} can't be written.\n{}\n\nWorkspace copy: {}\n\n{}", path.display(), why, redirected.display(), options))
+        .set_buttons(buttons)
+        .show();
+    match answer {
+        MessageDialogResult::Yes => AccessChoice::Relaunch,
+        MessageDialogResult::No | MessageDialogResult::Ok => AccessChoice::Workspace(redirected.to_path_buf()),
+        _ => AccessChoice::Cancel,
+    }
+}
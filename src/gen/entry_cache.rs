@@ -0,0 +1,160 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use super::paths::VirtualPath;
+
+// Decompressed bytes are only worth keeping for recently viewed entries
+pub const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+// Archive, normalized entry name, and the archive's mtime so edited archives miss
+type CacheKey = (PathBuf, VirtualPath, u64);
+
+// Least recently used entries sit at the front of the map and are evicted first
+pub struct EntryCache {
+    entries: IndexMap<CacheKey, Vec<u8>>,
+    used_bytes: usize,
+    budget: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub type SharedEntryCache = Arc<Mutex<EntryCache>>;
+
+fn cache_key(archive: &Path, entry_name: &str) -> CacheKey {
+    let modified = std::fs::metadata(archive)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    (archive.to_path_buf(), VirtualPath::new(entry_name), modified)
+}
+
+impl EntryCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: IndexMap::new(),
+            used_bytes: 0,
+            budget,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn shared() -> SharedEntryCache {
+        Arc::new(Mutex::new(Self::new(DEFAULT_CACHE_BUDGET)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.used_bytes = 0;
+    }
+
+    // Evicts down to a smaller budget straight away
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.used_bytes > self.budget {
+            match self.entries.shift_remove_index(0) {
+                Some((_, evicted)) => self.used_bytes -= evicted.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<u8>> {
+        let index = self.entries.get_index_of(key)?;
+        let last = self.entries.len() - 1;
+        self.entries.move_index(index, last);
+        self.entries.get_index(last).map(|(_, data)| data.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, data: Vec<u8>) {
+        // One huge entry shouldn't flush everything else
+        if data.len() > self.budget / 4 {
+            return;
+        }
+        self.used_bytes += data.len();
+        if let Some(previous) = self.entries.insert(key, data) {
+            self.used_bytes -= previous.len();
+        }
+        self.evict();
+    }
+}
+
+// Returns the cached bytes for an archive entry, or runs `load` and remembers its result.
+// The lock isn't held while loading so other viewers aren't blocked on a slow decompress.
+pub fn get_or_load(
+    cache: &Mutex<EntryCache>,
+    archive: &Path,
+    entry_name: &str,
+    load: impl FnOnce() -> Result<Vec<u8>, Box<dyn std::error::Error>>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let key = cache_key(archive, entry_name);
+    {
+        let mut cache = cache.lock().unwrap();
+        if let Some(data) = cache.get(&key) {
+            cache.hits += 1;
+            return Ok(data);
+        }
+        cache.misses += 1;
+    }
+
+    let data = load()?;
+    cache.lock().unwrap().insert(key, data.clone());
+    Ok(data)
+}
+
+// What the viewers read files through. Files under an extraction folder are looked up as
+// entries of the archive they came from, so opening one shares the cache with tree reads.
+#[derive(Clone)]
+pub struct ViewerSource {
+    cache: SharedEntryCache,
+    // Extraction folder -> archive and when it was extracted
+    extracted: Arc<Mutex<HashMap<PathBuf, (PathBuf, SystemTime)>>>,
+}
+
+impl ViewerSource {
+    pub fn new(cache: SharedEntryCache) -> Self {
+        Self {
+            cache,
+            extracted: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn add_extracted(&self, dir: &Path, archive: &Path) {
+        self.extracted.lock().unwrap().insert(dir.to_path_buf(), (archive.to_path_buf(), SystemTime::now()));
+    }
+
+    // The archive and entry name a file was extracted from, unless it's been edited since
+    fn archive_entry(&self, path: &Path) -> Option<(PathBuf, VirtualPath)> {
+        let extracted = self.extracted.lock().unwrap();
+        let (dir, (archive, extracted_at)) = extracted.iter().find(|(dir, _)| path.starts_with(dir))?;
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        if modified > *extracted_at {
+            return None;
+        }
+        Some((archive.clone(), VirtualPath::from_path(path.strip_prefix(dir).ok()?)))
+    }
+
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let disk_path = super::paths::long_path(path);
+        match self.archive_entry(path) {
+            Some((archive, entry)) => get_or_load(&self.cache, &archive, entry.as_str(), || Ok(std::fs::read(&disk_path)?)),
+            None => Ok(std::fs::read(disk_path)?),
+        }
+    }
+}
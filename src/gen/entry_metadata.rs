@@ -0,0 +1,341 @@
+use binrw::{BinRead, BinWrite};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::c3dtw::read_zip::{ZipDirEndLocator, ZipDirEntry};
+use super::paths::long_path;
+use super::release_manifest::relative_name;
+
+// Kept in the folder being archived so every repack of it gets the same edits; never packed itself
+pub const METADATA_FILE: &str = ".tundra_entry_metadata.json";
+const EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4B, 0x05, 0x06];
+const ZIP64_LOCATOR_SIGNATURE: [u8; 4] = [0x50, 0x4B, 0x06, 0x07];
+
+// Fields left out keep what the writer put there
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    // "YYYY-MM-DD HH:MM:SS", stored as a DOS time so seconds are rounded down to even
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_attributes: Option<u32>,
+    // Central directory comment, which DtW's tooling reads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+// Entry name with forward slashes -> its edits
+pub type MetadataEdits = BTreeMap<String, EntryMetadata>;
+
+pub fn load(folder: &Path) -> Result<MetadataEdits, String> {
+    match fs::read_to_string(long_path(&folder.join(METADATA_FILE))) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Broken {}: {}", METADATA_FILE, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(MetadataEdits::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn save(folder: &Path, edits: &MetadataEdits) -> io::Result<()> {
+    let path = folder.join(METADATA_FILE);
+    if edits.is_empty() {
+        return match fs::remove_file(long_path(&path)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let json = serde_json::to_string_pretty(edits).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    fs::write(long_path(&path), json)
+}
+
+// (time, date) as ZIP headers store them
+pub fn parse_dos_time(text: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("\"{}\" isn't a time like 2017-06-13 12:00:00", text);
+    let numbers: Vec<u32> = text
+        .split(|c: char| c == '-' || c == ':' || c == ' ' || c == 'T')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let (year, month, day, hour, minute, second) = match numbers[..] {
+        [year, month, day] => (year, month, day, 0, 0, 0),
+        [year, month, day, hour, minute] => (year, month, day, hour, minute, 0),
+        [year, month, day, hour, minute, second] => (year, month, day, hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    if !(1980..=2107).contains(&year) {
+        return Err(format!("ZIP times only go from 1980 to 2107, not {}", year));
+    }
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+    let time = (hour << 11 | minute << 5 | second / 2) as u16;
+    let date = ((year - 1980) << 9 | month << 5 | day) as u16;
+    Ok((time, date))
+}
+
+fn find_end_locator(file: &mut fs::File) -> io::Result<u64> {
+    let length = file.metadata()?.len();
+    // The locator is at most a full-length archive comment from the end
+    let tail_length = length.min(22 + u16::MAX as u64);
+    file.seek(SeekFrom::Start(length - tail_length))?;
+    let mut tail = vec![0u8; tail_length as usize];
+    file.read_exact(&mut tail)?;
+    let position = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|i| tail[*i..*i + 4] == EOCD_SIGNATURE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No end of central directory record"))?;
+    if position >= 20 && tail[position - 20..position - 16] == ZIP64_LOCATOR_SIGNATURE {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "Entry metadata can't be edited in ZIP64 archives"));
+    }
+    Ok(length - tail_length + position as u64)
+}
+
+// Rewrites the central directory of `archive` with the edits, and the times in the local headers
+// to match. Returns how many entries were changed.
+pub fn apply(archive: &Path, edits: &MetadataEdits) -> Result<usize, String> {
+    let times: BTreeMap<&str, (u16, u16)> = edits
+        .iter()
+        .filter_map(|(name, edit)| edit.modified.as_ref().map(|text| (name, text)))
+        .map(|(name, text)| parse_dos_time(text).map(|time| (name.as_str(), time)).map_err(|e| format!("{}: {}", name, e)))
+        .collect::<Result<_, _>>()?;
+    let mut file = fs::OpenOptions::new().read(true).write(true).open(long_path(archive)).map_err(|e| e.to_string())?;
+    let locator_position = find_end_locator(&mut file).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(locator_position)).map_err(|e| e.to_string())?;
+    let mut locator = ZipDirEndLocator::read(&mut file).map_err(|e| e.to_string())?;
+
+    file.seek(SeekFrom::Start(locator.directory_offset as u64)).map_err(|e| e.to_string())?;
+    let mut entries = Vec::with_capacity(locator.entries_in_directory as usize);
+    for _ in 0..locator.entries_in_directory {
+        entries.push(ZipDirEntry::read(&mut file).map_err(|e| e.to_string())?);
+    }
+
+    let mut changed = 0;
+    for entry in &mut entries {
+        let Some(edit) = edits.get(&entry.file_name) else {
+            continue;
+        };
+        if let Some((time, date)) = times.get(entry.file_name.as_str()) {
+            (entry.file_time, entry.file_date) = (*time, *date);
+            // Local headers keep their own copy, 10 bytes in
+            file.seek(SeekFrom::Start(entry.header_offset as u64 + 10)).map_err(|e| e.to_string())?;
+            file.write_all(&time.to_le_bytes()).and_then(|_| file.write_all(&date.to_le_bytes())).map_err(|e| e.to_string())?;
+        }
+        if let Some(attributes) = edit.external_attributes {
+            entry.external_attributes = attributes;
+        }
+        if let Some(comment) = &edit.comment {
+            if comment.len() > u16::MAX as usize {
+                return Err(format!("{}: the comment is too long", entry.file_name));
+            }
+            entry.file_comment = comment.clone();
+        }
+        changed += 1;
+    }
+    let missing: Vec<&str> = edits.keys().filter(|name| !entries.iter().any(|e| &e.file_name == *name)).map(String::as_str).collect();
+    if !missing.is_empty() {
+        eprintln!("Metadata edits for entries not in {}: {}", archive.display(), missing.join(", "));
+    }
+
+    // Comments change the directory's length, so it's written out again along with the locator
+    file.set_len(locator.directory_offset as u64).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(locator.directory_offset as u64)).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(&mut file);
+    for entry in &entries {
+        entry.write(&mut writer).map_err(|e| e.to_string())?;
+    }
+    let directory_end = writer.stream_position().map_err(|e| e.to_string())?;
+    locator.directory_size = (directory_end - locator.directory_offset as u64) as u32;
+    locator.write(&mut writer).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(changed)
+}
+
+// One edited entry in the form, blank fields are left as written
+struct Row {
+    name: String,
+    modified: String,
+    attributes: String,
+    comment: String,
+}
+
+// The edits for one folder in the Create archive prompt
+pub struct MetadataEditor {
+    pub folder: PathBuf,
+    rows: Vec<Row>,
+    // Entry names the folder would be packed as
+    entries: Vec<String>,
+    error: Option<String>,
+}
+
+impl MetadataEditor {
+    pub fn new(folder: &Path) -> Self {
+        let entries = walkdir::WalkDir::new(long_path(folder))
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.file_name() != METADATA_FILE)
+            .map(|e| relative_name(&long_path(folder), e.path()))
+            .collect();
+        let (rows, error) = match load(folder) {
+            Ok(edits) => (
+                edits
+                    .into_iter()
+                    .map(|(name, edit)| Row {
+                        name,
+                        modified: edit.modified.unwrap_or_default(),
+                        attributes: edit.external_attributes.map(|a| format!("{:08X}", a)).unwrap_or_default(),
+                        comment: edit.comment.unwrap_or_default(),
+                    })
+                    .collect(),
+                None,
+            ),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        Self { folder: folder.to_path_buf(), rows, entries, error }
+    }
+
+    fn edits(&self) -> Result<MetadataEdits, String> {
+        let mut edits = MetadataEdits::new();
+        for row in &self.rows {
+            let modified = Some(row.modified.trim()).filter(|s| !s.is_empty()).map(String::from);
+            if let Some(text) = &modified {
+                parse_dos_time(text).map_err(|e| format!("{}: {}", row.name, e))?;
+            }
+            let attributes = Some(row.attributes.trim().trim_start_matches("0x"))
+                .filter(|s| !s.is_empty())
+                .map(|s| u32::from_str_radix(s, 16).map_err(|_| format!("{}: attributes must be hex, like 00000020", row.name)))
+                .transpose()?;
+            let comment = Some(row.comment.clone()).filter(|s| !s.is_empty());
+            edits.insert(row.name.clone(), EntryMetadata { modified, external_attributes: attributes, comment });
+        }
+        Ok(edits)
+    }
+
+    // Checks the form and writes it into the folder, ready for the archive to be created
+    pub fn save(&mut self) -> bool {
+        let result = self.edits().and_then(|edits| save(&self.folder, &edits).map_err(|e| e.to_string()));
+        self.error = result.err();
+        self.error.is_none()
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("Entry metadata ({} edited)", self.rows.len())).id_source("entry_metadata").show(ui, |ui| {
+            ui.weak("Blank fields keep what the archive writer sets. Times are UTC; attributes are the raw 32-bit value in hex.");
+            let mut remove = None;
+            egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+                egui::Grid::new("entry_metadata_grid").num_columns(5).striped(true).show(ui, |ui| {
+                    ui.strong("Entry");
+                    ui.strong("Modified");
+                    ui.strong("Attributes");
+                    ui.strong("Comment");
+                    ui.end_row();
+                    for (index, row) in self.rows.iter_mut().enumerate() {
+                        ui.label(&row.name);
+                        ui.add(egui::TextEdit::singleline(&mut row.modified).desired_width(140.0).hint_text("2017-06-13 12:00:00"));
+                        ui.add(egui::TextEdit::singleline(&mut row.attributes).desired_width(80.0).hint_text("00000020"));
+                        ui.add(egui::TextEdit::singleline(&mut row.comment).desired_width(160.0));
+                        if ui.small_button("🗑").on_hover_text("Stop editing this entry").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if let Some(index) = remove {
+                self.rows.remove(index);
+            }
+            let available: Vec<&String> = self.entries.iter().filter(|name| !self.rows.iter().any(|row| &row.name == *name)).collect();
+            let mut added = None;
+            egui::ComboBox::from_id_source("entry_metadata_add").selected_text("Edit an entry...").width(280.0).show_ui(ui, |ui| {
+                for name in available {
+                    if ui.selectable_label(false, name).clicked() {
+                        added = Some(name.clone());
+                    }
+                }
+            });
+            if let Some(name) = added {
+                self.rows.push(Row { name, modified: String::new(), attributes: String::new(), comment: String::new() });
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dos_time_covers_1980_to_2107() {
+        assert_eq!(parse_dos_time("1980-01-01 00:00:00"), Ok((0, 1 << 5 | 1)));
+        assert_eq!(parse_dos_time("2107-12-31 23:59:58"), Ok((23 << 11 | 59 << 5 | 29, 127 << 9 | 12 << 5 | 31)));
+        assert!(parse_dos_time("1979-12-31 23:59:59").is_err());
+        assert!(parse_dos_time("2108-01-01").is_err());
+    }
+
+    #[test]
+    fn dos_time_rounds_seconds_down_and_rejects_bad_fields() {
+        assert_eq!(parse_dos_time("2017-06-13 12:00:59").map(|(time, _)| time & 0x1F), Ok(29));
+        assert_eq!(parse_dos_time("2017-06-13"), parse_dos_time("2017-06-13 00:00:00"));
+        assert_eq!(parse_dos_time("2017-06-13T12:30"), parse_dos_time("2017-06-13 12:30:00"));
+        for text in ["2017-13-01", "2017-06-00", "2017-06-13 24:00", "2017-06-13 12:60", "2017-06-13 12:00:60", "2017-06", "yesterday"] {
+            assert!(parse_dos_time(text).is_err(), "{} should be refused", text);
+        }
+    }
+
+    fn read_directory(path: &Path) -> Vec<ZipDirEntry> {
+        let mut file = fs::File::open(path).unwrap();
+        let position = find_end_locator(&mut file).unwrap();
+        file.seek(SeekFrom::Start(position)).unwrap();
+        let locator = ZipDirEndLocator::read(&mut file).unwrap();
+        file.seek(SeekFrom::Start(locator.directory_offset as u64)).unwrap();
+        (0..locator.entries_in_directory).map(|_| ZipDirEntry::read(&mut file).unwrap()).collect()
+    }
+
+    #[test]
+    fn apply_round_trips_through_a_zip() {
+        let dir = std::env::temp_dir().join(format!("tundra_entry_metadata_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for (name, contents) in [("a.txt", "first"), ("sub/b.txt", "second")] {
+            writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut edits = MetadataEdits::new();
+        edits.insert("a.txt".to_string(), EntryMetadata {
+            modified: Some("2017-06-13 12:34:56".to_string()),
+            external_attributes: Some(0x20),
+            comment: Some("edited by the test".to_string()),
+        });
+        edits.insert("missing.txt".to_string(), EntryMetadata::default());
+        assert_eq!(apply(&path, &edits), Ok(1));
+
+        let (time, date) = parse_dos_time("2017-06-13 12:34:56").unwrap();
+        let entries = read_directory(&path);
+        let edited = entries.iter().find(|e| e.file_name == "a.txt").unwrap();
+        assert_eq!((edited.file_time, edited.file_date), (time, date));
+        assert_eq!(edited.external_attributes, 0x20);
+        assert_eq!(edited.file_comment, "edited by the test");
+        let untouched = entries.iter().find(|e| e.file_name == "sub/b.txt").unwrap();
+        assert!(untouched.file_comment.is_empty());
+
+        // The local header carries the same time, and the archive still reads
+        let data = fs::read(&path).unwrap();
+        let local = edited.header_offset as usize + 10;
+        assert_eq!(data[local..local + 4], [time.to_le_bytes(), date.to_le_bytes()].concat()[..]);
+        let mut archive = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+        let mut contents = String::new();
+        archive.by_name("sub/b.txt").unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "second");
+        assert_eq!(archive.by_name("a.txt").unwrap().comment(), "edited by the test");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use super::paths::long_path;
+
+// Progress is written at most this often, so a crash loses a second of work at worst
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct JournalEntry {
+    size: u64,
+    crc32: u32,
+}
+
+// Archive entries an extraction has already written, kept next to the output so an interrupted
+// extraction resumes instead of starting over. Entries that failed are never recorded and get
+// another try on the next run.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExtractJournal {
+    // The journal only applies to the archive as it was; a changed size or date starts over
+    archive_size: u64,
+    archive_modified: u64,
+    entries: HashMap<String, JournalEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    last_save: Option<Instant>,
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+impl ExtractJournal {
+    pub fn open(path: PathBuf, archive: &Path) -> Self {
+        let metadata = fs::metadata(long_path(archive)).ok();
+        let archive_size = metadata.as_ref().map_or(0, |m| m.len());
+        let archive_modified = metadata.as_ref().map_or(0, crate::modified_millis);
+        let previous = fs::read_to_string(long_path(&path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|journal| journal.archive_size == archive_size && journal.archive_modified == archive_modified);
+        let mut journal = previous.unwrap_or_else(|| Self { archive_size, archive_modified, ..Default::default() });
+        journal.path = path;
+        journal
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Written by an earlier run and still intact at `output`
+    pub fn is_done(&self, name: &str, output: &Path) -> bool {
+        let Some(entry) = self.entries.get(name) else {
+            return false;
+        };
+        let size_matches = fs::metadata(long_path(output)).map_or(false, |m| m.len() == entry.size);
+        size_matches && fs::read(long_path(output)).map_or(false, |data| crc32(&data) == entry.crc32)
+    }
+
+    pub fn record(&mut self, name: &str, data: &[u8]) {
+        self.entries.insert(name.to_string(), JournalEntry { size: data.len() as u64, crc32: crc32(data) });
+        if self.last_save.map_or(true, |time| time.elapsed() >= SAVE_INTERVAL) {
+            self.save();
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.last_save = Some(Instant::now());
+        let result = serde_json::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(long_path(&self.path), json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Failed to save extraction journal {}: {}", self.path.display(), e);
+        }
+    }
+}
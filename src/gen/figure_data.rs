@@ -0,0 +1,271 @@
+use super::read_scene::{self, ContainerData, Data, ScenePath};
+use eframe::egui;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+// Replaces the built-in fields when present, so modders can map keys the list doesn't know yet
+pub const SCHEMA_PATH: &str = "figure_schema.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSpec {
+    // Key name, or the end of a key path such as "Movement/RunSpeed"; matched ignoring case
+    pub key: String,
+    pub label: String,
+    pub group: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    // Int holding 0 or 1, edited as a checkbox
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flag: bool,
+}
+
+pub fn field(key: &str, label: &str, group: &str, range: Option<(f64, f64)>) -> FieldSpec {
+    FieldSpec { key: key.to_string(), label: label.to_string(), group: group.to_string(), min: range.map(|r| r.0), max: range.map(|r| r.1), flag: false }
+}
+
+fn flag(key: &str, label: &str, group: &str) -> FieldSpec {
+    FieldSpec { flag: true, ..field(key, label, group, None) }
+}
+
+// Keys seen in DI3 character and playset data; the ranges are what the shipped figures use
+pub fn default_schema() -> Vec<FieldSpec> {
+    vec![
+        field("CharacterName", "Name", "Character", None),
+        field("PlaysetId", "Playset", "Character", None),
+        field("MaxHealth", "Max health", "Stats", Some((1.0, 10000.0))),
+        field("Health", "Health", "Stats", Some((0.0, 10000.0))),
+        field("Armor", "Armor", "Stats", Some((0.0, 1.0))),
+        field("HealthRegen", "Health regen", "Stats", Some((0.0, 100.0))),
+        field("WalkSpeed", "Walk speed", "Movement", Some((0.0, 50.0))),
+        field("RunSpeed", "Run speed", "Movement", Some((0.0, 50.0))),
+        field("SprintSpeed", "Sprint speed", "Movement", Some((0.0, 50.0))),
+        field("JumpHeight", "Jump height", "Movement", Some((0.0, 20.0))),
+        flag("CanDoubleJump", "Double jump", "Movement"),
+        flag("CanGlide", "Glide", "Movement"),
+        field("MeleeDamage", "Melee damage", "Combat", Some((0.0, 1000.0))),
+        field("RangedDamage", "Ranged damage", "Combat", Some((0.0, 1000.0))),
+        field("AttackSpeed", "Attack speed", "Combat", Some((0.0, 10.0))),
+        field("CriticalChance", "Critical chance", "Combat", Some((0.0, 1.0))),
+        field("UnlockLevel", "Unlock level", "Unlocks", Some((0.0, 20.0))),
+        field("UnlockCost", "Unlock cost", "Unlocks", Some((0.0, 100000.0))),
+        field("SkillPoints", "Skill points", "Unlocks", Some((0.0, 100.0))),
+        flag("UnlockedByDefault", "Unlocked by default", "Unlocks"),
+        field("Abilities", "Abilities", "Abilities", None),
+        field("SkillTree", "Skill tree", "Abilities", None),
+        field("AbilityCooldown", "Ability cooldown", "Abilities", Some((0.0, 600.0))),
+    ]
+}
+
+// The schema file when there is one; a broken file falls back to the built-in fields with a message
+pub fn load_schema(path: &str, default: fn() -> Vec<FieldSpec>) -> (Vec<FieldSpec>, Option<String>) {
+    match std::fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(schema) => (schema, None),
+            Err(e) => (default(), Some(format!("{} is invalid, using the built-in fields: {}", path, e))),
+        },
+        Err(_) => (default(), None),
+    }
+}
+
+pub fn write_schema(path: &str, schema: &[FieldSpec]) -> Result<String, String> {
+    let result = serde_json::to_string_pretty(schema)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()))
+        .map(|_| format!("Wrote {}", path))
+        .map_err(|e| format!("Failed to write {}: {}", path, e));
+    match &result {
+        Ok(message) => println!("{}", message),
+        Err(error) => eprintln!("{}", error),
+    }
+    result
+}
+
+fn matches(key: &str, path: &[&str]) -> bool {
+    let wanted: Vec<&str> = key.split('/').collect();
+    wanted.len() <= path.len() && wanted.iter().rev().zip(path.iter().rev()).all(|(w, k)| w.eq_ignore_ascii_case(k))
+}
+
+// A scene value matched by a schema field
+pub struct FigureField {
+    pub path: ScenePath,
+    // Index into the schema
+    pub spec: usize,
+    // Value when the scene was opened, for resetting
+    pub original: Data,
+}
+
+pub fn collect_fields(map: &IndexMap<String, ContainerData>, schema: &[FieldSpec], path: &mut ScenePath, fields: &mut Vec<FigureField>) {
+    for (key, value) in map {
+        let items: Vec<(Option<usize>, &Data)> = match value {
+            ContainerData::Single(data) => vec![(None, data)],
+            ContainerData::Multiple(list) => list.iter().enumerate().map(|(i, data)| (Some(i), data)).collect(),
+        };
+        for (index, data) in items {
+            path.push((key.clone(), index));
+            match data {
+                Data::Container(children) => collect_fields(children, schema, path, fields),
+                Data::Binary(_) | Data::Uuid(_) => {}
+                _ => {
+                    let keys: Vec<&str> = path.iter().map(|(k, _)| k.as_str()).collect();
+                    if let Some(spec) = schema.iter().position(|s| matches(&s.key, &keys)) {
+                        fields.push(FigureField { path: path.clone(), spec, original: data.clone() });
+                    }
+                }
+            }
+            path.pop();
+        }
+    }
+}
+
+pub fn out_of_range(spec: &FieldSpec, data: &Data) -> bool {
+    let values: Vec<f64> = match data {
+        Data::Int(v) => vec![*v as f64],
+        Data::Float(v) => vec![*v as f64],
+        Data::IntVec(vs) => vs.iter().map(|v| *v as f64).collect(),
+        Data::FloatVec(vs) => vs.iter().map(|v| *v as f64).collect(),
+        _ => return false,
+    };
+    values.iter().any(|v| spec.min.map_or(false, |min| *v < min) || spec.max.map_or(false, |max| *v > max))
+}
+
+pub fn edit_value(ui: &mut egui::Ui, spec: &FieldSpec, data: &mut Data) -> bool {
+    match data {
+        Data::Int(value) if spec.flag => {
+            let mut on = *value != 0;
+            let changed = ui.checkbox(&mut on, "").changed();
+            if changed {
+                *value = on as i32;
+            }
+            changed
+        }
+        Data::Int(value) => ui.add(egui::DragValue::new(value)).changed(),
+        Data::Float(value) => ui.add(egui::DragValue::new(value).speed(0.01)).changed(),
+        Data::IntVec(values) => values.iter_mut().fold(false, |changed, v| ui.add(egui::DragValue::new(v)).changed() || changed),
+        Data::FloatVec(values) => values.iter_mut().fold(false, |changed, v| ui.add(egui::DragValue::new(v).speed(0.01)).changed() || changed),
+        Data::String(value) => ui.text_edit_singleline(value).changed(),
+        Data::StringVec(values) => values
+            .iter_mut()
+            .fold(false, |changed, v| ui.add(egui::TextEdit::singleline(v).desired_width(120.0)).changed() || changed),
+        _ => false,
+    }
+}
+
+// Forms over the scene's known gameplay keys; edits go straight into the loaded scene, which is
+// saved the usual way
+#[derive(Default)]
+pub struct FigureEditor {
+    schema: Vec<FieldSpec>,
+    schema_error: Option<String>,
+    // None until the open scene has been scanned
+    fields: Option<Vec<FigureField>>,
+    search: String,
+    status: Option<Result<String, String>>,
+}
+
+impl FigureEditor {
+    // Called when another scene is loaded
+    pub fn reset(&mut self) {
+        self.fields = None;
+    }
+
+    fn scan(&mut self, scene: &IndexMap<String, ContainerData>) {
+        if self.schema.is_empty() {
+            (self.schema, self.schema_error) = load_schema(SCHEMA_PATH, default_schema);
+        }
+        let mut fields = Vec::new();
+        collect_fields(scene, &self.schema, &mut Vec::new(), &mut fields);
+        self.fields = Some(fields);
+    }
+
+    // Returns true when a value in the scene was changed
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, scene: &mut IndexMap<String, ContainerData>) -> bool {
+        if self.fields.is_none() {
+            self.scan(scene);
+        }
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+            if ui.button("Reload schema").on_hover_text(format!("Reads {} again", SCHEMA_PATH)).clicked() {
+                (self.schema, self.schema_error) = load_schema(SCHEMA_PATH, default_schema);
+                self.fields = None;
+            }
+            if ui.button("Write schema file").on_hover_text(format!("Saves the current fields to {} to extend them", SCHEMA_PATH)).clicked() {
+                self.status = Some(write_schema(SCHEMA_PATH, &self.schema));
+            }
+        });
+        if let Some(error) = &self.schema_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+        if self.fields.is_none() {
+            return false;
+        }
+        let fields = self.fields.as_ref().unwrap();
+        if fields.is_empty() {
+            ui.label("No character, stat or ability keys from the schema were found in this scene.");
+            ui.weak(format!("Keys can be added to {} once the scene's Tree tab shows what they're called.", SCHEMA_PATH));
+            return false;
+        }
+        ui.label(format!("{} known values", fields.len()));
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        let mut groups: Vec<&str> = Vec::new();
+        for spec in &self.schema {
+            if !groups.contains(&spec.group.as_str()) {
+                groups.push(&spec.group);
+            }
+        }
+        egui::ScrollArea::vertical().id_source("figure_fields").auto_shrink([false, false]).show(ui, |ui| {
+            for group in groups {
+                let rows: Vec<&FigureField> = fields
+                    .iter()
+                    .filter(|f| self.schema[f.spec].group == group)
+                    .filter(|f| search.is_empty() || self.schema[f.spec].label.to_lowercase().contains(&search) || read_scene::scene_path_label(&f.path).to_lowercase().contains(&search))
+                    .collect();
+                if rows.is_empty() {
+                    continue;
+                }
+                egui::CollapsingHeader::new(format!("{} ({})", group, rows.len())).default_open(true).show(ui, |ui| {
+                    egui::Grid::new(("figure_group", group)).num_columns(3).striped(true).show(ui, |ui| {
+                        for row in rows {
+                            let spec = &self.schema[row.spec];
+                            let Some(data) = read_scene::find_data_mut(scene, &row.path) else {
+                                continue;
+                            };
+                            // Files holding several characters repeat keys, so the owning container tells them apart
+                            let owner = read_scene::scene_path_label(&row.path[..row.path.len() - 1].to_vec());
+                            ui.label(&spec.label).on_hover_text(read_scene::scene_path_label(&row.path));
+                            ui.horizontal_wrapped(|ui| {
+                                changed |= edit_value(ui, spec, data);
+                                if format!("{:?}", data) != format!("{:?}", row.original) && ui.small_button("↺").on_hover_text("Back to the value the scene was opened with").clicked() {
+                                    *data = row.original.clone();
+                                    changed = true;
+                                }
+                                if out_of_range(spec, data) {
+                                    let range = format!("{} to {}", spec.min.map_or("any".to_string(), |v| v.to_string()), spec.max.map_or("any".to_string(), |v| v.to_string()));
+                                    ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text(format!("Outside the usual range ({})", range));
+                                }
+                            });
+                            ui.weak(owner);
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        });
+        changed
+    }
+}
@@ -0,0 +1,290 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+
+// What to do with a target that already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    Overwrite,
+    KeepBoth,
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub source: PathBuf,
+    pub target: PathBuf,
+}
+
+// Files laid out under `dest_dir`. Folders keep their structure below their own name.
+pub fn plan(sources: &[PathBuf], dest_dir: &Path) -> Vec<Transfer> {
+    let mut transfers = Vec::new();
+    for source in sources {
+        let Some(name) = source.file_name() else {
+            continue;
+        };
+        if source.is_dir() {
+            let files = walkdir::WalkDir::new(source)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file());
+            for file in files {
+                let relative = file.path().strip_prefix(source).unwrap_or(file.path());
+                transfers.push(Transfer { source: file.path().to_path_buf(), target: dest_dir.join(name).join(relative) });
+            }
+        } else {
+            transfers.push(Transfer { source: source.clone(), target: dest_dir.join(name) });
+        }
+    }
+    transfers
+}
+
+pub fn conflict_count(transfers: &[Transfer]) -> usize {
+    transfers.iter().filter(|t| t.target != t.source && t.target.exists()).count()
+}
+
+// "name (2).ext", "name (3).ext", ... next to `path`
+fn free_name(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+// Rename, falling back to copy and delete across drives
+fn move_file(source: &Path, target: &Path) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(long_path(parent))?;
+    }
+    if fs::rename(long_path(source), long_path(target)).is_ok() {
+        return Ok(());
+    }
+    fs::copy(long_path(source), long_path(target))?;
+    fs::remove_file(long_path(source))
+}
+
+// Enough to put the file system back: files a copy created, files a move relocated (from, to)
+// and originals an overwrite set aside (original path, backup)
+#[derive(Debug, Clone, Default)]
+pub struct UndoRecord {
+    pub description: String,
+    created: Vec<PathBuf>,
+    moved: Vec<(PathBuf, PathBuf)>,
+    backups: Vec<(PathBuf, PathBuf)>,
+}
+
+impl UndoRecord {
+    // A file written from scratch; undoing deletes it
+    pub fn created_file(path: &Path, description: String) -> Self {
+        Self { description, created: vec![path.to_path_buf()], ..Default::default() }
+    }
+}
+
+// Overwritten files are moved into `backup_dir` so the operation can be undone. Returns the
+// undo record and a summary.
+pub fn apply(transfers: &[Transfer], is_move: bool, policy: ConflictPolicy, backup_dir: &Path, description: String) -> (UndoRecord, String) {
+    let mut record = UndoRecord { description, ..Default::default() };
+    let mut done = 0;
+    let mut skipped = 0;
+    for (index, transfer) in transfers.iter().enumerate() {
+        if transfer.target == transfer.source {
+            skipped += 1;
+            continue;
+        }
+        let mut target = transfer.target.clone();
+        if target.exists() {
+            match policy {
+                ConflictPolicy::Skip => {
+                    skipped += 1;
+                    continue;
+                }
+                ConflictPolicy::KeepBoth => target = free_name(&target),
+                ConflictPolicy::Overwrite => {
+                    let name = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    let backup = backup_dir.join(format!("{}_{}", index, name));
+                    if let Err(e) = move_file(&target, &backup) {
+                        eprintln!("Failed to set aside {}: {}", target.display(), e);
+                        continue;
+                    }
+                    record.backups.push((target.clone(), backup));
+                }
+            }
+        }
+
+        let result = if is_move {
+            move_file(&transfer.source, &target).map(|_| record.moved.push((transfer.source.clone(), target.clone())))
+        } else {
+            target.parent()
+                .map_or(Ok(()), |parent| fs::create_dir_all(long_path(parent)))
+                .and_then(|_| fs::copy(long_path(&transfer.source), long_path(&target)))
+                .map(|_| record.created.push(target.clone()))
+        };
+        match result {
+            Ok(()) => done += 1,
+            Err(e) => eprintln!("Failed to {} {}: {}", if is_move { "move" } else { "copy" }, transfer.source.display(), e),
+        }
+    }
+
+    let mut summary = format!("{} {} of {} files", if is_move { "Moved" } else { "Copied" }, done, transfers.len());
+    if skipped > 0 {
+        summary.push_str(&format!(" ({} skipped)", skipped));
+    }
+    println!("{}: {}", record.description, summary);
+    (record, summary)
+}
+
+// Renames `path` within its folder
+pub fn rename(path: &Path, new_name: &str) -> Result<UndoRecord, String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name == "." || new_name == ".." {
+        return Err(format!("\"{}\" isn't a valid name", new_name));
+    }
+    let target = path.with_file_name(new_name);
+    if target.exists() && !target.as_os_str().eq_ignore_ascii_case(path.as_os_str()) {
+        return Err(format!("{} already exists", target.display()));
+    }
+    fs::rename(long_path(path), long_path(&target)).map_err(|e| e.to_string())?;
+    println!("Renamed {} to {}", path.display(), new_name);
+    Ok(UndoRecord {
+        description: format!("rename of {}", path.display()),
+        moved: vec![(path.to_path_buf(), target)],
+        ..Default::default()
+    })
+}
+
+// Reverses `record`: moved files go back, copies are deleted, then overwritten originals return
+pub fn undo(record: &UndoRecord) -> String {
+    let mut failed = 0;
+    for (source, target) in record.moved.iter().rev() {
+        if let Err(e) = move_file(target, source) {
+            eprintln!("Failed to move {} back: {}", target.display(), e);
+            failed += 1;
+        }
+    }
+    for created in &record.created {
+        if let Err(e) = fs::remove_file(long_path(created)) {
+            eprintln!("Failed to remove {}: {}", created.display(), e);
+            failed += 1;
+        }
+    }
+    for (original, backup) in &record.backups {
+        if let Err(e) = move_file(backup, original) {
+            eprintln!("Failed to restore {}: {}", original.display(), e);
+            failed += 1;
+        }
+    }
+
+    let total = record.moved.len() + record.created.len() + record.backups.len();
+    if failed == 0 {
+        format!("Undid {} ({} files)", record.description, total)
+    } else {
+        format!("Undid {} with {} of {} files failing", record.description, failed, total)
+    }
+}
+
+const STAGED_SUFFIX: &str = ".tundra-staged";
+const SET_ASIDE_SUFFIX: &str = ".tundra-old";
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// Writes that land together or not at all. Every file is staged next to its target first, so the
+// final renames stay on one drive; originals are set aside until all of them have gone through
+// and put back if any fails. The undo record then reverts the whole set at once.
+pub struct Transaction {
+    description: String,
+    backup_dir: PathBuf,
+    // (staged, target)
+    staged: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Transaction {
+    pub fn new(backup_dir: &Path, description: String) -> Self {
+        Self { description, backup_dir: backup_dir.to_path_buf(), staged: Vec::new() }
+    }
+
+    pub fn stage_copy(&mut self, source: &Path, target: &Path) -> std::io::Result<()> {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(long_path(parent))?;
+        }
+        let staged = with_suffix(target, STAGED_SUFFIX);
+        self.staged.push((staged.clone(), target.to_path_buf()));
+        fs::copy(long_path(source), long_path(&staged)).map(|_| ())
+    }
+
+    // Removes the staged files; nothing has been replaced yet
+    pub fn abort(self) {
+        for (staged, _) in &self.staged {
+            let _ = fs::remove_file(long_path(staged));
+        }
+    }
+
+    pub fn commit(self) -> Result<UndoRecord, String> {
+        // (target, set aside original) for each file replaced so far
+        let mut done: Vec<(PathBuf, Option<PathBuf>)> = Vec::new();
+        let mut failure = None;
+        for (staged, target) in &self.staged {
+            let set_aside = with_suffix(target, SET_ASIDE_SUFFIX);
+            let original = if target.exists() {
+                if let Err(e) = fs::rename(long_path(target), long_path(&set_aside)) {
+                    failure = Some(format!("{}: {}", target.display(), e));
+                    break;
+                }
+                Some(set_aside)
+            } else {
+                None
+            };
+            if let Err(e) = fs::rename(long_path(staged), long_path(target)) {
+                if let Some(original) = &original {
+                    let _ = fs::rename(long_path(original), long_path(target));
+                }
+                failure = Some(format!("{}: {}", target.display(), e));
+                break;
+            }
+            done.push((target.clone(), original));
+        }
+
+        if let Some(failure) = failure {
+            let mut unrestored = 0;
+            for (target, original) in done.iter().rev() {
+                let result = match original {
+                    Some(original) => fs::rename(long_path(original), long_path(target)),
+                    None => fs::remove_file(long_path(target)),
+                };
+                if let Err(e) = result {
+                    eprintln!("Failed to roll back {}: {}", target.display(), e);
+                    unrestored += 1;
+                }
+            }
+            for (staged, _) in &self.staged {
+                let _ = fs::remove_file(long_path(staged));
+            }
+            return Err(if unrestored == 0 {
+                format!("{}; all {} files rolled back", failure, done.len())
+            } else {
+                format!("{}; {} of {} files couldn't be rolled back", failure, unrestored, done.len())
+            });
+        }
+
+        let mut record = UndoRecord { description: self.description, ..Default::default() };
+        for (index, (target, original)) in done.into_iter().enumerate() {
+            let Some(original) = original else {
+                record.created.push(target);
+                continue;
+            };
+            let name = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let backup = self.backup_dir.join(format!("{}_{}", index, name));
+            match move_file(&original, &backup) {
+                Ok(()) => record.backups.push((target, backup)),
+                Err(e) => eprintln!("Failed to keep {} for undo: {}", original.display(), e),
+            }
+        }
+        Ok(record)
+    }
+}
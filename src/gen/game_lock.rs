@@ -0,0 +1,162 @@
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use super::paths::{display_name, long_path};
+use super::safe_write;
+
+// ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+#[cfg(windows)]
+const IN_USE_CODES: [i32; 2] = [32, 33];
+// ETXTBSY, for a running executable
+#[cfg(not(windows))]
+const IN_USE_CODES: [i32; 1] = [26];
+
+pub fn is_in_use_error(e: &io::Error) -> bool {
+    e.raw_os_error().map_or(false, |code| IN_USE_CODES.contains(&code))
+}
+
+// Opens the file for writing without changing it; the game keeps its archives open without
+// sharing write access, so this fails the same way the real write would
+pub fn is_in_use(path: &Path) -> bool {
+    match fs::OpenOptions::new().write(true).open(long_path(path)) {
+        Ok(_) => false,
+        Err(e) => is_in_use_error(&e),
+    }
+}
+
+// The game started with Run Game, while it's still running
+#[derive(Default)]
+pub struct GameProcess {
+    child: Option<Child>,
+}
+
+impl GameProcess {
+    pub fn track(&mut self, child: Child) {
+        self.child = Some(child);
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        let Some(child) = &mut self.child else {
+            return false;
+        };
+        match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                println!("Game exited ({})", status);
+                self.child = None;
+                false
+            }
+            Err(e) => {
+                eprintln!("Lost track of the game process: {}", e);
+                self.child = None;
+                false
+            }
+        }
+    }
+}
+
+// Next to temp/ rather than in it, since temp/ is removed on exit and a queued write may outlive Tundra
+pub const STAGING_DIR: &str = "queued_writes";
+
+// A write that went to a staging file because the target was in use, copied over it later
+#[derive(Debug, Clone)]
+pub struct QueuedWrite {
+    pub staged: PathBuf,
+    pub target: PathBuf,
+}
+
+#[derive(Default)]
+pub struct WriteQueue {
+    pub items: Vec<QueuedWrite>,
+    staged_count: usize,
+}
+
+impl WriteQueue {
+    // Where to write instead of `target`; distinct per queued write so two never share a file
+    pub fn stage(&mut self, staging_dir: &Path, target: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(long_path(staging_dir))?;
+        self.staged_count += 1;
+        let staged = staging_dir.join(format!("{}_{}", self.staged_count, display_name(target)));
+        self.items.push(QueuedWrite { staged: staged.clone(), target: target.to_path_buf() });
+        Ok(staged)
+    }
+
+    // Copies every staged file over its target; ones still locked stay queued for the next try
+    pub fn flush(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        self.items.retain(|item| {
+            if !item.staged.exists() {
+                messages.push(format!("Nothing was written for {}, dropped from the queue", item.target.display()));
+                return false;
+            }
+            // Checked first so a retry doesn't push another copy of the same file into the backups
+            if is_in_use(&item.target) {
+                return true;
+            }
+            match safe_write::prepare(&item.target).and_then(|_| fs::copy(long_path(&item.staged), long_path(&item.target))) {
+                Ok(_) => {
+                    let _ = fs::remove_file(long_path(&item.staged));
+                    messages.push(format!("Wrote queued {}", item.target.display()));
+                    false
+                }
+                Err(e) if is_in_use_error(&e) => true,
+                Err(e) => {
+                    messages.push(format!("Failed to write queued {}: {} (the new file is at {})", item.target.display(), e, item.staged.display()));
+                    false
+                }
+            }
+        });
+        messages
+    }
+}
+
+pub enum InUseChoice {
+    Retry,
+    Queue,
+    Cancel,
+}
+
+pub fn ask(path: &Path, game_running: bool) -> InUseChoice {
+    let holder = if game_running {
+        "The game started with Run Game is still running and has it open."
+    } else {
+        "Another program has it open; if the game is running, close it first."
+    };
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("File in use")
+        .set_description(format!(
+            "{} can't be written right now.\n{}\n\nYes: try again\nNo: write it once the game exits\nCancel: don't write anything",
+            path.display(),
+            holder
+        ))
+        .set_buttons(MessageButtons::YesNoCancel)
+        .show();
+    match answer {
+        MessageDialogResult::Yes => InUseChoice::Retry,
+        MessageDialogResult::No => InUseChoice::Queue,
+        _ => InUseChoice::Cancel,
+    }
+}
+
+// Asked when Tundra is closed with writes still queued. Returns whether to close anyway; the
+// staged files are kept either way.
+pub fn ask_close(items: &[QueuedWrite]) -> bool {
+    let listing = items.iter()
+        .map(|item| format!("{}\n  -> {}", item.staged.display(), item.target.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Writes still queued")
+        .set_description(format!(
+            "{} file(s) are waiting for the game to exit and haven't been written yet:\n{}\n\nYes: keep Tundra open until they're written\nNo: close anyway; the new files stay where they are to copy over by hand",
+            items.len(),
+            listing
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    answer == MessageDialogResult::No
+}
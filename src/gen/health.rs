@@ -0,0 +1,121 @@
+use eframe::egui;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+}
+
+impl CheckStatus {
+    fn icon(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "✔",
+            CheckStatus::Warning => "⚠",
+            CheckStatus::Failed => "✖",
+        }
+    }
+
+    fn color(&self, ui: &egui::Ui) -> egui::Color32 {
+        match self {
+            CheckStatus::Ok => egui::Color32::from_rgb(80, 170, 80),
+            CheckStatus::Warning => ui.visuals().warn_fg_color,
+            CheckStatus::Failed => egui::Color32::RED,
+        }
+    }
+}
+
+// What a check's fix-it button asks the app to open
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthFix {
+    ChangeExecutable,
+    Options,
+}
+
+impl HealthFix {
+    fn label(&self) -> &'static str {
+        match self {
+            HealthFix::ChangeExecutable => "Pick the executable...",
+            HealthFix::Options => "Open options",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    // How to put it right, shown unless the check passed
+    pub hint: Option<String>,
+    pub fix: Option<HealthFix>,
+}
+
+impl HealthCheck {
+    pub fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Ok, detail: detail.into(), hint: None, fix: None }
+    }
+
+    pub fn warning(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: CheckStatus::Warning, hint: Some(hint.into()), ..Self::ok(name, detail) }
+    }
+
+    pub fn failed(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: CheckStatus::Failed, hint: Some(hint.into()), ..Self::ok(name, detail) }
+    }
+
+    pub fn with_fix(mut self, fix: HealthFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+// Checks run when a game's editor opens, so a broken setup says what's wrong instead of
+// showing an empty tree
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub game: String,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    pub fn problems(&self) -> usize {
+        self.checks.iter().filter(|c| c.status != CheckStatus::Ok).count()
+    }
+
+    pub fn summary(&self) -> String {
+        match self.problems() {
+            0 => format!("{}: all {} checks passed", self.game, self.checks.len()),
+            n => format!("{}: {} of {} checks need attention", self.game, n, self.checks.len()),
+        }
+    }
+
+    pub fn show_ui(&self, ui: &mut egui::Ui) -> Option<HealthFix> {
+        let mut fix = None;
+        ui.label(self.summary());
+        ui.separator();
+        egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+            for check in &self.checks {
+                ui.horizontal(|ui| {
+                    ui.colored_label(check.status.color(ui), check.status.icon());
+                    ui.strong(&check.name);
+                    ui.label(&check.detail);
+                });
+                if check.status == CheckStatus::Ok {
+                    continue;
+                }
+                ui.indent(&check.name, |ui| {
+                    if let Some(hint) = &check.hint {
+                        ui.weak(hint);
+                    }
+                    if let Some(action) = check.fix {
+                        if ui.small_button(action.label()).clicked() {
+                            fix = Some(action);
+                        }
+                    }
+                });
+            }
+        });
+        fix
+    }
+}
@@ -0,0 +1,178 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use super::zip_writer::ZipSettings;
+
+const HISTORY_PATH: &str = "tundra_history.json";
+const MAX_ENTRIES: usize = 200;
+
+// Operations that write output somewhere and can be run again with the same inputs, e.g. after
+// a game update changed the source files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Operation {
+    CopyFiles { files: Vec<PathBuf>, output_dir: PathBuf, keep_structure: bool },
+    DumpDecryptedArchive { archive: PathBuf, output: PathBuf },
+    // The entry's stored bytes plus a .json of its header fields
+    ExportRawEntry { archive: PathBuf, entry: String, output: PathBuf },
+    BatchSceneDump { output_dir: PathBuf },
+    SchemaReport { report_path: PathBuf },
+    ConvertEndianness { source: PathBuf, output: PathBuf, target_name: String },
+    ExportTextureSet { mtb: PathBuf, output_dir: PathBuf },
+    // `model` is the IBUF; the VBUF and MTB are found next to it again
+    ExportForBlender { model: PathBuf, output_dir: PathBuf },
+    ExportSceneGltf { scene: PathBuf, output_dir: PathBuf },
+    CreateArchive { folder: PathBuf, output: PathBuf, settings: ZipSettings },
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string())
+}
+
+impl Operation {
+    pub fn describe(&self) -> String {
+        match self {
+            Operation::CopyFiles { files, output_dir, keep_structure } => format!(
+                "{} {} file(s) to {}",
+                if *keep_structure { "Exported" } else { "Extracted" },
+                files.len(),
+                output_dir.display()
+            ),
+            Operation::DumpDecryptedArchive { archive, output } => {
+                format!("Dumped {} decrypted to {}", file_name(archive), output.display())
+            }
+            Operation::ExportRawEntry { archive, entry, output } => {
+                format!("Exported raw {} from {} to {}", entry, file_name(archive), output.display())
+            }
+            Operation::BatchSceneDump { output_dir } => format!("Converted scenes to JSON in {}", output_dir.display()),
+            Operation::SchemaReport { report_path } => format!("Wrote OCT schema report {}", report_path.display()),
+            Operation::ConvertEndianness { source, output, target_name } => {
+                format!("Saved {} endian copy of {} to {}", target_name, file_name(source), output.display())
+            }
+            Operation::ExportTextureSet { mtb, output_dir } => {
+                format!("Exported textures of {} to {}", file_name(mtb), output_dir.display())
+            }
+            Operation::ExportForBlender { model, output_dir } => {
+                format!("Exported {} for Blender to {}", file_name(model), output_dir.display())
+            }
+            Operation::ExportSceneGltf { scene, output_dir } => {
+                format!("Exported scene {} as glTF to {}", file_name(scene), output_dir.display())
+            }
+            Operation::CreateArchive { folder, output, settings } => {
+                format!("Archived {} to {} ({})", file_name(folder), output.display(), settings.describe())
+            }
+        }
+    }
+
+    // Where the operation writes, so a repeat can be checked against the write guard again
+    pub fn output_mut(&mut self) -> &mut PathBuf {
+        match self {
+            Operation::CopyFiles { output_dir, .. }
+            | Operation::BatchSceneDump { output_dir }
+            | Operation::ExportTextureSet { output_dir, .. }
+            | Operation::ExportForBlender { output_dir, .. }
+            | Operation::ExportSceneGltf { output_dir, .. } => output_dir,
+            Operation::DumpDecryptedArchive { output, .. }
+            | Operation::ExportRawEntry { output, .. }
+            | Operation::ConvertEndianness { output, .. }
+            | Operation::CreateArchive { output, .. } => output,
+            Operation::SchemaReport { report_path } => report_path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub time: String,
+    pub operation: Operation,
+    pub result: String,
+}
+
+// Kept on disk so batch workflows can be repeated in later sessions
+pub struct OperationHistory {
+    entries: Vec<HistoryEntry>,
+    filter: String,
+}
+
+impl OperationHistory {
+    pub fn load() -> Self {
+        let entries = std::fs::read_to_string(HISTORY_PATH)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            filter: String::new(),
+        }
+    }
+
+    fn save(&self) {
+        match serde_json::to_string_pretty(&self.entries) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(HISTORY_PATH, json) {
+                    eprintln!("Failed to save operation history: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize operation history: {}", e),
+        }
+    }
+
+    pub fn record(&mut self, time: String, operation: Operation, result: impl Into<String>) {
+        let result = result.into();
+        println!("[history] {}: {}", operation.describe(), result);
+        self.entries.push(HistoryEntry { time, operation, result });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.save();
+    }
+
+    // Newest first. Returns an operation the user asked to repeat.
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) -> Option<Operation> {
+        let mut repeat = None;
+        let mut remove = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+            if ui.add_enabled(!self.entries.is_empty(), egui::Button::new("Clear history")).clicked() {
+                self.entries.clear();
+                self.save();
+            }
+        });
+        ui.separator();
+
+        if self.entries.is_empty() {
+            ui.label("No operations recorded yet");
+            return None;
+        }
+
+        let filter = self.filter.to_lowercase();
+        egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+            for (index, entry) in self.entries.iter().enumerate().rev() {
+                let description = entry.operation.describe();
+                if !filter.is_empty() && !description.to_lowercase().contains(&filter) {
+                    continue;
+                }
+                ui.horizontal(|ui| {
+                    ui.weak(&entry.time);
+                    ui.label(&description).on_hover_text(&entry.result);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                        if ui.small_button("Repeat").clicked() {
+                            repeat = Some(entry.operation.clone());
+                        }
+                    });
+                });
+            }
+        });
+
+        if let Some(index) = remove {
+            self.entries.remove(index);
+            self.save();
+        }
+        repeat
+    }
+}
@@ -0,0 +1,141 @@
+use eframe::egui;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Holds the port the running instance listens on; next to temp/, which it guards
+pub const LOCK_PATH: &str = "tundra.lock";
+// First line of a handoff, so a stray connection to the port isn't taken for one
+const HANDOFF_HEADER: &str = "tundra-open 1";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+// The running instance's answer once it has queued the paths
+const HANDOFF_ACK: &str = "tundra-opened";
+const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub enum Acquired {
+    // This is the only instance; paths forwarded by later launches arrive through the lock
+    Primary(InstanceLock),
+    // Another instance is running and has been handed the paths
+    Forwarded,
+}
+
+// Makes this the running instance, or hands `paths` to the one already running. A lock left by
+// an instance that crashed points at a port nobody answers on, or at one that never acknowledges
+// the handoff (a hung instance, or another program that reused the port), and is taken over.
+pub fn acquire(paths: &[PathBuf]) -> std::io::Result<Acquired> {
+    let lock_path = PathBuf::from(LOCK_PATH);
+    if let Some(port) = std::fs::read_to_string(&lock_path).ok().and_then(|content| content.trim().parse::<u16>().ok()) {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        match hand_off(&address, paths) {
+            Ok(true) => {
+                println!("Tundra is already running, handed it {} path(s)", paths.len());
+                return Ok(Acquired::Forwarded);
+            }
+            Ok(false) => println!("The instance in {} didn't acknowledge the handoff, taking over the lock", LOCK_PATH),
+            Err(_) => println!("Taking over the lock left by an instance that's no longer running"),
+        }
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    std::fs::write(&lock_path, port.to_string())?;
+
+    let (sender, received) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let ctx: Arc<Mutex<Option<egui::Context>>> = Arc::new(Mutex::new(None));
+    let (thread_stop, thread_ctx) = (stop.clone(), ctx.clone());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if thread_stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            match read_handoff(&stream) {
+                Some(paths) => {
+                    let _ = sender.send(paths);
+                    if let Some(ctx) = thread_ctx.lock().unwrap().as_ref() {
+                        ctx.request_repaint();
+                    }
+                    if let Err(e) = stream.write_all(format!("{}\n", HANDOFF_ACK).as_bytes()) {
+                        eprintln!("Failed to acknowledge a handoff: {}", e);
+                    }
+                }
+                None => eprintln!("Ignored a connection to the instance lock that wasn't a handoff"),
+            }
+        }
+    });
+    Ok(Acquired::Primary(InstanceLock { path: lock_path, port, received, stop, ctx }))
+}
+
+// Sends `paths` and waits for the acknowledgement; Ok(false) when something answered on the port
+// but didn't acknowledge in time
+fn hand_off(address: &SocketAddr, paths: &[PathBuf]) -> std::io::Result<bool> {
+    let mut stream = TcpStream::connect_timeout(address, CONNECT_TIMEOUT)?;
+    let mut message = format!("{}\n", HANDOFF_HEADER);
+    for path in paths {
+        message.push_str(&format!("{}\n", path.display()));
+    }
+    stream.write_all(message.as_bytes())?;
+    // Ends the message, so the running instance doesn't wait for more paths
+    stream.shutdown(Shutdown::Write)?;
+    stream.set_read_timeout(Some(ACK_TIMEOUT))?;
+    let mut reply = String::new();
+    Ok(BufReader::new(stream).read_line(&mut reply).is_ok() && reply.trim_end() == HANDOFF_ACK)
+}
+
+fn read_handoff(stream: &TcpStream) -> Option<Vec<PathBuf>> {
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT)).ok()?;
+    let mut lines = BufReader::new(stream).lines();
+    if lines.next()?.ok()? != HANDOFF_HEADER {
+        return None;
+    }
+    Some(lines.map_while(Result::ok).filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+}
+
+pub struct InstanceLock {
+    path: PathBuf,
+    port: u16,
+    // One list per later launch; an empty one is a launch without paths
+    received: mpsc::Receiver<Vec<PathBuf>>,
+    stop: Arc<AtomicBool>,
+    ctx: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl InstanceLock {
+    // The lock is taken before the window exists; handoffs only wake the UI once it's known
+    pub fn set_context(&self, ctx: &egui::Context) {
+        *self.ctx.lock().unwrap() = Some(ctx.clone());
+    }
+
+    pub fn take_handoffs(&self) -> Vec<Vec<PathBuf>> {
+        self.received.try_iter().collect()
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+        // Only remove the lock if a later instance hasn't taken it over
+        if std::fs::read_to_string(&self.path).map_or(false, |content| content.trim() == self.port.to_string()) {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                eprintln!("Failed to remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+// Arguments are resolved against the launching process's folder, which the running instance
+// doesn't share
+pub fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.to_path_buf())
+}
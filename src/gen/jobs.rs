@@ -0,0 +1,221 @@
+use image::ImageFormat;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use super::dds::DdsLayout;
+use super::paths::{self, VirtualPath};
+
+// A job file is JSON describing steps that run one after another, e.g.
+//
+// {
+//   "name": "Dump character textures",
+//   "game": "DisneyInfinity30",
+//   "steps": [
+//     { "action": "extract", "archive": "assets/characters.zip", "pattern": "**/*.tbody", "output": "out/raw" },
+//     { "action": "convert_textures", "input": "out/raw", "output": "out/png" },
+//     { "action": "write_manifest", "input": "out/png", "output": "out/png/manifest.txt" }
+//   ]
+// }
+//
+// Relative paths are taken from the folder the job file is in, so a job can be kept next to its output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    #[serde(default)]
+    pub name: String,
+    // Game the archives come from, same names as in the config ("DisneyInfinity30", ...)
+    #[serde(default)]
+    pub game: Option<String>,
+    // Keep going after a failed step instead of stopping the job
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<JobStep>,
+}
+
+fn all_files() -> String {
+    "**".to_string()
+}
+
+fn texture_files() -> String {
+    "*.tbody".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum JobStep {
+    // Writes the archive entries matching `pattern` below `output`, keeping their folders
+    Extract {
+        archive: PathBuf,
+        #[serde(default = "all_files")]
+        pattern: String,
+        output: PathBuf,
+    },
+    // Decodes TBODY/DDS files below `input` to PNGs under `output`, named by the texture template
+    // (mirroring `input` by default)
+    ConvertTextures {
+        input: PathBuf,
+        #[serde(default = "texture_files")]
+        pattern: String,
+        output: PathBuf,
+    },
+    // Converts OCT/BENT files below `input` to JSON mirrored under `output`
+    ConvertScenes {
+        input: PathBuf,
+        output: PathBuf,
+    },
+    // Lists every file below `input` with its size
+    WriteManifest {
+        input: PathBuf,
+        output: PathBuf,
+    },
+}
+
+impl JobStep {
+    pub fn describe(&self) -> String {
+        match self {
+            JobStep::Extract { archive, pattern, output } => {
+                format!("Extract {} from {} to {}", pattern, paths::display_name(archive), output.display())
+            }
+            JobStep::ConvertTextures { input, pattern, output } => {
+                format!("Convert {} in {} to PNG in {}", pattern, input.display(), output.display())
+            }
+            JobStep::ConvertScenes { input, output } => {
+                format!("Convert scenes in {} to JSON in {}", input.display(), output.display())
+            }
+            JobStep::WriteManifest { input, output } => format!("Write manifest of {} to {}", input.display(), output.display()),
+        }
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut PathBuf> {
+        match self {
+            JobStep::Extract { archive, output, .. } => vec![archive, output],
+            JobStep::ConvertTextures { input, output, .. }
+            | JobStep::ConvertScenes { input, output }
+            | JobStep::WriteManifest { input, output } => vec![input, output],
+        }
+    }
+
+    // What the step reads, for remote jobs to be checked against the scanned tree
+    pub fn inputs(&self) -> Vec<&Path> {
+        match self {
+            JobStep::Extract { archive, .. } => vec![archive],
+            JobStep::ConvertTextures { input, .. }
+            | JobStep::ConvertScenes { input, .. }
+            | JobStep::WriteManifest { input, .. } => vec![input],
+        }
+    }
+
+    // Where the step writes, so the app can check it against the write guard before running
+    pub fn output_mut(&mut self) -> &mut PathBuf {
+        match self {
+            JobStep::Extract { output, .. }
+            | JobStep::ConvertTextures { output, .. }
+            | JobStep::ConvertScenes { output, .. }
+            | JobStep::WriteManifest { output, .. } => output,
+        }
+    }
+}
+
+impl Job {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut job = Self::from_json(&std::fs::read_to_string(path)?, path.parent().unwrap_or(Path::new(".")))?;
+        if job.name.is_empty() {
+            job.name = paths::display_name(path);
+        }
+        Ok(job)
+    }
+
+    // Relative paths in the job are joined onto `base_dir`
+    pub fn from_json(text: &str, base_dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut job: Job = serde_json::from_str(text)?;
+        for step in &mut job.steps {
+            for step_path in step.paths_mut() {
+                if step_path.is_relative() {
+                    *step_path = base_dir.join(&*step_path);
+                }
+            }
+        }
+        Ok(job)
+    }
+}
+
+// Case-insensitive glob over '/'-separated names. `*` and `?` stay within one folder, `**`
+// crosses folders. Patterns without a '/' are matched against the file name alone.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = VirtualPath::new(pattern);
+    let name = VirtualPath::new(name);
+    let name = if pattern.as_str().contains('/') {
+        name.as_str()
+    } else {
+        name.as_str().rsplit('/').next().unwrap_or_default()
+    };
+    glob_match_bytes(pattern.as_str().as_bytes(), name.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+            // "**/" also matches no folders at all
+            let rest = &rest[1..];
+            let rest_without_slash = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=name.len()).any(|skip| glob_match_bytes(rest, &name[skip..]))
+                || glob_match_bytes(rest_without_slash, name)
+        }
+        Some((b'*', rest)) => {
+            let folder_end = name.iter().position(|&c| c == b'/').unwrap_or(name.len());
+            (0..=folder_end).any(|skip| glob_match_bytes(rest, &name[skip..]))
+        }
+        Some((b'?', rest)) => name.first().map_or(false, |&c| c != b'/') && glob_match_bytes(rest, &name[1..]),
+        Some((&c, rest)) => name.first() == Some(&c) && glob_match_bytes(rest, &name[1..]),
+    }
+}
+
+// Files below `root` whose path relative to it matches `pattern`, as (full path, relative path)
+pub fn matching_files(root: &Path, pattern: &str) -> Vec<(PathBuf, PathBuf)> {
+    let mut files: Vec<(PathBuf, PathBuf)> = walkdir::WalkDir::new(paths::long_path(root))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(paths::long_path(root)).ok()?.to_path_buf();
+            glob_match(pattern, &relative.to_string_lossy()).then(|| (e.into_path(), relative))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+// Decodes a TBODY/DDS (first face for cubemaps and arrays) to PNG bytes
+pub fn texture_png_bytes(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    texture_png_with_size(data).map(|(png, _)| png)
+}
+
+// Like texture_png_bytes, also returning the width and height for export naming
+pub fn texture_png_with_size(data: &[u8]) -> Result<(Vec<u8>, (u32, u32)), Box<dyn std::error::Error>> {
+    let first_surface = DdsLayout::parse(data)
+        .filter(|layout| layout.surface_count() > 1)
+        .and_then(|layout| layout.extract_surface(data, 0));
+    let image = image::load_from_memory_with_format(first_surface.as_deref().unwrap_or(data), ImageFormat::Dds)?;
+
+    let mut png = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok((png, (image.width(), image.height())))
+}
+
+// Width and height from a TBODY/DDS header, without decoding the rest of the file
+pub fn texture_size(path: &Path) -> Option<(u32, u32)> {
+    let mut header = Vec::new();
+    std::fs::File::open(paths::long_path(path)).ok()?.take(148).read_to_end(&mut header).ok()?;
+    DdsLayout::parse(&header).map(|layout| (layout.width, layout.height))
+}
+
+pub fn write_manifest(root: &Path, output: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut listing = String::new();
+    let files = matching_files(root, "**");
+    for (path, relative) in &files {
+        let size = std::fs::metadata(path)?.len();
+        listing.push_str(&format!("{}\t{}\n", relative.to_string_lossy().replace('\\', "/"), size));
+    }
+    paths::write_creating_dirs(output, listing.as_bytes())?;
+    Ok(files.len())
+}
@@ -0,0 +1,69 @@
+use super::storage_analyzer::format_size;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Decompressed archive data held in memory at once, entry cache included; set from Options
+pub const DEFAULT_BUDGET_MB: u64 = 2048;
+pub const MIN_BUDGET_MB: u64 = 256;
+
+// The entry cache gets a quarter of the budget up to its usual size; buffers share the rest
+const CACHE_SHARE: u64 = 4;
+
+static BUFFER_BUDGET: AtomicU64 = AtomicU64::new(DEFAULT_BUDGET_MB * 1024 * 1024 / CACHE_SHARE * (CACHE_SHARE - 1));
+static RESERVED: AtomicU64 = AtomicU64::new(0);
+static REFUSED: AtomicU64 = AtomicU64::new(0);
+
+// Applies a new budget and returns the part left for the entry cache
+pub fn set_budget_mb(megabytes: u64, max_cache: usize) -> usize {
+    let total = megabytes.max(MIN_BUDGET_MB) * 1024 * 1024;
+    let cache = (total / CACHE_SHARE).min(max_cache as u64);
+    BUFFER_BUDGET.store(total - cache, Ordering::Relaxed);
+    cache as usize
+}
+
+pub fn buffer_budget() -> u64 {
+    BUFFER_BUDGET.load(Ordering::Relaxed)
+}
+
+pub fn reserved() -> u64 {
+    RESERVED.load(Ordering::Relaxed)
+}
+
+// Operations turned down since startup
+pub fn refused() -> u64 {
+    REFUSED.load(Ordering::Relaxed)
+}
+
+// Counts `bytes` against the budget until dropped
+#[must_use]
+pub struct Reservation {
+    bytes: u64,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        RESERVED.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+// Claims room for a buffer about to be allocated. Running out of memory aborts the whole app, so
+// an operation that would go over the budget is refused with a message saying what to do instead.
+pub fn reserve(bytes: u64, what: &str) -> Result<Reservation, String> {
+    let budget = buffer_budget();
+    let mut current = RESERVED.load(Ordering::Relaxed);
+    loop {
+        if current + bytes > budget {
+            REFUSED.fetch_add(1, Ordering::Relaxed);
+            return Err(format!(
+                "{} needs {} but only {} of the {} memory budget is free; wait for other archive work to finish or raise the budget in Options",
+                what,
+                format_size(bytes),
+                format_size(budget.saturating_sub(current)),
+                format_size(budget)
+            ));
+        }
+        match RESERVED.compare_exchange_weak(current, current + bytes, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return Ok(Reservation { bytes }),
+            Err(actual) => current = actual,
+        }
+    }
+}
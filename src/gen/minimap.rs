@@ -0,0 +1,88 @@
+use image::{Rgba, RgbaImage};
+
+// Longest side of the rendered image
+pub const MINIMAP_SIZE: u32 = 768;
+
+const BACKGROUND: Rgba<u8> = Rgba([24, 26, 30, 255]);
+const LOW: [f32; 3] = [40.0, 70.0, 110.0];
+const HIGH: [f32; 3] = [235.0, 230.0, 200.0];
+
+pub type Triangle = [[f32; 3]; 3];
+
+// Applies a column-major instance matrix, as scene_export builds them
+pub fn transform(matrix: &[f32; 16], p: [f32; 3]) -> [f32; 3] {
+    [
+        matrix[0] * p[0] + matrix[4] * p[1] + matrix[8] * p[2] + matrix[12],
+        matrix[1] * p[0] + matrix[5] * p[1] + matrix[9] * p[2] + matrix[13],
+        matrix[2] * p[0] + matrix[6] * p[1] + matrix[10] * p[2] + matrix[14],
+    ]
+}
+
+// Top-down (X/Z) projection of the triangles, shaded by height so roads, ramps and buildings read
+// apart. Each pixel keeps the highest surface over it, as seen from above.
+pub fn render(triangles: &[Triangle], size: u32) -> Option<RgbaImage> {
+    let (mut min, mut max) = ([f32::MAX; 3], [f32::MIN; 3]);
+    for point in triangles.iter().flatten() {
+        for axis in 0..3 {
+            if point[axis].is_finite() {
+                min[axis] = min[axis].min(point[axis]);
+                max[axis] = max[axis].max(point[axis]);
+            }
+        }
+    }
+    let (width, depth) = (max[0] - min[0], max[2] - min[2]);
+    if !(width > 0.0 && depth > 0.0) {
+        return None;
+    }
+    let scale = (size - 1) as f32 / width.max(depth);
+    let (image_width, image_height) = ((width * scale) as u32 + 1, (depth * scale) as u32 + 1);
+    let height_range = (max[1] - min[1]).max(f32::EPSILON);
+
+    let mut heights = vec![f32::MIN; (image_width * image_height) as usize];
+    for triangle in triangles {
+        if triangle.iter().flatten().any(|v| !v.is_finite()) {
+            continue;
+        }
+        // Screen space: x right, z down, y kept for the height test
+        let points: Vec<[f32; 3]> = triangle.iter().map(|p| [(p[0] - min[0]) * scale, (p[2] - min[2]) * scale, p[1]]).collect();
+        let [a, b, c] = [points[0], points[1], points[2]];
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        // Walls seen edge-on still leave a line, so they don't vanish from the map
+        if area.abs() < f32::EPSILON {
+            for point in [a, b, c] {
+                let index = point[1] as u32 * image_width + point[0] as u32;
+                let height = &mut heights[index as usize];
+                *height = height.max(point[2]);
+            }
+            continue;
+        }
+        let x0 = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let x1 = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(image_width - 1);
+        let y0 = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let y1 = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(image_height - 1);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = ((b[0] - px) * (c[1] - py) - (b[1] - py) * (c[0] - px)) / area;
+                let w1 = ((c[0] - px) * (a[1] - py) - (c[1] - py) * (a[0] - px)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                let height = &mut heights[(y * image_width + x) as usize];
+                *height = height.max(w0 * a[2] + w1 * b[2] + w2 * c[2]);
+            }
+        }
+    }
+
+    let mut image = RgbaImage::from_pixel(image_width, image_height, BACKGROUND);
+    for (index, height) in heights.iter().enumerate() {
+        if *height == f32::MIN {
+            continue;
+        }
+        let t = ((height - min[1]) / height_range).clamp(0.0, 1.0);
+        let color = [0, 1, 2].map(|i| (LOW[i] + (HIGH[i] - LOW[i]) * t) as u8);
+        image.put_pixel(index as u32 % image_width, index as u32 / image_width, Rgba([color[0], color[1], color[2], 255]));
+    }
+    Some(image)
+}
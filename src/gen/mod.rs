@@ -1,6 +1,64 @@
 pub mod mtb_reader;
 pub mod tbody_viewer;
+pub mod dds;
+pub mod texture_watch;
 pub mod mtb_viewer;
 pub mod read_scene;
+pub mod oct_schema;
+pub mod storage_analyzer;
+pub mod tasks;
+pub mod command_palette;
+pub mod write_guard;
+pub mod paths;
+pub mod entry_cache;
+pub mod profiling;
+pub mod updater;
+pub mod themes;
+pub mod history;
+pub mod jobs;
+pub mod remote;
+pub mod scene_export;
+pub mod validation;
+pub mod sniff;
+pub mod file_ops;
+pub mod templates;
+pub mod binary_template;
+pub mod analyze;
+pub mod signatures;
+pub mod zip_writer;
+pub mod extract_journal;
+pub mod dry_run;
+pub mod naming;
+pub mod release_manifest;
+pub mod archive_diff;
+pub mod scene_diff;
+pub mod selection_bus;
+pub mod detached;
+pub mod scene_tree;
+pub mod docs;
+pub mod tags;
+pub mod ab_slots;
+pub mod health;
+pub mod profiles;
+pub mod instance;
+pub mod associations;
+pub mod text_browser;
+pub mod figure_data;
+pub mod toy_box;
+pub mod vehicle_stats;
+pub mod tracks;
+pub mod minimap;
+pub mod usage_stats;
+pub mod memory_guard;
+pub mod game_lock;
+pub mod elevation;
+pub mod safe_write;
+pub mod mod_conflicts;
+pub mod mod_package;
+pub mod mod_browser;
+pub mod codecs;
+pub mod raw_entry;
+pub mod entry_metadata;
+pub mod shell;
 
 pub use mtb_viewer::MtbViewer;
\ No newline at end of file
@@ -0,0 +1,322 @@
+use eframe::egui;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use super::paths::long_path;
+use super::storage_analyzer::format_size;
+use super::tasks::TaskContext;
+use super::shell::open_in_browser;
+
+const USER_AGENT: &str = concat!("Tundra/", env!("CARGO_PKG_VERSION"));
+const SCREENSHOT_HEIGHT: f32 = 180.0;
+
+// One published mod in the community index:
+// {"mods": [{"name", "version", "game", "author", "description", "package_url", "sha256",
+//   "size", "screenshots": [urls], "homepage"}]}
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub version: String,
+    // GameType::as_str, same as in the package manifest
+    pub game: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    pub package_url: String,
+    // Of the whole .tundramod, checked after downloading when given
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub screenshots: Vec<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    // Whether the index itself was a local file, the only case its entries may point at local paths
+    #[serde(skip)]
+    pub local_index: bool,
+}
+
+#[derive(Deserialize)]
+struct Index {
+    mods: Vec<IndexEntry>,
+}
+
+fn is_web_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+// The index and packages may also be local paths, for testing one before publishing it. A
+// downloaded index can't send Tundra reading local files, so those need `allow_local`.
+fn open_url(url: &str, allow_local: bool) -> Result<(Box<dyn Read + Send>, Option<u64>), String> {
+    if !is_web_url(url) {
+        if !allow_local {
+            return Err(format!("{} isn't an http(s) URL, which is all an online index may link to", url));
+        }
+        let path = url.trim_start_matches("file://");
+        let file = std::fs::File::open(long_path(Path::new(path))).map_err(|e| format!("{}: {}", path, e))?;
+        let size = file.metadata().ok().map(|m| m.len());
+        return Ok((Box::new(file), size));
+    }
+    let response = ureq::get(url).set("User-Agent", USER_AGENT).call().map_err(|e| e.to_string())?;
+    let size = response.header("Content-Length").and_then(|length| length.parse().ok());
+    Ok((Box::new(response.into_reader()), size))
+}
+
+// Mods in the index made for `game`
+pub fn fetch_index(url: &str, game: &str) -> Result<Vec<IndexEntry>, String> {
+    let (mut reader, _) = open_url(url, true)?;
+    let mut body = String::new();
+    reader.read_to_string(&mut body).map_err(|e| e.to_string())?;
+    let index: Index = serde_json::from_str(&body).map_err(|e| format!("Not a mod index: {}", e))?;
+    let local_index = !is_web_url(url);
+    Ok(index.mods.into_iter()
+        .filter(|entry| entry.game == game)
+        .map(|entry| IndexEntry { local_index, ..entry })
+        .collect())
+}
+
+// Downloads a package to `output`, checking it against the index's hash when there is one
+pub fn download(entry: &IndexEntry, output: &Path, task: &TaskContext) -> Result<(), String> {
+    let (mut reader, size) = open_url(&entry.package_url, entry.local_index)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(long_path(parent)).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::File::create(long_path(output)).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut received: u64 = 0;
+    let total = size.or(entry.size);
+    loop {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+        received += read as u64;
+        task.set_message(match total {
+            Some(total) => format!("Downloading {} ({} of {})", entry.name, format_size(received), format_size(total)),
+            None => format!("Downloading {} ({})", entry.name, format_size(received)),
+        });
+    }
+    if let Some(expected) = &entry.sha256 {
+        let hash: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        if !hash.eq_ignore_ascii_case(expected) {
+            return Err(format!("The download of {} doesn't match the index's hash; try again later", entry.name));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+enum IndexState {
+    Idle,
+    Loading,
+    Loaded(Vec<IndexEntry>),
+    Failed(String),
+}
+
+pub enum BrowserAction {
+    // The index URL was edited
+    SaveUrl,
+    Install(IndexEntry),
+}
+
+pub struct ModBrowser {
+    state: Arc<Mutex<IndexState>>,
+    // For the game the index was fetched for
+    game: String,
+    selected: Option<usize>,
+    search: String,
+    // Downloaded bytes per URL, None when it failed; turned into textures on the UI thread
+    screenshot_bytes: Arc<Mutex<HashMap<String, Option<Vec<u8>>>>>,
+    screenshots: HashMap<String, Option<egui::TextureHandle>>,
+    requested: HashSet<String>,
+}
+
+impl ModBrowser {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(IndexState::Idle)),
+            game: String::new(),
+            selected: None,
+            search: String::new(),
+            screenshot_bytes: Arc::new(Mutex::new(HashMap::new())),
+            screenshots: HashMap::new(),
+            requested: HashSet::new(),
+        }
+    }
+
+    pub fn refresh(&mut self, url: &str, game: &str, ctx: &egui::Context) {
+        *self.state.lock().unwrap() = IndexState::Loading;
+        self.game = game.to_string();
+        self.selected = None;
+        let (state, url, game, ctx) = (self.state.clone(), url.to_string(), game.to_string(), ctx.clone());
+        thread::spawn(move || {
+            let result = match fetch_index(&url, &game) {
+                Ok(entries) => {
+                    println!("Mod index lists {} mods for {}", entries.len(), game);
+                    IndexState::Loaded(entries)
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch mod index {}: {}", url, e);
+                    IndexState::Failed(e)
+                }
+            };
+            *state.lock().unwrap() = result;
+            ctx.request_repaint();
+        });
+    }
+
+    // Some(None) when it couldn't be loaded, None while it's still downloading
+    fn screenshot(&mut self, url: &str, allow_local: bool, ctx: &egui::Context) -> Option<Option<egui::TextureHandle>> {
+        if let Some(texture) = self.screenshots.get(url) {
+            return Some(texture.clone());
+        }
+        let bytes = self.screenshot_bytes.lock().unwrap().remove(url);
+        let Some(bytes) = bytes else {
+            if self.requested.insert(url.to_string()) {
+                let (slot, url, ctx) = (self.screenshot_bytes.clone(), url.to_string(), ctx.clone());
+                thread::spawn(move || {
+                    let bytes = open_url(&url, allow_local).ok().and_then(|(mut reader, _)| {
+                        let mut bytes = Vec::new();
+                        reader.read_to_end(&mut bytes).ok().map(|_| bytes)
+                    });
+                    slot.lock().unwrap().insert(url, bytes);
+                    ctx.request_repaint();
+                });
+            }
+            return None;
+        };
+        let texture = bytes.and_then(|bytes| image::load_from_memory(&bytes).ok()).map(|image| {
+            let image = image.thumbnail(640, 360).to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            ctx.load_texture(format!("mod_screenshot_{}", url), egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice()), Default::default())
+        });
+        self.screenshots.insert(url.to_string(), texture.clone());
+        Some(texture)
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, url: &mut String, game: &str) -> Option<BrowserAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.label("Index URL:");
+            let response = ui.add(egui::TextEdit::singleline(url).desired_width(360.0).hint_text("https://.../tundra_mods.json"));
+            if response.lost_focus() {
+                action = Some(BrowserAction::SaveUrl);
+            }
+            let loading = matches!(*self.state.lock().unwrap(), IndexState::Loading);
+            if ui.add_enabled(!url.trim().is_empty() && !loading, egui::Button::new("Refresh")).clicked() {
+                self.refresh(url.trim(), game, ui.ctx());
+            }
+        });
+        ui.weak("A community-maintained list of published mods; packages install like Install mod package.");
+        ui.separator();
+
+        if self.game != game && !matches!(*self.state.lock().unwrap(), IndexState::Loading) {
+            *self.state.lock().unwrap() = IndexState::Idle;
+        }
+        let state = self.state.lock().unwrap().clone();
+        let entries = match state {
+            IndexState::Idle => {
+                ui.label(if url.trim().is_empty() { "Enter the URL of a mod index to browse it." } else { "Press Refresh to load the index." });
+                return action;
+            }
+            IndexState::Loading => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Fetching the index...");
+                });
+                return action;
+            }
+            IndexState::Failed(e) => {
+                ui.colored_label(egui::Color32::RED, e);
+                return action;
+            }
+            IndexState::Loaded(entries) => entries,
+        };
+        if entries.is_empty() {
+            ui.label(format!("The index has no mods for {}.", game));
+            return action;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+        });
+        let search = self.search.to_lowercase();
+        ui.columns(2, |columns| {
+            egui::ScrollArea::vertical().id_source("mod_index_list").max_height(420.0).show(&mut columns[0], |ui| {
+                for (index, entry) in entries.iter().enumerate() {
+                    let text = format!("{} {} {}", entry.name, entry.author, entry.description).to_lowercase();
+                    if !search.is_empty() && !text.contains(&search) {
+                        continue;
+                    }
+                    let label = if entry.author.is_empty() {
+                        format!("{} {}", entry.name, entry.version)
+                    } else {
+                        format!("{} {} by {}", entry.name, entry.version, entry.author)
+                    };
+                    if ui.selectable_label(self.selected == Some(index), label).clicked() {
+                        self.selected = Some(index);
+                    }
+                }
+            });
+
+            let ui = &mut columns[1];
+            let Some(entry) = self.selected.and_then(|index| entries.get(index)) else {
+                ui.weak("Pick a mod to see its details.");
+                return;
+            };
+            ui.heading(&entry.name);
+            ui.label(format!("Version {}{}", entry.version, entry.size.map_or(String::new(), |size| format!(", {}", format_size(size)))));
+            if !entry.author.is_empty() {
+                ui.label(format!("By {}", entry.author));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Download and install").clicked() {
+                    action = Some(BrowserAction::Install(entry.clone()));
+                }
+                if let Some(homepage) = &entry.homepage {
+                    if ui.button("Homepage").clicked() {
+                        if let Err(e) = open_in_browser(homepage) {
+                            eprintln!("Failed to open {}: {}", homepage, e);
+                        }
+                    }
+                }
+            });
+            if entry.sha256.is_none() {
+                ui.weak("The index gives no hash for this package; only the files inside it are checked.");
+            }
+            ui.separator();
+            egui::ScrollArea::vertical().id_source("mod_index_details").max_height(320.0).show(ui, |ui| {
+                ui.label(if entry.description.is_empty() { "No description." } else { entry.description.as_str() });
+                let screenshots = entry.screenshots.clone();
+                for url in &screenshots {
+                    match self.screenshot(url, entry.local_index, ui.ctx()) {
+                        Some(Some(texture)) => {
+                            let size = texture.size_vec2();
+                            ui.add(egui::Image::new(&texture).fit_to_exact_size(size * (SCREENSHOT_HEIGHT / size.y)));
+                        }
+                        Some(None) => {
+                            ui.weak(format!("Couldn't load {}", url));
+                        }
+                        None => {
+                            ui.spinner();
+                        }
+                    }
+                }
+            });
+        });
+        action
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+
+// Always deployed last, over every installed mod
+pub const WORKSPACE_NAME: &str = "Workspace";
+
+// A folder laid out like the game install, deployed over it in load order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledMod {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+// One mod's files as (absolute, relative to the game folder)
+pub struct ModFiles {
+    pub name: String,
+    pub files: Vec<(PathBuf, PathBuf)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub relative: PathBuf,
+    // Indexes into the sources providing it, in load order
+    pub providers: Vec<usize>,
+    pub winner: usize,
+    // Archive entries the providers disagree on; filled in by a conflict check
+    pub entries: Vec<String>,
+}
+
+// Windows treats names case-insensitively, so two mods differing only in case still collide
+pub fn key(relative: &Path) -> String {
+    relative.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+fn winner(relative: &Path, providers: &[usize], sources: &[ModFiles], overrides: &HashMap<String, String>) -> usize {
+    overrides
+        .get(&key(relative))
+        .and_then(|name| providers.iter().copied().find(|index| &sources[*index].name == name))
+        .unwrap_or(providers[providers.len() - 1])
+}
+
+fn same_contents(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (fs::metadata(long_path(a)), fs::metadata(long_path(b))) else {
+        return false;
+    };
+    if meta_a.len() != meta_b.len() {
+        return false;
+    }
+    let (Ok(file_a), Ok(file_b)) = (fs::File::open(long_path(a)), fs::File::open(long_path(b))) else {
+        return false;
+    };
+    let (mut reader_a, mut reader_b) = (BufReader::new(file_a), BufReader::new(file_b));
+    let (mut buffer_a, mut buffer_b) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let Ok(read) = reader_a.read(&mut buffer_a) else {
+            return false;
+        };
+        if read == 0 {
+            return true;
+        }
+        if reader_b.read_exact(&mut buffer_b[..read]).is_err() || buffer_a[..read] != buffer_b[..read] {
+            return false;
+        }
+    }
+}
+
+// (key, providers in load order) for every relative path
+fn providers(sources: &[ModFiles]) -> BTreeMap<String, Vec<(usize, usize)>> {
+    let mut map: BTreeMap<String, Vec<(usize, usize)>> = BTreeMap::new();
+    for (source_index, source) in sources.iter().enumerate() {
+        for (file_index, (_, relative)) in source.files.iter().enumerate() {
+            map.entry(key(relative)).or_default().push((source_index, file_index));
+        }
+    }
+    map
+}
+
+// Files more than one source provides with different contents
+pub fn find_conflicts(sources: &[ModFiles], overrides: &HashMap<String, String>) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    for found in providers(sources).into_values().filter(|found| found.len() > 1) {
+        let (first_source, first_file) = found[0];
+        let first = &sources[first_source].files[first_file].0;
+        if found[1..].iter().all(|(source, file)| same_contents(first, &sources[*source].files[*file].0)) {
+            continue;
+        }
+        let relative = sources[first_source].files[first_file].1.clone();
+        let indexes: Vec<usize> = found.iter().map(|(source, _)| *source).collect();
+        conflicts.push(Conflict { winner: winner(&relative, &indexes, sources, overrides), relative, providers: indexes, entries: Vec::new() });
+    }
+    conflicts
+}
+
+// What a deploy copies: each relative path once, from the source that wins it
+pub fn merge(sources: &[ModFiles], overrides: &HashMap<String, String>) -> Vec<(PathBuf, PathBuf)> {
+    providers(sources)
+        .into_values()
+        .map(|found| {
+            let indexes: Vec<usize> = found.iter().map(|(source, _)| *source).collect();
+            let relative = &sources[found[0].0].files[found[0].1].1;
+            let chosen = winner(relative, &indexes, sources, overrides);
+            let (source, file) = found.iter().find(|(source, _)| *source == chosen).copied().unwrap_or(found[found.len() - 1]);
+            sources[source].files[file].clone()
+        })
+        .collect()
+}
+
+// Entry names the archives don't agree on: missing from some, or with a different CRC. Only the
+// winning archive is deployed, so the others' changes to these are lost.
+pub fn differing_entries(listings: &[Vec<(String, u32)>]) -> Vec<String> {
+    let mut seen: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for listing in listings {
+        for (name, crc) in listing {
+            seen.entry(name.as_str()).or_default().push(*crc);
+        }
+    }
+    seen.into_iter()
+        .filter(|(_, crcs)| crcs.len() != listings.len() || crcs.iter().any(|crc| *crc != crcs[0]))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
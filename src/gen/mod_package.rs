@@ -0,0 +1,253 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+use super::release_manifest::{hash_file, relative_name};
+use super::safe_write;
+use super::tasks::TaskContext;
+
+// A .tundramod is a ZIP holding tundra_mod.json and the mod's files under files/, laid out like
+// the game folder
+pub const PACKAGE_EXTENSION: &str = "tundramod";
+pub const MANIFEST_NAME: &str = "tundra_mod.json";
+const FILES_PREFIX: &str = "files/";
+// Raised when a package needs something older versions can't install
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModManifest {
+    pub format: u32,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    // GameType::as_str of the game it's for
+    pub game: String,
+    pub files: Vec<PackageFile>,
+    #[serde(default)]
+    pub install: InstallRules,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageFile {
+    // Relative to the game folder, with forward slashes
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallRules {
+    // Installed mods this one goes after in the load order, when they're there
+    #[serde(default)]
+    pub load_after: Vec<String>,
+    // Files this mod keeps even against mods loaded after it
+    #[serde(default)]
+    pub overrides: Vec<String>,
+}
+
+// Mod names become folder names when installed
+pub fn folder_name(name: &str) -> String {
+    let cleaned: String = name.chars().map(|c| if c.is_alphanumeric() || " -_.()".contains(c) { c } else { '_' }).collect();
+    let cleaned = cleaned.trim().trim_matches('.').to_string();
+    if cleaned.is_empty() { "mod".to_string() } else { cleaned }
+}
+
+// Packs every file below `folder` with a manifest listing their hashes. Returns a summary.
+pub fn write_package(folder: &Path, output: &Path, mut manifest: ModManifest, task: &TaskContext) -> Result<String, String> {
+    let files: Vec<PathBuf> = walkdir::WalkDir::new(long_path(folder))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path() != output)
+        .map(|e| e.into_path())
+        .collect();
+    if files.is_empty() {
+        return Err(format!("{} has no files to package", folder.display()));
+    }
+    task.set_total(files.len() * 2);
+    manifest.format = FORMAT_VERSION;
+    manifest.files.clear();
+    for path in &files {
+        let name = relative_name(&long_path(folder), path);
+        task.advance(format!("Hashing {}", name));
+        let size = fs::metadata(path).map(|m| m.len()).map_err(|e| format!("{}: {}", name, e))?;
+        let sha256 = hash_file(path).map_err(|e| format!("{}: {}", name, e))?;
+        manifest.files.push(PackageFile { path: name, size, sha256 });
+    }
+
+    safe_write::prepare(output).map_err(|e| e.to_string())?;
+    let result = (|| -> Result<(), String> {
+        let file = fs::File::create(long_path(output)).map_err(|e| e.to_string())?;
+        let mut writer = zip::ZipWriter::new(BufWriter::new(file));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        writer.start_file(MANIFEST_NAME, options).map_err(|e| e.to_string())?;
+        writer.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+        for (path, listed) in files.iter().zip(&manifest.files) {
+            if task.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            task.advance(format!("Packing {}", listed.path));
+            let options = options.large_file(listed.size >= u32::MAX as u64);
+            writer.start_file(format!("{}{}", FILES_PREFIX, listed.path), options).map_err(|e| format!("{}: {}", listed.path, e))?;
+            let mut source = fs::File::open(path).map_err(|e| format!("{}: {}", listed.path, e))?;
+            std::io::copy(&mut source, &mut writer).map_err(|e| format!("{}: {}", listed.path, e))?;
+        }
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(long_path(output));
+        return Err(e);
+    }
+    println!("Wrote mod package {} ({} files)", output.display(), files.len());
+    Ok(format!("{} {}: {} files → {}", manifest.name, manifest.version, files.len(), output.display()))
+}
+
+pub fn read_manifest(package: &Path) -> Result<ModManifest, String> {
+    let file = fs::File::open(long_path(package)).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Not a mod package: {}", e))?;
+    let mut json = String::new();
+    archive.by_name(MANIFEST_NAME)
+        .map_err(|_| format!("Not a mod package: {} is missing", MANIFEST_NAME))?
+        .read_to_string(&mut json)
+        .map_err(|e| e.to_string())?;
+    let manifest: ModManifest = serde_json::from_str(&json).map_err(|e| format!("Broken {}: {}", MANIFEST_NAME, e))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!("{} needs a newer version of Tundra (package format {})", manifest.name, manifest.format));
+    }
+    Ok(manifest)
+}
+
+// Unpacks into `mods_dir`/<name>, checking every file against the manifest first; an existing
+// install of the same mod is only replaced once the new one checks out. Returns the manifest and
+// the folder it's installed in.
+pub fn install(package: &Path, mods_dir: &Path, game: &str, task: &TaskContext) -> Result<(ModManifest, PathBuf), String> {
+    let manifest = read_manifest(package)?;
+    if manifest.game != game {
+        return Err(format!("{} is made for {}, not {}", manifest.name, manifest.game, game));
+    }
+    let folder = mods_dir.join(folder_name(&manifest.name));
+    let staging = mods_dir.join(format!("{}.tundra-staged", folder_name(&manifest.name)));
+    let _ = fs::remove_dir_all(long_path(&staging));
+
+    let result = (|| -> Result<(), String> {
+        let file = fs::File::open(long_path(package)).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        task.set_total(manifest.files.len());
+        for listed in &manifest.files {
+            if task.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            task.advance(listed.path.clone());
+            // Nothing may land outside the mod's folder
+            let relative = Path::new(&listed.path);
+            if relative.is_absolute() || relative.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+                return Err(format!("{} points outside the mod folder", listed.path));
+            }
+            let mut entry = archive.by_name(&format!("{}{}", FILES_PREFIX, listed.path)).map_err(|_| format!("{} is listed but missing", listed.path))?;
+            let target = staging.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(long_path(parent)).map_err(|e| e.to_string())?;
+            }
+            let mut output = fs::File::create(long_path(&target)).map_err(|e| format!("{}: {}", listed.path, e))?;
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let read = entry.read(&mut buffer).map_err(|e| format!("{}: {}", listed.path, e))?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                output.write_all(&buffer[..read]).map_err(|e| format!("{}: {}", listed.path, e))?;
+            }
+            let hash: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+            if !hash.eq_ignore_ascii_case(&listed.sha256) {
+                return Err(format!("{} is damaged (hash doesn't match the manifest); download the package again", listed.path));
+            }
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(long_path(&staging));
+        return Err(e);
+    }
+
+    if folder.exists() {
+        fs::remove_dir_all(long_path(&folder)).map_err(|e| format!("Couldn't replace the installed {}: {}", manifest.name, e))?;
+    }
+    fs::rename(long_path(&staging), long_path(&folder)).map_err(|e| e.to_string())?;
+    println!("Installed mod {} {} into {}", manifest.name, manifest.version, folder.display());
+    Ok((manifest, folder))
+}
+
+// The fields asked for before packing; `sources` are the workspace and installed mods by name
+pub struct ExportForm {
+    pub source: usize,
+    pub manifest: ModManifest,
+    load_after: String,
+}
+
+pub enum ExportAction {
+    Export,
+    Cancel,
+}
+
+impl ExportForm {
+    pub fn new(game: &str) -> Self {
+        Self {
+            source: 0,
+            manifest: ModManifest { name: "My mod".to_string(), version: "1.0".to_string(), game: game.to_string(), ..Default::default() },
+            load_after: String::new(),
+        }
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, sources: &[String]) -> Option<ExportAction> {
+        let mut action = None;
+        egui::Grid::new("mod_export_form").num_columns(2).show(ui, |ui| {
+            ui.label("Files from");
+            egui::ComboBox::from_id_source("mod_export_source")
+                .selected_text(sources.get(self.source).cloned().unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for (index, name) in sources.iter().enumerate() {
+                        ui.selectable_value(&mut self.source, index, name);
+                    }
+                });
+            ui.end_row();
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.manifest.name);
+            ui.end_row();
+            ui.label("Version");
+            ui.text_edit_singleline(&mut self.manifest.version);
+            ui.end_row();
+            ui.label("Author");
+            ui.text_edit_singleline(&mut self.manifest.author);
+            ui.end_row();
+            ui.label("Description");
+            ui.text_edit_multiline(&mut self.manifest.description);
+            ui.end_row();
+            ui.label("Load after").on_hover_text("Comma-separated mod names this one should be deployed after, when installed");
+            ui.text_edit_singleline(&mut self.load_after);
+            ui.end_row();
+        });
+        ui.small(format!("For {}", self.manifest.game));
+        ui.horizontal(|ui| {
+            let valid = !self.manifest.name.trim().is_empty() && !self.manifest.version.trim().is_empty();
+            if ui.add_enabled(valid, egui::Button::new("Export...")).clicked() {
+                self.manifest.name = self.manifest.name.trim().to_string();
+                self.manifest.install.load_after = self.load_after.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect();
+                action = Some(ExportAction::Export);
+            }
+            if ui.button("Cancel").clicked() {
+                action = Some(ExportAction::Cancel);
+            }
+        });
+        action
+    }
+}
@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use super::read_scene::GameType;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MtbTextureInfo {
     pub name: String,
     pub tbody_filename: String,
     pub offset: usize,
+    // Material slot (diffuse, normal, ...) once MATP is parsed; None until then
+    #[serde(default)]
+    pub slot: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,8 +19,50 @@ pub struct MtbFile {
     pub is_ui_mtb: bool,
 }
 
+// Per-game TEXB layout. The Avalanche titles share the format but not the platform byte order,
+// and only the Octane 2 games name their texture bodies .tbody.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MtbParams {
+    // None picks the byte order from the texture count
+    pub big_endian: Option<bool>,
+    pub texture_extension: &'static str,
+}
+
+impl Default for MtbParams {
+    fn default() -> Self {
+        Self { big_endian: None, texture_extension: "tbody" }
+    }
+}
+
+impl MtbParams {
+    pub fn for_game(game_type: &GameType) -> Self {
+        match game_type {
+            GameType::DisneyInfinity30 | GameType::Cars3DrivenToWinXB1 => Self { big_endian: Some(false), texture_extension: "tbody" },
+            // Also shipped on PS3 and 360, so dumps from either byte order turn up
+            GameType::Cars2TheVideoGame | GameType::Cars2Arcade | GameType::ToyShit3 => Self { big_endian: None, texture_extension: "dds" },
+        }
+    }
+}
+
+fn read_u32(data: &[u8], cursor: usize, big_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(cursor..cursor + 4)?.try_into().ok()?;
+    Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+}
+
+// Texture counts are small, so whichever byte order reads the smaller count is the right one
+fn detect_big_endian(data: &[u8], start: usize) -> bool {
+    match (read_u32(data, start, false), read_u32(data, start, true)) {
+        (Some(little), Some(big)) => big < little,
+        _ => false,
+    }
+}
+
 impl MtbFile {
     pub fn parse_from_bytes(data: &[u8], file_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_with_params(data, file_path, &MtbParams::default())
+    }
+
+    pub fn parse_with_params(data: &[u8], file_path: &Path, params: &MtbParams) -> Result<Self, Box<dyn std::error::Error>> {
         let mut textures = Vec::new();
         let mut is_ui_mtb = false;
 
@@ -50,7 +96,9 @@ impl MtbFile {
         };
 
         // Skip past TEXB header (4 bytes)
-        let mut cursor = texb_start + 4;
+        let cursor = texb_start + 4;
+        let big_endian = params.big_endian.unwrap_or_else(|| detect_big_endian(data, cursor));
+        println!("TEXB byte order: {}", if big_endian { "big endian" } else { "little endian" });
 
         // Debug the TEXB section
         Self::debug_texb_section(data, texb_start);
@@ -61,11 +109,11 @@ impl MtbFile {
         
         if has_matp {
             println!("Detected normal MTB (has MATP section)");
-            textures.extend_from_slice(&Self::parse_normal_texb_section(data, cursor));
+            textures.extend_from_slice(&Self::parse_normal_texb_section(data, cursor, big_endian, params.texture_extension));
         } else {
             println!("Detected UI MTB (no MATP section)");
             is_ui_mtb = true;
-            textures.extend_from_slice(&Self::parse_ui_texb_section(data, cursor));
+            textures.extend_from_slice(&Self::parse_ui_texb_section(data, cursor, big_endian, params.texture_extension));
         }
 
         println!("Extracted {} valid textures from TEXB section", textures.len());
@@ -77,43 +125,40 @@ impl MtbFile {
         })
     }
 
-    fn parse_normal_texb_section(data: &[u8], start: usize) -> Vec<MtbTextureInfo> {
+    fn parse_normal_texb_section(data: &[u8], start: usize, big_endian: bool, extension: &str) -> Vec<MtbTextureInfo> {
         let mut textures = Vec::new();
         let mut cursor = start;
         let matp_header = b"MATP";
 
         println!("Parsing normal MTB TEXB section");
 
-        // Read texture count (little endian u32)
-        if cursor + 4 > data.len() {
+        // Read texture count (u32)
+        let Some(texture_count) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let texture_count = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]) as usize;
+        };
+        let texture_count = texture_count as usize;
         cursor += 4;
 
         println!("Texture count: {}", texture_count);
 
-        // Read section size (little endian u32)
-        if cursor + 4 > data.len() {
+        // Read section size (u32)
+        let Some(section_size) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let section_size = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]);
+        };
         cursor += 4;
         println!("Section size: 0x{:08X} ({} bytes)", section_size, section_size);
 
         // Read another field (might be actual texture count or offsets)
-        if cursor + 4 > data.len() {
+        let Some(field3) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let field3 = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]);
+        };
         cursor += 4;
         println!("Field 3: 0x{:08X}", field3);
 
         // Skip padding or unknown data (4 bytes of zeros)
-        if cursor + 4 > data.len() {
+        let Some(padding) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let padding = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]);
+        };
         println!("Padding: 0x{:08X}", padding);
         cursor += 4;
 
@@ -149,7 +194,7 @@ impl MtbFile {
                 .map(|b| format!("{:02x}", b))
                 .collect::<String>();
             
-            let tbody_filename = format!("{}.tbody", hex_filename);
+            let tbody_filename = format!("{}.{}", hex_filename, extension);
             
             // Create a readable name
             let name: String = texture_bytes
@@ -164,6 +209,7 @@ impl MtbFile {
                 name,
                 tbody_filename,
                 offset: cursor,
+                slot: None,
             });
             
             cursor += 12;
@@ -172,42 +218,41 @@ impl MtbFile {
         textures
     }
 
-    fn parse_ui_texb_section(data: &[u8], start: usize) -> Vec<MtbTextureInfo> {
+    fn parse_ui_texb_section(data: &[u8], start: usize, big_endian: bool, extension: &str) -> Vec<MtbTextureInfo> {
         let mut textures = Vec::new();
         let mut cursor = start;
 
         println!("Parsing UI MTB TEXB section");
 
-        // Read texture count (little endian u32)
-        if cursor + 4 > data.len() {
+        // Read texture count (u32)
+        let Some(texture_count) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let texture_count = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]) as usize;
+        };
+        let texture_count = texture_count as usize;
         cursor += 4;
 
         println!("UI Texture count: {}", texture_count);
 
-        // Read section size (little endian u32)
-        if cursor + 4 > data.len() {
+        // Read section size (u32)
+        let Some(section_size) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let section_size = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]);
+        };
         cursor += 4;
         println!("UI Section size: 0x{:08X} ({} bytes)", section_size, section_size);
 
         // Read actual texture count for UI MTB
-        if cursor + 4 > data.len() {
+        let Some(actual_texture_count) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let actual_texture_count = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]) as usize;
+        };
+        let actual_texture_count = actual_texture_count as usize;
         cursor += 4;
         println!("UI Actual texture count: {}", actual_texture_count);
 
         // The next bytes are the material name string length (u32) followed by the string
-        if cursor + 4 > data.len() {
+        let Some(string_length) = read_u32(data, cursor, big_endian) else {
             return textures;
-        }
-        let string_length = u32::from_le_bytes([data[cursor], data[cursor + 1], data[cursor + 2], data[cursor + 3]]) as usize;
+        };
+        let string_length = string_length as usize;
         cursor += 4;
         println!("UI Material name length: {}", string_length);
 
@@ -248,7 +293,7 @@ impl MtbFile {
                 .map(|b| format!("{:02x}", b))
                 .collect::<String>();
 
-            let tbody_filename = format!("{}.tbody", hex_filename);
+            let tbody_filename = format!("{}.{}", hex_filename, extension);
 
             // Create a readable name from the hex for display
             let name = format!("texture_{}", i);
@@ -260,6 +305,7 @@ impl MtbFile {
                 name,
                 tbody_filename,
                 offset: cursor,
+                slot: None,
             });
         
             cursor += 8;
@@ -307,8 +353,8 @@ impl MtbFile {
         println!("=== End Debug ===");
     }
 
-    pub fn load_from_file(file_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_from_file(file_path: &Path, params: &MtbParams) -> Result<Self, Box<dyn std::error::Error>> {
         let data = std::fs::read(file_path)?;
-        Self::parse_from_bytes(&data, file_path)
+        Self::parse_with_params(&data, file_path, params)
     }
 }
\ No newline at end of file
@@ -1,13 +1,109 @@
 use eframe::egui;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use super::mtb_reader::MtbFile;
-use super::tbody_viewer::TbodyViewer;
+use std::sync::{Arc, Mutex};
+use super::mtb_reader::{MtbFile, MtbParams, MtbTextureInfo};
+use super::tbody_viewer::{DecodeRequest, TbodyTexture, TbodyViewer, TextureStats, TextureViewSettings};
+use super::shell;
+use super::texture_watch::TextureWatcher;
+use super::write_guard::WriteGuard;
+use super::selection_bus::SelectionBus;
+use super::naming::{self, Naming};
+use super::paths::{self, VirtualPath};
+
+// Shortest shared hash prefix that still counts as a suggestion
+const MIN_MATCH_PREFIX: usize = 6;
+const MAX_MATCHES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureSource {
+    Configured,
+    SameFolder,
+    GlobalTextures,
+    ArchiveSibling,
+    Bound,
+}
+
+impl TextureSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextureSource::Configured => "configured path",
+            TextureSource::SameFolder => "same folder",
+            TextureSource::GlobalTextures => "assets/textures",
+            TextureSource::ArchiveSibling => "archive sibling",
+            TextureSource::Bound => "user binding",
+        }
+    }
+}
 
 pub struct MtbViewer {
     mtb_file: Option<MtbFile>,
     tbody_viewer: TbodyViewer,
     base_path: Option<PathBuf>,
     loaded_textures: bool,
+    // Extra folders searched first, set per game from the app config
+    pub search_paths: Vec<PathBuf>,
+    // TEXB layout of the selected game
+    pub params: MtbParams,
+    // Extraction root when the MTB came out of an archive
+    pub archive_root: Option<PathBuf>,
+    resolved: HashMap<String, (PathBuf, TextureSource)>,
+    // Remembered user picks for missing textures, checked before any search path
+    pub bindings: HashMap<String, PathBuf>,
+    // Set when the user asks for suggestions; the app runs the search and fills `matches`
+    pub match_request: Option<String>,
+    pub matches: Arc<Mutex<HashMap<String, Vec<TextureMatch>>>>,
+    bind_request: Option<(String, PathBuf)>,
+    export_status: Option<String>,
+    // Export folder waiting for the textures to finish decoding
+    pending_export: Option<PathBuf>,
+    // MTB, output folder and result of the last export, for the app's operation history
+    finished_export: Option<(PathBuf, PathBuf, String)>,
+    // Exported PNGs being edited elsewhere; kept across file loads
+    pub watcher: TextureWatcher,
+    pub write_guard: WriteGuard,
+    pub naming: Naming,
+    pub selection: SelectionBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextureMatch {
+    pub path: PathBuf,
+    pub score: usize,
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+// Scores a candidate file name against a missing "<hash>.tbody" name. The hash is also tried with
+// its bytes reversed since some tools write the 8-byte id as a little endian integer.
+pub fn texture_match_score(tbody_filename: &str, candidate_name: &str) -> Option<usize> {
+    let hash = tbody_filename.split('.').next()?.to_lowercase();
+    let candidate = candidate_name.to_lowercase();
+    let stem = candidate.split('.').next()?;
+
+    if candidate == tbody_filename.to_lowercase() {
+        return Some(hash.len() + 2);
+    }
+    if hash.len() >= MIN_MATCH_PREFIX && candidate.contains(&hash) {
+        return Some(hash.len() + 1);
+    }
+
+    let reversed = hash.as_bytes()
+        .chunks(2)
+        .rev()
+        .map(|pair| String::from_utf8_lossy(pair).into_owned())
+        .collect::<String>();
+    let score = common_prefix_len(&hash, stem).max(common_prefix_len(&reversed, stem));
+    (score >= MIN_MATCH_PREFIX).then_some(score)
+}
+
+// Keeps the best matches first, dropping the tail
+pub fn sort_texture_matches(matches: &mut Vec<TextureMatch>) {
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    matches.dedup_by(|a, b| a.path == b.path);
+    matches.truncate(MAX_MATCHES);
 }
 
 impl MtbViewer {
@@ -17,52 +113,301 @@ impl MtbViewer {
             tbody_viewer: TbodyViewer::new(),
             base_path: None,
             loaded_textures: false,
+            search_paths: Vec::new(),
+            params: MtbParams::default(),
+            archive_root: None,
+            resolved: HashMap::new(),
+            bindings: HashMap::new(),
+            match_request: None,
+            matches: Arc::new(Mutex::new(HashMap::new())),
+            bind_request: None,
+            export_status: None,
+            pending_export: None,
+            finished_export: None,
+            watcher: TextureWatcher::new(),
+            write_guard: WriteGuard::default(),
+            naming: Naming::default(),
+            selection: SelectionBus::default(),
         }
     }
 
-    pub fn load_mtb_file(&mut self, file_path: &Path, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_mtb_bytes(&mut self, file_path: &Path, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         self.clear();
         
-        let mtb_file = MtbFile::load_from_file(file_path)?;
+        let mtb_file = MtbFile::parse_with_params(data, file_path, &self.params)?;
         self.mtb_file = Some(mtb_file);
         self.base_path = file_path.parent().map(|p| p.to_path_buf());
         
         // Try to load associated textures
-        self.load_associated_textures(ctx);
+        self.load_associated_textures();
         
         Ok(())
     }
 
-    pub fn load_tbody_file(&mut self, file_path: &Path, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_tbody_file(&mut self, file_path: &Path) {
+        self.clear();
+        self.tbody_viewer.queue_texture(file_path, None, None);
+        self.tbody_viewer.load_all();
+        self.loaded_textures = true;
+    }
+
+    pub fn compare_textures(&mut self, a: &Path, b: &Path, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
         self.clear();
-        self.tbody_viewer.load_texture(file_path, ctx)?;
+        self.tbody_viewer.compare_files(a, b, ctx)?;
         self.loaded_textures = true;
         Ok(())
     }
 
-    fn load_associated_textures(&mut self, ctx: &egui::Context) {
-        if let Some(mtb_file) = &self.mtb_file {
-            if let Some(base_path) = &self.base_path {
-                for texture_info in &mtb_file.textures {
-                    // ONLY search in the central textures folder
-                    let textures_path = base_path.parent()
-                        .and_then(|p| p.parent())
-                        .map(|assets_dir| assets_dir.join("textures").join(&texture_info.tbody_filename))
-                        .unwrap_or_default();
-                    
-                    if textures_path.exists() {
-                        if let Ok(()) = self.tbody_viewer.load_texture(&textures_path, ctx) {
-                            println!("Loaded texture: {} from {}", texture_info.tbody_filename, textures_path.display());
-                        } else {
-                            println!("Failed to load texture: {}", texture_info.tbody_filename);
-                        }
-                    } else {
-                        println!("Texture not found in textures folder: {}", texture_info.tbody_filename);
-                    }
+    // Textures queued by the last load, for the app to decode on the task manager
+    pub fn take_decode_requests(&mut self) -> Vec<DecodeRequest> {
+        self.tbody_viewer.take_decode_requests()
+    }
+
+    // Bindings are keyed by the name as the MTB spelled it, which may differ in case between files
+    fn binding(&self, tbody_filename: &str) -> Option<&PathBuf> {
+        let reference = VirtualPath::new(tbody_filename);
+        self.bindings.iter()
+            .find(|(name, _)| VirtualPath::new(name) == reference)
+            .map(|(_, path)| path)
+    }
+
+    // Search order: configured paths, the MTB's folder, assets/textures, then anywhere in the source archive
+    fn resolve_texture(&self, base_path: &Path, tbody_filename: &str) -> Option<(PathBuf, TextureSource)> {
+        if let Some(bound) = self.binding(tbody_filename).filter(|p| p.is_file()) {
+            return Some((bound.clone(), TextureSource::Bound));
+        }
+
+        for dir in &self.search_paths {
+            if let Some(candidate) = paths::resolve_in(dir, tbody_filename) {
+                return Some((candidate, TextureSource::Configured));
+            }
+        }
+
+        if let Some(candidate) = paths::resolve_in(base_path, tbody_filename) {
+            return Some((candidate, TextureSource::SameFolder));
+        }
+
+        let global = base_path.parent()
+            .and_then(|p| p.parent())
+            .and_then(|assets_dir| paths::resolve_in(assets_dir, &format!("textures/{}", tbody_filename)));
+        if let Some(candidate) = global {
+            return Some((candidate, TextureSource::GlobalTextures));
+        }
+
+        let archive_root = self.archive_root.as_ref()?;
+        walkdir::WalkDir::new(archive_root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_type().is_file() && VirtualPath::from_path(e.path()).ends_with(&VirtualPath::new(tbody_filename)))
+            .map(|e| (e.into_path(), TextureSource::ArchiveSibling))
+    }
+
+    // Textures of another MTB looked up with this viewer's search settings, without loading it
+    pub fn resolve_material(&self, mtb_path: &Path) -> Result<Vec<(MtbTextureInfo, Option<PathBuf>)>, Box<dyn std::error::Error>> {
+        let mtb_file = MtbFile::load_from_file(mtb_path, &self.params)?;
+        let base_path = mtb_path.parent().unwrap_or(Path::new("."));
+        Ok(mtb_file.textures
+            .into_iter()
+            .map(|texture_info| {
+                let path = self.resolve_texture(base_path, &texture_info.tbody_filename).map(|(path, _)| path);
+                (texture_info, path)
+            })
+            .collect())
+    }
+
+    fn load_associated_textures(&mut self) {
+        let (Some(mtb_file), Some(base_path)) = (self.mtb_file.clone(), self.base_path.clone()) else {
+            return;
+        };
+
+        let group = mtb_file.file_path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for texture_info in &mtb_file.textures {
+            match self.resolve_texture(&base_path, &texture_info.tbody_filename) {
+                Some((texture_path, source)) => {
+                    // Bound files may be named differently, keep the name the MTB asks for
+                    self.tbody_viewer.queue_texture(&texture_path, Some(texture_info.tbody_filename.clone()), Some(group.clone()));
+                    println!("Queued texture: {} from {} ({})", texture_info.tbody_filename, texture_path.display(), source.as_str());
+                    self.resolved.insert(texture_info.tbody_filename.clone(), (texture_path, source));
                 }
-                self.loaded_textures = true;
+                None => println!("Texture not found in any search path: {}", texture_info.tbody_filename),
+            }
+        }
+        self.loaded_textures = true;
+    }
+
+    // Bindings that could not be loaded from disk, usually because they point inside an archive
+    pub fn pending_bindings(&self) -> Vec<(String, PathBuf)> {
+        let Some(mtb_file) = &self.mtb_file else {
+            return Vec::new();
+        };
+        mtb_file.textures
+            .iter()
+            .filter(|t| !self.resolved.contains_key(&t.tbody_filename))
+            .filter_map(|t| self.binding(&t.tbody_filename).map(|p| (t.tbody_filename.clone(), p.clone())))
+            .collect()
+    }
+
+    // The texture the user picked from the suggestions, for the app to load and remember
+    pub fn take_bind_request(&mut self) -> Option<(String, PathBuf)> {
+        self.bind_request.take()
+    }
+
+    pub fn bind_texture_data(&mut self, tbody_filename: &str, source_path: &Path, data: &[u8], ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
+        let mut texture = TbodyTexture::load_from_bytes(data, source_path, ctx)?;
+        texture.name = tbody_filename.to_string();
+        texture.group = self.mtb_file.as_ref()
+            .and_then(|m| m.file_path.file_name())
+            .map(|n| n.to_string_lossy().into_owned());
+        self.tbody_viewer.textures.retain(|t| t.name != tbody_filename);
+        self.tbody_viewer.textures.push(texture);
+        self.bindings.insert(tbody_filename.to_string(), source_path.to_path_buf());
+        self.resolved.insert(tbody_filename.to_string(), (source_path.to_path_buf(), TextureSource::Bound));
+        println!("Bound texture {} to {}", tbody_filename, source_path.display());
+        Ok(())
+    }
+
+    // Slot from MATP when known, otherwise a guess: blue-dominant textures are treated as normal maps
+    fn slot_name(slot: Option<&str>, texture: &TbodyTexture, index: usize) -> String {
+        if let Some(slot) = slot {
+            return slot.to_lowercase();
+        }
+        let mean = TextureStats::compute(&texture.image).mean;
+        let looks_normal = mean[2] > 180.0 && (mean[0] - 128.0).abs() < 40.0 && (mean[1] - 128.0).abs() < 40.0;
+        if looks_normal {
+            "normal".to_string()
+        } else if index == 0 {
+            "diffuse".to_string()
+        } else {
+            format!("texture{}", index)
+        }
+    }
+
+    // Writes every loaded texture of the MTB, named by the material texture template (<mtb>_<slot>.png
+    // by default), plus a list mapping names back to hashes
+    pub fn export_named_set(&self, output_dir: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let mtb_file = self.mtb_file.as_ref().ok_or("No MTB file loaded")?;
+        let stem = mtb_file.file_path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "material".to_string());
+
+        std::fs::create_dir_all(output_dir)?;
+        let mut used_names: HashMap<PathBuf, usize> = HashMap::new();
+        let mut listing = String::new();
+        let mut exported = 0;
+        for (index, texture_info) in mtb_file.textures.iter().enumerate() {
+            let Some(texture) = self.tbody_viewer.textures.iter().find(|t| t.name == texture_info.tbody_filename) else {
+                listing.push_str(&format!("(missing)\t{}\n", texture_info.tbody_filename));
+                continue;
+            };
+
+            let slot = Self::slot_name(texture_info.slot.as_deref(), texture, index);
+            let relative = self.naming.material_texture(&mtb_file.file_path, &texture_info.tbody_filename, &slot, texture.dimensions);
+            let relative = naming::unique(relative, &mut used_names);
+
+            let png_path = output_dir.join(&relative);
+            if let Some(parent) = png_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            texture.save_png(&png_path)?;
+            listing.push_str(&format!("{}\t{}\n", naming::to_uri(&relative), texture_info.tbody_filename));
+            exported += 1;
+        }
+
+        std::fs::write(output_dir.join(format!("{}_textures.txt", stem)), listing)?;
+        println!("Exported {} textures from {} to {}", exported, mtb_file.file_path.display(), output_dir.display());
+        Ok(exported)
+    }
+
+    pub fn take_detach_request(&mut self) -> Option<TbodyTexture> {
+        self.tbody_viewer.take_detach_request()
+    }
+
+    pub fn mtb_path(&self) -> Option<&Path> {
+        self.mtb_file.as_ref().map(|mtb_file| mtb_file.file_path.as_path())
+    }
+
+    // Loads every texture and exports the set once they have all decoded
+    pub fn export_when_ready(&mut self, output_dir: PathBuf) {
+        self.tbody_viewer.load_all();
+        self.pending_export = Some(output_dir);
+    }
+
+    pub fn take_finished_export(&mut self) -> Option<(PathBuf, PathBuf, String)> {
+        self.finished_export.take()
+    }
+
+    fn run_pending_export(&mut self) {
+        if self.tbody_viewer.is_decoding() {
+            return;
+        }
+        let (Some(output_dir), Some(mtb_path)) = (self.pending_export.take(), self.mtb_path().map(Path::to_path_buf)) else {
+            return;
+        };
+        let status = match self.export_named_set(&output_dir) {
+            Ok(count) => format!("Exported {} textures to {}", count, output_dir.display()),
+            Err(e) => format!("Export failed: {}", e),
+        };
+        self.export_status = Some(status.clone());
+        self.finished_export = Some((mtb_path, output_dir, status));
+    }
+
+    // Exports the texture to PNG, opens it in the default editor and watches it for changes
+    fn edit_externally(&mut self, index: usize) {
+        let Some(texture) = self.tbody_viewer.textures.get(index) else {
+            return;
+        };
+        if !texture.file_path.is_file() {
+            self.export_status = Some(format!("{} is not a file on disk and can't be reinjected", texture.file_path.display()));
+            return;
+        }
+
+        let default_name = Path::new(&texture.name).with_extension("png");
+        let Some(png_path) = rfd::FileDialog::new()
+            .set_title("Export texture for editing")
+            .set_file_name(default_name.to_string_lossy())
+            .add_filter("PNG", &["png"])
+            .save_file()
+            .and_then(|p| self.write_guard.resolve(&p)) else {
+            return;
+        };
+
+        // Reinjecting writes the .tbody, so a protected original is edited as a workspace copy
+        let Some(tbody_path) = self.write_guard.resolve(&texture.file_path) else {
+            return;
+        };
+        if tbody_path != texture.file_path {
+            if let Err(e) = std::fs::copy(&texture.file_path, &tbody_path) {
+                self.export_status = Some(format!("Failed to copy {} to the workspace: {}", texture.file_path.display(), e));
+                return;
             }
         }
+
+        if let Err(e) = texture.save_png(&png_path) {
+            self.export_status = Some(format!("Export failed: {}", e));
+            return;
+        }
+        if let Err(e) = shell::open_in_default_app(&png_path) {
+            eprintln!("Failed to open {}: {}", png_path.display(), e);
+        }
+        self.tbody_viewer.textures[index].file_path = tbody_path.clone();
+        self.watcher.add(png_path, tbody_path);
+    }
+
+    // Reinjects watched PNGs that changed and refreshes any texture showing them
+    pub fn poll_watches(&mut self, ctx: &egui::Context) {
+        for path in self.watcher.poll(ctx) {
+            self.tbody_viewer.reload_texture(&path, ctx);
+        }
+    }
+
+    pub fn texture_view_settings(&self) -> TextureViewSettings {
+        self.tbody_viewer.view_settings()
+    }
+
+    pub fn apply_texture_view_settings(&mut self, settings: &TextureViewSettings) {
+        self.tbody_viewer.apply_view_settings(settings);
     }
 
     pub fn clear(&mut self) {
@@ -70,6 +415,11 @@ impl MtbViewer {
         self.tbody_viewer.clear();
         self.base_path = None;
         self.loaded_textures = false;
+        self.resolved.clear();
+        self.matches.lock().unwrap().clear();
+        self.bind_request = None;
+        self.export_status = None;
+        self.pending_export = None;
     }
 
     pub fn has_content(&self) -> bool {
@@ -82,9 +432,33 @@ impl MtbViewer {
             return;
         }
 
+        self.run_pending_export();
+        self.tbody_viewer.selection = self.selection.clone();
+
         // Show MTB file information if available
         if let Some(mtb_file) = &self.mtb_file {
-            ui.heading("MTB Texture Links");
+            let mut export = false;
+            ui.horizontal(|ui| {
+                ui.heading("MTB Texture Links");
+                let ready = !self.tbody_viewer.textures.is_empty() && !self.tbody_viewer.is_decoding();
+                export = ui.add_enabled(ready, egui::Button::new("Export as PNG set..."))
+                    .on_hover_text("Save each texture named by the material texture template in Options")
+                    .on_disabled_hover_text("Load all textures first")
+                    .clicked();
+            });
+            if export {
+                let output_dir = rfd::FileDialog::new()
+                    .set_title("Export textures to")
+                    .pick_folder()
+                    .and_then(|p| self.write_guard.resolve(&p));
+                if let Some(output_dir) = output_dir {
+                    self.pending_export = Some(output_dir);
+                    ui.ctx().request_repaint();
+                }
+            }
+            if let Some(status) = &self.export_status {
+                ui.label(status);
+            }
             ui.separator();
             
             ui.label(format!("File: {}", mtb_file.file_path.display()));
@@ -108,11 +482,56 @@ impl MtbViewer {
                         ui.colored_label(egui::Color32::RED, "Missing");
                     }
                 });
-                
-                // Show search info for missing textures
-                if !is_loaded {
-                    ui.indent("missing_texture_info", |ui| {
-                        ui.label("Expected location: assets/textures/");
+
+                if let Some((path, source)) = self.resolved.get(&texture_info.tbody_filename) {
+                    ui.indent(("resolved_texture_info", &texture_info.tbody_filename), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.small(format!("From {}:", source.as_str()));
+                            let current = self.selection.is_current(path);
+                            if ui.selectable_label(current, egui::RichText::new(path.display().to_string()).small())
+                                .on_hover_text("Show in the file tree")
+                                .clicked()
+                            {
+                                self.selection.publish(path);
+                            }
+                        });
+                    });
+                } else if !is_loaded {
+                    // Show search info for missing textures
+                    ui.indent(("missing_texture_info", &texture_info.tbody_filename), |ui| {
+                        ui.label(format!(
+                            "Searched {} configured path(s), the MTB folder, assets/textures/{}",
+                            self.search_paths.len(),
+                            if self.archive_root.is_some() { " and the source archive" } else { "" }
+                        ));
+
+                        let matches = self.matches.lock().unwrap().get(&texture_info.tbody_filename).cloned();
+                        match matches {
+                            None => {
+                                if ui.small_button("Find similar files...").clicked() {
+                                    self.match_request = Some(texture_info.tbody_filename.clone());
+                                }
+                            }
+                            Some(matches) if matches.is_empty() => {
+                                ui.small("No files with a matching hash prefix were found");
+                            }
+                            Some(matches) => {
+                                egui::CollapsingHeader::new(format!("{} suggestions", matches.len()))
+                                    .id_source(("texture_matches", &texture_info.tbody_filename))
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        for candidate in &matches {
+                                            ui.horizontal(|ui| {
+                                                if ui.small_button("Bind").clicked() {
+                                                    self.bind_request = Some((texture_info.tbody_filename.clone(), candidate.path.clone()));
+                                                }
+                                                ui.small(format!("{} chars", candidate.score));
+                                                ui.monospace(candidate.path.display().to_string());
+                                            });
+                                        }
+                                    });
+                            }
+                        }
                     });
                 }
             }
@@ -120,14 +539,29 @@ impl MtbViewer {
             ui.separator();
         }
 
+        if !self.watcher.watches.is_empty() {
+            let updated = egui::CollapsingHeader::new(format!("Watched for edits ({})", self.watcher.watches.len()))
+                .default_open(true)
+                .show(ui, |ui| self.watcher.show_ui(ui))
+                .body_returned
+                .unwrap_or_default();
+            for path in updated {
+                self.tbody_viewer.reload_texture(&path, ui.ctx());
+            }
+            ui.separator();
+        }
+
         // Show textures
-        if !self.tbody_viewer.textures.is_empty() {
+        if !self.tbody_viewer.textures.is_empty() || self.tbody_viewer.is_decoding() {
             if self.mtb_file.is_some() {
                 ui.heading("Loaded Textures");
             }
             self.tbody_viewer.show_ui(ui, available_size);
+            if let Some(index) = self.tbody_viewer.take_edit_request() {
+                self.edit_externally(index);
+            }
         } else if self.loaded_textures {
-            ui.label("No textures could be loaded. Make sure TBODY files are available in assets/textures/ folder.");
+            ui.label("No textures could be loaded. Make sure TBODY files are available in assets/textures/ or add a texture search path in Options.");
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// File names exporters give what they write. `{variable}`s are filled in per file and `/` makes
+// folders below the one picked for the export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingTemplates {
+    // Textures converted on their own, like a job's ConvertTextures step
+    pub texture: String,
+    // Textures exported with a model or scene, placed relative to its .gltf
+    pub material_texture: String,
+    pub model: String,
+    pub scene: String,
+}
+
+impl Default for NamingTemplates {
+    fn default() -> Self {
+        Self {
+            texture: "{folder}/{name}.png".to_string(),
+            material_texture: "{name}_{slot}.png".to_string(),
+            model: "{name}.gltf".to_string(),
+            scene: "{name}.gltf".to_string(),
+        }
+    }
+}
+
+// Shown next to the templates in Options
+pub const VARIABLES: &[(&str, &str)] = &[
+    ("game", "selected game, as job files name it"),
+    ("folder", "folder of the source file"),
+    ("name", "source file name without extension; the model's for its textures"),
+    ("slot", "material slot (diffuse, normal, ...)"),
+    ("texture", "texture file name without extension"),
+    ("width", "texture width"),
+    ("height", "texture height"),
+    ("lod", "level of detail from a _lodN name suffix, 0 otherwise"),
+];
+
+// Values that come from file names mustn't add folders of their own
+fn sanitize(value: &str) -> String {
+    value.chars().map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c }).collect()
+}
+
+// Replaces each known `{variable}`; unknown ones are left as written so typos show in the output.
+// `folder` may hold several levels and is the only value allowed to. `..` and empty parts are
+// dropped so nothing lands outside the export folder, and `extension` is added when missing.
+pub fn expand(template: &str, vars: &[(&str, String)], extension: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let key = &after[..end];
+            vars.iter().find(|(name, _)| *name == key).map(|(name, value)| {
+                let value = if *name == "folder" { value.replace('\\', "/") } else { sanitize(value) };
+                (value, end)
+            })
+        });
+        match value {
+            Some((value, end)) => {
+                expanded.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+
+    let mut path: PathBuf = expanded
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .collect();
+    if path.as_os_str().is_empty() {
+        path = PathBuf::from("unnamed");
+    }
+    if !path.extension().map_or(false, |e| e.eq_ignore_ascii_case(extension)) {
+        let mut name = path.into_os_string();
+        name.push(".");
+        name.push(extension);
+        path = PathBuf::from(name);
+    }
+    path
+}
+
+pub fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+pub fn folder_name(path: &Path) -> String {
+    path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+// "rock_lod2" is LOD 2; the games don't store it anywhere else
+pub fn lod_from_name(name: &str) -> u32 {
+    let lower = name.to_lowercase();
+    lower.rfind("_lod").and_then(|index| lower[index + 4..].parse().ok()).unwrap_or(0)
+}
+
+// Forward-slash form of a relative path, as glTF URIs and listings want it
+pub fn to_uri(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect::<Vec<_>>().join("/")
+}
+
+// Numbers repeats of a name the way the texture exports always have: slot, slot2, slot3...
+pub fn unique(path: PathBuf, used: &mut HashMap<PathBuf, usize>) -> PathBuf {
+    let count = used.entry(path.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        return path;
+    }
+    let extension = path.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}{}.{}", file_stem(&path), count, extension))
+}
+
+// The templates along with what they need from the app, handed to the exporters
+#[derive(Debug, Clone, Default)]
+pub struct Naming {
+    pub templates: NamingTemplates,
+    pub game: String,
+}
+
+impl Naming {
+    fn vars(&self, folder: String, name: String) -> Vec<(&'static str, String)> {
+        vec![("game", self.game.clone()), ("folder", folder), ("lod", lod_from_name(&name).to_string()), ("name", name)]
+    }
+
+    // `relative` is the source's path below the folder being converted, which `{folder}` mirrors
+    pub fn texture(&self, relative: &Path, (width, height): (u32, u32)) -> PathBuf {
+        let folder = relative.parent().map(to_uri).unwrap_or_default();
+        let mut vars = self.vars(folder, file_stem(relative));
+        vars.extend([("texture", file_stem(relative)), ("width", width.to_string()), ("height", height.to_string())]);
+        expand(&self.templates.texture, &vars, "png")
+    }
+
+    // A texture of `owner`'s material (the model, or the MTB itself), relative to the owner's export
+    pub fn material_texture(&self, owner: &Path, texture: &str, slot: &str, (width, height): (u32, u32)) -> PathBuf {
+        let mut vars = self.vars(folder_name(owner), file_stem(owner));
+        vars.extend([
+            ("texture", file_stem(Path::new(texture))),
+            ("slot", slot.to_string()),
+            ("width", width.to_string()),
+            ("height", height.to_string()),
+        ]);
+        expand(&self.templates.material_texture, &vars, "png")
+    }
+
+    pub fn model(&self, source: &Path) -> PathBuf {
+        expand(&self.templates.model, &self.vars(folder_name(source), file_stem(source)), "gltf")
+    }
+
+    pub fn scene(&self, source: &Path) -> PathBuf {
+        expand(&self.templates.scene, &self.vars(folder_name(source), file_stem(source)), "gltf")
+    }
+}
@@ -0,0 +1,183 @@
+use super::read_scene::{ContainerData, Data};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+const MAX_SAMPLES: usize = 5;
+const MAX_SAMPLE_LEN: usize = 64;
+
+// Everything seen for one key path, e.g. "Meshes/[]/Name"
+#[derive(Debug, Default, Serialize)]
+pub struct KeyStats {
+    pub occurrences: usize,
+    pub files: usize,
+    pub value_types: BTreeMap<String, usize>,
+    pub int_range: Option<(i32, i32)>,
+    pub float_range: Option<(f32, f32)>,
+    pub vec_len_range: Option<(usize, usize)>,
+    pub samples: BTreeSet<String>,
+    #[serde(skip)]
+    last_file: usize,
+}
+
+impl KeyStats {
+    fn add_int(&mut self, value: i32) {
+        let (min, max) = self.int_range.unwrap_or((value, value));
+        self.int_range = Some((min.min(value), max.max(value)));
+    }
+
+    fn add_float(&mut self, value: f32) {
+        if value.is_nan() {
+            return;
+        }
+        let (min, max) = self.float_range.unwrap_or((value, value));
+        self.float_range = Some((min.min(value), max.max(value)));
+    }
+
+    fn add_len(&mut self, len: usize) {
+        let (min, max) = self.vec_len_range.unwrap_or((len, len));
+        self.vec_len_range = Some((min.min(len), max.max(len)));
+    }
+
+    fn add_sample(&mut self, sample: &str) {
+        if self.samples.len() < MAX_SAMPLES {
+            self.samples.insert(sample.chars().take(MAX_SAMPLE_LEN).collect());
+        }
+    }
+}
+
+// Aggregated key names, value types and ranges across many parsed OCT files
+#[derive(Debug, Default, Serialize)]
+pub struct SchemaReport {
+    pub file_count: usize,
+    pub keys: BTreeMap<String, KeyStats>,
+}
+
+impl SchemaReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_scene(&mut self, scene: &IndexMap<String, ContainerData>) {
+        self.file_count += 1;
+        self.visit_container("", scene);
+    }
+
+    fn visit_container(&mut self, prefix: &str, children: &IndexMap<String, ContainerData>) {
+        for (key, value) in children {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}/{}", prefix, key)
+            };
+
+            match value {
+                ContainerData::Single(data) => self.visit_data(&path, data),
+                ContainerData::Multiple(items) => {
+                    // Repeated keys collapse into one "[]" path so list items share a schema
+                    self.stats_for(&path).add_len(items.len());
+                    let item_path = format!("{}/[]", path);
+                    for item in items {
+                        self.visit_data(&item_path, item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn stats_for(&mut self, path: &str) -> &mut KeyStats {
+        let file = self.file_count;
+        let stats = self.keys.entry(path.to_string()).or_default();
+        if stats.last_file != file {
+            stats.last_file = file;
+            stats.files += 1;
+        }
+        stats
+    }
+
+    fn visit_data(&mut self, path: &str, data: &Data) {
+        let stats = self.stats_for(path);
+        stats.occurrences += 1;
+
+        let type_name = match data {
+            Data::Container(_) => "Container",
+            Data::Binary(_) => "Binary",
+            Data::Uuid(_) => "Uuid",
+            Data::Int(_) => "Int",
+            Data::IntVec(_) => "IntVec",
+            Data::Float(_) => "Float",
+            Data::FloatVec(_) => "FloatVec",
+            Data::String(_) => "String",
+            Data::StringVec(_) => "StringVec",
+        };
+        *stats.value_types.entry(type_name.to_string()).or_insert(0) += 1;
+
+        match data {
+            Data::Container(children) => self.visit_container(path, children),
+            Data::Binary(bytes) => stats.add_len(bytes.len()),
+            Data::Uuid(uuid) => stats.add_sample(&uuid.to_string()),
+            Data::Int(value) => stats.add_int(*value),
+            Data::IntVec(values) => {
+                stats.add_len(values.len());
+                values.iter().for_each(|v| stats.add_int(*v));
+            }
+            Data::Float(value) => stats.add_float(*value),
+            Data::FloatVec(values) => {
+                stats.add_len(values.len());
+                values.iter().for_each(|v| stats.add_float(*v));
+            }
+            Data::String(value) => stats.add_sample(value),
+            Data::StringVec(values) => {
+                stats.add_len(values.len());
+                values.iter().for_each(|v| stats.add_sample(v));
+            }
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# OCT schema report\n\n");
+        out.push_str(&format!("{} files analyzed, {} key paths\n\n", self.file_count, self.keys.len()));
+        out.push_str("| Key path | Files | Count | Types | Range | Length | Samples |\n");
+        out.push_str("|---|---|---|---|---|---|---|\n");
+
+        for (path, stats) in &self.keys {
+            let types = stats.value_types
+                .iter()
+                .map(|(name, count)| format!("{} ({})", name, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let mut ranges = Vec::new();
+            if let Some((min, max)) = stats.int_range {
+                ranges.push(format!("{}..{}", min, max));
+            }
+            if let Some((min, max)) = stats.float_range {
+                ranges.push(format!("{:.3}..{:.3}", min, max));
+            }
+
+            let length = stats.vec_len_range
+                .map(|(min, max)| if min == max { min.to_string() } else { format!("{}..{}", min, max) })
+                .unwrap_or_default();
+
+            let samples = stats.samples
+                .iter()
+                .map(|s| format!("`{}`", s.replace('|', "\\|").replace('`', "'")))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} | {} | {} |\n",
+                path,
+                stats.files,
+                stats.occurrences,
+                types,
+                ranges.join(", "),
+                length,
+                samples
+            ));
+        }
+
+        out
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::{Component, Path, PathBuf};
+
+// Windows rejects paths past MAX_PATH (260) unless they use the \\?\ prefix. Stay a bit
+// under it since some APIs append to the path themselves.
+#[cfg(windows)]
+const LONG_PATH_THRESHOLD: usize = 240;
+
+// Returns a path the filesystem APIs accept regardless of length. On Windows long paths are made
+// absolute and verbatim (\\?\C:\... or \\?\UNC\server\share\...); elsewhere the path is unchanged.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::ffi::{OsStr, OsString};
+    use std::path::Prefix;
+
+    if path.as_os_str().len() < LONG_PATH_THRESHOLD {
+        return path.to_path_buf();
+    }
+
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(dir) => dir.join(path),
+            Err(_) => return path.to_path_buf(),
+        }
+    };
+
+    let mut components = absolute.components();
+    let mut verbatim = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) => OsString::from(format!(r"\\?\{}:", letter as char)),
+            Prefix::UNC(server, share) => {
+                let mut result = OsString::from(r"\\?\UNC\");
+                result.push(server);
+                result.push(r"\");
+                result.push(share);
+                result
+            }
+            // Verbatim and device paths already bypass the limit
+            _ => return absolute,
+        },
+        _ => return absolute,
+    };
+
+    // Verbatim paths skip normalization, so . and .. have to be resolved here
+    let mut parts: Vec<&OsStr> = Vec::new();
+    for component in components {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part),
+            _ => {}
+        }
+    }
+    for part in parts {
+        verbatim.push(r"\");
+        verbatim.push(part);
+    }
+    PathBuf::from(verbatim)
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// Joins an archive entry name onto `root`. Entry names use either separator depending on the
+// tool that built the archive. Only plain names are kept: ".", "..", drive letters and anything
+// else push would treat as a root are dropped so nothing escapes `root`.
+pub fn archive_entry_path(root: &Path, entry_name: &str) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for part in entry_name.split(['/', '\\']) {
+        let mut components = Path::new(part).components();
+        let plain = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+        if !plain || part.contains(':') {
+            continue;
+        }
+        path.push(part);
+    }
+    path
+}
+
+// `path` with "." and ".." worked out and the part that exists resolved by the OS, so two
+// spellings of the same folder compare equal. Windows paths are also lowercased.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut lexical = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                lexical.pop();
+            }
+            other => lexical.push(other),
+        }
+    }
+    // Canonicalize the deepest folder that exists and keep the rest as written
+    let mut existing = lexical.as_path();
+    let mut rest = Vec::new();
+    let mut normalized = loop {
+        if let Ok(mut canonical) = std::fs::canonicalize(existing) {
+            canonical.extend(rest.iter().rev());
+            break canonical;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break lexical.clone(),
+        }
+    };
+    if cfg!(windows) {
+        normalized = PathBuf::from(normalized.to_string_lossy().to_lowercase());
+    }
+    normalized
+}
+
+// Whether `path` is `root` or below it, however either is spelled
+pub fn is_within(path: &Path, root: &Path) -> bool {
+    normalize(path).starts_with(normalize(root))
+}
+
+// Name shown in the UI; non-UTF-8 names are shown lossily instead of being dropped
+pub fn display_name(path: &Path) -> String {
+    match path.components().next_back() {
+        Some(Component::Normal(name)) => name.to_string_lossy().into_owned(),
+        _ => path.to_string_lossy().into_owned(),
+    }
+}
+
+// Writes `contents` to `path`, creating missing parent folders. Errors name the path so a
+// failing deep extraction says which file it was on.
+pub fn write_creating_dirs(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let target = long_path(path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", parent.display(), e)))?;
+    }
+    std::fs::write(&target, contents)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+// Game data refers to files with whatever casing and separators the original tools used, so
+// references are compared in a normalized form: lowercase, '/'-separated, no empty or "." parts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VirtualPath(String);
+
+impl VirtualPath {
+    pub fn new(reference: &str) -> Self {
+        let parts: Vec<String> = reference
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(|part| part.to_lowercase())
+            .collect();
+        Self(parts.join("/"))
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        Self::new(&path.to_string_lossy())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // Whole-component suffix match, so "textures/a.tbody" matches ".../Textures/A.TBODY" but not "xa.tbody"
+    pub fn ends_with(&self, other: &VirtualPath) -> bool {
+        self.0 == other.0
+            || (self.0.ends_with(&other.0) && self.0.as_bytes()[self.0.len() - other.0.len() - 1] == b'/')
+    }
+}
+
+// Finds `reference` below `dir` on disk, matching every component case-insensitively.
+// Exact matches are tried first so case-insensitive filesystems never list a folder.
+pub fn resolve_in(dir: &Path, reference: &str) -> Option<PathBuf> {
+    let mut current = dir.to_path_buf();
+    for part in reference.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".") {
+        if part == ".." {
+            current.pop();
+            continue;
+        }
+
+        let exact = current.join(part);
+        if long_path(&exact).exists() {
+            current = exact;
+            continue;
+        }
+
+        let wanted = part.to_lowercase();
+        let entry = std::fs::read_dir(long_path(&current))
+            .ok()?
+            .flatten()
+            .find(|entry| entry.file_name().to_string_lossy().to_lowercase() == wanted)?;
+        current.push(entry.file_name());
+    }
+    long_path(&current).exists().then_some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_entry_path_stays_under_root() {
+        let root = Path::new("out");
+        assert_eq!(archive_entry_path(root, "a/b.oct"), root.join("a").join("b.oct"));
+        assert_eq!(archive_entry_path(root, "a\\b.oct"), root.join("a").join("b.oct"));
+        assert_eq!(archive_entry_path(root, "C:"), root);
+        assert_eq!(archive_entry_path(root, "C:/x"), root.join("x"));
+        assert_eq!(archive_entry_path(root, "C:x"), root);
+        assert_eq!(archive_entry_path(root, "\\\\server\\x"), root.join("server").join("x"));
+        assert_eq!(archive_entry_path(root, "/abs"), root.join("abs"));
+        assert_eq!(archive_entry_path(root, "a/../../b"), root.join("a").join("b"));
+        assert_eq!(archive_entry_path(root, "./a/./b"), root.join("a").join("b"));
+    }
+}
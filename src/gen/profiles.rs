@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+// Named profiles sit in this folder next to the config Tundra was started with. Each is a whole
+// config of its own, so game setups, ignore lists and layouts don't leak between them.
+pub const PROFILES_DIR: &str = "tundra_profiles";
+
+// Shown for the config Tundra was started with
+pub const DEFAULT_PROFILE: &str = "Default";
+
+pub fn profiles_dir(base_config: &Path) -> PathBuf {
+    base_config.parent().unwrap_or(Path::new("")).join(PROFILES_DIR)
+}
+
+pub fn profile_path(base_config: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        return base_config.to_path_buf();
+    }
+    profiles_dir(base_config).join(format!("{}.json", name))
+}
+
+// Default first, then the saved profiles by name
+pub fn list(base_config: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(profiles_dir(base_config))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |e| e.eq_ignore_ascii_case("json")))
+                .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+// Names become file names, so anything a file system would trip over is refused
+pub fn validate_name(name: &str, existing: &[String]) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Enter a name".to_string());
+    }
+    if name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']) || name.starts_with('.') {
+        return Err(format!("\"{}\" can't be used as a file name", name));
+    }
+    if existing.iter().any(|e| e.eq_ignore_ascii_case(name)) {
+        return Err(format!("There's already a profile called \"{}\"", name));
+    }
+    Ok(())
+}
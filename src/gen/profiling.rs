@@ -0,0 +1,158 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const MAX_RECORDS: usize = 500;
+// Anything slower than this is also written to the log
+const SLOW_THRESHOLD: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub struct TimingRecord {
+    pub category: &'static str,
+    pub label: String,
+    pub duration: Duration,
+    // Time since the app started, so startup steps line up
+    pub started_at: Duration,
+}
+
+struct Recorder {
+    app_start: Instant,
+    records: VecDeque<TimingRecord>,
+}
+
+// Timers run on the scan and task threads too, so the recorder is process-wide
+fn recorder() -> &'static Mutex<Recorder> {
+    static RECORDER: OnceLock<Mutex<Recorder>> = OnceLock::new();
+    RECORDER.get_or_init(|| Mutex::new(Recorder {
+        app_start: Instant::now(),
+        records: VecDeque::new(),
+    }))
+}
+
+pub fn record(category: &'static str, label: impl Into<String>, start: Instant) {
+    let duration = start.elapsed();
+    let label = label.into();
+    if duration >= SLOW_THRESHOLD {
+        println!("[timing] {} {}: {:.1} ms", category, label, duration.as_secs_f64() * 1000.0);
+    }
+
+    let mut recorder = recorder().lock().unwrap();
+    let started_at = start.saturating_duration_since(recorder.app_start);
+    recorder.records.push_back(TimingRecord { category, label, duration, started_at });
+    while recorder.records.len() > MAX_RECORDS {
+        recorder.records.pop_front();
+    }
+}
+
+// Records how long the enclosing scope took when dropped
+pub struct ScopedTimer {
+    category: &'static str,
+    label: String,
+    start: Instant,
+}
+
+impl ScopedTimer {
+    pub fn new(category: &'static str, label: impl Into<String>) -> Self {
+        // Touch the recorder so the first timer doesn't start before the app clock
+        let _ = recorder();
+        Self {
+            category,
+            label: label.into(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for ScopedTimer {
+    fn drop(&mut self) {
+        record(self.category, std::mem::take(&mut self.label), self.start);
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+// Plain-text listing of the slowest operations, for pasting into bug reports
+fn report(records: &[TimingRecord]) -> String {
+    let mut report = format!("Tundra {} timings\n", env!("CARGO_PKG_VERSION"));
+    for record in records {
+        report.push_str(&format!(
+            "{:>10}  {:<10} {} (at +{})\n",
+            format_duration(record.duration),
+            record.category,
+            record.label,
+            format_duration(record.started_at)
+        ));
+    }
+    report
+}
+
+pub struct DiagnosticsView {
+    category: Option<&'static str>,
+}
+
+impl DiagnosticsView {
+    pub fn new() -> Self {
+        Self { category: None }
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        let mut records: Vec<TimingRecord> = recorder().lock().unwrap().records.iter().cloned().collect();
+        let mut categories: Vec<&'static str> = records.iter().map(|r| r.category).collect();
+        categories.sort();
+        categories.dedup();
+
+        let startup: Duration = records.iter().filter(|r| r.category == "startup").map(|r| r.duration).sum();
+        if !startup.is_zero() {
+            ui.label(format!("Startup: {}", format_duration(startup)));
+        }
+
+        records.retain(|r| self.category.map_or(true, |c| r.category == c));
+        records.sort_by(|a, b| b.duration.cmp(&a.duration));
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("diagnostics_category")
+                .selected_text(self.category.unwrap_or("All operations"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.category, None, "All operations");
+                    for category in &categories {
+                        ui.selectable_value(&mut self.category, Some(*category), *category);
+                    }
+                });
+            if ui.button("Copy report").clicked() {
+                ui.output_mut(|o| o.copied_text = report(&records));
+            }
+            if ui.button("Clear").clicked() {
+                recorder().lock().unwrap().records.clear();
+            }
+        });
+        ui.separator();
+
+        if records.is_empty() {
+            ui.label("Nothing timed yet");
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("diagnostics_grid").striped(true).show(ui, |ui| {
+                ui.strong("Duration");
+                ui.strong("Category");
+                ui.strong("Operation");
+                ui.end_row();
+                for record in &records {
+                    let text = format_duration(record.duration);
+                    if record.duration >= SLOW_THRESHOLD {
+                        ui.colored_label(egui::Color32::from_rgb(230, 160, 60), text);
+                    } else {
+                        ui.label(text);
+                    }
+                    ui.label(record.category);
+                    ui.label(&record.label).on_hover_text(format!("Started {} after launch", format_duration(record.started_at)));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
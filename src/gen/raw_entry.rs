@@ -0,0 +1,51 @@
+use serde::Serialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use super::safe_write;
+
+// Header fields of an entry exported byte for byte, saved next to it so it can be decrypted,
+// decompressed or put back by hand
+#[derive(Debug, Clone, Serialize)]
+pub struct RawEntryHeader {
+    pub archive: String,
+    pub entry: String,
+    // Which reader the offsets come from: "Disney Infinity 3.0", "Cars 3" or "zip"
+    pub format: String,
+    pub compression_method: u16,
+    pub compression: String,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub crc32: String,
+    pub local_header_offset: u64,
+    pub data_offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_hash: Option<String>,
+    // What still has to be undone before the data can be decompressed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<String>,
+    // Hex, as stored in the central directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_field: Option<String>,
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// "mickey.tbody.raw" -> "mickey.tbody.raw.json"
+pub fn sidecar_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_os_string();
+    name.push(".json");
+    PathBuf::from(name)
+}
+
+pub fn write(output: &Path, data: &[u8], header: &RawEntryHeader) -> io::Result<()> {
+    if data.len() as u64 != header.compressed_size {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} bytes read, the header says {}", data.len(), header.compressed_size)));
+    }
+    let json = serde_json::to_string_pretty(header).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    safe_write::write(output, data)?;
+    safe_write::write(&sidecar_path(output), json.as_bytes())
+}
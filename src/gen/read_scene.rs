@@ -2,11 +2,13 @@ use anyhow::anyhow;
 use binrw::{BinRead, BinWrite, BinReaderExt, BinWriterExt, Endian, NullString};
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use modular_bitfield::prelude::*;
+use super::paths;
 
 // OCT Header structure
 #[derive(BinRead, BinWrite, Debug)]
@@ -154,6 +156,13 @@ pub struct AnimationData {
     pub animations: Vec<AnimationInfo>,
 }
 
+// A plottable keyframe curve, x is the key time (or key index when no times are stored)
+#[derive(Debug, Clone)]
+pub struct AnimationCurve {
+    pub name: String,
+    pub points: Vec<[f64; 2]>,
+}
+
 // Main OCT file handler
 pub struct SceneFileHandler {
     pub current_scene: Option<IndexMap<String, ContainerData>>,
@@ -161,8 +170,20 @@ pub struct SceneFileHandler {
     pub endian: Option<Endian>,
     pub animation_data: Option<AnimationData>,
     pub current_bent_path: Option<PathBuf>,
+    pub warnings: Vec<String>,
+    pub modified: bool,
+    animation_curves: Option<Vec<AnimationCurve>>,
+    root_node_id: String,
+    root_level: u8,
+    raw_header: Vec<u8>,
+    // Child keys in file order for containers whose repeated keys are interleaved (a, b, a), which
+    // the key map groups together; saving puts them back in this order
+    interleaved: HashMap<ScenePath, Vec<String>>,
 }
 
+// Location of a value inside the scene: keys, with an index for repeated keys
+pub type ScenePath = Vec<(String, Option<usize>)>;
+
 #[derive(Debug, Clone)]
 pub struct TextureInfo {
     pub name: String,
@@ -188,24 +209,42 @@ impl SceneFileHandler {
             endian: None,
             animation_data: None,
             current_bent_path: None,
+            warnings: Vec::new(),
+            modified: false,
+            animation_curves: None,
+            root_node_id: String::new(),
+            root_level: 0,
+            raw_header: Vec::new(),
+            interleaved: HashMap::new(),
         }
     }
 
+    // A scene that wasn't read from a file, e.g. a new-file template; saved with a blank header
+    pub fn from_scene(root_node_id: &str, scene: IndexMap<String, ContainerData>, endian: Endian) -> Self {
+        let mut handler = Self::new();
+        handler.root_node_id = root_node_id.to_string();
+        handler.current_scene = Some(scene);
+        handler.endian = Some(endian);
+        handler
+    }
+
     pub fn load_scene_file<R: Read + Seek>(&mut self, reader: &mut R) -> anyhow::Result<()> {
-        let mut magic: [u8; 8] = [0u8; 8];
-        reader.read_exact(&mut magic)?;
+        // Magic, header and 40 byte padding; kept so the scene can be written back
+        let mut raw_header = vec![0u8; 60];
+        reader.read_exact(&mut raw_header)?;
 
-        let endian = match magic {
+        let endian = match raw_header[..8] {
             [0x29, 0x76, 0x01, 0x45, 0xcd, 0xcc, 0x8c, 0x3f] => Endian::Little,
             [0x45, 0x01, 0x76, 0x29, 0x3f, 0x8c, 0xcc, 0xcd] => Endian::Big,
-            _ => return Err(anyhow!("Invalid magic: {magic:x?}")),
+            _ => return Err(anyhow!("Invalid magic: {:x?}", &raw_header[..8])),
         };
 
         self.endian = Some(endian);
-        let header: OctHeader = reader.read_type(endian)?;
-
-        // 40 byte padding
-        reader.seek(SeekFrom::Current(40))?;
+        self.warnings.clear();
+        self.modified = false;
+        self.animation_curves = None;
+        let header: OctHeader = Cursor::new(&raw_header[8..]).read_type(endian)?;
+        self.raw_header = raw_header;
 
         let start = reader.stream_position()?;
         let mut string_table = Vec::new();
@@ -222,28 +261,31 @@ impl SceneFileHandler {
         let mut root_node = node;
 
         while (reader.stream_position()? - start) < header.data_tree_size as u64 {
-            let RawNode { level, node } = reader.read_type_args(endian, string_table.as_slice())?;
-
-            let mut curr_level = root_level;
-            let mut curr_node = &mut root_node;
-
-            while curr_level < level {
-                curr_level += 1;
-                let nodes = if let NodeData::Container(children) = &mut curr_node.data {
-                    children
-                } else {
-                    return Err(anyhow!("Expected container"));
-                };
-
-                if curr_level == level {
-                    nodes.push(node);
+            let offset = reader.stream_position()?;
+            // Payload sizes of unknown nodes can't be derived, so a read error ends the tree here
+            let RawNode { level, node } = match reader.read_type_args(endian, string_table.as_slice()) {
+                Ok(raw_node) => raw_node,
+                Err(e) => {
+                    self.warnings.push(format!("Stopped parsing at 0x{:x}: {}", offset, e));
                     break;
-                } else {
-                    curr_node = nodes.last_mut().unwrap();
                 }
+            };
+
+            // A misplaced node has already been fully read, so it can be skipped safely
+            if let Err(e) = Self::insert_node(&mut root_node, root_level, level, node) {
+                self.warnings.push(format!("Skipped node at 0x{:x}: {}", offset, e));
             }
         }
 
+        for warning in &self.warnings {
+            eprintln!("OCT warning: {}", warning);
+        }
+
+        self.root_node_id = root_node.id.clone();
+        self.root_level = root_level;
+        self.interleaved.clear();
+        record_interleaved(&root_node, &mut Vec::new(), &mut self.interleaved);
+
         if let Data::Container(children) = root_node.data.try_into()? {
             self.current_scene = Some(children);
             Ok(())
@@ -252,6 +294,208 @@ impl SceneFileHandler {
         }
     }
 
+    fn insert_node(root_node: &mut Node, root_level: u8, level: u8, node: Node) -> anyhow::Result<()> {
+        let mut curr_level = root_level;
+        let mut curr_node = root_node;
+
+        while curr_level < level {
+            curr_level += 1;
+            let NodeData::Container(nodes) = &mut curr_node.data else {
+                return Err(anyhow!("Expected container for '{}' at level {}", node.id, level));
+            };
+
+            if curr_level == level {
+                nodes.push(node);
+                return Ok(());
+            }
+            curr_node = nodes
+                .last_mut()
+                .ok_or_else(|| anyhow!("Missing parent for '{}' at level {}", node.id, level))?;
+        }
+
+        Err(anyhow!("Node '{}' at level {} is not below the root level {}", node.id, level, root_level))
+    }
+
+    // Writes the current scene back out in its original byte order.
+    // The string table is rebuilt from the nodes, so its order may differ from the source file.
+    pub fn save_scene_file<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let scene = self.current_scene.as_ref().ok_or_else(|| anyhow!("No scene loaded"))?;
+        let endian = self.endian.ok_or_else(|| anyhow!("Unknown scene endianness"))?;
+
+        let mut root = Node {
+            id: self.root_node_id.clone(),
+            data: Data::Container(scene.clone()).into(),
+        };
+        restore_interleaved(&mut root, &mut Vec::new(), &self.interleaved)?;
+        let mut nodes = Vec::new();
+        flatten_nodes(root, self.root_level, &mut nodes);
+
+        let mut string_table: Vec<String> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut add_string = |string: &str| {
+            if seen.insert(string.to_string()) {
+                string_table.push(string.to_string());
+            }
+        };
+        for RawNode { node, .. } in &nodes {
+            match node.id.split_once('#') {
+                Some((key, name)) => {
+                    add_string(key);
+                    add_string(name);
+                }
+                None => add_string(&node.id),
+            }
+            match &node.data {
+                NodeData::String(string) => add_string(string),
+                NodeData::StringVec(strings) => strings.iter().for_each(|s| add_string(s)),
+                _ => {}
+            }
+        }
+
+        let mut string_bytes = Vec::new();
+        for string in &string_table {
+            string_bytes.extend_from_slice(string.as_bytes());
+            string_bytes.push(0);
+        }
+
+        let raw_header = if self.raw_header.len() == 60 { self.raw_header.clone() } else { vec![0u8; 60] };
+        let magic = match endian {
+            Endian::Little => Self::LITTLE_MAGIC,
+            Endian::Big => Self::BIG_MAGIC,
+        };
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_all(&magic)?;
+        writer.write_all(&raw_header[8..12])?;
+        writer.write_type(&(string_bytes.len() as u32), endian)?;
+        let tree_size_pos = writer.stream_position()?;
+        writer.write_type(&0u32, endian)?;
+        writer.write_all(&raw_header[20..60])?;
+        writer.write_all(&string_bytes)?;
+
+        let tree_start = writer.stream_position()?;
+        for node in &nodes {
+            writer.write_type_args(node, endian, string_table.as_slice())?;
+        }
+        let tree_end = writer.stream_position()?;
+
+        writer.seek(SeekFrom::Start(tree_size_pos))?;
+        writer.write_type(&((tree_end - tree_start) as u32), endian)?;
+
+        super::safe_write::write(path.as_ref(), &writer.into_inner())?;
+        println!("Saved scene with {} nodes to {}", nodes.len(), path.as_ref().display());
+        Ok(())
+    }
+
+    // Every binary payload in the scene with its size; UUIDs are excluded
+    pub fn binary_blobs(&self) -> Vec<(ScenePath, usize)> {
+        let mut blobs = Vec::new();
+        if let Some(scene) = &self.current_scene {
+            collect_binary_blobs(scene, &mut Vec::new(), &mut blobs);
+        }
+        blobs
+    }
+
+    pub fn binary_blob(&self, path: &ScenePath) -> Option<&[u8]> {
+        match find_data(self.current_scene.as_ref()?, path)? {
+            Data::Binary(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn replace_binary_blob(&mut self, path: &ScenePath, data: Vec<u8>) -> anyhow::Result<()> {
+        let scene = self.current_scene.as_mut().ok_or_else(|| anyhow!("No scene loaded"))?;
+        match find_data_mut(scene, path) {
+            Some(Data::Binary(blob)) => {
+                *blob = data;
+                self.modified = true;
+                Ok(())
+            }
+            _ => Err(anyhow!("No binary node at {}", scene_path_label(path))),
+        }
+    }
+
+    // Parses an in-memory OCT/BENT file and serializes its node tree as pretty JSON,
+    // along with any warnings raised while parsing
+    pub fn scene_to_json(data: &[u8]) -> anyhow::Result<(String, Vec<String>)> {
+        let mut handler = Self::new();
+        handler.load_scene_file(&mut Cursor::new(data))?;
+        let scene = handler.current_scene.ok_or_else(|| anyhow!("No scene data"))?;
+        Ok((serde_json::to_string_pretty(&scene)?, handler.warnings))
+    }
+
+    const LITTLE_MAGIC: [u8; 8] = [0x29, 0x76, 0x01, 0x45, 0xcd, 0xcc, 0x8c, 0x3f];
+    const BIG_MAGIC: [u8; 8] = [0x45, 0x01, 0x76, 0x29, 0x3f, 0x8c, 0xcc, 0xcd];
+
+    // Rewrites an OCT/BENT file for the other platform family and returns the new endianness.
+    // Nodes are converted one by one so their order and the string table stay untouched;
+    // binary payloads are copied as-is since their layout isn't known.
+    pub fn convert_endianness<P: AsRef<Path>, Q: AsRef<Path>>(input_path: P, output_path: Q) -> anyhow::Result<Endian> {
+        let data = fs::read(input_path)?;
+        let mut reader = Cursor::new(data.as_slice());
+
+        let mut magic: [u8; 8] = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+
+        let (endian, target, target_magic) = match magic {
+            Self::LITTLE_MAGIC => (Endian::Little, Endian::Big, Self::BIG_MAGIC),
+            Self::BIG_MAGIC => (Endian::Big, Endian::Little, Self::LITTLE_MAGIC),
+            _ => return Err(anyhow!("Invalid magic: {magic:x?}")),
+        };
+
+        let header: OctHeader = reader.read_type(endian)?;
+
+        // 40 byte padding
+        reader.seek(SeekFrom::Current(40))?;
+
+        let string_table_start = reader.stream_position()? as usize;
+        let mut string_table = Vec::new();
+        while (reader.stream_position()? as usize - string_table_start) < header.string_table_size as usize {
+            let null_string: NullString = reader.read_type(endian)?;
+            string_table.push(null_string.to_string());
+        }
+        let string_table_end = reader.stream_position()? as usize;
+
+        let tree_start = reader.stream_position()?;
+        let mut nodes = Vec::new();
+        while (reader.stream_position()? - tree_start) < header.data_tree_size as u64 {
+            let raw_node: RawNode = reader.read_type_args(endian, string_table.as_slice())?;
+            nodes.push(raw_node);
+        }
+        let tree_end = reader.stream_position()? as usize;
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_all(&target_magic)?;
+        // The word the header skips is unknown, but like every other header field it's a u32 in
+        // the file's byte order, so it gets swapped too
+        let unknown: u32 = Cursor::new(&data[8..12]).read_type(endian)?;
+        writer.write_type(&unknown, target)?;
+        writer.write_type(&header.string_table_size, target)?;
+        let tree_size_pos = writer.stream_position()?;
+        writer.write_type(&0u32, target)?;
+        writer.write_all(&data[20..60])?;
+
+        // Strings carry no byte order, copy the table verbatim
+        writer.write_all(&data[string_table_start..string_table_end])?;
+
+        let new_tree_start = writer.stream_position()?;
+        for node in &nodes {
+            writer.write_type_args(node, target, string_table.as_slice())?;
+        }
+        let new_tree_end = writer.stream_position()?;
+
+        // Keep anything trailing the data tree
+        writer.write_all(&data[tree_end..])?;
+
+        writer.seek(SeekFrom::Start(tree_size_pos))?;
+        writer.write_type(&((new_tree_end - new_tree_start) as u32), target)?;
+
+        super::safe_write::write(output_path.as_ref(), &writer.into_inner())?;
+        println!("Converted {} nodes from {:?} to {:?} endian: {}", nodes.len(), endian, target, output_path.as_ref().display());
+
+        Ok(target)
+    }
+
     pub fn load_bent_file<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
         let mut file = fs::File::open(&path)?;
         self.load_bent_file_reader(&mut file)?;
@@ -380,13 +624,8 @@ impl SceneFileHandler {
 
     pub fn find_corresponding_bent_file<P: AsRef<Path>>(oct_path: P) -> Option<PathBuf> {
         let oct_path = oct_path.as_ref();
-        let bent_path = oct_path.with_extension("bent");
-        
-        if bent_path.exists() {
-            Some(bent_path)
-        } else {
-            None
-        }
+        let bent_name = oct_path.with_extension("bent").file_name()?.to_string_lossy().into_owned();
+        paths::resolve_in(oct_path.parent()?, &bent_name)
     }
 
 pub fn extract_textures(&mut self, game_type: &GameType) -> anyhow::Result<()> {
@@ -433,8 +672,7 @@ pub fn extract_textures(&mut self, game_type: &GameType) -> anyhow::Result<()> {
                             Some(ContainerData::Single(Data::Binary(data))),
                         ) = (container.get(Self::PATH_KEY), container.get(Self::DATA_KEY))
                         {
-                            let out = output_path
-                                .join(path.replace('\\', std::path::MAIN_SEPARATOR_STR))
+                            let out = paths::archive_entry_path(output_path, path)
                                 .with_extension("dds");
                             
                             if let Some(parent) = out.parent() {
@@ -466,6 +704,18 @@ pub fn extract_textures(&mut self, game_type: &GameType) -> anyhow::Result<()> {
         self.current_scene.is_some()
     }
 
+    // Keyframe curves found in the loaded scene, resolved on first use
+    pub fn animation_curves(&mut self) -> &[AnimationCurve] {
+        if self.animation_curves.is_none() {
+            let mut curves = Vec::new();
+            if let Some(scene) = &self.current_scene {
+                collect_animation_curves(scene, "", false, &mut curves);
+            }
+            self.animation_curves = Some(curves);
+        }
+        self.animation_curves.as_deref().unwrap_or_default()
+    }
+
     pub fn has_animation_data(&self) -> bool {
         self.animation_data.is_some()
     }
@@ -476,10 +726,231 @@ pub fn extract_textures(&mut self, game_type: &GameType) -> anyhow::Result<()> {
 
     pub fn clear(&mut self) {
         self.current_scene = None;
+        self.interleaved.clear();
         self.extracted_textures.clear();
         self.endian = None;
         self.animation_data = None;
         self.current_bent_path = None;
+        self.warnings.clear();
+        self.modified = false;
+        self.animation_curves = None;
+    }
+}
+
+pub fn scene_path_label(path: &ScenePath) -> String {
+    path.iter()
+        .map(|(key, index)| match index {
+            Some(index) => format!("{}[{}]", key, index),
+            None => key.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Guesses what an embedded payload is from its magic bytes; returns a description and file extension
+pub fn guess_payload_type(data: &[u8]) -> (&'static str, &'static str) {
+    match data {
+        [b'D', b'D', b'S', b' ', ..] => ("DDS texture", "dds"),
+        [0x29, 0x76, 0x01, 0x45, 0xcd, 0xcc, 0x8c, 0x3f, ..]
+        | [0x45, 0x01, 0x76, 0x29, 0x3f, 0x8c, 0xcc, 0xcd, ..] => ("Nested OCT", "oct"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => ("WAV audio", "wav"),
+        [b'R', b'I', b'F', b'F', ..] | [b'R', b'I', b'F', b'X', ..] => ("RIFF container", "riff"),
+        [b'O', b'g', b'g', b'S', ..] => ("Ogg audio", "ogg"),
+        [b'F', b'S', b'B', b'4' | b'5', ..] => ("FMOD sound bank", "fsb"),
+        [0x89, b'P', b'N', b'G', ..] => ("PNG image", "png"),
+        [b'P', b'K', 0x03, 0x04, ..] => ("ZIP archive", "zip"),
+        [0x78, 0x01 | 0x5e | 0x9c | 0xda, ..] => ("zlib stream", "zlib"),
+        _ => ("Unknown", "bin"),
+    }
+}
+
+fn collect_binary_blobs(map: &IndexMap<String, ContainerData>, path: &mut ScenePath, blobs: &mut Vec<(ScenePath, usize)>) {
+    for (key, value) in map {
+        let items: Vec<(Option<usize>, &Data)> = match value {
+            ContainerData::Single(data) => vec![(None, data)],
+            ContainerData::Multiple(list) => list.iter().enumerate().map(|(i, d)| (Some(i), d)).collect(),
+        };
+        for (index, data) in items {
+            path.push((key.clone(), index));
+            match data {
+                Data::Binary(bytes) => blobs.push((path.clone(), bytes.len())),
+                Data::Container(children) => collect_binary_blobs(children, path, blobs),
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+}
+
+pub fn find_data<'a>(map: &'a IndexMap<String, ContainerData>, path: &[(String, Option<usize>)]) -> Option<&'a Data> {
+    let ((key, index), rest) = path.split_first()?;
+    let data = match (map.get(key)?, index) {
+        (ContainerData::Single(data), None) => data,
+        (ContainerData::Multiple(list), Some(index)) => list.get(*index)?,
+        _ => return None,
+    };
+    match data {
+        _ if rest.is_empty() => Some(data),
+        Data::Container(children) => find_data(children, rest),
+        _ => None,
+    }
+}
+
+pub fn find_data_mut<'a>(map: &'a mut IndexMap<String, ContainerData>, path: &[(String, Option<usize>)]) -> Option<&'a mut Data> {
+    let ((key, index), rest) = path.split_first()?;
+    let data = match (map.get_mut(key)?, index) {
+        (ContainerData::Single(data), None) => data,
+        (ContainerData::Multiple(list), Some(index)) => list.get_mut(*index)?,
+        _ => return None,
+    };
+    if rest.is_empty() {
+        return Some(data);
+    }
+    match data {
+        Data::Container(children) => find_data_mut(children, rest),
+        _ => None,
+    }
+}
+
+const CURVE_CONTAINER_HINTS: [&str; 6] = ["anim", "track", "key", "channel", "curve", "bone"];
+const CURVE_TIME_KEYS: [&str; 4] = ["Time", "Times", "KeyTimes", "Keys"];
+
+// Float vectors only count as keyframes below an animation-looking container, so mesh data stays out
+fn collect_animation_curves(map: &IndexMap<String, ContainerData>, prefix: &str, in_animation: bool, curves: &mut Vec<AnimationCurve>) {
+    let times = CURVE_TIME_KEYS.iter().find_map(|key| match map.get(*key) {
+        Some(ContainerData::Single(Data::FloatVec(times))) => Some(times),
+        _ => None,
+    });
+
+    for (key, value) in map {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}/{}", prefix, key) };
+        let lower = key.to_lowercase();
+        let child_in_animation = in_animation || CURVE_CONTAINER_HINTS.iter().any(|hint| lower.contains(hint));
+
+        let items: Vec<&Data> = match value {
+            ContainerData::Single(data) => vec![data],
+            ContainerData::Multiple(list) => list.iter().collect(),
+        };
+
+        for (index, data) in items.into_iter().enumerate() {
+            let path = if matches!(value, ContainerData::Multiple(_)) { format!("{}[{}]", path, index) } else { path.clone() };
+            match data {
+                Data::Container(children) => collect_animation_curves(children, &path, child_in_animation, curves),
+                Data::FloatVec(values) if child_in_animation && values.len() >= 2 => {
+                    if CURVE_TIME_KEYS.contains(&key.as_str()) {
+                        continue;
+                    }
+
+                    // Vector keys are stored interleaved, split them into one curve per component
+                    let components: &[&str] = if lower.contains("rot") && values.len() % 4 == 0 {
+                        &["x", "y", "z", "w"]
+                    } else if (lower.contains("pos") || lower.contains("trans") || lower.contains("scale")) && values.len() % 3 == 0 {
+                        &["x", "y", "z"]
+                    } else {
+                        &[""]
+                    };
+                    let key_count = values.len() / components.len();
+                    let times = times.filter(|t| t.len() == key_count);
+
+                    for (component_index, component) in components.iter().enumerate() {
+                        let points = (0..key_count)
+                            .map(|i| {
+                                let x = times.map_or(i as f64, |t| t[i] as f64);
+                                [x, values[i * components.len() + component_index] as f64]
+                            })
+                            .collect();
+                        let name = if component.is_empty() { path.clone() } else { format!("{}.{}", path, component) };
+                        curves.push(AnimationCurve { name, points });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Inverse of the level-based tree building in load_scene_file
+// Each child's place in the key map: its key, and its index when the key repeats
+fn child_paths(children: &[Node]) -> Vec<(String, Option<usize>)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for child in children {
+        *counts.entry(child.id.as_str()).or_insert(0) += 1;
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    children.iter().map(|child| {
+        let index = seen.entry(child.id.as_str()).or_insert(0);
+        *index += 1;
+        (child.id.clone(), (counts[child.id.as_str()] > 1).then_some(*index - 1))
+    }).collect()
+}
+
+fn record_interleaved(node: &Node, path: &mut ScenePath, out: &mut HashMap<ScenePath, Vec<String>>) {
+    let NodeData::Container(children) = &node.data else {
+        return;
+    };
+    // Interleaved when a key shows up again after a different one
+    let mut finished = std::collections::HashSet::new();
+    let mut interleaved = false;
+    for pair in children.windows(2).filter(|pair| pair[0].id != pair[1].id) {
+        finished.insert(pair[0].id.as_str());
+        if finished.contains(pair[1].id.as_str()) {
+            interleaved = true;
+            break;
+        }
+    }
+    if interleaved {
+        out.insert(path.clone(), children.iter().map(|c| c.id.clone()).collect());
+    }
+    for (child, child_path) in children.iter().zip(child_paths(children)) {
+        path.push(child_path);
+        record_interleaved(child, path, out);
+        path.pop();
+    }
+}
+
+fn restore_interleaved(node: &mut Node, path: &mut ScenePath, order: &HashMap<ScenePath, Vec<String>>) -> anyhow::Result<()> {
+    let NodeData::Container(children) = &mut node.data else {
+        return Ok(());
+    };
+    if let Some(keys) = order.get(path) {
+        let mut current: Vec<&str> = children.iter().map(|c| c.id.as_str()).collect();
+        let mut recorded: Vec<&str> = keys.iter().map(String::as_str).collect();
+        current.sort_unstable();
+        recorded.sort_unstable();
+        if current != recorded {
+            return Err(anyhow!(
+                "Entries were added or removed under {}, whose repeated keys are interleaved in the file; saving would change their order",
+                scene_path_label(path)
+            ));
+        }
+        // The key map kept each key's entries in file order, so they're dealt back out in turn
+        let mut grouped: IndexMap<String, std::collections::VecDeque<Node>> = IndexMap::new();
+        for child in children.drain(..) {
+            grouped.entry(child.id.clone()).or_default().push_back(child);
+        }
+        for key in keys {
+            children.extend(grouped.get_mut(key).and_then(|nodes| nodes.pop_front()));
+        }
+    }
+    let paths = child_paths(children);
+    for (child, child_path) in children.iter_mut().zip(paths) {
+        path.push(child_path);
+        restore_interleaved(child, path, order)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+fn flatten_nodes(node: Node, level: u8, out: &mut Vec<RawNode>) {
+    let Node { id, data } = node;
+    match data {
+        NodeData::Container(children) => {
+            out.push(RawNode { level, node: Node { id, data: NodeData::Container(Vec::new()) } });
+            for child in children {
+                flatten_nodes(child, level + 1, out);
+            }
+        }
+        data => out.push(RawNode { level, node: Node { id, data } }),
     }
 }
 
@@ -492,19 +963,27 @@ impl BinRead for RawNode {
         endian: Endian,
         args: Self::Args<'_>,
     ) -> binrw::BinResult<Self> {
+        let header_pos = reader.stream_position()?;
         let header_data: u16 = reader.read_type(endian)?;
         let header = NodeHeader::from(header_data);
 
-        let key_idx: u16 = reader.read_type(endian)?;
-        let key = &args[key_idx as usize];
+        let key = &read_string_ref(reader, endian, args)?;
 
         let name = if header.name() {
-            let name_idx: u16 = reader.read_type(endian)?;
-            Some(args[name_idx as usize].clone())
+            Some(read_string_ref(reader, endian, args)?)
         } else {
             None
         };
 
+        let unsupported = |detail: String| binrw::Error::AssertFail {
+            pos: header_pos,
+            message: format!("Unsupported node '{}' (header bits 0x{:04x}): {}", key, header_data, detail),
+        };
+        // data_type() panics on bit patterns outside the enum
+        if header.data_type_or_err().is_err() {
+            return Err(unsupported("unknown data type bits".to_string()));
+        }
+
         let level = header.level();
 
         let len_size = header.len_size() as usize + 1;
@@ -518,16 +997,12 @@ impl BinRead for RawNode {
             data: match (header.data_type(), header.r#type()) {
                 (DataType::None, Type::Container) => NodeData::Container(vec![]),
 
-                (DataType::String, Type::Scalar) => NodeData::String({
-                    let idx: u16 = reader.read_type(endian)?;
-                    args[idx as usize].clone()
-                }),
+                (DataType::String, Type::Scalar) => NodeData::String(read_string_ref(reader, endian, args)?),
                 (DataType::String, Type::Vec) => NodeData::StringVec({
                     let len = read_u32(reader, endian, len_size)? as usize;
                     let mut vec = Vec::with_capacity(len);
                     for _ in 0..len {
-                        let idx: u16 = reader.read_type(endian)?;
-                        vec.push(args[idx as usize].clone());
+                        vec.push(read_string_ref(reader, endian, args)?);
                     }
                     vec
                 }),
@@ -574,7 +1049,7 @@ impl BinRead for RawNode {
                     }
                 }
 
-                x => unimplemented!("{:?}", x),
+                x => return Err(unsupported(format!("{:?}", x))),
             },
         };
 
@@ -627,14 +1102,15 @@ impl BinWrite for RawNode {
             NodeData::Uuid(_) => (DataType::Binary, Type::Scalar),
         };
 
+        let pos = writer.stream_position()?;
         let key;
         let name;
 
         if let Some((k, n)) = self.node.id.split_once('#') {
-            key = find_string_index(args, k);
-            name = Some(find_string_index(args, n));
+            key = find_string_index(args, k, pos)?;
+            name = Some(find_string_index(args, n, pos)?);
         } else {
-            key = find_string_index(args, &self.node.id);
+            key = find_string_index(args, &self.node.id, pos)?;
             name = None;
         }
 
@@ -656,11 +1132,11 @@ impl BinWrite for RawNode {
 
         match &self.node.data {
             NodeData::Container(_) => {}
-            NodeData::String(data) => writer.write_type(&find_string_index(args, data), endian)?,
+            NodeData::String(data) => writer.write_type(&find_string_index(args, data, pos)?, endian)?,
             NodeData::StringVec(data) => {
                 write_u32(writer, data.len() as u32, endian, len_size as usize)?;
                 for x in data {
-                    writer.write_type(&find_string_index(args, x), endian)?;
+                    writer.write_type(&find_string_index(args, x, pos)?, endian)?;
                 }
             }
             NodeData::Float(data) => writer.write_type(data, endian)?,
@@ -700,8 +1176,25 @@ impl BinWrite for RawNode {
 }
 
 // Helper functions
-fn find_string_index(strings: &[String], string: &str) -> u16 {
-    strings.iter().position(|s| s == string).unwrap_or(0) as u16
+fn read_string_ref<R: Read + Seek>(reader: &mut R, endian: Endian, strings: &[String]) -> binrw::BinResult<String> {
+    let pos = reader.stream_position()?;
+    let idx: u16 = reader.read_type(endian)?;
+    strings.get(idx as usize).cloned().ok_or_else(|| binrw::Error::AssertFail {
+        pos,
+        message: format!("String index {} out of range ({} strings)", idx, strings.len()),
+    })
+}
+
+// A string missing from the table can't be written as some other string's index without
+// corrupting the file, so it's an error naming the string
+fn find_string_index(strings: &[String], string: &str, pos: u64) -> binrw::BinResult<u16> {
+    strings.iter()
+        .position(|s| s == string)
+        .and_then(|index| u16::try_from(index).ok())
+        .ok_or_else(|| binrw::Error::AssertFail {
+            pos,
+            message: format!("\"{}\" isn't in the string table (or is past index {})", string, u16::MAX),
+        })
 }
 
 const fn get_u32_size(i: u32) -> u8 {
@@ -880,4 +1373,57 @@ impl From<Data> for NodeData {
             Data::Uuid(data) => NodeData::Uuid(data),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(id: &str, value: i32) -> Node {
+        Node { id: id.to_string(), data: NodeData::Int(value) }
+    }
+
+    fn flattened(node: Node) -> Vec<String> {
+        let mut nodes = Vec::new();
+        flatten_nodes(node, 0, &mut nodes);
+        nodes.iter().map(|raw| match raw.node.data {
+            NodeData::Int(value) => format!("{}{}", raw.node.id, value),
+            _ => raw.node.id.clone(),
+        }).collect()
+    }
+
+    #[test]
+    fn interleaved_keys_keep_their_order_through_the_key_map() {
+        let inner = Node { id: "c".to_string(), data: NodeData::Container(vec![int("x", 1), int("y", 2), int("x", 3), int("y", 4)]) };
+        let root = Node { id: "root".to_string(), data: NodeData::Container(vec![int("a", 1), int("b", 2), int("a", 3), inner]) };
+        let mut order = HashMap::new();
+        record_interleaved(&root, &mut Vec::new(), &mut order);
+        assert_eq!(order.len(), 2);
+
+        let data: Data = root.data.try_into().unwrap();
+        let mut saved = Node { id: "root".to_string(), data: data.into() };
+        restore_interleaved(&mut saved, &mut Vec::new(), &order).unwrap();
+        assert_eq!(flattened(saved), ["root", "a1", "b2", "a3", "c", "x1", "y2", "x3", "y4"]);
+    }
+
+    #[test]
+    fn missing_strings_are_an_error_rather_than_index_0() {
+        let strings = vec!["key".to_string(), "value".to_string()];
+        let write = |id: &str, value: &str| {
+            let node = RawNode { level: 0, node: Node { id: id.to_string(), data: NodeData::String(value.to_string()) } };
+            Cursor::new(Vec::new()).write_type_args(&node, Endian::Little, strings.as_slice())
+        };
+        assert!(write("key", "value").is_ok());
+        assert!(write("key#name", "value").is_err());
+        assert!(write("key", "other").is_err());
+    }
+
+    #[test]
+    fn adding_to_interleaved_keys_refuses_to_save() {
+        let root = Node { id: "root".to_string(), data: NodeData::Container(vec![int("a", 1), int("b", 2), int("a", 3)]) };
+        let mut order = HashMap::new();
+        record_interleaved(&root, &mut Vec::new(), &mut order);
+
+        let mut saved = Node { id: "root".to_string(), data: NodeData::Container(vec![int("a", 1), int("a", 3), int("a", 5), int("b", 2)]) };
+        assert!(restore_interleaved(&mut saved, &mut Vec::new(), &order).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::paths::long_path;
+use super::tasks::TaskContext;
+
+// sha256sum's format, "<hash>  <path>" per line, so `sha256sum -c` checks it on Linux and macOS
+pub const MANIFEST_FILE: &str = "SHA256SUMS.txt";
+pub const INSTRUCTIONS_FILE: &str = "VERIFY.txt";
+
+const INSTRUCTIONS: &str = "\
+Checking this mod's files
+
+Every file is listed in SHA256SUMS.txt with its SHA-256 hash. To check them:
+
+- Tundra: Tools > Verify manifest..., then pick SHA256SUMS.txt
+- Linux or macOS: run `sha256sum -c SHA256SUMS.txt` in this folder
+  (`shasum -a 256 -c SHA256SUMS.txt` on older macOS)
+- Windows PowerShell, in this folder:
+  Get-Content SHA256SUMS.txt | ForEach-Object {
+    $hash, $file = $_ -split '  ', 2
+    if ((Get-FileHash -Algorithm SHA256 $file).Hash -ne $hash) { \"FAILED: $file\" }
+  }
+
+A file that doesn't match was changed or damaged after the release; download the mod again.
+";
+
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(long_path(path))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Forward slashes whatever the platform, so the manifest checks the same everywhere
+pub fn relative_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect::<Vec<_>>().join("/")
+}
+
+fn is_manifest_file(name: &str) -> bool {
+    name == MANIFEST_FILE || name == INSTRUCTIONS_FILE
+}
+
+// Hashes every file below `folder` into `manifest`, with the verification instructions next to
+// it. Returns a summary.
+pub fn generate(folder: &Path, manifest: &Path, task: &TaskContext) -> Result<String, String> {
+    let files: Vec<PathBuf> = walkdir::WalkDir::new(folder)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && !is_manifest_file(&relative_name(folder, e.path())))
+        .map(|e| e.into_path())
+        .collect();
+    task.set_total(files.len());
+
+    let mut listing = String::new();
+    for path in &files {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let name = relative_name(folder, path);
+        task.advance(name.clone());
+        let hash = hash_file(path).map_err(|e| format!("{}: {}", name, e))?;
+        listing.push_str(&format!("{}  {}\n", hash, name));
+    }
+
+    fs::write(long_path(manifest), listing).map_err(|e| e.to_string())?;
+    let instructions = manifest.with_file_name(INSTRUCTIONS_FILE);
+    fs::write(long_path(&instructions), INSTRUCTIONS).map_err(|e| e.to_string())?;
+    println!("Wrote release manifest {} for {} files", manifest.display(), files.len());
+    Ok(format!("{} files hashed → {}", files.len(), manifest.display()))
+}
+
+#[derive(Debug, Default)]
+pub struct Verification {
+    pub matched: usize,
+    pub changed: Vec<String>,
+    pub missing: Vec<String>,
+    // In the folder but not in the manifest
+    pub unlisted: Vec<String>,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.changed.is_empty() && self.missing.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} match, {} changed, {} missing, {} not listed",
+            if self.passed() { "Passed" } else { "Failed" },
+            self.matched,
+            self.changed.len(),
+            self.missing.len(),
+            self.unlisted.len()
+        )
+    }
+}
+
+// Checks the files next to `manifest` against it. Also reads manifests written by sha256sum,
+// including its binary mode marker ("<hash> *<path>").
+pub fn verify(manifest: &Path, task: &TaskContext) -> Result<Verification, String> {
+    let root = manifest.parent().ok_or("Manifest has no folder")?;
+    let content = fs::read_to_string(long_path(manifest)).map_err(|e| e.to_string())?;
+    let mut expected: Vec<(String, String)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (hash, name) = line.split_once(' ').ok_or_else(|| format!("Line {} isn't \"<hash>  <path>\"", index + 1))?;
+        let name = name.strip_prefix(' ').or_else(|| name.strip_prefix('*')).unwrap_or(name);
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Line {}: {} isn't a SHA-256 hash", index + 1, hash));
+        }
+        expected.push((hash.to_lowercase(), name.to_string()));
+    }
+    task.set_total(expected.len());
+
+    let mut result = Verification::default();
+    for (hash, name) in &expected {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        task.advance(name.clone());
+        let path = root.join(name);
+        if !path.is_file() {
+            result.missing.push(name.clone());
+            continue;
+        }
+        match hash_file(&path) {
+            Ok(actual) if actual == *hash => result.matched += 1,
+            Ok(_) => result.changed.push(name.clone()),
+            Err(e) => result.changed.push(format!("{} ({})", name, e)),
+        }
+    }
+
+    let listed: HashSet<&str> = expected.iter().map(|(_, name)| name.as_str()).collect();
+    let manifest_name = relative_name(root, manifest);
+    result.unlisted = walkdir::WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| relative_name(root, e.path()))
+        .filter(|name| !is_manifest_file(name) && *name != manifest_name && !listed.contains(name.as_str()))
+        .collect();
+    Ok(result)
+}
@@ -0,0 +1,241 @@
+use eframe::egui;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 7878;
+// Jobs are posted as JSON, nothing legitimate comes close to this
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+// Converting a large archive entry can take a while
+const REPLY_TIMEOUT: Duration = Duration::from_secs(120);
+
+// A request waiting for the app to answer it on the UI thread
+pub struct RemoteRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub body: Vec<u8>,
+    reply: mpsc::Sender<RemoteResponse>,
+}
+
+impl RemoteRequest {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+
+    // Can be sent from any thread, so slow reads don't have to block the UI
+    pub fn respond(self, response: RemoteResponse) {
+        let _ = self.reply.send(response);
+    }
+}
+
+pub struct RemoteResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl RemoteResponse {
+    pub fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_vec_pretty(value) {
+            Ok(body) => Self { status: 200, content_type: "application/json", body },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    pub fn bytes(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self { status: 200, content_type, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::json!({ "error": message }).to_string().into_bytes();
+        Self { status, content_type: "application/json", body }
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(value) => {
+                        decoded.push(value);
+                        index += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn write_response(stream: &mut TcpStream, response: &RemoteResponse) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+// Reads one request, hands it to the app and writes back whatever it answers
+// A page on another name that resolves to 127.0.0.1 (DNS rebinding) still sends its own name as the Host
+fn is_local_host(host: &str, port: u16) -> bool {
+    host == format!("127.0.0.1:{}", port) || host.eq_ignore_ascii_case(&format!("localhost:{}", port))
+}
+
+fn handle_connection(stream: TcpStream, port: u16, requests: &mpsc::Sender<RemoteRequest>, ctx: &egui::Context) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return write_response(&mut writer, &RemoteResponse::error(400, "Malformed request line"));
+    };
+
+    let mut content_length = 0;
+    let mut from_browser = false;
+    let mut local_host = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+            if name.trim().eq_ignore_ascii_case("origin") {
+                from_browser = true;
+            }
+            if name.trim().eq_ignore_ascii_case("host") {
+                local_host = is_local_host(value.trim(), port);
+            }
+        }
+    }
+    // Web pages could otherwise drive the API through the user's browser
+    if from_browser {
+        return write_response(&mut writer, &RemoteResponse::error(403, "Requests from web pages are not accepted"));
+    }
+    if !local_host {
+        return write_response(&mut writer, &RemoteResponse::error(403, "Host must be 127.0.0.1 or localhost"));
+    }
+    if content_length > MAX_BODY_SIZE {
+        return write_response(&mut writer, &RemoteResponse::error(413, "Request body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+
+    let (reply, response) = mpsc::channel();
+    let request = RemoteRequest {
+        method: method.to_uppercase(),
+        path: percent_decode(path),
+        query,
+        body,
+        reply,
+    };
+    if requests.send(request).is_err() {
+        return write_response(&mut writer, &RemoteResponse::error(503, "Tundra is shutting down"));
+    }
+    ctx.request_repaint();
+
+    let response = response
+        .recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| RemoteResponse::error(503, "Timed out waiting for Tundra"));
+    write_response(&mut writer, &response)
+}
+
+// Local HTTP server for scripts and other tools. Only listens on 127.0.0.1; requests are queued
+// for the app, which answers them from `take_requests` each frame.
+pub struct RemoteServer {
+    pub port: u16,
+    requests: mpsc::Receiver<RemoteRequest>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RemoteServer {
+    pub fn start(port: u16, ctx: &egui::Context) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let port = listener.local_addr()?.port();
+        let (sender, requests) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let (sender, ctx) = (sender.clone(), ctx.clone());
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, port, &sender, &ctx) {
+                        eprintln!("Remote API connection failed: {}", e);
+                    }
+                });
+            }
+            println!("Remote API stopped");
+        });
+
+        println!("Remote API listening on http://127.0.0.1:{}", port);
+        Ok(Self { port, requests, stop })
+    }
+
+    pub fn take_requests(&self) -> Vec<RemoteRequest> {
+        self.requests.try_iter().collect()
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // The accept loop only notices the flag on its next connection
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+    }
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use super::paths::long_path;
+
+// Copies of the previous versions kept next to a file each writer overwrites; set from Options
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
+pub const MAX_BACKUP_COUNT: usize = 20;
+
+static BACKUP_COUNT: AtomicUsize = AtomicUsize::new(DEFAULT_BACKUP_COUNT);
+
+pub fn set_backup_count(count: usize) {
+    BACKUP_COUNT.store(count.min(MAX_BACKUP_COUNT), Ordering::Relaxed);
+}
+
+// "mickey.tbody" -> "mickey.tbody.bak", then ".bak2", ".bak3"... from newest to oldest
+pub fn backup_path(path: &Path, generation: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    if generation <= 1 {
+        name.push(".bak");
+    } else {
+        name.push(format!(".bak{}", generation));
+    }
+    PathBuf::from(name)
+}
+
+// Shifts the existing backups down one and copies the current file in as the newest. The oldest
+// past the limit is removed; it's a copy, the file itself stays in place until it's written.
+// Returns whether a backup was made, so a failed write knows whether `.bak` is the file it replaced.
+pub fn rotate_backups(path: &Path) -> io::Result<bool> {
+    let count = BACKUP_COUNT.load(Ordering::Relaxed);
+    if count == 0 || !path.is_file() {
+        return Ok(false);
+    }
+    let oldest = backup_path(path, count);
+    if oldest.exists() {
+        fs::remove_file(long_path(&oldest))?;
+    }
+    for generation in (1..count).rev() {
+        let from = backup_path(path, generation);
+        if from.exists() {
+            fs::rename(long_path(&from), long_path(&backup_path(path, generation + 1)))?;
+        }
+    }
+    fs::copy(long_path(path), long_path(&backup_path(path, 1)))?;
+    Ok(true)
+}
+
+// Every writer that replaces a file goes through here or through `prepare` for streamed output
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    prepare(path)?;
+    fs::write(long_path(path), contents)
+}
+
+// For writers that open the file themselves: backs up what's about to be replaced
+pub fn prepare(path: &Path) -> io::Result<bool> {
+    rotate_backups(path).map_err(|e| io::Error::new(e.kind(), format!("Couldn't back up {}: {}", path.display(), e)))
+}
+
+// Deletes go to the OS trash so they can be taken back
+pub fn delete(paths: &[PathBuf]) -> Result<(), String> {
+    trash::delete_all(paths).map_err(|e| e.to_string())
+}
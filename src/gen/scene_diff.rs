@@ -0,0 +1,242 @@
+use eframe::egui;
+use indexmap::IndexMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use super::archive_diff::ChangeKind;
+use super::paths::long_path;
+use super::read_scene::{scene_path_label, ContainerData, Data, SceneFileHandler, ScenePath};
+
+// Vectors and binary payloads longer than this are summarised in the report
+const MAX_SHOWN_VALUES: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct SceneChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+// Differences between two parsed OCT/BENT files, key by key
+#[derive(Debug, Clone)]
+pub struct SceneDiff {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub changes: Vec<SceneChange>,
+}
+
+pub fn load(path: &Path) -> Result<IndexMap<String, ContainerData>, String> {
+    let data = std::fs::read(long_path(path)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut handler = SceneFileHandler::new();
+    handler.load_scene_file(&mut Cursor::new(data)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    handler.current_scene.ok_or_else(|| format!("{}: no scene data", path.display()))
+}
+
+fn list<T: ToString>(values: &[T]) -> String {
+    let shown: Vec<String> = values.iter().take(MAX_SHOWN_VALUES).map(|v| v.to_string()).collect();
+    if values.len() > MAX_SHOWN_VALUES {
+        format!("[{}, ... ({} values)]", shown.join(", "), values.len())
+    } else {
+        format!("[{}]", shown.join(", "))
+    }
+}
+
+pub fn format_value(data: &Data) -> String {
+    match data {
+        Data::Container(children) => format!("{{{} keys}}", children.len()),
+        Data::Binary(bytes) => {
+            let mut crc = flate2::Crc::new();
+            crc.update(bytes);
+            format!("{} bytes, CRC {:08x}", bytes.len(), crc.sum())
+        }
+        Data::Uuid(uuid) => uuid.to_string(),
+        Data::Int(value) => value.to_string(),
+        Data::IntVec(values) => list(values),
+        Data::Float(value) => value.to_string(),
+        Data::FloatVec(values) => list(values),
+        Data::String(value) => format!("\"{}\"", value),
+        Data::StringVec(values) => list(&values.iter().map(|v| format!("\"{}\"", v)).collect::<Vec<_>>()),
+    }
+}
+
+// Floats compare by bits so NaNs, which the games do store, equal themselves
+fn same_value(a: &Data, b: &Data) -> bool {
+    match (a, b) {
+        (Data::Float(a), Data::Float(b)) => a.to_bits() == b.to_bits(),
+        (Data::FloatVec(a), Data::FloatVec(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.to_bits() == b.to_bits()),
+        (Data::Int(a), Data::Int(b)) => a == b,
+        (Data::IntVec(a), Data::IntVec(b)) => a == b,
+        (Data::String(a), Data::String(b)) => a == b,
+        (Data::StringVec(a), Data::StringVec(b)) => a == b,
+        (Data::Uuid(a), Data::Uuid(b)) => a == b,
+        (Data::Binary(a), Data::Binary(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn items(value: &ContainerData) -> Vec<(Option<usize>, &Data)> {
+    match value {
+        ContainerData::Single(data) => vec![(None, data)],
+        ContainerData::Multiple(list) => list.iter().enumerate().map(|(i, d)| (Some(i), d)).collect(),
+    }
+}
+
+fn change(kind: ChangeKind, path: &ScenePath, before: Option<&Data>, after: Option<&Data>) -> SceneChange {
+    SceneChange { kind, path: scene_path_label(path), before: before.map(format_value), after: after.map(format_value) }
+}
+
+fn compare_data(old: &Data, new: &Data, path: &mut ScenePath, changes: &mut Vec<SceneChange>) {
+    match (old, new) {
+        (Data::Container(old), Data::Container(new)) => compare_maps(old, new, path, changes),
+        _ if same_value(old, new) => {}
+        _ => changes.push(change(ChangeKind::Changed, path, Some(old), Some(new))),
+    }
+}
+
+// Repeated keys are compared by position, so an item inserted in the middle shows as every later
+// one changing; that's also how the game reads them
+fn compare_maps(old: &IndexMap<String, ContainerData>, new: &IndexMap<String, ContainerData>, path: &mut ScenePath, changes: &mut Vec<SceneChange>) {
+    for (key, old_value) in old {
+        let old_items = items(old_value);
+        let new_items = new.get(key).map(items).unwrap_or_default();
+        for position in 0..old_items.len().max(new_items.len()) {
+            let (index, old_data) = old_items.get(position).map_or((None, None), |(i, d)| (*i, Some(*d)));
+            let (new_index, new_data) = new_items.get(position).map_or((None, None), |(i, d)| (*i, Some(*d)));
+            path.push((key.clone(), index.or(new_index)));
+            match (old_data, new_data) {
+                (Some(old_data), Some(new_data)) => compare_data(old_data, new_data, path, changes),
+                (Some(old_data), None) => changes.push(change(ChangeKind::Removed, path, Some(old_data), None)),
+                (None, Some(new_data)) => changes.push(change(ChangeKind::Added, path, None, Some(new_data))),
+                (None, None) => {}
+            }
+            path.pop();
+        }
+    }
+    for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+        for (index, data) in items(new_value) {
+            path.push((key.clone(), index));
+            changes.push(change(ChangeKind::Added, path, None, Some(data)));
+            path.pop();
+        }
+    }
+}
+
+pub fn compare(old: &Path, new: &Path) -> Result<SceneDiff, String> {
+    let (old_scene, new_scene) = (load(old)?, load(new)?);
+    let mut changes = Vec::new();
+    compare_maps(&old_scene, &new_scene, &mut Vec::new(), &mut changes);
+    Ok(SceneDiff { old: old.to_path_buf(), new: new.to_path_buf(), changes })
+}
+
+fn markdown_cell(value: &Option<String>) -> String {
+    value.as_deref().map_or(String::new(), |v| format!("`{}`", v.replace('|', "\\|").replace('`', "'")))
+}
+
+impl SceneDiff {
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {} → {}\n\n", self.old.display(), self.new.display());
+        out.push_str(&format!(
+            "{} added, {} removed, {} changed\n\n",
+            self.count(ChangeKind::Added),
+            self.count(ChangeKind::Removed),
+            self.count(ChangeKind::Changed)
+        ));
+        if self.changes.is_empty() {
+            out.push_str("No differences.\n");
+            return out;
+        }
+        out.push_str("| Change | Key | Before | After |\n|---|---|---|---|\n");
+        for change in &self.changes {
+            out.push_str(&format!(
+                "| {} | `{}` | {} | {} |\n",
+                change.kind.label(),
+                change.path,
+                markdown_cell(&change.before),
+                markdown_cell(&change.after)
+            ));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let changes: Vec<serde_json::Value> = self.changes
+            .iter()
+            .map(|change| serde_json::json!({
+                "change": change.kind.label().to_lowercase(),
+                "key": change.path,
+                "before": change.before,
+                "after": change.after,
+            }))
+            .collect();
+        serde_json::json!({
+            "old": self.old.display().to_string(),
+            "new": self.new.display().to_string(),
+            "changes": changes,
+        })
+    }
+}
+
+pub enum SceneDiffAction {
+    ExportJson,
+    ExportMarkdown,
+}
+
+#[derive(Default)]
+pub struct SceneDiffView {
+    filter: String,
+    hidden: Vec<ChangeKind>,
+}
+
+impl SceneDiffView {
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, diff: &SceneDiff) -> Option<SceneDiffAction> {
+        let mut action = None;
+        ui.label(format!("{} → {}", diff.old.display(), diff.new.display()));
+        ui.horizontal(|ui| {
+            for kind in [ChangeKind::Added, ChangeKind::Removed, ChangeKind::Changed] {
+                let mut shown = !self.hidden.contains(&kind);
+                if ui.checkbox(&mut shown, format!("{} ({})", kind.label(), diff.count(kind))).changed() {
+                    self.hidden.retain(|k| *k != kind);
+                    if !shown {
+                        self.hidden.push(kind);
+                    }
+                }
+            }
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+            if ui.button("Export JSON...").clicked() {
+                action = Some(SceneDiffAction::ExportJson);
+            }
+            if ui.button("Export Markdown...").clicked() {
+                action = Some(SceneDiffAction::ExportMarkdown);
+            }
+        });
+        ui.separator();
+        if diff.changes.is_empty() {
+            ui.label("The scenes hold the same keys and values.");
+            return action;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let changes: Vec<&SceneChange> = diff.changes
+            .iter()
+            .filter(|c| !self.hidden.contains(&c.kind) && c.path.to_lowercase().contains(&filter))
+            .collect();
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::both().id_source("scene_diff_rows").auto_shrink([false, true]).show_rows(ui, row_height, changes.len(), |ui, range| {
+            egui::Grid::new("scene_diff_grid").striped(true).num_columns(4).show(ui, |ui| {
+                for change in &changes[range] {
+                    ui.colored_label(change.kind.color(ui), change.kind.label());
+                    ui.monospace(&change.path);
+                    ui.label(change.before.as_deref().unwrap_or(""));
+                    ui.label(change.after.as_deref().unwrap_or(""));
+                    ui.end_row();
+                }
+            });
+        });
+        action
+    }
+}
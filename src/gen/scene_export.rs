@@ -0,0 +1,163 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use super::read_scene::{ContainerData, Data};
+
+pub const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+
+// A placed mesh found in a scene. `matrix` is column-major like glTF and already includes the
+// transforms of the containers above it.
+#[derive(Debug, Clone)]
+pub struct SceneInstance {
+    pub name: String,
+    pub ibuf: PathBuf,
+    pub matrix: [f32; 16],
+}
+
+// Lower-cased file stem of a reference like "Assets/Models/Rock_01.ibuf"
+pub fn reference_stem(reference: &str) -> String {
+    let file_name = reference.rsplit(['/', '\\']).next().unwrap_or(reference);
+    file_name.split('.').next().unwrap_or(file_name).to_lowercase()
+}
+
+fn multiply(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+    let mut result = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            result[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    result
+}
+
+// Both layouts turn up in scene files; a row-major matrix has its translation in the last column
+// and 0 0 0 1 along the bottom row
+fn matrix_from_floats(values: &[f32]) -> [f32; 16] {
+    let mut matrix = [0.0; 16];
+    matrix.copy_from_slice(&values[..16]);
+    let row_major = matrix[12..15].iter().all(|&v| v == 0.0)
+        && matrix[15] == 1.0
+        && [3, 7, 11].iter().any(|&i| matrix[i] != 0.0);
+    if row_major {
+        let mut transposed = [0.0; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                transposed[column * 4 + row] = matrix[row * 4 + column];
+            }
+        }
+        matrix = transposed;
+    }
+    matrix
+}
+
+fn trs_matrix(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> [f32; 16] {
+    let [x, y, z, w] = rotation;
+    let rotation_columns = [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w)],
+        [2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w)],
+        [2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y)],
+    ];
+    let mut matrix = IDENTITY;
+    for column in 0..3 {
+        for row in 0..3 {
+            matrix[column * 4 + row] = rotation_columns[column][row] * scale[column];
+        }
+    }
+    matrix[12..15].copy_from_slice(&translation);
+    matrix
+}
+
+fn single_values(map: &IndexMap<String, ContainerData>) -> impl Iterator<Item = (&String, &Data)> {
+    map.iter().filter_map(|(key, value)| match value {
+        ContainerData::Single(data) => Some((key, data)),
+        ContainerData::Multiple(_) => None,
+    })
+}
+
+// Local transform of a container: a 16 float matrix, or position / rotation (quaternion, x y z w)
+// / scale keys. Euler rotations aren't used since their order and units differ between games.
+fn local_transform(map: &IndexMap<String, ContainerData>) -> Option<[f32; 16]> {
+    let mut translation = None;
+    let mut rotation = None;
+    let mut scale = None;
+    for (key, data) in single_values(map) {
+        let key = key.to_lowercase();
+        match data {
+            Data::FloatVec(values) if values.len() == 16 => return Some(matrix_from_floats(values)),
+            Data::FloatVec(values) if values.len() == 3 && (key.contains("pos") || key.contains("translat")) => {
+                translation = Some([values[0], values[1], values[2]]);
+            }
+            Data::FloatVec(values) if values.len() == 4 && (key.contains("rot") || key.contains("quat") || key.contains("orient")) => {
+                rotation = Some([values[0], values[1], values[2], values[3]]);
+            }
+            Data::FloatVec(values) if values.len() == 3 && key.contains("scale") => scale = Some([values[0], values[1], values[2]]),
+            Data::Float(value) if key.contains("scale") => scale = Some([*value; 3]),
+            _ => {}
+        }
+    }
+    if translation.is_none() && rotation.is_none() && scale.is_none() {
+        return None;
+    }
+    Some(trs_matrix(
+        translation.unwrap_or([0.0; 3]),
+        rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]),
+        scale.unwrap_or([1.0; 3]),
+    ))
+}
+
+// First string in the container naming a model we have an IBUF for
+fn mesh_reference(map: &IndexMap<String, ContainerData>, meshes: &HashMap<String, PathBuf>) -> Option<PathBuf> {
+    single_values(map).find_map(|(_, data)| {
+        let references: Vec<&String> = match data {
+            Data::String(value) => vec![value],
+            Data::StringVec(values) => values.iter().collect(),
+            _ => Vec::new(),
+        };
+        references.into_iter().find_map(|reference| meshes.get(&reference_stem(reference)).cloned())
+    })
+}
+
+fn instance_name(key: &str, map: &IndexMap<String, ContainerData>) -> String {
+    single_values(map)
+        .find_map(|(name_key, data)| match data {
+            Data::String(value) if name_key.eq_ignore_ascii_case("name") => Some(value.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn collect(key: &str, map: &IndexMap<String, ContainerData>, parent: &[f32; 16], meshes: &HashMap<String, PathBuf>, instances: &mut Vec<SceneInstance>) {
+    let matrix = match local_transform(map) {
+        Some(local) => multiply(parent, &local),
+        None => *parent,
+    };
+    if let Some(ibuf) = mesh_reference(map, meshes) {
+        instances.push(SceneInstance { name: instance_name(key, map), ibuf, matrix });
+    }
+
+    for (child_key, value) in map {
+        let children: Vec<&Data> = match value {
+            ContainerData::Single(data) => vec![data],
+            ContainerData::Multiple(items) => items.iter().collect(),
+        };
+        for child in children {
+            if let Data::Container(child_map) = child {
+                collect(child_key, child_map, &matrix, meshes, instances);
+            }
+        }
+    }
+}
+
+// Containers that reference one of `meshes` (keyed by lower-cased IBUF stem), with their world
+// transforms. OCT layouts differ per game, so this looks for the common shapes rather than
+// specific node names.
+pub fn collect_instances(scene: &IndexMap<String, ContainerData>, meshes: &HashMap<String, PathBuf>) -> Vec<SceneInstance> {
+    let mut instances = Vec::new();
+    collect("Scene", scene, &IDENTITY, meshes, &mut instances);
+    instances
+}
@@ -0,0 +1,251 @@
+use eframe::egui;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use super::read_scene::{ContainerData, Data};
+use super::scene_diff::format_value;
+
+const INDENT: f32 = 16.0;
+// SVG layout, in pixels
+const SVG_ROW: f32 = 18.0;
+const SVG_CHAR: f32 = 7.2;
+const SVG_MARGIN: f32 = 12.0;
+
+// One visible row: a key, or one item of a repeated key
+pub struct TreeLine {
+    pub depth: usize,
+    // Path label like scene_path_label's, used to remember what's expanded
+    pub id: String,
+    pub label: String,
+    // Summary for plain values, key count for containers
+    pub value: String,
+    pub container: bool,
+}
+
+fn items(value: &ContainerData) -> Vec<(Option<usize>, &Data)> {
+    match value {
+        ContainerData::Single(data) => vec![(None, data)],
+        ContainerData::Multiple(list) => list.iter().enumerate().map(|(i, d)| (Some(i), d)).collect(),
+    }
+}
+
+fn collect(map: &IndexMap<String, ContainerData>, parent: &str, depth: usize, expanded: &HashSet<String>, lines: &mut Vec<TreeLine>) {
+    for (key, value) in map {
+        for (index, data) in items(value) {
+            let label = index.map_or_else(|| key.clone(), |i| format!("{}[{}]", key, i));
+            let id = if parent.is_empty() { label.clone() } else { format!("{}/{}", parent, label) };
+            let children = match data {
+                Data::Container(children) => Some(children),
+                _ => None,
+            };
+            lines.push(TreeLine { depth, id: id.clone(), label, value: format_value(data), container: children.is_some() });
+            if let Some(children) = children.filter(|_| expanded.contains(&id)) {
+                collect(children, &id, depth + 1, expanded, lines);
+            }
+        }
+    }
+}
+
+// Rows as the tree shows them: children only below expanded containers
+fn visible_lines(scene: &IndexMap<String, ContainerData>, expanded: &HashSet<String>) -> Vec<TreeLine> {
+    let mut lines = Vec::new();
+    collect(scene, "", 0, expanded, &mut lines);
+    lines
+}
+
+fn container_ids(map: &IndexMap<String, ContainerData>, parent: &str, ids: &mut HashSet<String>) {
+    for (key, value) in map {
+        for (index, data) in items(value) {
+            if let Data::Container(children) = data {
+                let label = index.map_or_else(|| key.clone(), |i| format!("{}[{}]", key, i));
+                let id = if parent.is_empty() { label } else { format!("{}/{}", parent, label) };
+                container_ids(children, &id, ids);
+                ids.insert(id);
+            }
+        }
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// The rows drawn as an indented tree with connectors. Monospace text keeps the width estimate
+// close enough that nothing is cut off.
+fn to_svg(title: &str, lines: &[TreeLine]) -> String {
+    let text_width = |line: &TreeLine| (line.label.len() + line.value.len() + 3) as f32 * SVG_CHAR + line.depth as f32 * INDENT;
+    let width = lines.iter().map(text_width).fold(title.len() as f32 * SVG_CHAR, f32::max) + SVG_MARGIN * 2.0 + INDENT;
+    let height = (lines.len() + 1) as f32 * SVG_ROW + SVG_MARGIN * 2.0;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"monospace\" font-size=\"12\">\n",
+        width, height
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#1e1e1e\"/>\n");
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"#ffffff\" font-weight=\"bold\">{}</text>\n",
+        SVG_MARGIN,
+        SVG_MARGIN + SVG_ROW * 0.7,
+        escape_xml(title)
+    ));
+
+    // Each row's connector runs up to the closest row above that's one level shallower
+    let mut last_at_depth: Vec<usize> = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        let y = SVG_MARGIN + (row + 1) as f32 * SVG_ROW;
+        let x = SVG_MARGIN + line.depth as f32 * INDENT;
+        let middle = y + SVG_ROW / 2.0;
+        if line.depth > 0 {
+            let parent_row = last_at_depth.get(line.depth - 1).copied().unwrap_or(0);
+            let top = SVG_MARGIN + (parent_row + 2) as f32 * SVG_ROW - 4.0;
+            let stem = x - INDENT / 2.0;
+            svg.push_str(&format!(
+                "<path d=\"M{:.1} {:.1} V{:.1} H{:.1}\" stroke=\"#707070\" fill=\"none\"/>\n",
+                stem, top, middle, x
+            ));
+        }
+        last_at_depth.truncate(line.depth);
+        last_at_depth.push(row);
+
+        let color = if line.container { "#8cc8ff" } else { "#e0e0e0" };
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\"><tspan fill=\"{}\">{}</tspan><tspan fill=\"#9a9a9a\"> = {}</tspan></text>\n",
+            x + 2.0,
+            middle + 4.0,
+            color,
+            escape_xml(&line.label),
+            escape_xml(&line.value)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub enum SceneTreeAction {
+    ExportSvg,
+    ExportPng,
+}
+
+// Key hierarchy of the open scene, with its expansion exported as drawn
+#[derive(Default)]
+pub struct SceneTreeView {
+    // Scene the expansion belongs to; another scene starts collapsed
+    source: Option<PathBuf>,
+    expanded: HashSet<String>,
+    // Where the rows were drawn last frame, cropped out of the screenshot for PNG export
+    view_rect: Option<egui::Rect>,
+    pending_png: Option<(egui::Rect, PathBuf)>,
+    status: Option<Result<String, String>>,
+}
+
+impl SceneTreeView {
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, source: &Path, scene: &IndexMap<String, ContainerData>) -> Option<SceneTreeAction> {
+        if self.source.as_deref() != Some(source) {
+            *self = Self { source: Some(source.to_path_buf()), ..Default::default() };
+        }
+        self.poll_png(ui.ctx());
+
+        let mut action = None;
+        ui.horizontal(|ui| {
+            if ui.button("Expand all").clicked() {
+                container_ids(scene, "", &mut self.expanded);
+            }
+            if ui.button("Collapse all").clicked() {
+                self.expanded.clear();
+            }
+            if ui.button("Export SVG...").on_hover_text("Every expanded row, including the ones scrolled out of view").clicked() {
+                action = Some(SceneTreeAction::ExportSvg);
+            }
+            if ui.add_enabled(self.pending_png.is_none(), egui::Button::new("Export PNG..."))
+                .on_hover_text("The tree as it's shown right now")
+                .clicked()
+            {
+                action = Some(SceneTreeAction::ExportPng);
+            }
+        });
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+        ui.separator();
+
+        let lines = visible_lines(scene, &self.expanded);
+        let mut toggled = None;
+        let row_height = ui.spacing().interact_size.y;
+        let output = egui::ScrollArea::both().id_source("scene_tree_rows").auto_shrink([false, true]).show_rows(ui, row_height, lines.len(), |ui, range| {
+            for line in &lines[range] {
+                ui.horizontal(|ui| {
+                    ui.add_space(line.depth as f32 * INDENT);
+                    if line.container {
+                        let open = self.expanded.contains(&line.id);
+                        if ui.small_button(if open { "⏷" } else { "⏵" }).clicked() {
+                            toggled = Some(line.id.clone());
+                        }
+                        ui.strong(&line.label);
+                    } else {
+                        ui.label(&line.label);
+                    }
+                    ui.weak(&line.value);
+                });
+            }
+        });
+        self.view_rect = Some(output.inner_rect);
+        if let Some(id) = toggled {
+            if !self.expanded.remove(&id) {
+                self.expanded.insert(id);
+            }
+        }
+        action
+    }
+
+    // The rows as expanded right now, scrolled out of view or not
+    pub fn svg(&self, title: &str, scene: &IndexMap<String, ContainerData>) -> String {
+        to_svg(title, &visible_lines(scene, &self.expanded))
+    }
+
+    pub fn set_status(&mut self, status: Result<String, String>) {
+        self.status = Some(status);
+    }
+
+    // Asks for a screenshot of the window; the tree is cropped out of it once it arrives
+    pub fn request_png(&mut self, ctx: &egui::Context, path: PathBuf) {
+        if let Some(rect) = self.view_rect {
+            self.pending_png = Some((rect, path));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            ctx.request_repaint();
+        }
+    }
+
+    fn poll_png(&mut self, ctx: &egui::Context) {
+        if self.pending_png.is_none() {
+            return;
+        }
+        let screenshot = ctx.input(|i| {
+            i.raw.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some((image.clone(), i.pixels_per_point)),
+                _ => None,
+            })
+        });
+        let (Some((image, pixels_per_point)), Some((rect, path))) = (screenshot, self.pending_png.take()) else {
+            return;
+        };
+        let result = save_region(&image, rect, pixels_per_point, &path).map(|_| {
+            println!("Saved scene tree view to {}", path.display());
+            format!("Saved {}", path.display())
+        });
+        self.status = Some(result);
+    }
+}
+
+fn save_region(image: &egui::ColorImage, rect: egui::Rect, pixels_per_point: f32, path: &Path) -> Result<(), String> {
+    let bounds = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(image.size[0] as f32, image.size[1] as f32) / pixels_per_point);
+    let region = image.region(&rect.intersect(bounds), Some(pixels_per_point));
+    let [width, height] = region.size;
+    let rgba: Vec<u8> = region.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+    let buffer = image::RgbaImage::from_raw(width as u32, height as u32, rgba).ok_or("Screenshot is empty")?;
+    buffer.save_with_format(path, image::ImageFormat::Png).map_err(|e| e.to_string())
+}
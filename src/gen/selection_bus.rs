@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Selection {
+    // Picked in a viewer, waiting for the file tree to reveal it
+    published: Option<PathBuf>,
+    // Selected in the file tree, for the viewers to highlight
+    current: Option<PathBuf>,
+}
+
+// Keeps the file tree and the viewers pointing at the same source file. Clones share the state,
+// so each viewer holds one like it holds the write guard.
+#[derive(Clone, Default)]
+pub struct SelectionBus(Arc<Mutex<Selection>>);
+
+impl SelectionBus {
+    // A viewer picked the instance or texture loaded from `path`
+    pub fn publish(&self, path: &Path) {
+        let mut selection = self.0.lock().unwrap();
+        selection.published = Some(path.to_path_buf());
+        selection.current = Some(path.to_path_buf());
+    }
+
+    pub fn take_published(&self) -> Option<PathBuf> {
+        self.0.lock().unwrap().published.take()
+    }
+
+    pub fn set_current(&self, path: Option<&Path>) {
+        self.0.lock().unwrap().current = path.map(Path::to_path_buf);
+    }
+
+    pub fn is_current(&self, path: &Path) -> bool {
+        self.0.lock().unwrap().current.as_deref() == Some(path)
+    }
+}
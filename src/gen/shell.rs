@@ -0,0 +1,40 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+// The OS's handler for a file or link. On Windows this is the protocol handler itself rather than
+// `cmd /C start`, since cmd would act on &, | and ^ in names that come from archives.
+fn open_with_handler(target: &OsStr) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler").arg(target);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("open");
+        command.arg(target);
+        command
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(target);
+        command
+    };
+    command.spawn().map(|_| ())
+}
+
+// Hands the file or folder to whatever the OS has registered for it
+pub fn open_in_default_app(path: &Path) -> std::io::Result<()> {
+    open_with_handler(path.as_os_str())
+}
+
+// Web links from downloaded data only: anything but http(s) is refused
+pub fn open_in_browser(url: &str) -> std::io::Result<()> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "only http and https links are opened"));
+    }
+    open_with_handler(OsStr::new(url))
+}
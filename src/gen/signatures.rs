@@ -0,0 +1,149 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::OnceLock;
+use super::sniff::{parse_magic, FileKind};
+
+// Shipped signatures, see the file itself for the format
+const BUILT_IN: &str = include_str!("../../signatures.toml");
+// Read from the working directory next to tundra_config.json; its entries are checked first
+pub const USER_FILE: &str = "signatures.toml";
+
+fn default_scan() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Signature {
+    pub name: String,
+    #[serde(default)]
+    magic: String,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    search: bool,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    pub viewer: Option<FileKind>,
+    #[serde(default = "default_scan")]
+    scan: bool,
+    #[serde(default)]
+    length_field: Option<usize>,
+    #[serde(default)]
+    length_add: usize,
+    #[serde(skip)]
+    magic_bytes: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignatureFile {
+    #[serde(default)]
+    signature: Vec<Signature>,
+}
+
+impl Signature {
+    pub fn extension(&self) -> &str {
+        self.extensions.first().map_or("bin", |e| e.as_str())
+    }
+
+    fn matches(&self, head: &[u8]) -> bool {
+        let magic = &self.magic_bytes;
+        if magic.is_empty() {
+            return false;
+        }
+        if self.search {
+            head.windows(magic.len()).any(|window| window == magic.as_slice())
+        } else {
+            head.get(self.offset..).map_or(false, |rest| rest.starts_with(magic))
+        }
+    }
+
+    // Size of the data starting at `data`, when the format stores it
+    pub fn length(&self, data: &[u8]) -> Option<usize> {
+        let field = self.length_field?;
+        let bytes = data.get(field..field + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize + self.length_add)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SignatureDb {
+    pub signatures: Vec<Signature>,
+    // Indexes of the signatures Analyze looks for mid-file, by the first byte of their magic
+    scannable: Vec<Vec<usize>>,
+}
+
+fn parse(text: &str) -> Result<Vec<Signature>, String> {
+    let file: SignatureFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    let mut signatures = file.signature;
+    for signature in &mut signatures {
+        signature.magic_bytes = parse_magic(&signature.magic)
+            .ok_or_else(|| format!("{}: magic \"{}\" isn't hex", signature.name, signature.magic))?;
+        if signature.magic_bytes.is_empty() && signature.extensions.is_empty() {
+            return Err(format!("{}: needs a magic or extensions", signature.name));
+        }
+    }
+    Ok(signatures)
+}
+
+impl SignatureDb {
+    pub fn load() -> Self {
+        let mut signatures = Vec::new();
+        if Path::new(USER_FILE).is_file() {
+            match std::fs::read_to_string(USER_FILE).map_err(|e| e.to_string()).and_then(|text| parse(&text)) {
+                Ok(user) => {
+                    println!("Loaded {} signatures from {}", user.len(), USER_FILE);
+                    signatures.extend(user);
+                }
+                Err(e) => eprintln!("Failed to load {}: {}", USER_FILE, e),
+            }
+        }
+        match parse(BUILT_IN) {
+            Ok(built_in) => signatures.extend(built_in),
+            Err(e) => eprintln!("Built-in signatures are invalid: {}", e),
+        }
+
+        let mut scannable = vec![Vec::new(); 256];
+        for (index, signature) in signatures.iter().enumerate() {
+            // Signatures found by searching or away from the start can't be told apart mid-file
+            if signature.scan && !signature.search && signature.offset == 0 {
+                if let Some(&first) = signature.magic_bytes.first() {
+                    scannable[first as usize].push(index);
+                }
+            }
+        }
+        Self { signatures, scannable }
+    }
+
+    // First signature whose magic matches the start of a file
+    pub fn identify(&self, head: &[u8]) -> Option<&Signature> {
+        self.signatures.iter().find(|signature| signature.matches(head))
+    }
+
+    // Like identify, for the Analyze tool's walk through the middle of a file
+    pub fn identify_embedded(&self, data: &[u8]) -> Option<&Signature> {
+        let first = *data.first()?;
+        self.scannable[first as usize]
+            .iter()
+            .map(|&index| &self.signatures[index])
+            .find(|signature| data.starts_with(&signature.magic_bytes))
+    }
+
+    pub fn kind_from_magic(&self, head: &[u8]) -> Option<FileKind> {
+        self.signatures.iter().filter(|s| s.viewer.is_some()).find(|s| s.matches(head)).and_then(|s| s.viewer)
+    }
+
+    pub fn kind_from_extension(&self, path: &Path) -> Option<FileKind> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        self.signatures
+            .iter()
+            .filter(|s| s.extensions.iter().any(|e| e.eq_ignore_ascii_case(&extension)))
+            .find_map(|s| s.viewer)
+    }
+}
+
+// Loaded on first use, which startup does so errors show up early
+pub fn database() -> &'static SignatureDb {
+    static DATABASE: OnceLock<SignatureDb> = OnceLock::new();
+    DATABASE.get_or_init(SignatureDb::load)
+}
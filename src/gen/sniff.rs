@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use super::signatures;
+
+// MTBs carry a short header before TEXB, so the start of the file is searched rather than offset 0
+const SNIFF_SIZE: usize = 4096;
+
+// What a file holds, which decides the viewer it opens in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileKind {
+    Scene,
+    Texture,
+    Material,
+    ModelBuffer,
+    Archive,
+    Audio,
+    Text,
+    Hex,
+}
+
+impl FileKind {
+    // Kinds with a viewer, offered under "Open as"
+    pub const VIEWABLE: [FileKind; 6] = [
+        FileKind::Scene,
+        FileKind::Texture,
+        FileKind::Material,
+        FileKind::ModelBuffer,
+        FileKind::Text,
+        FileKind::Hex,
+    ];
+
+    pub const ALL: [FileKind; 8] = [
+        FileKind::Scene,
+        FileKind::Texture,
+        FileKind::Material,
+        FileKind::ModelBuffer,
+        FileKind::Archive,
+        FileKind::Audio,
+        FileKind::Text,
+        FileKind::Hex,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileKind::Scene => "Scene (OCT/BENT)",
+            FileKind::Texture => "Texture (TBODY/DDS)",
+            FileKind::Material => "Material (MTB)",
+            FileKind::ModelBuffer => "Model (IBUF/VBUF)",
+            FileKind::Archive => "ZIP archive",
+            FileKind::Audio => "RIFF audio",
+            FileKind::Text => "Text",
+            FileKind::Hex => "Hex",
+        }
+    }
+}
+
+// User rule for a game, e.g. "treat .bin as OCT". Both fields are optional; a rule with an
+// extension and a magic needs both to match. `magic` is hex ("29 76 01 45") compared against the
+// start of the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileAssociation {
+    #[serde(default)]
+    pub extension: String,
+    #[serde(default)]
+    pub magic: String,
+    pub kind: FileKind,
+}
+
+pub fn parse_magic(text: &str) -> Option<Vec<u8>> {
+    let hex: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl FileAssociation {
+    pub fn matches(&self, path: &Path, head: &[u8]) -> bool {
+        let extension = self.extension.trim().trim_start_matches('.');
+        if extension.is_empty() && self.magic.trim().is_empty() {
+            return false;
+        }
+        let extension_matches = extension.is_empty()
+            || path.extension().map_or(false, |e| e.to_string_lossy().eq_ignore_ascii_case(extension));
+        let magic_matches = self.magic.trim().is_empty()
+            || parse_magic(&self.magic).map_or(false, |magic| !magic.is_empty() && head.starts_with(&magic));
+        extension_matches && magic_matches
+    }
+}
+
+// User rules come first, then magic bytes, then the extension, both from the signature database;
+// magic wins over the extension since console dumps are often misnamed. IBUF/VBUF have no header,
+// so they're only ever known by extension. Encrypted Disney Infinity archives don't start with PK
+// either and are left to the extension as well.
+pub fn detect(path: &Path, associations: &[FileAssociation]) -> Option<FileKind> {
+    let mut head = Vec::with_capacity(SNIFF_SIZE);
+    let read = std::fs::File::open(super::paths::long_path(path))
+        .and_then(|file| file.take(SNIFF_SIZE as u64).read_to_end(&mut head))
+        .is_ok();
+    if !read {
+        head.clear();
+    }
+    associations
+        .iter()
+        .find(|association| association.matches(path, &head))
+        .map(|association| association.kind)
+        .or_else(|| signatures::database().kind_from_magic(&head))
+        .or_else(|| signatures::database().kind_from_extension(path))
+}
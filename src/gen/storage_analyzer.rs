@@ -0,0 +1,317 @@
+use eframe::egui;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+// A single sized file, either on disk or inside an archive
+#[derive(Debug, Clone)]
+pub struct StorageItem {
+    pub path: PathBuf,
+    pub size: u64,
+    pub archive: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtensionStats {
+    pub extension: String,
+    pub total_size: u64,
+    pub file_count: usize,
+}
+
+pub struct StorageReport {
+    pub root: PathBuf,
+    pub total_size: u64,
+    pub file_count: usize,
+    pub archive_entry_count: usize,
+    node_sizes: HashMap<PathBuf, u64>,
+    children: HashMap<PathBuf, BTreeSet<PathBuf>>,
+    extensions: Vec<ExtensionStats>,
+    extensions_with_archives: Vec<ExtensionStats>,
+}
+
+impl StorageReport {
+    pub fn build(root: &Path, items: &[StorageItem]) -> Self {
+        let mut node_sizes: HashMap<PathBuf, u64> = HashMap::new();
+        let mut children: HashMap<PathBuf, BTreeSet<PathBuf>> = HashMap::new();
+        let mut extensions: HashMap<String, (u64, usize)> = HashMap::new();
+        let mut extensions_with_archives: HashMap<String, (u64, usize)> = HashMap::new();
+        let mut total_size = 0;
+        let mut file_count = 0;
+        let mut archive_entry_count = 0;
+
+        let archives: std::collections::HashSet<&Path> = items.iter()
+            .filter_map(|item| item.archive.as_deref())
+            .collect();
+
+        for item in items {
+            *node_sizes.entry(item.path.clone()).or_insert(0) += item.size;
+
+            // Archive entries roll up to the archive only; the archive itself counts its size on disk
+            let stop_at = item.archive.as_deref().unwrap_or(root);
+            let mut child = item.path.as_path();
+            while let Some(parent) = child.parent() {
+                children.entry(parent.to_path_buf()).or_default().insert(child.to_path_buf());
+                if parent == stop_at || !parent.starts_with(root) {
+                    break;
+                }
+                *node_sizes.entry(parent.to_path_buf()).or_insert(0) += item.size;
+                child = parent;
+            }
+
+            let extension = item.path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_else(|| "(none)".to_string());
+            let is_archive = archives.contains(item.path.as_path());
+
+            if item.archive.is_some() {
+                archive_entry_count += 1;
+                let stats = extensions_with_archives.entry(extension).or_insert((0, 0));
+                stats.0 += item.size;
+                stats.1 += 1;
+            } else {
+                total_size += item.size;
+                file_count += 1;
+                let stats = extensions.entry(extension.clone()).or_insert((0, 0));
+                stats.0 += item.size;
+                stats.1 += 1;
+                if !is_archive {
+                    let stats = extensions_with_archives.entry(extension).or_insert((0, 0));
+                    stats.0 += item.size;
+                    stats.1 += 1;
+                }
+            }
+        }
+
+        node_sizes.insert(root.to_path_buf(), total_size);
+
+        Self {
+            root: root.to_path_buf(),
+            total_size,
+            file_count,
+            archive_entry_count,
+            node_sizes,
+            children,
+            extensions: Self::sorted_extensions(extensions),
+            extensions_with_archives: Self::sorted_extensions(extensions_with_archives),
+        }
+    }
+
+    fn sorted_extensions(map: HashMap<String, (u64, usize)>) -> Vec<ExtensionStats> {
+        let mut stats: Vec<ExtensionStats> = map
+            .into_iter()
+            .map(|(extension, (total_size, file_count))| ExtensionStats { extension, total_size, file_count })
+            .collect();
+        stats.sort_by(|a, b| b.total_size.cmp(&a.total_size));
+        stats
+    }
+
+    pub fn size_of(&self, path: &Path) -> u64 {
+        self.node_sizes.get(path).copied().unwrap_or(0)
+    }
+
+    // Children of a folder or archive, largest first
+    pub fn children_of(&self, path: &Path) -> Vec<(PathBuf, u64)> {
+        let mut result: Vec<(PathBuf, u64)> = self.children
+            .get(path)
+            .map(|set| set.iter().map(|p| (p.clone(), self.size_of(p))).collect())
+            .unwrap_or_default();
+        result.sort_by(|a, b| b.1.cmp(&a.1));
+        result
+    }
+
+    pub fn has_children(&self, path: &Path) -> bool {
+        self.children.get(path).map_or(false, |set| !set.is_empty())
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StorageTab {
+    Folders,
+    Extensions,
+}
+
+pub struct StorageAnalyzer {
+    report: Option<StorageReport>,
+    analysis_thread: Option<thread::JoinHandle<StorageReport>>,
+    current_dir: PathBuf,
+    tab: StorageTab,
+    include_archive_contents: bool,
+}
+
+impl StorageAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            report: None,
+            analysis_thread: None,
+            current_dir: PathBuf::new(),
+            tab: StorageTab::Folders,
+            include_archive_contents: true,
+        }
+    }
+
+    // Collecting items can mean opening every archive, so it runs off the UI thread
+    pub fn start<F>(&mut self, root: PathBuf, collect_items: F)
+    where
+        F: FnOnce() -> Vec<StorageItem> + Send + 'static,
+    {
+        self.report = None;
+        self.current_dir = root.clone();
+        self.analysis_thread = Some(thread::spawn(move || {
+            let items = collect_items();
+            println!("Storage analysis collected {} items", items.len());
+            StorageReport::build(&root, &items)
+        }));
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.analysis_thread.is_some()
+    }
+
+    fn check_completion(&mut self) {
+        if self.analysis_thread.as_ref().map_or(false, |t| t.is_finished()) {
+            if let Some(thread) = self.analysis_thread.take() {
+                match thread.join() {
+                    Ok(report) => self.report = Some(report),
+                    Err(e) => eprintln!("Storage analysis thread panicked: {:?}", e),
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.report = None;
+        self.analysis_thread = None;
+        self.current_dir = PathBuf::new();
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        self.check_completion();
+
+        if self.is_running() {
+            ui.horizontal(|ui| {
+                ui.add(egui::Spinner::new());
+                ui.label("Analyzing storage (reading archives)...");
+            });
+            ui.ctx().request_repaint();
+            return;
+        }
+
+        let Some(report) = &self.report else {
+            ui.label("No storage report available");
+            return;
+        };
+
+        ui.label(format!("Root: {}", report.root.display()));
+        ui.label(format!(
+            "{} files, {} on disk, {} archive entries",
+            report.file_count,
+            format_size(report.total_size),
+            report.archive_entry_count
+        ));
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tab, StorageTab::Folders, "By folder");
+            ui.selectable_value(&mut self.tab, StorageTab::Extensions, "By extension");
+        });
+        ui.separator();
+
+        match self.tab {
+            StorageTab::Folders => {
+                let mut navigate_to = None;
+
+                ui.horizontal(|ui| {
+                    let can_go_up = self.current_dir != report.root && self.current_dir.starts_with(&report.root);
+                    if ui.add_enabled(can_go_up, egui::Button::new("⬆ Up")).clicked() {
+                        navigate_to = self.current_dir.parent().map(|p| p.to_path_buf());
+                    }
+                    let relative = self.current_dir.strip_prefix(&report.root).unwrap_or(&self.current_dir);
+                    ui.label(format!("/{}", relative.display()));
+                    ui.label(format!("({})", format_size(report.size_of(&self.current_dir))));
+                });
+
+                let rows = report.children_of(&self.current_dir);
+                let largest = rows.first().map(|(_, size)| *size).unwrap_or(0);
+
+                egui::ScrollArea::vertical()
+                    .id_source("storage_folders_scroll")
+                    .show(ui, |ui| {
+                        for (path, size) in &rows {
+                            let name = path.file_name()
+                                .and_then(|n| n.to_str())
+                                .unwrap_or("Unknown");
+                            let can_open = report.has_children(path);
+                            if Self::size_bar(ui, name, *size, largest, can_open) {
+                                navigate_to = Some(path.clone());
+                            }
+                        }
+                    });
+
+                if let Some(dir) = navigate_to {
+                    self.current_dir = dir;
+                }
+            }
+            StorageTab::Extensions => {
+                ui.checkbox(&mut self.include_archive_contents, "Count files inside archives instead of the archives");
+
+                let stats = if self.include_archive_contents {
+                    &report.extensions_with_archives
+                } else {
+                    &report.extensions
+                };
+                let largest = stats.first().map(|s| s.total_size).unwrap_or(0);
+
+                egui::ScrollArea::vertical()
+                    .id_source("storage_extensions_scroll")
+                    .show(ui, |ui| {
+                        for stat in stats {
+                            let label = format!(".{} ({} files)", stat.extension, stat.file_count);
+                            Self::size_bar(ui, &label, stat.total_size, largest, false);
+                        }
+                    });
+            }
+        }
+    }
+
+    // Draws one labelled bar scaled against the largest row; returns true when clicked
+    fn size_bar(ui: &mut egui::Ui, label: &str, size: u64, largest: u64, clickable: bool) -> bool {
+        let mut clicked = false;
+        ui.horizontal(|ui| {
+            let bar_width = 200.0;
+            let fraction = if largest > 0 { size as f32 / largest as f32 } else { 0.0 };
+            let (rect, response) = ui.allocate_exact_size(
+                egui::Vec2::new(bar_width, ui.spacing().interact_size.y * 0.8),
+                egui::Sense::click(),
+            );
+            ui.painter().rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+            let mut filled = rect;
+            filled.set_width(bar_width * fraction);
+            ui.painter().rect_filled(filled, 2.0, egui::Color32::from_rgb(70, 130, 200));
+
+            ui.monospace(format!("{:>10}", format_size(size)));
+            let text_response = if clickable {
+                ui.link(label)
+            } else {
+                ui.label(label)
+            };
+
+            clicked = clickable && (text_response.clicked() || response.clicked());
+        });
+        clicked
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use super::jobs::glob_match;
+
+// Kept next to the config like the scan cache, so tags survive rescans and config resets
+pub const TAGS_PATH: &str = "tundra_tags.json";
+
+// Offered when tagging, along with every tag already in use
+pub const SUGGESTED_TAGS: [&str; 6] = ["character", "track", "vehicle", "ui", "audio", "effect"];
+
+pub const QUERY_HELP: &str = "Terms that must all match, e.g. \"untagged ext:tbody size>1MB\":\n\
+tag:name, -tag:name, tagged, untagged, ext:tbody, size>1MB, size<100KB, *.oct (glob), or text in the name";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub query: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagDb {
+    // Files and archive entries, by the path the tree shows them at
+    files: BTreeMap<PathBuf, BTreeSet<String>>,
+    pub queries: Vec<SavedQuery>,
+    // Bumped on every change so cached query results know they're stale
+    #[serde(skip)]
+    pub generation: u64,
+}
+
+impl TagDb {
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Failed to read tags from {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) {
+        match serde_json::to_string_pretty(self) {
+            Ok(serialized) => {
+                if let Err(e) = std::fs::write(path, serialized) {
+                    eprintln!("Failed to write tags: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize tags: {}", e),
+        }
+    }
+
+    pub fn tags(&self, path: &Path) -> Option<&BTreeSet<String>> {
+        self.files.get(path)
+    }
+
+    pub fn has_tag(&self, path: &Path, tag: &str) -> bool {
+        self.files.get(path).map_or(false, |tags| tags.contains(tag))
+    }
+
+    pub fn add(&mut self, path: &Path, tag: &str) {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() {
+            return;
+        }
+        self.files.entry(path.to_path_buf()).or_default().insert(tag);
+        self.generation += 1;
+    }
+
+    pub fn remove(&mut self, path: &Path, tag: &str) {
+        if let Some(tags) = self.files.get_mut(path) {
+            tags.remove(tag);
+            if tags.is_empty() {
+                self.files.remove(path);
+            }
+        }
+        self.generation += 1;
+    }
+
+    // Suggestions first, then what's been used, without repeats
+    pub fn known_tags(&self) -> Vec<String> {
+        let mut known: Vec<String> = SUGGESTED_TAGS.iter().map(|t| t.to_string()).collect();
+        let used: BTreeSet<&String> = self.files.values().flatten().collect();
+        known.extend(used.into_iter().filter(|t| !SUGGESTED_TAGS.contains(&t.as_str())).cloned());
+        known
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Tag(String),
+    NotTag(String),
+    Tagged,
+    Untagged,
+    Extension(String),
+    Larger(u64),
+    Smaller(u64),
+    Glob(String),
+    Name(String),
+}
+
+// "1MB", "512KB", "100" (bytes); 1024-based like format_size
+fn parse_size(text: &str) -> Option<u64> {
+    let upper = text.trim().to_uppercase();
+    let split = upper.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(split);
+    let multiplier = match unit.trim() {
+        "" | "B" => 1u64,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return None,
+    };
+    number.parse::<f64>().ok().map(|n| (n * multiplier as f64) as u64)
+}
+
+#[derive(Debug, Clone)]
+pub struct Query(Vec<Term>);
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for word in text.split_whitespace() {
+            let lower = word.to_lowercase();
+            let term = if let Some(tag) = lower.strip_prefix("-tag:") {
+                Term::NotTag(tag.to_string())
+            } else if let Some(tag) = lower.strip_prefix("tag:") {
+                Term::Tag(tag.to_string())
+            } else if lower == "tagged" {
+                Term::Tagged
+            } else if lower == "untagged" {
+                Term::Untagged
+            } else if let Some(extension) = lower.strip_prefix("ext:") {
+                Term::Extension(extension.trim_start_matches('.').to_string())
+            } else if let Some(size) = lower.strip_prefix("size>") {
+                Term::Larger(parse_size(size).ok_or_else(|| format!("\"{}\" isn't a size", size))?)
+            } else if let Some(size) = lower.strip_prefix("size<") {
+                Term::Smaller(parse_size(size).ok_or_else(|| format!("\"{}\" isn't a size", size))?)
+            } else if let Some(name) = lower.strip_prefix("name:") {
+                Term::Name(name.to_string())
+            } else if lower.contains(['*', '?']) {
+                Term::Glob(word.to_string())
+            } else {
+                Term::Name(lower)
+            };
+            terms.push(term);
+        }
+        if terms.is_empty() {
+            return Err("The query is empty".to_string());
+        }
+        Ok(Self(terms))
+    }
+
+    pub fn matches(&self, name: &str, size: u64, tags: Option<&BTreeSet<String>>) -> bool {
+        let lower = name.to_lowercase();
+        let tagged = tags.map_or(false, |t| !t.is_empty());
+        self.0.iter().all(|term| match term {
+            Term::Tag(tag) => tags.map_or(false, |t| t.contains(tag)),
+            Term::NotTag(tag) => !tags.map_or(false, |t| t.contains(tag)),
+            Term::Tagged => tagged,
+            Term::Untagged => !tagged,
+            Term::Extension(extension) => lower.rsplit_once('.').map_or(false, |(_, e)| e == extension),
+            Term::Larger(limit) => size > *limit,
+            Term::Smaller(limit) => size < *limit,
+            Term::Glob(pattern) => glob_match(pattern, name),
+            Term::Name(text) => lower.contains(text.as_str()),
+        })
+    }
+}
@@ -0,0 +1,217 @@
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Progress updates can arrive thousands of times a second, repaint for them at most this often
+const PROGRESS_REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskProgress {
+    pub current: usize,
+    pub total: usize,
+    pub message: String,
+    pub errors: Vec<String>,
+    pub summary: Option<String>,
+}
+
+// Handed to the task closure to report progress and check for cancellation
+#[derive(Clone)]
+pub struct TaskContext {
+    progress: Arc<Mutex<TaskProgress>>,
+    cancel_flag: Arc<Mutex<bool>>,
+    ctx: egui::Context,
+}
+
+impl TaskContext {
+    // For running task code outside the GUI, e.g. jobs started from the command line
+    pub fn detached() -> Self {
+        Self {
+            progress: Arc::new(Mutex::new(TaskProgress::default())),
+            cancel_flag: Arc::new(Mutex::new(false)),
+            ctx: egui::Context::default(),
+        }
+    }
+
+    pub fn progress(&self) -> TaskProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.progress.lock().unwrap().total = total;
+    }
+
+    pub fn advance(&self, message: impl Into<String>) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.current += 1;
+        progress.message = message.into();
+        self.ctx.request_repaint_after(PROGRESS_REPAINT_INTERVAL);
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.progress.lock().unwrap().message = message.into();
+        self.ctx.request_repaint_after(PROGRESS_REPAINT_INTERVAL);
+    }
+
+    pub fn add_error(&self, error: impl Into<String>) {
+        self.progress.lock().unwrap().errors.push(error.into());
+        self.ctx.request_repaint_after(PROGRESS_REPAINT_INTERVAL);
+    }
+
+    pub fn finish(&self, summary: impl Into<String>) {
+        self.progress.lock().unwrap().summary = Some(summary.into());
+        self.ctx.request_repaint();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancel_flag.lock().unwrap()
+    }
+}
+
+pub struct Task {
+    pub name: String,
+    progress: Arc<Mutex<TaskProgress>>,
+    cancel_flag: Arc<Mutex<bool>>,
+    thread: Option<thread::JoinHandle<()>>,
+    start_time: Instant,
+    finished_after: Option<std::time::Duration>,
+}
+
+impl Task {
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().map_or(false, |t| !t.is_finished())
+    }
+
+    pub fn progress(&self) -> TaskProgress {
+        self.progress.lock().unwrap().clone()
+    }
+}
+
+pub struct TaskManager {
+    pub tasks: Vec<Task>,
+    ctx: egui::Context,
+}
+
+impl TaskManager {
+    pub fn new(ctx: egui::Context) -> Self {
+        Self { tasks: Vec::new(), ctx }
+    }
+
+    pub fn spawn<F>(&mut self, name: impl Into<String>, work: F)
+    where
+        F: FnOnce(TaskContext) + Send + 'static,
+    {
+        let name = name.into();
+        let progress = Arc::new(Mutex::new(TaskProgress::default()));
+        let cancel_flag = Arc::new(Mutex::new(false));
+        let context = TaskContext {
+            progress: progress.clone(),
+            cancel_flag: cancel_flag.clone(),
+            ctx: self.ctx.clone(),
+        };
+
+        println!("Starting task: {}", name);
+        let ctx = self.ctx.clone();
+        // Wake the UI when the task ends so its result shows without waiting for input
+        let thread = thread::spawn(move || {
+            work(context);
+            ctx.request_repaint();
+        });
+
+        self.tasks.push(Task {
+            name,
+            progress,
+            cancel_flag,
+            thread: Some(thread),
+            start_time: Instant::now(),
+            finished_after: None,
+        });
+    }
+
+    pub fn has_running(&self) -> bool {
+        self.tasks.iter().any(|t| t.is_running())
+    }
+
+    fn check_completion(&mut self) {
+        for task in &mut self.tasks {
+            if task.thread.as_ref().map_or(false, |t| t.is_finished()) {
+                if let Some(thread) = task.thread.take() {
+                    if let Err(e) = thread.join() {
+                        eprintln!("Task '{}' panicked: {:?}", task.name, e);
+                        task.progress.lock().unwrap().errors.push("Task panicked".to_string());
+                    }
+                    task.finished_after = Some(task.start_time.elapsed());
+                    println!("Task finished: {}", task.name);
+                }
+            }
+        }
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        self.check_completion();
+
+        if self.tasks.is_empty() {
+            ui.label("No tasks have been run this session");
+            return;
+        }
+
+        // Keeps elapsed times ticking; progress itself repaints through the task context
+        if self.has_running() {
+            ui.ctx().request_repaint_after(Duration::from_millis(500));
+        }
+
+        let mut remove = None;
+        egui::ScrollArea::vertical()
+            .id_source("tasks_scroll")
+            .show(ui, |ui| {
+                for (index, task) in self.tasks.iter().enumerate().rev() {
+                    let progress = task.progress();
+                    ui.push_id(index, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.strong(&task.name);
+                            if task.is_running() {
+                                if ui.small_button("Cancel").clicked() {
+                                    *task.cancel_flag.lock().unwrap() = true;
+                                }
+                            } else if ui.small_button("Dismiss").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+
+                        let fraction = if progress.total > 0 {
+                            progress.current as f32 / progress.total as f32
+                        } else {
+                            0.0
+                        };
+                        let elapsed = task.finished_after.unwrap_or_else(|| task.start_time.elapsed());
+                        ui.add(egui::ProgressBar::new(fraction)
+                            .text(format!("{}/{} ({:.1}s)", progress.current, progress.total, elapsed.as_secs_f32())));
+
+                        if task.is_running() {
+                            if *task.cancel_flag.lock().unwrap() {
+                                ui.label("Cancelling...");
+                            } else {
+                                ui.small(&progress.message);
+                            }
+                        } else if let Some(summary) = &progress.summary {
+                            ui.label(summary);
+                        }
+
+                        if !progress.errors.is_empty() {
+                            egui::CollapsingHeader::new(format!("{} errors", progress.errors.len()))
+                                .show(ui, |ui| {
+                                    for error in &progress.errors {
+                                        ui.colored_label(egui::Color32::RED, error);
+                                    }
+                                });
+                        }
+                    });
+                    ui.separator();
+                }
+            });
+
+        if let Some(index) = remove {
+            self.tasks.remove(index);
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use eframe::egui;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use image::ImageFormat;
+use super::dds::DdsLayout;
+use super::entry_cache::ViewerSource;
+use super::profiling::ScopedTimer;
+use super::selection_bus::SelectionBus;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
 pub struct TbodyTexture {
@@ -8,6 +14,16 @@ pub struct TbodyTexture {
     pub texture_handle: Option<egui::TextureHandle>,
     pub dimensions: (u32, u32),
     pub file_path: PathBuf,
+    pub image: egui::ColorImage,
+    // Faces or slices of cubemaps and texture arrays; empty for plain 2D textures
+    pub surfaces: Vec<egui::TextureHandle>,
+    pub layout: Option<DdsLayout>,
+    pub current_surface: usize,
+    pub show_cross: bool,
+    // Size of the file the texture was read from
+    pub data_size: usize,
+    // Name of the MTB that referenced this texture, used for grouping
+    pub group: Option<String>,
 }
 
 impl TbodyTexture {
@@ -16,11 +32,101 @@ impl TbodyTexture {
         Self::load_from_bytes(&data, file_path, ctx)
     }
 
+    // Loads a TBODY/DDS or any other image format the image crate can guess, for replacement textures
+    pub fn load_any(file_path: &Path, ctx: &egui::Context) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read(file_path)?;
+        let is_dds = file_path.extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| e.eq_ignore_ascii_case("tbody") || e.eq_ignore_ascii_case("dds"));
+        if is_dds {
+            return Self::load_from_bytes(&data, file_path, ctx);
+        }
+        let img = image::load_from_memory(&data)?;
+        let mut texture = Self::from_image(img, file_path, ctx)?;
+        texture.data_size = data.len();
+        Ok(texture)
+    }
+
     pub fn load_from_bytes(data: &[u8], file_path: &Path, ctx: &egui::Context) -> Result<Self, Box<dyn std::error::Error>> {
+        // Cubemaps and arrays are split into single surfaces, the image crate only reads plain 2D DDS
+        if let Some(layout) = DdsLayout::parse(data).filter(|l| l.surface_count() > 1) {
+            let mut images = Vec::new();
+            for index in 0..layout.surface_count() {
+                let Some(surface) = layout.extract_surface(data, index) else {
+                    break;
+                };
+                match image::load_from_memory_with_format(&surface, ImageFormat::Dds) {
+                    Ok(img) => images.push(img),
+                    Err(e) => {
+                        eprintln!("Failed to decode surface {} of {}: {}", index, file_path.display(), e);
+                        break;
+                    }
+                }
+            }
+
+            if !images.is_empty() {
+                let mut texture = Self::from_image(images[0].clone(), file_path, ctx)?;
+                texture.surfaces = images
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, img)| {
+                        ctx.load_texture(format!("{}#{}", texture.name, index), Self::color_image(&img), Default::default())
+                    })
+                    .collect();
+                texture.show_cross = layout.is_cubemap && texture.surfaces.len() >= 6;
+                texture.layout = Some(layout);
+                texture.data_size = data.len();
+                return Ok(texture);
+            }
+        }
+
         // TBODY files are actually DDS files, so we need to handle DDS format
         let img = image::load_from_memory_with_format(data, ImageFormat::Dds)?;
+        let mut texture = Self::from_image(img, file_path, ctx)?;
+        texture.data_size = data.len();
+        Ok(texture)
+    }
+
+    fn color_image(img: &image::DynamicImage) -> egui::ColorImage {
         let rgba = img.to_rgba8();
-        let dimensions = (rgba.width(), rgba.height());
+        egui::ColorImage::from_rgba_unmultiplied(
+            [rgba.width() as usize, rgba.height() as usize],
+            rgba.as_flat_samples().as_slice(),
+        )
+    }
+
+    // Writes the decoded pixels (first surface for cubemaps and arrays) as a PNG
+    pub fn save_png(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let [width, height] = self.image.size;
+        let rgba: Vec<u8> = self.image.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+        let buffer = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+            .ok_or("Pixel buffer does not match the texture size")?;
+        buffer.save_with_format(path, ImageFormat::Png)?;
+        Ok(())
+    }
+
+    // Handle for the surface currently paged to, or the texture itself
+    pub fn display_handle(&self) -> Option<&egui::TextureHandle> {
+        self.surfaces.get(self.current_surface).or(self.texture_handle.as_ref())
+    }
+
+    // Unfolded cube cross: +Y on top, -X +Z +X -Z across the middle, -Y below
+    pub fn show_cube_cross(&self, ui: &mut egui::Ui, size: f32) {
+        const CROSS: [(usize, f32, f32); 6] = [(0, 2.0, 1.0), (1, 0.0, 1.0), (2, 1.0, 0.0), (3, 1.0, 2.0), (4, 1.0, 1.0), (5, 3.0, 1.0)];
+        let cell = size / 4.0;
+        let (rect, _) = ui.allocate_exact_size(egui::Vec2::new(cell * 4.0, cell * 3.0), egui::Sense::hover());
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        for (face, column, row) in CROSS {
+            if let Some(handle) = self.surfaces.get(face) {
+                let min = rect.min + egui::Vec2::new(column * cell, row * cell);
+                let face_rect = egui::Rect::from_min_size(min, egui::Vec2::splat(cell));
+                ui.painter().image(handle.id(), face_rect, uv, egui::Color32::WHITE);
+            }
+        }
+    }
+
+    fn from_image(img: image::DynamicImage, file_path: &Path, ctx: &egui::Context) -> Result<Self, Box<dyn std::error::Error>> {
+        let dimensions = (img.width(), img.height());
         
         let name = file_path.file_name()
             .and_then(|n| n.to_str())
@@ -28,94 +134,949 @@ impl TbodyTexture {
             .to_string();
 
         // Create texture handle
-        let pixels = rgba.as_flat_samples();
-        let texture_handle = Some(ctx.load_texture(
-            name.clone(),
-            egui::ColorImage::from_rgba_unmultiplied(
-                [dimensions.0 as usize, dimensions.1 as usize],
-                pixels.as_slice(),
-            ),
-            Default::default(),
-        ));
+        let image = Self::color_image(&img);
+        let texture_handle = Some(ctx.load_texture(name.clone(), image.clone(), Default::default()));
 
         Ok(TbodyTexture {
             name,
             texture_handle,
             dimensions,
             file_path: file_path.to_path_buf(),
+            image,
+            surfaces: Vec::new(),
+            layout: None,
+            current_surface: 0,
+            show_cross: false,
+            data_size: 0,
+            group: None,
         })
     }
 }
 
+// Filled in by the decode task once the texture is ready
+type DecodeSlot = Arc<Mutex<Option<Result<TbodyTexture, String>>>>;
+
+// Shown as a placeholder card until its decode finishes. Decoding only starts once the card
+// scrolls into view or "Load all" is pressed.
+struct PendingTexture {
+    name: String,
+    group: Option<String>,
+    file_path: PathBuf,
+    slot: DecodeSlot,
+    requested: bool,
+}
+
+impl PendingTexture {
+    fn request(&mut self, requests: &mut Vec<DecodeRequest>) {
+        if self.requested {
+            return;
+        }
+        self.requested = true;
+        requests.push(DecodeRequest {
+            file_path: self.file_path.clone(),
+            slot: self.slot.clone(),
+        });
+    }
+}
+
+// A texture waiting to be decoded off the UI thread
+pub struct DecodeRequest {
+    pub file_path: PathBuf,
+    slot: DecodeSlot,
+}
+
+impl DecodeRequest {
+    pub fn run(self, source: &ViewerSource, ctx: &egui::Context) -> Result<(), String> {
+        let _timer = ScopedTimer::new("texture", format!("Decode {}", self.file_path.display()));
+        let result = source.read(&self.file_path)
+            .and_then(|data| TbodyTexture::load_from_bytes(&data, &self.file_path, ctx))
+            .map_err(|e| e.to_string());
+        let outcome = result.as_ref().map(|_| ()).map_err(|e| e.clone());
+        *self.slot.lock().unwrap() = Some(result);
+        ctx.request_repaint();
+        outcome
+    }
+
+    pub fn cancel(self) {
+        *self.slot.lock().unwrap() = Some(Err("Cancelled".to_string()));
+    }
+}
+
+// Per-channel statistics over the decoded RGBA pixels
+pub struct TextureStats {
+    pub histograms: [[u32; 256]; 4],
+    pub min: [u8; 4],
+    pub max: [u8; 4],
+    pub mean: [f32; 4],
+    pub transparent: usize,
+    pub opaque: usize,
+    pub pixel_count: usize,
+}
+
+impl TextureStats {
+    pub fn compute(image: &egui::ColorImage) -> Self {
+        let mut histograms = [[0u32; 256]; 4];
+        let mut min = [u8::MAX; 4];
+        let mut max = [0u8; 4];
+        let mut sums = [0u64; 4];
+        let mut transparent = 0;
+        let mut opaque = 0;
+
+        for pixel in &image.pixels {
+            let rgba = pixel.to_srgba_unmultiplied();
+            for channel in 0..4 {
+                let value = rgba[channel];
+                histograms[channel][value as usize] += 1;
+                min[channel] = min[channel].min(value);
+                max[channel] = max[channel].max(value);
+                sums[channel] += value as u64;
+            }
+            match rgba[3] {
+                0 => transparent += 1,
+                255 => opaque += 1,
+                _ => {}
+            }
+        }
+
+        let pixel_count = image.pixels.len();
+        if pixel_count == 0 {
+            min = [0; 4];
+        }
+        let mean = sums.map(|sum| sum as f32 / pixel_count.max(1) as f32);
+
+        Self { histograms, min, max, mean, transparent, opaque, pixel_count }
+    }
+
+    pub fn show_ui(&self, ui: &mut egui::Ui) {
+        const CHANNELS: [(&str, egui::Color32); 4] = [
+            ("R", egui::Color32::RED),
+            ("G", egui::Color32::GREEN),
+            ("B", egui::Color32::from_rgb(80, 120, 255)),
+            ("A", egui::Color32::GRAY),
+        ];
+
+        egui::Grid::new("texture_stats_grid").striped(true).show(ui, |ui| {
+            ui.strong("Channel");
+            ui.strong("Min");
+            ui.strong("Max");
+            ui.strong("Mean");
+            ui.end_row();
+            for (channel, (name, color)) in CHANNELS.iter().enumerate() {
+                ui.colored_label(*color, *name);
+                ui.label(self.min[channel].to_string());
+                ui.label(self.max[channel].to_string());
+                ui.label(format!("{:.1}", self.mean[channel]));
+                ui.end_row();
+            }
+        });
+
+        let total = self.pixel_count.max(1) as f32;
+        ui.label(format!(
+            "Alpha coverage: {:.1}% opaque, {:.1}% transparent, {:.1}% partial",
+            self.opaque as f32 * 100.0 / total,
+            self.transparent as f32 * 100.0 / total,
+            (self.pixel_count - self.opaque - self.transparent) as f32 * 100.0 / total
+        ));
+
+        egui_plot::Plot::new("texture_histogram_plot")
+            .height(160.0)
+            .legend(egui_plot::Legend::default())
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                for (channel, (name, color)) in CHANNELS.iter().enumerate() {
+                    let points: egui_plot::PlotPoints = self.histograms[channel]
+                        .iter()
+                        .enumerate()
+                        .map(|(value, count)| [value as f64, *count as f64])
+                        .collect();
+                    plot_ui.line(egui_plot::Line::new(points).color(*color).name(*name));
+                }
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareMode {
+    SideBySide,
+    Swipe,
+    Difference,
+}
+
+// A/B comparison of a loaded texture against a replacement
+struct TextureComparison {
+    original: usize,
+    replacement: TbodyTexture,
+    mode: CompareMode,
+    swipe: f32,
+    difference: Option<egui::TextureHandle>,
+    differing_pixels: usize,
+    max_difference: u8,
+}
+
+impl TextureComparison {
+    fn new(original: usize, original_texture: &TbodyTexture, replacement: TbodyTexture, ctx: &egui::Context) -> Self {
+        let mut comparison = Self {
+            original,
+            replacement,
+            mode: CompareMode::SideBySide,
+            swipe: 0.5,
+            difference: None,
+            differing_pixels: 0,
+            max_difference: 0,
+        };
+
+        let a = &original_texture.image;
+        let b = &comparison.replacement.image;
+        if a.size == b.size {
+            // Largest per-channel difference per pixel, mapped black -> red -> yellow
+            let pixels: Vec<egui::Color32> = a.pixels.iter().zip(&b.pixels).map(|(pa, pb)| {
+                let diff = (0..4).map(|i| pa[i].abs_diff(pb[i])).max().unwrap_or(0);
+                if diff > 0 {
+                    comparison.differing_pixels += 1;
+                }
+                comparison.max_difference = comparison.max_difference.max(diff);
+                let heat = diff as f32 / 255.0;
+                egui::Color32::from_rgb(
+                    ((heat * 2.0).min(1.0) * 255.0) as u8,
+                    (((heat - 0.5) * 2.0).clamp(0.0, 1.0) * 255.0) as u8,
+                    0,
+                )
+            }).collect();
+            let image = egui::ColorImage { size: a.size, pixels };
+            comparison.difference = Some(ctx.load_texture("texture_difference", image, Default::default()));
+        }
+
+        comparison
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TextureSort {
+    Manual,
+    Name,
+    Size,
+    Dimensions,
+}
+
+impl TextureSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextureSort::Manual => "Manual (drag ☰)",
+            TextureSort::Name => "Name",
+            TextureSort::Size => "File size",
+            TextureSort::Dimensions => "Dimensions",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TextureViewMode {
+    Grid,
+    List,
+    Detail,
+}
+
+// The parts of the texture grid layout worth remembering between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureViewSettings {
+    pub sort: TextureSort,
+    pub view_mode: TextureViewMode,
+    pub group_by_source: bool,
+}
+
+// Things clicked while drawing the textures, applied once drawing is done
+#[derive(Default)]
+struct TextureActions {
+    compare: Option<usize>,
+    select: Option<usize>,
+    focus: Option<usize>,
+    surface_change: Option<(usize, usize, bool)>,
+    // (dragged texture, texture it was dropped on)
+    reorder: Option<(usize, usize)>,
+    edit: Option<usize>,
+    detach: Option<usize>,
+}
+
 pub struct TbodyViewer {
     pub textures: Vec<TbodyTexture>,
+    comparison: Option<TextureComparison>,
+    selected: Option<(usize, TextureStats)>,
+    // Manual display order, indices into `textures`
+    order: Vec<usize>,
+    sort: TextureSort,
+    view_mode: TextureViewMode,
+    group_by_source: bool,
+    detail_index: usize,
+    edit_request: Option<usize>,
+    detach_request: Option<usize>,
+    pending: Vec<PendingTexture>,
+    decode_requests: Vec<DecodeRequest>,
+    decode_errors: Vec<String>,
+    // Clicking a texture's name reveals its file in the tree
+    pub selection: SelectionBus,
 }
 
 impl TbodyViewer {
     pub fn new() -> Self {
         Self {
             textures: Vec::new(),
+            comparison: None,
+            selected: None,
+            order: Vec::new(),
+            sort: TextureSort::Manual,
+            view_mode: TextureViewMode::Grid,
+            group_by_source: false,
+            detail_index: 0,
+            edit_request: None,
+            detach_request: None,
+            pending: Vec::new(),
+            decode_requests: Vec::new(),
+            decode_errors: Vec::new(),
+            selection: SelectionBus::default(),
         }
     }
 
-    pub fn load_texture(&mut self, file_path: &Path, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
-        let texture = TbodyTexture::load_from_file(file_path, ctx)?;
-        self.textures.push(texture);
-        Ok(())
+    // Adds a placeholder that is decoded once it becomes visible.
+    // `name` overrides the file name, since bound files may be named differently from the MTB reference.
+    pub fn queue_texture(&mut self, file_path: &Path, name: Option<String>, group: Option<String>) {
+        self.pending.push(PendingTexture {
+            name: name.unwrap_or_else(|| file_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()),
+            group,
+            file_path: file_path.to_path_buf(),
+            slot: Arc::new(Mutex::new(None)),
+            requested: false,
+        });
+    }
+
+    pub fn load_all(&mut self) {
+        for pending in &mut self.pending {
+            pending.request(&mut self.decode_requests);
+        }
+    }
+
+    pub fn take_decode_requests(&mut self) -> Vec<DecodeRequest> {
+        std::mem::take(&mut self.decode_requests)
+    }
+
+    pub fn is_decoding(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // Moves finished decodes into the texture list
+    fn collect_decoded(&mut self) {
+        let mut index = 0;
+        while index < self.pending.len() {
+            let Some(result) = self.pending[index].slot.lock().unwrap().take() else {
+                index += 1;
+                continue;
+            };
+            let pending = self.pending.remove(index);
+            match result {
+                Ok(mut texture) => {
+                    texture.name = pending.name;
+                    texture.group = pending.group;
+                    self.textures.push(texture);
+                }
+                Err(e) => {
+                    eprintln!("Failed to decode {}: {}", pending.name, e);
+                    self.decode_errors.push(format!("{}: {}", pending.name, e));
+                }
+            }
+        }
+    }
+
+    fn show_pending_header(&mut self, ui: &mut egui::Ui) {
+        let waiting = self.pending.iter().filter(|p| !p.requested).count();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} texture(s) loading, {} not loaded yet", self.pending.len() - waiting, waiting));
+            if waiting > 0 && ui.button("Load all").clicked() {
+                self.load_all();
+            }
+        });
+    }
+
+    fn show_pending_cards(&mut self, ui: &mut egui::Ui) {
+        self.show_pending_header(ui);
+        ui.horizontal_wrapped(|ui| {
+            for pending in &mut self.pending {
+                let response = ui.group(|ui| {
+                    ui.set_min_size(egui::Vec2::splat(120.0));
+                    ui.vertical(|ui| {
+                        if pending.requested {
+                            ui.spinner();
+                        } else {
+                            ui.weak("Not loaded");
+                        }
+                        ui.label(&pending.name);
+                    });
+                }).response;
+                if ui.is_rect_visible(response.rect) {
+                    pending.request(&mut self.decode_requests);
+                }
+            }
+        });
     }
 
     pub fn clear(&mut self) {
         self.textures.clear();
+        self.comparison = None;
+        self.selected = None;
+        self.order.clear();
+        self.detail_index = 0;
+        self.edit_request = None;
+        self.detach_request = None;
+        self.pending.clear();
+        self.decode_requests.clear();
+        self.decode_errors.clear();
     }
 
-    pub fn show_ui(&self, ui: &mut egui::Ui, available_size: egui::Vec2) {
-        if self.textures.is_empty() {
-            ui.label("No textures loaded");
+    pub fn view_settings(&self) -> TextureViewSettings {
+        TextureViewSettings {
+            sort: self.sort,
+            view_mode: self.view_mode,
+            group_by_source: self.group_by_source,
+        }
+    }
+
+    pub fn apply_view_settings(&mut self, settings: &TextureViewSettings) {
+        self.sort = settings.sort;
+        self.view_mode = settings.view_mode;
+        self.group_by_source = settings.group_by_source;
+    }
+
+    // Texture the user asked to edit in an external program
+    pub fn take_edit_request(&mut self) -> Option<usize> {
+        self.edit_request.take()
+    }
+
+    // Copy of the texture the user asked to pop out into its own window
+    pub fn take_detach_request(&mut self) -> Option<TbodyTexture> {
+        self.detach_request.take().and_then(|index| self.textures.get(index).cloned())
+    }
+
+    // Re-reads textures loaded from `path` after it changed on disk
+    pub fn reload_texture(&mut self, path: &Path, ctx: &egui::Context) {
+        for texture in self.textures.iter_mut().filter(|t| t.file_path == path) {
+            match TbodyTexture::load_from_file(path, ctx) {
+                Ok(mut reloaded) => {
+                    reloaded.name = texture.name.clone();
+                    reloaded.group = texture.group.clone();
+                    *texture = reloaded;
+                }
+                Err(e) => eprintln!("Failed to reload {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    // Keeps the manual order in step with textures added or removed since the last frame
+    fn sync_order(&mut self) {
+        let count = self.textures.len();
+        if self.order.len() == count && self.order.iter().all(|&i| i < count) {
             return;
         }
+        self.order.retain(|&i| i < count);
+        for index in 0..count {
+            if !self.order.contains(&index) {
+                self.order.push(index);
+            }
+        }
+    }
 
-        // Calculate layout based on available space and number of textures
-        let texture_count = self.textures.len();
-        let max_textures_per_row = (available_size.x / 200.0).max(1.0) as usize;
-        let textures_per_row = texture_count.min(max_textures_per_row);
-        let row_count = (texture_count + textures_per_row - 1) / textures_per_row;
-        
-        let texture_size = if textures_per_row > 0 {
-            (available_size.x / textures_per_row as f32 * 0.9).min(200.0)
+    fn sorted_indices(&self) -> Vec<usize> {
+        let mut indices = self.order.clone();
+        match self.sort {
+            TextureSort::Manual => {}
+            TextureSort::Name => indices.sort_by_key(|&i| self.textures[i].name.to_lowercase()),
+            TextureSort::Size => indices.sort_by_key(|&i| std::cmp::Reverse(self.textures[i].data_size)),
+            TextureSort::Dimensions => indices.sort_by_key(|&i| {
+                let (width, height) = self.textures[i].dimensions;
+                std::cmp::Reverse(width as u64 * height as u64)
+            }),
+        }
+        indices
+    }
+
+    // Groups keep the order their first texture appears in
+    fn grouped_indices(&self) -> Vec<(String, Vec<usize>)> {
+        let indices = self.sorted_indices();
+        if !self.group_by_source {
+            return vec![(String::new(), indices)];
+        }
+
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for index in indices {
+            let name = self.textures[index].group.clone().unwrap_or_else(|| "Loose textures".to_string());
+            match groups.iter_mut().find(|(group, _)| *group == name) {
+                Some((_, members)) => members.push(index),
+                None => groups.push((name, vec![index])),
+            }
+        }
+        groups
+    }
+
+    fn start_comparison(&mut self, index: usize, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Select replacement texture to compare")
+            .add_filter("Textures", &["tbody", "dds", "png", "tga", "bmp", "jpg"])
+            .pick_file() else {
+            return;
+        };
+
+        match TbodyTexture::load_any(&path, ctx) {
+            Ok(replacement) => {
+                self.comparison = Some(TextureComparison::new(index, &self.textures[index], replacement, ctx));
+            }
+            Err(e) => eprintln!("Failed to load comparison texture {}: {}", path.display(), e),
+        }
+    }
+
+    // Shows `original` compared against `replacement`, for the pinned A/B slots
+    pub fn compare_files(&mut self, original: &Path, replacement: &Path, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
+        self.clear();
+        let original = TbodyTexture::load_any(original, ctx)?;
+        let replacement = TbodyTexture::load_any(replacement, ctx)?;
+        self.comparison = Some(TextureComparison::new(0, &original, replacement, ctx));
+        self.textures.push(original);
+        Ok(())
+    }
+
+    fn show_comparison(&mut self, ui: &mut egui::Ui, available_size: egui::Vec2) {
+        let Some(comparison) = &mut self.comparison else {
+            return;
+        };
+        let Some(original) = self.textures.get(comparison.original) else {
+            self.comparison = None;
+            return;
+        };
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.heading("Compare");
+            ui.selectable_value(&mut comparison.mode, CompareMode::SideBySide, "Side by side");
+            ui.selectable_value(&mut comparison.mode, CompareMode::Swipe, "Swipe");
+            ui.add_enabled_ui(comparison.difference.is_some(), |ui| {
+                ui.selectable_value(&mut comparison.mode, CompareMode::Difference, "Difference");
+            });
+            close = ui.button("Close").clicked();
+        });
+
+        ui.label(format!(
+            "A: {} ({}x{})    B: {} ({}x{})",
+            original.name, original.dimensions.0, original.dimensions.1,
+            comparison.replacement.name, comparison.replacement.dimensions.0, comparison.replacement.dimensions.1
+        ));
+        if comparison.difference.is_some() {
+            let total = original.image.pixels.len().max(1);
+            ui.label(format!(
+                "{} of {} pixels differ ({:.2}%), max channel difference {}",
+                comparison.differing_pixels,
+                total,
+                comparison.differing_pixels as f32 * 100.0 / total as f32,
+                comparison.max_difference
+            ));
         } else {
-            200.0
+            ui.colored_label(egui::Color32::YELLOW, "Sizes differ, pixel difference is unavailable");
+        }
+
+        let (Some(a), Some(b)) = (&original.texture_handle, &comparison.replacement.texture_handle) else {
+            return;
+        };
+
+        let max_side = (available_size.x * 0.45).min(available_size.y * 0.8).max(64.0);
+        let fit = |dimensions: (u32, u32), max: f32| {
+            let scale = max / dimensions.0.max(dimensions.1).max(1) as f32;
+            egui::Vec2::new(dimensions.0 as f32 * scale, dimensions.1 as f32 * scale)
         };
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for row in 0..row_count {
+        match comparison.mode {
+            CompareMode::SideBySide => {
                 ui.horizontal(|ui| {
-                    for col in 0..textures_per_row {
-                        let index = row * textures_per_row + col;
-                        if index >= self.textures.len() {
-                            break;
-                        }
+                    ui.add(egui::Image::new(a).fit_to_exact_size(fit(original.dimensions, max_side)));
+                    ui.add(egui::Image::new(b).fit_to_exact_size(fit(comparison.replacement.dimensions, max_side)));
+                });
+            }
+            CompareMode::Swipe => {
+                ui.add(egui::Slider::new(&mut comparison.swipe, 0.0..=1.0).text("Divider"));
+                let size = fit(original.dimensions, max_side * 2.0);
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    comparison.swipe = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                }
+
+                // A on the left of the divider, B on the right, both stretched over the same rect
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                let split_x = rect.left() + rect.width() * comparison.swipe;
+                let mut left = rect;
+                left.set_right(split_x);
+                let mut right = rect;
+                right.set_left(split_x);
+                ui.painter().with_clip_rect(left).image(a.id(), rect, uv, egui::Color32::WHITE);
+                ui.painter().with_clip_rect(right).image(b.id(), rect, uv, egui::Color32::WHITE);
+                ui.painter().line_segment(
+                    [egui::pos2(split_x, rect.top()), egui::pos2(split_x, rect.bottom())],
+                    (2.0, egui::Color32::WHITE),
+                );
+            }
+            CompareMode::Difference => {
+                if let Some(difference) = &comparison.difference {
+                    ui.add(egui::Image::new(difference).fit_to_exact_size(fit(original.dimensions, max_side * 2.0)));
+                }
+            }
+        }
+
+        if close {
+            self.comparison = None;
+        }
+        ui.separator();
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, available_size: egui::Vec2) {
+        self.collect_decoded();
 
-                        let texture = &self.textures[index];
-                        ui.vertical(|ui| {
-                            // Show texture name
-                            ui.label(&texture.name);
-                            
-                            // Show texture
-                            if let Some(texture_handle) = &texture.texture_handle {
-                                let display_size = egui::Vec2::splat(texture_size);
-                                ui.add(egui::Image::new(texture_handle)
-                                    .max_size(display_size)
-                                    .maintain_aspect_ratio(true));
-                            } else {
-                                ui.label("Failed to load texture");
-                            }
-                            
-                            // Show dimensions
-                            ui.label(format!("{}x{}", texture.dimensions.0, texture.dimensions.1));
-                        });
+        if !self.decode_errors.is_empty() {
+            egui::CollapsingHeader::new(format!("Failed to decode ({})", self.decode_errors.len()))
+                .id_source("texture_decode_errors")
+                .show(ui, |ui| {
+                    for error in &self.decode_errors {
+                        ui.colored_label(egui::Color32::LIGHT_RED, error);
                     }
                 });
+        }
+
+        if self.textures.is_empty() {
+            if self.pending.is_empty() {
+                ui.label("No textures loaded");
+            } else {
+                egui::ScrollArea::vertical().show(ui, |ui| self.show_pending_cards(ui));
+            }
+            return;
+        }
+
+        self.show_comparison(ui, available_size);
+
+        if let Some((index, stats)) = &self.selected {
+            if let Some(texture) = self.textures.get(*index) {
+                let mut close = false;
+                ui.horizontal(|ui| {
+                    ui.heading(format!("Statistics: {}", texture.name));
+                    close = ui.button("Close").clicked();
+                });
+                stats.show_ui(ui);
+                ui.separator();
+                if close {
+                    self.selected = None;
+                }
+            }
+        }
+
+        self.sync_order();
+
+        ui.horizontal(|ui| {
+            ui.label("View:");
+            ui.selectable_value(&mut self.view_mode, TextureViewMode::Grid, "Grid");
+            ui.selectable_value(&mut self.view_mode, TextureViewMode::List, "List");
+            ui.selectable_value(&mut self.view_mode, TextureViewMode::Detail, "Detail");
+            ui.separator();
+            ui.label("Sort:");
+            egui::ComboBox::from_id_source("texture_sort")
+                .selected_text(self.sort.as_str())
+                .show_ui(ui, |ui| {
+                    for sort in [TextureSort::Manual, TextureSort::Name, TextureSort::Size, TextureSort::Dimensions] {
+                        ui.selectable_value(&mut self.sort, sort, sort.as_str());
+                    }
+                });
+            ui.checkbox(&mut self.group_by_source, "Group by MTB");
+        });
+        ui.separator();
+
+        let mut actions = TextureActions::default();
+        let groups = self.grouped_indices();
+
+        if self.view_mode == TextureViewMode::Detail {
+            if !self.pending.is_empty() {
+                self.show_pending_header(ui);
+            }
+            let indices: Vec<usize> = groups.into_iter().flat_map(|(_, members)| members).collect();
+            self.show_detail(ui, available_size, &indices, &mut actions);
+        } else {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (name, members) in &groups {
+                    if name.is_empty() {
+                        self.show_group(ui, available_size, members, &mut actions);
+                    } else {
+                        egui::CollapsingHeader::new(format!("{} ({})", name, members.len()))
+                            .id_source(("texture_group", name))
+                            .default_open(true)
+                            .show(ui, |ui| self.show_group(ui, available_size, members, &mut actions));
+                    }
+                }
+                if !self.pending.is_empty() {
+                    ui.separator();
+                    self.show_pending_cards(ui);
+                }
+            });
+        }
+
+        if let Some((index, current, cross)) = actions.surface_change {
+            let texture = &mut self.textures[index];
+            texture.current_surface = current;
+            texture.show_cross = cross;
+        }
+
+        if let Some((dragged, target)) = actions.reorder {
+            if let (Some(from), Some(to)) = (
+                self.order.iter().position(|&i| i == dragged),
+                self.order.iter().position(|&i| i == target),
+            ) {
+                let moved = self.order.remove(from);
+                self.order.insert(to, moved);
+            }
+        }
+
+        if let Some(index) = actions.focus {
+            self.detail_index = index;
+            self.view_mode = TextureViewMode::Detail;
+        }
+
+        if let Some(index) = actions.select {
+            self.selected = Some((index, TextureStats::compute(&self.textures[index].image)));
+            self.selection.publish(&self.textures[index].file_path);
+        }
+
+        if let Some(index) = actions.compare {
+            self.start_comparison(index, ui.ctx());
+        }
+
+        if actions.edit.is_some() {
+            self.edit_request = actions.edit;
+        }
+
+        if actions.detach.is_some() {
+            self.detach_request = actions.detach;
+        }
+    }
+
+    fn show_group(&self, ui: &mut egui::Ui, available_size: egui::Vec2, indices: &[usize], actions: &mut TextureActions) {
+        match self.view_mode {
+            TextureViewMode::List => {
+                for &index in indices {
+                    self.show_list_row(ui, index, actions);
+                }
+            }
+            _ => {
+                // Calculate layout based on available space and number of textures
+                let max_textures_per_row = (available_size.x / 200.0).max(1.0) as usize;
+                let textures_per_row = indices.len().min(max_textures_per_row).max(1);
+                let texture_size = (available_size.x / textures_per_row as f32 * 0.9).min(200.0);
+
+                for row in indices.chunks(textures_per_row) {
+                    ui.horizontal(|ui| {
+                        for &index in row {
+                            self.show_card(ui, index, texture_size, actions);
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    // Drag handle for manual ordering, and highlights the texture a drag would land on
+    fn show_reorder_handle(&self, ui: &mut egui::Ui, index: usize) {
+        if self.sort == TextureSort::Manual {
+            ui.dnd_drag_source(egui::Id::new(("texture_drag", index)), index, |ui| {
+                ui.label("☰");
+            });
+        }
+    }
+
+    fn check_drop(&self, response: &egui::Response, index: usize, actions: &mut TextureActions) {
+        if self.sort != TextureSort::Manual {
+            return;
+        }
+        if response.dnd_hover_payload::<usize>().map_or(false, |dragged| *dragged != index) {
+            response.ctx.debug_painter().rect_stroke(response.rect, 2.0, (2.0, egui::Color32::LIGHT_BLUE));
+        }
+        if let Some(dragged) = response.dnd_release_payload::<usize>() {
+            actions.reorder = Some((*dragged, index));
+        }
+    }
+
+    // Showing statistics, or the file selected in the tree
+    fn is_highlighted(&self, index: usize) -> bool {
+        self.selected.as_ref().map_or(false, |(i, _)| *i == index) || self.selection.is_current(&self.textures[index].file_path)
+    }
+
+    fn show_card(&self, ui: &mut egui::Ui, index: usize, texture_size: f32, actions: &mut TextureActions) {
+        let texture = &self.textures[index];
+        let response = ui.vertical(|ui| {
+            // Show texture name, clicking it shows statistics
+            ui.horizontal(|ui| {
+                self.show_reorder_handle(ui, index);
+                if ui.selectable_label(self.is_highlighted(index), &texture.name).clicked() {
+                    actions.select = Some(index);
+                }
+            });
+
+            // Show texture, double click opens it in the detail view
+            if texture.show_cross {
+                texture.show_cube_cross(ui, texture_size);
+            } else if let Some(texture_handle) = texture.display_handle() {
+                let display_size = egui::Vec2::splat(texture_size);
+                let image = ui.add(egui::Image::new(texture_handle)
+                    .max_size(display_size)
+                    .maintain_aspect_ratio(true)
+                    .sense(egui::Sense::click()));
+                if image.double_clicked() {
+                    actions.focus = Some(index);
+                }
+            } else {
+                ui.label("Failed to load texture");
+            }
+
+            // Show dimensions
+            ui.label(format!("{}x{}", texture.dimensions.0, texture.dimensions.1));
+
+            self.show_surface_controls(ui, index, actions);
+
+            ui.horizontal(|ui| {
+                if ui.small_button("Compare...").clicked() {
+                    actions.compare = Some(index);
+                }
+                if ui.small_button("Edit externally...").clicked() {
+                    actions.edit = Some(index);
+                }
+                if ui.small_button("Pop out").on_hover_text("Show this texture in a window of its own").clicked() {
+                    actions.detach = Some(index);
+                }
+            });
+        }).response;
+        self.check_drop(&response, index, actions);
+    }
+
+    fn show_list_row(&self, ui: &mut egui::Ui, index: usize, actions: &mut TextureActions) {
+        let texture = &self.textures[index];
+        let response = ui.horizontal(|ui| {
+            self.show_reorder_handle(ui, index);
+            if let Some(texture_handle) = texture.display_handle() {
+                let image = ui.add(egui::Image::new(texture_handle)
+                    .max_size(egui::Vec2::splat(48.0))
+                    .maintain_aspect_ratio(true)
+                    .sense(egui::Sense::click()));
+                if image.double_clicked() {
+                    actions.focus = Some(index);
+                }
+            }
+            if ui.selectable_label(self.is_highlighted(index), &texture.name).clicked() {
+                actions.select = Some(index);
+            }
+            ui.label(format!("{}x{}", texture.dimensions.0, texture.dimensions.1));
+            ui.label(format!("{:.1} KB", texture.data_size as f64 / 1024.0));
+            if let Some(layout) = &texture.layout {
+                ui.label(if layout.is_cubemap { "Cubemap" } else { "Array" });
+            }
+            if ui.small_button("Compare...").clicked() {
+                actions.compare = Some(index);
+            }
+            if ui.small_button("Edit externally...").clicked() {
+                actions.edit = Some(index);
+            }
+            if ui.small_button("Pop out").on_hover_text("Show this texture in a window of its own").clicked() {
+                actions.detach = Some(index);
+            }
+        }).response;
+        self.check_drop(&response, index, actions);
+    }
+
+    fn show_surface_controls(&self, ui: &mut egui::Ui, index: usize, actions: &mut TextureActions) {
+        let texture = &self.textures[index];
+        let (Some(layout), false) = (&texture.layout, texture.surfaces.is_empty()) else {
+            return;
+        };
+
+        let count = texture.surfaces.len();
+        ui.horizontal(|ui| {
+            let mut current = texture.current_surface;
+            let mut cross = texture.show_cross;
+            if ui.add_enabled(!cross && current > 0, egui::Button::new("◀").small()).clicked() {
+                current -= 1;
+            }
+            ui.small(if cross {
+                format!("{} faces", count)
+            } else {
+                format!("{} ({}/{})", layout.surface_label(current), current + 1, count)
+            });
+            if ui.add_enabled(!cross && current + 1 < count, egui::Button::new("▶").small()).clicked() {
+                current += 1;
+            }
+            if layout.is_cubemap && count >= 6 {
+                ui.checkbox(&mut cross, "Cross");
+            }
+            if current != texture.current_surface || cross != texture.show_cross {
+                actions.surface_change = Some((index, current, cross));
             }
         });
     }
+
+    // One large preview with a filmstrip of every texture underneath
+    fn show_detail(&self, ui: &mut egui::Ui, available_size: egui::Vec2, indices: &[usize], actions: &mut TextureActions) {
+        let current = if indices.contains(&self.detail_index) {
+            self.detail_index
+        } else {
+            indices[0]
+        };
+        let position = indices.iter().position(|&i| i == current).unwrap_or(0);
+        let texture = &self.textures[current];
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(position > 0, egui::Button::new("◀")).clicked() {
+                actions.focus = Some(indices[position - 1]);
+            }
+            ui.label(format!("{}/{}", position + 1, indices.len()));
+            if ui.add_enabled(position + 1 < indices.len(), egui::Button::new("▶")).clicked() {
+                actions.focus = Some(indices[position + 1]);
+            }
+            ui.strong(&texture.name);
+            ui.label(format!("{}x{}, {:.1} KB", texture.dimensions.0, texture.dimensions.1, texture.data_size as f64 / 1024.0));
+            if ui.small_button("Statistics").clicked() {
+                actions.select = Some(current);
+            }
+            if ui.small_button("Compare...").clicked() {
+                actions.compare = Some(current);
+            }
+            if ui.small_button("Pop out").on_hover_text("Show this texture in a window of its own").clicked() {
+                actions.detach = Some(current);
+            }
+        });
+        self.show_surface_controls(ui, current, actions);
+
+        let filmstrip_height = 96.0;
+        let preview_side = (available_size.y - filmstrip_height - 80.0).min(available_size.x).max(64.0);
+        if texture.show_cross {
+            texture.show_cube_cross(ui, preview_side);
+        } else if let Some(texture_handle) = texture.display_handle() {
+            ui.add(egui::Image::new(texture_handle)
+                .max_size(egui::Vec2::splat(preview_side))
+                .maintain_aspect_ratio(true));
+        }
+
+        ui.separator();
+        egui::ScrollArea::horizontal()
+            .id_source("texture_filmstrip")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for &index in indices {
+                        let Some(texture_handle) = self.textures[index].display_handle() else {
+                            continue;
+                        };
+                        let response = ui.add(egui::ImageButton::new(
+                            egui::Image::new(texture_handle).fit_to_exact_size(egui::Vec2::splat(filmstrip_height - 16.0)),
+                        ).selected(index == current))
+                            .on_hover_text(&self.textures[index].name);
+                        if response.clicked() {
+                            actions.focus = Some(index);
+                        }
+                    }
+                });
+            });
+    }
 }
\ No newline at end of file
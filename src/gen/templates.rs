@@ -0,0 +1,97 @@
+use binrw::Endian;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use super::read_scene::{ContainerData, Data, SceneFileHandler};
+
+// Starting points offered by "New file" in the tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Template {
+    LuaScript,
+    OctScene,
+    Material,
+}
+
+impl Template {
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            Template::LuaScript => "new_script.lua",
+            Template::OctScene => "new_scene.oct",
+            Template::Material => "new_material.mtb",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Template::LuaScript => "lua",
+            Template::OctScene => "oct",
+            Template::Material => "mtb",
+        }
+    }
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+pub fn write_lua_script(path: &Path) -> std::io::Result<()> {
+    let script = format!("-- {}\n\nlocal M = {{}}\n\nreturn M\n", stem(path));
+    std::fs::write(path, script)
+}
+
+// One named container with an identity transform, enough for the scene viewer to open and for
+// nodes to be pasted into
+pub fn write_oct_scene(path: &Path, endian: Endian) -> anyhow::Result<()> {
+    let mut node = IndexMap::new();
+    node.insert("Name".to_string(), ContainerData::Single(Data::String(stem(path))));
+    node.insert("Position".to_string(), ContainerData::Single(Data::FloatVec(vec![0.0; 3])));
+    node.insert("Rotation".to_string(), ContainerData::Single(Data::FloatVec(vec![0.0, 0.0, 0.0, 1.0])));
+    node.insert("Scale".to_string(), ContainerData::Single(Data::FloatVec(vec![1.0; 3])));
+
+    let mut scene = IndexMap::new();
+    scene.insert(format!("Node#{}", stem(path)), ContainerData::Single(Data::Container(node)));
+    SceneFileHandler::from_scene("Scene", scene, endian).save_scene_file(path)
+}
+
+// Texture ids are the 16 hex digit names the MTB reader turns back into "<id>.tbody"
+pub fn texture_id(path: &Path) -> Option<[u8; 8]> {
+    let stem = stem(path);
+    if stem.len() != 16 {
+        return None;
+    }
+    let mut id = [0u8; 8];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(stem.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(id)
+}
+
+// A TEXB section without MATP, the layout of UI materials: counts, section size, material name,
+// then an 8 byte id per texture
+pub fn write_mtb(path: &Path, textures: &[PathBuf], big_endian: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ids = Vec::new();
+    for texture in textures {
+        let id = texture_id(texture).ok_or_else(|| format!("{} isn't named by a 16 digit texture id", texture.display()))?;
+        ids.push(id);
+    }
+
+    let u32_bytes = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+    let name = stem(path);
+    let mut body = Vec::new();
+    body.extend_from_slice(&u32_bytes(ids.len() as u32));
+    body.extend_from_slice(&u32_bytes(name.len() as u32));
+    body.extend_from_slice(name.as_bytes());
+    // "TEXB", the count and the size come before the body
+    while (12 + body.len()) % 4 != 0 {
+        body.push(0);
+    }
+    for id in &ids {
+        body.extend_from_slice(id);
+    }
+
+    let mut data = b"TEXB".to_vec();
+    data.extend_from_slice(&u32_bytes(ids.len() as u32));
+    data.extend_from_slice(&u32_bytes(body.len() as u32));
+    data.extend_from_slice(&body);
+    std::fs::write(path, data)?;
+    Ok(())
+}
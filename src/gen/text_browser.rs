@@ -0,0 +1,220 @@
+use eframe::egui;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+// Shortest run of characters kept as a string
+const MIN_STRING_CHARS: usize = 4;
+
+// Language codes looked for in file and folder names, e.g. "strings_fr.dct" or "en_us/ui.dct"
+const LANGUAGE_CODES: [&str; 22] = [
+    "en", "en_us", "en_gb", "fr", "de", "es", "it", "nl", "pt", "pt_br", "ru", "pl", "sv", "da", "no", "fi", "ja", "ko", "zh", "zh_tw", "cs", "tr",
+];
+
+// One string found in a text bank
+#[derive(Debug, Clone)]
+pub struct TextEntry {
+    // File the string came from, as "<archive>/<entry>" for archive entries
+    pub source: String,
+    pub language: String,
+    pub offset: u64,
+    pub text: String,
+}
+
+// Language from the last name component that's a known code, so a file's own name wins over its
+// folders; "?" when there's none
+pub fn language_of(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let stem = lower.rsplit_once('.').map_or(lower.as_str(), |(stem, _)| stem);
+    let parts: Vec<&str> = stem.split(['/', '\\', '_', '-', '.']).collect();
+    // Two-part codes such as en_us first, so they aren't read as plain "en"
+    for pair in parts.windows(2).rev() {
+        let code = format!("{}_{}", pair[0], pair[1]);
+        if LANGUAGE_CODES.contains(&code.as_str()) {
+            return code;
+        }
+    }
+    parts.iter().rev().find(|part| LANGUAGE_CODES.contains(part)).map_or("?".to_string(), |code| code.to_string())
+}
+
+fn is_text_char(c: char) -> bool {
+    !c.is_control() || c == '\n' || c == '\t'
+}
+
+fn keep(text: &str, chars: usize) -> bool {
+    chars >= MIN_STRING_CHARS && text.chars().any(char::is_alphabetic)
+}
+
+// The DCT layout isn't known yet, so strings are pulled out the way a strings dump would: runs
+// of UTF-8, and runs of UTF-16LE, which localized text is usually stored as.
+pub fn extract_strings(data: &[u8]) -> Vec<(u64, String)> {
+    let mut found = Vec::new();
+    // Byte ranges of the UTF-8 runs
+    let mut utf8_runs = Vec::new();
+    let mut position = 0;
+    while position < data.len() {
+        let run = data[position..].iter().position(|&b| b == 0 || (b < 0x20 && b != b'\n' && b != b'\t')).unwrap_or(data.len() - position);
+        if let Ok(text) = std::str::from_utf8(&data[position..position + run]) {
+            if keep(text, text.chars().count()) {
+                found.push((position as u64, text.trim().to_string()));
+                utf8_runs.push(position..position + run);
+            }
+        }
+        position += run + 1;
+    }
+
+    // Plain ASCII also decodes as UTF-16 (to CJK characters), so runs that are mostly UTF-8 text
+    // are left to the pass above. Latin text in UTF-16 has a zero in every other byte, so it
+    // never forms a UTF-8 run.
+    let mut push = |start: usize, end: usize, text: &mut String, chars: usize| {
+        let covered: usize = utf8_runs.iter().map(|run| run.end.min(end).saturating_sub(run.start.max(start))).sum();
+        if keep(text, chars) && covered * 2 <= end - start {
+            found.push((start as u64, text.trim().to_string()));
+        }
+        text.clear();
+    };
+    // Offsets count 16-bit units, two bytes each; a surrogate pair takes two of them
+    let (mut text, mut start, mut chars, mut offset) = (String::new(), 0, 0, 0);
+    let units = data.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+    for decoded in char::decode_utf16(units) {
+        let width = decoded.as_ref().map_or(2, |c| c.len_utf16() * 2);
+        match decoded {
+            Ok(c) if is_text_char(c) && c != '\0' => {
+                if chars == 0 {
+                    start = offset;
+                }
+                text.push(c);
+                chars += 1;
+            }
+            _ => {
+                push(start, offset, &mut text, chars);
+                chars = 0;
+            }
+        }
+        offset += width;
+    }
+    push(start, offset, &mut text, chars);
+
+    found.sort_by_key(|(offset, _)| *offset);
+    found
+}
+
+pub fn entries_from(source: &str, data: &[u8]) -> Vec<TextEntry> {
+    let language = language_of(source);
+    extract_strings(data)
+        .into_iter()
+        .map(|(offset, text)| TextEntry { source: source.to_string(), language: language.clone(), offset, text })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(entries: &[&TextEntry]) -> String {
+    let mut csv = String::from("language,source,offset,text\n");
+    for entry in entries {
+        csv.push_str(&format!("{},{},{},{}\n", csv_field(&entry.language), csv_field(&entry.source), entry.offset, csv_field(&entry.text)));
+    }
+    csv
+}
+
+pub enum TextBrowserAction {
+    Rescan,
+    // The rows passing the search and language filter
+    ExportCsv(String),
+}
+
+// Every string of the game's text banks in one searchable list
+#[derive(Default)]
+pub struct TextBrowser {
+    pub entries: Vec<TextEntry>,
+    pub scanning: bool,
+    search: String,
+    hidden_languages: BTreeSet<String>,
+}
+
+impl TextBrowser {
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) -> Option<TextBrowserAction> {
+        let mut action = None;
+        let languages: BTreeSet<&str> = self.entries.iter().map(|e| e.language.as_str()).collect();
+        let sources: BTreeSet<&str> = self.entries.iter().map(|e| e.source.as_str()).collect();
+        ui.horizontal(|ui| {
+            if self.scanning {
+                ui.spinner();
+                ui.label("Reading text banks...");
+            } else {
+                ui.label(format!("{} strings in {} files", self.entries.len(), sources.len()));
+                if ui.button("Rescan").clicked() {
+                    action = Some(TextBrowserAction::Rescan);
+                }
+            }
+        });
+        ui.weak("Strings are extracted as UTF-16 and UTF-8 runs until the DCT layout is decoded, so some rows may be noise.");
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Text in any language"));
+        });
+        ui.horizontal_wrapped(|ui| {
+            for language in &languages {
+                let mut shown = !self.hidden_languages.contains(*language);
+                if ui.checkbox(&mut shown, *language).changed() {
+                    if shown {
+                        self.hidden_languages.remove(*language);
+                    } else {
+                        self.hidden_languages.insert(language.to_string());
+                    }
+                }
+            }
+        });
+
+        let search = self.search.to_lowercase();
+        let rows: Vec<&TextEntry> = self.entries.iter()
+            .filter(|e| !self.hidden_languages.contains(&e.language))
+            .filter(|e| search.is_empty() || e.text.to_lowercase().contains(&search))
+            .collect();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} shown", rows.len()));
+            if ui.add_enabled(!rows.is_empty(), egui::Button::new("Export CSV...")).clicked() {
+                action = Some(TextBrowserAction::ExportCsv(to_csv(&rows)));
+            }
+        });
+        ui.separator();
+
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical().id_source("text_rows").auto_shrink([false, false]).show_rows(ui, row_height, rows.len(), |ui, range| {
+            egui::Grid::new("text_grid").striped(true).num_columns(3).show(ui, |ui| {
+                for entry in &rows[range] {
+                    ui.label(&entry.language);
+                    ui.label(Path::new(&entry.source).file_name().map_or(entry.source.clone(), |n| n.to_string_lossy().into_owned()))
+                        .on_hover_text(format!("{} at 0x{:x}", entry.source, entry.offset));
+                    ui.add(egui::Label::new(&entry.text).truncate(true));
+                    ui.end_row();
+                }
+            });
+        });
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn utf16_offsets_count_surrogate_pairs_as_two_units() {
+        // The emoji is a surrogate pair; the control character ends the first run
+        let mut data = utf16("Hi 😀 there\u{1}");
+        let second = data.len();
+        data.extend(utf16("Second string"));
+        let found = extract_strings(&data);
+        assert_eq!(found, vec![(0, "Hi 😀 there".to_string()), (second as u64, "Second string".to_string())]);
+    }
+}
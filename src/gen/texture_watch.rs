@@ -0,0 +1,149 @@
+use eframe::egui;
+use image::ImageFormat;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use super::dds::{self, DdsLayout};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// A PNG exported for editing and the .tbody it gets written back into
+pub struct TextureWatch {
+    pub png_path: PathBuf,
+    pub tbody_path: PathBuf,
+    // When off, changes are only flagged instead of written into the .tbody
+    pub reinject: bool,
+    pub pending_change: bool,
+    pub status: String,
+    last_modified: Option<SystemTime>,
+}
+
+pub struct TextureWatcher {
+    pub watches: Vec<TextureWatch>,
+    last_poll: Instant,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Converts the PNG back to DXT in the same flavor and mip count as the original, rotating .bak copies
+pub fn reinject_png(png_path: &Path, tbody_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let original = std::fs::read(tbody_path)?;
+    let layout = DdsLayout::parse(&original).ok_or("Original texture is not a supported DDS")?;
+    if layout.surface_count() > 1 {
+        return Err("Cubemaps and texture arrays can't be reinjected from a single PNG".into());
+    }
+    // Only the formats encode_dxt writes; anything else would change what the game expects
+    let with_alpha = match original.get(84..88) {
+        Some(b"DXT1") => false,
+        Some(b"DXT5") => true,
+        Some(four_cc) => {
+            let format = if four_cc == [0; 4] { "uncompressed".to_string() } else { String::from_utf8_lossy(four_cc).into_owned() };
+            return Err(format!("Only DXT1 and DXT5 textures can be reinjected, this one is {}", format).into());
+        }
+        None => return Err("Original texture is not a supported DDS".into()),
+    };
+
+    let image = image::load_from_memory_with_format(&std::fs::read(png_path)?, ImageFormat::Png)?.to_rgba8();
+    let encoded = dds::encode_dxt(&image, with_alpha, layout.mip_count);
+
+    super::safe_write::write(tbody_path, &encoded)?;
+
+    let mut status = format!("Reinjected as {} ({}x{})", if with_alpha { "DXT5" } else { "DXT1" }, image.width(), image.height());
+    if (image.width(), image.height()) != (layout.width, layout.height) {
+        status.push_str(&format!(", size changed from {}x{}", layout.width, layout.height));
+    }
+    Ok(status)
+}
+
+impl TextureWatcher {
+    pub fn new() -> Self {
+        Self {
+            watches: Vec::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn add(&mut self, png_path: PathBuf, tbody_path: PathBuf) {
+        self.watches.retain(|w| w.png_path != png_path);
+        println!("Watching {} for changes to {}", png_path.display(), tbody_path.display());
+        self.watches.push(TextureWatch {
+            last_modified: modified_time(&png_path),
+            png_path,
+            tbody_path,
+            reinject: true,
+            pending_change: false,
+            status: "Waiting for changes".to_string(),
+        });
+    }
+
+    // Returns the .tbody files that were rewritten so open viewers can reload them
+    pub fn poll(&mut self, ctx: &egui::Context) -> Vec<PathBuf> {
+        if self.watches.is_empty() {
+            return Vec::new();
+        }
+        ctx.request_repaint_after(POLL_INTERVAL);
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut updated = Vec::new();
+        for watch in &mut self.watches {
+            let modified = modified_time(&watch.png_path);
+            if modified.is_none() || modified == watch.last_modified {
+                continue;
+            }
+            watch.last_modified = modified;
+
+            if !watch.reinject {
+                watch.pending_change = true;
+                watch.status = "Changed on disk, not reinjected".to_string();
+                continue;
+            }
+
+            match reinject_png(&watch.png_path, &watch.tbody_path) {
+                Ok(status) => {
+                    println!("{}: {}", watch.tbody_path.display(), status);
+                    watch.pending_change = false;
+                    watch.status = status;
+                    updated.push(watch.tbody_path.clone());
+                }
+                // Editors often save in several steps, the next change retries
+                Err(e) => watch.status = format!("Reinject failed: {}", e),
+            }
+        }
+        updated
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) -> Vec<PathBuf> {
+        let mut updated = Vec::new();
+        let mut remove = None;
+        for (index, watch) in self.watches.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(watch.png_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+                ui.label("→");
+                ui.monospace(watch.tbody_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+                ui.checkbox(&mut watch.reinject, "Auto reinject");
+                if watch.pending_change && ui.small_button("Reinject now").clicked() {
+                    match reinject_png(&watch.png_path, &watch.tbody_path) {
+                        Ok(status) => {
+                            watch.pending_change = false;
+                            watch.status = status;
+                            updated.push(watch.tbody_path.clone());
+                        }
+                        Err(e) => watch.status = format!("Reinject failed: {}", e),
+                    }
+                }
+                if ui.small_button("Stop").clicked() {
+                    remove = Some(index);
+                }
+            });
+            ui.small(&watch.status);
+        }
+        if let Some(index) = remove {
+            self.watches.remove(index);
+        }
+        updated
+    }
+}
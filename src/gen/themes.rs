@@ -0,0 +1,276 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Reading the preference is cheap on Windows but spawns a process elsewhere
+const SYSTEM_THEME_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Theme files are JSON; every field is optional and falls back to the base dark or light style.
+// Colors are "#rrggbb" strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeFile {
+    pub name: String,
+    pub dark: bool,
+    pub accent: Option<String>,
+    pub text: Option<String>,
+    pub panel_fill: Option<String>,
+    pub window_fill: Option<String>,
+    pub extreme_bg: Option<String>,
+    pub faint_bg: Option<String>,
+    pub widget_fill: Option<String>,
+    pub hovered_fill: Option<String>,
+    pub warning: Option<String>,
+    pub error: Option<String>,
+    pub rounding: Option<f32>,
+}
+
+pub fn parse_color(text: &str) -> Option<egui::Color32> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(egui::Color32::from_rgb((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+pub fn format_color(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+// Tints selections, hyperlinks and the active widget outline
+pub fn apply_accent(visuals: &mut egui::Visuals, accent: egui::Color32) {
+    visuals.selection.bg_fill = accent.linear_multiply(if visuals.dark_mode { 0.6 } else { 0.4 });
+    visuals.selection.stroke.color = accent;
+    visuals.hyperlink_color = accent;
+    visuals.widgets.active.bg_stroke.color = accent;
+    visuals.widgets.hovered.bg_stroke.color = accent;
+}
+
+impl ThemeFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "High contrast".to_string(),
+            dark: true,
+            accent: Some("#ffd700".to_string()),
+            text: Some("#ffffff".to_string()),
+            panel_fill: Some("#000000".to_string()),
+            window_fill: Some("#000000".to_string()),
+            extreme_bg: Some("#000000".to_string()),
+            faint_bg: Some("#1a1a1a".to_string()),
+            widget_fill: Some("#202020".to_string()),
+            hovered_fill: Some("#404040".to_string()),
+            warning: Some("#ffd700".to_string()),
+            error: Some("#ff4040".to_string()),
+            rounding: Some(0.0),
+        }
+    }
+
+    pub fn solarized_dark() -> Self {
+        Self {
+            name: "Solarized dark".to_string(),
+            dark: true,
+            accent: Some("#268bd2".to_string()),
+            text: Some("#93a1a1".to_string()),
+            panel_fill: Some("#002b36".to_string()),
+            window_fill: Some("#073642".to_string()),
+            extreme_bg: Some("#00212b".to_string()),
+            faint_bg: Some("#073642".to_string()),
+            widget_fill: Some("#0a4351".to_string()),
+            hovered_fill: Some("#125566".to_string()),
+            warning: Some("#b58900".to_string()),
+            error: Some("#dc322f".to_string()),
+            rounding: None,
+        }
+    }
+
+    pub fn solarized_light() -> Self {
+        Self {
+            name: "Solarized light".to_string(),
+            dark: false,
+            accent: Some("#268bd2".to_string()),
+            text: Some("#586e75".to_string()),
+            panel_fill: Some("#fdf6e3".to_string()),
+            window_fill: Some("#eee8d5".to_string()),
+            extreme_bg: Some("#fffbf0".to_string()),
+            faint_bg: Some("#eee8d5".to_string()),
+            widget_fill: Some("#e6dfc8".to_string()),
+            hovered_fill: Some("#d9d2bb".to_string()),
+            warning: Some("#b58900".to_string()),
+            error: Some("#dc322f".to_string()),
+            rounding: None,
+        }
+    }
+
+    pub fn visuals(&self) -> egui::Visuals {
+        let mut visuals = if self.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+        let color = |value: &Option<String>| value.as_deref().and_then(parse_color);
+
+        if let Some(text) = color(&self.text) {
+            visuals.override_text_color = Some(text);
+        }
+        if let Some(fill) = color(&self.panel_fill) {
+            visuals.panel_fill = fill;
+        }
+        if let Some(fill) = color(&self.window_fill) {
+            visuals.window_fill = fill;
+        }
+        if let Some(fill) = color(&self.extreme_bg) {
+            visuals.extreme_bg_color = fill;
+        }
+        if let Some(fill) = color(&self.faint_bg) {
+            visuals.faint_bg_color = fill;
+        }
+        if let Some(fill) = color(&self.widget_fill) {
+            visuals.widgets.inactive.bg_fill = fill;
+            visuals.widgets.inactive.weak_bg_fill = fill;
+            visuals.widgets.noninteractive.weak_bg_fill = fill;
+        }
+        if let Some(fill) = color(&self.hovered_fill) {
+            visuals.widgets.hovered.bg_fill = fill;
+            visuals.widgets.hovered.weak_bg_fill = fill;
+        }
+        if let Some(warning) = color(&self.warning) {
+            visuals.warn_fg_color = warning;
+        }
+        if let Some(error) = color(&self.error) {
+            visuals.error_fg_color = error;
+        }
+        if let Some(rounding) = self.rounding {
+            let rounding = egui::Rounding::same(rounding);
+            visuals.window_rounding = rounding;
+            visuals.menu_rounding = rounding;
+            for widget in [
+                &mut visuals.widgets.noninteractive,
+                &mut visuals.widgets.inactive,
+                &mut visuals.widgets.hovered,
+                &mut visuals.widgets.active,
+                &mut visuals.widgets.open,
+            ] {
+                widget.rounding = rounding;
+            }
+        }
+        if let Some(accent) = color(&self.accent) {
+            apply_accent(&mut visuals, accent);
+        }
+        visuals
+    }
+}
+
+// OS dark mode preference, used by Theme::System
+pub fn system_prefers_dark() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        if let Ok(personalize) = hkcu.open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize") {
+            if let Ok(apps_use_light_theme) = personalize.get_value::<u32, _>("AppsUseLightTheme") {
+                return apps_use_light_theme != 1;
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        // The key only exists while dark mode is on
+        if let Ok(output) = Command::new("defaults").args(&["read", "-g", "AppleInterfaceStyle"]).output() {
+            return output.status.success() && String::from_utf8_lossy(&output.stdout).to_lowercase().contains("dark");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+
+        let gsettings = |key: &str| {
+            Command::new("gsettings").args(["get", "org.gnome.desktop.interface", key]).output().ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_lowercase())
+        };
+        // GNOME 42+ and most portals set color-scheme; older desktops only have a dark GTK theme name
+        match gsettings("color-scheme") {
+            Some(scheme) if scheme.contains("prefer-dark") => return true,
+            Some(scheme) if scheme.contains("prefer-light") => return false,
+            _ => {}
+        }
+        if let Some(theme) = gsettings("gtk-theme") {
+            return theme.contains("dark");
+        }
+    }
+
+    // Dark is the default when the preference can't be read
+    true
+}
+
+// Polls the OS preference on a background thread while Theme::System is selected, and wakes
+// the UI when it flips
+pub struct SystemThemeWatcher {
+    prefers_dark: Arc<AtomicBool>,
+    active: Arc<AtomicBool>,
+    applied: bool,
+}
+
+impl SystemThemeWatcher {
+    pub fn start(ctx: &egui::Context) -> Self {
+        let initial = system_prefers_dark();
+        let prefers_dark = Arc::new(AtomicBool::new(initial));
+        let active = Arc::new(AtomicBool::new(false));
+
+        let (thread_dark, thread_active, ctx) = (prefers_dark.clone(), active.clone(), ctx.clone());
+        std::thread::spawn(move || loop {
+            std::thread::sleep(SYSTEM_THEME_POLL_INTERVAL);
+            if !thread_active.load(Ordering::Relaxed) {
+                continue;
+            }
+            let dark = system_prefers_dark();
+            if thread_dark.swap(dark, Ordering::Relaxed) != dark {
+                println!("System theme changed to {}", if dark { "dark" } else { "light" });
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            prefers_dark,
+            active,
+            applied: initial,
+        }
+    }
+
+    pub fn prefers_dark(&self) -> bool {
+        self.prefers_dark.load(Ordering::Relaxed)
+    }
+
+    // The preference isn't polled while another theme is selected, so it's re-read when
+    // System is picked again
+    pub fn set_active(&self, active: bool) {
+        if active && !self.active.swap(true, Ordering::Relaxed) {
+            self.prefers_dark.store(system_prefers_dark(), Ordering::Relaxed);
+        } else if !active {
+            self.active.store(false, Ordering::Relaxed);
+        }
+    }
+
+    // True once per change of the OS preference since the last call
+    pub fn take_change(&mut self) -> bool {
+        let dark = self.prefers_dark();
+        let changed = dark != self.applied;
+        self.applied = dark;
+        changed
+    }
+}
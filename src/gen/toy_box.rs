@@ -0,0 +1,323 @@
+use super::read_scene::{self, ContainerData, Data, SceneFileHandler, ScenePath};
+use eframe::egui;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// A toy placed in a Toy Box level. Only what the plot and the JSON round trip need is kept; the
+// rest of the toy stays in the level untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedToy {
+    // Container holding the toy, used to match toys on import
+    pub container: ScenePath,
+    pub name: String,
+    pub position: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Vec<f32>>,
+    #[serde(skip)]
+    position_key: String,
+    #[serde(skip)]
+    rotation_key: Option<String>,
+    #[serde(skip)]
+    scale_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ToyBoxExport {
+    source: String,
+    toys: Vec<PlacedToy>,
+}
+
+const NAME_HINTS: [&str; 5] = ["name", "toy", "asset", "type", "id"];
+
+// The best string to call a toy by: a "Name" key, then anything toy- or asset-like
+fn toy_name(key: &str, map: &IndexMap<String, ContainerData>) -> String {
+    let strings: Vec<(&String, &String)> = map
+        .iter()
+        .filter_map(|(k, v)| match v {
+            ContainerData::Single(Data::String(value)) if !value.is_empty() => Some((k, value)),
+            _ => None,
+        })
+        .collect();
+    NAME_HINTS
+        .iter()
+        .find_map(|hint| strings.iter().find(|(k, _)| k.to_lowercase().contains(hint)).map(|(_, value)| (*value).clone()))
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn float_vec<'a>(map: &'a IndexMap<String, ContainerData>, hints: &[&str], lengths: &[usize]) -> Option<(&'a String, &'a Vec<f32>)> {
+    map.iter().find_map(|(key, value)| match value {
+        ContainerData::Single(Data::FloatVec(values))
+            if lengths.contains(&values.len()) && hints.iter().any(|hint| key.to_lowercase().contains(hint)) =>
+        {
+            Some((key, values))
+        }
+        _ => None,
+    })
+}
+
+fn collect_toys(key: &str, map: &IndexMap<String, ContainerData>, path: &mut ScenePath, toys: &mut Vec<PlacedToy>) {
+    if let Some((position_key, position)) = float_vec(map, &["pos", "translat", "location"], &[3]) {
+        let rotation = float_vec(map, &["rot", "quat", "orient"], &[3, 4]);
+        let scale = float_vec(map, &["scale"], &[3]);
+        toys.push(PlacedToy {
+            container: path.clone(),
+            name: toy_name(key, map),
+            position: [position[0], position[1], position[2]],
+            rotation: rotation.map(|(_, values)| values.clone()),
+            scale: scale.map(|(_, values)| values.clone()),
+            position_key: position_key.clone(),
+            rotation_key: rotation.map(|(k, _)| k.clone()),
+            scale_key: scale.map(|(k, _)| k.clone()),
+        });
+    }
+    for (child_key, value) in map {
+        let items: Vec<(Option<usize>, &Data)> = match value {
+            ContainerData::Single(data) => vec![(None, data)],
+            ContainerData::Multiple(list) => list.iter().enumerate().map(|(i, data)| (Some(i), data)).collect(),
+        };
+        for (index, data) in items {
+            if let Data::Container(children) = data {
+                path.push((child_key.clone(), index));
+                collect_toys(child_key, children, path, toys);
+                path.pop();
+            }
+        }
+    }
+}
+
+fn set_floats(scene: &mut IndexMap<String, ContainerData>, container: &ScenePath, key: &str, values: &[f32]) -> bool {
+    let mut path = container.clone();
+    path.push((key.to_string(), None));
+    match read_scene::find_data_mut(scene, &path) {
+        Some(Data::FloatVec(current)) if current.len() == values.len() => {
+            current.copy_from_slice(values);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub enum ToyBoxAction {
+    Open,
+    ExportJson,
+    ImportJson,
+    SaveAs,
+}
+
+// Toy Box levels are OCT containers; a placed toy is any container with a position and, where
+// there are any, rotation and scale keys next to it
+#[derive(Default)]
+pub struct ToyBox {
+    pub source: Option<PathBuf>,
+    level: Option<SceneFileHandler>,
+    pub toys: Vec<PlacedToy>,
+    selected: Option<usize>,
+    pub status: Option<Result<String, String>>,
+}
+
+impl ToyBox {
+    pub fn open(&mut self, path: &Path) -> Result<String, String> {
+        let mut level = SceneFileHandler::new();
+        let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        level
+            .load_scene_file(&mut file)
+            .map_err(|e| format!("{} isn't a Toy Box level this version can read: {}", path.display(), e))?;
+        let mut toys = Vec::new();
+        if let Some(scene) = &level.current_scene {
+            collect_toys("Level", scene, &mut Vec::new(), &mut toys);
+        }
+        let message = format!("Loaded {} placed toys from {}", toys.len(), path.display());
+        self.source = Some(path.to_path_buf());
+        self.level = Some(level);
+        self.toys = toys;
+        self.selected = None;
+        Ok(message)
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.level.as_ref().map_or(false, |level| level.modified)
+    }
+
+    // Edits stay in memory, the same as a discarded scene, but no longer count as unsaved
+    pub fn discard_changes(&mut self) {
+        if let Some(level) = &mut self.level {
+            level.modified = false;
+        }
+    }
+
+    pub fn export_json(&self, path: &Path) -> Result<String, String> {
+        let export = ToyBoxExport {
+            source: self.source.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            toys: self.toys.clone(),
+        };
+        let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(format!("Exported {} toys to {}", self.toys.len(), path.display()))
+    }
+
+    // Moves the level's toys to the transforms in an exported JSON file. Toys are matched by
+    // container, so adding or removing toys in the JSON has no effect.
+    pub fn import_json(&mut self, path: &Path) -> Result<String, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let import: ToyBoxExport = serde_json::from_str(&content).map_err(|e| format!("{} isn't a Toy Box export: {}", path.display(), e))?;
+        let level = self.level.as_mut().ok_or("Open a level first")?;
+        let scene = level.current_scene.as_mut().ok_or("The level has no scene data")?;
+        let (mut updated, mut unmatched) = (0, 0);
+        for imported in &import.toys {
+            let Some(toy) = self.toys.iter_mut().find(|t| t.container == imported.container) else {
+                unmatched += 1;
+                continue;
+            };
+            let mut changed = set_floats(scene, &toy.container, &toy.position_key, &imported.position);
+            toy.position = imported.position;
+            if let (Some(key), Some(values)) = (&toy.rotation_key, &imported.rotation) {
+                changed |= set_floats(scene, &toy.container, key, values);
+                toy.rotation = Some(values.clone());
+            }
+            if let (Some(key), Some(values)) = (&toy.scale_key, &imported.scale) {
+                changed |= set_floats(scene, &toy.container, key, values);
+                toy.scale = Some(values.clone());
+            }
+            if changed {
+                updated += 1;
+            }
+        }
+        level.modified |= updated > 0;
+        let mut message = format!("Updated {} toys from {}", updated, path.display());
+        if unmatched > 0 {
+            message.push_str(&format!("; {} toys aren't in this level", unmatched));
+        }
+        Ok(message)
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<String, String> {
+        let level = self.level.as_mut().ok_or("Open a level first")?;
+        level.save_scene_file(path).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+        level.modified = false;
+        Ok(format!("Saved {}", path.display()))
+    }
+
+    // Top-down view over X and Z, scaled to fit every toy
+    fn show_plot(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) = ui.allocate_painter(egui::vec2(ui.available_width(), 280.0), egui::Sense::click());
+        let rect = response.rect.shrink(12.0);
+        painter.rect_stroke(response.rect, 2.0, ui.visuals().widgets.noninteractive.bg_stroke);
+        let (mut min, mut max) = (egui::pos2(f32::MAX, f32::MAX), egui::pos2(f32::MIN, f32::MIN));
+        for toy in &self.toys {
+            min = min.min(egui::pos2(toy.position[0], toy.position[2]));
+            max = max.max(egui::pos2(toy.position[0], toy.position[2]));
+        }
+        let span = (max - min).max(egui::vec2(1.0, 1.0));
+        let scale = (rect.width() / span.x).min(rect.height() / span.y);
+        let to_screen = |toy: &PlacedToy| rect.center() + (egui::pos2(toy.position[0], toy.position[2]) - (min + span / 2.0)) * scale;
+
+        let pointer = response.hover_pos();
+        let mut hovered = None;
+        for (index, toy) in self.toys.iter().enumerate() {
+            let point = to_screen(toy);
+            let selected = self.selected == Some(index);
+            let color = if selected { ui.visuals().selection.bg_fill } else { ui.visuals().text_color() };
+            painter.circle_filled(point, if selected { 6.0 } else { 3.5 }, color);
+            if pointer.map_or(false, |p| p.distance(point) < 6.0) {
+                hovered = Some(index);
+            }
+        }
+        if let Some(index) = hovered {
+            let toy = &self.toys[index];
+            response.clone().on_hover_text(format!("{}\n{:.2}, {:.2}, {:.2}", toy.name, toy.position[0], toy.position[1], toy.position[2]));
+            if response.clicked() {
+                self.selected = Some(index);
+            }
+        }
+    }
+
+    fn show_selected(&mut self, ui: &mut egui::Ui) {
+        let (Some(index), Some(level)) = (self.selected, self.level.as_mut()) else {
+            return;
+        };
+        let toy = &mut self.toys[index];
+        ui.horizontal(|ui| {
+            ui.strong(&toy.name);
+            ui.weak(read_scene::scene_path_label(&toy.container));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Position:");
+            let mut changed = false;
+            for value in &mut toy.position {
+                changed |= ui.add(egui::DragValue::new(value).speed(0.1)).changed();
+            }
+            if changed {
+                if let Some(scene) = level.current_scene.as_mut() {
+                    level.modified |= set_floats(scene, &toy.container, &toy.position_key, &toy.position);
+                }
+            }
+        });
+        if let Some(rotation) = &toy.rotation {
+            ui.label(format!("Rotation: {:?}", rotation));
+        }
+        if let Some(scale) = &toy.scale {
+            ui.label(format!("Scale: {:?}", scale));
+        }
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) -> Option<ToyBoxAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            if ui.button("Open level...").clicked() {
+                action = Some(ToyBoxAction::Open);
+            }
+            let loaded = self.level.is_some();
+            if ui.add_enabled(loaded, egui::Button::new("Export JSON...")).clicked() {
+                action = Some(ToyBoxAction::ExportJson);
+            }
+            if ui.add_enabled(loaded, egui::Button::new("Import JSON...")).clicked() {
+                action = Some(ToyBoxAction::ImportJson);
+            }
+            let label = if self.is_modified() { "Save level as... ●" } else { "Save level as..." };
+            if ui.add_enabled(loaded, egui::Button::new(label)).clicked() {
+                action = Some(ToyBoxAction::SaveAs);
+            }
+        });
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+        let Some(source) = &self.source else {
+            ui.weak("Open a Toy Box level to see the toys placed in it.");
+            return action;
+        };
+        ui.label(format!("{}: {} toys", source.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned()), self.toys.len()));
+        if self.toys.is_empty() {
+            ui.label("No containers with a position were found in this level.");
+            return action;
+        }
+        ui.separator();
+        self.show_plot(ui);
+        self.show_selected(ui);
+        ui.separator();
+
+        let mut picked = None;
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical().id_source("toy_box_rows").max_height(200.0).show_rows(ui, row_height, self.toys.len(), |ui, range| {
+            for index in range {
+                let toy = &self.toys[index];
+                let text = format!("{}  ({:.1}, {:.1}, {:.1})", toy.name, toy.position[0], toy.position[1], toy.position[2]);
+                if ui.selectable_label(self.selected == Some(index), text).clicked() {
+                    picked = Some(index);
+                }
+            }
+        });
+        if picked.is_some() {
+            self.selected = picked;
+        }
+        action
+    }
+}
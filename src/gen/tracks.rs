@@ -0,0 +1,180 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// Image names that usually mean a level's preview rather than one of its textures
+const THUMBNAIL_HINTS: [&str; 5] = ["thumb", "preview", "loading", "icon", "select"];
+const THUMBNAIL_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+const THUMBNAIL_SIZE: f32 = 64.0;
+
+// A track or level found among the game's scene files
+#[derive(Debug, Clone)]
+pub struct TrackEntry {
+    pub name: String,
+    // Loose file, or <archive>/<entry> like the file tree uses
+    pub scene: PathBuf,
+    pub archive: Option<PathBuf>,
+    pub size: u64,
+    pub has_bent: bool,
+    // Encoded image bytes, decoded when the row is first shown
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+fn components(path: &str) -> Vec<String> {
+    path.split(['/', '\\']).map(str::to_lowercase).collect()
+}
+
+// The level a scene belongs to: the folder after one named like a level folder, or the file's own
+// stem when that's where the hint is. None for scenes outside any level-looking folder.
+pub fn level_name(path: &str, hints: &[&str]) -> Option<String> {
+    let parts = components(path);
+    let file = parts.last()?;
+    let stem = file.rsplit_once('.').map_or(file.as_str(), |(stem, _)| stem).to_string();
+    let folders = &parts[..parts.len() - 1];
+    for (index, folder) in folders.iter().enumerate() {
+        if hints.iter().any(|hint| folder.contains(hint)) {
+            return Some(folders.get(index + 1).cloned().unwrap_or(stem));
+        }
+    }
+    hints.iter().any(|hint| stem.contains(hint)).then_some(stem)
+}
+
+pub fn is_thumbnail_candidate(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    THUMBNAIL_EXTENSIONS.iter().any(|e| lower.ends_with(&format!(".{}", e)))
+}
+
+// An image in the scene's folder named after the level or like a preview; `images` are paths in
+// the same form as `scene`
+pub fn find_thumbnail<'a>(scene: &str, level: &str, images: &'a [String]) -> Option<&'a String> {
+    let folder = scene.rsplit_once(['/', '\\']).map_or("", |(folder, _)| folder);
+    let level = level.to_lowercase();
+    let in_folder = |image: &&String| image.rsplit_once(['/', '\\']).map_or("", |(folder, _)| folder) == folder;
+    let name_of = |image: &String| image.rsplit(['/', '\\']).next().unwrap_or(image).to_lowercase();
+    images
+        .iter()
+        .filter(in_folder)
+        .find(|image| name_of(image).contains(&level))
+        .or_else(|| images.iter().filter(in_folder).find(|image| THUMBNAIL_HINTS.iter().any(|hint| name_of(image).contains(hint))))
+}
+
+// "radiator_springs" -> "Radiator Springs"
+pub fn display_name(name: &str) -> String {
+    name.split(['_', '-', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub enum TrackAction {
+    Rescan,
+    Open(PathBuf),
+    Reveal(PathBuf),
+    Export(PathBuf),
+}
+
+#[derive(Default)]
+pub struct TrackBrowser {
+    pub tracks: Vec<TrackEntry>,
+    pub scanning: bool,
+    search: String,
+    textures: HashMap<PathBuf, Option<egui::TextureHandle>>,
+}
+
+impl TrackBrowser {
+    pub fn set_tracks(&mut self, tracks: Vec<TrackEntry>) {
+        self.tracks = tracks;
+        self.textures.clear();
+        self.scanning = false;
+    }
+
+    fn thumbnail(&mut self, ctx: &egui::Context, index: usize) -> Option<egui::TextureHandle> {
+        let track = &self.tracks[index];
+        self.textures
+            .entry(track.scene.clone())
+            .or_insert_with(|| {
+                let image = image::load_from_memory(track.thumbnail.as_ref()?).ok()?.thumbnail(128, 128).to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice());
+                Some(ctx.load_texture(format!("track_{}", track.scene.display()), color_image, Default::default()))
+            })
+            .clone()
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, game: &str) -> Option<TrackAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            if self.scanning {
+                ui.spinner();
+                ui.label("Looking for tracks and levels...");
+            } else {
+                ui.label(format!("{}: {} tracks and levels", game, self.tracks.len()));
+                if ui.button("Rescan").clicked() {
+                    action = Some(TrackAction::Rescan);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+        });
+        ui.weak("Found from scene files under level-like folders; catalog files aren't read yet.");
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        let rows: Vec<usize> = (0..self.tracks.len())
+            .filter(|i| {
+                let track = &self.tracks[*i];
+                search.is_empty() || track.name.to_lowercase().contains(&search) || track.scene.to_string_lossy().to_lowercase().contains(&search)
+            })
+            .collect();
+        let row_height = THUMBNAIL_SIZE + ui.spacing().item_spacing.y;
+        egui::ScrollArea::vertical().id_source("track_rows").auto_shrink([false, false]).show_rows(ui, row_height, rows.len(), |ui, range| {
+            for index in rows[range].iter().copied() {
+                let thumbnail = self.thumbnail(ui.ctx(), index);
+                let track = &self.tracks[index];
+                ui.horizontal(|ui| {
+                    ui.set_height(THUMBNAIL_SIZE);
+                    match thumbnail {
+                        Some(texture) => {
+                            ui.add(egui::Image::new(&texture).fit_to_exact_size(egui::Vec2::splat(THUMBNAIL_SIZE)));
+                        }
+                        None => {
+                            let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(THUMBNAIL_SIZE), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 4.0, ui.visuals().faint_bg_color);
+                        }
+                    }
+                    ui.vertical(|ui| {
+                        ui.strong(&track.name);
+                        let file = track.scene.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned());
+                        let mut details = format!("{} · {:.1} KB", file, track.size as f64 / 1024.0);
+                        if track.has_bent {
+                            details.push_str(" · animations");
+                        }
+                        if let Some(archive) = &track.archive {
+                            details.push_str(&format!(" · in {}", archive.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned())));
+                        }
+                        ui.weak(details).on_hover_text(track.scene.display().to_string());
+                        ui.horizontal(|ui| {
+                            if ui.small_button("Open scene").clicked() {
+                                action = Some(TrackAction::Open(track.scene.clone()));
+                            }
+                            let reveal = if track.archive.is_some() { "Show archive" } else { "Show in tree" };
+                            if ui.small_button(reveal).clicked() {
+                                action = Some(TrackAction::Reveal(track.archive.clone().unwrap_or_else(|| track.scene.clone())));
+                            }
+                            if ui.small_button("Export...").clicked() {
+                                action = Some(TrackAction::Export(track.scene.clone()));
+                            }
+                        });
+                    });
+                });
+            }
+        });
+        action
+    }
+}
@@ -0,0 +1,285 @@
+use eframe::egui;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use super::release_manifest;
+use super::shell::open_in_browser;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Cassinni4/Tundra/releases/latest";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+    // "sha256:<hex>", filled in by GitHub for newer uploads
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    // The Windows build is published as a bare executable
+    fn windows_executable(&self) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name.to_lowercase().ends_with(".exe"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum UpdateState {
+    Idle,
+    Checking,
+    UpToDate,
+    Available(Release),
+    Downloading,
+    // Swapped in, takes effect on the next start
+    Installed(String),
+    Failed(String),
+}
+
+// "v1.2.3", "1.2" and "1.2.3-beta" all compare by their numeric parts
+fn parse_version(text: &str) -> Vec<u64> {
+    text.trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let (mut candidate, mut current) = (parse_version(candidate), parse_version(current));
+    let length = candidate.len().max(current.len());
+    candidate.resize(length, 0);
+    current.resize(length, 0);
+    candidate > current
+}
+
+fn fetch_latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    let body = ureq::get(LATEST_RELEASE_URL)
+        .set("User-Agent", concat!("Tundra/", env!("CARGO_PKG_VERSION")))
+        .set("Accept", "application/vnd.github+json")
+        .call()?
+        .into_string()?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+fn download_text(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(ureq::get(url)
+        .set("User-Agent", concat!("Tundra/", env!("CARGO_PKG_VERSION")))
+        .call()?
+        .into_string()?)
+}
+
+// The asset's hash from GitHub's digest, or else from a SHA256SUMS.txt published with the release
+fn expected_sha256(release: &Release, asset: &ReleaseAsset) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(hash) = asset.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")) {
+        return Ok(hash.to_string());
+    }
+    if let Some(manifest) = release.assets.iter().find(|a| a.name == release_manifest::MANIFEST_FILE) {
+        let listing = download_text(&manifest.browser_download_url)?;
+        let hash = listing.lines()
+            .filter_map(|line| line.split_once("  "))
+            .find(|(_, name)| name.trim_start_matches('*') == asset.name)
+            .map(|(hash, _)| hash.to_string());
+        if let Some(hash) = hash {
+            return Ok(hash);
+        }
+    }
+    Err(format!("{} has no published SHA-256, so it wasn't installed; download it from the release page instead", asset.name).into())
+}
+
+// Windows lets a running executable be renamed but not overwritten, so the current one is moved
+// aside to <exe>.old and removed on the next start
+fn install_executable(release: &Release, asset: &ReleaseAsset) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = expected_sha256(release, asset)?;
+    let current = std::env::current_exe()?;
+    let downloaded = current.with_extension("exe.new");
+    let previous = current.with_extension("exe.old");
+
+    let mut reader = ureq::get(&asset.browser_download_url)
+        .set("User-Agent", concat!("Tundra/", env!("CARGO_PKG_VERSION")))
+        .call()?
+        .into_reader();
+    let mut file = std::fs::File::create(&downloaded)?;
+    let written = std::io::copy(&mut reader, &mut file)?;
+    drop(file);
+    if asset.size != 0 && written != asset.size {
+        let _ = std::fs::remove_file(&downloaded);
+        return Err(format!("Download was cut short ({} of {} bytes)", written, asset.size).into());
+    }
+    if !release_manifest::hash_file(&downloaded)?.eq_ignore_ascii_case(&expected) {
+        let _ = std::fs::remove_file(&downloaded);
+        return Err(format!("{} doesn't match its published SHA-256, so it wasn't installed", asset.name).into());
+    }
+
+    if previous.exists() {
+        std::fs::remove_file(&previous)?;
+    }
+    std::fs::rename(&current, &previous)?;
+    if let Err(e) = std::fs::rename(&downloaded, &current) {
+        // Put the old executable back so the install isn't left without one
+        let _ = std::fs::rename(&previous, &current);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+// Leftover from a previous self-update
+pub fn remove_previous_executable() {
+    let Ok(current) = std::env::current_exe() else {
+        return;
+    };
+    let previous = current.with_extension("exe.old");
+    if previous.exists() {
+        match std::fs::remove_file(&previous) {
+            Ok(()) => println!("Removed previous executable: {}", previous.display()),
+            Err(e) => eprintln!("Failed to remove {}: {}", previous.display(), e),
+        }
+    }
+}
+
+pub struct UpdateChecker {
+    state: Arc<Mutex<UpdateState>>,
+}
+
+impl UpdateChecker {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(UpdateState::Idle)),
+        }
+    }
+
+    pub fn state(&self) -> UpdateState {
+        self.state.lock().unwrap().clone()
+    }
+
+    // `skipped` is a version the user chose to ignore; it isn't offered again
+    pub fn check(&mut self, ctx: &egui::Context, skipped: Option<String>) {
+        if matches!(self.state(), UpdateState::Checking | UpdateState::Downloading) {
+            return;
+        }
+        *self.state.lock().unwrap() = UpdateState::Checking;
+
+        let state = self.state.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = match fetch_latest_release() {
+                Ok(release) if is_newer(&release.tag_name, env!("CARGO_PKG_VERSION"))
+                    && skipped.as_deref() != Some(release.tag_name.as_str()) =>
+                {
+                    println!("Update available: {}", release.tag_name);
+                    UpdateState::Available(release)
+                }
+                Ok(_) => UpdateState::UpToDate,
+                Err(e) => {
+                    eprintln!("Update check failed: {}", e);
+                    UpdateState::Failed(e.to_string())
+                }
+            };
+            *state.lock().unwrap() = result;
+            ctx.request_repaint();
+        });
+    }
+
+    fn install(&mut self, release: Release, ctx: &egui::Context) {
+        let Some(asset) = release.windows_executable().cloned() else {
+            return;
+        };
+        *self.state.lock().unwrap() = UpdateState::Downloading;
+
+        let state = self.state.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = match install_executable(&release, &asset) {
+                Ok(()) => {
+                    println!("Installed {}", release.tag_name);
+                    UpdateState::Installed(release.tag_name)
+                }
+                Err(e) => {
+                    eprintln!("Failed to install update: {}", e);
+                    UpdateState::Failed(e.to_string())
+                }
+            };
+            *state.lock().unwrap() = result;
+            ctx.request_repaint();
+        });
+    }
+
+    // Short status line for the Options window
+    pub fn status(&self) -> Option<String> {
+        match self.state() {
+            UpdateState::Idle => None,
+            UpdateState::Checking => Some("Checking for updates...".to_string()),
+            UpdateState::UpToDate => Some(format!("Tundra {} is up to date", env!("CARGO_PKG_VERSION"))),
+            UpdateState::Available(release) => Some(format!("{} is available", release.tag_name)),
+            UpdateState::Downloading => Some("Downloading update...".to_string()),
+            UpdateState::Installed(tag) => Some(format!("{} installed, restart Tundra to use it", tag)),
+            UpdateState::Failed(e) => Some(format!("Update failed: {}", e)),
+        }
+    }
+
+    // Changelog dialog while an update is on offer. Returns a version the user asked to skip,
+    // for the app to remember.
+    pub fn show_ui(&mut self, ctx: &egui::Context) -> Option<String> {
+        let UpdateState::Available(release) = self.state() else {
+            return None;
+        };
+
+        let mut skipped = None;
+        let mut close = false;
+        let mut install = false;
+        egui::Window::new(format!("Tundra {} is available", release.tag_name))
+            .collapsible(false)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label(format!("You are running {}.", env!("CARGO_PKG_VERSION")));
+                if let Some(name) = release.name.as_ref().filter(|n| !n.is_empty() && **n != release.tag_name) {
+                    ui.heading(name);
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    ui.label(release.body.as_deref().unwrap_or("No release notes."));
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if cfg!(windows) && release.windows_executable().is_some() && ui.button("Download and install").clicked() {
+                        install = true;
+                    }
+                    if ui.button("Open release page").clicked() {
+                        if let Err(e) = open_in_browser(&release.html_url) {
+                            eprintln!("Failed to open {}: {}", release.html_url, e);
+                        }
+                    }
+                    if ui.button("Skip this version").clicked() {
+                        skipped = Some(release.tag_name.clone());
+                        close = true;
+                    }
+                    if ui.button("Later").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if install {
+            self.install(release, ctx);
+        } else if close {
+            *self.state.lock().unwrap() = UpdateState::Idle;
+        }
+        skipped
+    }
+}
@@ -0,0 +1,202 @@
+use super::read_scene::{ContainerData, Data};
+use super::storage_analyzer::{format_size, StorageItem};
+use eframe::egui;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const TOP_COUNT: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AssetCategory {
+    Models,
+    Textures,
+    Audio,
+    Video,
+    Scripts,
+    Scenes,
+    Text,
+    Other,
+}
+
+impl AssetCategory {
+    pub const ALL: [AssetCategory; 8] = [
+        AssetCategory::Models,
+        AssetCategory::Textures,
+        AssetCategory::Audio,
+        AssetCategory::Video,
+        AssetCategory::Scripts,
+        AssetCategory::Scenes,
+        AssetCategory::Text,
+        AssetCategory::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetCategory::Models => "Models",
+            AssetCategory::Textures => "Textures",
+            AssetCategory::Audio => "Audio",
+            AssetCategory::Video => "Video",
+            AssetCategory::Scripts => "Scripts",
+            AssetCategory::Scenes => "Scenes",
+            AssetCategory::Text => "Text",
+            AssetCategory::Other => "Other",
+        }
+    }
+
+    pub fn of(path: &Path) -> Self {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        match extension.as_str() {
+            "ibuf" | "vbuf" => AssetCategory::Models,
+            "mtb" | "tbody" | "dds" | "png" | "tga" | "jpg" | "jpeg" | "bmp" => AssetCategory::Textures,
+            "wem" | "bnk" | "wav" | "ogg" | "xwb" | "xsb" => AssetCategory::Audio,
+            "bik" | "usm" | "wmv" => AssetCategory::Video,
+            "lua" | "luac" | "script" => AssetCategory::Scripts,
+            "oct" | "bent" => AssetCategory::Scenes,
+            "dct" | "txt" | "xml" | "json" | "ini" => AssetCategory::Text,
+            _ => AssetCategory::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub count: usize,
+    pub size: u64,
+}
+
+// Everything the dashboard shows, cached per game as JSON in the temp folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub game: String,
+    // Loose file count and size when the report was made; a different tree means it's stale
+    pub fingerprint: (usize, u64),
+    pub generated: u64,
+    pub categories: Vec<(AssetCategory, CategoryStats)>,
+    pub largest: Vec<(PathBuf, u64)>,
+    // Texture stem and how many scene references point at it
+    pub referenced_textures: Vec<(String, usize)>,
+    pub scenes_read: usize,
+}
+
+impl UsageReport {
+    // Archive entries are counted as assets; the archives holding them aren't, so nothing is
+    // counted twice
+    pub fn build(game: &str, fingerprint: (usize, u64), items: &[StorageItem]) -> Self {
+        let archives: HashSet<&Path> = items.iter().filter_map(|item| item.archive.as_deref()).collect();
+        let mut categories: HashMap<AssetCategory, CategoryStats> = HashMap::new();
+        let mut files: Vec<(PathBuf, u64)> = Vec::new();
+        for item in items.iter().filter(|item| !archives.contains(item.path.as_path())) {
+            let stats = categories.entry(AssetCategory::of(&item.path)).or_default();
+            stats.count += 1;
+            stats.size += item.size;
+            files.push((item.path.clone(), item.size));
+        }
+        files.sort_by(|a, b| b.1.cmp(&a.1));
+        files.truncate(TOP_COUNT);
+        let generated = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        Self {
+            game: game.to_string(),
+            fingerprint,
+            generated,
+            categories: AssetCategory::ALL.iter().filter_map(|c| categories.remove(c).map(|stats| (*c, stats))).collect(),
+            largest: files,
+            referenced_textures: Vec::new(),
+            scenes_read: 0,
+        }
+    }
+
+    pub fn set_references(&mut self, counts: HashMap<String, usize>, scenes_read: usize) {
+        let mut counts: Vec<(String, usize)> = counts.into_iter().filter(|(_, count)| *count > 0).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(TOP_COUNT);
+        self.referenced_textures = counts;
+        self.scenes_read = scenes_read;
+    }
+
+    pub fn cache_path(temp_dir: &Path, game: &str) -> PathBuf {
+        temp_dir.join(format!("usage_{}.json", game.to_lowercase().replace([' ', ':', '(', ')'], "_")))
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        std::fs::read_to_string(path).ok().and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self, path: &Path) {
+        let result = serde_json::to_string(self).map_err(|e| e.to_string()).and_then(|json| super::paths::write_creating_dirs(path, json.as_bytes()).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Failed to cache usage report at {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn show_ui(&self, ui: &mut egui::Ui, stale: bool) {
+        if stale {
+            ui.colored_label(ui.visuals().warn_fg_color, "The file tree changed since this was computed; refresh for current numbers.");
+        }
+        let total: u64 = self.categories.iter().map(|(_, s)| s.size).sum();
+        let largest_category = self.categories.iter().map(|(_, s)| s.size).max().unwrap_or(1).max(1);
+        ui.strong("By type");
+        egui::Grid::new("usage_categories").num_columns(4).striped(true).show(ui, |ui| {
+            for (category, stats) in &self.categories {
+                ui.label(category.label());
+                ui.label(format!("{} files", stats.count));
+                ui.label(format_size(stats.size));
+                ui.add(egui::ProgressBar::new(stats.size as f32 / largest_category as f32)
+                    .desired_width(160.0)
+                    .text(format!("{:.1}%", stats.size as f64 * 100.0 / total.max(1) as f64)));
+                ui.end_row();
+            }
+        });
+        ui.separator();
+
+        ui.columns(2, |columns| {
+            columns[0].strong("Largest files");
+            egui::ScrollArea::vertical().id_source("usage_largest").max_height(260.0).show(&mut columns[0], |ui| {
+                for (path, size) in &self.largest {
+                    ui.horizontal(|ui| {
+                        ui.label(format_size(*size));
+                        ui.label(path.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned())).on_hover_text(path.display().to_string());
+                    });
+                }
+            });
+            columns[1].strong("Most referenced textures");
+            egui::ScrollArea::vertical().id_source("usage_textures").max_height(260.0).show(&mut columns[1], |ui| {
+                if self.referenced_textures.is_empty() {
+                    ui.weak(format!("No texture references found in {} scenes", self.scenes_read));
+                }
+                for (texture, count) in &self.referenced_textures {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}×", count));
+                        ui.label(texture);
+                    });
+                }
+            });
+        });
+    }
+}
+
+fn count_reference(value: &str, textures: &HashSet<String>, counts: &mut HashMap<String, usize>) {
+    let stem = super::scene_export::reference_stem(value);
+    if textures.contains(&stem) {
+        *counts.entry(stem).or_insert(0) += 1;
+    }
+}
+
+// Counts every string in the scene naming one of `textures` (lower-cased stems)
+pub fn count_texture_references(map: &IndexMap<String, ContainerData>, textures: &HashSet<String>, counts: &mut HashMap<String, usize>) {
+    for value in map.values() {
+        let items: Vec<&Data> = match value {
+            ContainerData::Single(data) => vec![data],
+            ContainerData::Multiple(list) => list.iter().collect(),
+        };
+        for data in items {
+            match data {
+                Data::Container(children) => count_texture_references(children, textures, counts),
+                Data::String(value) => count_reference(value, textures, counts),
+                Data::StringVec(values) => values.iter().for_each(|v| count_reference(v, textures, counts)),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use eframe::egui;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use super::dds::DdsLayout;
+use super::mtb_reader::MtbFile;
+use super::read_scene::{self, ContainerData, Data, SceneFileHandler, ScenePath};
+
+// Node type changes are reported individually up to this many per file
+const MAX_SCENE_FINDINGS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    // The game is likely to crash or refuse the file; deploying is blocked
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub file: PathBuf,
+    pub severity: Severity,
+    pub message: String,
+}
+
+// Results of checking modified files against the originals they replace
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+    pub files_checked: usize,
+}
+
+impl ValidationReport {
+    pub fn add(&mut self, file: &Path, results: Vec<(Severity, String)>) {
+        self.files_checked += 1;
+        self.findings.extend(results.into_iter().map(|(severity, message)| Finding {
+            file: file.to_path_buf(),
+            severity,
+            message,
+        }));
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+
+    pub fn is_blocking(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    pub fn show_ui(&self, ui: &mut egui::Ui) {
+        let (errors, warnings) = (self.count(Severity::Error), self.count(Severity::Warning));
+        ui.label(format!("{} files checked: {} errors, {} warnings", self.files_checked, errors, warnings));
+        if self.findings.is_empty() {
+            ui.label("Nothing found that would stop the game loading these files.");
+            return;
+        }
+
+        let mut findings: Vec<&Finding> = self.findings.iter().collect();
+        findings.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.file.cmp(&b.file)));
+        egui::ScrollArea::vertical().max_height(300.0).auto_shrink([false, true]).show(ui, |ui| {
+            egui::Grid::new("validation_findings").striped(true).num_columns(3).show(ui, |ui| {
+                for finding in findings {
+                    match finding.severity {
+                        Severity::Error => ui.colored_label(ui.visuals().error_fg_color, "Error"),
+                        Severity::Warning => ui.colored_label(ui.visuals().warn_fg_color, "Warning"),
+                    };
+                    ui.label(finding.file.display().to_string());
+                    ui.label(&finding.message);
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+fn error(message: impl Into<String>) -> (Severity, String) {
+    (Severity::Error, message.into())
+}
+
+fn warning(message: impl Into<String>) -> (Severity, String) {
+    (Severity::Warning, message.into())
+}
+
+// Checks a modified file by its extension. `original` is the file it replaces in the game, when
+// there is one. Archives are checked by the app, which owns the archive readers.
+pub fn validate_file(path: &Path, original: Option<&Path>) -> Vec<(Severity, String)> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => return vec![error(format!("Can't read file: {}", e))],
+    };
+    let original_data = original.and_then(|p| std::fs::read(p).ok());
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+
+    match extension.as_str() {
+        "oct" | "bent" => validate_scene(&data, original_data.as_deref()),
+        "mtb" => validate_mtb(path, &data, original_data.as_deref()),
+        "tbody" | "dds" => validate_texture(&data, original_data.as_deref()),
+        "vbuf" | "ibuf" => validate_buffers(path, original),
+        _ => Vec::new(),
+    }
+}
+
+fn data_kind(data: &Data) -> &'static str {
+    match data {
+        Data::Container(_) => "container",
+        Data::Binary(_) => "binary",
+        Data::Uuid(_) => "UUID",
+        Data::Int(_) => "int",
+        Data::IntVec(_) => "int list",
+        Data::Float(_) => "float",
+        Data::FloatVec(_) => "float list",
+        Data::String(_) => "string",
+        Data::StringVec(_) => "string list",
+    }
+}
+
+// Nodes present in both scenes whose type changed, and nodes the original had that are gone
+fn compare_scenes(
+    modified: &IndexMap<String, ContainerData>,
+    original: &IndexMap<String, ContainerData>,
+    path: &mut ScenePath,
+    results: &mut Vec<(Severity, String)>,
+) {
+    for (key, original_value) in original {
+        if results.len() >= MAX_SCENE_FINDINGS {
+            return;
+        }
+        let Some(modified_value) = modified.get(key) else {
+            path.push((key.clone(), None));
+            results.push(warning(format!("Node {} from the original is missing", read_scene::scene_path_label(path))));
+            path.pop();
+            continue;
+        };
+        let pairs: Vec<(Option<usize>, &Data, &Data)> = match (modified_value, original_value) {
+            (ContainerData::Single(a), ContainerData::Single(b)) => vec![(None, a, b)],
+            (ContainerData::Multiple(a), ContainerData::Multiple(b)) => {
+                a.iter().zip(b).enumerate().map(|(i, (a, b))| (Some(i), a, b)).collect()
+            }
+            _ => {
+                path.push((key.clone(), None));
+                results.push(error(format!("Node {} changed between single and repeated", read_scene::scene_path_label(path))));
+                path.pop();
+                continue;
+            }
+        };
+        for (index, modified_data, original_data) in pairs {
+            path.push((key.clone(), index));
+            match (modified_data, original_data) {
+                (Data::Container(a), Data::Container(b)) => compare_scenes(a, b, path, results),
+                (a, b) if data_kind(a) != data_kind(b) => results.push(error(format!(
+                    "Node {} changed type from {} to {}",
+                    read_scene::scene_path_label(path),
+                    data_kind(b),
+                    data_kind(a)
+                ))),
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+}
+
+fn validate_scene(data: &[u8], original: Option<&[u8]>) -> Vec<(Severity, String)> {
+    let mut handler = SceneFileHandler::new();
+    if let Err(e) = handler.load_scene_file(&mut std::io::Cursor::new(data)) {
+        return vec![error(format!("Not a readable OCT file: {}", e))];
+    }
+    let mut results: Vec<(Severity, String)> = handler.warnings.iter().map(|w| warning(w.clone())).collect();
+
+    let mut original_handler = SceneFileHandler::new();
+    let original_loaded = original.map_or(false, |original| original_handler.load_scene_file(&mut std::io::Cursor::new(original)).is_ok());
+    if original_loaded {
+        if handler.endian != original_handler.endian {
+            results.push(error(format!("Endianness is {:?} but the game's file is {:?}", handler.endian, original_handler.endian)));
+        }
+        if let (Some(modified), Some(original)) = (&handler.current_scene, &original_handler.current_scene) {
+            compare_scenes(modified, original, &mut Vec::new(), &mut results);
+        }
+    }
+    results
+}
+
+fn validate_mtb(path: &Path, data: &[u8], original: Option<&[u8]>) -> Vec<(Severity, String)> {
+    let mtb = match MtbFile::parse_from_bytes(data, path) {
+        Ok(mtb) => mtb,
+        Err(e) => return vec![error(format!("Not a readable MTB file: {}", e))],
+    };
+
+    let mut results = Vec::new();
+    for texture in &mtb.textures {
+        if texture.offset >= data.len() {
+            results.push(error(format!("Texture entry {} points past the end of the file", texture.tbody_filename)));
+        } else if texture.offset % 4 != 0 {
+            results.push(warning(format!("Texture entry {} isn't 4-byte aligned (offset {:#x})", texture.tbody_filename, texture.offset)));
+        }
+    }
+    if let Some(original) = original.and_then(|original| MtbFile::parse_from_bytes(original, path).ok()) {
+        if original.textures.len() != mtb.textures.len() {
+            results.push(error(format!("Has {} texture entries, the game's file has {}", mtb.textures.len(), original.textures.len())));
+        }
+        if original.is_ui_mtb != mtb.is_ui_mtb {
+            results.push(warning("UI material flag differs from the game's file"));
+        }
+    }
+    results
+}
+
+// Pixel flags, FourCC and bit count, plus the DXGI format for DX10 headers
+fn pixel_format(data: &[u8]) -> &[u8] {
+    let end = if data.get(84..88) == Some(b"DX10") { 132 } else { 92 };
+    data.get(80..end).unwrap_or_default()
+}
+
+fn validate_texture(data: &[u8], original: Option<&[u8]>) -> Vec<(Severity, String)> {
+    let Some(layout) = DdsLayout::parse(data) else {
+        return vec![error("Not a DDS texture the game can load")];
+    };
+
+    let mut results = Vec::new();
+    if layout.extract_surface(data, layout.surface_count() - 1).is_none() {
+        results.push(error("File is shorter than its header says (truncated mips or faces)"));
+    }
+    if !layout.width.is_power_of_two() || !layout.height.is_power_of_two() {
+        results.push(warning(format!("{}x{} isn't a power of two", layout.width, layout.height)));
+    }
+    let full_chain = 32 - layout.width.max(layout.height).max(1).leading_zeros();
+    if layout.mip_count > full_chain {
+        results.push(error(format!("{} mips is more than a {}x{} texture can have", layout.mip_count, layout.width, layout.height)));
+    }
+
+    if let Some(original_layout) = original.and_then(DdsLayout::parse) {
+        let original = original.unwrap_or_default();
+        if pixel_format(data) != pixel_format(original) {
+            results.push(error("Pixel format differs from the game's texture"));
+        }
+        if layout.is_cubemap != original_layout.is_cubemap || layout.array_size != original_layout.array_size {
+            results.push(error("Cubemap or array layout differs from the game's texture"));
+        }
+        if (layout.width, layout.height) != (original_layout.width, original_layout.height) {
+            results.push(warning(format!(
+                "Size is {}x{}, the game's texture is {}x{}",
+                layout.width, layout.height, original_layout.width, original_layout.height
+            )));
+        }
+        if layout.mip_count != original_layout.mip_count {
+            results.push(warning(format!("Has {} mips, the game's texture has {}", layout.mip_count, original_layout.mip_count)));
+        }
+    }
+    results
+}
+
+fn read_indices(path: &Path) -> Option<Vec<u16>> {
+    let data = std::fs::read(path).ok()?;
+    Some(data.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect())
+}
+
+// Vertex stride implied by a VBUF and the IBUF indexing it
+fn vertex_stride(vbuf_size: u64, indices: &[u16]) -> Option<u64> {
+    let vertex_count = *indices.iter().max()? as u64 + 1;
+    (vbuf_size % vertex_count == 0).then_some(vbuf_size / vertex_count)
+}
+
+// VBUF/IBUF are checked as a pair, from whichever of the two was modified
+fn validate_buffers(path: &Path, original: Option<&Path>) -> Vec<(Severity, String)> {
+    let (ibuf, vbuf) = (path.with_extension("ibuf"), path.with_extension("vbuf"));
+    let mut results = Vec::new();
+    let Ok(vbuf_size) = std::fs::metadata(&vbuf).map(|m| m.len()) else {
+        return vec![warning("No VBUF next to it to check against")];
+    };
+    if std::fs::metadata(&ibuf).map_or(false, |m| m.len() % 2 != 0) {
+        results.push(error("IBUF has an odd size, indices are 16-bit"));
+    }
+    let Some(indices) = read_indices(&ibuf) else {
+        results.push(warning("No IBUF next to it to check against"));
+        return results;
+    };
+    if indices.len() % 3 != 0 {
+        results.push(warning(format!("{} indices isn't a whole number of triangles", indices.len())));
+    }
+
+    // The stride of the game's pair is what the engine expects
+    let original_stride = original.and_then(|original| {
+        let original_vbuf = std::fs::metadata(original.with_extension("vbuf")).ok()?.len();
+        vertex_stride(original_vbuf, &read_indices(&original.with_extension("ibuf"))?)
+    });
+    match original_stride {
+        Some(stride) if vbuf_size % stride != 0 => {
+            results.push(error(format!("VBUF size {} isn't a multiple of the game's {}-byte vertex stride", vbuf_size, stride)));
+        }
+        Some(stride) => {
+            let vertex_count = vbuf_size / stride;
+            if let Some(&max) = indices.iter().max().filter(|&&max| max as u64 >= vertex_count) {
+                results.push(error(format!("Index {} is past the {} vertices in the VBUF", max, vertex_count)));
+            }
+        }
+        None if vertex_stride(vbuf_size, &indices).is_none() => {
+            results.push(warning("VBUF size doesn't divide evenly by the vertices the IBUF uses"));
+        }
+        None => {}
+    }
+    results
+}
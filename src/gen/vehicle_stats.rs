@@ -0,0 +1,192 @@
+use super::figure_data::{self, field, FieldSpec, FigureField};
+use super::read_scene::{self, ContainerData, Data, ScenePath};
+use eframe::egui;
+use indexmap::IndexMap;
+
+// Replaces the built-in columns when present, like figure_schema.json does for DI3
+pub const SCHEMA_PATH: &str = "vehicle_schema.json";
+
+const NAME_KEYS: [&str; 4] = ["name", "vehicle", "car", "id"];
+
+// Handling keys the Cars games share; Cars 3 adds a few of its own
+pub fn default_schema() -> Vec<FieldSpec> {
+    vec![
+        field("TopSpeed", "Top speed", "Speed", Some((0.0, 500.0))),
+        field("MaxSpeed", "Max speed", "Speed", Some((0.0, 500.0))),
+        field("BoostSpeed", "Boost speed", "Speed", Some((0.0, 500.0))),
+        field("Acceleration", "Accel", "Acceleration", Some((0.0, 100.0))),
+        field("BoostAcceleration", "Boost accel", "Acceleration", Some((0.0, 100.0))),
+        field("BrakeForce", "Brake", "Acceleration", Some((0.0, 100.0))),
+        field("Handling", "Handling", "Handling", Some((0.0, 10.0))),
+        field("TurnRate", "Turn rate", "Handling", Some((0.0, 10.0))),
+        field("Grip", "Grip", "Handling", Some((0.0, 10.0))),
+        field("DriftFactor", "Drift", "Handling", Some((0.0, 10.0))),
+        field("Mass", "Mass", "Body", Some((0.0, 10000.0))),
+        field("Weight", "Weight", "Body", Some((0.0, 10000.0))),
+        field("BoostCapacity", "Boost capacity", "Boost", Some((0.0, 1000.0))),
+        field("BoostRecharge", "Boost recharge", "Boost", Some((0.0, 100.0))),
+    ]
+}
+
+// Values grouped by the container they sit in; each such container is taken to be one vehicle
+struct VehicleRow {
+    owner: ScenePath,
+    name: String,
+    // Index into `fields` per column, when the vehicle has that key
+    cells: Vec<Option<usize>>,
+}
+
+fn vehicle_name(scene: &IndexMap<String, ContainerData>, owner: &ScenePath) -> String {
+    let fallback = owner.last().map_or("Scene".to_string(), |(key, index)| match index {
+        Some(index) => format!("{}[{}]", key, index),
+        None => key.clone(),
+    });
+    let map = if owner.is_empty() {
+        Some(scene)
+    } else {
+        match read_scene::find_data(scene, owner) {
+            Some(Data::Container(map)) => Some(map),
+            _ => None,
+        }
+    };
+    map.and_then(|map| {
+        NAME_KEYS.iter().find_map(|hint| {
+            map.iter().find_map(|(key, value)| match value {
+                ContainerData::Single(Data::String(name)) if key.to_lowercase().contains(hint) && !name.is_empty() => Some(name.clone()),
+                _ => None,
+            })
+        })
+    })
+    .unwrap_or(fallback)
+}
+
+// One row per vehicle and one column per handling key found, edited in place in the loaded
+// scene and saved the usual way
+#[derive(Default)]
+pub struct VehicleStatsEditor {
+    schema: Vec<FieldSpec>,
+    schema_error: Option<String>,
+    // None until the open scene has been scanned
+    fields: Option<Vec<FigureField>>,
+    columns: Vec<usize>,
+    rows: Vec<VehicleRow>,
+    search: String,
+    status: Option<Result<String, String>>,
+}
+
+impl VehicleStatsEditor {
+    // Called when another scene is loaded
+    pub fn reset(&mut self) {
+        self.fields = None;
+    }
+
+    fn scan(&mut self, scene: &IndexMap<String, ContainerData>) {
+        if self.schema.is_empty() {
+            (self.schema, self.schema_error) = figure_data::load_schema(SCHEMA_PATH, default_schema);
+        }
+        let mut fields = Vec::new();
+        figure_data::collect_fields(scene, &self.schema, &mut Vec::new(), &mut fields);
+
+        self.columns = (0..self.schema.len()).filter(|spec| fields.iter().any(|f| f.spec == *spec)).collect();
+        let mut rows: Vec<VehicleRow> = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let owner = field.path[..field.path.len() - 1].to_vec();
+            let row = match rows.iter().position(|row| row.owner == owner) {
+                Some(row) => row,
+                None => {
+                    rows.push(VehicleRow { name: vehicle_name(scene, &owner), owner, cells: vec![None; self.columns.len()] });
+                    rows.len() - 1
+                }
+            };
+            if let Some(column) = self.columns.iter().position(|spec| *spec == field.spec) {
+                // The first match wins if a vehicle repeats a key
+                rows[row].cells[column].get_or_insert(index);
+            }
+        }
+        self.rows = rows;
+        self.fields = Some(fields);
+    }
+
+    // Returns true when a value in the scene was changed
+    pub fn show_ui(&mut self, ui: &mut egui::Ui, scene: &mut IndexMap<String, ContainerData>) -> bool {
+        if self.fields.is_none() {
+            self.scan(scene);
+        }
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Vehicle:");
+            ui.text_edit_singleline(&mut self.search);
+            if ui.button("Reload schema").on_hover_text(format!("Reads {} again", SCHEMA_PATH)).clicked() {
+                (self.schema, self.schema_error) = figure_data::load_schema(SCHEMA_PATH, default_schema);
+                self.fields = None;
+            }
+            if ui.button("Write schema file").on_hover_text(format!("Saves the current columns to {} to extend them", SCHEMA_PATH)).clicked() {
+                self.status = Some(figure_data::write_schema(SCHEMA_PATH, &self.schema));
+            }
+        });
+        if let Some(error) = &self.schema_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+        let Some(fields) = &self.fields else {
+            return false;
+        };
+        if self.rows.is_empty() {
+            ui.label("No handling keys from the schema were found in this scene.");
+            ui.weak(format!("Keys can be added to {} once the scene's Tree tab shows what they're called.", SCHEMA_PATH));
+            return false;
+        }
+        ui.label(format!("{} vehicles, {} columns", self.rows.len(), self.columns.len()));
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        egui::ScrollArea::both().id_source("vehicle_stats").auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("vehicle_stats_grid").striped(true).num_columns(self.columns.len() + 1).show(ui, |ui| {
+                ui.strong("Vehicle");
+                for spec in &self.columns {
+                    let spec = &self.schema[*spec];
+                    ui.strong(&spec.label).on_hover_text(format!("{} ({})", spec.key, spec.group));
+                }
+                ui.end_row();
+
+                for row in self.rows.iter().filter(|row| search.is_empty() || row.name.to_lowercase().contains(&search)) {
+                    ui.label(&row.name).on_hover_text(read_scene::scene_path_label(&row.owner));
+                    for (column, cell) in row.cells.iter().enumerate() {
+                        let Some(field) = cell.map(|index| &fields[index]) else {
+                            ui.weak("—");
+                            continue;
+                        };
+                        let spec = &self.schema[self.columns[column]];
+                        let Some(data) = read_scene::find_data_mut(scene, &field.path) else {
+                            ui.weak("—");
+                            continue;
+                        };
+                        ui.horizontal(|ui| {
+                            changed |= figure_data::edit_value(ui, spec, data);
+                            if format!("{:?}", data) != format!("{:?}", field.original) {
+                                if ui.small_button("↺").on_hover_text(format!("Back to {:?}", field.original)).clicked() {
+                                    *data = field.original.clone();
+                                    changed = true;
+                                }
+                            }
+                            if figure_data::out_of_range(spec, data) {
+                                ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text("Outside the usual range");
+                            }
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        changed
+    }
+}
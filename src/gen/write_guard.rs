@@ -0,0 +1,60 @@
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::path::{Path, PathBuf};
+use super::paths;
+
+// Keeps writes out of the game install unless the user confirms them. Declined writes are
+// redirected to the same relative path under the workspace folder.
+#[derive(Debug, Clone, Default)]
+pub struct WriteGuard {
+    pub enabled: bool,
+    pub game_root: Option<PathBuf>,
+    pub workspace: PathBuf,
+}
+
+impl WriteGuard {
+    pub fn is_protected(&self, path: &Path) -> bool {
+        self.enabled && self.game_root.as_ref().map_or(false, |root| paths::is_within(path, root))
+    }
+
+    pub fn workspace_path(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(self.game_root.as_ref()?).ok()?;
+        Some(self.workspace.join(relative))
+    }
+
+    // Where a write to `path` should go, or None if the user cancelled
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        if !self.is_protected(path) {
+            return Some(path.to_path_buf());
+        }
+        let redirected = self.workspace_path(path)?;
+
+        let answer = MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Protected game folder")
+            .set_description(format!(
+                "{} is inside the game install.\n\nYes: write into the game folder anyway\nNo: write to the workspace instead ({})\nCancel: don't write anything",
+                path.display(),
+                redirected.display()
+            ))
+            .set_buttons(MessageButtons::YesNoCancel)
+            .show();
+
+        match answer {
+            MessageDialogResult::Yes => {
+                println!("Confirmed write into game folder: {}", path.display());
+                Some(path.to_path_buf())
+            }
+            MessageDialogResult::No => {
+                if let Some(parent) = redirected.parent() {
+                    if let Err(e) = std::fs::create_dir_all(parent) {
+                        eprintln!("Failed to create workspace folder {}: {}", parent.display(), e);
+                        return None;
+                    }
+                }
+                println!("Redirected write from {} to {}", path.display(), redirected.display());
+                Some(redirected)
+            }
+            _ => None,
+        }
+    }
+}
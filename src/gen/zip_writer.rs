@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::BufWriter;
+use std::path::Path;
+use super::entry_metadata::{self, METADATA_FILE};
+use super::paths::long_path;
+use super::safe_write;
+use super::storage_analyzer::format_size;
+use super::tasks::TaskContext;
+
+// How entries are compressed. `level` is deflate's 0-9 and unused when storing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ZipSettings {
+    pub level: u32,
+    pub store_only: bool,
+}
+
+impl Default for ZipSettings {
+    fn default() -> Self {
+        Self { level: 6, store_only: false }
+    }
+}
+
+impl ZipSettings {
+    pub fn describe(&self) -> String {
+        if self.store_only {
+            "stored".to_string()
+        } else {
+            format!("deflate level {}", self.level)
+        }
+    }
+}
+
+// Entry times are UTC like the rest of Tundra's timestamps; the format can't go before 1980
+fn dos_time(millis: u64) -> zip::DateTime {
+    let (year, month, day, hour, minute, second) = crate::civil_time(millis);
+    u16::try_from(year)
+        .ok()
+        .and_then(|year| zip::DateTime::from_date_and_time(year, month as u8, day as u8, hour as u8, minute as u8, second as u8).ok())
+        .unwrap_or_default()
+}
+
+// Writes every file below `folder` into a new archive at `output`, named relative to the folder,
+// then applies the folder's entry metadata edits. An archive it replaces is backed up first, and
+// put back if the new one is cancelled or fails rather than left half written. Returns a summary.
+pub fn create_from_folder(folder: &Path, output: &Path, settings: ZipSettings, task: &TaskContext) -> Result<String, String> {
+    let files: Vec<walkdir::DirEntry> = walkdir::WalkDir::new(folder)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        // The archive may be saved inside the folder it's made from
+        .filter(|e| e.file_type().is_file() && e.path() != output && e.file_name() != METADATA_FILE)
+        .collect();
+    task.set_total(files.len());
+    let edits = entry_metadata::load(folder)?;
+
+    let replacing = output.is_file();
+    let backed_up = safe_write::prepare(output).map_err(|e| e.to_string())?;
+    let result = write_archive(folder, output, &files, settings, task).and_then(|total| {
+        if edits.is_empty() {
+            return Ok((total, 0));
+        }
+        task.set_message("Editing entry metadata");
+        entry_metadata::apply(output, &edits).map(|edited| (total, edited))
+    });
+    // With backups turned off there's nothing of the old archive left, and an older .bak isn't it
+    if result.is_err() {
+        if backed_up {
+            let _ = fs::copy(long_path(&safe_write::backup_path(output, 1)), long_path(output));
+        } else if !replacing {
+            let _ = fs::remove_file(long_path(output));
+        }
+    }
+    let (total, edited) = result?;
+    let written = fs::metadata(long_path(output)).map(|m| m.len()).unwrap_or(0);
+    let mut summary = format!(
+        "{} files, {} → {} ({})",
+        files.len(),
+        format_size(total),
+        format_size(written),
+        settings.describe()
+    );
+    if edited > 0 {
+        summary.push_str(&format!(", metadata edited on {}", edited));
+    }
+    Ok(summary)
+}
+
+fn write_archive(folder: &Path, output: &Path, files: &[walkdir::DirEntry], settings: ZipSettings, task: &TaskContext) -> Result<u64, String> {
+    let file = fs::File::create(long_path(output)).map_err(|e| e.to_string())?;
+    let mut writer = zip::ZipWriter::new(BufWriter::new(file));
+    let method = if settings.store_only { zip::CompressionMethod::Stored } else { zip::CompressionMethod::Deflated };
+    let level = if settings.store_only { None } else { Some(settings.level.min(9) as i64) };
+
+    let mut total = 0;
+    for entry in files {
+        if task.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        // Archive names always use forward slashes
+        let relative = entry.path().strip_prefix(folder).unwrap_or(entry.path());
+        let name: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        let name = name.join("/");
+        task.advance(name.clone());
+
+        let metadata = entry.metadata().map_err(|e| format!("{}: {}", name, e))?;
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(method)
+            .compression_level(level)
+            .last_modified_time(dos_time(crate::modified_millis(&metadata)))
+            .large_file(metadata.len() >= u32::MAX as u64);
+        writer.start_file(name.as_str(), options).map_err(|e| format!("{}: {}", name, e))?;
+        let mut source = fs::File::open(long_path(entry.path())).map_err(|e| format!("{}: {}", name, e))?;
+        total += std::io::copy(&mut source, &mut writer).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(total)
+}
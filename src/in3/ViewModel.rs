@@ -1,7 +1,9 @@
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
 use std::path::PathBuf;
-use std::fs::File;
 use super::binary_reader::BinaryReader;
+use crate::gen::read_scene::GameType;
 
 #[derive(Debug, Clone)]
 pub struct Vertex {
@@ -24,6 +26,159 @@ pub struct Model {
     pub bounds_max: [f32; 3],
 }
 
+#[derive(Debug, Clone)]
+pub struct MeshStats {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    pub surface_area: f32,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+}
+
+impl MeshStats {
+    fn from_mesh(mesh: &Mesh) -> Self {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for vertex in &mesh.vertices {
+            for i in 0..3 {
+                min[i] = min[i].min(vertex.position[i]);
+                max[i] = max[i].max(vertex.position[i]);
+            }
+        }
+        if mesh.vertices.is_empty() {
+            min = [0.0; 3];
+            max = [0.0; 3];
+        }
+
+        let mut triangle_count = 0;
+        let mut surface_area = 0.0;
+        for chunk in mesh.indices.chunks_exact(3) {
+            let (Some(a), Some(b), Some(c)) = (
+                mesh.vertices.get(chunk[0] as usize),
+                mesh.vertices.get(chunk[1] as usize),
+                mesh.vertices.get(chunk[2] as usize),
+            ) else {
+                continue;
+            };
+            triangle_count += 1;
+
+            // Half the cross product length of two edges
+            let ab = sub(b.position, a.position);
+            let ac = sub(c.position, a.position);
+            let cross = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+            surface_area += length(cross) * 0.5;
+        }
+
+        Self {
+            vertex_count: mesh.vertices.len(),
+            triangle_count,
+            surface_area,
+            bounds_min: min,
+            bounds_max: max,
+        }
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AxisPlacement {
+    ModelCenter,
+    Corner,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewportSettings {
+    pub background: [u8; 3],
+    pub show_grid: bool,
+    pub grid_spacing: f32,
+    pub grid_lines: usize,
+    pub shaded: bool,
+    pub mesh_color: [u8; 3],
+    // Light direction in degrees
+    pub light_azimuth: f32,
+    pub light_elevation: f32,
+    pub light_intensity: f32,
+    pub ambient: f32,
+    pub axis_placement: AxisPlacement,
+}
+
+impl Default for ViewportSettings {
+    fn default() -> Self {
+        Self {
+            background: [20, 20, 40],
+            show_grid: false,
+            grid_spacing: 1.0,
+            grid_lines: 10,
+            shaded: false,
+            mesh_color: [180, 180, 200],
+            light_azimuth: 45.0,
+            light_elevation: 45.0,
+            light_intensity: 0.8,
+            ambient: 0.2,
+            axis_placement: AxisPlacement::ModelCenter,
+        }
+    }
+}
+
+impl ViewportSettings {
+    fn light_direction(&self) -> [f32; 3] {
+        let azimuth = self.light_azimuth.to_radians();
+        let elevation = self.light_elevation.to_radians();
+        [
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        ]
+    }
+}
+
+// Vertex/index buffer conventions of a game's model files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferLayout {
+    // None picks the byte order from the index buffer
+    pub big_endian: Option<bool>,
+    // Bytes per vertex; None works it out from the highest index
+    pub stride: Option<usize>,
+    // Float normal and UV offsets within a vertex, for games that interleave them
+    pub normal_offset: Option<usize>,
+    pub uv_offset: Option<usize>,
+}
+
+impl Default for BufferLayout {
+    fn default() -> Self {
+        Self { big_endian: Some(false), stride: Some(12), normal_offset: None, uv_offset: None }
+    }
+}
+
+impl BufferLayout {
+    pub fn for_game(game_type: &GameType) -> Self {
+        match game_type {
+            // Position-only streams
+            GameType::DisneyInfinity30 | GameType::Cars3DrivenToWinXB1 => Self::default(),
+            // Interleaved position, normal and UV; dumps come from both the PC and console builds
+            GameType::Cars2TheVideoGame | GameType::Cars2Arcade | GameType::ToyShit3 => Self {
+                big_endian: None,
+                stride: None,
+                normal_offset: Some(12),
+                uv_offset: Some(24),
+            },
+        }
+    }
+}
+
 pub struct ModelViewer {
     pub current_model: Option<Model>,
     pub camera_rotation: [f32; 2],
@@ -32,8 +187,22 @@ pub struct ModelViewer {
     pub show_vertices: bool,
     pub vertex_scale: f32,
     pub debug_info: String,
+    pub mesh_stats: Vec<MeshStats>,
+    pub measure_mode: bool,
+    // (mesh index, vertex index) of the picked measurement points
+    pub measure_points: Vec<(usize, usize)>,
+    // Raw VBUF bytes and the stride used to slice them per vertex in the table
+    pub vbuf_data: Vec<u8>,
+    pub raw_stride: usize,
+    pub vertex_table_page: usize,
+    pub selected_vertex: Option<(usize, usize)>,
+    pub settings: ViewportSettings,
+    // Buffer conventions of the selected game
+    pub layout: BufferLayout,
 }
 
+const VERTEX_TABLE_PAGE_SIZE: usize = 100;
+
 impl Default for ModelViewer {
     fn default() -> Self {
         Self {
@@ -44,6 +213,15 @@ impl Default for ModelViewer {
             show_vertices: false,
             vertex_scale: 0.1,
             debug_info: String::new(),
+            mesh_stats: Vec::new(),
+            measure_mode: false,
+            measure_points: Vec::new(),
+            vbuf_data: Vec::new(),
+            raw_stride: 12,
+            vertex_table_page: 0,
+            selected_vertex: None,
+            settings: ViewportSettings::default(),
+            layout: BufferLayout::default(),
         }
     }
 }
@@ -54,29 +232,36 @@ impl ModelViewer {
     }
 
     pub fn load_model_from_files(&mut self, ibuf_path: &PathBuf, vbuf_path: &PathBuf) -> Result<(), String> {
+        let ibuf = std::fs::read(ibuf_path).map_err(|e| format!("Failed to open IBUF file: {}", e))?;
+        let vbuf = std::fs::read(vbuf_path).map_err(|e| format!("Failed to open VBUF file: {}", e))?;
+        self.load_model_from_bytes(ibuf_path, &ibuf, vbuf_path, &vbuf)
+    }
+
+    // The paths only name the model; the buffers are read by the caller
+    pub fn load_model_from_bytes(&mut self, ibuf_path: &PathBuf, ibuf: &[u8], vbuf_path: &PathBuf, vbuf: &[u8]) -> Result<(), String> {
         self.debug_info = format!("Loading model:\nIBUF: {}\nVBUF: {}", 
             ibuf_path.display(), vbuf_path.display());
 
-        // Parse vertex buffer (VBUF)
-        let vertices = match self.parse_vertex_buffer(vbuf_path) {
-            Ok(v) => {
-                self.debug_info.push_str(&format!("\nParsed {} vertices", v.len()));
-                v
+        // Parse index buffer (IBUF) first, since the vertex stride may be worked out from it
+        let (indices, big_endian) = match self.parse_index_buffer(ibuf) {
+            Ok((i, big_endian)) => {
+                self.debug_info.push_str(&format!("\nParsed {} indices ({})", i.len(), if big_endian { "big endian" } else { "little endian" }));
+                (i, big_endian)
             }
             Err(e) => {
-                self.debug_info.push_str(&format!("\nVBUF Error: {}", e));
+                self.debug_info.push_str(&format!("\nIBUF Error: {}", e));
                 return Err(e);
             }
         };
 
-        // Parse index buffer (IBUF)
-        let indices = match self.parse_index_buffer(ibuf_path) {
-            Ok(i) => {
-                self.debug_info.push_str(&format!("\nParsed {} indices", i.len()));
-                i
+        // Parse vertex buffer (VBUF)
+        let (vertices, stride) = match self.parse_vertex_buffer(vbuf, big_endian, &indices) {
+            Ok((v, stride)) => {
+                self.debug_info.push_str(&format!("\nParsed {} vertices (stride {})", v.len(), stride));
+                (v, stride)
             }
             Err(e) => {
-                self.debug_info.push_str(&format!("\nIBUF Error: {}", e));
+                self.debug_info.push_str(&format!("\nVBUF Error: {}", e));
                 return Err(e);
             }
         };
@@ -89,12 +274,18 @@ impl ModelViewer {
         let mesh = Mesh {
             vertices,
             indices,
-            name: "Disney Infinity Model".to_string(),
+            name: ibuf_path.file_stem().map_or_else(|| "Model".to_string(), |s| s.to_string_lossy().into_owned()),
         };
 
         // Calculate bounding box
         let (bounds_min, bounds_max) = self.calculate_bounds(&[mesh.clone()]);
 
+        self.mesh_stats = vec![MeshStats::from_mesh(&mesh)];
+        self.measure_points.clear();
+        self.vbuf_data = vbuf.to_vec();
+        self.raw_stride = stride;
+        self.vertex_table_page = 0;
+        self.selected_vertex = None;
         self.current_model = Some(Model {
             meshes: vec![mesh],
             bounds_min,
@@ -105,72 +296,31 @@ impl ModelViewer {
         Ok(())
     }
 
-    fn parse_vertex_buffer(&self, vbuf_path: &PathBuf) -> Result<Vec<Vertex>, String> {
-        let file = File::open(vbuf_path)
-            .map_err(|e| format!("Failed to open VBUF file: {}", e))?;
-        
-        let mut reader = BinaryReader::new(file);
-        
-        // Try different vertex formats
-        let file_size = std::fs::metadata(vbuf_path)
-            .map(|m| m.len())
-            .unwrap_or(0);
-        
-        let mut vertices = Vec::new();
-        
-        // Try simple position-only format first (12 bytes per vertex)
-        let vertex_count = file_size / 12;
-        if vertex_count > 0 && vertex_count < 100000 { // Sanity check
-            if let Ok(simple_vertices) = self.parse_simple_vertices(&mut reader, vertex_count as usize) {
-                vertices = simple_vertices;
-            }
-        }
-        
-        // If simple parsing failed, try more complex formats
-        if vertices.is_empty() {
-            // Reset and try alternative format
-            let _ = reader.seek(0);
-            if let Ok(complex_vertices) = self.parse_complex_vertices(&mut reader) {
-                vertices = complex_vertices;
+    // Returns the vertices and the stride they were read with
+    fn parse_vertex_buffer(&self, vbuf: &[u8], big_endian: bool, indices: &[u16]) -> Result<(Vec<Vertex>, usize), String> {
+        let mut reader = BinaryReader::new(Cursor::new(vbuf)).big_endian(big_endian);
+        let file_size = vbuf.len() as u64;
+
+        // Every vertex is referenced, so the highest index gives the vertex count
+        let stride = self.layout.stride.unwrap_or_else(|| {
+            let vertex_count = indices.iter().copied().max().map_or(0, |max| max as u64 + 1);
+            if vertex_count > 0 && file_size % vertex_count == 0 && file_size / vertex_count >= 12 {
+                (file_size / vertex_count) as usize
+            } else {
+                12
             }
-        }
-        
-        if vertices.is_empty() {
-            return Err("Could not parse any vertices from VBUF file".to_string());
-        }
-        
-        Ok(vertices)
-    }
-
-    fn parse_simple_vertices(&self, reader: &mut BinaryReader<File>, count: usize) -> Result<Vec<Vertex>, String> {
-        let mut vertices = Vec::with_capacity(count);
-        
-        for _ in 0..count {
-            match reader.read_f32_array(3) {
-                Ok(pos) => {
-                    vertices.push(Vertex {
-                        position: [pos[0], pos[1], pos[2]],
-                        normal: [0.0, 1.0, 0.0], // Default normal
-                        uv: [0.0, 0.0], // Default UV
-                    });
-                }
-                Err(_) => break, // Stop if we can't read more
-            }
-        }
+        });
         
-        Ok(vertices)
-    }
-
-    fn parse_complex_vertices(&self, reader: &mut BinaryReader<File>) -> Result<Vec<Vertex>, String> {
         let mut vertices = Vec::new();
-        
-        // Try to read until EOF
-        while let Ok(pos) = reader.read_f32_array(3) {
-            // Try to read normal (3 floats)
-            let normal = reader.read_f32_array(3).unwrap_or_else(|_| vec![0.0, 1.0, 0.0]);
-            
-            // Try to read UV (2 floats)
-            let uv = reader.read_f32_array(2).unwrap_or_else(|_| vec![0.0, 0.0]);
+        for i in 0..file_size / stride as u64 {
+            let start = i * stride as u64;
+            let Some(pos) = Self::read_floats_at(&mut reader, start, Some(0), 3, stride) else {
+                break; // Stop if we can't read more
+            };
+            let normal = Self::read_floats_at(&mut reader, start, self.layout.normal_offset, 3, stride)
+                .unwrap_or_else(|| vec![0.0, 1.0, 0.0]);
+            let uv = Self::read_floats_at(&mut reader, start, self.layout.uv_offset, 2, stride)
+                .unwrap_or_else(|| vec![0.0, 0.0]);
             
             vertices.push(Vertex {
                 position: [pos[0], pos[1], pos[2]],
@@ -179,14 +329,22 @@ impl ModelViewer {
             });
         }
         
-        Ok(vertices)
+        if vertices.is_empty() {
+            return Err("Could not parse any vertices from VBUF file".to_string());
+        }
+        
+        Ok((vertices, stride))
     }
 
-    fn parse_index_buffer(&self, ibuf_path: &PathBuf) -> Result<Vec<u16>, String> {
-        let file = File::open(ibuf_path)
-            .map_err(|e| format!("Failed to open IBUF file: {}", e))?;
-        
-        let mut reader = BinaryReader::new(file);
+    // `count` floats at `offset` into the vertex at `start`, if they fit in the stride
+    fn read_floats_at(reader: &mut BinaryReader<Cursor<&[u8]>>, start: u64, offset: Option<usize>, count: usize, stride: usize) -> Option<Vec<f32>> {
+        let offset = offset.filter(|offset| offset + count * 4 <= stride)?;
+        reader.seek(start + offset as u64).ok()?;
+        reader.read_f32_array(count).ok()
+    }
+
+    fn read_indices(ibuf: &[u8], big_endian: bool) -> Vec<u16> {
+        let mut reader = BinaryReader::new(Cursor::new(ibuf)).big_endian(big_endian);
         let mut indices = Vec::new();
         
         // Read until EOF
@@ -194,7 +352,24 @@ impl ModelViewer {
             indices.push(index);
         }
         
-        Ok(indices)
+        indices
+    }
+
+    // Returns the indices and whether they (and so the vertices) are big endian
+    fn parse_index_buffer(&self, ibuf: &[u8]) -> Result<(Vec<u16>, bool), String> {
+        if let Some(big_endian) = self.layout.big_endian {
+            return Ok((Self::read_indices(ibuf, big_endian), big_endian));
+        }
+
+        // Indices stay below the vertex count, so the wrong byte order reads much larger ones
+        let little = Self::read_indices(ibuf, false);
+        let big = Self::read_indices(ibuf, true);
+        let max = |indices: &[u16]| indices.iter().copied().max().unwrap_or(0);
+        if max(&big) < max(&little) {
+            Ok((big, true))
+        } else {
+            Ok((little, false))
+        }
     }
 
     fn calculate_bounds(&self, meshes: &[Mesh]) -> ([f32; 3], [f32; 3]) {
@@ -226,6 +401,10 @@ impl ModelViewer {
     pub fn clear_model(&mut self) {
         self.current_model = None;
         self.debug_info.clear();
+        self.mesh_stats.clear();
+        self.measure_points.clear();
+        self.vbuf_data.clear();
+        self.selected_vertex = None;
     }
 
     pub fn has_model(&self) -> bool {
@@ -249,12 +428,55 @@ impl ModelViewer {
                 model.bounds_min[0], model.bounds_min[1], model.bounds_min[2],
                 model.bounds_max[0], model.bounds_max[1], model.bounds_max[2]));
 
+            let size = [
+                model.bounds_max[0] - model.bounds_min[0],
+                model.bounds_max[1] - model.bounds_min[1],
+                model.bounds_max[2] - model.bounds_min[2],
+            ];
+            ui.label(format!("Size: {:.3} x {:.3} x {:.3}", size[0], size[1], size[2]));
+
+            egui::CollapsingHeader::new("Mesh Statistics").show(ui, |ui| {
+                egui::Grid::new("mesh_stats_grid").striped(true).show(ui, |ui| {
+                    ui.strong("Mesh");
+                    ui.strong("Vertices");
+                    ui.strong("Triangles");
+                    ui.strong("Surface area");
+                    ui.strong("Dimensions");
+                    ui.end_row();
+
+                    for (mesh, stats) in model.meshes.iter().zip(&self.mesh_stats) {
+                        ui.label(&mesh.name);
+                        ui.label(stats.vertex_count.to_string());
+                        ui.label(stats.triangle_count.to_string());
+                        ui.label(format!("{:.3}", stats.surface_area));
+                        ui.label(format!(
+                            "{:.3} x {:.3} x {:.3}",
+                            stats.bounds_max[0] - stats.bounds_min[0],
+                            stats.bounds_max[1] - stats.bounds_min[1],
+                            stats.bounds_max[2] - stats.bounds_min[2]
+                        ));
+                        ui.end_row();
+                    }
+                });
+            });
+
+            egui::CollapsingHeader::new("Viewport Settings").show(ui, |ui| {
+                self.show_viewport_settings(ui);
+            });
+
+            egui::CollapsingHeader::new("Vertex Table").show(ui, |ui| {
+                self.show_vertex_table(ui, model);
+            });
+
             ui.separator();
 
             // Controls
             ui.horizontal(|ui| {
                 ui.checkbox(&mut self.show_wireframe, "Wireframe");
                 ui.checkbox(&mut self.show_vertices, "Vertices");
+                if ui.checkbox(&mut self.measure_mode, "Measure").changed() {
+                    self.measure_points.clear();
+                }
                 
                 // Add a clear button
                 if ui.button("Clear Model").clicked() {
@@ -267,6 +489,13 @@ impl ModelViewer {
                 ui.add(egui::Slider::new(&mut self.vertex_scale, 0.01..=1.0).text("Vertex Scale"));
             }
 
+            if self.measure_mode {
+                match self.measured_distance(model) {
+                    Some(distance) => ui.label(format!("Distance: {:.4} units", distance)),
+                    None => ui.label("Click two vertices in the view to measure the distance between them"),
+                };
+            }
+
             // Debug info
             if ui.button("Show Debug Info").clicked() {
                 // Debug info is already being collected during loading
@@ -287,13 +516,14 @@ impl ModelViewer {
     }
 
     fn show_3d_view(&mut self, ui: &mut egui::Ui, available_size: egui::Vec2, model: &Model) {
-        let (response, painter) = ui.allocate_painter(available_size, egui::Sense::drag());
+        let (response, painter) = ui.allocate_painter(available_size, egui::Sense::click_and_drag());
 
         // Draw a background so we can see the viewport area
+        let [r, g, b] = self.settings.background;
         painter.rect_filled(
             response.rect,
             egui::Rounding::ZERO, // Fixed: use ZERO instead of none()
-            egui::Color32::from_rgba_unmultiplied(20, 20, 40, 255),
+            egui::Color32::from_rgb(r, g, b),
         );
 
         // Handle camera rotation via dragging
@@ -336,6 +566,14 @@ impl ModelViewer {
         let max_size = model_size[0].max(model_size[1]).max(model_size[2]);
         let scale = if max_size > 0.0 { 2.0 / max_size } else { 1.0 };
 
+        if self.settings.show_grid {
+            self.draw_grid(&painter, model, center, scale, &camera_pos, available_size);
+        }
+
+        if self.settings.shaded {
+            self.draw_shaded(&painter, model, center, scale, &camera_pos, available_size);
+        }
+
         // Draw the model
         let mut triangle_count = 0;
         let mut vertex_count = 0;
@@ -384,8 +622,40 @@ impl ModelViewer {
             }
         }
 
+        if self.measure_mode {
+            if response.clicked() {
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    let picked = self.pick_vertex(model, pointer, center, scale, &camera_pos, available_size);
+                    if let Some(picked) = picked {
+                        if self.measure_points.len() >= 2 {
+                            self.measure_points.clear();
+                        }
+                        self.measure_points.push(picked);
+                    }
+                }
+            }
+            self.draw_measurement(&painter, model, center, scale, &camera_pos, available_size);
+        } else if response.clicked() {
+            // Clicking a vertex selects it in the vertex table
+            if let Some(pointer) = response.interact_pointer_pos() {
+                self.selected_vertex = self.pick_vertex(model, pointer, center, scale, &camera_pos, available_size);
+                if let Some((_, index)) = self.selected_vertex {
+                    self.vertex_table_page = index / VERTEX_TABLE_PAGE_SIZE;
+                }
+            }
+        }
+
+        if let Some(position) = self.selected_vertex.and_then(|p| Self::measure_position(model, p)) {
+            let pos = self.project_point(&position, center, scale, &camera_pos, available_size);
+            painter.circle_stroke(pos, 7.0, (2.0, egui::Color32::from_rgb(255, 120, 0)));
+        }
+
         // Draw coordinate axes
-        self.draw_coordinate_axes(&painter, center, scale, &camera_pos, available_size);
+        match self.settings.axis_placement {
+            AxisPlacement::ModelCenter => self.draw_coordinate_axes(&painter, center, scale, &camera_pos, available_size),
+            AxisPlacement::Corner => self.draw_corner_axes(&painter, response.rect, center, scale, &camera_pos, available_size),
+            AxisPlacement::Hidden => {}
+        }
 
         // Draw stats in corner
         let stats_text = format!("Triangles: {} | Vertices: {}", triangle_count, vertex_count);
@@ -438,6 +708,302 @@ impl ModelViewer {
         painter.text(z_end, egui::Align2::LEFT_TOP, "Z", egui::FontId::default(), egui::Color32::BLUE);
     }
 
+    fn show_viewport_settings(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.settings;
+        ui.horizontal(|ui| {
+            ui.label("Background:");
+            ui.color_edit_button_srgb(&mut settings.background);
+            ui.separator();
+            ui.label("Axes:");
+            egui::ComboBox::from_id_source("axis_placement")
+                .selected_text(format!("{:?}", settings.axis_placement))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.axis_placement, AxisPlacement::ModelCenter, "ModelCenter");
+                    ui.selectable_value(&mut settings.axis_placement, AxisPlacement::Corner, "Corner");
+                    ui.selectable_value(&mut settings.axis_placement, AxisPlacement::Hidden, "Hidden");
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.show_grid, "Ground grid");
+            ui.add_enabled(settings.show_grid, egui::DragValue::new(&mut settings.grid_spacing)
+                .speed(0.05)
+                .clamp_range(0.01..=1000.0)
+                .prefix("spacing "));
+            ui.add_enabled(settings.show_grid, egui::DragValue::new(&mut settings.grid_lines)
+                .clamp_range(1..=100)
+                .prefix("lines "));
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.shaded, "Shaded");
+            ui.add_enabled_ui(settings.shaded, |ui| {
+                ui.color_edit_button_srgb(&mut settings.mesh_color);
+            });
+        });
+
+        ui.add_enabled_ui(settings.shaded, |ui| {
+            ui.add(egui::Slider::new(&mut settings.light_azimuth, -180.0..=180.0).text("Light azimuth"));
+            ui.add(egui::Slider::new(&mut settings.light_elevation, -90.0..=90.0).text("Light elevation"));
+            ui.add(egui::Slider::new(&mut settings.light_intensity, 0.0..=1.0).text("Light intensity"));
+            ui.add(egui::Slider::new(&mut settings.ambient, 0.0..=1.0).text("Ambient"));
+        });
+
+        if ui.button("Reset viewport settings").clicked() {
+            *settings = ViewportSettings::default();
+        }
+    }
+
+    // Ground plane grid under the model, spaced in model units
+    fn draw_grid(&self, painter: &egui::Painter, model: &Model, center: [f32; 3], scale: f32, camera_pos: &[f32; 3], viewport_size: egui::Vec2) {
+        let spacing = self.settings.grid_spacing.max(0.001);
+        let half = self.settings.grid_lines as f32 * spacing;
+        let y = model.bounds_min[1];
+        let color = egui::Color32::from_gray(70);
+
+        for i in 0..=self.settings.grid_lines * 2 {
+            let offset = -half + i as f32 * spacing;
+            let lines = [
+                ([center[0] + offset, y, center[2] - half], [center[0] + offset, y, center[2] + half]),
+                ([center[0] - half, y, center[2] + offset], [center[0] + half, y, center[2] + offset]),
+            ];
+            for (start, end) in lines {
+                let a = self.project_point(&start, center, scale, camera_pos, viewport_size);
+                let b = self.project_point(&end, center, scale, camera_pos, viewport_size);
+                painter.line_segment([a, b], (1.0, color));
+            }
+        }
+    }
+
+    // Flat-shaded triangles, sorted back to front
+    fn draw_shaded(&self, painter: &egui::Painter, model: &Model, center: [f32; 3], scale: f32, camera_pos: &[f32; 3], viewport_size: egui::Vec2) {
+        let light = self.settings.light_direction();
+        let [r, g, b] = self.settings.mesh_color;
+        let mut triangles = Vec::new();
+
+        for mesh in &model.meshes {
+            for chunk in mesh.indices.chunks_exact(3) {
+                let (Some(v0), Some(v1), Some(v2)) = (
+                    mesh.vertices.get(chunk[0] as usize),
+                    mesh.vertices.get(chunk[1] as usize),
+                    mesh.vertices.get(chunk[2] as usize),
+                ) else {
+                    continue;
+                };
+
+                let e1 = sub(v1.position, v0.position);
+                let e2 = sub(v2.position, v0.position);
+                let normal = [
+                    e1[1] * e2[2] - e1[2] * e2[1],
+                    e1[2] * e2[0] - e1[0] * e2[2],
+                    e1[0] * e2[1] - e1[1] * e2[0],
+                ];
+                let normal_length = length(normal);
+                if normal_length <= f32::EPSILON {
+                    continue;
+                }
+                let diffuse = ((normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2]) / normal_length).abs();
+                let brightness = (self.settings.ambient + diffuse * self.settings.light_intensity).min(1.0);
+
+                let centroid = [
+                    (v0.position[0] + v1.position[0] + v2.position[0]) / 3.0,
+                    (v0.position[1] + v1.position[1] + v2.position[1]) / 3.0,
+                    (v0.position[2] + v1.position[2] + v2.position[2]) / 3.0,
+                ];
+                let view_centroid = [
+                    (centroid[0] - center[0]) * scale,
+                    (centroid[1] - center[1]) * scale,
+                    (centroid[2] - center[2]) * scale,
+                ];
+                let depth = length(sub(view_centroid, *camera_pos));
+
+                let points = [v0, v1, v2]
+                    .map(|v| self.project_point(&v.position, center, scale, camera_pos, viewport_size));
+                let color = egui::Color32::from_rgb(
+                    (r as f32 * brightness) as u8,
+                    (g as f32 * brightness) as u8,
+                    (b as f32 * brightness) as u8,
+                );
+                triangles.push((depth, points, color));
+            }
+        }
+
+        triangles.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, points, color) in triangles {
+            painter.add(egui::Shape::convex_polygon(points.to_vec(), color, egui::Stroke::NONE));
+        }
+    }
+
+    // Orientation-only axes drawn in the bottom right corner of the viewport
+    fn draw_corner_axes(&self, painter: &egui::Painter, rect: egui::Rect, center: [f32; 3], scale: f32, camera_pos: &[f32; 3], viewport_size: egui::Vec2) {
+        let origin = self.project_point(&center, center, scale, camera_pos, viewport_size);
+        let corner = rect.right_bottom() + egui::Vec2::new(-50.0, -50.0);
+        let axes = [
+            ([1.0, 0.0, 0.0], egui::Color32::RED, "X"),
+            ([0.0, 1.0, 0.0], egui::Color32::GREEN, "Y"),
+            ([0.0, 0.0, 1.0], egui::Color32::BLUE, "Z"),
+        ];
+
+        for (direction, color, label) in axes {
+            let tip = [center[0] + direction[0] / scale, center[1] + direction[1] / scale, center[2] + direction[2] / scale];
+            let projected = self.project_point(&tip, center, scale, camera_pos, viewport_size) - origin;
+            if projected.length() < f32::EPSILON {
+                continue;
+            }
+            let end = corner + projected.normalized() * 35.0;
+            painter.line_segment([corner, end], (2.0, color));
+            painter.text(end, egui::Align2::LEFT_TOP, label, egui::FontId::default(), color);
+        }
+    }
+
+    fn raw_vertex_bytes(&self, index: usize) -> &[u8] {
+        let start = (index * self.raw_stride).min(self.vbuf_data.len());
+        let end = (start + self.raw_stride).min(self.vbuf_data.len());
+        &self.vbuf_data[start..end]
+    }
+
+    fn show_vertex_table(&mut self, ui: &mut egui::Ui, model: &Model) {
+        // Only the first mesh has a VBUF behind it
+        let Some(mesh) = model.meshes.first() else {
+            return;
+        };
+
+        let page_count = mesh.vertices.len().div_ceil(VERTEX_TABLE_PAGE_SIZE).max(1);
+        self.vertex_table_page = self.vertex_table_page.min(page_count - 1);
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.vertex_table_page > 0, egui::Button::new("◀")).clicked() {
+                self.vertex_table_page -= 1;
+            }
+            ui.label(format!("Page {} / {}", self.vertex_table_page + 1, page_count));
+            if ui.add_enabled(self.vertex_table_page + 1 < page_count, egui::Button::new("▶")).clicked() {
+                self.vertex_table_page += 1;
+            }
+            ui.separator();
+            ui.label("Raw stride:");
+            ui.add(egui::DragValue::new(&mut self.raw_stride).clamp_range(1..=256));
+            if ui.button("Export CSV...").clicked() {
+                self.export_vertex_csv(mesh);
+            }
+        });
+
+        let start = self.vertex_table_page * VERTEX_TABLE_PAGE_SIZE;
+        let end = (start + VERTEX_TABLE_PAGE_SIZE).min(mesh.vertices.len());
+
+        egui::ScrollArea::both()
+            .id_source("vertex_table_scroll")
+            .max_height(250.0)
+            .show(ui, |ui| {
+                egui::Grid::new("vertex_table_grid").striped(true).show(ui, |ui| {
+                    ui.strong("#");
+                    ui.strong("Position");
+                    ui.strong("Normal");
+                    ui.strong("UV");
+                    ui.strong("Raw bytes");
+                    ui.end_row();
+
+                    for index in start..end {
+                        let vertex = &mesh.vertices[index];
+                        let selected = self.selected_vertex == Some((0, index));
+                        if ui.selectable_label(selected, index.to_string()).clicked() {
+                            self.selected_vertex = if selected { None } else { Some((0, index)) };
+                        }
+                        ui.monospace(format!("{:.4}, {:.4}, {:.4}", vertex.position[0], vertex.position[1], vertex.position[2]));
+                        ui.monospace(format!("{:.3}, {:.3}, {:.3}", vertex.normal[0], vertex.normal[1], vertex.normal[2]));
+                        ui.monospace(format!("{:.4}, {:.4}", vertex.uv[0], vertex.uv[1]));
+                        let raw: Vec<String> = self.raw_vertex_bytes(index).iter().map(|b| format!("{:02x}", b)).collect();
+                        ui.monospace(raw.join(" "));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    fn export_vertex_csv(&self, mesh: &Mesh) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export vertex table")
+            .set_file_name("vertices.csv")
+            .add_filter("CSV", &["csv"])
+            .save_file() else {
+            return;
+        };
+
+        let mut csv = String::from("index,px,py,pz,nx,ny,nz,u,v,raw\n");
+        for (index, vertex) in mesh.vertices.iter().enumerate() {
+            let raw: Vec<String> = self.raw_vertex_bytes(index).iter().map(|b| format!("{:02x}", b)).collect();
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                index,
+                vertex.position[0], vertex.position[1], vertex.position[2],
+                vertex.normal[0], vertex.normal[1], vertex.normal[2],
+                vertex.uv[0], vertex.uv[1],
+                raw.join(" ")
+            ));
+        }
+
+        match std::fs::write(&path, csv) {
+            Ok(()) => println!("Exported {} vertices to {}", mesh.vertices.len(), path.display()),
+            Err(e) => eprintln!("Failed to export vertex table: {}", e),
+        }
+    }
+
+    // Nearest vertex to the pointer in screen space, within a few pixels
+    fn pick_vertex(&self, model: &Model, pointer: egui::Pos2, center: [f32; 3], scale: f32, camera_pos: &[f32; 3], viewport_size: egui::Vec2) -> Option<(usize, usize)> {
+        const PICK_RADIUS: f32 = 10.0;
+        let mut best = None;
+        let mut best_distance = PICK_RADIUS;
+
+        for (mesh_index, mesh) in model.meshes.iter().enumerate() {
+            for (vertex_index, vertex) in mesh.vertices.iter().enumerate() {
+                let pos = self.project_point(&vertex.position, center, scale, camera_pos, viewport_size);
+                let distance = pos.distance(pointer);
+                if distance < best_distance {
+                    best_distance = distance;
+                    best = Some((mesh_index, vertex_index));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn measure_position(model: &Model, point: (usize, usize)) -> Option<[f32; 3]> {
+        model.meshes.get(point.0)?.vertices.get(point.1).map(|v| v.position)
+    }
+
+    fn measured_distance(&self, model: &Model) -> Option<f32> {
+        match self.measure_points.as_slice() {
+            [a, b] => Some(length(sub(
+                Self::measure_position(model, *b)?,
+                Self::measure_position(model, *a)?,
+            ))),
+            _ => None,
+        }
+    }
+
+    fn draw_measurement(&self, painter: &egui::Painter, model: &Model, center: [f32; 3], scale: f32, camera_pos: &[f32; 3], viewport_size: egui::Vec2) {
+        let points: Vec<egui::Pos2> = self.measure_points
+            .iter()
+            .filter_map(|p| Self::measure_position(model, *p))
+            .map(|p| self.project_point(&p, center, scale, camera_pos, viewport_size))
+            .collect();
+
+        for point in &points {
+            painter.circle_filled(*point, 5.0, egui::Color32::LIGHT_BLUE);
+        }
+
+        if let ([a, b], Some(distance)) = (points.as_slice(), self.measured_distance(model)) {
+            painter.line_segment([*a, *b], (2.0, egui::Color32::LIGHT_BLUE));
+            painter.text(
+                a.lerp(*b, 0.5),
+                egui::Align2::CENTER_BOTTOM,
+                format!("{:.4}", distance),
+                egui::FontId::default(),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+
     fn is_point_in_viewport(&self, point: egui::Pos2, viewport_size: egui::Vec2) -> bool {
         point.x >= 0.0 && point.x <= viewport_size.x && point.y >= 0.0 && point.y <= viewport_size.y
     }
@@ -2,11 +2,18 @@ use std::io::{Read, Seek, SeekFrom};
 
 pub struct BinaryReader<T: Read + Seek> {
     reader: T,
+    big_endian: bool,
 }
 
 impl<T: Read + Seek> BinaryReader<T> {
     pub fn new(reader: T) -> Self {
-        Self { reader }
+        Self { reader, big_endian: false }
+    }
+
+    // Console builds store their buffers big endian
+    pub fn big_endian(mut self, big_endian: bool) -> Self {
+        self.big_endian = big_endian;
+        self
     }
 
     pub fn seek(&mut self, pos: u64) -> std::io::Result<()> {
@@ -21,19 +28,19 @@ impl<T: Read + Seek> BinaryReader<T> {
     pub fn read_f32(&mut self) -> std::io::Result<f32> {
         let mut buf = [0u8; 4];
         self.reader.read_exact(&mut buf)?;
-        Ok(f32::from_le_bytes(buf))
+        Ok(if self.big_endian { f32::from_be_bytes(buf) } else { f32::from_le_bytes(buf) })
     }
 
     pub fn read_u16(&mut self) -> std::io::Result<u16> {
         let mut buf = [0u8; 2];
         self.reader.read_exact(&mut buf)?;
-        Ok(u16::from_le_bytes(buf))
+        Ok(if self.big_endian { u16::from_be_bytes(buf) } else { u16::from_le_bytes(buf) })
     }
 
     pub fn read_u32(&mut self) -> std::io::Result<u32> {
         let mut buf = [0u8; 4];
         self.reader.read_exact(&mut buf)?;
-        Ok(u32::from_le_bytes(buf))
+        Ok(if self.big_endian { u32::from_be_bytes(buf) } else { u32::from_le_bytes(buf) })
     }
 
     pub fn read_bytes(&mut self, count: usize) -> std::io::Result<Vec<u8>> {
@@ -0,0 +1,195 @@
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use super::ViewModel::Model;
+
+// glTF component and target constants
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// PNG file names (relative to the .gltf) wired into a model's material
+#[derive(Debug, Clone, Default)]
+pub struct GltfMaterial {
+    pub name: String,
+    pub base_color: Option<String>,
+    pub normal: Option<String>,
+}
+
+// Binary buffer plus the bufferViews and accessors describing it
+struct BufferBuilder {
+    data: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl BufferBuilder {
+    // Returns the accessor index
+    fn push(&mut self, bytes: &[u8], target: u32, accessor: Value) -> usize {
+        // Accessors need their offsets aligned to the component size
+        while self.data.len() % 4 != 0 {
+            self.data.push(0);
+        }
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.data.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.data.extend_from_slice(bytes);
+
+        let mut accessor = accessor;
+        accessor["bufferView"] = json!(self.views.len() - 1);
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_floats<const N: usize>(&mut self, values: &[[f32; N]], kind: &str, with_bounds: bool) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let mut accessor = json!({ "componentType": FLOAT, "count": values.len(), "type": kind });
+        // Required for positions
+        if with_bounds {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+            for value in values {
+                for i in 0..N {
+                    min[i] = min[i].min(value[i]);
+                    max[i] = max[i].max(value[i]);
+                }
+            }
+            accessor["min"] = json!(min.to_vec());
+            accessor["max"] = json!(max.to_vec());
+        }
+        self.push(&bytes, ARRAY_BUFFER, accessor)
+    }
+}
+
+// A model written once and placed by any number of nodes
+pub struct GltfModel<'a> {
+    pub name: String,
+    pub model: &'a Model,
+    pub material: Option<GltfMaterial>,
+}
+
+// `matrix` is column-major; None leaves the node at the origin
+pub struct GltfNode {
+    pub name: String,
+    pub model: usize,
+    pub matrix: Option<[f32; 16]>,
+}
+
+// Writes <name>.gltf and <name>.bin into `output_dir` for a single model. Returns the .gltf path.
+pub fn write_gltf(model: &Model, name: &str, material: Option<&GltfMaterial>, output_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let models = [GltfModel { name: name.to_string(), model, material: material.cloned() }];
+    let nodes = [GltfNode { name: name.to_string(), model: 0, matrix: None }];
+    write_gltf_scene(name, &models, &nodes, output_dir)
+}
+
+// Each model becomes one glTF mesh with a primitive per game mesh, and each node places one
+fn push_model(buffer: &mut BufferBuilder, model: &GltfModel, material_index: Option<usize>) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut primitives = Vec::new();
+    for (index, mesh) in model.model.meshes.iter().enumerate() {
+        if mesh.vertices.is_empty() || mesh.indices.is_empty() {
+            continue;
+        }
+        if let Some(&bad) = mesh.indices.iter().find(|&&i| i as usize >= mesh.vertices.len()) {
+            return Err(format!("{}: mesh {} has index {} past its {} vertices", model.name, index, bad, mesh.vertices.len()).into());
+        }
+
+        let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.position).collect();
+        let normals: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.normal).collect();
+        let uvs: Vec<[f32; 2]> = mesh.vertices.iter().map(|v| v.uv).collect();
+        let position = buffer.push_floats(&positions, "VEC3", true);
+        let normal = buffer.push_floats(&normals, "VEC3", false);
+        let uv = buffer.push_floats(&uvs, "VEC2", false);
+        let index_bytes: Vec<u8> = mesh.indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let indices = buffer.push(
+            &index_bytes,
+            ELEMENT_ARRAY_BUFFER,
+            json!({ "componentType": UNSIGNED_SHORT, "count": mesh.indices.len(), "type": "SCALAR" }),
+        );
+
+        let mut primitive = json!({
+            "attributes": { "POSITION": position, "NORMAL": normal, "TEXCOORD_0": uv },
+            "indices": indices,
+        });
+        if let Some(material_index) = material_index {
+            primitive["material"] = json!(material_index);
+        }
+        primitives.push(primitive);
+    }
+    if primitives.is_empty() {
+        return Err(format!("{} has no triangles to export", model.name).into());
+    }
+    Ok(json!({ "name": model.name, "primitives": primitives }))
+}
+
+// Writes <name>.gltf and <name>.bin into `output_dir` with every node in one scene. Returns the
+// .gltf path.
+pub fn write_gltf_scene(name: &str, models: &[GltfModel], nodes: &[GltfNode], output_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut buffer = BufferBuilder { data: Vec::new(), views: Vec::new(), accessors: Vec::new() };
+    let mut meshes = Vec::new();
+    let mut materials = Vec::new();
+    let mut images = Vec::new();
+
+    for model in models {
+        let material_index = model.material.as_ref().map(|material| {
+            let mut texture_ref = |uri: &Option<String>| {
+                uri.as_ref().map(|uri| {
+                    images.push(json!({ "uri": uri }));
+                    json!({ "index": images.len() - 1 })
+                })
+            };
+            // Game materials aren't PBR, so the surface is left fully rough and non-metallic
+            let mut pbr = json!({ "metallicFactor": 0.0, "roughnessFactor": 1.0 });
+            if let Some(base_color) = texture_ref(&material.base_color) {
+                pbr["baseColorTexture"] = base_color;
+            }
+            let mut gltf_material = json!({ "name": material.name, "pbrMetallicRoughness": pbr });
+            if let Some(normal) = texture_ref(&material.normal) {
+                gltf_material["normalTexture"] = normal;
+            }
+            materials.push(gltf_material);
+            materials.len() - 1
+        });
+        meshes.push(push_model(&mut buffer, model, material_index)?);
+    }
+
+    let gltf_nodes: Vec<Value> = nodes
+        .iter()
+        .map(|node| {
+            let mut gltf_node = json!({ "name": node.name, "mesh": node.model });
+            if let Some(matrix) = node.matrix {
+                gltf_node["matrix"] = json!(matrix.to_vec());
+            }
+            gltf_node
+        })
+        .collect();
+
+    let bin_name = format!("{}.bin", name);
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": concat!("Tundra ", env!("CARGO_PKG_VERSION")) },
+        "scene": 0,
+        "scenes": [{ "name": name, "nodes": (0..gltf_nodes.len()).collect::<Vec<_>>() }],
+        "nodes": gltf_nodes,
+        "meshes": meshes,
+        "buffers": [{ "uri": bin_name, "byteLength": buffer.data.len() }],
+        "bufferViews": buffer.views,
+        "accessors": buffer.accessors,
+    });
+    if !materials.is_empty() {
+        document["materials"] = json!(materials);
+    }
+    if !images.is_empty() {
+        let textures: Vec<Value> = (0..images.len()).map(|i| json!({ "sampler": 0, "source": i })).collect();
+        document["images"] = json!(images);
+        document["textures"] = json!(textures);
+        document["samplers"] = json!([{}]);
+    }
+
+    std::fs::create_dir_all(output_dir)?;
+    std::fs::write(output_dir.join(&bin_name), &buffer.data)?;
+    let gltf_path = output_dir.join(format!("{}.gltf", name));
+    std::fs::write(&gltf_path, serde_json::to_string_pretty(&document)?)?;
+    Ok(gltf_path)
+}
@@ -1,3 +1,4 @@
 pub mod ViewModel;
 pub mod binary_reader;
-pub mod read_zip;
\ No newline at end of file
+pub mod read_zip;
+pub mod gltf_export;
\ No newline at end of file
@@ -1,6 +1,6 @@
 use aes::cipher::{KeyIvInit, StreamCipher};
 use binrw::BinRead;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 type Aes128CtrCipher = ctr::Ctr128BE<aes::Aes128>;
@@ -34,9 +34,31 @@ struct ZipLocalFileHeader {
 
 pub struct DisneyInfinityZipReader;
 
+// Decrypts the first `encrypted` bytes of an entry as they're read
+struct DecryptingReader<R: Read> {
+    inner: R,
+    cipher: Aes128CtrCipher,
+    encrypted: usize,
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        let decrypt = read.min(self.encrypted);
+        self.cipher.apply_keystream(&mut buf[..decrypt]);
+        self.encrypted -= decrypt;
+        Ok(read)
+    }
+}
+
 impl DisneyInfinityZipReader {
+    // Archives named psx_* are encrypted with the PlayStation key instead
+    pub fn uses_psx_key(file_name: &str) -> bool {
+        file_name.to_lowercase().starts_with("psx_")
+    }
+
     fn get_key(file_name: &str) -> &'static [u8; 16] {
-        if file_name.to_lowercase().starts_with("psx_") {
+        if Self::uses_psx_key(file_name) {
             &PSX_KEY
         } else {
             &DI3_KEY
@@ -153,7 +175,8 @@ impl DisneyInfinityZipReader {
             }
             
             // Try to read the file header at this offset
-            if let Some(entry) = Self::read_file_header(&mut reader, key, header_offset, file_size) {
+            if let Some(mut entry) = Self::read_file_header(&mut reader, key, header_offset, file_size) {
+                entry.name_hash = name_mmh3;
                 entries.push(entry);
             }
         }
@@ -198,7 +221,8 @@ impl DisneyInfinityZipReader {
             }
             
             // Try to read the file header at this offset
-            if let Some(entry) = Self::read_file_header(reader, key, header_offset, file_size) {
+            if let Some(mut entry) = Self::read_file_header(reader, key, header_offset, file_size) {
+                entry.name_hash = name_mmh3;
                 entries.push(entry);
             }
             
@@ -291,10 +315,39 @@ impl DisneyInfinityZipReader {
             compressed_size: header.compressed_size,
             uncompressed_size: header.uncompressed_size,
             compression_method: header.compression,
-            extra_field_length: header.extra_field_length
+            extra_field_length: header.extra_field_length,
+            crc32: header.crc32,
+            name_hash: 0,
         })
     }
 
+    // Where an entry's data starts: after its local header, name and extra field
+    pub fn data_offset(entry: &DisneyInfinityZipEntry) -> u64 {
+        entry.header_offset as u64 + 30 + entry.name.len() as u64 + entry.extra_field_length as u64
+    }
+
+    // How many leading bytes of an entry are encrypted; .dct files are encrypted throughout
+    pub fn encrypted_length(entry: &DisneyInfinityZipEntry) -> usize {
+        if entry.name.to_lowercase().ends_with(".dct") {
+            entry.compressed_size as usize
+        } else {
+            0x200.min(entry.compressed_size as usize)
+        }
+    }
+
+    // An entry's bytes exactly as stored, still encrypted and compressed
+    pub fn read_raw<P: AsRef<Path>>(
+        zip_path: P,
+        entry: &DisneyInfinityZipEntry,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(zip_path.as_ref())?;
+        let mut reader = std::io::BufReader::new(file);
+        reader.seek(SeekFrom::Start(Self::data_offset(entry)))?;
+        let mut data = vec![0u8; entry.compressed_size as usize];
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
     pub fn extract_file<P: AsRef<Path>>(
         zip_path: P,
         entry: &DisneyInfinityZipEntry,
@@ -309,51 +362,171 @@ impl DisneyInfinityZipReader {
         
         let key = Self::get_key(file_name);
         
-        let file = std::fs::File::open(path)?;
-        let mut reader = std::io::BufReader::new(file);
-        
-        // Seek to the file data (header offset + header size + file name + extra field)
-        let data_offset = entry.header_offset as u64 + 30 + entry.name.len() as u64 + entry.extra_field_length as u64;
-        reader.seek(SeekFrom::Start(data_offset))?;
-        
-        // Read compressed data
-        let mut compressed_data = vec![0u8; entry.compressed_size as usize];
-        reader.read_exact(&mut compressed_data)?;
+        let mut compressed_data = Self::read_raw(path, entry)?;
         
         // Decrypt only the first 0x200 bytes (unless it's a .dct file)
-        let bytes_to_decrypt = if entry.name.to_lowercase().ends_with(".dct") {
-            compressed_data.len()
-        } else {
-            0x200.min(compressed_data.len())
-        };
-        
-        Self::decrypt_data(&mut compressed_data, key, bytes_to_decrypt);
+        Self::decrypt_data(&mut compressed_data, key, Self::encrypted_length(entry));
         
         // Decompress if needed
-        if entry.compression_method == 0 {
-            // Store - no compression
-            Ok(compressed_data)
-        } else {
-            let mut decoder = flate2::read::ZlibDecoder::new(&compressed_data[..]);
-            let mut decompressed_data = Vec::new();
+        crate::gen::codecs::decompress(entry.compression_method, &compressed_data, entry.uncompressed_size as usize, &entry.name).map_err(|e| e.into())
+    }
+
+    // Like extract_file, but decompresses into `output` as it reads, for entries too big to hold
+    pub fn extract_file_to<P: AsRef<Path>>(
+        zip_path: P,
+        entry: &DisneyInfinityZipEntry,
+        output: &mut impl Write,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let path = zip_path.as_ref();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        reader.seek(SeekFrom::Start(Self::data_offset(entry)))?;
+        let input = DecryptingReader {
+            inner: reader.take(entry.compressed_size as u64),
+            cipher: Self::create_cipher(Self::get_key(file_name)),
+            encrypted: Self::encrypted_length(entry),
+        };
+        let written = crate::gen::codecs::decompress_to(entry.compression_method, input, output, &entry.name)?;
+        if written != entry.uncompressed_size as u64 {
+            return Err(format!("{} came out as {} bytes, expected {}", entry.name, written, entry.uncompressed_size).into());
+        }
+        Ok(written)
+    }
+
+    // Writes a copy of the archive with every encrypted region decrypted in place and a
+    // standard central directory appended, so regular zip tools can open the result.
+    pub fn dump_decrypted<P: AsRef<Path>, Q: AsRef<Path>>(
+        zip_path: P,
+        output_path: Q,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let path = zip_path.as_ref();
+
+        // Get file name from path for key selection
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let key = Self::get_key(file_name);
+
+        let entries = Self::read_zip_contents(path)?;
+        let mut data = std::fs::read(path)?;
+        let file_size = data.len();
+
+        if file_size < 8 {
+            return Err("Archive is too small to be a Disney Infinity 3.0 zip".into());
+        }
+
+        // PK\xff\xff header and file count are decrypted separately
+        Self::decrypt_data(&mut data[0..4], key, 4);
+        Self::decrypt_data(&mut data[4..8], key, 4);
+        let files_count = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let max_reasonable_files = file_size / 100;
 
-            // Try zlib
-            if decoder.read_to_end(&mut decompressed_data).is_ok() && decompressed_data.len() == entry.uncompressed_size as usize {
-                println!("Successfully decompressed {}", entry.name);
-                return Ok(decompressed_data);
+        // Octane entry table (name hash + header offset per entry)
+        let mut table_pos = 8;
+        let mut table_index = 0;
+        while table_pos + 8 <= file_size {
+            if files_count <= max_reasonable_files && table_index >= files_count {
+                break;
             }
 
-            // Try deflate if zlib fails
-            decompressed_data.clear();
-            reader.seek(SeekFrom::Start(data_offset))?;
-            let mut decoder = flate2::read::DeflateDecoder::new(&compressed_data[..]);
-            if decoder.read_to_end(&mut decompressed_data).is_ok() && decompressed_data.len() == entry.uncompressed_size as usize {
-                println!("Successfully decompressed {}", entry.name);
-                return Ok(decompressed_data);
-            } else {
-                return Err(format!("Failed to decompress {}", entry.name).into());
+            Self::decrypt_data(&mut data[table_pos..table_pos + 8], key, 8);
+
+            // Manual tables end on a zero offset, same as read_zip_contents_manual
+            let header_offset = u32::from_le_bytes(data[table_pos + 4..table_pos + 8].try_into().unwrap());
+            table_pos += 8;
+            table_index += 1;
+
+            if files_count > max_reasonable_files && (header_offset == 0 || table_index > 10000) {
+                break;
+            }
+        }
+
+        let mut central_directory = Vec::new();
+        let mut written_entries = 0usize;
+        let mut seen_offsets = std::collections::HashSet::new();
+
+        for entry in &entries {
+            // Decrypting a region twice would re-encrypt it
+            if !seen_offsets.insert(entry.header_offset) {
+                continue;
             }
+
+            let header_start = entry.header_offset as usize;
+            let name_start = header_start + 30;
+            if name_start > file_size {
+                println!("Skipping {}: header runs past end of archive", entry.name);
+                continue;
+            }
+
+            // The name's length comes from the header, since a name that isn't valid UTF-8 has a
+            // different length once decoded
+            let mut header = data[header_start..name_start].to_vec();
+            Self::decrypt_data(&mut header, key, 30);
+            let name_length = u16::from_le_bytes(header[26..28].try_into().unwrap()) as usize;
+            let name_end = name_start + name_length;
+            let data_start = name_end + entry.extra_field_length as usize;
+            let data_end = data_start + entry.compressed_size as usize;
+
+            if data_end > file_size {
+                println!("Skipping {}: data runs past end of archive", entry.name);
+                continue;
+            }
+
+            // Local file header and file name are separate regions
+            data[header_start..name_start].copy_from_slice(&header);
+            Self::decrypt_data(&mut data[name_start..name_end], key, 0x200);
+
+            // Only the first 0x200 bytes of file data are encrypted (unless it's a .dct file)
+            let bytes_to_decrypt = if entry.name.to_lowercase().ends_with(".dct") {
+                entry.compressed_size as usize
+            } else {
+                0x200
+            };
+            Self::decrypt_data(&mut data[data_start..data_end], key, bytes_to_decrypt);
+
+            // Build the matching central directory record from the decrypted local header
+            let local = &data[header_start..name_end];
+            central_directory.extend_from_slice(b"PK\x01\x02");
+            central_directory.extend_from_slice(&local[4..6]); // version made by
+            central_directory.extend_from_slice(&local[4..6]); // version needed to extract
+            central_directory.extend_from_slice(&local[6..26]); // flags .. uncompressed size
+            central_directory.extend_from_slice(&local[26..28]); // file name length
+            central_directory.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+            central_directory.extend_from_slice(&0u16.to_le_bytes()); // file comment length
+            central_directory.extend_from_slice(&0u16.to_le_bytes()); // disk number start
+            central_directory.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            central_directory.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            central_directory.extend_from_slice(&entry.header_offset.to_le_bytes());
+            central_directory.extend_from_slice(&local[30..]);
+
+            written_entries += 1;
         }
+
+        if written_entries > u16::MAX as usize || data.len() + central_directory.len() > u32::MAX as usize {
+            return Err("Archive is too large for a standard zip central directory".into());
+        }
+
+        let directory_offset = data.len() as u32;
+        let directory_size = central_directory.len() as u32;
+        data.extend_from_slice(&central_directory);
+
+        // End of central directory record
+        data.extend_from_slice(b"PK\x05\x06");
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&(written_entries as u16).to_le_bytes());
+        data.extend_from_slice(&(written_entries as u16).to_le_bytes());
+        data.extend_from_slice(&directory_size.to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+
+        crate::gen::safe_write::write(output_path.as_ref(), &data)?;
+
+        println!("Dumped {} decrypted entries from {} to {}",
+                 written_entries, file_name, output_path.as_ref().display());
+        Ok(written_entries)
     }
 }
 
@@ -365,5 +538,9 @@ pub struct DisneyInfinityZipEntry {
     pub compressed_size: u32,
     pub uncompressed_size: u32,
     pub compression_method: u16,
-    pub extra_field_length: u16
+    pub extra_field_length: u16,
+    // Of the uncompressed data, from the local header
+    pub crc32: u32,
+    // MurmurHash3 of the name, from the archive's entry table
+    pub name_hash: u32,
 }
\ No newline at end of file
@@ -15,7 +15,68 @@ use in3::read_zip::DisneyInfinityZipReader;
 
 mod gen;
 use gen::MtbViewer;
-use gen::read_scene::{SceneFileHandler, GameType as SceneGameType};
+use gen::tbody_viewer::TextureViewSettings;
+use gen::mtb_reader::MtbParams;
+use gen::mtb_viewer::{self, TextureMatch};
+use gen::read_scene::{self, SceneFileHandler, ScenePath, GameType as SceneGameType};
+use gen::storage_analyzer::{format_size, StorageAnalyzer, StorageItem};
+use gen::oct_schema::SchemaReport;
+use gen::tasks::{TaskContext, TaskManager};
+use gen::command_palette::{CommandPalette, PaletteItem};
+use gen::write_guard::WriteGuard;
+use gen::paths::{self, long_path};
+use gen::entry_cache::{self, EntryCache, SharedEntryCache, ViewerSource};
+use gen::profiling::{DiagnosticsView, ScopedTimer};
+use gen::updater::{self, UpdateChecker};
+use gen::themes::{self, SystemThemeWatcher, ThemeFile};
+use gen::shell::open_in_default_app;
+use gen::history::{Operation, OperationHistory};
+use gen::file_ops::{self, ConflictPolicy};
+use gen::templates::{self, Template};
+use gen::binary_template::{self, BinaryTemplate};
+use gen::analyze::{self, Analysis, InflatedStream, StreamSearch};
+use gen::jobs::{self, Job, JobStep};
+use gen::remote::{self, RemoteRequest, RemoteResponse, RemoteServer};
+use gen::scene_export;
+use gen::validation::{self, Severity, ValidationReport};
+use gen::sniff::{self, FileAssociation, FileKind};
+use gen::signatures;
+use gen::zip_writer::{self, ZipSettings};
+use gen::extract_journal::ExtractJournal;
+use gen::dry_run::{DryRunReport, DryRunResults};
+use gen::naming::{self, Naming, NamingTemplates};
+use gen::release_manifest;
+use gen::archive_diff::{self, ArchiveDiff, DiffAction, DiffEntry, DiffView};
+use gen::scene_diff::{self, SceneDiff, SceneDiffAction, SceneDiffView};
+use gen::selection_bus::SelectionBus;
+use gen::detached::DetachedViewers;
+use gen::scene_tree::{SceneTreeAction, SceneTreeView};
+use gen::figure_data::FigureEditor;
+use gen::vehicle_stats::VehicleStatsEditor;
+use gen::minimap;
+use gen::usage_stats::{self, AssetCategory, UsageReport};
+use gen::memory_guard;
+use gen::game_lock::{self, GameProcess, InUseChoice, WriteQueue};
+use gen::elevation::{self, AccessChoice};
+use gen::safe_write;
+use gen::mod_conflicts::{self, Conflict, InstalledMod, ModFiles};
+use gen::mod_package::{self, ExportAction, ExportForm, ModManifest};
+use gen::mod_browser::{self, BrowserAction, IndexEntry, ModBrowser};
+use gen::codecs;
+use gen::raw_entry::{self, RawEntryHeader};
+use gen::entry_metadata::MetadataEditor;
+use gen::docs;
+use gen::tags::{self, Query, SavedQuery, TagDb};
+use gen::ab_slots::{self, AbAction, AbResult, AbSlots, CompareRoute, ModelSummary, Slot};
+use gen::health::{HealthCheck, HealthFix, HealthReport};
+use gen::profiles;
+use gen::instance::{self, Acquired, InstanceLock};
+use gen::associations;
+use gen::text_browser::{self, TextBrowser, TextBrowserAction, TextEntry};
+use gen::toy_box::{ToyBox, ToyBoxAction};
+use gen::tracks::{self, TrackAction, TrackBrowser, TrackEntry};
+use in3::gltf_export::{self, GltfMaterial, GltfModel, GltfNode};
+use rayon::prelude::*;
 
 // Import Cars 3 ZIP reader
 mod c3dtw;
@@ -51,6 +112,16 @@ impl GameType {
         }
     }
 
+    fn scene_game_type(&self) -> SceneGameType {
+        match self {
+            GameType::ToyShit3 => SceneGameType::ToyShit3,
+            GameType::Cars2Arcade => SceneGameType::Cars2Arcade,
+            GameType::Cars2TheVideoGame => SceneGameType::Cars2TheVideoGame,
+            GameType::DisneyInfinity30 => SceneGameType::DisneyInfinity30,
+            GameType::Cars3DrivenToWinXB1 => SceneGameType::Cars3DrivenToWinXB1,
+        }
+    }
+
     fn all() -> Vec<Self> {
         vec![
             GameType::DisneyInfinity30,
@@ -65,14 +136,113 @@ impl GameType {
         matches!(self, GameType::Cars2TheVideoGame | GameType::Cars2Arcade | GameType::DisneyInfinity30 | GameType::ToyShit3 | GameType::Cars3DrivenToWinXB1)
     }
 
+    // Folder and file name parts that mark a scene as a track or level
+    fn level_hints(&self) -> &'static [&'static str] {
+        match self {
+            GameType::DisneyInfinity30 => &["playset", "toybox", "adventure", "world", "level"],
+            GameType::ToyShit3 => &["level", "world", "toybox"],
+            _ => &["track", "race", "circuit", "world", "level"],
+        }
+    }
+
     fn uses_special_zip_reader(&self) -> bool {
         matches!(self, GameType::DisneyInfinity30 | GameType::Cars3DrivenToWinXB1)
     }
+
+    // Asset folders relative to the executable's folder, in scan order. A component ending in `*`
+    // matches every folder starting with the rest, for the arcade cabinet's numbered data
+    // partitions which sit next to the folder holding sdaemon.exe.
+    fn asset_root_rules(&self) -> &'static [&'static str] {
+        match self {
+            GameType::Cars2Arcade => &["assets", "data/assets", "data*", "../data*", "../partition*"],
+            _ => &["assets"],
+        }
+    }
+
+    // Existing folders matched by the rules. The first rule to reach a folder wins, so a folder is
+    // never scanned twice as part of another root.
+    fn asset_roots(&self, game_dir: &Path) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = Vec::new();
+        for rule in self.asset_root_rules() {
+            let mut candidates = vec![game_dir.to_path_buf()];
+            for component in rule.split('/') {
+                candidates = candidates.iter().flat_map(|dir| expand_root_component(dir, component)).collect();
+            }
+            for candidate in candidates {
+                let overlaps = roots.iter().any(|root| candidate.starts_with(root) || root.starts_with(&candidate));
+                if candidate.is_dir() && !overlaps {
+                    roots.push(candidate);
+                }
+            }
+        }
+        roots
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn expand_root_component(dir: &Path, component: &str) -> Vec<PathBuf> {
+    if component == ".." {
+        return dir.parent().map(|parent| vec![parent.to_path_buf()]).unwrap_or_default();
+    }
+    let Some(prefix) = component.strip_suffix('*') else {
+        return vec![dir.join(component)];
+    };
+    let mut matches: Vec<PathBuf> = fs::read_dir(long_path(dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir() && entry.file_name().to_string_lossy().to_lowercase().starts_with(&prefix.to_lowercase()))
+                .map(|entry| dir.join(entry.file_name()))
+                .collect()
+        })
+        .unwrap_or_default();
+    matches.sort();
+    matches
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct GameConfig {
     executable_path: PathBuf,
+    #[serde(default)]
+    texture_search_paths: Vec<PathBuf>,
+    // Missing .tbody name -> file the user picked for it (may point inside an archive)
+    #[serde(default)]
+    texture_bindings: HashMap<String, PathBuf>,
+    // Checked before the built-in detection, e.g. .bin files this game uses for scenes
+    #[serde(default)]
+    file_associations: Vec<FileAssociation>,
+    // Extracted assets browsed without an install; replaces the executable's folders when set
+    #[serde(default)]
+    asset_folder: Option<PathBuf>,
+    #[serde(default)]
+    extra_roots: Vec<ExtraRoot>,
+    // Deployed before the workspace, in this order
+    #[serde(default)]
+    mods: Vec<InstalledMod>,
+    // Lower-cased relative path -> mod deployed for it, where it isn't the last one providing it
+    #[serde(default)]
+    mod_overrides: HashMap<String, String>,
+}
+
+// Folder shown in the tree next to the game's own, e.g. a mod in progress or a console dump
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ExtraRoot {
+    path: PathBuf,
+    // Space-separated globs left out of this root's tree, e.g. "*.bak .git"
+    #[serde(default)]
+    ignore: String,
+}
+
+impl ExtraRoot {
+    fn label(&self) -> String {
+        format!("{} (extra)", paths::display_name(&self.path))
+    }
+}
+
+// One folder of a scan, with the globs pruned from its tree
+struct ScanRoot {
+    path: PathBuf,
+    label: String,
+    ignore: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -81,6 +251,109 @@ struct AppState {
     game_configs: HashMap<GameType, GameConfig>,
     current_step: AppStep,
     theme: Theme,
+    #[serde(default)]
+    favorites: Vec<PathBuf>,
+    #[serde(default)]
+    viewport: ViewModel::ViewportSettings,
+    #[serde(default = "default_protect_game_dir")]
+    protect_game_dir: bool,
+    #[serde(default)]
+    layouts: HashMap<GameType, WorkspaceLayout>,
+    #[serde(default)]
+    tree_show_details: bool,
+    #[serde(default)]
+    tree_sort: TreeSort,
+    // Frames per second cap, 0 for none. The UI only repaints on input or progress either way.
+    #[serde(default)]
+    max_fps: u32,
+    // Opt-in, nothing is fetched unless the user turns it on
+    #[serde(default)]
+    check_for_updates: bool,
+    #[serde(default)]
+    skipped_update: Option<String>,
+    // Applied on top of whichever theme is selected
+    #[serde(default)]
+    accent_color: Option<[u8; 3]>,
+    // Job files opened in the Jobs window, most recent first
+    #[serde(default)]
+    recent_jobs: Vec<PathBuf>,
+    // Local HTTP server for scripts and other tools, off unless turned on
+    #[serde(default)]
+    remote_api: bool,
+    #[serde(default = "default_remote_api_port")]
+    remote_api_port: u16,
+    // Decompressed archive data kept in memory at once, in MB
+    #[serde(default = "default_memory_budget_mb")]
+    memory_budget_mb: u64,
+    // Rotated .bak copies kept when a file is overwritten
+    #[serde(default = "default_backup_count")]
+    backup_count: usize,
+    // Community list of published mods; the mod browser stays empty without one
+    #[serde(default)]
+    mod_index_url: String,
+    #[serde(default)]
+    naming: NamingTemplates,
+    #[serde(default)]
+    panels: PanelLayout,
+}
+
+// Panel sizes as last dragged, and whether the central area is split
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct PanelLayout {
+    file_panel_width: f32,
+    scene_panel_width: f32,
+    // Hex view of the selected file below the viewer
+    split: bool,
+    split_height: f32,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self { file_panel_width: 300.0, scene_panel_width: 400.0, split: false, split_height: 250.0 }
+    }
+}
+
+fn default_remote_api_port() -> u16 {
+    remote::DEFAULT_PORT
+}
+
+fn default_memory_budget_mb() -> u64 {
+    memory_guard::DEFAULT_BUDGET_MB
+}
+
+fn default_backup_count() -> usize {
+    safe_write::DEFAULT_BACKUP_COUNT
+}
+
+// Everything needed to recreate a setup on another machine. Kept as its own versioned
+// wrapper so more sections can be added without breaking older exports.
+#[derive(Debug, Deserialize)]
+struct SettingsBundle {
+    format_version: u32,
+    tundra_version: String,
+    app_state: AppState,
+}
+
+const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+// Panels and viewer settings remembered per game and restored when switching back to it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WorkspaceLayout {
+    #[serde(default)]
+    expanded_folders: Vec<PathBuf>,
+    #[serde(default)]
+    scene_tab: Option<SceneTabs>,
+    #[serde(default)]
+    show_tasks: bool,
+    #[serde(default)]
+    viewport: Option<ViewModel::ViewportSettings>,
+    #[serde(default)]
+    textures: Option<TextureViewSettings>,
+}
+
+fn default_protect_game_dir() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -95,6 +368,27 @@ enum Theme {
     Dark,
     Light,
     System,
+    HighContrast,
+    SolarizedDark,
+    SolarizedLight,
+    // User theme file, see gen::themes::ThemeFile
+    Custom(PathBuf),
+}
+
+impl Theme {
+    const PRESETS: [Theme; 6] = [Theme::Dark, Theme::Light, Theme::System, Theme::HighContrast, Theme::SolarizedDark, Theme::SolarizedLight];
+
+    fn label(&self) -> String {
+        match self {
+            Theme::Dark => "Dark".to_string(),
+            Theme::Light => "Light".to_string(),
+            Theme::System => "System".to_string(),
+            Theme::HighContrast => "High contrast".to_string(),
+            Theme::SolarizedDark => "Solarized dark".to_string(),
+            Theme::SolarizedLight => "Solarized light".to_string(),
+            Theme::Custom(path) => format!("Custom: {}", paths::display_name(path)),
+        }
+    }
 }
 
 impl Default for Theme {
@@ -110,17 +404,43 @@ impl Default for AppState {
             game_configs: HashMap::new(),
             current_step: AppStep::GameSelection,
             theme: Theme::Dark,
+            favorites: Vec::new(),
+            viewport: ViewModel::ViewportSettings::default(),
+            protect_game_dir: true,
+            layouts: HashMap::new(),
+            tree_show_details: false,
+            tree_sort: TreeSort::Name,
+            max_fps: 0,
+            check_for_updates: false,
+            skipped_update: None,
+            accent_color: None,
+            recent_jobs: Vec::new(),
+            remote_api: false,
+            remote_api_port: remote::DEFAULT_PORT,
+            memory_budget_mb: memory_guard::DEFAULT_BUDGET_MB,
+            backup_count: safe_write::DEFAULT_BACKUP_COUNT,
+            mod_index_url: String::new(),
+            naming: NamingTemplates::default(),
+            panels: PanelLayout::default(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct FileEntry {
     path: PathBuf,
+    display_name: String,
     is_directory: bool,
     is_zip: bool,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    modified: u64,
     children: Vec<FileEntry>,
+    #[serde(skip)]
     zip_contents_loaded: bool,
+    #[serde(skip)]
+    zip_load_error: Option<String>,
 }
 
 impl FileEntry {
@@ -130,42 +450,464 @@ impl FileEntry {
             .map(|ext| ext.eq_ignore_ascii_case("zip"))
             .unwrap_or(false);
 
+        let display_name = paths::display_name(&path);
+
         Self {
             path,
+            display_name,
             is_directory,
             is_zip,
+            size: 0,
+            modified: 0,
             children: Vec::new(),
             zip_contents_loaded: false,
+            zip_load_error: None,
         }
     }
 }
 
+// Scanned tree of one root folder, stored on disk between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScanCache {
+    root: PathBuf,
+    root_modified: u64,
+    entries: Vec<FileEntry>,
+}
+
+// Modification time in milliseconds since the epoch, 0 if unavailable
+fn modified_millis(metadata: &fs::Metadata) -> u64 {
+    metadata.modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Year, month, day, hour, minute and second in UTC for a millisecond timestamp
+fn civil_time(millis: u64) -> (i64, i64, i64, u64, u64, u64) {
+    let seconds = millis / 1000;
+    let (days, time_of_day) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day, time_of_day / 3600, time_of_day % 3600 / 60, time_of_day % 60)
+}
+
+// "YYYY-MM-DD HH:MM" in UTC for a millisecond timestamp
+fn format_timestamp(millis: u64) -> String {
+    if millis == 0 {
+        return String::new();
+    }
+    let (year, month, day, hour, minute, _) = civil_time(millis);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+const CONFIG_PATH: &str = "tundra_config.json";
+const SCAN_CACHE_PATH: &str = "tundra_scan_cache.json";
+const MAX_SCAN_THREADS: usize = 4;
+// Text and hex views of files without a dedicated viewer
+const PREVIEW_SIZE: usize = 64 * 1024;
+// Archives the setup check tries to open, spread over the whole install
+const HEALTH_ARCHIVE_SAMPLES: usize = 8;
+
+fn read_preview(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    fs::File::open(long_path(path))
+        .and_then(|file| file.take(PREVIEW_SIZE as u64).read_to_end(&mut head))
+        .map(|_| head)
+        .ok()
+}
+
 #[derive(Debug, Clone)]
 struct ZipEntry {
     name: String,
     is_directory: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// One file of an archive as its reader lists it
+#[derive(Debug, Clone)]
+struct ArchiveEntryInfo {
+    name: String,
+    method: String,
+    compressed_size: u64,
+    size: u64,
+    crc32: u32,
+    // Disney Infinity archives store a hash of each name
+    name_hash: Option<u32>,
+}
+
+impl ArchiveEntryInfo {
+    // Compressed size as a fraction of the original
+    fn ratio(&self) -> f64 {
+        if self.size == 0 {
+            1.0
+        } else {
+            self.compressed_size as f64 / self.size as f64
+        }
+    }
+
+    fn sort(entries: &mut [ArchiveEntryInfo], (column, ascending): (ArchiveSort, bool)) {
+        entries.sort_by(|a, b| {
+            let order = match column {
+                ArchiveSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                ArchiveSort::Method => a.method.cmp(&b.method),
+                ArchiveSort::Compressed => a.compressed_size.cmp(&b.compressed_size),
+                ArchiveSort::Size => a.size.cmp(&b.size),
+                ArchiveSort::Ratio => a.ratio().total_cmp(&b.ratio()),
+            };
+            if ascending { order } else { order.reverse() }
+        });
+    }
+}
+
+// Same names the zip crate shows for its methods
+fn compression_name(method: u16) -> String {
+    match method {
+        0 => "Stored".to_string(),
+        8 => "Deflated".to_string(),
+        9 => "Deflate64".to_string(),
+        12 => "Bzip2".to_string(),
+        14 => "Lzma".to_string(),
+        93 => "Zstd".to_string(),
+        99 => "Aes".to_string(),
+        other => codecs::codec_name(other).map_or_else(|| format!("Unsupported({})", other), |name| format!("{} ({})", name, other)),
+    }
+}
+
+// Column the archive properties entry list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveSort {
+    Name,
+    Method,
+    Compressed,
+    Size,
+    Ratio,
+}
+
+// One visible line of the flattened file tree
+#[derive(Debug, Clone)]
+struct TreeRow {
+    index_path: Vec<usize>,
+    path: PathBuf,
+    display_name: String,
+    depth: usize,
+    expandable: bool,
+    is_zip: bool,
+    zip_load_error: Option<String>,
+    // Totals for folders, from the scan
+    size: u64,
+    modified: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum TreeSort {
+    #[default]
+    Name,
+    Size,
+    Modified,
+}
+
+impl TreeSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TreeSort::Name => "Name",
+            TreeSort::Size => "Size",
+            TreeSort::Modified => "Modified",
+        }
+    }
+}
+
+// Tree actions that have to wait until the visible rows have been drawn
+#[derive(Debug, Clone)]
+enum TreeAction {
+    Toggle(Vec<usize>),
+    RangeSelect(PathBuf),
+    RemoveSelection,
+}
+
+// Every action reachable from menus, context menus and the command palette
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AppCommand {
+    OpenSelected,
+    ExtractSelection,
+    ExportSelectionWithStructure,
+    FavoriteSelection,
+    RemoveSelection,
+    Rescan,
+    SwitchGame,
+    ChangeExecutable,
+    Options,
+    StorageAnalyzer,
+    BatchSceneDump,
+    SchemaReport,
+    ShowTasks,
+    Diagnostics,
+    History,
+    Jobs,
+    ExportForBlender,
+    ExportSceneGltf,
+    Deploy,
+    ToggleWireframe,
+    CloseSceneViewer,
+    RunGame,
+    CopySelection,
+    CutSelection,
+    PasteIntoSelection,
+    UndoFileOperation,
+    RenameSelection,
+    DeleteSelection,
+    NewLuaScript,
+    NewOctScene,
+    NewMaterial,
+    AnalyzeSelection,
+    CreateArchive,
+    ToggleDryRun,
+    ToggleSplitView,
+    GenerateReleaseManifest,
+    VerifyManifest,
+    CompareVersions,
+    CompareScenes,
+    GenerateDocs,
+    ComparePinned,
+    HealthCheck,
+    TextBrowser,
+    ToyBox,
+    Tracks,
+    UsageStats,
+    ExportModPackage,
+    InstallModPackage,
+    ModBrowser,
+}
+
+impl AppCommand {
+    const ALL: [AppCommand; 49] = [
+        AppCommand::OpenSelected,
+        AppCommand::ExtractSelection,
+        AppCommand::ExportSelectionWithStructure,
+        AppCommand::FavoriteSelection,
+        AppCommand::RemoveSelection,
+        AppCommand::Rescan,
+        AppCommand::SwitchGame,
+        AppCommand::ChangeExecutable,
+        AppCommand::Options,
+        AppCommand::StorageAnalyzer,
+        AppCommand::BatchSceneDump,
+        AppCommand::SchemaReport,
+        AppCommand::ShowTasks,
+        AppCommand::Diagnostics,
+        AppCommand::History,
+        AppCommand::Jobs,
+        AppCommand::ExportForBlender,
+        AppCommand::ExportSceneGltf,
+        AppCommand::Deploy,
+        AppCommand::ToggleWireframe,
+        AppCommand::CloseSceneViewer,
+        AppCommand::RunGame,
+        AppCommand::CopySelection,
+        AppCommand::CutSelection,
+        AppCommand::PasteIntoSelection,
+        AppCommand::UndoFileOperation,
+        AppCommand::RenameSelection,
+        AppCommand::DeleteSelection,
+        AppCommand::NewLuaScript,
+        AppCommand::NewOctScene,
+        AppCommand::NewMaterial,
+        AppCommand::AnalyzeSelection,
+        AppCommand::CreateArchive,
+        AppCommand::ToggleDryRun,
+        AppCommand::ToggleSplitView,
+        AppCommand::GenerateReleaseManifest,
+        AppCommand::VerifyManifest,
+        AppCommand::CompareVersions,
+        AppCommand::CompareScenes,
+        AppCommand::GenerateDocs,
+        AppCommand::ComparePinned,
+        AppCommand::HealthCheck,
+        AppCommand::TextBrowser,
+        AppCommand::ToyBox,
+        AppCommand::Tracks,
+        AppCommand::UsageStats,
+        AppCommand::ExportModPackage,
+        AppCommand::InstallModPackage,
+        AppCommand::ModBrowser,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            AppCommand::OpenSelected => "Open selected file",
+            AppCommand::ExtractSelection => "Extract to folder...",
+            AppCommand::ExportSelectionWithStructure => "Export with folder structure...",
+            AppCommand::FavoriteSelection => "Add to favorites",
+            AppCommand::RemoveSelection => "Remove from project",
+            AppCommand::Rescan => "Full rescan",
+            AppCommand::SwitchGame => "Change Game",
+            AppCommand::ChangeExecutable => "Change game executable...",
+            AppCommand::Options => "Options",
+            AppCommand::StorageAnalyzer => "Storage analyzer",
+            AppCommand::BatchSceneDump => "Batch OCT → JSON...",
+            AppCommand::SchemaReport => "OCT schema report...",
+            AppCommand::ShowTasks => "Tasks",
+            AppCommand::Diagnostics => "Diagnostics",
+            AppCommand::History => "Operation history",
+            AppCommand::Jobs => "Jobs",
+            AppCommand::ExportForBlender => "Export for Blender...",
+            AppCommand::ExportSceneGltf => "Export scene as glTF...",
+            AppCommand::Deploy => "Validate and deploy workspace",
+            AppCommand::ToggleWireframe => "Toggle wireframe",
+            AppCommand::CloseSceneViewer => "Close Scene Viewer",
+            AppCommand::RunGame => "Run Game",
+            AppCommand::CopySelection => "Copy",
+            AppCommand::CutSelection => "Cut",
+            AppCommand::PasteIntoSelection => "Paste into folder",
+            AppCommand::UndoFileOperation => "Undo last file operation",
+            AppCommand::RenameSelection => "Rename...",
+            AppCommand::DeleteSelection => "Move to recycle bin",
+            AppCommand::NewLuaScript => "New Lua script...",
+            AppCommand::NewOctScene => "New OCT scene...",
+            AppCommand::NewMaterial => "New MTB material...",
+            AppCommand::AnalyzeSelection => "Analyze",
+            AppCommand::CreateArchive => "Create archive from folder...",
+            AppCommand::ToggleDryRun => "Toggle dry run",
+            AppCommand::ToggleSplitView => "Toggle hex view below the viewer",
+            AppCommand::GenerateReleaseManifest => "Generate release manifest...",
+            AppCommand::VerifyManifest => "Verify manifest...",
+            AppCommand::CompareVersions => "Compare archives or game folders",
+            AppCommand::CompareScenes => "Compare OCT scenes...",
+            AppCommand::GenerateDocs => "Generate Markdown docs...",
+            AppCommand::ComparePinned => "Compare pinned A and B",
+            AppCommand::HealthCheck => "Check game setup",
+            AppCommand::TextBrowser => "Text browser",
+            AppCommand::ToyBox => "Toy Box levels",
+            AppCommand::Tracks => "Tracks and levels",
+            AppCommand::UsageStats => "Asset usage",
+            AppCommand::ExportModPackage => "Export mod package...",
+            AppCommand::InstallModPackage => "Install mod package...",
+            AppCommand::ModBrowser => "Mod browser",
+        }
+    }
+}
+
+// Textures written for one model by the glTF exports
+struct MaterialExport {
+    mtb_path: Option<PathBuf>,
+    material: Option<GltfMaterial>,
+    // Per texture: source file, slot and the PNG written or why it wasn't
+    records: Vec<serde_json::Value>,
+    exported: usize,
+    total: usize,
+}
+
+// Paste waiting for the user to decide what happens to existing files
+struct PendingTransfer {
+    transfers: Vec<file_ops::Transfer>,
+    is_move: bool,
+    conflicts: usize,
+    description: String,
+}
+
+// Action held back until unsaved changes are saved or discarded
+#[derive(Debug, Clone)]
+enum PendingAction {
+    OpenFile(PathBuf, Option<FileKind>),
+    CloseSceneViewer,
+    SwitchGame,
+    Exit,
+    OpenToyBoxLevel(PathBuf),
+}
+
+impl PendingAction {
+    // Documents the action would close or replace, so only those are asked about
+    fn documents(&self) -> &'static [Document] {
+        match self {
+            PendingAction::OpenFile(..) | PendingAction::CloseSceneViewer => &[Document::Scene],
+            PendingAction::OpenToyBoxLevel(_) => &[Document::ToyBox],
+            PendingAction::SwitchGame | PendingAction::Exit => &Document::ALL,
+        }
+    }
+}
+
+// An editor that holds a document which can have unsaved changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Document {
+    Scene,
+    ToyBox,
+}
+
+impl Document {
+    const ALL: [Document; 2] = [Document::Scene, Document::ToyBox];
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum SceneTabs {
     SceneInfo,
+    Tree,
     Textures,
+    Instances,
+    Blobs,
     Animations,
+    FigureData,
+    VehicleStats,
+    Minimap,
 }
 
 struct TundraEditor {
     state: AppState,
     pending_file_selection: bool,
     selected_file: Option<PathBuf>,
+    // The file the viewers are showing, which selected_file goes back to when opening another is held up
+    opened_file: Option<PathBuf>,
+    selected_files: std::collections::HashSet<PathBuf>,
+    selection_anchor: Option<PathBuf>,
+    tree_rows: Vec<TreeRow>,
+    tree_rows_dirty: bool,
+    pending_tree_action: Option<TreeAction>,
+    // Tree entries from the top level down to the selected file and the index of the innermost
+    // archive among them, rebuilt with the tree rows
+    breadcrumbs: Option<(PathBuf, Vec<(PathBuf, String)>, Option<usize>)>,
+    tree_scroll_target: Option<PathBuf>,
+    archive_properties: Option<PathBuf>,
+    // Entries of the archive in the properties window, listed once per archive, and their order
+    archive_listing: Option<(PathBuf, Result<Vec<ArchiveEntryInfo>, String>)>,
+    archive_sort: (ArchiveSort, bool),
     file_tree: Vec<FileEntry>,
     expanded_folders: std::collections::HashSet<PathBuf>,
     file_icons: HashMap<String, egui::TextureHandle>,
+    // Config of the active profile, and the one Tundra was started with (tundra_config.json or
+    // --config), which profiles are kept next to
     config_path: PathBuf,
+    base_config_path: PathBuf,
+    active_profile: String,
+    new_profile: String,
+    profile_status: Option<Result<String, String>>,
     model_viewer: ViewModel::ModelViewer,
+    // What the selected file was opened as, from its contents or "Open as"
+    selected_kind: Option<FileKind>,
+    file_preview: Option<Vec<u8>>,
+    // Structure definitions from binary_templates/ and the one laid over the hex view, with the
+    // fields it found and which field owns each byte of the preview
+    binary_templates: Vec<BinaryTemplate>,
+    hex_template: Option<usize>,
+    template_fields: Vec<binary_template::FieldValue>,
+    template_error: Option<String>,
+    byte_fields: Vec<Option<usize>>,
+    // IBUF and VBUF the current model was loaded from
+    model_files: Option<(PathBuf, PathBuf)>,
     show_options: bool,
     scan_progress: Option<ScanProgress>,
     scan_thread: Option<thread::JoinHandle<Vec<FileEntry>>>,
     scan_cancel: Arc<Mutex<bool>>,
+    force_full_rescan: bool,
+    // Folders the last game scan covered
+    asset_roots: Vec<PathBuf>,
     mtb_viewer: MtbViewer,
     egui_ctx: Option<egui::Context>,
     should_exit: bool,
@@ -174,6 +916,120 @@ struct TundraEditor {
     scene_viewer: SceneFileHandler,
     show_scene_viewer: bool,
     scene_tabs: SceneTabs,
+    selected_blob: Option<ScenePath>,
+    // Mesh instances of the open scene, found when the Instances tab is first shown
+    scene_instances: Option<(PathBuf, Vec<scene_export::SceneInstance>)>,
+    scene_tree: SceneTreeView,
+    figure_editor: FigureEditor,
+    vehicle_stats: VehicleStatsEditor,
+    // Rendered top-down view of the open scene, with the image kept for export
+    minimap: Option<(PathBuf, egui::TextureHandle, image::RgbaImage)>,
+    minimap_result: Arc<Mutex<Option<(PathBuf, Result<image::RgbaImage, String>)>>>,
+    minimap_rendering: bool,
+    hidden_curves: std::collections::HashSet<String>,
+    storage_analyzer: StorageAnalyzer,
+    show_storage_analyzer: bool,
+    task_manager: TaskManager,
+    show_tasks: bool,
+    entry_cache: SharedEntryCache,
+    viewer_source: ViewerSource,
+    diagnostics: DiagnosticsView,
+    show_diagnostics: bool,
+    history: OperationHistory,
+    show_history: bool,
+    show_jobs: bool,
+    show_deploy: bool,
+    // Filled in by the validation task; deploying is only offered once it's there
+    deploy_report: Arc<Mutex<Option<ValidationReport>>>,
+    deploy_validating: bool,
+    // Source names and the conflicts between them, from the last check
+    mod_conflicts: Arc<Mutex<Option<(Vec<String>, Vec<Conflict>)>>>,
+    checking_conflicts: bool,
+    mod_export: Option<ExportForm>,
+    mod_install: Arc<Mutex<Option<Result<(ModManifest, PathBuf), String>>>>,
+    // Entropy and embedded header scan of one file, filled in by its task
+    analysis: Arc<Mutex<Option<Result<Analysis, String>>>>,
+    show_analysis: bool,
+    // Compressed streams found in the analyzed file
+    stream_search: StreamSearch,
+    streams: Arc<Mutex<Option<Result<Vec<InflatedStream>, String>>>>,
+    streams_searching: bool,
+    remote_server: Option<RemoteServer>,
+    // Port that failed to bind and why, so it isn't retried every frame
+    remote_error: Option<(u16, String)>,
+    // Set by --remote-api, runs the server for this session without saving the setting
+    remote_api_session: bool,
+    // None with --multi-instance, or when the lock couldn't be taken
+    instance_lock: Option<InstanceLock>,
+    // Handed over by another launch before there was a game to open them with
+    pending_open: Vec<PathBuf>,
+    association_status: Option<Result<String, String>>,
+    text_browser: TextBrowser,
+    show_text_browser: bool,
+    toy_box: ToyBox,
+    show_toy_box: bool,
+    track_browser: TrackBrowser,
+    show_tracks: bool,
+    track_result: Arc<Mutex<Option<Vec<TrackEntry>>>>,
+    usage_report: Option<UsageReport>,
+    usage_result: Arc<Mutex<Option<UsageReport>>>,
+    usage_running: bool,
+    show_usage: bool,
+    mod_browser: ModBrowser,
+    show_mod_browser: bool,
+    text_result: Arc<Mutex<Option<Vec<TextEntry>>>>,
+    last_frame: Instant,
+    game_process: GameProcess,
+    // Writes waiting for the game to let go of their target
+    write_queue: WriteQueue,
+    last_queue_check: Instant,
+    updater: UpdateChecker,
+    system_theme: SystemThemeWatcher,
+    command_palette: CommandPalette,
+    unsaved_prompt: Option<PendingAction>,
+    // Files picked with Copy or Cut (true), pasted into a folder of any root
+    file_clipboard: Option<(Vec<PathBuf>, bool)>,
+    pending_transfer: Option<PendingTransfer>,
+    undo_stack: Vec<file_ops::UndoRecord>,
+    // File being renamed and the name typed so far
+    rename_target: Option<(PathBuf, String)>,
+    rename_error: Option<String>,
+    // Folder waiting for the compression settings before it's archived
+    archive_folder: Option<PathBuf>,
+    zip_settings: ZipSettings,
+    // Entry metadata edits for the folder in the Create archive prompt
+    archive_metadata: Option<MetadataEditor>,
+    // While on, batch operations and deploys list what they'd write instead of writing it
+    dry_run: bool,
+    dry_run_results: DryRunResults,
+    show_dry_run: bool,
+    // Compare window: the two sides and the last result
+    diff_old: Option<PathBuf>,
+    diff_new: Option<PathBuf>,
+    diff_result: Arc<Mutex<Option<Result<ArchiveDiff, String>>>>,
+    diff_running: bool,
+    diff_inside_archives: bool,
+    diff_view: DiffView,
+    show_diff: bool,
+    scene_diff: Option<Result<SceneDiff, String>>,
+    scene_diff_view: SceneDiffView,
+    // Source files picked in the viewers, revealed in the file tree
+    selection_bus: SelectionBus,
+    detached: DetachedViewers,
+    tags: TagDb,
+    ab_slots: AbSlots,
+    health_result: Arc<Mutex<Option<HealthReport>>>,
+    health_report: Option<HealthReport>,
+    show_health: bool,
+    // Game the last check ran for; entering the editor for another one checks again
+    health_checked: Option<GameType>,
+    new_tag: String,
+    new_query: String,
+    // Saved query results for (tag generation, tree generation)
+    query_results: Option<((u64, u64), Vec<Result<Vec<PathBuf>, String>>)>,
+    // Bumped whenever the tree rows are rebuilt, which every change to the tree triggers
+    tree_generation: u64,
+    allow_close: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -185,28 +1041,57 @@ struct ScanProgress {
 }
 
 impl TundraEditor {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let config_path = PathBuf::from("tundra_config.json");
+    fn new(cc: &eframe::CreationContext<'_>, launch: LaunchOptions) -> Self {
+        let LaunchOptions { remote_api_session, config_path, instance_lock, open } = launch;
+        if let Some(lock) = &instance_lock {
+            lock.set_context(&cc.egui_ctx);
+        }
         
         // Create temp directory for ZIP extraction
         let temp_dir = PathBuf::from("temp");
         if let Err(e) = fs::create_dir_all(&temp_dir) {
             eprintln!("Failed to create temp directory: {}", e);
         }
+        let entry_cache = EntryCache::shared();
         
         let mut app = Self {
             state: AppState::default(),
             pending_file_selection: false,
             selected_file: None,
+            opened_file: None,
+            selected_files: std::collections::HashSet::new(),
+            selection_anchor: None,
+            tree_rows: Vec::new(),
+            tree_rows_dirty: true,
+            pending_tree_action: None,
+            breadcrumbs: None,
+            tree_scroll_target: None,
+            archive_properties: None,
+            archive_listing: None,
+            archive_sort: (ArchiveSort::Name, true),
             file_tree: Vec::new(),
             expanded_folders: std::collections::HashSet::new(),
             file_icons: HashMap::new(),
+            base_config_path: config_path.clone(),
             config_path,
+            active_profile: profiles::DEFAULT_PROFILE.to_string(),
+            new_profile: String::new(),
+            profile_status: None,
             model_viewer: ViewModel::ModelViewer::new(),
+            model_files: None,
+            selected_kind: None,
+            file_preview: None,
+            binary_templates: Vec::new(),
+            hex_template: None,
+            template_fields: Vec::new(),
+            template_error: None,
+            byte_fields: Vec::new(),
             show_options: false,
             scan_progress: None,
             scan_thread: None,
             scan_cancel: Arc::new(Mutex::new(false)),
+            force_full_rescan: false,
+            asset_roots: Vec::new(),
             mtb_viewer: MtbViewer::new(),
             egui_ctx: Some(cc.egui_ctx.clone()),
             should_exit: false,
@@ -215,82 +1100,309 @@ impl TundraEditor {
             scene_viewer: SceneFileHandler::new(),
             show_scene_viewer: false,
             scene_tabs: SceneTabs::SceneInfo,
+            selected_blob: None,
+            scene_instances: None,
+            scene_tree: SceneTreeView::default(),
+            figure_editor: FigureEditor::default(),
+            vehicle_stats: VehicleStatsEditor::default(),
+            minimap: None,
+            minimap_result: Arc::new(Mutex::new(None)),
+            minimap_rendering: false,
+            hidden_curves: std::collections::HashSet::new(),
+            storage_analyzer: StorageAnalyzer::new(),
+            show_storage_analyzer: false,
+            task_manager: TaskManager::new(cc.egui_ctx.clone()),
+            command_palette: CommandPalette::new(),
+            unsaved_prompt: None,
+            file_clipboard: None,
+            pending_transfer: None,
+            undo_stack: Vec::new(),
+            rename_target: None,
+            rename_error: None,
+            archive_folder: None,
+            zip_settings: ZipSettings::default(),
+            archive_metadata: None,
+            dry_run: false,
+            dry_run_results: DryRunResults::default(),
+            show_dry_run: false,
+            diff_old: None,
+            diff_new: None,
+            diff_result: Arc::new(Mutex::new(None)),
+            diff_running: false,
+            diff_inside_archives: true,
+            diff_view: DiffView::default(),
+            show_diff: false,
+            scene_diff: None,
+            scene_diff_view: SceneDiffView::default(),
+            selection_bus: SelectionBus::default(),
+            detached: DetachedViewers::default(),
+            tags: TagDb::load(Path::new(tags::TAGS_PATH)),
+            ab_slots: AbSlots::default(),
+            health_result: Arc::new(Mutex::new(None)),
+            health_report: None,
+            show_health: false,
+            health_checked: None,
+            new_tag: String::new(),
+            new_query: String::new(),
+            query_results: None,
+            tree_generation: 0,
+            allow_close: false,
+            show_tasks: false,
+            viewer_source: ViewerSource::new(entry_cache.clone()),
+            entry_cache,
+            diagnostics: DiagnosticsView::new(),
+            show_diagnostics: false,
+            history: OperationHistory::load(),
+            show_history: false,
+            show_jobs: false,
+            show_deploy: false,
+            deploy_report: Arc::new(Mutex::new(None)),
+            deploy_validating: false,
+            mod_conflicts: Arc::new(Mutex::new(None)),
+            checking_conflicts: false,
+            mod_export: None,
+            mod_install: Arc::new(Mutex::new(None)),
+            analysis: Arc::new(Mutex::new(None)),
+            show_analysis: false,
+            stream_search: StreamSearch::default(),
+            streams: Arc::new(Mutex::new(None)),
+            streams_searching: false,
+            remote_server: None,
+            remote_error: None,
+            remote_api_session,
+            instance_lock,
+            pending_open: open,
+            association_status: None,
+            text_browser: TextBrowser::default(),
+            show_text_browser: false,
+            toy_box: ToyBox::default(),
+            show_toy_box: false,
+            track_browser: TrackBrowser::default(),
+            show_tracks: false,
+            track_result: Arc::new(Mutex::new(None)),
+            usage_report: None,
+            usage_result: Arc::new(Mutex::new(None)),
+            usage_running: false,
+            show_usage: false,
+            mod_browser: ModBrowser::new(),
+            show_mod_browser: false,
+            text_result: Arc::new(Mutex::new(None)),
+            last_frame: Instant::now(),
+            game_process: GameProcess::default(),
+            write_queue: WriteQueue::default(),
+            last_queue_check: Instant::now(),
+            updater: UpdateChecker::new(),
+            system_theme: SystemThemeWatcher::start(&cc.egui_ctx),
         };
 
         // Load file icons
-        app.load_file_icons(cc);
+        {
+            let _timer = ScopedTimer::new("startup", "Load file icons");
+            app.load_file_icons(cc);
+        }
 
         // Try to load state from JSON file
-        app.load_from_json();
+        {
+            let _timer = ScopedTimer::new("startup", "Load saved state");
+            app.load_from_json();
+        }
+
+        // Paths on the command line skip the wizard and open in the last game's editor
+        if !app.pending_open.is_empty() {
+            app.skip_to_editor();
+        }
+
+        {
+            let _timer = ScopedTimer::new("startup", "Load file signatures");
+            println!("{} file signatures", signatures::database().signatures.len());
+        }
 
         // Apply theme
-        app.apply_theme(cc);
+        app.apply_theme(&cc.egui_ctx);
+        app.reload_binary_templates();
+
+        updater::remove_previous_executable();
+        if app.state.check_for_updates {
+            app.updater.check(&cc.egui_ctx, app.state.skipped_update.clone());
+        }
 
         app
     }
 
-    fn apply_theme(&self, cc: &eframe::CreationContext<'_>) {
-        match self.state.theme {
-            Theme::Dark => {
-                cc.egui_ctx.set_visuals(egui::Visuals::dark());
-            }   
-            Theme::Light => {
-                cc.egui_ctx.set_visuals(egui::Visuals::light());
-            }
+    fn apply_theme(&self, ctx: &egui::Context) {
+        ctx.set_visuals(self.theme_visuals());
+    }
+
+    fn theme_visuals(&self) -> egui::Visuals {
+        let mut visuals = match &self.state.theme {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
             Theme::System => {
-                // System theme follows the OS preference
-                #[cfg(target_os = "windows")]
-                {
-                    use winreg::enums::*;
-                    use winreg::RegKey;
-                
-                    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-                    if let Ok(personalize) = hkcu.open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize") {
-                        if let Ok(apps_use_light_theme) = personalize.get_value::<u32, _>("AppsUseLightTheme") {
-                            if apps_use_light_theme == 1 {
-                                cc.egui_ctx.set_visuals(egui::Visuals::light());
-                                return;
-                            }
-                        }
-                    }
+                if self.system_theme.prefers_dark() {
+                    egui::Visuals::dark()
+                } else {
+                    egui::Visuals::light()
                 }
-            
-                #[cfg(target_os = "macos")]
-                {
-                    use std::process::Command;
-                
-                    if let Ok(output) = Command::new("defaults").args(&["read", "-g", "AppleInterfaceStyle"]).output() {
-                        if output.status.success() {
-                            let theme = String::from_utf8_lossy(&output.stdout);
-                            if theme.to_lowercase().contains("dark") {
-                                cc.egui_ctx.set_visuals(egui::Visuals::dark());
-                                return;
-                            }
-                        }
-                    }
-                    cc.egui_ctx.set_visuals(egui::Visuals::light());
-                    return;
+            }
+            Theme::HighContrast => ThemeFile::high_contrast().visuals(),
+            Theme::SolarizedDark => ThemeFile::solarized_dark().visuals(),
+            Theme::SolarizedLight => ThemeFile::solarized_light().visuals(),
+            Theme::Custom(path) => match ThemeFile::load(path) {
+                Ok(theme) => theme.visuals(),
+                Err(e) => {
+                    eprintln!("Failed to load theme {}: {}", path.display(), e);
+                    egui::Visuals::dark()
                 }
-            
-                #[cfg(target_os = "linux")]
-                {
-                    use std::process::Command;
-                
-                    // Try to detect GTK theme
-                    if let Ok(output) = Command::new("gsettings").args(&["get", "org.gnome.desktop.interface", "gtk-theme"]).output() {
-                        if output.status.success() {
-                            let theme = String::from_utf8_lossy(&output.stdout).to_lowercase();
-                            if theme.contains("dark") {
-                                cc.egui_ctx.set_visuals(egui::Visuals::dark());
-                                return;
-                            }
-                        }
+            },
+        };
+        if let Some([r, g, b]) = self.state.accent_color {
+            themes::apply_accent(&mut visuals, egui::Color32::from_rgb(r, g, b));
+        }
+        visuals
+    }
+
+    // Current visuals as a theme file, for users to tweak by hand
+    fn save_theme_file(&self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Save theme")
+            .set_file_name("tundra_theme.json")
+            .add_filter("Theme", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let visuals = ctx.style().visuals.clone();
+        let theme = ThemeFile {
+            name: paths::display_name(&path.with_extension("")),
+            dark: visuals.dark_mode,
+            accent: Some(themes::format_color(visuals.hyperlink_color)),
+            text: Some(themes::format_color(visuals.text_color())),
+            panel_fill: Some(themes::format_color(visuals.panel_fill)),
+            window_fill: Some(themes::format_color(visuals.window_fill)),
+            extreme_bg: Some(themes::format_color(visuals.extreme_bg_color)),
+            faint_bg: Some(themes::format_color(visuals.faint_bg_color)),
+            widget_fill: Some(themes::format_color(visuals.widgets.inactive.bg_fill)),
+            hovered_fill: Some(themes::format_color(visuals.widgets.hovered.bg_fill)),
+            warning: Some(themes::format_color(visuals.warn_fg_color)),
+            error: Some(themes::format_color(visuals.error_fg_color)),
+            rounding: Some(visuals.window_rounding.nw),
+        };
+        match theme.save(&path) {
+            Ok(()) => println!("Saved theme to {}", path.display()),
+            Err(e) => eprintln!("Failed to save theme: {}", e),
+        }
+    }
+
+    // Saves the current profile and loads `name` in its place, rescanning its game
+    fn switch_profile(&mut self, name: &str, ctx: &egui::Context) {
+        if self.has_unsaved_changes() {
+            self.profile_status = Some(Err("Save or discard the open documents before switching profiles".to_string()));
+            return;
+        }
+        if self.state.current_step == AppStep::Editor {
+            self.capture_layout();
+        }
+        self.state.viewport = self.model_viewer.settings.clone();
+        self.save_state();
+
+        self.config_path = profiles::profile_path(&self.base_config_path, name);
+        self.active_profile = name.to_string();
+        self.state = AppState::default();
+        self.file_tree.clear();
+        self.tree_rows_dirty = true;
+        self.selected_file = None;
+        self.selected_files.clear();
+        self.model_viewer.clear_model();
+        self.mtb_viewer.clear();
+        self.scene_viewer.clear();
+        self.show_scene_viewer = false;
+        self.health_checked = None;
+        self.load_from_json();
+        self.apply_theme(ctx);
+        println!("Switched to profile {} ({})", name, self.config_path.display());
+        self.profile_status = Some(Ok(format!("Using {}", self.config_path.display())));
+    }
+
+    // Starts a profile from a copy of the current settings
+    fn create_profile(&mut self, ctx: &egui::Context) {
+        let name = self.new_profile.trim().to_string();
+        if let Err(e) = profiles::validate_name(&name, &profiles::list(&self.base_config_path)) {
+            self.profile_status = Some(Err(e));
+            return;
+        }
+        let path = profiles::profile_path(&self.base_config_path, &name);
+        let written = serde_json::to_string_pretty(&self.state).map_err(|e| e.to_string())
+            .and_then(|json| paths::write_creating_dirs(&path, json.as_bytes()).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => {
+                self.new_profile.clear();
+                self.switch_profile(&name, ctx);
+            }
+            Err(e) => self.profile_status = Some(Err(format!("Failed to create {}: {}", path.display(), e))),
+        }
+    }
+
+    fn delete_profile(&mut self, ctx: &egui::Context) {
+        let name = self.active_profile.clone();
+        let path = self.config_path.clone();
+        let confirmed = rfd::MessageDialog::new()
+            .set_title("Delete profile")
+            .set_description(format!("Delete the profile \"{}\" and its settings?", name))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if confirmed != rfd::MessageDialogResult::Yes {
+            return;
+        }
+        // Back to the default first, or switching would save the profile right back
+        self.switch_profile(profiles::DEFAULT_PROFILE, ctx);
+        match safe_write::delete(&[path.clone()]) {
+            Ok(()) => {
+                println!("Deleted profile {}", name);
+                self.profile_status = Some(Ok(format!("Deleted \"{}\"", name)));
+            }
+            Err(e) => self.profile_status = Some(Err(format!("Failed to delete {}: {}", path.display(), e))),
+        }
+    }
+
+    fn show_profiles_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let names = profiles::list(&self.base_config_path);
+        let mut selected = self.active_profile.clone();
+        ui.horizontal(|ui| {
+            ui.label("Profile:");
+            egui::ComboBox::from_id_source("config_profile")
+                .selected_text(&selected)
+                .show_ui(ui, |ui| {
+                    for name in &names {
+                        ui.selectable_value(&mut selected, name.clone(), name);
                     }
-                }
-            
-                // Default fallback to dark theme
-                cc.egui_ctx.set_visuals(egui::Visuals::dark());
+                })
+                .response
+                .on_hover_text("Each profile has its own game setups, ignore lists and layouts. Start with --config <file> to open another config directly.");
+            let is_default = self.active_profile == profiles::DEFAULT_PROFILE;
+            if ui.add_enabled(!is_default, egui::Button::new("Delete")).clicked() {
+                self.delete_profile(ctx);
+            }
+        });
+        if selected != self.active_profile {
+            self.switch_profile(&selected, ctx);
+        }
+        ui.horizontal(|ui| {
+            ui.label("New profile:");
+            let response = ui.text_edit_singleline(&mut self.new_profile);
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("Create from current").clicked() || submitted) && !self.new_profile.trim().is_empty() {
+                self.create_profile(ctx);
+            }
+        });
+        match &self.profile_status {
+            Some(Ok(message)) => {
+                ui.small(message);
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
             }
+            None => {}
         }
     }
 
@@ -299,23 +1411,15 @@ impl TundraEditor {
             match serde_json::from_str::<AppState>(&file_content) {
                 Ok(loaded_state) => {
                     self.state = loaded_state;
+                    self.model_viewer.settings = self.state.viewport.clone();
+                    self.apply_memory_budget();
+                    safe_write::set_backup_count(self.state.backup_count);
                     println!("Loaded state from JSON with {} configured games", self.state.game_configs.len());
                     
                     // If we have a selected game with a valid path, scan its assets folder
-                    if let Some(game_type) = &self.state.selected_game {
-                        if let Some(config) = self.state.game_configs.get(game_type) {
-                            if game_type != &GameType::Cars3DrivenToWinXB1 {
-                                if self.validate_executable(game_type, &config.executable_path) {
-                                    let path = config.executable_path.clone();
-                                    self.scan_assets_folder(&path);
-                                }
-                            } else {
-                                if self.validate_executable(game_type, &config.executable_path) {
-                                    let path = config.executable_path.clone();
-                                    self.scan_dtw_folder(&path);
-                                }
-                            }
-                        }
+                    if let Some(game_type) = self.state.selected_game.clone() {
+                        self.start_game_scan(&game_type);
+                        self.restore_layout(&game_type);
                     }
                 }
                 Err(e) => {
@@ -327,6 +1431,110 @@ impl TundraEditor {
         }
     }
 
+    // Checks a write-back target can actually be written: folders needing elevation are offered a
+    // restart as administrator or the workspace, and files held open by the game are queued
+    fn resolve_write_target(&mut self, path: &Path) -> Option<PathBuf> {
+        let path = self.resolve_access(path)?;
+        self.resolve_in_use(&path)
+    }
+
+    fn resolve_access(&mut self, path: &Path) -> Option<PathBuf> {
+        match elevation::check_writable(path) {
+            Err(e) if elevation::is_access_denied(&e) => {}
+            _ => return Some(path.to_path_buf()),
+        }
+        let guard = self.write_guard();
+        let redirected = guard.workspace_path(path)
+            .unwrap_or_else(|| guard.workspace.join(path.file_name().unwrap_or_default()));
+        match elevation::ask(path, &redirected, self.has_unsaved_changes()) {
+            AccessChoice::Relaunch => {
+                self.save_state();
+                // The elevated instance would otherwise hand its paths back to this one
+                self.instance_lock = None;
+                match elevation::relaunch_as_admin() {
+                    Ok(()) => self.should_exit = true,
+                    Err(e) => eprintln!("Failed to restart as administrator: {}", e),
+                }
+                None
+            }
+            AccessChoice::Workspace(redirected) => {
+                if let Some(parent) = redirected.parent() {
+                    if let Err(e) = fs::create_dir_all(long_path(parent)) {
+                        eprintln!("Failed to create workspace folder {}: {}", parent.display(), e);
+                        return None;
+                    }
+                }
+                println!("Redirected write from {} to {}", path.display(), redirected.display());
+                Some(redirected)
+            }
+            AccessChoice::Cancel => None,
+        }
+    }
+
+    // Where a write to `path` should go when the game or something else has it open: the path
+    // itself once it's free, a staging file copied over it after the game exits, or None
+    fn resolve_in_use(&mut self, path: &Path) -> Option<PathBuf> {
+        while game_lock::is_in_use(path) {
+            let running = self.game_process.is_running();
+            match game_lock::ask(path, running) {
+                InUseChoice::Retry => continue,
+                InUseChoice::Queue => {
+                    return match self.write_queue.stage(Path::new(game_lock::STAGING_DIR), path) {
+                        Ok(staged) => {
+                            println!("Queued write to {} until the game exits", path.display());
+                            Some(staged)
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to stage write to {}: {}", path.display(), e);
+                            None
+                        }
+                    };
+                }
+                InUseChoice::Cancel => return None,
+            }
+        }
+        Some(path.to_path_buf())
+    }
+
+    // Writes what it can straight away; anything still locked is only left behind if the user says so
+    fn close_with_queued_writes(&mut self) -> bool {
+        if !self.write_queue.items.is_empty() && !self.game_process.is_running() && !self.task_manager.has_running() {
+            for message in self.write_queue.flush() {
+                println!("{}", message);
+            }
+        }
+        if self.write_queue.items.is_empty() {
+            return true;
+        }
+        if !game_lock::ask_close(&self.write_queue.items) {
+            return false;
+        }
+        for item in &self.write_queue.items {
+            println!("Left queued write to {} at {}", item.target.display(), item.staged.display());
+        }
+        true
+    }
+
+    // Writes queued files once the game has exited and whatever was writing them has finished
+    fn poll_write_queue(&mut self, ctx: &egui::Context) {
+        if self.write_queue.items.is_empty() || self.last_queue_check.elapsed() < std::time::Duration::from_secs(2) {
+            return;
+        }
+        self.last_queue_check = Instant::now();
+        ctx.request_repaint_after(std::time::Duration::from_secs(2));
+        if self.game_process.is_running() || self.task_manager.has_running() {
+            return;
+        }
+        for message in self.write_queue.flush() {
+            println!("{}", message);
+        }
+    }
+
+    fn apply_memory_budget(&mut self) {
+        let cache = memory_guard::set_budget_mb(self.state.memory_budget_mb, entry_cache::DEFAULT_CACHE_BUDGET);
+        self.entry_cache.lock().unwrap().set_budget(cache);
+    }
+
     fn load_file_icons(&mut self, cc: &eframe::CreationContext<'_>) {
         let icon_files = [
             ("bik", "src/art/bik.png"),
@@ -400,21 +1608,18 @@ impl TundraEditor {
                     .add_filter("Executable", &["exe"])
                     .pick_file()
                 {
-                    let config = GameConfig {
-                        executable_path: file_path.clone(),
-                    };
+                    // Keep per-game settings when only the executable changes
+                    let mut config = self.state.game_configs.get(&game_type).cloned().unwrap_or_default();
+                    config.executable_path = file_path.clone();
+                    // Picking an executable replaces a bare asset folder
+                    config.asset_folder = None;
                     self.state.game_configs.insert(game_type.clone(), config);
                     
                     // Save state immediately when a new executable is selected
                     self.save_state();
                     
                     // Automatically go to editor if valid executable
-                    if self.validate_executable(&game_type, &file_path) {
-                        if game_type != GameType::Cars3DrivenToWinXB1 {
-                            self.scan_assets_folder(&file_path);
-                        } else {
-                            self.scan_dtw_folder(&file_path);
-                        }
+                    if self.start_game_scan(&game_type) {
                         self.state.current_step = AppStep::Editor;
                         println!("Valid executable selected for {}, opening editor", game_type.as_str());
                     } else {
@@ -436,6 +1641,28 @@ impl TundraEditor {
         false
     }
 
+    // Scans a game's bare asset folder when one is set, otherwise the folders next to its
+    // executable. Returns false when neither is usable.
+    fn start_game_scan(&mut self, game_type: &GameType) -> bool {
+        let Some(config) = self.state.game_configs.get(game_type) else {
+            return false;
+        };
+        if let Some(folder) = config.asset_folder.clone().filter(|folder| folder.is_dir()) {
+            self.scan_folder(&folder);
+            return true;
+        }
+        if !self.validate_executable(game_type, &config.executable_path) {
+            return false;
+        }
+        let path = config.executable_path.clone();
+        if game_type != &GameType::Cars3DrivenToWinXB1 {
+            self.scan_assets_folder(&path);
+        } else {
+            self.scan_dtw_folder(&path);
+        }
+        true
+    }
+
     fn get_game_path(&self, game_type: &GameType) -> Option<PathBuf> {
         self.state
             .game_configs
@@ -443,15 +1670,251 @@ impl TundraEditor {
             .map(|config| config.executable_path.clone())
     }
 
+    fn export_settings(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export Tundra settings")
+            .set_file_name("tundra_settings.json")
+            .add_filter("JSON", &["json"])
+            .save_file() else {
+            return;
+        };
+
+        self.state.viewport = self.model_viewer.settings.clone();
+        if self.state.current_step == AppStep::Editor {
+            self.capture_layout();
+        }
+
+        let bundle = serde_json::json!({
+            "format_version": SETTINGS_BUNDLE_VERSION,
+            "tundra_version": env!("CARGO_PKG_VERSION"),
+            "app_state": &self.state,
+        });
+        match serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()))
+        {
+            Ok(()) => println!("Exported settings to {}", path.display()),
+            Err(e) => eprintln!("Failed to export settings: {}", e),
+        }
+    }
+
+    fn import_settings(&mut self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import Tundra settings")
+            .add_filter("JSON", &["json"])
+            .pick_file() else {
+            return;
+        };
+
+        let bundle = match fs::read_to_string(&path).map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<SettingsBundle>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                eprintln!("Failed to read settings from {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if bundle.format_version > SETTINGS_BUNDLE_VERSION {
+            eprintln!("Settings were exported by a newer Tundra ({}), some values may be ignored", bundle.tundra_version);
+        }
+
+        let confirmed = rfd::MessageDialog::new()
+            .set_title("Import settings")
+            .set_description(format!("Replace the current settings with the ones from {}?", path.display()))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if confirmed != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        let mut imported = bundle.app_state;
+        // Install paths rarely match between machines, keep local executables the import can't find
+        for (game_type, config) in imported.game_configs.iter_mut() {
+            if !config.executable_path.exists() {
+                if let Some(local) = self.state.game_configs.get(game_type).filter(|c| c.executable_path.exists()) {
+                    config.executable_path = local.executable_path.clone();
+                }
+            }
+        }
+        imported.selected_game = self.state.selected_game.clone();
+        imported.current_step = self.state.current_step.clone();
+
+        self.state = imported;
+        self.model_viewer.settings = self.state.viewport.clone();
+        if let Some(game_type) = self.state.selected_game.clone() {
+            self.restore_layout(&game_type);
+        }
+        self.apply_theme(ctx);
+        self.save_state();
+        println!("Imported settings from {}", path.display());
+    }
+
+    fn capture_layout(&mut self) {
+        let Some(game_type) = self.state.selected_game.clone() else {
+            return;
+        };
+        let mut expanded_folders: Vec<PathBuf> = self.expanded_folders.iter().cloned().collect();
+        expanded_folders.sort();
+        self.state.layouts.insert(game_type, WorkspaceLayout {
+            expanded_folders,
+            scene_tab: Some(self.scene_tabs.clone()),
+            show_tasks: self.show_tasks,
+            viewport: Some(self.model_viewer.settings.clone()),
+            textures: Some(self.mtb_viewer.texture_view_settings()),
+        });
+    }
+
+    // Games without a saved layout keep the current viewer settings
+    fn restore_layout(&mut self, game_type: &GameType) {
+        let Some(layout) = self.state.layouts.get(game_type).cloned() else {
+            return;
+        };
+        println!("Restoring {} layout", game_type.as_str());
+        self.expanded_folders = layout.expanded_folders.into_iter().collect();
+        self.tree_rows_dirty = true;
+        if let Some(tab) = layout.scene_tab {
+            self.scene_tabs = tab;
+        }
+        self.show_tasks = layout.show_tasks;
+        if let Some(viewport) = layout.viewport {
+            self.model_viewer.settings = viewport;
+        }
+        if let Some(textures) = layout.textures {
+            self.mtb_viewer.apply_texture_view_settings(&textures);
+        }
+    }
+
+    // Writes into the selected game's install folder need confirmation while protection is on
+    fn write_guard(&self) -> WriteGuard {
+        let game = self.state.selected_game.as_ref();
+        WriteGuard {
+            enabled: self.state.protect_game_dir,
+            game_root: game
+                .filter(|g| self.state.game_configs.get(*g).map_or(true, |c| c.asset_folder.is_none()))
+                .and_then(|g| self.get_game_path(g))
+                .and_then(|exe| exe.parent().map(|p| p.to_path_buf())),
+            workspace: PathBuf::from("workspace").join(game.map_or("Unknown", |g| g.as_str())),
+        }
+    }
+
+    fn naming(&self) -> Naming {
+        Naming {
+            templates: self.state.naming.clone(),
+            // Named the way job files and the config name games ("DisneyInfinity30")
+            game: self.state.selected_game.as_ref().map(|g| format!("{:?}", g)).unwrap_or_default(),
+        }
+    }
+
     fn scan_directory_threaded(path: PathBuf, cancel_flag: Arc<Mutex<bool>>) -> Vec<FileEntry> {
+        Self::scan_directory_cached(path, None, cancel_flag, None)
+    }
+
+    // Top-level folders are scanned in parallel; capped because spinning disks slow down with
+    // too many readers seeking at once
+    fn scan_thread_pool() -> Option<rayon::ThreadPool> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_SCAN_THREADS);
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("scan-{}", index))
+            .build()
+        {
+            Ok(pool) => Some(pool),
+            Err(e) => {
+                eprintln!("Failed to start scan threads, scanning on one thread: {}", e);
+                None
+            }
+        }
+    }
+
+    // Loads the cached tree for `root`, rescans only what changed and writes the result back.
+    fn scan_root_with_cache(root: PathBuf, use_cache: bool, cancel_flag: Arc<Mutex<bool>>) -> Vec<FileEntry> {
+        let _timer = ScopedTimer::new("scan", format!("{}{}", root.display(), if use_cache { "" } else { " (full)" }));
+        let mut caches: Vec<ScanCache> = fs::read_to_string(SCAN_CACHE_PATH)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let cached = if use_cache {
+            caches.iter().find(|cache| cache.root == root)
+        } else {
+            None
+        };
+        if cached.is_some() {
+            println!("Using scan cache for {}", root.display());
+        }
+
+        let pool = Self::scan_thread_pool();
+        let entries = Self::scan_directory_cached(
+            root.clone(),
+            cached.map(|cache| (cache.root_modified, cache.entries.as_slice())),
+            cancel_flag.clone(),
+            pool.as_ref(),
+        );
+
+        // Don't store a partial tree from a cancelled scan
+        if *cancel_flag.lock().unwrap() {
+            return entries;
+        }
+
+        let root_modified = fs::metadata(&root).map(|m| modified_millis(&m)).unwrap_or(0);
+        caches.retain(|cache| cache.root != root);
+        caches.push(ScanCache {
+            root,
+            root_modified,
+            entries: entries.clone(),
+        });
+
+        match serde_json::to_string(&caches) {
+            Ok(serialized) => {
+                if let Err(e) = fs::write(SCAN_CACHE_PATH, serialized) {
+                    eprintln!("Failed to write scan cache: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize scan cache: {}", e),
+        }
+
+        entries
+    }
+
+    // A folder's mtime only changes when its direct children do, so unchanged folders reuse
+    // their cached listing but are still walked to check the folders below them.
+    fn scan_directory_cached(
+        path: PathBuf,
+        cached: Option<(u64, &[FileEntry])>,
+        cancel_flag: Arc<Mutex<bool>>,
+        pool: Option<&rayon::ThreadPool>,
+    ) -> Vec<FileEntry> {
         let mut entries = Vec::new();
         
         // Check if cancelled before starting
         if *cancel_flag.lock().unwrap() {
             return entries;
         }
+
+        let current_modified = fs::metadata(long_path(&path)).map(|m| modified_millis(&m)).unwrap_or(0);
+        if let Some((cached_modified, cached_entries)) = cached {
+            if cached_modified == current_modified && current_modified != 0 {
+                for cached_entry in cached_entries {
+                    if *cancel_flag.lock().unwrap() {
+                        break;
+                    }
+
+                    let mut file_entry = cached_entry.clone();
+                    if file_entry.is_directory {
+                        file_entry.modified = fs::metadata(long_path(&file_entry.path)).map(|m| modified_millis(&m)).unwrap_or(0);
+                    }
+                    entries.push(file_entry);
+                }
+                Self::scan_subdirectories(&mut entries, Some(cached_entries), &cancel_flag, pool);
+                return entries;
+            }
+        }
         
-        if let Ok(read_dir) = fs::read_dir(&path) {
+        let read_dir = fs::read_dir(long_path(&path));
+        if let Err(e) = &read_dir {
+            eprintln!("Failed to read directory {}: {}", path.display(), e);
+        }
+        if let Ok(read_dir) = read_dir {
             let mut dir_entries: Vec<_> = read_dir.flatten().collect();
             
             // Sort entries: directories first, then files
@@ -474,11 +1937,10 @@ impl TundraEditor {
                     break;
                 }
                 
-                let entry_path = entry.path();
-                let file_name = entry_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or_default();
+                // Keep the un-prefixed path for display; entry.path() carries \\?\ when listing a long path
+                let os_name = entry.file_name();
+                let entry_path = path.join(&os_name);
+                let file_name = os_name.to_string_lossy();
 
                 // Cars 3/macOS garbage ignore list
                 let ignore = [
@@ -499,27 +1961,57 @@ impl TundraEditor {
                     ".DS_Store"
                 ];
 
-                if ignore.contains(&file_name) || file_name.starts_with("._") {
+                if ignore.contains(&file_name.as_ref()) || file_name.starts_with("._") {
                     continue;
                 }
 
-                let is_directory = entry_path.is_dir();
+                let is_directory = entry.path().is_dir();
                 
                 let mut file_entry = FileEntry::new(entry_path.clone(), is_directory);
-                
-                // Recursively scan directories (with cancellation check)
-                if is_directory {
-                    file_entry.children = Self::scan_directory_threaded(entry_path, cancel_flag.clone());
+                if let Ok(metadata) = fs::metadata(entry.path()) {
+                    file_entry.modified = modified_millis(&metadata);
+                    if !is_directory {
+                        file_entry.size = metadata.len();
+                    }
                 }
                 
                 entries.push(file_entry);
             }
+
+            // Recursively scan directories (with cancellation check)
+            Self::scan_subdirectories(&mut entries, cached.map(|(_, cached_entries)| cached_entries), &cancel_flag, pool);
         }
         
         entries
     }
 
+    // Fills in directory children once a listing is complete. With a pool the directories are
+    // scanned concurrently, but each result lands in its own slot so the order stays the same.
+    fn scan_subdirectories(
+        entries: &mut [FileEntry],
+        cached_entries: Option<&[FileEntry]>,
+        cancel_flag: &Arc<Mutex<bool>>,
+        pool: Option<&rayon::ThreadPool>,
+    ) {
+        let scan = |file_entry: &mut FileEntry| {
+            if !file_entry.is_directory || *cancel_flag.lock().unwrap() {
+                return;
+            }
+            let cached_dir = cached_entries
+                .and_then(|cached| cached.iter().find(|e| e.path == file_entry.path))
+                .map(|e| (e.modified, e.children.as_slice()));
+            // Only the top level fans out, deeper folders stay on the worker that found them
+            file_entry.children = Self::scan_directory_cached(file_entry.path.clone(), cached_dir, cancel_flag.clone(), None);
+        };
+
+        match pool {
+            Some(pool) => pool.install(|| entries.par_iter_mut().for_each(scan)),
+            None => entries.iter_mut().for_each(scan),
+        }
+    }
+
     fn read_zip_contents(&self, zip_path: &Path) -> Result<Vec<ZipEntry>, Box<dyn std::error::Error>> {
+        let _timer = ScopedTimer::new("archive", format!("List {}", zip_path.display()));
         // Check if this is a Disney Infinity 3.0 encrypted zip
         if let Some(game_type) = &self.state.selected_game {
             if matches!(game_type, GameType::DisneyInfinity30) {
@@ -587,7 +2079,7 @@ impl TundraEditor {
         let mut entries = Vec::new();
         
         for i in 0..archive.len() {
-            let file = archive.by_index(i)?;
+            let file = archive.by_index_raw(i)?;
             let is_directory = file.name().ends_with('/');
         
             entries.push(ZipEntry {
@@ -624,535 +2116,5446 @@ impl TundraEditor {
         // Fall back to regular zip extraction
         let file = fs::File::open(zip_path)?;
         let mut archive = zip::ZipArchive::new(file)?;
-        let mut file = archive.by_name(entry_name)?;
-        
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
-        
-        Ok(contents)
+        let index = archive.index_for_name(entry_name).ok_or(zip::result::ZipError::FileNotFound)?;
+        Ok(codecs::read_zip_entry(&mut archive, index)?)
     }
 
-    fn extract_zip_to_temp(&self, zip_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        // Create a unique temp directory for this zip file
-        let zip_file_name = zip_path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown_zip");
-        
-        let extract_dir = self.temp_dir.join(zip_file_name);
-        
-        // Clear existing directory if it exists
-        if extract_dir.exists() {
-            fs::remove_dir_all(&extract_dir)?;
-        }
-        
-        // Create the directory
-        fs::create_dir_all(&extract_dir)?;
-        
-        println!("Extracting {} to {}", zip_path.display(), extract_dir.display());
-        
-        // Extract based on game type
-        if let Some(game_type) = &self.state.selected_game {
-            if matches!(game_type, GameType::DisneyInfinity30) && DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
-                // Use Disney Infinity extraction
-                let entries = DisneyInfinityZipReader::read_zip_contents(zip_path)?;
-                
-                for entry in entries {
-                    if !entry.is_directory {
-                        match DisneyInfinityZipReader::extract_file(zip_path, &entry) {
-                            Ok(content) => {
-                                let file_path = extract_dir.join(&entry.name);
-                                
-                                // Create parent directories if needed
-                                if let Some(parent) = file_path.parent() {
-                                    fs::create_dir_all(parent)?;
-                                }
-                                
-                                fs::write(&file_path, content)?;
-                                println!("Extracted: {}", entry.name);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to extract {}: {}", entry.name, e);
-                            }
-                        }
-                    }
-                }
-            } else if matches!(game_type, GameType::Cars3DrivenToWinXB1) {
-                // Use Cars 3 extraction
-                let entries = DrivenToWinZip::read_zip_contents(zip_path)?;
-                let mut file = fs::File::open(zip_path)?;
-                
-                for entry in entries {
-                    let file_name = entry.file_name.clone();
-                    if !file_name.ends_with('/') {
-                        match DrivenToWinZip::extract_zip_file(entry, &mut file) {
-                            Ok(content) => {
-                                let file_path = extract_dir.join(&file_name);
-                                
-                                // Create parent directories if needed
-                                if let Some(parent) = file_path.parent() {
-                                    fs::create_dir_all(parent)?;
-                                }
-                                
-                                fs::write(&file_path, content)?;
-                                println!("Extracted: {}", file_name);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to extract {}: {}", file_name, e);
-                            }
-                        }
-                    }
-                }
-            } else {
-                // Use regular zip extraction
-                let file = fs::File::open(zip_path)?;
-                let mut archive = zip::ZipArchive::new(file)?;
-                
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i)?;
-                    let file_name = file.name().to_string();
-                    
-                    // Skip directories (they're created automatically)
-                    if file_name.ends_with('/') {
-                        continue;
-                    }
-                    
-                    let file_path = extract_dir.join(&file_name);
-                    
-                    // Create parent directories if needed
-                    if let Some(parent) = file_path.parent() {
-                        fs::create_dir_all(parent)?;
-                    }
-                    
-                    let mut content = Vec::new();
-                    file.read_to_end(&mut content)?;
-                    
-                    fs::write(&file_path, content)?;
-                    println!("Extracted: {}", file_name);
-                }
-            }
+    // Lists every file in an archive without extracting it
+    fn list_archive_entries(game_type: Option<&GameType>, zip_path: &Path) -> Result<Vec<ArchiveEntryInfo>, Box<dyn std::error::Error>> {
+        if matches!(game_type, Some(GameType::DisneyInfinity30)) && DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
+            let entries = DisneyInfinityZipReader::read_zip_contents(zip_path)?;
+            return Ok(entries
+                .into_iter()
+                .filter(|entry| !entry.is_directory)
+                .map(|entry| ArchiveEntryInfo {
+                    method: compression_name(entry.compression_method),
+                    compressed_size: entry.compressed_size as u64,
+                    size: entry.uncompressed_size as u64,
+                    crc32: entry.crc32,
+                    name_hash: Some(entry.name_hash),
+                    name: entry.name,
+                })
+                .collect());
         }
-        
-        println!("Extraction complete: {} files extracted", extract_dir.display());
-        Ok(extract_dir)
-    }
 
-    fn scan_assets_folder(&mut self, executable_path: &Path) {
-        // Cancel any ongoing scan
-        *self.scan_cancel.lock().unwrap() = true;
-        if let Some(thread) = self.scan_thread.take() {
-            let _ = thread.join();
+        if matches!(game_type, Some(GameType::Cars3DrivenToWinXB1)) {
+            if let Ok(entries) = DrivenToWinZip::read_zip_contents(zip_path) {
+                return Ok(entries
+                    .into_iter()
+                    .filter(|entry| !entry.file_name.ends_with('/'))
+                    .map(|entry| ArchiveEntryInfo {
+                        method: compression_name(entry.compression_type),
+                        compressed_size: entry.compressed_size as u64,
+                        size: entry.uncompressed_size as u64,
+                        crc32: entry.file_crc,
+                        name_hash: None,
+                        name: entry.file_name,
+                    })
+                    .collect());
+            }
         }
-        
-        // Reset cancel flag
-        *self.scan_cancel.lock().unwrap() = false;
-        
-        self.file_tree.clear();
-        self.selected_file = None;
-        self.model_viewer.clear_model();
-        self.mtb_viewer.clear();
-        self.scene_viewer.clear();
-        self.show_scene_viewer = false;
 
-        // Get the directory containing the executable
-        if let Some(parent_dir) = executable_path.parent() {
-            let assets_dir = parent_dir.join("assets");
-            
-            println!("Starting threaded scan of: {}", assets_dir.display());
-            
-            if assets_dir.exists() && assets_dir.is_dir() {
-                let scan_path = assets_dir.clone(); // Clone here to avoid move
-                let cancel_flag = self.scan_cancel.clone();
-                
-                // Start threaded scan
-                self.scan_thread = Some(thread::spawn(move || {
-                    Self::scan_directory_threaded(scan_path, cancel_flag)
-                }));
-                
-                // Show progress immediately
-                self.scan_progress = Some(ScanProgress {
-                    current_path: assets_dir,
-                    total_files: 0, // We don't know the total yet
-                    processed_files: 0,
-                    start_time: Instant::now(),
-                });
-            } else {
-                println!("Assets folder not found: {}", assets_dir.display());
-                // Fall back to scanning the parent directory
-                let scan_path = parent_dir.to_path_buf();
-                let cancel_flag = self.scan_cancel.clone();
-                
-                self.scan_thread = Some(thread::spawn(move || {
-                    Self::scan_directory_threaded(scan_path, cancel_flag)
-                }));
-                
-                self.scan_progress = Some(ScanProgress {
-                    current_path: parent_dir.to_path_buf(),
-                    total_files: 0,
-                    processed_files: 0,
-                    start_time: Instant::now(),
+        let file = fs::File::open(zip_path)?;
+        let mut archive = zip::ZipArchive::new(file)?;
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index_raw(i)?;
+            if !file.name().ends_with('/') {
+                entries.push(ArchiveEntryInfo {
+                    name: file.name().to_string(),
+                    method: file.compression().to_string(),
+                    compressed_size: file.compressed_size(),
+                    size: file.size(),
+                    crc32: file.crc32(),
+                    name_hash: None,
                 });
             }
-        } else {
-            println!("Could not get parent directory of executable: {}", executable_path.display());
         }
+        Ok(entries)
     }
 
-    fn scan_dtw_folder(&mut self, executable_path: &Path) {
-        // Cancel any ongoing scan
-        *self.scan_cancel.lock().unwrap() = true;
-        if let Some(thread) = self.scan_thread.take() {
-            let _ = thread.join();
-        }
-        
-        // Reset cancel flag
-        *self.scan_cancel.lock().unwrap() = false;
-        
-        self.file_tree.clear();
-        self.selected_file = None;
-        self.model_viewer.clear_model();
-        self.mtb_viewer.clear();
-        self.scene_viewer.clear();
-        self.show_scene_viewer = false;
+    fn start_storage_analysis(&mut self) {
+        let Some(root) = self.file_tree.first().and_then(|e| e.path.parent()).map(|p| p.to_path_buf()) else {
+            return;
+        };
 
-        // Get the directory containing the executable
-        if let Some(parent_dir) = executable_path.parent() {
-            println!("Starting threaded scan of: {}", parent_dir.display());
-            
-            let scan_path = parent_dir.to_path_buf();
-            let cancel_flag = self.scan_cancel.clone();
-            
-            self.scan_thread = Some(thread::spawn(move || {
-                Self::scan_directory_threaded(scan_path, cancel_flag)
-            }));
-            
-            self.scan_progress = Some(ScanProgress {
-                current_path: parent_dir.to_path_buf(),
-                total_files: 0,
-                processed_files: 0,
-                start_time: Instant::now(),
-            });
-        } else {
-            println!("Could not get parent directory of executable: {}", executable_path.display());
-        }
+        let tree = self.file_tree.clone();
+        let game_type = self.state.selected_game.clone();
+        let temp_dir = self.temp_dir.clone();
+
+        self.storage_analyzer.start(root, move || {
+            let mut items = Vec::new();
+            Self::collect_storage_items(&tree, game_type.as_ref(), &temp_dir, &mut items);
+            items
+        });
     }
 
-    fn check_scan_completion(&mut self) {
-        if let Some(thread) = &self.scan_thread {
-            if thread.is_finished() {
-                if let Some(thread) = self.scan_thread.take() {
-                    match thread.join() {
-                        Ok(result) => {
-                            self.file_tree = result;
-                            self.scan_progress = None;
-                            println!("Scan completed with {} root entries", self.file_tree.len());
-                            
-                            // Log total file count
-                            let total_files = self.count_files(&self.file_tree);
-                            println!("Total files and directories found: {}", total_files);
-                        }
-                        Err(e) => {
-                            eprintln!("Scan thread panicked: {:?}", e);
-                            self.scan_progress = None;
+    fn collect_storage_items(entries: &[FileEntry], game_type: Option<&GameType>, temp_dir: &Path, items: &mut Vec<StorageItem>) {
+        for entry in entries {
+            if entry.is_directory {
+                Self::collect_storage_items(&entry.children, game_type, temp_dir, items);
+                continue;
+            }
+
+            // Extracted archive contents are counted from the archive itself
+            if entry.path.starts_with(temp_dir) {
+                continue;
+            }
+
+            items.push(StorageItem {
+                path: entry.path.clone(),
+                size: entry.size,
+                archive: None,
+            });
+
+            if entry.is_zip {
+                match Self::list_archive_entries(game_type, &entry.path) {
+                    Ok(archive_entries) => {
+                        for archive_entry in archive_entries {
+                            items.push(StorageItem {
+                                path: entry.path.join(archive_entry.name),
+                                size: archive_entry.size,
+                                archive: Some(entry.path.clone()),
+                            });
                         }
                     }
+                    Err(e) => eprintln!("Failed to list {}: {}", entry.path.display(), e),
                 }
             }
         }
     }
 
-    fn count_files(&self, entries: &[FileEntry]) -> usize {
-        let mut count = entries.len();
-        for entry in entries {
+    // Calls `visit` with the contents of every archive entry accepted by `wanted`; stops when `visit` returns false
+    fn for_each_archive_file(
+        game_type: Option<&GameType>,
+        zip_path: &Path,
+        wanted: &dyn Fn(&str) -> bool,
+        visit: &mut dyn FnMut(&str, Result<Vec<u8>, Box<dyn std::error::Error>>) -> bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if matches!(game_type, Some(GameType::DisneyInfinity30)) && DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
+            let entries = DisneyInfinityZipReader::read_zip_contents(zip_path)?;
+            for entry in entries.iter().filter(|e| !e.is_directory && wanted(&e.name)) {
+                // The compressed and decompressed copies are both held while extracting
+                let reservation = memory_guard::reserve(entry.compressed_size as u64 + entry.uncompressed_size as u64, &entry.name);
+                let data = match &reservation {
+                    Ok(_) => DisneyInfinityZipReader::extract_file(zip_path, entry),
+                    Err(e) => Err(e.clone().into()),
+                };
+                if !visit(&entry.name, data) {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
+        if matches!(game_type, Some(GameType::Cars3DrivenToWinXB1)) {
+            if let Ok(entries) = DrivenToWinZip::read_zip_contents(zip_path) {
+                let mut file = fs::File::open(zip_path)?;
+                for entry in entries.into_iter().filter(|e| !e.file_name.ends_with('/') && wanted(&e.file_name)) {
+                    let name = entry.file_name.clone();
+                    let reservation = memory_guard::reserve(entry.compressed_size as u64 + entry.uncompressed_size as u64, &name);
+                    let data = match &reservation {
+                        Ok(_) => DrivenToWinZip::extract_zip_file(entry, &mut file),
+                        Err(e) => Err(e.clone().into()),
+                    };
+                    if !visit(&name, data) {
+                        break;
+                    }
+                }
+                return Ok(());
+            }
+        }
+
+        let file = fs::File::open(zip_path)?;
+        let mut archive = zip::ZipArchive::new(file)?;
+        for i in 0..archive.len() {
+            let (name, size) = {
+                let file = archive.by_index_raw(i)?;
+                (file.name().to_string(), file.size())
+            };
+            if name.ends_with('/') || !wanted(&name) {
+                continue;
+            }
+            let reservation = memory_guard::reserve(size, &name);
+            let result = match &reservation {
+                Ok(_) => codecs::read_zip_entry(&mut archive, i).map_err(|e| e.into()),
+                Err(e) => Err(e.clone().into()),
+            };
+            if !visit(&name, result) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Reads a file from disk, or from inside an archive when the path is <archive>/<entry>
+    fn read_tree_file(game_type: Option<&GameType>, cache: &Mutex<EntryCache>, path: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let disk_path = long_path(path);
+        if disk_path.is_file() {
+            return Ok(fs::read(disk_path)?);
+        }
+
+        let archive = path.ancestors()
+            .skip(1)
+            .find(|p| p.is_file())
+            .ok_or_else(|| format!("{} not found", path.display()))?;
+        let entry_name = paths::VirtualPath::from_path(path.strip_prefix(archive)?);
+
+        entry_cache::get_or_load(cache, archive, entry_name.as_str(), || {
+            let mut found = None;
+            Self::for_each_archive_file(game_type, archive, &|name| paths::VirtualPath::new(name) == entry_name, &mut |_, result| {
+                found = Some(result);
+                false
+            })?;
+            match found {
+                Some(result) => result,
+                None => Err(format!("{} not found in {}", entry_name.as_str(), archive.display()).into()),
+            }
+        })
+    }
+
+    // Looks through the scanned tree and every archive for names close to a missing texture hash
+    fn start_texture_match_search(&mut self, tbody_filename: String) {
+        let tree = self.file_tree.clone();
+        let game_type = self.state.selected_game.clone();
+        let temp_dir = self.temp_dir.clone();
+        let matches = self.mtb_viewer.matches.clone();
+
+        self.task_manager.spawn(format!("Find matches for {}", tbody_filename), move |task| {
+            let mut files = Vec::new();
+            let mut archives = Vec::new();
+            Self::collect_texture_candidates(&tree, &temp_dir, &mut files, &mut archives);
+            task.set_total(archives.len() + 1);
+
+            let mut found = Vec::new();
+            let mut add_candidate = |path: PathBuf| {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                if let Some(score) = mtb_viewer::texture_match_score(&tbody_filename, &name) {
+                    found.push(TextureMatch { path, score });
+                }
+            };
+
+            files.into_iter().for_each(&mut add_candidate);
+            task.advance("Loose files");
+
+            for archive in &archives {
+                if task.is_cancelled() {
+                    break;
+                }
+                match Self::list_archive_entries(game_type.as_ref(), archive) {
+                    Ok(entries) => entries.into_iter().for_each(|entry| add_candidate(archive.join(entry.name))),
+                    Err(e) => task.add_error(format!("{}: {}", archive.display(), e)),
+                }
+                task.advance(archive.display().to_string());
+            }
+
+            mtb_viewer::sort_texture_matches(&mut found);
+            task.finish(format!("{} suggestions for {}", found.len(), tbody_filename));
+            matches.lock().unwrap().insert(tbody_filename, found);
+        });
+    }
+
+    fn collect_texture_candidates(entries: &[FileEntry], temp_dir: &Path, files: &mut Vec<PathBuf>, archives: &mut Vec<PathBuf>) {
+        for entry in entries {
             if entry.is_directory {
-                count += self.count_files(&entry.children);
+                Self::collect_texture_candidates(&entry.children, temp_dir, files, archives);
+            } else if entry.path.starts_with(temp_dir) {
+                continue;
+            } else if entry.is_zip {
+                archives.push(entry.path.clone());
+            } else {
+                files.push(entry.path.clone());
+            }
+        }
+    }
+
+    // Decodes queued textures in the background; the viewer shows placeholders until each one is ready
+    fn start_texture_decoding(&mut self, ctx: &egui::Context) {
+        let requests = self.mtb_viewer.take_decode_requests();
+        if requests.is_empty() {
+            return;
+        }
+
+        let ctx = ctx.clone();
+        let source = self.viewer_source.clone();
+        self.task_manager.spawn(format!("Decode {} texture(s)", requests.len()), move |task| {
+            task.set_total(requests.len());
+            let mut decoded = 0;
+            for request in requests {
+                if task.is_cancelled() {
+                    request.cancel();
+                    continue;
+                }
+                let name = paths::display_name(&request.file_path);
+                task.advance(name.clone());
+                match request.run(&source, &ctx) {
+                    Ok(()) => decoded += 1,
+                    Err(e) => task.add_error(format!("{}: {}", name, e)),
+                }
+            }
+            task.finish(format!("{} texture(s) decoded", decoded));
+        });
+    }
+
+    // Whether the texture loaded; only then is the binding worth remembering
+    fn bind_mtb_texture(&mut self, tbody_filename: &str, path: &Path, ctx: &egui::Context) -> bool {
+        let result = Self::read_tree_file(self.state.selected_game.as_ref(), &self.entry_cache, path)
+            .and_then(|data| self.mtb_viewer.bind_texture_data(tbody_filename, path, &data, ctx));
+        if let Err(e) = &result {
+            eprintln!("Failed to bind {} to {}: {}", tbody_filename, path.display(), e);
+        }
+        result.is_ok()
+    }
+
+    fn is_scene_file_name(name: &str) -> bool {
+        let lower = name.to_lowercase();
+        lower.ends_with(".oct") || lower.ends_with(".bent")
+    }
+
+    // Gathers loose OCT/BENT files and the archives that may contain more of them
+    fn collect_scene_sources(entries: &[FileEntry], temp_dir: &Path, files: &mut Vec<PathBuf>, archives: &mut Vec<PathBuf>) {
+        for entry in entries {
+            if entry.is_directory {
+                Self::collect_scene_sources(&entry.children, temp_dir, files, archives);
+            } else if entry.path.starts_with(temp_dir) {
+                continue;
+            } else if entry.is_zip {
+                archives.push(entry.path.clone());
+            } else if entry.path.file_name().and_then(|n| n.to_str()).map_or(false, Self::is_scene_file_name) {
+                files.push(entry.path.clone());
+            }
+        }
+    }
+
+    fn start_batch_scene_dump(&mut self, ctx: &egui::Context) {
+        let Some(output_dir) = rfd::FileDialog::new()
+            .set_title("Select output folder for JSON dump")
+            .pick_folder()
+            .and_then(|p| self.write_guard().resolve(&p)) else {
+            return;
+        };
+        self.run_operation(Operation::BatchSceneDump { output_dir }, ctx);
+    }
+
+    fn spawn_batch_scene_dump(&mut self, output_dir: PathBuf) -> String {
+        let Some(root) = self.file_tree.first().and_then(|e| e.path.parent()).map(|p| p.to_path_buf()) else {
+            return "Nothing scanned".to_string();
+        };
+
+        let mut files = Vec::new();
+        let mut archives = Vec::new();
+        Self::collect_scene_sources(&self.file_tree, &self.temp_dir, &mut files, &mut archives);
+        let game_type = self.state.selected_game.clone();
+
+        self.task_manager.spawn("Batch OCT → JSON", move |task| {
+            Self::batch_scene_dump(&task, &root, &output_dir, &files, &archives, game_type.as_ref());
+        });
+        self.show_tasks = true;
+        "Started in the background, see Tasks".to_string()
+    }
+
+    // Walks loose and archived OCT/BENT files, reporting progress and stopping early on cancellation
+    fn for_each_scene_file(
+        task: &TaskContext,
+        root: &Path,
+        files: &[PathBuf],
+        archives: &[PathBuf],
+        game_type: Option<&GameType>,
+        visit: &mut dyn FnMut(&Path, Result<Vec<u8>, String>),
+    ) {
+        // Archive entry counts aren't known up front, so archives count as one step each
+        task.set_total(files.len() + archives.len());
+
+        for path in files {
+            if task.is_cancelled() {
+                return;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            task.advance(relative.display().to_string());
+            visit(relative, fs::read(long_path(path)).map_err(|e| e.to_string()));
+        }
+
+        for archive in archives {
+            if task.is_cancelled() {
+                return;
+            }
+            let relative_archive = archive.strip_prefix(root).unwrap_or(archive);
+            task.advance(relative_archive.display().to_string());
+
+            let result = Self::for_each_archive_file(game_type, archive, &|name| Self::is_scene_file_name(name), &mut |name, data| {
+                if task.is_cancelled() {
+                    return false;
+                }
+                let relative = paths::archive_entry_path(relative_archive, name);
+                task.set_message(relative.display().to_string());
+                visit(&relative, data.map_err(|e| e.to_string()));
+                true
+            });
+
+            if let Err(e) = result {
+                visit(relative_archive, Err(e.to_string()));
+            }
+        }
+    }
+
+    fn batch_scene_dump(task: &TaskContext, root: &Path, output_dir: &Path, files: &[PathBuf], archives: &[PathBuf], game_type: Option<&GameType>) {
+        let mut converted = 0;
+        let mut partial = 0;
+        let mut failed = 0;
+        // One line per warning or failure for parse_failures.txt
+        let mut failures: Vec<String> = Vec::new();
+
+        Self::for_each_scene_file(task, root, files, archives, game_type, &mut |relative, data| {
+            // An archive outside the scanned root keeps its absolute path, which join would take over
+            let target = output_dir.join(relative);
+            let data = if target.starts_with(output_dir) {
+                data
+            } else {
+                Err(format!("would be written outside {}", output_dir.display()))
+            };
+            match data.and_then(|data| Self::write_scene_json(&data, &target)) {
+                Ok(warnings) => {
+                    converted += 1;
+                    if !warnings.is_empty() {
+                        partial += 1;
+                    }
+                    for warning in warnings {
+                        task.add_error(format!("{} (partial): {}", relative.display(), warning));
+                        failures.push(format!("{}\tpartial: {}", relative.display(), warning));
+                    }
+                }
+                Err(e) => {
+                    failed += 1;
+                    task.add_error(format!("{}: {}", relative.display(), e));
+                    failures.push(format!("{}\t{}", relative.display(), e));
+                }
+            }
+        });
+
+        if !failures.is_empty() {
+            let report_path = output_dir.join("parse_failures.txt");
+            if let Err(e) = fs::write(&report_path, failures.join("\n")) {
+                eprintln!("Failed to write {}: {}", report_path.display(), e);
+            }
+        }
+
+        let status = if task.is_cancelled() { "Cancelled" } else { "Done" };
+        let summary = format!(
+            "{}: {} converted ({} partially), {} failed → {}",
+            status,
+            converted,
+            partial,
+            failed,
+            output_dir.display()
+        );
+        println!("Batch OCT → JSON {}", summary);
+        task.finish(summary);
+    }
+
+    fn start_schema_report(&mut self, ctx: &egui::Context) {
+        let Some(report_path) = rfd::FileDialog::new()
+            .set_title("Save OCT schema report")
+            .set_file_name("oct_schema.md")
+            .add_filter("Markdown", &["md"])
+            .add_filter("JSON", &["json"])
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p)) else {
+            return;
+        };
+        self.run_operation(Operation::SchemaReport { report_path }, ctx);
+    }
+
+    fn spawn_schema_report(&mut self, report_path: PathBuf) -> String {
+        let Some(root) = self.file_tree.first().and_then(|e| e.path.parent()).map(|p| p.to_path_buf()) else {
+            return "Nothing scanned".to_string();
+        };
+
+        let mut files = Vec::new();
+        let mut archives = Vec::new();
+        Self::collect_scene_sources(&self.file_tree, &self.temp_dir, &mut files, &mut archives);
+        let game_type = self.state.selected_game.clone();
+
+        self.task_manager.spawn("OCT schema report", move |task| {
+            let mut report = SchemaReport::new();
+            let mut failed = 0;
+
+            Self::for_each_scene_file(&task, &root, &files, &archives, game_type.as_ref(), &mut |relative, data| {
+                let mut handler = SceneFileHandler::new();
+                let result = data.and_then(|data| {
+                    handler.load_scene_file(&mut std::io::Cursor::new(data)).map_err(|e| e.to_string())
+                });
+                match (result, &handler.current_scene) {
+                    (Ok(()), Some(scene)) => report.add_scene(scene),
+                    (Err(e), _) => {
+                        failed += 1;
+                        task.add_error(format!("{}: {}", relative.display(), e));
+                    }
+                    _ => failed += 1,
+                }
+            });
+
+            let is_json = report_path.extension().map_or(false, |e| e.eq_ignore_ascii_case("json"));
+            let contents = if is_json {
+                serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+            } else {
+                Ok(report.to_markdown())
+            };
+
+            match contents.and_then(|c| fs::write(&report_path, c).map_err(|e| e.to_string())) {
+                Ok(()) => task.finish(format!(
+                    "{} files analyzed ({} failed), {} key paths → {}",
+                    report.file_count,
+                    failed,
+                    report.keys.len(),
+                    report_path.display()
+                )),
+                Err(e) => task.finish(format!("Failed to write report: {}", e)),
+            }
+        });
+        self.show_tasks = true;
+        "Started in the background, see Tasks".to_string()
+    }
+
+    // Starts, stops or moves the server to match the options, then answers queued requests
+    fn update_remote_server(&mut self, ctx: &egui::Context) {
+        let wanted = (self.state.remote_api || self.remote_api_session).then_some(self.state.remote_api_port);
+        if self.remote_server.as_ref().map(|server| server.port) != wanted {
+            self.remote_server = None;
+            match wanted {
+                Some(port) if self.remote_error.as_ref().map_or(true, |(failed, _)| *failed != port) => {
+                    match RemoteServer::start(port, ctx) {
+                        Ok(server) => {
+                            self.remote_server = Some(server);
+                            self.remote_error = None;
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to start remote API on port {}: {}", port, e);
+                            self.remote_error = Some((port, format!("Couldn't listen on port {}: {}", port, e)));
+                        }
+                    }
+                }
+                Some(_) => {}
+                None => self.remote_error = None,
+            }
+        }
+
+        let requests = match &self.remote_server {
+            Some(server) => server.take_requests(),
+            None => return,
+        };
+        for request in requests {
+            self.handle_remote_request(request);
+        }
+    }
+
+    // Only files in the scanned tree, or entries of archives in it, can be read remotely
+    // <archive>.zip/../../x would pass the ancestor checks while naming a file outside it
+    fn has_dot_components(path: &Path) -> bool {
+        path.components().any(|c| matches!(c, std::path::Component::ParentDir | std::path::Component::CurDir))
+    }
+
+    fn remote_path_allowed(&self, path: &Path) -> bool {
+        if Self::has_dot_components(path) {
+            return false;
+        }
+        Self::find_entry(&self.file_tree, path).is_some()
+            || path.ancestors().skip(1).any(|a| Self::find_entry(&self.file_tree, a).map_or(false, |e| e.is_zip))
+    }
+
+    // Job outputs usually don't exist yet, so a scanned folder above one is enough
+    fn remote_output_allowed(&self, path: &Path) -> bool {
+        if Self::has_dot_components(path) {
+            return false;
+        }
+        self.remote_path_allowed(path)
+            || path.ancestors().skip(1).any(|a| Self::find_entry(&self.file_tree, a).map_or(false, |e| e.is_directory))
+    }
+
+    fn handle_remote_request(&mut self, request: RemoteRequest) {
+        println!("Remote API: {} {}", request.method, request.path);
+        let response = match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/api/status") => RemoteResponse::json(&serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "game": self.state.selected_game.as_ref().map(|g| g.as_str()),
+                "scanning": self.scan_progress.is_some(),
+                "files": self.count_files(&self.file_tree),
+            })),
+            ("GET", "/api/tree") => RemoteResponse::json(&self.file_tree),
+            ("GET", "/api/file") | ("GET", "/api/convert") => {
+                let Some(path) = request.param("path").map(PathBuf::from) else {
+                    return request.respond(RemoteResponse::error(400, "Missing path parameter"));
+                };
+                if !self.remote_path_allowed(&path) {
+                    return request.respond(RemoteResponse::error(404, "Not in the scanned tree"));
+                }
+
+                // Archive entries can take a while to read, so the UI thread doesn't wait for them
+                let convert = request.path == "/api/convert";
+                let game_type = self.state.selected_game.clone();
+                let cache = self.entry_cache.clone();
+                thread::spawn(move || {
+                    let response = match Self::read_tree_file(game_type.as_ref(), &cache, &path) {
+                        Ok(data) if convert => Self::convert_for_remote(&path, &data),
+                        Ok(data) => RemoteResponse::bytes("application/octet-stream", data),
+                        Err(e) => RemoteResponse::error(404, &e.to_string()),
+                    };
+                    request.respond(response);
+                });
+                return;
+            }
+            ("POST", "/api/job") => {
+                let base_dir = std::env::current_dir().unwrap_or_default();
+                match Job::from_json(&String::from_utf8_lossy(&request.body), &base_dir) {
+                    Ok(mut job) => {
+                        // Remote jobs read only what /api/file would serve and write nowhere protected
+                        let guard = self.write_guard();
+                        if !job.steps.iter().all(|step| step.inputs().iter().all(|input| self.remote_path_allowed(input))) {
+                            RemoteResponse::error(403, "Job reads from outside the scanned tree")
+                        } else if !job.steps.iter_mut().all(|step| self.remote_output_allowed(step.output_mut())) {
+                            RemoteResponse::error(403, "Job writes outside the scanned tree")
+                        } else if job.steps.iter_mut().any(|step| guard.is_protected(step.output_mut())) {
+                            RemoteResponse::error(403, "Job writes into the protected game folder")
+                        } else {
+                            if job.name.is_empty() {
+                                job.name = "Remote job".to_string();
+                            }
+                            let name = job.name.clone();
+                            self.spawn_job(job);
+                            RemoteResponse::json(&serde_json::json!({ "started": name }))
+                        }
+                    }
+                    Err(e) => RemoteResponse::error(400, &format!("Invalid job: {}", e)),
+                }
+            }
+            (_, "/api/job") => RemoteResponse::error(405, "Jobs are started with POST"),
+            _ => RemoteResponse::error(404, "Unknown endpoint"),
+        };
+        request.respond(response);
+    }
+
+    // Textures come back as PNG and scenes as JSON
+    fn convert_for_remote(path: &Path, data: &[u8]) -> RemoteResponse {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+        match extension.as_str() {
+            "tbody" | "dds" => match jobs::texture_png_bytes(data) {
+                Ok(png) => RemoteResponse::bytes("image/png", png),
+                Err(e) => RemoteResponse::error(500, &e.to_string()),
+            },
+            "oct" | "bent" => match SceneFileHandler::scene_to_json(data) {
+                Ok((json, _)) => RemoteResponse::bytes("application/json", json.into_bytes()),
+                Err(e) => RemoteResponse::error(500, &e.to_string()),
+            },
+            _ => RemoteResponse::error(400, &format!("No conversion for .{} files", extension)),
+        }
+    }
+
+    // Files waiting in the workspace, with their path relative to the game folder they replace
+    // Enabled mods in load order, then the workspace
+    fn mod_sources(&self) -> Vec<ModFiles> {
+        let config = self.state.selected_game.as_ref().and_then(|g| self.state.game_configs.get(g));
+        let mut sources: Vec<ModFiles> = config
+            .map(|c| c.mods.iter().filter(|m| m.enabled && m.path.is_dir()).map(|m| ModFiles { name: m.name.clone(), files: jobs::matching_files(&m.path, "**") }).collect())
+            .unwrap_or_default();
+        let workspace = self.write_guard().workspace;
+        if workspace.is_dir() {
+            sources.push(ModFiles { name: mod_conflicts::WORKSPACE_NAME.to_string(), files: jobs::matching_files(&workspace, "**") });
+        }
+        sources
+    }
+
+    fn mod_overrides(&self) -> HashMap<String, String> {
+        self.state.selected_game.as_ref()
+            .and_then(|g| self.state.game_configs.get(g))
+            .map(|c| c.mod_overrides.clone())
+            .unwrap_or_default()
+    }
+
+    // Each file deploying puts in the game folder, from the mod that wins it
+    fn deploy_files(&self) -> Vec<(PathBuf, PathBuf)> {
+        mod_conflicts::merge(&self.mod_sources(), &self.mod_overrides())
+    }
+
+    // Reads every entry so a CRC mismatch or broken compression shows up before the game hits it
+    fn validate_archive(path: &Path) -> Vec<(Severity, String)> {
+        let mut results = Vec::new();
+        if DisneyInfinityZipReader::is_disney_infinity_zip(path) {
+            let entries = match DisneyInfinityZipReader::read_zip_contents(path) {
+                Ok(entries) => entries,
+                Err(e) => return vec![(Severity::Error, format!("Can't read archive: {}", e))],
+            };
+            for entry in entries.iter().filter(|e| !e.is_directory) {
+                match DisneyInfinityZipReader::extract_file(path, entry) {
+                    Ok(data) => {
+                        let mut crc = flate2::Crc::new();
+                        crc.update(&data);
+                        if crc.sum() != entry.crc32 {
+                            results.push((Severity::Error, format!("{}: CRC is {:08x}, header says {:08x}", entry.name, crc.sum(), entry.crc32)));
+                        }
+                    }
+                    Err(e) => results.push((Severity::Error, format!("{}: {}", entry.name, e))),
+                }
+            }
+            return results;
+        }
+
+        // The zip crate checks each entry's CRC once it has been read to the end
+        let archive = fs::File::open(long_path(path)).map_err(|e| e.to_string())
+            .and_then(|file| zip::ZipArchive::new(file).map_err(|e| e.to_string()));
+        let mut archive = match archive {
+            Ok(archive) => archive,
+            Err(e) => return vec![(Severity::Error, format!("Can't read archive: {}", e))],
+        };
+        for index in 0..archive.len() {
+            // Entries the zip crate can't decode are checked by the codec registry instead
+            let result = if codecs::unsupported_method(&mut archive, index).is_some() {
+                codecs::read_zip_entry(&mut archive, index).map(|_| ())
+            } else {
+                archive.by_index(index).map_err(|e| e.to_string()).and_then(|mut entry| {
+                    let name = entry.name().to_string();
+                    std::io::copy(&mut entry, &mut std::io::sink()).map(|_| ()).map_err(|e| format!("{}: {}", name, e))
+                })
+            };
+            if let Err(e) = result {
+                results.push((Severity::Error, e));
+            }
+        }
+        results
+    }
+
+    fn start_deploy_validation(&mut self) {
+        let files = self.deploy_files();
+        let game_root = self.write_guard().game_root;
+        let report_slot = self.deploy_report.clone();
+        *report_slot.lock().unwrap() = None;
+        self.deploy_validating = true;
+
+        self.task_manager.spawn("Pre-deploy validation", move |task| {
+            task.set_total(files.len());
+            let mut report = ValidationReport::default();
+            for (path, relative) in &files {
+                if task.is_cancelled() {
+                    break;
+                }
+                task.advance(relative.display().to_string());
+                let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+                // A modified VBUF/IBUF pair is checked once, from the VBUF
+                if extension == "ibuf" && path.with_extension("vbuf").exists() {
+                    continue;
+                }
+                let original = game_root.as_ref().map(|root| root.join(relative)).filter(|p| p.is_file());
+                let results = if extension == "zip" {
+                    Self::validate_archive(path)
+                } else {
+                    validation::validate_file(path, original.as_deref())
+                };
+                report.add(relative, results);
+            }
+
+            let summary = format!(
+                "{} files: {} errors, {} warnings",
+                report.files_checked,
+                report.count(Severity::Error),
+                report.count(Severity::Warning)
+            );
+            *report_slot.lock().unwrap() = Some(report);
+            task.finish(summary);
+        });
+    }
+
+    // Copies the workspace and enabled mods over the game install, once validation found nothing
+    // blocking
+    fn deploy_workspace(&mut self) {
+        let Some(game_root) = self.write_guard().game_root else {
+            return;
+        };
+        let sources = self.mod_sources();
+        let overrides = self.mod_overrides();
+        let files = mod_conflicts::merge(&sources, &overrides);
+        if self.dry_run {
+            let mut report = DryRunReport::new(format!("Deploy workspace to {}", game_root.display()));
+            for (path, relative) in &files {
+                report.add(game_root.join(relative), path.display().to_string());
+            }
+            self.dry_run_results.push(report);
+            self.show_dry_run = true;
+            return;
+        }
+        let conflicts = mod_conflicts::find_conflicts(&sources, &overrides);
+        let mut conflict_note = String::new();
+        if !conflicts.is_empty() {
+            let lines: Vec<String> = conflicts.iter().take(10)
+                .map(|c| format!("{}: {} wins", c.relative.display(), sources[c.winner].name))
+                .collect();
+            let more = if conflicts.len() > lines.len() { format!("\n... and {} more", conflicts.len() - lines.len()) } else { String::new() };
+            conflict_note = format!("\n\n{} files are changed by more than one mod; the Deploy window lets you pick which one wins:\n{}{}", conflicts.len(), lines.join("\n"), more);
+        }
+        let confirmed = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Deploy workspace")
+            .set_description(format!("Copy {} files from the workspace and mods into {}?\n\nThe game's files are overwritten. If any file fails, none are; Undo reverts them all.{}", files.len(), game_root.display(), conflict_note))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if confirmed != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        // Every file is staged before any is replaced, so a failure leaves the install as it was
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let backup_dir = self.temp_dir.join("_undo").join(now.to_string());
+        let mut transaction = file_ops::Transaction::new(&backup_dir, format!("deploy to {}", game_root.display()));
+        for (path, relative) in &files {
+            let target = game_root.join(relative);
+            if let Err(e) = safe_write::prepare(&target).and_then(|_| transaction.stage_copy(path, &target)) {
+                eprintln!("Failed to stage {}, nothing was deployed: {}", relative.display(), e);
+                transaction.abort();
+                return;
+            }
+        }
+        match transaction.commit() {
+            Ok(record) => {
+                println!("Deployed {} files to {}", files.len(), game_root.display());
+                self.undo_stack.push(record);
+            }
+            Err(e) => eprintln!("Deploy failed and was rolled back: {}", e),
+        }
+        // Files may be edited again before the next deploy
+        *self.deploy_report.lock().unwrap() = None;
+    }
+
+    fn show_deploy_ui(&mut self, ui: &mut egui::Ui) {
+        let guard = self.write_guard();
+        let Some(game_root) = guard.game_root.clone() else {
+            ui.label("Set the game executable first, files are deployed next to it.");
+            return;
+        };
+        ui.label(format!("Workspace: {}", guard.workspace.display()));
+        ui.label(format!("Game folder: {}", game_root.display()));
+        ui.separator();
+        self.show_mods_ui(ui);
+        ui.separator();
+
+        let report = self.deploy_report.lock().unwrap().clone();
+        if report.is_some() {
+            self.deploy_validating = false;
+        }
+        let validating = self.deploy_validating;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!validating, egui::Button::new("Validate")).clicked() {
+                self.start_deploy_validation();
+            }
+            let can_deploy = report.as_ref().map_or(false, |r| r.files_checked > 0 && !r.is_blocking());
+            if ui.add_enabled(can_deploy, egui::Button::new("Deploy"))
+                .on_disabled_hover_text("Validate first; errors block deploying")
+                .clicked()
+            {
+                self.deploy_workspace();
+            }
+            ui.checkbox(&mut self.dry_run, "Dry run")
+                .on_hover_text("List the files deploying would overwrite without copying them");
+        });
+        ui.separator();
+
+        match report {
+            Some(report) => report.show_ui(ui),
+            None if validating => {
+                ui.spinner();
+            }
+            None => {
+                ui.label("Checks modified OCT, MTB, texture, VBUF/IBUF and archive files against the game's originals.");
+            }
+        }
+    }
+
+    fn show_mods_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(game) = self.state.selected_game.clone() else {
+            return;
+        };
+        let mut list_changed = false;
+        let mut check = false;
+        // (relative path, winning mod, whether that's the load order's pick, its index)
+        let mut override_change = None;
+        egui::CollapsingHeader::new("Mods").id_source("deploy_mods").default_open(true).show(ui, |ui| {
+            ui.small("Deployed in this order; a later mod's files replace an earlier one's and the workspace goes last");
+            let Some(config) = self.state.game_configs.get_mut(&game) else {
+                return;
+            };
+            // (index, -1 up / 1 down / 0 remove)
+            let mut action = None;
+            let count = config.mods.len();
+            egui::Grid::new("installed_mods").num_columns(2).show(ui, |ui| {
+                for (index, installed) in config.mods.iter_mut().enumerate() {
+                    list_changed |= ui.checkbox(&mut installed.enabled, &installed.name).on_hover_text(installed.path.display().to_string()).changed();
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(index > 0, egui::Button::new("⏶").small()).clicked() {
+                            action = Some((index, -1));
+                        }
+                        if ui.add_enabled(index + 1 < count, egui::Button::new("⏷").small()).clicked() {
+                            action = Some((index, 1));
+                        }
+                        if ui.small_button("Remove").on_hover_text("Takes the mod out of the list; its folder stays").clicked() {
+                            action = Some((index, 0));
+                        }
+                    });
+                    ui.end_row();
+                }
+                ui.weak(mod_conflicts::WORKSPACE_NAME);
+                ui.weak("always last");
+                ui.end_row();
+            });
+            match action {
+                Some((index, 0)) => {
+                    config.mods.remove(index);
+                    list_changed = true;
+                }
+                Some((index, step)) => {
+                    config.mods.swap(index, (index as i32 + step) as usize);
+                    list_changed = true;
+                }
+                None => {}
+            }
+            if ui.button("Add mod folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new().set_title("Mod folder, laid out like the game folder").pick_folder() {
+                    config.mods.push(InstalledMod { name: paths::display_name(&folder), path: folder, enabled: true });
+                    list_changed = true;
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.checking_conflicts, egui::Button::new("Check conflicts"))
+                    .on_hover_text("Finds files more than one mod changes, and the archive entries they disagree on")
+                    .clicked()
+                {
+                    check = true;
+                }
+                if self.checking_conflicts {
+                    ui.spinner();
+                }
+            });
+            let result = self.mod_conflicts.lock().unwrap().clone();
+            if result.is_some() {
+                self.checking_conflicts = false;
+            }
+            let Some((names, conflicts)) = result else {
+                return;
+            };
+            if conflicts.is_empty() {
+                ui.label("No two mods change the same file differently.");
+                return;
+            }
+            ui.colored_label(ui.visuals().warn_fg_color, format!("{} files are changed by more than one mod", conflicts.len()));
+            egui::ScrollArea::vertical().id_source("mod_conflicts").max_height(200.0).show(ui, |ui| {
+                for conflict in &conflicts {
+                    ui.horizontal(|ui| {
+                        let mut chosen = conflict.winner;
+                        egui::ComboBox::from_id_source(("conflict_winner", &conflict.relative))
+                            .selected_text(&names[chosen])
+                            .show_ui(ui, |ui| {
+                                for provider in &conflict.providers {
+                                    ui.selectable_value(&mut chosen, *provider, &names[*provider]);
+                                }
+                            });
+                        let label = ui.label(conflict.relative.display().to_string());
+                        if !conflict.entries.is_empty() {
+                            label.on_hover_text(format!(
+                                "Only the winning archive is deployed; entries the others change differently:\n{}",
+                                conflict.entries.join("\n")
+                            ));
+                        }
+                        if chosen != conflict.winner {
+                            let last = conflict.providers[conflict.providers.len() - 1];
+                            override_change = Some((conflict.relative.clone(), names[chosen].clone(), chosen == last, chosen));
+                        }
+                    });
+                }
+            });
+        });
+
+        if check {
+            self.spawn_conflict_check();
+        }
+        if let Some((relative, name, is_default, chosen)) = override_change {
+            if let Some(config) = self.state.game_configs.get_mut(&game) {
+                let key = mod_conflicts::key(&relative);
+                if is_default {
+                    config.mod_overrides.remove(&key);
+                } else {
+                    config.mod_overrides.insert(key, name);
+                }
+            }
+            if let Some((_, conflicts)) = self.mod_conflicts.lock().unwrap().as_mut() {
+                if let Some(conflict) = conflicts.iter_mut().find(|c| c.relative == relative) {
+                    conflict.winner = chosen;
+                }
+            }
+            *self.deploy_report.lock().unwrap() = None;
+            self.save_state();
+        }
+        if list_changed {
+            // Indexes in the last check and the validated files no longer match
+            *self.mod_conflicts.lock().unwrap() = None;
+            *self.deploy_report.lock().unwrap() = None;
+            self.save_state();
+        }
+    }
+
+    // Installed packages go here, one folder per mod, next to the workspace
+    fn mods_dir(&self) -> PathBuf {
+        PathBuf::from("mods").join(self.state.selected_game.as_ref().map_or("Unknown", |g| g.as_str()))
+    }
+
+    fn show_mod_export(&mut self, ctx: &egui::Context) {
+        let Some(game) = self.state.selected_game.clone() else {
+            self.mod_export = None;
+            return;
+        };
+        let mods: Vec<InstalledMod> = self.state.game_configs.get(&game).map(|c| c.mods.clone()).unwrap_or_default();
+        let mut sources = vec![mod_conflicts::WORKSPACE_NAME.to_string()];
+        sources.extend(mods.iter().map(|m| m.name.clone()));
+
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new("Export mod package")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(form) = &mut self.mod_export {
+                    action = form.show_ui(ui, &sources);
+                }
+            });
+        match action {
+            Some(ExportAction::Export) => {
+                let Some(form) = self.mod_export.take() else {
+                    return;
+                };
+                let (folder, source_name) = match form.source {
+                    0 => (self.write_guard().workspace, mod_conflicts::WORKSPACE_NAME.to_string()),
+                    index => (mods[index - 1].path.clone(), mods[index - 1].name.clone()),
+                };
+                let mut manifest = form.manifest;
+                // Files this source was picked to win keep winning wherever it's installed
+                manifest.install.overrides = self.mod_overrides().into_iter().filter(|(_, name)| *name == source_name).map(|(path, _)| path).collect();
+                let file_name = format!("{}-{}.{}", mod_package::folder_name(&manifest.name), manifest.version, mod_package::PACKAGE_EXTENSION);
+                let Some(output) = rfd::FileDialog::new()
+                    .set_title("Export mod package")
+                    .set_file_name(file_name)
+                    .add_filter("Tundra mod", &[mod_package::PACKAGE_EXTENSION])
+                    .save_file() else {
+                    return;
+                };
+                self.task_manager.spawn(format!("Package {}", manifest.name), move |task| {
+                    match mod_package::write_package(&folder, &output, manifest, &task) {
+                        Ok(summary) => task.finish(summary),
+                        Err(e) => {
+                            task.add_error(e.clone());
+                            task.finish(format!("Failed: {}", e));
+                        }
+                    }
+                });
+                self.show_tasks = true;
+            }
+            Some(ExportAction::Cancel) => self.mod_export = None,
+            None if !open => self.mod_export = None,
+            None => {}
+        }
+    }
+
+    fn install_mod_package(&mut self) {
+        let Some(game) = self.state.selected_game.clone() else {
+            return;
+        };
+        let Some(package) = rfd::FileDialog::new()
+            .set_title("Install mod package")
+            .add_filter("Tundra mod", &[mod_package::PACKAGE_EXTENSION])
+            .pick_file() else {
+            return;
+        };
+        self.spawn_mod_install(package, game);
+    }
+
+    // Downloads into temp/ and installs the same way a picked package is
+    fn spawn_mod_download(&mut self, entry: IndexEntry) {
+        let Some(game) = self.state.selected_game.clone() else {
+            return;
+        };
+        let mods_dir = self.mods_dir();
+        let package = self.temp_dir.join("downloads").join(format!("{}-{}.{}", mod_package::folder_name(&entry.name), mod_package::folder_name(&entry.version), mod_package::PACKAGE_EXTENSION));
+        let slot = self.mod_install.clone();
+        self.task_manager.spawn(format!("Download {}", entry.name), move |task| {
+            let result = mod_browser::download(&entry, &package, &task)
+                .and_then(|_| mod_package::install(&package, &mods_dir, game.as_str(), &task));
+            let _ = fs::remove_file(long_path(&package));
+            match &result {
+                Ok((manifest, _)) => task.finish(format!("Installed {} {}", manifest.name, manifest.version)),
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+            *slot.lock().unwrap() = Some(result);
+        });
+        self.show_tasks = true;
+    }
+
+    fn spawn_mod_install(&mut self, package: PathBuf, game: GameType) {
+        let mods_dir = self.mods_dir();
+        let slot = self.mod_install.clone();
+        self.task_manager.spawn(format!("Install {}", paths::display_name(&package)), move |task| {
+            let result = mod_package::install(&package, &mods_dir, game.as_str(), &task);
+            match &result {
+                Ok((manifest, _)) => task.finish(format!("Installed {} {}", manifest.name, manifest.version)),
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+            *slot.lock().unwrap() = Some(result);
+        });
+        self.show_tasks = true;
+    }
+
+    // Adds a freshly installed package to the load order and tells the user what's next
+    fn poll_mod_install(&mut self) {
+        let Some(result) = self.mod_install.lock().unwrap().take() else {
+            return;
+        };
+        let (manifest, folder) = match result {
+            Ok(installed) => installed,
+            Err(e) => {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Mod not installed")
+                    .set_description(e)
+                    .show();
+                return;
+            }
+        };
+        let Some(config) = self.state.selected_game.as_ref().and_then(|g| self.state.game_configs.get_mut(g)) else {
+            return;
+        };
+        // A reinstall keeps its place unless its rules say otherwise
+        let previous = config.mods.iter().position(|m| m.name == manifest.name);
+        if let Some(index) = previous {
+            config.mods.remove(index);
+        }
+        let after = config.mods.iter().rposition(|m| manifest.install.load_after.contains(&m.name));
+        let index = match (after, previous) {
+            (Some(after), _) => after + 1,
+            (None, Some(previous)) => previous.min(config.mods.len()),
+            (None, None) => config.mods.len(),
+        };
+        config.mods.insert(index, InstalledMod { name: manifest.name.clone(), path: folder, enabled: true });
+        for path in &manifest.install.overrides {
+            config.mod_overrides.insert(mod_conflicts::key(Path::new(path)), manifest.name.clone());
+        }
+        *self.mod_conflicts.lock().unwrap() = None;
+        *self.deploy_report.lock().unwrap() = None;
+        self.save_state();
+
+        let mut description = format!("{} {} is installed", manifest.name, manifest.version);
+        if !manifest.author.is_empty() {
+            description.push_str(&format!(" (by {})", manifest.author));
+        }
+        description.push_str(&format!(", {} files.", manifest.files.len()));
+        if !manifest.description.is_empty() {
+            description.push_str(&format!("\n\n{}", manifest.description));
+        }
+        description.push_str("\n\nOpen Tools > Validate and deploy workspace to put it into the game.");
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Info)
+            .set_title("Mod installed")
+            .set_description(description)
+            .show();
+    }
+
+    fn spawn_conflict_check(&mut self) {
+        let sources = self.mod_sources();
+        let overrides = self.mod_overrides();
+        let game_type = self.state.selected_game.clone();
+        let slot = self.mod_conflicts.clone();
+        *slot.lock().unwrap() = None;
+        self.checking_conflicts = true;
+        self.task_manager.spawn("Mod conflicts", move |task| {
+            let mut conflicts = mod_conflicts::find_conflicts(&sources, &overrides);
+            task.set_total(conflicts.len());
+            for conflict in conflicts.iter_mut() {
+                if task.is_cancelled() {
+                    break;
+                }
+                task.advance(conflict.relative.display().to_string());
+                let key = mod_conflicts::key(&conflict.relative);
+                let paths: Vec<PathBuf> = conflict.providers.iter()
+                    .filter_map(|source| sources[*source].files.iter().find(|(_, relative)| mod_conflicts::key(relative) == key))
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                if !paths.first().map_or(false, |p| Self::is_archive_path(p)) {
+                    continue;
+                }
+                let mut listings = Vec::new();
+                for path in &paths {
+                    match Self::list_archive_entries(game_type.as_ref(), path) {
+                        Ok(entries) => listings.push(entries.into_iter().map(|e| (e.name, e.crc32)).collect()),
+                        Err(e) => task.add_error(format!("{}: {}", path.display(), e)),
+                    }
+                }
+                if listings.len() == paths.len() {
+                    conflict.entries = mod_conflicts::differing_entries(&listings);
+                }
+            }
+            let names = sources.iter().map(|s| s.name.clone()).collect();
+            let summary = format!("{} conflicting files", conflicts.len());
+            *slot.lock().unwrap() = Some((names, conflicts));
+            task.finish(summary);
+        });
+    }
+
+    fn start_analysis(&mut self, path: PathBuf) {
+        let slot = self.analysis.clone();
+        *slot.lock().unwrap() = None;
+        *self.streams.lock().unwrap() = None;
+        self.streams_searching = false;
+        self.show_analysis = true;
+
+        self.task_manager.spawn(format!("Analyze {}", paths::display_name(&path)), move |task| {
+            let result = analyze::analyze(&path, &task);
+            let summary = match &result {
+                Ok(analysis) => format!("{} embedded headers, {:.2} bits/byte overall", analysis.hits.len(), analysis.overall_entropy),
+                Err(e) => e.clone(),
+            };
+            *slot.lock().unwrap() = Some(result);
+            task.finish(summary);
+        });
+    }
+
+    fn show_analysis_ui(&mut self, ui: &mut egui::Ui) {
+        let slot = self.analysis.clone();
+        let result = slot.lock().unwrap();
+        let analysis = match &*result {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Analyzing...");
+                });
+                return;
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+                return;
+            }
+            Some(Ok(analysis)) => analysis,
+        };
+
+        ui.label(analysis.path.display().to_string());
+        ui.label(format!("Format: {}", analysis.format.unwrap_or("unknown")))
+            .on_hover_text(format!("From the signature database; add formats to {}", signatures::USER_FILE));
+        ui.label(format!(
+            "{} ({}), {:.2} bits/byte overall",
+            format_size(analysis.size as u64),
+            analysis.size,
+            analysis.overall_entropy
+        ));
+        ui.weak("Near 8 bits/byte is compressed or encrypted data, low values are padding, tables or text");
+        let points: egui_plot::PlotPoints = analysis.entropy
+            .iter()
+            .enumerate()
+            .map(|(block, &entropy)| [(block * analysis.block_size) as f64, entropy as f64])
+            .collect();
+        egui_plot::Plot::new("analysis_entropy_plot")
+            .height(180.0)
+            .include_y(0.0)
+            .include_y(8.0)
+            .allow_scroll(false)
+            .x_axis_label("Offset")
+            .y_axis_label("Entropy")
+            .show(ui, |plot_ui| {
+                plot_ui.line(egui_plot::Line::new(points).name(format!("{} byte blocks", analysis.block_size)));
+                // Marking every hit would bury the line in files full of small streams
+                for hit in analysis.hits.iter().take(256) {
+                    plot_ui.vline(egui_plot::VLine::new(hit.offset as f64).name(hit.name));
+                }
+            });
+        ui.separator();
+
+        ui.strong(format!("Embedded headers ({})", analysis.hits.len()));
+        if analysis.truncated {
+            ui.weak("Stopped listing after the first matches");
+        }
+        let mut carve = None;
+        egui::ScrollArea::vertical().id_source("analysis_hits").show(ui, |ui| {
+            egui::Grid::new("analysis_hits_grid").striped(true).num_columns(4).show(ui, |ui| {
+                ui.strong("Offset");
+                ui.strong("Format");
+                ui.strong("Length");
+                ui.label("");
+                ui.end_row();
+                for hit in &analysis.hits {
+                    ui.monospace(format!("0x{:08x}", hit.offset));
+                    ui.label(hit.name);
+                    ui.label(format_size(hit.length as u64));
+                    if ui.small_button("Carve...").clicked() {
+                        carve = Some(hit.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        let path = analysis.path.clone();
+        drop(result);
+        if let Some(hit) = carve {
+            self.carve_region(&path, &hit);
+        }
+        ui.separator();
+        self.show_stream_search_ui(ui, &path);
+    }
+
+    fn start_stream_search(&mut self, path: PathBuf) {
+        let slot = self.streams.clone();
+        *slot.lock().unwrap() = None;
+        self.streams_searching = true;
+        let search = self.stream_search;
+
+        self.task_manager.spawn(format!("Find compressed streams in {}", paths::display_name(&path)), move |task| {
+            let result = analyze::find_streams(&path, search, &task);
+            let summary = match &result {
+                Ok(streams) => format!("{} streams inflated", streams.len()),
+                Err(e) => e.clone(),
+            };
+            *slot.lock().unwrap() = Some(result);
+            task.finish(summary);
+        });
+    }
+
+    fn show_stream_search_ui(&mut self, ui: &mut egui::Ui, path: &Path) {
+        ui.strong("Compressed streams");
+        let slot = self.streams.clone();
+        let result = slot.lock().unwrap();
+        if result.is_some() {
+            self.streams_searching = false;
+        }
+        let searching = self.streams_searching;
+        let mut search = false;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.stream_search.raw_deflate, "Raw deflate too")
+                .on_hover_text("Tries every offset without a zlib header, much slower");
+            ui.label("Min inflated size:");
+            ui.add(egui::DragValue::new(&mut self.stream_search.min_inflated).suffix(" B"));
+            search = ui.add_enabled(!searching, egui::Button::new("Find streams")).clicked();
+        });
+
+        let mut export = None;
+        let mut export_all = false;
+        match &*result {
+            None if searching => {
+                ui.spinner();
+            }
+            None => {
+                ui.weak("Tries to inflate at every offset and lists what decompresses cleanly");
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            Some(Ok(streams)) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} streams", streams.len()));
+                    export_all = ui.add_enabled(!streams.is_empty(), egui::Button::new("Export all...")).clicked();
+                });
+                egui::ScrollArea::vertical().id_source("analysis_streams").max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("analysis_streams_grid").striped(true).num_columns(6).show(ui, |ui| {
+                        ui.strong("Offset");
+                        ui.strong("Format");
+                        ui.strong("Compressed");
+                        ui.strong("Inflated");
+                        ui.strong("Preview");
+                        ui.label("");
+                        ui.end_row();
+                        for stream in streams {
+                            let preview: String = stream.preview
+                                .iter()
+                                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                                .collect();
+                            ui.monospace(format!("0x{:08x}", stream.offset));
+                            ui.label(stream.format.label());
+                            ui.label(stream.compressed.map_or("?".to_string(), |size| format_size(size as u64)));
+                            ui.label(format_size(stream.inflated));
+                            ui.monospace(preview.chars().take(32).collect::<String>()).on_hover_text(preview);
+                            if ui.small_button("Export...").clicked() {
+                                export = Some(stream.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        }
+
+        let streams = match &*result {
+            Some(Ok(streams)) if export_all => streams.clone(),
+            _ => Vec::new(),
+        };
+        drop(result);
+        if search {
+            self.start_stream_search(path.to_path_buf());
+        }
+        if let Some(stream) = export {
+            self.export_streams(path, &[stream], false);
+        }
+        if export_all {
+            self.export_streams(path, &streams, true);
+        }
+    }
+
+    // Saves inflated streams, one through a save dialog or all of them into a picked folder
+    fn export_streams(&mut self, path: &Path, streams: &[InflatedStream], to_folder: bool) {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let file_name = |stream: &InflatedStream| format!("{}_{:08x}.{}", stem, stream.offset, analyze::guess_extension(&stream.preview));
+        let mut dialog = rfd::FileDialog::new().set_title("Export inflated data");
+        if let Some(parent) = path.parent() {
+            dialog = dialog.set_directory(parent);
+        }
+        let outputs: Vec<PathBuf> = if to_folder {
+            let Some(folder) = dialog.pick_folder().and_then(|p| self.write_guard().resolve(&p)) else {
+                return;
+            };
+            streams.iter().map(|stream| folder.join(file_name(stream))).collect()
+        } else {
+            let Some(stream) = streams.first() else {
+                return;
+            };
+            let Some(output) = dialog.set_file_name(file_name(stream)).save_file().and_then(|p| self.write_guard().resolve(&p)) else {
+                return;
+            };
+            vec![output]
+        };
+
+        match analyze::export_streams(path, streams, &outputs) {
+            Ok(exported) => {
+                println!("Exported {} of {} inflated streams from {}", exported, streams.len(), path.display());
+                self.refresh_tree();
+            }
+            Err(e) => eprintln!("Failed to export streams from {}: {}", path.display(), e),
+        }
+    }
+
+    // Saves one embedded region as its own file
+    fn carve_region(&mut self, path: &Path, hit: &analyze::MagicHit) {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut dialog = rfd::FileDialog::new()
+            .set_title("Carve region")
+            .set_file_name(format!("{}_{:08x}.{}", stem, hit.offset, hit.extension))
+            .add_filter(hit.name, &[hit.extension]);
+        if let Some(parent) = path.parent() {
+            dialog = dialog.set_directory(parent);
+        }
+        let Some(output) = dialog.save_file().and_then(|p| self.write_guard().resolve(&p)) else {
+            return;
+        };
+        match analyze::carve(path, hit.offset, hit.length, &output) {
+            Ok(()) => {
+                println!("Carved {} bytes at 0x{:x} to {}", hit.length, hit.offset, output.display());
+                self.refresh_tree();
+            }
+            Err(e) => eprintln!("Failed to carve {}: {}", output.display(), e),
+        }
+    }
+
+    fn show_jobs_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Open job file...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Open job file")
+                    .add_filter("Job", &["json"])
+                    .pick_file()
+                {
+                    self.state.recent_jobs.retain(|p| *p != path);
+                    self.state.recent_jobs.insert(0, path.clone());
+                    self.save_state();
+                    self.start_job(&path);
+                }
+            }
+            ui.weak("Also runs from the command line: tundra --job <file>");
+        });
+        ui.separator();
+
+        if self.state.recent_jobs.is_empty() {
+            ui.label("No job files opened yet");
+            return;
+        }
+
+        let mut run = None;
+        let mut remove = None;
+        for (index, path) in self.state.recent_jobs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(paths::display_name(path)).on_hover_text(path.display().to_string());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    if ui.small_button("Run").clicked() {
+                        run = Some(path.clone());
+                    }
+                });
+            });
+        }
+
+        if let Some(path) = run {
+            self.start_job(&path);
+        }
+        if let Some(index) = remove {
+            self.state.recent_jobs.remove(index);
+            self.save_state();
+        }
+    }
+
+    fn start_job(&mut self, path: &Path) {
+        let job = match Job::load(path) {
+            Ok(job) => job,
+            Err(e) => {
+                eprintln!("Failed to load job {}: {}", path.display(), e);
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Invalid job file")
+                    .set_description(format!("{}\n\n{}", path.display(), e))
+                    .show();
+                return;
+            }
+        };
+        self.spawn_job(job);
+    }
+
+    fn spawn_job(&mut self, mut job: Job) {
+        // Outputs are checked up front, the guard's dialog can't be shown from the task thread
+        let guard = self.write_guard();
+        for step in &mut job.steps {
+            match guard.resolve(step.output_mut()) {
+                Some(output) => *step.output_mut() = output,
+                None => return,
+            }
+        }
+
+        let game_type = job.game.as_deref().and_then(Self::parse_game_type).or_else(|| self.state.selected_game.clone());
+        let naming = Naming { game: game_type.as_ref().map(|g| format!("{:?}", g)).unwrap_or_default(), ..self.naming() };
+        if self.dry_run {
+            let results = self.dry_run_results.clone();
+            self.task_manager.spawn(format!("Dry run: {}", job.name), move |task| {
+                let report = Self::plan_job(&task, &job, game_type.as_ref(), &naming);
+                let summary = report.summary();
+                results.push(report);
+                task.finish(summary);
+            });
+            self.show_tasks = true;
+            self.show_dry_run = true;
+            return;
+        }
+        self.task_manager.spawn(format!("Job: {}", job.name), move |task| {
+            let summary = Self::run_job(&task, &job, game_type.as_ref(), &naming);
+            task.finish(summary);
+        });
+        self.show_tasks = true;
+    }
+
+    // Job files name games the way the config does ("DisneyInfinity30")
+    fn parse_game_type(name: &str) -> Option<GameType> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    // Runs the steps in order. A failed step stops the job unless it asks to continue.
+    fn run_job(task: &TaskContext, job: &Job, game_type: Option<&GameType>, naming: &Naming) -> String {
+        println!("Running job {} ({} steps)", job.name, job.steps.len());
+        task.set_total(job.steps.len());
+
+        let mut completed = 0;
+        for (index, step) in job.steps.iter().enumerate() {
+            if task.is_cancelled() {
+                return format!("Cancelled after {} of {} steps", completed, job.steps.len());
+            }
+            let description = step.describe();
+            task.advance(format!("{}/{}: {}", index + 1, job.steps.len(), description));
+            println!("[job] {}/{}: {}", index + 1, job.steps.len(), description);
+
+            match Self::run_job_step(task, step, game_type, naming) {
+                Ok(summary) => {
+                    println!("[job]   {}", summary);
+                    completed += 1;
+                }
+                Err(e) => {
+                    eprintln!("[job]   Failed: {}", e);
+                    task.add_error(format!("Step {} ({}): {}", index + 1, description, e));
+                    if !job.continue_on_error {
+                        return format!("Stopped at step {} of {}: {}", index + 1, job.steps.len(), e);
+                    }
+                }
+            }
+        }
+        format!("{} of {} steps completed", completed, job.steps.len())
+    }
+
+    // What each step of a job would write, following the same matching as run_job_step
+    fn plan_job(task: &TaskContext, job: &Job, game_type: Option<&GameType>, naming: &Naming) -> DryRunReport {
+        let mut report = DryRunReport::new(format!("Job: {}", job.name));
+        task.set_total(job.steps.len());
+        for step in &job.steps {
+            if task.is_cancelled() {
+                break;
+            }
+            task.advance(step.describe());
+            match step {
+                JobStep::Extract { archive, pattern, output } => match Self::list_archive_entries(game_type, archive) {
+                    Ok(entries) => {
+                        for entry in entries.iter().filter(|e| jobs::glob_match(pattern, &e.name)) {
+                            report.add(paths::archive_entry_path(output, &entry.name), format!("{} in {}", entry.name, archive.display()));
+                        }
+                    }
+                    Err(e) => report.errors.push(format!("{}: {}", archive.display(), e)),
+                },
+                JobStep::ConvertTextures { input, pattern, output } => {
+                    for (path, relative) in jobs::matching_files(input, pattern) {
+                        let size = jobs::texture_size(&path).unwrap_or((0, 0));
+                        report.add(output.join(naming.texture(&relative, size)), path.display().to_string());
+                    }
+                }
+                JobStep::ConvertScenes { input, output } => {
+                    for (path, relative) in jobs::matching_files(input, "**") {
+                        if relative.file_name().and_then(|n| n.to_str()).map_or(false, Self::is_scene_file_name) {
+                            report.add(PathBuf::from(format!("{}.json", output.join(&relative).display())), path.display().to_string());
+                        }
+                    }
+                }
+                JobStep::WriteManifest { input, output } => report.add(output.clone(), input.display().to_string()),
+            }
+        }
+        report
+    }
+
+    fn run_job_step(task: &TaskContext, step: &JobStep, game_type: Option<&GameType>, naming: &Naming) -> Result<String, String> {
+        match step {
+            JobStep::Extract { archive, pattern, output } => {
+                let mut written = 0;
+                Self::for_each_archive_file(game_type, archive, &|name| jobs::glob_match(pattern, name), &mut |name, data| {
+                    if task.is_cancelled() {
+                        return false;
+                    }
+                    task.set_message(name.to_string());
+                    let result = data.map_err(|e| e.to_string()).and_then(|data| {
+                        paths::write_creating_dirs(&paths::archive_entry_path(output, name), &data).map_err(|e| e.to_string())
+                    });
+                    match result {
+                        Ok(()) => written += 1,
+                        Err(e) => task.add_error(format!("{}: {}", name, e)),
+                    }
+                    true
+                }).map_err(|e| e.to_string())?;
+                Ok(format!("{} entries extracted", written))
+            }
+            JobStep::ConvertTextures { input, pattern, output } => {
+                let mut converted = 0;
+                for (path, relative) in jobs::matching_files(input, pattern) {
+                    if task.is_cancelled() {
+                        break;
+                    }
+                    task.set_message(relative.display().to_string());
+                    let result = fs::read(&path).map_err(|e| e.into())
+                        .and_then(|data| jobs::texture_png_with_size(&data))
+                        .and_then(|(png, size)| Ok(paths::write_creating_dirs(&output.join(naming.texture(&relative, size)), &png)?));
+                    match result {
+                        Ok(()) => converted += 1,
+                        Err(e) => task.add_error(format!("{}: {}", relative.display(), e)),
+                    }
+                }
+                Ok(format!("{} textures converted", converted))
+            }
+            JobStep::ConvertScenes { input, output } => {
+                let mut converted = 0;
+                for (path, relative) in jobs::matching_files(input, "**") {
+                    if task.is_cancelled() {
+                        break;
+                    }
+                    if !relative.file_name().and_then(|n| n.to_str()).map_or(false, Self::is_scene_file_name) {
+                        continue;
+                    }
+                    task.set_message(relative.display().to_string());
+                    let result = fs::read(&path).map_err(|e| e.to_string())
+                        .and_then(|data| Self::write_scene_json(&data, &output.join(&relative)));
+                    match result {
+                        Ok(warnings) => {
+                            converted += 1;
+                            for warning in warnings {
+                                task.add_error(format!("{} (partial): {}", relative.display(), warning));
+                            }
+                        }
+                        Err(e) => task.add_error(format!("{}: {}", relative.display(), e)),
+                    }
+                }
+                Ok(format!("{} scenes converted", converted))
+            }
+            JobStep::WriteManifest { input, output } => {
+                let count = jobs::write_manifest(input, output).map_err(|e| e.to_string())?;
+                Ok(format!("{} files listed in {}", count, output.display()))
+            }
+        }
+    }
+
+    // Writes `<mirrored path>.json` next to where the source would sit in the output tree
+    fn write_scene_json(data: &[u8], mirrored_path: &Path) -> Result<Vec<String>, String> {
+        let (json, warnings) = SceneFileHandler::scene_to_json(data).map_err(|e| e.to_string())?;
+        let output_path = PathBuf::from(format!("{}.json", mirrored_path.display()));
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&output_path, json).map_err(|e| e.to_string())?;
+        Ok(warnings)
+    }
+
+    // Entries over the memory budget are decompressed straight into their file instead. They aren't
+    // journaled, since that needs the whole entry, so a resumed extraction writes them again.
+    fn stream_entry_to_disk(
+        extract_dir: &Path,
+        name: &str,
+        write: impl FnOnce(&mut fs::File) -> Result<u64, Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let target = paths::archive_entry_path(extract_dir, name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(long_path(parent))?;
+        }
+        if let Err(e) = write(&mut fs::File::create(long_path(&target))?) {
+            // A partial file would look extracted
+            let _ = fs::remove_file(long_path(&target));
+            return Err(e);
+        }
+        println!("Streamed {} to disk, it's over the memory budget", name);
+        Ok(())
+    }
+
+    // Entries written by an earlier, interrupted extraction of the same archive are kept when
+    // they're intact, and the rest are extracted again
+    fn extract_zip_to_temp(&self, zip_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let _timer = ScopedTimer::new("archive", format!("Extract {}", zip_path.display()));
+        // Create a unique temp directory for this zip file
+        let zip_file_name = zip_path.file_stem()
+            .unwrap_or_else(|| std::ffi::OsStr::new("unknown_zip"));
+        
+        let extract_dir = self.temp_dir.join(zip_file_name);
+        let journal_path = self.temp_dir.join(format!("{}.extract.json", zip_file_name.to_string_lossy()));
+        let mut journal = ExtractJournal::open(journal_path, zip_path);
+        
+        // Nothing to resume, so start from a clean directory
+        if journal.is_empty() && extract_dir.exists() {
+            fs::remove_dir_all(long_path(&extract_dir))?;
+        }
+        
+        // Create the directory
+        fs::create_dir_all(long_path(&extract_dir))?;
+        
+        println!("Extracting {} to {}", zip_path.display(), extract_dir.display());
+        let (mut extracted, mut resumed, mut failed) = (0, 0, 0);
+        let mut store = |name: &str, content: Result<Vec<u8>, Box<dyn std::error::Error>>, journal: &mut ExtractJournal| -> std::io::Result<()> {
+            match content {
+                Ok(content) => {
+                    paths::write_creating_dirs(&paths::archive_entry_path(&extract_dir, name), &content)?;
+                    journal.record(name, &content);
+                    extracted += 1;
+                    println!("Extracted: {}", name);
+                }
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("Failed to extract {}: {}", name, e);
+                }
+            }
+            Ok(())
+        };
+        
+        // Extract based on game type
+        if let Some(game_type) = &self.state.selected_game {
+            if matches!(game_type, GameType::DisneyInfinity30) && DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
+                // Use Disney Infinity extraction
+                let entries = DisneyInfinityZipReader::read_zip_contents(zip_path)?;
+                
+                for entry in entries {
+                    if entry.is_directory {
+                        continue;
+                    }
+                    if journal.is_done(&entry.name, &paths::archive_entry_path(&extract_dir, &entry.name)) {
+                        resumed += 1;
+                        continue;
+                    }
+                    match memory_guard::reserve(entry.compressed_size as u64 + entry.uncompressed_size as u64, &entry.name) {
+                        Ok(_reservation) => store(&entry.name, DisneyInfinityZipReader::extract_file(zip_path, &entry), &mut journal)?,
+                        Err(_) => {
+                            let streamed = Self::stream_entry_to_disk(&extract_dir, &entry.name, |file| DisneyInfinityZipReader::extract_file_to(zip_path, &entry, file));
+                            if let Err(e) = streamed {
+                                store(&entry.name, Err(e), &mut journal)?;
+                            }
+                        }
+                    }
+                }
+            } else if matches!(game_type, GameType::Cars3DrivenToWinXB1) {
+                // Use Cars 3 extraction
+                let entries = DrivenToWinZip::read_zip_contents(zip_path)?;
+                let mut file = fs::File::open(zip_path)?;
+                
+                for entry in entries {
+                    let file_name = entry.file_name.clone();
+                    if file_name.ends_with('/') {
+                        continue;
+                    }
+                    if journal.is_done(&file_name, &paths::archive_entry_path(&extract_dir, &file_name)) {
+                        resumed += 1;
+                        continue;
+                    }
+                    match memory_guard::reserve(entry.compressed_size as u64 + entry.uncompressed_size as u64, &file_name) {
+                        Ok(_reservation) => store(&file_name, DrivenToWinZip::extract_zip_file(entry, &mut file), &mut journal)?,
+                        Err(_) => {
+                            let streamed = Self::stream_entry_to_disk(&extract_dir, &file_name, |output| DrivenToWinZip::extract_zip_file_to(&entry, &mut file, output));
+                            if let Err(e) = streamed {
+                                store(&file_name, Err(e), &mut journal)?;
+                            }
+                        }
+                    }
+                }
+            } else {
+                // Use regular zip extraction
+                let file = fs::File::open(zip_path)?;
+                let mut archive = zip::ZipArchive::new(file)?;
+                
+                for i in 0..archive.len() {
+                    let (file_name, size) = {
+                        let file = archive.by_index_raw(i)?;
+                        (file.name().to_string(), file.size())
+                    };
+                    
+                    // Skip directories (they're created automatically)
+                    if file_name.ends_with('/') {
+                        continue;
+                    }
+                    if journal.is_done(&file_name, &paths::archive_entry_path(&extract_dir, &file_name)) {
+                        resumed += 1;
+                        continue;
+                    }
+                    
+                    match memory_guard::reserve(size, &file_name) {
+                        Ok(_reservation) => {
+                            let content = codecs::read_zip_entry(&mut archive, i).map_err(|e| e.into());
+                            store(&file_name, content, &mut journal)?;
+                        }
+                        Err(_) => {
+                            let streamed = Self::stream_entry_to_disk(&extract_dir, &file_name, |output| Ok(std::io::copy(&mut archive.by_index(i)?, output)?));
+                            if let Err(e) = streamed {
+                                store(&file_name, Err(e), &mut journal)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        journal.save();
+        self.viewer_source.add_extracted(&extract_dir, zip_path);
+        
+        println!(
+            "Extraction complete: {} files extracted, {} kept from an earlier run, {} failed",
+            extracted, resumed, failed
+        );
+        Ok(extract_dir)
+    }
+
+    fn dump_decrypted_archive(&mut self, zip_path: &Path, ctx: &egui::Context) {
+        if !DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
+            println!("Not a Disney Infinity encrypted zip, nothing to dump: {}", zip_path.display());
+            return;
+        }
+
+        let default_name = format!(
+            "{}_decrypted.zip",
+            zip_path.file_stem().and_then(|s| s.to_str()).unwrap_or("archive")
+        );
+
+        if let Some(output_path) = rfd::FileDialog::new()
+            .set_title("Save decrypted archive")
+            .set_file_name(&default_name)
+            .add_filter("Zip archive", &["zip"])
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p))
+        {
+            self.run_operation(Operation::DumpDecryptedArchive { archive: zip_path.to_path_buf(), output: output_path }, ctx);
+        }
+    }
+
+    fn write_decrypted_archive(zip_path: &Path, output_path: &Path) -> String {
+        match DisneyInfinityZipReader::dump_decrypted(zip_path, output_path) {
+            Ok(count) => {
+                println!("Wrote decrypted archive with {} entries to {}", count, output_path.display());
+                format!("{} entries written", count)
+            }
+            Err(e) => {
+                eprintln!("Failed to dump decrypted archive: {}", e);
+                format!("Failed: {}", e)
+            }
+        }
+    }
+
+    fn export_raw_entry(&mut self, zip_path: &Path, entry_name: &str, ctx: &egui::Context) {
+        let default_name = format!("{}.raw", entry_name.rsplit('/').next().unwrap_or(entry_name));
+        if let Some(output_path) = rfd::FileDialog::new()
+            .set_title("Export raw entry")
+            .set_file_name(&default_name)
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p))
+        {
+            let operation = Operation::ExportRawEntry { archive: zip_path.to_path_buf(), entry: entry_name.to_string(), output: output_path };
+            self.run_operation(operation, ctx);
+        }
+    }
+
+    fn write_raw_entry(game_type: Option<&GameType>, zip_path: &Path, entry_name: &str, output_path: &Path) -> String {
+        let result = Self::read_raw_entry(game_type, zip_path, entry_name)
+            .and_then(|(data, header)| raw_entry::write(output_path, &data, &header).map(|_| data.len()).map_err(|e| e.into()));
+        match result {
+            Ok(size) => {
+                println!("Exported raw {} ({} bytes) to {}", entry_name, size, output_path.display());
+                format!("{} written with its header", format_size(size as u64))
+            }
+            Err(e) => {
+                eprintln!("Failed to export raw {}: {}", entry_name, e);
+                format!("Failed: {}", e)
+            }
+        }
+    }
+
+    // An entry's bytes as stored, still compressed and for Disney Infinity still encrypted
+    fn read_raw_entry(game_type: Option<&GameType>, zip_path: &Path, entry_name: &str) -> Result<(Vec<u8>, RawEntryHeader), Box<dyn std::error::Error>> {
+        let archive = zip_path.display().to_string();
+        if matches!(game_type, Some(GameType::DisneyInfinity30)) && DisneyInfinityZipReader::is_disney_infinity_zip(zip_path) {
+            let entries = DisneyInfinityZipReader::read_zip_contents(zip_path)?;
+            let entry = entries.into_iter().find(|e| e.name == entry_name).ok_or_else(|| format!("{} isn't in the archive", entry_name))?;
+            let data = DisneyInfinityZipReader::read_raw(zip_path, &entry)?;
+            let archive_name = zip_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let encryption = format!(
+                "AES-128-CTR with the {} key and a zero IV over the first {} bytes",
+                if DisneyInfinityZipReader::uses_psx_key(archive_name) { "PSX" } else { "DI3" },
+                DisneyInfinityZipReader::encrypted_length(&entry)
+            );
+            let header = RawEntryHeader {
+                archive,
+                entry: entry.name.clone(),
+                format: "Disney Infinity 3.0".to_string(),
+                compression_method: entry.compression_method,
+                compression: compression_name(entry.compression_method),
+                compressed_size: entry.compressed_size as u64,
+                uncompressed_size: entry.uncompressed_size as u64,
+                crc32: format!("{:08x}", entry.crc32),
+                local_header_offset: entry.header_offset as u64,
+                data_offset: DisneyInfinityZipReader::data_offset(&entry),
+                flags: None,
+                name_hash: Some(format!("{:08x}", entry.name_hash)),
+                encryption: Some(encryption),
+                extra_field: None,
+            };
+            return Ok((data, header));
+        }
+
+        if matches!(game_type, Some(GameType::Cars3DrivenToWinXB1)) {
+            if let Ok(entries) = DrivenToWinZip::read_zip_contents(zip_path) {
+                let entry = entries.into_iter().find(|e| e.file_name == entry_name).ok_or_else(|| format!("{} isn't in the archive", entry_name))?;
+                let (data_offset, data) = DrivenToWinZip::read_raw(&entry, &mut fs::File::open(zip_path)?)?;
+                let header = RawEntryHeader {
+                    archive,
+                    entry: entry.file_name.clone(),
+                    format: "Cars 3".to_string(),
+                    compression_method: entry.compression_type,
+                    compression: compression_name(entry.compression_type),
+                    compressed_size: entry.compressed_size as u64,
+                    uncompressed_size: entry.uncompressed_size as u64,
+                    crc32: format!("{:08x}", entry.file_crc),
+                    local_header_offset: entry.header_offset as u64,
+                    data_offset,
+                    flags: Some(entry.flags),
+                    name_hash: None,
+                    encryption: None,
+                    extra_field: (!entry.file_extra_field.is_empty()).then(|| raw_entry::hex(&entry.file_extra_field)),
+                };
+                return Ok((data, header));
+            }
+        }
+
+        let file = fs::File::open(zip_path)?;
+        let mut zip = zip::ZipArchive::new(file)?;
+        let index = zip.index_for_name(entry_name).ok_or_else(|| format!("{} isn't in the archive", entry_name))?;
+        let mut file = zip.by_index_raw(index)?;
+        #[allow(deprecated)]
+        let method = file.compression().to_u16();
+        let header = RawEntryHeader {
+            archive,
+            entry: file.name().to_string(),
+            format: "zip".to_string(),
+            compression_method: method,
+            compression: compression_name(method),
+            compressed_size: file.compressed_size(),
+            uncompressed_size: file.size(),
+            crc32: format!("{:08x}", file.crc32()),
+            local_header_offset: file.header_start(),
+            data_offset: file.data_start(),
+            flags: None,
+            name_hash: None,
+            encryption: None,
+            extra_field: file.extra_data().filter(|extra| !extra.is_empty()).map(raw_entry::hex),
+        };
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Ok((data, header))
+    }
+
+    fn scan_assets_folder(&mut self, executable_path: &Path) {
+        // Cancel any ongoing scan
+        *self.scan_cancel.lock().unwrap() = true;
+        if let Some(thread) = self.scan_thread.take() {
+            let _ = thread.join();
+        }
+        
+        // Reset cancel flag
+        *self.scan_cancel.lock().unwrap() = false;
+        
+        self.file_tree.clear();
+        self.tree_rows_dirty = true;
+        self.selected_file = None;
+        self.selected_files.clear();
+        self.selection_anchor = None;
+        self.model_viewer.clear_model();
+        self.mtb_viewer.clear();
+        self.scene_viewer.clear();
+        self.show_scene_viewer = false;
+
+        // Get the directory containing the executable
+        if let Some(parent_dir) = executable_path.parent() {
+            let mut roots = self.state.selected_game.as_ref()
+                .map(|game_type| game_type.asset_roots(parent_dir))
+                .unwrap_or_default();
+            if roots.is_empty() {
+                println!("Assets folder not found under: {}", parent_dir.display());
+                // Fall back to scanning the parent directory
+                roots.push(parent_dir.to_path_buf());
+            }
+            self.asset_roots = roots.clone();
+
+            // Named by their path from the install, e.g. "data1/assets" or, beside it, "data2"
+            let labelled: Vec<(PathBuf, String)> = roots
+                .iter()
+                .map(|root| {
+                    let relative = root.strip_prefix(parent_dir)
+                        .or_else(|_| root.strip_prefix(parent_dir.parent().unwrap_or(parent_dir)))
+                        .unwrap_or(root);
+                    (root.clone(), relative.display().to_string())
+                })
+                .collect();
+            
+            self.start_scan(labelled);
+        } else {
+            println!("Could not get parent directory of executable: {}", executable_path.display());
+        }
+    }
+
+    // A single root fills the tree directly; with several, each becomes a top-level folder
+    // named by its label. Wakes the UI when the scan ends, since nothing else would repaint an
+    // idle window.
+    fn spawn_scan(&self, roots: Vec<ScanRoot>, use_cache: bool) -> thread::JoinHandle<Vec<FileEntry>> {
+        let cancel_flag = self.scan_cancel.clone();
+        let ctx = self.egui_ctx.clone();
+        thread::spawn(move || {
+            let single = roots.len() == 1;
+            let mut entries = Vec::new();
+            for root in roots {
+                // Each root keeps its own cache entry; ignores are applied after so they can
+                // change without a full rescan
+                let mut children = Self::scan_root_with_cache(root.path.clone(), use_cache, cancel_flag.clone());
+                if !root.ignore.is_empty() {
+                    Self::prune_ignored(&mut children, &root.path, &root.ignore);
+                }
+                if single {
+                    entries = children;
+                    break;
+                }
+                let mut root_entry = FileEntry::new(root.path.clone(), true);
+                root_entry.display_name = root.label;
+                root_entry.modified = fs::metadata(long_path(&root.path)).map(|m| modified_millis(&m)).unwrap_or(0);
+                root_entry.children = children;
+                entries.push(root_entry);
+            }
+            if let Some(ctx) = ctx {
+                ctx.request_repaint();
+            }
+            entries
+        })
+    }
+
+    fn prune_ignored(entries: &mut Vec<FileEntry>, root: &Path, ignore: &[String]) {
+        entries.retain(|entry| {
+            let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path).to_string_lossy().into_owned();
+            !ignore.iter().any(|pattern| jobs::glob_match(pattern, &relative))
+        });
+        for entry in entries.iter_mut() {
+            Self::prune_ignored(&mut entry.children, root, ignore);
+        }
+    }
+
+    fn scan_dtw_folder(&mut self, executable_path: &Path) {
+        // Get the directory containing the executable
+        if let Some(parent_dir) = executable_path.parent() {
+            self.scan_folder(parent_dir);
+        } else {
+            println!("Could not get parent directory of executable: {}", executable_path.display());
+        }
+    }
+
+    // Scans `dir` as a single root, for Cars 3 installs and bare asset dumps
+    fn scan_folder(&mut self, dir: &Path) {
+        // Cancel any ongoing scan
+        *self.scan_cancel.lock().unwrap() = true;
+        if let Some(thread) = self.scan_thread.take() {
+            let _ = thread.join();
+        }
+        
+        // Reset cancel flag
+        *self.scan_cancel.lock().unwrap() = false;
+        
+        self.file_tree.clear();
+        self.tree_rows_dirty = true;
+        self.selected_file = None;
+        self.selected_files.clear();
+        self.selection_anchor = None;
+        self.model_viewer.clear_model();
+        self.mtb_viewer.clear();
+        self.scene_viewer.clear();
+        self.show_scene_viewer = false;
+
+        self.asset_roots = vec![dir.to_path_buf()];
+        self.start_scan(vec![(dir.to_path_buf(), paths::display_name(dir))]);
+    }
+
+    // Scans the game's folders along with the selected game's extra roots
+    fn start_scan(&mut self, game_roots: Vec<(PathBuf, String)>) {
+        let extra_roots = self.state.selected_game.as_ref()
+            .and_then(|g| self.state.game_configs.get(g))
+            .map(|config| config.extra_roots.clone())
+            .unwrap_or_default();
+        let mut roots: Vec<ScanRoot> = game_roots
+            .into_iter()
+            .map(|(path, label)| ScanRoot { path, label, ignore: Vec::new() })
+            .collect();
+        for extra in extra_roots {
+            if !extra.path.is_dir() {
+                println!("Extra folder not found: {}", extra.path.display());
+                continue;
+            }
+            roots.push(ScanRoot {
+                label: extra.label(),
+                ignore: extra.ignore.split_whitespace().map(str::to_string).collect(),
+                path: extra.path,
+            });
+        }
+
+        println!("Starting threaded scan of: {}", roots.iter().map(|root| root.path.display().to_string()).collect::<Vec<_>>().join(", "));
+        let current_path = roots[0].path.clone();
+        let use_cache = !std::mem::take(&mut self.force_full_rescan);
+        
+        // Start threaded scan
+        self.scan_thread = Some(self.spawn_scan(roots, use_cache));
+        
+        // Show progress immediately
+        self.scan_progress = Some(ScanProgress {
+            current_path,
+            total_files: 0, // We don't know the total yet
+            processed_files: 0,
+            start_time: Instant::now(),
+        });
+    }
+
+    fn full_rescan(&mut self) {
+        if let Some(game_type) = self.state.selected_game.clone() {
+            self.force_full_rescan = true;
+            if !self.start_game_scan(&game_type) {
+                self.force_full_rescan = false;
+            }
+        }
+    }
+
+    fn check_scan_completion(&mut self) {
+        if let Some(thread) = &self.scan_thread {
+            if thread.is_finished() {
+                if let Some(thread) = self.scan_thread.take() {
+                    match thread.join() {
+                        Ok(result) => {
+                            self.file_tree = result;
+                            self.tree_rows_dirty = true;
+                            self.scan_progress = None;
+                            println!("Scan completed with {} root entries", self.file_tree.len());
+                            
+                            // Log total file count
+                            let total_files = self.count_files(&self.file_tree);
+                            println!("Total files and directories found: {}", total_files);
+                        }
+                        Err(e) => {
+                            eprintln!("Scan thread panicked: {:?}", e);
+                            self.scan_progress = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn count_files(&self, entries: &[FileEntry]) -> usize {
+        let mut count = entries.len();
+        for entry in entries {
+            if entry.is_directory {
+                count += self.count_files(&entry.children);
+            }
+        }
+        count
+    }
+
+    fn handle_model_file_selection(&mut self, file_path: &PathBuf, ctx: &egui::Context) {
+        self.open_file_as(file_path, None, ctx);
+    }
+
+    // Routes by the file's magic bytes, falling back to its extension. `kind` overrides both, for
+    // the "Open as" menu.
+    fn open_file_as(&mut self, file_path: &PathBuf, kind: Option<FileKind>, ctx: &egui::Context) {
+        // Loading anything replaces the open scene
+        if self.prompt_if_unsaved(PendingAction::OpenFile(file_path.clone(), kind)) {
+            // Callers select the new file first; until the prompt is answered the open one stays
+            // selected, so the prompt and Save... name the right file and Cancel leaves it as it was
+            self.selected_file = self.opened_file.clone();
+            self.selected_files = self.opened_file.iter().cloned().collect();
+            self.selection_anchor = self.opened_file.clone();
+            return;
+        }
+
+        println!("File selected: {}", file_path.display());
+        self.opened_file = Some(file_path.clone());
+        let associations = self.state.selected_game.as_ref()
+            .and_then(|g| self.state.game_configs.get(g))
+            .map(|c| c.file_associations.as_slice())
+            .unwrap_or_default();
+        let kind = kind.or_else(|| sniff::detect(file_path, associations));
+        self.selected_kind = kind;
+        // Text and hex views show the start of the file, as does the split view's hex pane
+        self.file_preview = match kind {
+            Some(FileKind::Text | FileKind::Hex) => read_preview(file_path),
+            _ if self.state.panels.split => read_preview(file_path),
+            _ => None,
+        };
+        // A template whose magic matches is applied on its own; otherwise the hex view starts plain
+        self.hex_template = self.file_preview.as_ref().and_then(|preview| {
+            self.binary_templates.iter().position(|t| !t.magic.is_empty() && preview.starts_with(&t.magic))
+        });
+        self.apply_binary_template();
+        
+        // Clear scene viewer when non-scene files are selected
+        if kind != Some(FileKind::Scene) {
+            self.show_scene_viewer = false;
+            self.scene_viewer.clear();
+        } else {
+            // For scene files, automatically try to find and load corresponding .bent file
+            let bent_path = SceneFileHandler::find_corresponding_bent_file(file_path);
+            if let Some(bent_path) = bent_path {
+                println!("Found corresponding .bent file: {}", bent_path.display());
+                let loaded = self.viewer_source.read(&bent_path).map_err(|e| e.to_string())
+                    .and_then(|data| self.scene_viewer.load_bent_file_reader(&mut std::io::Cursor::new(data)).map_err(|e| e.to_string()));
+                if let Err(e) = loaded {
+                    println!("Failed to load .bent file: {}", e);
+                } else {
+                    println!("Successfully loaded animation data from .bent file");
+                }
+            } else {
+                println!("No corresponding .bent file found for: {}", file_path.display());
+            }
+            self.show_scene_viewer = true;
+        }
+        
+        match kind {
+            // Handle scene files (OCT files)
+            Some(FileKind::Scene) => {
+                println!("Loading scene file: {}", file_path.display());
+                match self.viewer_source.read(file_path) {
+                    Ok(data) => {
+                        let timer = ScopedTimer::new("parse", format!("Scene {}", file_path.display()));
+                        let loaded = self.scene_viewer.load_scene_file(&mut std::io::Cursor::new(data));
+                        drop(timer);
+                        self.figure_editor.reset();
+                        self.vehicle_stats.reset();
+                        if let Err(e) = loaded {
+                            eprintln!("Failed to load scene file: {}", e);
+                        } else {
+                            // Extract textures for supported games
+                            if let Some(game_type) = &self.state.selected_game {
+                                if let Err(e) = self.scene_viewer.extract_textures(&game_type.scene_game_type()) {
+                                    eprintln!("Failed to extract textures: {}", e);
+                                }
+                            }
+                            self.show_scene_viewer = true;
+                            println!("Scene file loaded successfully");
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to open scene file: {}", e);
+                    }
+                }
+                return;
+            }
+                
+            // Handle model files
+            Some(FileKind::ModelBuffer) => {
+                // Find the corresponding file; anything not named .vbuf is taken as the IBUF
+                let is_vbuf = file_path.extension().map_or(false, |e| e.eq_ignore_ascii_case("vbuf"));
+                let base_name = file_path.with_extension("");
+                let other_extension = if is_vbuf { "ibuf" } else { "vbuf" };
+                let other_file = base_name.with_extension(other_extension);
+                
+                println!("Looking for corresponding file: {}", other_file.display());
+                
+                if other_file.exists() {
+                    let (ibuf_path, vbuf_path) = if is_vbuf {
+                        (other_file, file_path.clone())
+                    } else {
+                        (file_path.clone(), other_file)
+                    };
+                    
+                    println!("Loading model from:\n  IBUF: {}\n  VBUF: {}", 
+                        ibuf_path.display(), vbuf_path.display());
+                    
+                    self.model_viewer.layout = self.buffer_layout();
+                    let loaded = match (self.viewer_source.read(&ibuf_path), self.viewer_source.read(&vbuf_path)) {
+                        (Ok(ibuf), Ok(vbuf)) => self.model_viewer.load_model_from_bytes(&ibuf_path, &ibuf, &vbuf_path, &vbuf),
+                        (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+                    };
+                    match loaded {
+                        Ok(_) => {
+                            println!("Successfully loaded model from {} and {}", 
+                                ibuf_path.display(), vbuf_path.display());
+                            self.model_files = Some((ibuf_path, vbuf_path));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to load model: {}", e);
+                            self.model_files = None;
+                        }
+                    }
+                } else {
+                    println!("Corresponding {} file not found: {}", other_extension, other_file.display());
+                    self.model_viewer.clear_model();
+                }
+                return;
+            }
+            
+            // Handle MTB and TBODY files
+            Some(FileKind::Material) => {
+                println!("Loading MTB file: {}", file_path.display());
+                self.configure_texture_search(file_path);
+                let _timer = ScopedTimer::new("parse", format!("MTB {}", file_path.display()));
+                let loaded = self.viewer_source.read(file_path)
+                    .and_then(|data| self.mtb_viewer.load_mtb_bytes(file_path, &data));
+                if let Err(e) = loaded {
+                    eprintln!("Failed to load MTB file: {}", e);
+                }
+                // Bindings into archives need the archive readers, which live here
+                for (tbody_filename, path) in self.mtb_viewer.pending_bindings() {
+                    self.bind_mtb_texture(&tbody_filename, &path, ctx);
+                }
+                return;
+            }
+            Some(FileKind::Texture) => {
+                println!("Loading TBODY file: {}", file_path.display());
+                self.mtb_viewer.load_tbody_file(file_path);
+                return;
+            }
+            _ => {}
+        }
+        
+        // Clear both viewers if it's not a supported file type
+        self.model_viewer.clear_model();
+        self.mtb_viewer.clear();
+    }
+
+    // IBUF/VBUF conventions of the selected game
+    fn buffer_layout(&self) -> ViewModel::BufferLayout {
+        self.state.selected_game.as_ref()
+            .map(|g| ViewModel::BufferLayout::for_game(&g.scene_game_type()))
+            .unwrap_or_default()
+    }
+
+    // Texture lookup for an MTB at `file_path`, from the selected game's config
+    fn configure_texture_search(&mut self, file_path: &Path) {
+        let config = self.state.selected_game.as_ref().and_then(|g| self.state.game_configs.get(g));
+        self.mtb_viewer.search_paths = config.map(|c| c.texture_search_paths.clone()).unwrap_or_default();
+        self.mtb_viewer.params = self.state.selected_game.as_ref()
+            .map(|g| MtbParams::for_game(&g.scene_game_type()))
+            .unwrap_or_default();
+        // Files extracted from an archive live under temp/<archive name>/
+        self.mtb_viewer.archive_root = file_path.strip_prefix(&self.temp_dir).ok()
+            .and_then(|relative| relative.components().next())
+            .map(|first| self.temp_dir.join(first));
+        self.mtb_viewer.bindings = config.map(|c| c.texture_bindings.clone()).unwrap_or_default();
+    }
+
+    fn show_file_tree_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        // Check if scan is complete
+        self.check_scan_completion();
+
+        // Show progress if scanning
+        if let Some(progress) = &self.scan_progress {
+            ui.heading("Scanning Files...");
+            ui.label(format!("Scanning: {}", progress.current_path.display()));
+            ui.label(format!("Elapsed: {:?}", progress.start_time.elapsed()));
+            ui.add(egui::Spinner::new().size(32.0));
+            ui.label("This may take a while for large directories...");
+            return;
+        }
+
+        if self.file_tree.is_empty() {
+            ui.label("No files found");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.state.tree_show_details, "Size / date").changed() {
+                self.save_state();
+            }
+            let previous_sort = self.state.tree_sort;
+            egui::ComboBox::from_id_source("tree_sort")
+                .selected_text(format!("Sort: {}", self.state.tree_sort.as_str()))
+                .show_ui(ui, |ui| {
+                    for sort in [TreeSort::Name, TreeSort::Size, TreeSort::Modified] {
+                        ui.selectable_value(&mut self.state.tree_sort, sort, sort.as_str());
+                    }
+                });
+            if self.state.tree_sort != previous_sort {
+                self.tree_rows_dirty = true;
+                self.save_state();
+            }
+        });
+
+        if self.tree_rows_dirty {
+            self.rebuild_tree_rows();
+        }
+
+        // Only the rows inside the viewport are laid out each frame
+        let row_height = ui.spacing().interact_size.y;
+        let total_rows = self.tree_rows.len();
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false; 2]);
+        if let Some(target) = self.tree_scroll_target.take() {
+            // Leave a few rows of context above the folder
+            if let Some(index) = self.tree_rows.iter().position(|row| row.path == target) {
+                let spacing = ui.spacing().item_spacing.y;
+                scroll_area = scroll_area.vertical_scroll_offset(index.saturating_sub(3) as f32 * (row_height + spacing));
+            }
+        }
+        scroll_area
+            .show_rows(ui, row_height, total_rows, |ui, row_range| {
+                for row_index in row_range {
+                    let row = self.tree_rows[row_index].clone();
+                    self.show_tree_row(ui, &row, ctx);
+                }
+            });
+
+        if let Some(action) = self.pending_tree_action.take() {
+            self.apply_tree_action(action);
+        }
+    }
+
+    fn rebuild_tree_rows(&mut self) {
+        let zip_browsing = self.state.selected_game
+            .as_ref()
+            .map_or(false, |game_type| game_type.supports_zip_browsing());
+
+        let mut rows = Vec::new();
+        Self::flatten_tree(&self.file_tree, &self.expanded_folders, zip_browsing, self.state.tree_sort, &mut Vec::new(), 0, &mut rows);
+        self.tree_rows = rows;
+        self.tree_rows_dirty = false;
+        self.tree_generation += 1;
+        self.breadcrumbs = None;
+    }
+
+    fn flatten_tree(
+        entries: &[FileEntry],
+        expanded: &std::collections::HashSet<PathBuf>,
+        zip_browsing: bool,
+        sort: TreeSort,
+        index_path: &mut Vec<usize>,
+        depth: usize,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        // Index paths point into the unsorted tree, only the display order changes.
+        // Folders stay above files like the scan orders them.
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        match sort {
+            TreeSort::Name => {}
+            TreeSort::Size => order.sort_by_key(|&i| (!entries[i].is_directory, std::cmp::Reverse(Self::entry_size(&entries[i])))),
+            TreeSort::Modified => order.sort_by_key(|&i| (!entries[i].is_directory, std::cmp::Reverse(entries[i].modified))),
+        }
+
+        for index in order {
+            let entry = &entries[index];
+            index_path.push(index);
+
+            let expandable = entry.is_directory || (entry.is_zip && zip_browsing);
+            rows.push(TreeRow {
+                index_path: index_path.clone(),
+                path: entry.path.clone(),
+                display_name: entry.display_name.clone(),
+                depth,
+                expandable,
+                is_zip: entry.is_zip,
+                zip_load_error: entry.zip_load_error.clone(),
+                size: Self::entry_size(entry),
+                modified: entry.modified,
+            });
+
+            if expandable && expanded.contains(&entry.path) {
+                Self::flatten_tree(&entry.children, expanded, zip_browsing, sort, index_path, depth + 1, rows);
+            }
+
+            index_path.pop();
+        }
+    }
+
+    // Folder sizes are the sum of everything scanned below them
+    fn entry_size(entry: &FileEntry) -> u64 {
+        if entry.is_directory {
+            entry.children.iter().map(Self::entry_size).sum()
+        } else {
+            entry.size
+        }
+    }
+
+    fn find_entry<'a>(entries: &'a [FileEntry], path: &Path) -> Option<&'a FileEntry> {
+        for entry in entries {
+            if entry.path == path {
+                return Some(entry);
+            }
+            // Archive contents are extracted to the temp folder, so they aren't below the archive's path
+            if path.starts_with(&entry.path) || entry.is_zip {
+                if let Some(found) = Self::find_entry(&entry.children, path) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    // Folders, archives and the file itself along the way to `path`. Extracted archive contents
+    // live in the temp folder, so archives are searched even when the path isn't below them.
+    fn tree_ancestors(entries: &[FileEntry], path: &Path, chain: &mut Vec<(PathBuf, String)>) -> bool {
+        for entry in entries {
+            if entry.path == path {
+                chain.push((entry.path.clone(), entry.display_name.clone()));
+                return true;
+            }
+            if path.starts_with(&entry.path) || entry.is_zip {
+                chain.push((entry.path.clone(), entry.display_name.clone()));
+                if Self::tree_ancestors(&entry.children, path, chain) {
+                    return true;
+                }
+                chain.pop();
+            }
+        }
+        false
+    }
+
+    fn show_breadcrumbs(&mut self, ui: &mut egui::Ui) {
+        let Some(selected) = self.selected_file.clone() else {
+            return;
+        };
+        if self.breadcrumbs.as_ref().map_or(true, |(path, _, _)| *path != selected) {
+            let mut chain = Vec::new();
+            Self::tree_ancestors(&self.file_tree, &selected, &mut chain);
+            let archive = chain[..chain.len().saturating_sub(1)].iter()
+                .rposition(|(path, _)| Self::find_entry(&self.file_tree, path).map_or(false, |e| e.is_zip));
+            self.breadcrumbs = Some((selected.clone(), chain, archive));
+        }
+        let (crumbs, archive) = self.breadcrumbs.as_ref()
+            .map(|(_, chain, archive)| (chain.clone(), *archive))
+            .unwrap_or_default();
+
+        let mut reveal = None;
+        ui.horizontal_wrapped(|ui| {
+            if let Some(index) = archive {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Archive properties").clicked() {
+                        self.archive_properties = Some(crumbs[index].0.clone());
+                    }
+                    if ui.small_button("Show archive in tree").clicked() {
+                        reveal = Some(index);
+                    }
+                    ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                        Self::show_breadcrumb_links(ui, &selected, &crumbs, &mut reveal);
+                    });
+                });
+            } else {
+                Self::show_breadcrumb_links(ui, &selected, &crumbs, &mut reveal);
+            }
+        });
+        ui.separator();
+
+        if let Some(index) = reveal {
+            self.reveal_in_tree(&crumbs[..=index]);
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn show_breadcrumb_links(ui: &mut egui::Ui, selected: &Path, crumbs: &[(PathBuf, String)], reveal: &mut Option<usize>) {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 2.0;
+            if crumbs.is_empty() {
+                // Opened from outside the scanned tree
+                ui.weak(selected.display().to_string());
+                return;
+            }
+            let last = crumbs.len() - 1;
+            for (index, (path, name)) in crumbs.iter().enumerate() {
+                if index == last {
+                    ui.strong(name).on_hover_text(path.display().to_string());
+                } else {
+                    if ui.link(name).on_hover_text("Show in the file tree").clicked() {
+                        *reveal = Some(index);
+                    }
+                    ui.weak(">");
+                }
+            }
+        });
+    }
+
+    fn show_archive_properties(&mut self, ctx: &egui::Context) {
+        let Some(archive_path) = self.archive_properties.clone() else {
+            return;
+        };
+        let Some(entry) = Self::find_entry(&self.file_tree, &archive_path) else {
+            self.archive_properties = None;
+            return;
+        };
+        let (size, modified, files) = (entry.size, entry.modified, self.count_files(&entry.children));
+        let (loaded, load_error) = (entry.zip_contents_loaded, entry.zip_load_error.clone());
+        let extract_dir = archive_path.file_stem().map(|stem| self.temp_dir.join(stem));
+
+        let mut open = true;
+        let mut reveal = false;
+        egui::Window::new(format!("{} properties", entry.display_name))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("archive_properties_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Location:");
+                    ui.label(archive_path.display().to_string());
+                    ui.end_row();
+                    ui.label("Size:");
+                    ui.label(format!("{} ({} bytes)", format_size(size), size));
+                    ui.end_row();
+                    if modified != 0 {
+                        ui.label("Modified:");
+                        ui.label(format!("{} UTC", format_timestamp(modified)));
+                        ui.end_row();
+                    }
+                    ui.label("Entries:");
+                    ui.label(if loaded { files.to_string() } else { "Not opened yet".to_string() });
+                    ui.end_row();
+                    if let (true, Some(dir)) = (loaded, &extract_dir) {
+                        ui.label("Extracted to:");
+                        ui.label(dir.display().to_string());
+                        ui.end_row();
+                    }
+                    if let Some(error) = &load_error {
+                        ui.label("Error:");
+                        ui.colored_label(egui::Color32::RED, error);
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Show in tree").clicked() {
+                        reveal = true;
+                    }
+                    if ui.button("Open containing folder").clicked() {
+                        if let Some(parent) = archive_path.parent() {
+                            if let Err(e) = open_in_default_app(parent) {
+                                eprintln!("Failed to open {}: {}", parent.display(), e);
+                            }
+                        }
+                    }
+                });
+                ui.separator();
+                self.show_archive_entries(ui, &archive_path);
+            });
+
+        if reveal {
+            let mut chain = Vec::new();
+            if Self::tree_ancestors(&self.file_tree, &archive_path, &mut chain) {
+                self.reveal_in_tree(&chain);
+            }
+        }
+        if !open {
+            self.archive_properties = None;
+            self.archive_listing = None;
+        }
+    }
+
+    // Sortable table of the archive's entries with their compression
+    fn show_archive_entries(&mut self, ui: &mut egui::Ui, archive_path: &Path) {
+        if self.archive_listing.as_ref().map_or(true, |(path, _)| path != archive_path) {
+            let listing = Self::list_archive_entries(self.state.selected_game.as_ref(), archive_path)
+                .map(|mut entries| {
+                    ArchiveEntryInfo::sort(&mut entries, self.archive_sort);
+                    entries
+                })
+                .map_err(|e| e.to_string());
+            self.archive_listing = Some((archive_path.to_path_buf(), listing));
+        }
+        let Some((_, listing)) = &mut self.archive_listing else {
+            return;
+        };
+        let entries = match listing {
+            Ok(entries) => entries,
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Couldn't list entries: {}", e));
+                return;
+            }
+        };
+
+        let compressed: u64 = entries.iter().map(|e| e.compressed_size).sum();
+        let size: u64 = entries.iter().map(|e| e.size).sum();
+        ui.label(format!(
+            "{} files, {} compressed from {} ({:.0}%)",
+            entries.len(),
+            format_size(compressed),
+            format_size(size),
+            if size == 0 { 100.0 } else { compressed as f64 * 100.0 / size as f64 }
+        ));
+
+        let mut sort = self.archive_sort;
+        let mut export_raw = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::vertical().id_source("archive_entries").max_height(400.0).show_rows(ui, row_height, entries.len() + 1, |ui, range| {
+            egui::Grid::new("archive_entries_grid").striped(true).num_columns(5).show(ui, |ui| {
+                for row in range {
+                    // Header, clicking a column sorts by it and clicking it again flips the order
+                    if row == 0 {
+                        for (column, label) in [
+                            (ArchiveSort::Name, "Name"),
+                            (ArchiveSort::Method, "Method"),
+                            (ArchiveSort::Compressed, "Compressed"),
+                            (ArchiveSort::Size, "Size"),
+                            (ArchiveSort::Ratio, "Ratio"),
+                        ] {
+                            let arrow = match sort {
+                                (current, true) if current == column => " ⏶",
+                                (current, false) if current == column => " ⏷",
+                                _ => "",
+                            };
+                            if ui.selectable_label(sort.0 == column, format!("{}{}", label, arrow)).clicked() {
+                                sort = (column, sort.0 != column || !sort.1);
+                            }
+                        }
+                        ui.end_row();
+                        continue;
+                    }
+                    let entry = &entries[row - 1];
+                    ui.add(egui::Label::new(&entry.name).sense(egui::Sense::click())).context_menu(|ui| {
+                        if ui.button("Export raw entry...").on_hover_text("The bytes as stored, still compressed/encrypted, with a .json of the header").clicked() {
+                            export_raw = Some(entry.name.clone());
+                            ui.close_menu();
+                        }
+                    });
+                    ui.label(&entry.method);
+                    ui.label(format_size(entry.compressed_size));
+                    ui.label(format_size(entry.size));
+                    ui.label(format!("{:.0}%", entry.ratio() * 100.0));
+                    ui.end_row();
+                }
+            });
+        });
+
+        if sort != self.archive_sort {
+            self.archive_sort = sort;
+            ArchiveEntryInfo::sort(entries, sort);
+        }
+        if let Some(entry_name) = export_raw {
+            self.export_raw_entry(archive_path, &entry_name, &ui.ctx().clone());
+        }
+    }
+
+    // Reveals what a viewer picked and tells the viewers what the tree has selected. The picked file
+    // is only selected, not opened, so the viewer showing it stays up.
+    fn sync_selection(&mut self) {
+        if let Some(path) = self.selection_bus.take_published() {
+            let mut chain = Vec::new();
+            if Self::tree_ancestors(&self.file_tree, &path, &mut chain) {
+                self.selected_files.clear();
+                self.selected_files.insert(path.clone());
+                self.selection_anchor = Some(path);
+                self.reveal_in_tree(&chain);
+            } else {
+                println!("{} isn't in the file tree", path.display());
+            }
+        }
+        let current = if self.selected_files.len() == 1 { self.selected_files.iter().next() } else { None };
+        self.selection_bus.set_current(current.map(PathBuf::as_path));
+    }
+
+    // Expands every folder in `chain` and scrolls the tree to the last one
+    fn reveal_in_tree(&mut self, chain: &[(PathBuf, String)]) {
+        for (path, _) in chain {
+            self.expanded_folders.insert(path.clone());
+        }
+        self.tree_rows_dirty = true;
+        self.tree_scroll_target = chain.last().map(|(path, _)| path.clone());
+    }
+
+    fn show_row_details(&self, ui: &mut egui::Ui, row: &TreeRow) {
+        if !self.state.tree_show_details {
+            return;
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.weak(format_timestamp(row.modified));
+            if row.size > 0 || !row.expandable {
+                ui.weak(format_size(row.size));
+            }
+        });
+    }
+
+    fn entry_at_mut(&mut self, index_path: &[usize]) -> Option<&mut FileEntry> {
+        let (first, rest) = index_path.split_first()?;
+        let mut entry = self.file_tree.get_mut(*first)?;
+        for index in rest {
+            entry = entry.children.get_mut(*index)?;
+        }
+        Some(entry)
+    }
+
+    fn show_tree_row(&mut self, ui: &mut egui::Ui, row: &TreeRow, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.add_space(row.depth as f32 * ui.spacing().indent);
+
+            if row.expandable {
+                let is_open = self.expanded_folders.contains(&row.path);
+                let icon_size = egui::Vec2::splat(ui.spacing().icon_width);
+                let (_, icon_response) = ui.allocate_exact_size(icon_size, egui::Sense::click());
+                egui::collapsing_header::paint_default_icon(ui, if is_open { 1.0 } else { 0.0 }, &icon_response);
+
+                if row.is_zip {
+                    if let Some(zip_icon) = self.file_icons.get("zip") {
+                        egui::Image::new(zip_icon)
+                            .max_size(egui::Vec2::splat(16.0))
+                            .ui(ui);
+                    }
+                }
+
+                let response = ui.selectable_label(false, &row.display_name);
+                if response.clicked() || icon_response.clicked() {
+                    self.pending_tree_action = Some(TreeAction::Toggle(row.index_path.clone()));
+                }
+
+                if let Some(error) = &row.zip_load_error {
+                    ui.colored_label(egui::Color32::RED, "Failed to extract ZIP")
+                        .on_hover_text(error);
+                }
+
+                // Encrypted Disney Infinity archives can be dumped as a plain zip
+                if row.is_zip && matches!(self.state.selected_game, Some(GameType::DisneyInfinity30)) {
+                    response.context_menu(|ui| {
+                        if ui.button("Dump decrypted archive...").clicked() {
+                            self.dump_decrypted_archive(&row.path, ctx);
+                            ui.close_menu();
+                        }
+                    });
+                }
+                self.show_row_details(ui, row);
+                return;
+            }
+
+            // Show icon if available
+            if row.is_zip {
+                if let Some(zip_icon) = self.file_icons.get("zip") {
+                    egui::Image::new(zip_icon)
+                        .max_size(egui::Vec2::splat(16.0))
+                        .ui(ui);
+                }
+            } else if let Some(icon) = self.get_file_icon(&row.path) {
+                egui::Image::new(icon)
+                    .max_size(egui::Vec2::splat(16.0))
+                    .ui(ui);
+            } else {
+                // Placeholder for files without icons
+                ui.add_space(18.0);
+            }
+
+            // Check if this file is from a ZIP extraction (in temp directory)
+            let is_extracted_from_zip = row.path.starts_with(&self.temp_dir);
+
+            // Files inside ZIPs or extracted from ZIPs get green text (only for games that support ZIP browsing)
+            let should_be_green = if let Some(game_type) = &self.state.selected_game {
+                game_type.supports_zip_browsing() &&
+                (row.path.components().any(|c| {
+                    if let std::path::Component::Normal(name) = c {
+                        if let Some(name_str) = name.to_str() {
+                            return name_str.to_lowercase().ends_with(".zip");
+                        }
+                    }
+                    false
+                }) || is_extracted_from_zip)
+            } else {
+                false
+            };
+
+            let is_selected = self.selected_files.contains(&row.path);
+            let response = if should_be_green {
+                ui.selectable_label(is_selected, egui::RichText::new(&row.display_name).color(egui::Color32::GREEN))
+            } else {
+                ui.selectable_label(is_selected, &row.display_name)
+            };
+
+            if response.clicked() {
+                self.handle_tree_click(&row.path, ctx);
+            }
+            // Dragged onto the A/B slots
+            let response = response.interact(egui::Sense::drag());
+            response.dnd_set_drag_payload(row.path.clone());
+            if response.dragged() {
+                egui::show_tooltip_at_pointer(ctx, egui::Id::new("tree_drag"), |ui| ui.label(&row.display_name));
+            }
+            response.context_menu(|ui| {
+                self.show_selection_context_menu(ui, &row.path);
+            });
+            if let Some(tags) = self.tags.tags(&row.path) {
+                ui.weak(tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "));
+            }
+            self.show_row_details(ui, row);
+        });
+    }
+
+    fn load_zip_children(&mut self, index_path: &[usize]) {
+        let Some(zip_path) = self.entry_at_mut(index_path).map(|entry| entry.path.clone()) else {
+            return;
+        };
+
+        // Extract ZIP to temp directory and scan it
+        let result = self.extract_zip_to_temp(&zip_path).map(|extract_dir| {
+            let cancel_flag = Arc::new(Mutex::new(false));
+            Self::scan_directory_threaded(extract_dir, cancel_flag)
+        });
+
+        if let Some(entry) = self.entry_at_mut(index_path) {
+            match result {
+                Ok(extracted_entries) => {
+                    // Add extracted entries as children
+                    for mut extracted_entry in extracted_entries {
+                        // Mark these as extracted files (not ZIPs)
+                        extracted_entry.is_zip = false;
+                        entry.children.push(extracted_entry);
+                    }
+
+                    entry.zip_contents_loaded = true;
+                    entry.zip_load_error = None;
+                    println!("ZIP contents loaded and extracted to temp directory");
+                }
+                Err(e) => {
+                    eprintln!("Failed to extract ZIP {}: {}", zip_path.display(), e);
+                    entry.zip_load_error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    fn handle_tree_click(&mut self, path: &PathBuf, ctx: &egui::Context) {
+        let modifiers = ctx.input(|i| i.modifiers);
+
+        if modifiers.shift && self.selection_anchor.is_some() {
+            // Range needs the full row order, which is only known once the tree is drawn
+            self.pending_tree_action = Some(TreeAction::RangeSelect(path.clone()));
+        } else if modifiers.command {
+            if !self.selected_files.remove(path) {
+                self.selected_files.insert(path.clone());
+            }
+            self.selection_anchor = Some(path.clone());
+        } else {
+            self.selected_files.clear();
+            self.selected_files.insert(path.clone());
+            self.selection_anchor = Some(path.clone());
+            self.selected_file = Some(path.clone());
+            self.handle_model_file_selection(path, ctx);
+        }
+    }
+
+    fn apply_tree_action(&mut self, action: TreeAction) {
+        match action {
+            TreeAction::Toggle(index_path) => {
+                let Some(entry) = self.entry_at_mut(&index_path) else {
+                    return;
+                };
+                let path = entry.path.clone();
+                let needs_zip_load = entry.is_zip && !entry.zip_contents_loaded;
+
+                if !self.expanded_folders.remove(&path) {
+                    self.expanded_folders.insert(path);
+                    if needs_zip_load {
+                        self.load_zip_children(&index_path);
+                    }
+                }
+                self.tree_rows_dirty = true;
+            }
+            TreeAction::RangeSelect(target) => {
+                let file_rows: Vec<&PathBuf> = self.tree_rows.iter()
+                    .filter(|row| !row.expandable)
+                    .map(|row| &row.path)
+                    .collect();
+                let anchor_index = self.selection_anchor.as_ref()
+                    .and_then(|anchor| file_rows.iter().position(|p| *p == anchor));
+                let target_index = file_rows.iter().position(|p| **p == target);
+
+                if let (Some(a), Some(b)) = (anchor_index, target_index) {
+                    let (start, end) = if a <= b { (a, b) } else { (b, a) };
+                    self.selected_files = file_rows[start..=end].iter().map(|p| (*p).clone()).collect();
+                }
+            }
+            TreeAction::RemoveSelection => {
+                let selection = std::mem::take(&mut self.selected_files);
+                let removed = Self::remove_entries(&mut self.file_tree, &selection);
+                self.tree_rows_dirty = true;
+                if self.selected_file.as_ref().map_or(false, |p| selection.contains(p)) {
+                    self.selected_file = None;
+                }
+                self.selection_anchor = None;
+                println!("Removed {} entries from the project tree", removed);
+            }
+        }
+    }
+
+    fn remove_entries(entries: &mut Vec<FileEntry>, paths: &std::collections::HashSet<PathBuf>) -> usize {
+        let before = entries.len();
+        entries.retain(|entry| !paths.contains(&entry.path));
+        let mut removed = before - entries.len();
+        for entry in entries.iter_mut() {
+            removed += Self::remove_entries(&mut entry.children, paths);
+        }
+        removed
+    }
+
+    fn show_selection_context_menu(&mut self, ui: &mut egui::Ui, path: &PathBuf) {
+        // Right-clicking outside the selection acts on that row only
+        if !self.selected_files.contains(path) {
+            self.selected_files.clear();
+            self.selected_files.insert(path.clone());
+            self.selection_anchor = Some(path.clone());
+        }
+
+        ui.label(format!("{} selected", self.selected_files.len()));
+        ui.separator();
+
+        if self.selected_files.len() == 1 && path.is_file() {
+            ui.menu_button("Open as", |ui| {
+                for kind in FileKind::VIEWABLE {
+                    if ui.button(kind.label()).clicked() {
+                        ui.close_menu();
+                        self.selected_file = Some(path.clone());
+                        self.open_file_as(path, Some(kind), ui.ctx());
+                    }
+                }
+            });
+            if ui.button(AppCommand::AnalyzeSelection.label()).clicked() {
+                ui.close_menu();
+                self.execute_command(AppCommand::AnalyzeSelection, ui.ctx());
+            }
+        }
+        if self.selected_files.len() == 2 && ui.button("Compare").clicked() {
+            ui.close_menu();
+            let scenes = self.selected_files.iter().all(|p| p.file_name().and_then(|n| n.to_str()).map_or(false, Self::is_scene_file_name));
+            let command = if scenes { AppCommand::CompareScenes } else { AppCommand::CompareVersions };
+            self.execute_command(command, ui.ctx());
+        }
+
+        if self.selected_files.len() == 1 {
+            ui.horizontal(|ui| {
+                for slot in [Slot::A, Slot::B] {
+                    if ui.button(format!("Pin as {}", slot.label())).clicked() {
+                        ui.close_menu();
+                        self.ab_slots.pin(slot, path);
+                    }
+                }
+            });
+        }
+
+        ui.menu_button("Tags", |ui| self.show_tag_menu(ui));
+
+        for command in [
+            AppCommand::ExtractSelection,
+            AppCommand::ExportSelectionWithStructure,
+            AppCommand::FavoriteSelection,
+            AppCommand::RemoveSelection,
+        ] {
+            if ui.button(command.label()).clicked() {
+                ui.close_menu();
+                self.execute_command(command, ui.ctx());
+            }
+        }
+
+        if self.selected_files.len() == 1 && path.is_dir() {
+            ui.menu_button("New file", |ui| {
+                for command in [AppCommand::NewLuaScript, AppCommand::NewOctScene, AppCommand::NewMaterial] {
+                    if ui.add_enabled(self.command_enabled(command), egui::Button::new(command.label())).clicked() {
+                        ui.close_menu();
+                        self.execute_command(command, ui.ctx());
+                    }
+                }
+            });
+            for command in [AppCommand::CreateArchive, AppCommand::GenerateReleaseManifest, AppCommand::GenerateDocs] {
+                if ui.button(command.label()).clicked() {
+                    ui.close_menu();
+                    self.execute_command(command, ui.ctx());
+                }
+            }
+        }
+
+        ui.separator();
+        for command in [
+            AppCommand::CopySelection,
+            AppCommand::CutSelection,
+            AppCommand::PasteIntoSelection,
+            AppCommand::UndoFileOperation,
+            AppCommand::RenameSelection,
+            AppCommand::DeleteSelection,
+        ] {
+            let button = ui.add_enabled(self.command_enabled(command), egui::Button::new(command.label()));
+            let button = match (command, &self.file_clipboard) {
+                (AppCommand::PasteIntoSelection, Some((files, _))) => button.on_hover_text(format!("{} item(s)", files.len())),
+                (AppCommand::UndoFileOperation, _) => match self.undo_stack.last() {
+                    Some(record) => button.on_hover_text(&record.description),
+                    None => button,
+                },
+                (AppCommand::CutSelection | AppCommand::RenameSelection | AppCommand::DeleteSelection, _) => {
+                    button.on_disabled_hover_text("Archive entries and protected game files can only be copied")
+                }
+                _ => button,
+            };
+            if button.clicked() {
+                ui.close_menu();
+                self.execute_command(command, ui.ctx());
+            }
+        }
+    }
+
+    fn command_enabled(&self, command: AppCommand) -> bool {
+        match command {
+            AppCommand::OpenSelected => self.selected_files.len() == 1,
+            AppCommand::ExtractSelection
+            | AppCommand::ExportSelectionWithStructure
+            | AppCommand::FavoriteSelection
+            | AppCommand::RemoveSelection => !self.selected_files.is_empty(),
+            AppCommand::Rescan => self.scan_progress.is_none() && self.state.selected_game.is_some(),
+            AppCommand::ChangeExecutable => self.state.selected_game.is_some(),
+            AppCommand::StorageAnalyzer | AppCommand::BatchSceneDump | AppCommand::SchemaReport => !self.file_tree.is_empty(),
+            AppCommand::ToggleWireframe => self.model_viewer.has_model(),
+            AppCommand::ExportForBlender => self.model_viewer.has_model() && self.model_files.is_some(),
+            AppCommand::ExportSceneGltf => self.show_scene_viewer && self.scene_viewer.has_scene_loaded(),
+            AppCommand::Deploy => self.state.selected_game.is_some(),
+            AppCommand::CloseSceneViewer => self.show_scene_viewer,
+            AppCommand::SwitchGame
+            | AppCommand::Options
+            | AppCommand::ShowTasks
+            | AppCommand::Diagnostics
+            | AppCommand::History
+            | AppCommand::Jobs
+            | AppCommand::RunGame
+            | AppCommand::CreateArchive
+            | AppCommand::ToggleDryRun
+            | AppCommand::ToggleSplitView
+            | AppCommand::GenerateReleaseManifest
+            | AppCommand::VerifyManifest
+            | AppCommand::CompareVersions
+            | AppCommand::CompareScenes
+            | AppCommand::GenerateDocs => true,
+            AppCommand::ComparePinned => self.ab_slots.pinned().is_some(),
+            AppCommand::HealthCheck => self.state.selected_game.is_some(),
+            AppCommand::TextBrowser => !self.file_tree.is_empty(),
+            AppCommand::ToyBox => self.state.selected_game == Some(GameType::DisneyInfinity30),
+            AppCommand::Tracks => !self.file_tree.is_empty(),
+            AppCommand::UsageStats => !self.file_tree.is_empty(),
+            AppCommand::ExportModPackage | AppCommand::InstallModPackage | AppCommand::ModBrowser => self.state.selected_game.is_some(),
+            AppCommand::CopySelection => !self.selected_files.is_empty(),
+            AppCommand::CutSelection | AppCommand::DeleteSelection => {
+                !self.selected_files.is_empty() && self.selected_files.iter().all(|p| self.can_modify(p))
+            }
+            AppCommand::RenameSelection => self.selected_files.len() == 1 && self.selected_files.iter().all(|p| self.can_modify(p)),
+            AppCommand::PasteIntoSelection => {
+                self.file_clipboard.is_some() && self.selected_files.len() == 1 && self.selected_files.iter().all(|p| p.is_dir())
+            }
+            AppCommand::UndoFileOperation => !self.undo_stack.is_empty(),
+            AppCommand::AnalyzeSelection => self.selected_files.len() == 1 && self.selected_files.iter().all(|p| p.is_file()),
+            AppCommand::NewLuaScript | AppCommand::NewOctScene | AppCommand::NewMaterial => {
+                self.selected_files.len() == 1 && self.selected_files.iter().all(|p| p.is_dir() && self.can_modify(p))
+            }
+        }
+    }
+
+    // Single dispatch point for menus, context menus and the command palette
+    fn execute_command(&mut self, command: AppCommand, ctx: &egui::Context) {
+        match command {
+            AppCommand::OpenSelected => {
+                if let Some(path) = self.selected_files.iter().next().cloned() {
+                    self.selected_file = Some(path.clone());
+                    self.handle_model_file_selection(&path, ctx);
+                }
+            }
+            AppCommand::ExtractSelection => self.copy_selection_to_folder(false, ctx),
+            AppCommand::ExportSelectionWithStructure => self.copy_selection_to_folder(true, ctx),
+            AppCommand::FavoriteSelection => {
+                for path in &self.selected_files {
+                    if !self.state.favorites.contains(path) {
+                        self.state.favorites.push(path.clone());
+                    }
+                }
+                self.save_state();
+            }
+            AppCommand::RemoveSelection => self.pending_tree_action = Some(TreeAction::RemoveSelection),
+            AppCommand::Rescan => self.full_rescan(),
+            AppCommand::SwitchGame => {
+                if self.prompt_if_unsaved(PendingAction::SwitchGame) {
+                    return;
+                }
+                self.capture_layout();
+                self.state.current_step = AppStep::GameSelection;
+                self.save_state();
+            }
+            AppCommand::ChangeExecutable => self.open_file_dialog(),
+            AppCommand::Options => self.show_options = true,
+            AppCommand::StorageAnalyzer => {
+                if !self.show_storage_analyzer {
+                    self.show_storage_analyzer = true;
+                    self.start_storage_analysis();
+                }
+            }
+            AppCommand::BatchSceneDump => self.start_batch_scene_dump(ctx),
+            AppCommand::SchemaReport => self.start_schema_report(ctx),
+            AppCommand::ShowTasks => self.show_tasks = true,
+            AppCommand::Diagnostics => self.show_diagnostics = true,
+            AppCommand::History => self.show_history = true,
+            AppCommand::Jobs => self.show_jobs = true,
+            AppCommand::ExportForBlender => self.export_for_blender(ctx),
+            AppCommand::ExportSceneGltf => self.export_scene_gltf(ctx),
+            AppCommand::Deploy => self.show_deploy = true,
+            AppCommand::ToggleWireframe => self.model_viewer.show_wireframe = !self.model_viewer.show_wireframe,
+            AppCommand::CloseSceneViewer => {
+                if self.prompt_if_unsaved(PendingAction::CloseSceneViewer) {
+                    return;
+                }
+                self.show_scene_viewer = false;
+                self.scene_viewer.clear();
+            }
+            AppCommand::RunGame => self.run_game(),
+            AppCommand::CopySelection | AppCommand::CutSelection => {
+                let mut files: Vec<PathBuf> = self.selected_files.iter().cloned().collect();
+                files.sort();
+                self.file_clipboard = Some((files, command == AppCommand::CutSelection));
+            }
+            AppCommand::PasteIntoSelection => {
+                if let Some(dest) = self.selected_files.iter().next().cloned() {
+                    self.paste_into(&dest);
+                }
+            }
+            AppCommand::UndoFileOperation => {
+                if let Some(record) = self.undo_stack.pop() {
+                    println!("{}", file_ops::undo(&record));
+                    self.refresh_tree();
+                }
+            }
+            AppCommand::RenameSelection => {
+                if let Some(path) = self.selected_files.iter().next().cloned() {
+                    let name = paths::display_name(&path);
+                    self.rename_target = Some((path, name));
+                    self.rename_error = None;
+                }
+            }
+            AppCommand::DeleteSelection => self.delete_selection(),
+            AppCommand::NewLuaScript => self.create_from_template(Template::LuaScript),
+            AppCommand::NewOctScene => self.create_from_template(Template::OctScene),
+            AppCommand::NewMaterial => self.create_from_template(Template::Material),
+            AppCommand::AnalyzeSelection => {
+                if let Some(path) = self.selected_files.iter().next().cloned() {
+                    self.start_analysis(path);
+                }
+            }
+            AppCommand::CreateArchive => {
+                // The selected folder, or one picked when there isn't one
+                let selected = self.selected_files.iter().next().filter(|p| self.selected_files.len() == 1 && p.is_dir()).cloned();
+                self.archive_folder = selected.or_else(|| rfd::FileDialog::new().set_title("Folder to archive").pick_folder());
+            }
+            AppCommand::ToggleDryRun => {
+                self.dry_run = !self.dry_run;
+                println!("Dry run {}", if self.dry_run { "on" } else { "off" });
+            }
+            AppCommand::ToggleSplitView => {
+                self.state.panels.split = !self.state.panels.split;
+                self.load_split_preview();
+            }
+            AppCommand::GenerateReleaseManifest => {
+                let selected = self.selected_files.iter().next().filter(|p| self.selected_files.len() == 1 && p.is_dir()).cloned();
+                if let Some(folder) = selected.or_else(|| rfd::FileDialog::new().set_title("Folder to hash").pick_folder()) {
+                    self.spawn_release_manifest(folder);
+                }
+            }
+            AppCommand::GenerateDocs => {
+                let selected = self.selected_files.iter().next()
+                    .filter(|p| self.selected_files.len() == 1 && (p.is_dir() || Self::is_archive_path(p)))
+                    .cloned();
+                let Some(source) = selected.or_else(|| rfd::FileDialog::new().set_title("Folder to document").pick_folder()) else {
+                    return;
+                };
+                if let Some(output) = rfd::FileDialog::new()
+                    .set_title("Write the docs to")
+                    .pick_folder()
+                    .and_then(|p| self.write_guard().resolve(&p))
+                {
+                    self.spawn_docs(source, output);
+                }
+            }
+            AppCommand::VerifyManifest => {
+                if let Some(manifest) = rfd::FileDialog::new()
+                    .set_title("Verify manifest")
+                    .set_file_name(release_manifest::MANIFEST_FILE)
+                    .add_filter("Manifest", &["txt", "sha256"])
+                    .pick_file()
+                {
+                    self.spawn_manifest_verification(manifest);
+                }
+            }
+            AppCommand::CompareVersions => {
+                // Two selected archives or folders are compared straight away, oldest name first
+                let mut selected: Vec<PathBuf> = self.selected_files.iter().cloned().collect();
+                selected.sort();
+                let comparable = selected.iter().all(|p| p.is_dir()) || selected.iter().all(|p| Self::is_archive_path(p));
+                if selected.len() == 2 && comparable {
+                    self.diff_old = Some(selected[0].clone());
+                    self.diff_new = Some(selected[1].clone());
+                    self.start_diff();
+                }
+                self.show_diff = true;
+            }
+            AppCommand::CompareScenes => {
+                // Two selected scenes, or two picked one after the other
+                let mut selected: Vec<PathBuf> = self.selected_files.iter()
+                    .filter(|p| p.is_file() && p.file_name().and_then(|n| n.to_str()).map_or(false, Self::is_scene_file_name))
+                    .cloned()
+                    .collect();
+                selected.sort();
+                if selected.len() != 2 {
+                    let pick = |title: &str| rfd::FileDialog::new().set_title(title).add_filter("Scene", &["oct", "bent"]).pick_file();
+                    let (Some(old), Some(new)) = (pick("Old scene"), pick("New scene")) else {
+                        return;
+                    };
+                    selected = vec![old, new];
+                }
+                self.compare_scenes(&selected[0], &selected[1]);
+            }
+            AppCommand::ComparePinned => self.compare_pinned(ctx),
+            AppCommand::HealthCheck => {
+                self.start_health_check();
+                self.show_health = true;
+            }
+            AppCommand::TextBrowser => {
+                if !self.show_text_browser {
+                    self.show_text_browser = true;
+                    if self.text_browser.entries.is_empty() {
+                        self.spawn_text_scan();
+                    }
+                }
+            }
+            AppCommand::ToyBox => self.show_toy_box = true,
+            AppCommand::ExportModPackage => {
+                if let Some(game) = &self.state.selected_game {
+                    self.mod_export = Some(ExportForm::new(game.as_str()));
+                }
+            }
+            AppCommand::InstallModPackage => self.install_mod_package(),
+            AppCommand::ModBrowser => {
+                if !self.show_mod_browser {
+                    self.show_mod_browser = true;
+                    if let Some(game) = &self.state.selected_game {
+                        if !self.state.mod_index_url.trim().is_empty() {
+                            self.mod_browser.refresh(self.state.mod_index_url.trim(), game.as_str(), ctx);
+                        }
+                    }
+                }
+            }
+            AppCommand::UsageStats => {
+                self.show_usage = true;
+                let game = self.state.selected_game.as_ref().map_or("", |g| g.as_str());
+                if self.usage_report.as_ref().map_or(true, |r| r.game != game) && !self.usage_running {
+                    // The cached report is shown right away, even when stale; refreshing is up to the user
+                    self.usage_report = UsageReport::load(&UsageReport::cache_path(&self.temp_dir, game));
+                    if self.usage_report.is_none() {
+                        self.spawn_usage_stats();
+                    }
+                }
+            }
+            AppCommand::Tracks => {
+                if !self.show_tracks {
+                    self.show_tracks = true;
+                    if self.track_browser.tracks.is_empty() {
+                        self.spawn_track_scan();
+                    }
+                }
+            }
+        }
+    }
+
+    // Writes a template into the selected folder under a name picked in the save dialog
+    fn create_from_template(&mut self, template: Template) {
+        let Some(folder) = self.selected_files.iter().next().cloned() else {
+            return;
+        };
+        let mtb_params = self.state.selected_game.as_ref()
+            .map(|g| MtbParams::for_game(&g.scene_game_type()))
+            .unwrap_or_default();
+        let textures = if template == Template::Material {
+            let extension = mtb_params.texture_extension;
+            match rfd::FileDialog::new()
+                .set_title("Select the textures the material references")
+                .set_directory(&folder)
+                .add_filter("Textures", &[extension, "tbody", "dds"])
+                .pick_files()
+            {
+                Some(textures) => textures,
+                None => return,
+            }
+        } else {
+            Vec::new()
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("New file")
+            .set_directory(&folder)
+            .set_file_name(template.default_file_name())
+            .add_filter(template.extension(), &[template.extension()])
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p))
+        else {
+            return;
+        };
+
+        let result: Result<(), Box<dyn std::error::Error>> = match template {
+            Template::LuaScript => templates::write_lua_script(&path).map_err(Into::into),
+            Template::OctScene => templates::write_oct_scene(&path, binrw::Endian::Little).map_err(Into::into),
+            Template::Material => templates::write_mtb(&path, &textures, mtb_params.big_endian.unwrap_or(false)),
+        };
+        match result {
+            Ok(()) => {
+                println!("Created {}", path.display());
+                self.undo_stack.push(file_ops::UndoRecord::created_file(&path, format!("new file {}", path.display())));
+                self.refresh_tree();
+            }
+            Err(e) => eprintln!("Failed to create {}: {}", path.display(), e),
+        }
+    }
+
+    fn delete_selection(&mut self) {
+        let mut files: Vec<PathBuf> = self.selected_files.iter().filter(|p| self.can_modify(p)).cloned().collect();
+        files.sort();
+        if files.is_empty() {
+            return;
+        }
+        let names: Vec<String> = files.iter().take(10).map(|p| paths::display_name(p)).collect();
+        let more = if files.len() > names.len() { format!("\n... and {} more", files.len() - names.len()) } else { String::new() };
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Move to recycle bin")
+            .set_description(format!("Move {} item(s) to the recycle bin?\n\n{}{}", files.len(), names.join("\n"), more))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        match safe_write::delete(&files) {
+            Ok(()) => println!("Moved {} item(s) to the recycle bin", files.len()),
+            Err(e) => eprintln!("Failed to move files to the recycle bin: {}", e),
+        }
+        self.selected_files.clear();
+        self.refresh_tree();
+    }
+
+    fn show_rename_prompt(&mut self, ctx: &egui::Context) {
+        let Some((path, name)) = &mut self.rename_target else {
+            return;
+        };
+
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new("Rename")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(path.display().to_string());
+                let response = ui.text_edit_singleline(name);
+                response.request_focus();
+                confirm = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if let Some(error) = &self.rename_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ui.horizontal(|ui| {
+                    confirm |= ui.button("Rename").clicked();
+                    cancel = ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+                });
+            });
+
+        if cancel {
+            self.rename_target = None;
+        } else if confirm {
+            let (path, name) = (path.clone(), name.clone());
+            match file_ops::rename(&path, &name) {
+                Ok(record) => {
+                    self.undo_stack.push(record);
+                    self.rename_target = None;
+                    self.refresh_tree();
+                }
+                Err(e) => self.rename_error = Some(e),
+            }
+        }
+    }
+
+    fn show_archive_prompt(&mut self, ctx: &egui::Context) {
+        let Some(folder) = self.archive_folder.clone() else {
+            self.archive_metadata = None;
+            return;
+        };
+        if self.archive_metadata.as_ref().map_or(true, |editor| editor.folder != folder) {
+            self.archive_metadata = Some(MetadataEditor::new(&folder));
+        }
+
+        let mut create = false;
+        let mut cancel = false;
+        let settings = &mut self.zip_settings;
+        let metadata = &mut self.archive_metadata;
+        egui::Window::new("Create archive")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(folder.display().to_string());
+                ui.checkbox(&mut settings.store_only, "Store only (no compression)");
+                ui.add_enabled_ui(!settings.store_only, |ui| {
+                    ui.add(egui::Slider::new(&mut settings.level, 0..=9).text("Compression level"));
+                });
+                if let Some(editor) = metadata {
+                    editor.show_ui(ui);
+                }
+                ui.horizontal(|ui| {
+                    create = ui.button("Create...").clicked();
+                    cancel = ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+                });
+            });
+
+        if cancel {
+            self.archive_folder = None;
+        } else if create && self.archive_metadata.as_mut().map_or(true, |editor| editor.save()) {
+            let name = format!("{}.zip", paths::display_name(&folder));
+            let mut dialog = rfd::FileDialog::new().set_title("Save archive").set_file_name(name).add_filter("ZIP", &["zip"]);
+            if let Some(parent) = folder.parent() {
+                dialog = dialog.set_directory(parent);
+            }
+            if let Some(output) = dialog.save_file().and_then(|p| self.write_guard().resolve(&p)) {
+                self.archive_folder = None;
+                let settings = self.zip_settings;
+                self.run_operation(Operation::CreateArchive { folder, output, settings }, ctx);
+            }
+        }
+    }
+
+    fn spawn_create_archive(&mut self, folder: PathBuf, output: PathBuf, settings: ZipSettings) -> String {
+        self.task_manager.spawn(format!("Archive {}", paths::display_name(&folder)), move |task| {
+            match zip_writer::create_from_folder(&folder, &output, settings, &task) {
+                Ok(summary) => task.finish(summary),
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+        });
+        self.show_tasks = true;
+        "Started in the background, see Tasks".to_string()
+    }
+
+    // Writes SHA256SUMS.txt and VERIFY.txt into `folder` for a mod release
+    fn spawn_release_manifest(&mut self, folder: PathBuf) {
+        let Some(manifest) = self.write_guard().resolve(&folder.join(release_manifest::MANIFEST_FILE)) else {
+            return;
+        };
+        self.task_manager.spawn(format!("Release manifest {}", paths::display_name(&folder)), move |task| {
+            match release_manifest::generate(&folder, &manifest, &task) {
+                Ok(summary) => task.finish(summary),
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+        });
+        self.show_tasks = true;
+    }
+
+    // Strings of every .dct text bank under the game's folders, loose or inside archives
+    fn spawn_text_scan(&mut self) {
+        let game_type = self.state.selected_game.clone();
+        let mut roots = self.asset_roots.clone();
+        if let Some(config) = game_type.as_ref().and_then(|g| self.state.game_configs.get(g)) {
+            roots.extend(config.extra_roots.iter().map(|r| r.path.clone()));
+        }
+        let slot = self.text_result.clone();
+        self.text_browser.scanning = true;
+        self.task_manager.spawn("Text banks".to_string(), move |task| {
+            let is_bank = |name: &str| name.to_lowercase().ends_with(".dct");
+            let files: Vec<PathBuf> = roots.iter()
+                .flat_map(|root| walkdir::WalkDir::new(long_path(root)).into_iter().filter_map(|e| e.ok()))
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .filter(|path| Self::is_archive_path(path) || path.file_name().map_or(false, |n| is_bank(&n.to_string_lossy())))
+                .collect();
+            task.set_total(files.len());
+
+            let mut entries = Vec::new();
+            let mut banks = 0;
+            for path in files {
+                if task.is_cancelled() {
+                    break;
+                }
+                let name = paths::display_name(&path);
+                task.advance(name.clone());
+                if !Self::is_archive_path(&path) {
+                    match fs::read(long_path(&path)) {
+                        Ok(data) => {
+                            entries.extend(text_browser::entries_from(&path.to_string_lossy(), &data));
+                            banks += 1;
+                        }
+                        Err(e) => task.add_error(format!("{}: {}", path.display(), e)),
+                    }
+                    continue;
+                }
+                let result = Self::for_each_archive_file(game_type.as_ref(), &path, &is_bank, &mut |entry, data| {
+                    match data {
+                        Ok(data) => {
+                            entries.extend(text_browser::entries_from(&format!("{}/{}", name, entry), &data));
+                            banks += 1;
+                        }
+                        Err(e) => task.add_error(format!("{}/{}: {}", name, entry, e)),
+                    }
+                    !task.is_cancelled()
+                });
+                if let Err(e) = result {
+                    task.add_error(format!("{}: {}", name, e));
+                }
+            }
+            let summary = format!("{} strings from {} text banks", entries.len(), banks);
+            *slot.lock().unwrap() = Some(entries);
+            task.finish(summary);
+        });
+    }
+
+    fn show_text_browser_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(entries) = self.text_result.lock().unwrap().take() {
+            self.text_browser.entries = entries;
+            self.text_browser.scanning = false;
+        }
+        match self.text_browser.show_ui(ui) {
+            Some(TextBrowserAction::Rescan) => self.spawn_text_scan(),
+            Some(TextBrowserAction::ExportCsv(csv)) => {
+                let Some(path) = rfd::FileDialog::new()
+                    .set_title("Export strings")
+                    .set_file_name("strings.csv")
+                    .add_filter("CSV", &["csv"])
+                    .save_file()
+                    .and_then(|p| self.write_guard().resolve(&p)) else {
+                    return;
+                };
+                match fs::write(long_path(&path), csv) {
+                    Ok(()) => println!("Exported strings to {}", path.display()),
+                    Err(e) => eprintln!("Failed to write {}: {}", path.display(), e),
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn show_toy_box_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(action) = self.toy_box.show_ui(ui) else {
+            return;
+        };
+        let stem = self.toy_box.source.as_ref().map(|p| naming::file_stem(p)).unwrap_or_else(|| "level".to_string());
+        let result = match action {
+            ToyBoxAction::Open => {
+                let Some(path) = rfd::FileDialog::new().set_title("Open Toy Box level").pick_file() else {
+                    return;
+                };
+                if !self.prompt_if_unsaved(PendingAction::OpenToyBoxLevel(path.clone())) {
+                    self.open_toy_box_level(&path);
+                }
+                return;
+            }
+            ToyBoxAction::ExportJson => {
+                let Some(path) = rfd::FileDialog::new()
+                    .set_title("Export placed toys")
+                    .set_file_name(format!("{}_toys.json", stem))
+                    .add_filter("JSON", &["json"])
+                    .save_file()
+                    .and_then(|p| self.write_guard().resolve(&p)) else {
+                    return;
+                };
+                self.toy_box.export_json(&path)
+            }
+            ToyBoxAction::ImportJson => {
+                let Some(path) = rfd::FileDialog::new().set_title("Import placed toys").add_filter("JSON", &["json"]).pick_file() else {
+                    return;
+                };
+                self.toy_box.import_json(&path)
+            }
+            ToyBoxAction::SaveAs => {
+                self.save_toy_box_as();
+                return;
+            }
+        };
+        self.set_toy_box_status(result);
+    }
+
+    fn set_toy_box_status(&mut self, result: Result<String, String>) {
+        match &result {
+            Ok(message) => println!("{}", message),
+            Err(error) => eprintln!("{}", error),
+        }
+        self.toy_box.status = Some(result);
+    }
+
+    fn open_toy_box_level(&mut self, path: &Path) {
+        let result = self.toy_box.open(path);
+        self.set_toy_box_status(result);
+    }
+
+    // Returns false when the user cancelled the dialog or the save failed
+    fn save_toy_box_as(&mut self) -> bool {
+        let file_name = self.toy_box.source.as_ref().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Save Toy Box level")
+            .set_file_name(file_name)
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p))
+            .and_then(|p| self.resolve_write_target(&p)) else {
+            return false;
+        };
+        let result = self.toy_box.save(&path);
+        let saved = result.is_ok();
+        self.set_toy_box_status(result);
+        saved
+    }
+
+    fn spawn_track_scan(&mut self) {
+        let Some(game_type) = self.state.selected_game.clone() else {
+            return;
+        };
+        let mut roots = self.asset_roots.clone();
+        if let Some(config) = self.state.game_configs.get(&game_type) {
+            roots.extend(config.extra_roots.iter().map(|r| r.path.clone()));
+        }
+        let slot = self.track_result.clone();
+        self.track_browser.scanning = true;
+        self.task_manager.spawn("Tracks and levels".to_string(), move |task| {
+            let hints = game_type.level_hints();
+            let files: Vec<PathBuf> = roots.iter()
+                .flat_map(|root| walkdir::WalkDir::new(long_path(root)).into_iter().filter_map(|e| e.ok()))
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .collect();
+            let archives: Vec<&PathBuf> = files.iter().filter(|p| Self::is_archive_path(p)).collect();
+            task.set_total(archives.len() + 1);
+
+            // Loose scenes, with any loose images next to them as thumbnails
+            task.advance("Loose files".to_string());
+            let images: Vec<String> = files.iter()
+                .map(|p| p.to_string_lossy().into_owned())
+                .filter(|p| tracks::is_thumbnail_candidate(p))
+                .collect();
+            let mut found = Vec::new();
+            for path in files.iter().filter(|p| p.file_name().map_or(false, |n| Self::is_scene_file_name(&n.to_string_lossy()))) {
+                // Only folders below the asset root count, so a "World" folder above it doesn't match everything
+                let relative = roots.iter().find_map(|root| path.strip_prefix(long_path(root)).ok()).unwrap_or(path);
+                let Some(level) = tracks::level_name(&relative.to_string_lossy(), hints) else {
+                    continue;
+                };
+                let text = path.to_string_lossy();
+                let thumbnail = tracks::find_thumbnail(&text, &level, &images).and_then(|image| fs::read(long_path(Path::new(image))).ok());
+                found.push(TrackEntry {
+                    name: tracks::display_name(&level),
+                    scene: path.clone(),
+                    archive: None,
+                    size: fs::metadata(long_path(path)).map(|m| m.len()).unwrap_or(0),
+                    has_bent: path.with_extension("bent").is_file(),
+                    thumbnail,
+                });
+            }
+
+            for archive in archives {
+                if task.is_cancelled() {
+                    break;
+                }
+                task.advance(paths::display_name(archive));
+                let entries = match Self::list_archive_entries(Some(&game_type), archive) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        task.add_error(format!("{}: {}", archive.display(), e));
+                        continue;
+                    }
+                };
+                let images: Vec<String> = entries.iter().map(|e| e.name.clone()).filter(|n| tracks::is_thumbnail_candidate(n)).collect();
+                let names: std::collections::HashSet<String> = entries.iter().map(|e| e.name.to_lowercase()).collect();
+                let mut wanted_images = HashMap::new();
+                for entry in entries.iter().filter(|e| Self::is_scene_file_name(&e.name)) {
+                    let Some(level) = tracks::level_name(&entry.name, hints) else {
+                        continue;
+                    };
+                    if let Some(image) = tracks::find_thumbnail(&entry.name, &level, &images) {
+                        wanted_images.insert(image.clone(), found.len());
+                    }
+                    let bent = format!("{}.bent", entry.name.rsplit_once('.').map_or(entry.name.as_str(), |(stem, _)| stem));
+                    found.push(TrackEntry {
+                        name: tracks::display_name(&level),
+                        scene: archive.join(&entry.name),
+                        archive: Some(archive.clone()),
+                        size: entry.size,
+                        has_bent: names.contains(&bent.to_lowercase()),
+                        thumbnail: None,
+                    });
+                }
+                if wanted_images.is_empty() {
+                    continue;
+                }
+                let result = Self::for_each_archive_file(Some(&game_type), archive, &|name| wanted_images.contains_key(name), &mut |name, data| {
+                    if let (Some(index), Ok(data)) = (wanted_images.get(name), data) {
+                        found[*index].thumbnail = Some(data);
+                    }
+                    !task.is_cancelled()
+                });
+                if let Err(e) = result {
+                    task.add_error(format!("{}: {}", archive.display(), e));
+                }
+            }
+            found.sort_by_key(|t| t.name.to_lowercase());
+            let summary = format!("{} tracks and levels", found.len());
+            *slot.lock().unwrap() = Some(found);
+            task.finish(summary);
+        });
+    }
+
+    // Loose files and their total size, to tell whether a cached usage report still matches the tree
+    fn tree_fingerprint(entries: &[FileEntry], temp_dir: &Path) -> (usize, u64) {
+        entries.iter().fold((0, 0), |(count, size), entry| {
+            if entry.is_directory {
+                let (c, s) = Self::tree_fingerprint(&entry.children, temp_dir);
+                (count + c, size + s)
+            } else if entry.path.starts_with(temp_dir) {
+                (count, size)
+            } else {
+                (count + 1, size + entry.size)
+            }
+        })
+    }
+
+    fn spawn_usage_stats(&mut self) {
+        let Some(root) = self.file_tree.first().and_then(|e| e.path.parent()).map(|p| p.to_path_buf()) else {
+            return;
+        };
+        let tree = self.file_tree.clone();
+        let game_type = self.state.selected_game.clone();
+        let game = game_type.as_ref().map_or("", |g| g.as_str()).to_string();
+        let temp_dir = self.temp_dir.clone();
+        let fingerprint = Self::tree_fingerprint(&self.file_tree, &self.temp_dir);
+        let slot = self.usage_result.clone();
+        self.usage_running = true;
+        self.task_manager.spawn(format!("Asset usage for {}", game), move |task| {
+            task.set_message("Listing files and archives".to_string());
+            let mut items = Vec::new();
+            Self::collect_storage_items(&tree, game_type.as_ref(), &temp_dir, &mut items);
+            let mut report = UsageReport::build(&game, fingerprint, &items);
+
+            let textures: std::collections::HashSet<String> = items.iter()
+                .filter(|item| AssetCategory::of(&item.path) == AssetCategory::Textures)
+                .filter_map(|item| item.path.file_stem().map(|s| s.to_string_lossy().to_lowercase()))
+                .collect();
+            let (mut files, mut archives) = (Vec::new(), Vec::new());
+            Self::collect_scene_sources(&tree, &temp_dir, &mut files, &mut archives);
+            let mut counts = HashMap::new();
+            let mut scenes_read = 0;
+            Self::for_each_scene_file(&task, &root, &files, &archives, game_type.as_ref(), &mut |relative, data| {
+                let mut handler = SceneFileHandler::new();
+                match data.and_then(|data| handler.load_scene_file(&mut std::io::Cursor::new(data)).map_err(|e| e.to_string())) {
+                    Ok(()) => {
+                        if let Some(scene) = &handler.current_scene {
+                            usage_stats::count_texture_references(scene, &textures, &mut counts);
+                        }
+                        scenes_read += 1;
+                    }
+                    Err(e) => task.add_error(format!("{}: {}", relative.display(), e)),
+                }
+            });
+            report.set_references(counts, scenes_read);
+            report.save(&UsageReport::cache_path(&temp_dir, &game));
+            let summary = format!("{} assets, {} scenes read", report.categories.iter().map(|(_, s)| s.count).sum::<usize>(), scenes_read);
+            *slot.lock().unwrap() = Some(report);
+            task.finish(summary);
+        });
+        self.show_tasks = true;
+    }
+
+    fn show_usage_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(report) = self.usage_result.lock().unwrap().take() {
+            self.usage_report = Some(report);
+            self.usage_running = false;
+        }
+        ui.horizontal(|ui| {
+            if self.usage_running {
+                ui.spinner();
+                ui.label("Computing, see Tasks...");
+            } else if ui.button("Refresh").clicked() {
+                self.spawn_usage_stats();
+            }
+            if let Some(report) = &self.usage_report {
+                ui.weak(format!("{} · computed {}", report.game, format_timestamp(report.generated)));
+            }
+        });
+        ui.separator();
+        match &self.usage_report {
+            Some(report) => {
+                let stale = report.fingerprint != Self::tree_fingerprint(&self.file_tree, &self.temp_dir);
+                report.show_ui(ui, stale);
+            }
+            None if !self.usage_running => {
+                ui.label("No usage report for this game yet.");
+            }
+            None => {}
+        }
+    }
+
+    // Archived scenes are copied to the temp folder, with their .bent, so the scene viewer can
+    // read them like loose files
+    fn track_scene_on_disk(&self, scene: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        if long_path(scene).is_file() {
+            return Ok(scene.to_path_buf());
+        }
+        let game_type = self.state.selected_game.as_ref();
+        let file_name = scene.file_name().ok_or("Scene has no file name")?;
+        let target = self.temp_dir.join("tracks").join(file_name);
+        paths::write_creating_dirs(&target, &Self::read_tree_file(game_type, &self.entry_cache, scene)?)?;
+        if let Ok(bent) = Self::read_tree_file(game_type, &self.entry_cache, &scene.with_extension("bent")) {
+            paths::write_creating_dirs(&target.with_extension("bent"), &bent)?;
+        }
+        Ok(target)
+    }
+
+    fn show_tracks_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        if let Some(tracks) = self.track_result.lock().unwrap().take() {
+            self.track_browser.set_tracks(tracks);
+        }
+        let game = self.state.selected_game.as_ref().map_or("", |g| g.as_str());
+        match self.track_browser.show_ui(ui, game) {
+            Some(TrackAction::Rescan) => self.spawn_track_scan(),
+            Some(TrackAction::Open(scene)) => match self.track_scene_on_disk(&scene) {
+                Ok(path) => {
+                    self.selected_file = Some(path.clone());
+                    self.open_file_as(&path, Some(FileKind::Scene), ctx);
+                }
+                Err(e) => eprintln!("Failed to open {}: {}", scene.display(), e),
+            },
+            Some(TrackAction::Reveal(path)) => self.selection_bus.publish(&path),
+            Some(TrackAction::Export(scene)) => {
+                let Some(folder) = rfd::FileDialog::new()
+                    .set_title("Export track scene")
+                    .pick_folder()
+                    .and_then(|p| self.write_guard().resolve(&p)) else {
+                    return;
+                };
+                let result = self.track_scene_on_disk(&scene).and_then(|source| {
+                    let file_name = source.file_name().ok_or("Scene has no file name")?;
+                    fs::copy(long_path(&source), long_path(&folder.join(file_name)))?;
+                    let bent = source.with_extension("bent");
+                    if bent.is_file() {
+                        fs::copy(long_path(&bent), long_path(&folder.join(file_name).with_extension("bent")))?;
+                    }
+                    Ok(())
+                });
+                match result {
+                    Ok(()) => println!("Exported {} to {}", scene.display(), folder.display()),
+                    Err(e) => eprintln!("Failed to export {}: {}", scene.display(), e),
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Archives are extracted to the temp folder first and documented from there
+    fn spawn_docs(&mut self, source: PathBuf, output: PathBuf) {
+        let game_type = self.state.selected_game.clone();
+        let params = game_type.as_ref().map(|g| MtbParams::for_game(&g.scene_game_type())).unwrap_or_default();
+        let extract_dir = self.temp_dir.join("docs").join(paths::display_name(&source));
+        let title = paths::display_name(&source);
+        self.task_manager.spawn(format!("Docs for {}", title), move |task| {
+            let root = if Self::is_archive_path(&source) {
+                task.set_message(format!("Extracting {}", title));
+                // Leftovers from an earlier run would be documented as part of the archive
+                if extract_dir.exists() {
+                    if let Err(e) = fs::remove_dir_all(long_path(&extract_dir)) {
+                        eprintln!("Failed to clear {}: {}", extract_dir.display(), e);
+                    }
+                }
+                let mut failed = None;
+                let result = Self::for_each_archive_file(game_type.as_ref(), &source, &|_| true, &mut |name, data| {
+                    let written = data.map_err(|e| e.to_string()).and_then(|data| {
+                        paths::write_creating_dirs(&paths::archive_entry_path(&extract_dir, name), &data).map_err(|e| e.to_string())
+                    });
+                    if let Err(e) = written {
+                        failed = Some(format!("{}: {}", name, e));
+                    }
+                    failed.is_none() && !task.is_cancelled()
+                });
+                if let Some(e) = failed.or_else(|| result.err().map(|e| e.to_string())) {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                    return;
+                }
+                extract_dir
+            } else {
+                source
+            };
+            match docs::generate(&root, &title, &output, &params, &task) {
+                Ok(summary) => task.finish(summary),
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+        });
+        self.show_tasks = true;
+    }
+
+    // Each changed or missing file is listed as an error of the task
+    fn spawn_manifest_verification(&mut self, manifest: PathBuf) {
+        self.task_manager.spawn(format!("Verify {}", paths::display_name(&manifest)), move |task| {
+            match release_manifest::verify(&manifest, &task) {
+                Ok(verification) => {
+                    for name in &verification.changed {
+                        task.add_error(format!("Changed: {}", name));
+                    }
+                    for name in &verification.missing {
+                        task.add_error(format!("Missing: {}", name));
+                    }
+                    for name in &verification.unlisted {
+                        task.add_error(format!("Not in manifest: {}", name));
+                    }
+                    println!("Verified {}: {}", manifest.display(), verification.summary());
+                    task.finish(verification.summary());
+                }
+                Err(e) => {
+                    task.add_error(e.clone());
+                    task.finish(format!("Failed: {}", e));
+                }
+            }
+        });
+        self.show_tasks = true;
+    }
+
+    fn is_archive_path(path: &Path) -> bool {
+        path.is_file() && path.extension().map_or(false, |e| e.eq_ignore_ascii_case("zip"))
+    }
+
+    // With `inside_archives`, the archives in a folder are listed entry by entry (as
+    // "<archive>/<entry>") so assets moved between archives can be matched up
+    fn diff_listing(game_type: Option<&GameType>, path: &Path, inside_archives: bool, task: &TaskContext) -> Result<Vec<DiffEntry>, String> {
+        let to_entry = |e: ArchiveEntryInfo| DiffEntry { name: e.name, size: e.size, crc32: e.crc32, archive: None, name_hash: e.name_hash };
+        if !path.is_dir() {
+            let entries = Self::list_archive_entries(game_type, path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            return Ok(entries.into_iter().map(to_entry).collect());
+        }
+
+        let (mut entries, archives) = archive_diff::list_folder(path, inside_archives, task)?;
+        for (archive, archive_name) in archives {
+            task.set_message(archive_name.clone());
+            match Self::list_archive_entries(game_type, &archive) {
+                Ok(list) => entries.extend(list.into_iter().map(|info| DiffEntry {
+                    name: format!("{}/{}", archive_name, info.name),
+                    archive: Some((archive_name.clone(), info.name.clone())),
+                    ..to_entry(info)
+                })),
+                // Unreadable archives are still compared as files
+                Err(e) => {
+                    task.add_error(format!("{}: {}", archive_name, e));
+                    let (size, crc32) = archive_diff::hash_file(&archive).map_err(|e| format!("{}: {}", archive_name, e))?;
+                    entries.push(DiffEntry::file(archive_name, size, crc32));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn start_diff(&mut self) {
+        let (Some(old), Some(new)) = (self.diff_old.clone(), self.diff_new.clone()) else {
+            return;
+        };
+        let slot = self.diff_result.clone();
+        *slot.lock().unwrap() = None;
+        self.diff_running = true;
+        let game_type = self.state.selected_game.clone();
+        let inside_archives = self.diff_inside_archives;
+
+        self.task_manager.spawn(format!("Compare {} and {}", paths::display_name(&old), paths::display_name(&new)), move |task| {
+            let result = Self::diff_listing(game_type.as_ref(), &old, inside_archives, &task).and_then(|old_entries| {
+                let new_entries = Self::diff_listing(game_type.as_ref(), &new, inside_archives, &task)?;
+                Ok(archive_diff::diff(&old, &new, old_entries, new_entries))
+            });
+            let summary = match &result {
+                Ok(diff) => format!("{} differences, {} unchanged", diff.rows.len(), diff.unchanged),
+                Err(e) => e.clone(),
+            };
+            *slot.lock().unwrap() = Some(result);
+            task.finish(summary);
+        });
+    }
+
+    fn show_diff_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        for (label, old) in [("Old", true), ("New", false)] {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", label));
+                let side = if old { &mut self.diff_old } else { &mut self.diff_new };
+                ui.monospace(side.as_ref().map_or("(none)".to_string(), |p| p.display().to_string()));
+                if ui.button("Archive...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Archive", &["zip"]).pick_file() {
+                        *side = Some(path);
+                    }
+                }
+                if ui.button("Folder...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        *side = Some(path);
+                    }
+                }
+            });
+        }
+        let result = self.diff_result.lock().unwrap().clone();
+        if result.is_some() {
+            self.diff_running = false;
+        }
+        let ready = self.diff_old.is_some() && self.diff_new.is_some() && !self.diff_running;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(ready, egui::Button::new("Compare")).clicked() {
+                self.start_diff();
+            }
+            ui.checkbox(&mut self.diff_inside_archives, "Look inside archives")
+                .on_hover_text("When comparing folders, list each archive's entries so assets moved between archives are matched up");
+        });
+        ui.separator();
+
+        let action = match &result {
+            Some(Ok(diff)) => self.diff_view.show_ui(ui, diff),
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+                None
+            }
+            None if self.diff_running => {
+                ui.spinner();
+                None
+            }
+            None => {
+                ui.label("Pick two archives, or two game folders, e.g. before and after a patch.");
+                None
+            }
+        };
+        match (action, result) {
+            (Some(DiffAction::Open { old, entry }), Some(Ok(diff))) => {
+                if let Some(path) = self.diff_entry_path(if old { &diff.old } else { &diff.new }, old, &entry) {
+                    self.selected_file = Some(path.clone());
+                    self.handle_model_file_selection(&path, ctx);
+                }
+            }
+            (Some(DiffAction::CompareScenes { old, new }), Some(Ok(diff))) => {
+                if let (Some(old), Some(new)) = (self.diff_entry_path(&diff.old, true, &old), self.diff_entry_path(&diff.new, false, &new)) {
+                    self.compare_scenes(&old, &new);
+                }
+            }
+            (Some(DiffAction::CompareArchives(name)), Some(Ok(diff))) => {
+                self.diff_old = Some(diff.old.join(&name));
+                self.diff_new = Some(diff.new.join(&name));
+                self.start_diff();
+            }
+            _ => {}
+        }
+    }
+
+    // Files in a folder are used in place; archive entries are extracted to temp/compare/<side> first
+    fn diff_entry_path(&self, side: &Path, old: bool, entry: &DiffEntry) -> Option<PathBuf> {
+        let (archive, name) = match &entry.archive {
+            Some((archive, name)) => (side.join(archive), name.as_str()),
+            None if side.is_dir() => return Some(side.join(&entry.name)),
+            None => (side.to_path_buf(), entry.name.as_str()),
+        };
+        let output = paths::archive_entry_path(&self.temp_dir.join("compare").join(if old { "old" } else { "new" }), &entry.name);
+        let mut written = None;
+        let result = Self::for_each_archive_file(self.state.selected_game.as_ref(), &archive, &|entry| entry == name, &mut |_, data| {
+            written = Some(data.map_err(|e| e.to_string()).and_then(|data| {
+                paths::write_creating_dirs(&output, &data).map_err(|e| e.to_string())
+            }));
+            false
+        });
+        match result.map_err(|e| e.to_string()).and_then(|_| written.unwrap_or_else(|| Err("entry not found".to_string()))) {
+            Ok(()) => Some(output),
+            Err(e) => {
+                eprintln!("Failed to extract {} from {}: {}", name, archive.display(), e);
+                None
+            }
+        }
+    }
+
+    // Paths passed to later launches, which hand them over and exit
+    fn poll_instance_handoffs(&mut self, ctx: &egui::Context) {
+        let handoffs = self.instance_lock.as_ref().map(InstanceLock::take_handoffs).unwrap_or_default();
+        for paths in handoffs {
+            println!("Another launch handed over {} path(s)", paths.len());
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            self.pending_open.extend(paths);
+        }
+        if self.state.current_step == AppStep::Editor && self.state.selected_game.is_some() && !self.pending_open.is_empty() {
+            let paths = std::mem::take(&mut self.pending_open);
+            self.open_external_paths(paths, ctx);
+        }
+    }
+
+    // Files are opened through the sniffing layer; folders become roots of the selected game
+    fn open_external_paths(&mut self, paths: Vec<PathBuf>, ctx: &egui::Context) {
+        let Some(game_type) = self.state.selected_game.clone() else {
+            return;
+        };
+        let mut rescan = false;
+        let mut file = None;
+        for path in paths {
+            if path.is_dir() && !self.has_install(&game_type) {
+                // Without an install to scan, the folder is browsed on its own for this session
+                println!("Browsing {} on its own", path.display());
+                self.scan_folder(&path);
+            } else if path.is_dir() {
+                let config = self.state.game_configs.entry(game_type.clone()).or_default();
+                let known = self.asset_roots.iter().chain(config.extra_roots.iter().map(|r| &r.path)).any(|root| path.starts_with(root));
+                if !known {
+                    println!("Adding {} as a root", path.display());
+                    config.extra_roots.push(ExtraRoot { path, ignore: String::new() });
+                    rescan = true;
+                }
+            } else if path.is_file() {
+                file = Some(path);
+            } else {
+                eprintln!("Can't open {}: it doesn't exist", path.display());
+            }
+        }
+        if rescan {
+            self.save_state();
+            self.start_game_scan(&game_type);
+        }
+        // Only the last file stays open, so opening several would just flash through them
+        if let Some(path) = file {
+            self.selected_files.clear();
+            self.selected_files.insert(path.clone());
+            self.selection_anchor = Some(path.clone());
+            self.selected_file = Some(path.clone());
+            self.handle_model_file_selection(&path, ctx);
+            self.selection_bus.publish(&path);
+        }
+    }
+
+    // The last game used, or the first one set up. With none set up yet the game still has to be
+    // picked, and the paths wait for it.
+    fn skip_to_editor(&mut self) {
+        let configured = GameType::all().into_iter().find(|g| self.state.game_configs.contains_key(g));
+        let Some(game_type) = self.state.selected_game.clone().or(configured) else {
+            self.state.current_step = AppStep::GameSelection;
+            return;
+        };
+        if self.state.selected_game.as_ref() != Some(&game_type) {
+            self.state.selected_game = Some(game_type.clone());
+            self.start_game_scan(&game_type);
+            self.restore_layout(&game_type);
+        }
+        self.state.current_step = AppStep::Editor;
+    }
+
+    // Whether start_game_scan has an asset folder or executable to scan
+    fn has_install(&self, game_type: &GameType) -> bool {
+        self.state.game_configs.get(game_type).map_or(false, |config| {
+            config.asset_folder.as_ref().map_or(false, |f| f.is_dir()) || self.validate_executable(game_type, &config.executable_path)
+        })
+    }
+
+    // Checks the selected game's setup on the task manager, opening the report if anything's wrong
+    fn start_health_check(&mut self) {
+        let Some(game_type) = self.state.selected_game.clone() else {
+            return;
+        };
+        self.health_checked = Some(game_type.clone());
+        self.health_report = None;
+        let config = self.state.game_configs.get(&game_type).cloned().unwrap_or_default();
+        let slot = self.health_result.clone();
+        self.task_manager.spawn(format!("Check {} setup", game_type.as_str()), move |task| {
+            let report = HealthReport { game: game_type.as_str().to_string(), checks: Self::health_checks(&game_type, &config, &task) };
+            let summary = report.summary();
+            *slot.lock().unwrap() = Some(report);
+            task.finish(summary);
+        });
+    }
+
+    fn poll_health_check(&mut self) {
+        if self.state.current_step != AppStep::Editor {
+            self.health_checked = None;
+        } else if self.state.selected_game.is_some() && self.health_checked != self.state.selected_game {
+            self.start_health_check();
+        }
+        if let Some(report) = self.health_result.lock().unwrap().take() {
+            println!("{}", report.summary());
+            self.show_health |= report.problems() > 0;
+            self.health_report = Some(report);
+        }
+    }
+
+    fn health_checks(game_type: &GameType, config: &GameConfig, task: &TaskContext) -> Vec<HealthCheck> {
+        let mut checks = Vec::new();
+        task.set_total(3);
+        let game_dir = config.executable_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let roots = match &config.asset_folder {
+            Some(folder) => {
+                checks.push(if folder.is_dir() {
+                    HealthCheck::ok("Asset folder", folder.display().to_string())
+                } else {
+                    HealthCheck::failed("Asset folder", format!("{} doesn't exist", folder.display()), "Pick the folder again from the game selection screen, or pick the game's executable instead")
+                        .with_fix(HealthFix::ChangeExecutable)
+                });
+                vec![folder.clone()].into_iter().filter(|f| f.is_dir()).collect()
+            }
+            None => {
+                let executable = &config.executable_path;
+                checks.push(if !executable.is_file() {
+                    HealthCheck::failed("Executable", format!("{} doesn't exist", executable.display()), "The game may have moved or been uninstalled; pick its executable again")
+                        .with_fix(HealthFix::ChangeExecutable)
+                } else if !executable.file_name().map_or(false, |n| n.to_string_lossy().eq_ignore_ascii_case(game_type.expected_executable())) {
+                    HealthCheck::failed("Executable", format!("{} isn't {}", paths::display_name(executable), game_type.expected_executable()), "Pick the game's own executable, not a launcher or shortcut")
+                        .with_fix(HealthFix::ChangeExecutable)
+                } else {
+                    HealthCheck::ok("Executable", executable.display().to_string())
+                });
+                if game_type == &GameType::Cars3DrivenToWinXB1 {
+                    if game_dir.is_dir() { vec![game_dir.clone()] } else { Vec::new() }
+                } else {
+                    let roots = game_type.asset_roots(&game_dir);
+                    checks.push(if roots.is_empty() {
+                        HealthCheck::failed(
+                            "Assets folder",
+                            format!("No {} folder next to the executable", game_type.asset_root_rules().join(" or ")),
+                            "Tundra reads the unpacked game files; check the install is complete, or browse an extracted asset folder directly",
+                        )
+                    } else {
+                        HealthCheck::ok("Assets folder", roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", "))
+                    });
+                    roots
+                }
+            }
+        };
+        task.advance("Folders");
+
+        // Opening every archive would take as long as a scan, so a spread of them is tried
+        if !roots.is_empty() {
+            let archives: Vec<PathBuf> = roots.iter()
+                .flat_map(|root| walkdir::WalkDir::new(long_path(root)).into_iter().filter_map(|e| e.ok()))
+                .filter(|e| e.file_type().is_file() && Self::is_archive_path(e.path()))
+                .map(|e| e.into_path())
+                .collect();
+            let step = (archives.len() / HEALTH_ARCHIVE_SAMPLES).max(1);
+            let failed: Vec<String> = archives.iter()
+                .step_by(step)
+                .take(HEALTH_ARCHIVE_SAMPLES)
+                .filter_map(|archive| {
+                    task.set_message(paths::display_name(archive));
+                    Self::list_archive_entries(Some(game_type), archive).err().map(|e| format!("{}: {}", paths::display_name(archive), e))
+                })
+                .collect();
+            let tried = archives.len().min(HEALTH_ARCHIVE_SAMPLES);
+            checks.push(if archives.is_empty() {
+                HealthCheck::ok("Archives", "No archives under the asset folders")
+            } else if failed.is_empty() {
+                HealthCheck::ok("Archives", format!("Opened {} of {} archives", tried, archives.len()))
+            } else {
+                let hint = if game_type.uses_special_zip_reader() {
+                    "This game's archives are decrypted with a key picked by file name, so renamed or repacked archives can't be read"
+                } else {
+                    "The archives may be damaged or from another game; verify the game files"
+                };
+                HealthCheck::failed("Archives", format!("{} of {} tried couldn't be opened: {}", failed.len(), tried, failed.join("; ")), hint)
+            });
+        }
+        task.advance("Archives");
+
+        let missing: Vec<String> = config.texture_search_paths.iter().filter(|p| !p.is_dir()).map(|p| p.display().to_string()).collect();
+        checks.push(if !missing.is_empty() {
+            HealthCheck::failed("Texture folders", format!("Missing: {}", missing.join(", ")), "Remove or re-add them under Options > Texture search paths")
+                .with_fix(HealthFix::Options)
+        } else if !config.texture_search_paths.is_empty() {
+            HealthCheck::ok("Texture folders", format!("{} configured", config.texture_search_paths.len()))
+        } else {
+            let found = roots.iter().find_map(|root| {
+                walkdir::WalkDir::new(long_path(root)).max_depth(3).into_iter().filter_map(|e| e.ok())
+                    .find(|e| e.file_type().is_dir() && e.file_name().to_string_lossy().eq_ignore_ascii_case("textures"))
+                    .map(|e| e.into_path())
+            });
+            match found {
+                Some(folder) => HealthCheck::ok("Texture folders", folder.display().to_string()),
+                None => HealthCheck::warning(
+                    "Texture folders",
+                    "No textures folder found and none configured",
+                    "Materials only find textures next to them; add the folders they live in under Options",
+                )
+                .with_fix(HealthFix::Options),
+            }
+        });
+        task.advance("Textures");
+
+        // The catalog isn't parsed anywhere yet, so it's checked for being there and readable
+        if game_type == &GameType::Cars3DrivenToWinXB1 {
+            let catalog = game_dir.join("Catalog000.bin");
+            checks.push(match fs::metadata(long_path(&catalog)) {
+                Ok(metadata) if metadata.len() > 0 && fs::File::open(long_path(&catalog)).is_ok() => {
+                    HealthCheck::ok("Catalog", format!("{} ({})", catalog.display(), format_size(metadata.len())))
+                }
+                Ok(_) => HealthCheck::failed("Catalog", format!("{} is empty or unreadable", catalog.display()), "Copy Catalog000.bin from the console dump again"),
+                Err(_) => HealthCheck::failed("Catalog", format!("{} is missing", catalog.display()), "The dump looks incomplete; Catalog000.bin should sit next to game.consumer.exe"),
+            });
+        }
+
+        for root in config.extra_roots.iter().filter(|r| !r.path.is_dir()) {
+            checks.push(
+                HealthCheck::warning("Extra folder", format!("{} doesn't exist", root.path.display()), "Remove it or point it somewhere else under Options")
+                    .with_fix(HealthFix::Options),
+            );
+        }
+        checks
+    }
+
+    // Opens whichever comparison suits the two pinned files
+    fn compare_pinned(&mut self, ctx: &egui::Context) {
+        let Some((a, b)) = self.ab_slots.pinned() else {
+            return;
+        };
+        let route = ab_slots::route(&a, &b);
+        println!("Comparing {} with {} ({:?})", a.display(), b.display(), route);
+        match route {
+            CompareRoute::Scenes => self.compare_scenes(&a, &b),
+            CompareRoute::Containers => {
+                self.diff_old = Some(a);
+                self.diff_new = Some(b);
+                self.show_diff = true;
+                self.start_diff();
+            }
+            CompareRoute::Textures => {
+                if self.is_document_modified(Document::Scene) {
+                    self.ab_slots.set_result(&a, &b, AbResult::Error("Save or discard the open scene first".to_string()));
+                    return;
+                }
+                self.show_scene_viewer = false;
+                self.scene_viewer.clear();
+                self.model_viewer.clear_model();
+                self.selected_file = Some(a.clone());
+                self.selected_kind = Some(FileKind::Texture);
+                if let Err(e) = self.mtb_viewer.compare_textures(&a, &b, ctx) {
+                    eprintln!("Failed to compare textures: {}", e);
+                    self.ab_slots.set_result(&a, &b, AbResult::Error(e.to_string()));
+                }
+            }
+            CompareRoute::Models => {
+                let result = self.model_summary(&a)
+                    .and_then(|summary_a| Ok(AbResult::Models(summary_a, self.model_summary(&b)?)))
+                    .unwrap_or_else(AbResult::Error);
+                self.ab_slots.set_result(&a, &b, result);
+            }
+            CompareRoute::Text => {
+                let result = ab_slots::LineDiff::load(&a, &b).map_or_else(AbResult::Error, AbResult::Lines);
+                self.ab_slots.set_result(&a, &b, result);
+            }
+            CompareRoute::Bytes => {
+                let result = ab_slots::ByteDiff::load(&a, &b).map_or_else(AbResult::Error, AbResult::Bytes);
+                self.ab_slots.set_result(&a, &b, result);
+            }
+        }
+    }
+
+    // Loads an IBUF/VBUF pair on its own, leaving the model viewer as it is
+    fn model_summary(&self, path: &Path) -> Result<ModelSummary, String> {
+        let is_vbuf = path.extension().map_or(false, |e| e.eq_ignore_ascii_case("vbuf"));
+        let other = path.with_extension(if is_vbuf { "ibuf" } else { "vbuf" });
+        let (ibuf, vbuf) = if is_vbuf { (other, path.to_path_buf()) } else { (path.to_path_buf(), other) };
+        let mut viewer = ViewModel::ModelViewer::new();
+        viewer.layout = self.buffer_layout();
+        viewer.load_model_from_files(&ibuf, &vbuf).map_err(|e| format!("{}: {}", paths::display_name(path), e))?;
+        let model = viewer.current_model.ok_or("No model was loaded")?;
+        Ok(ModelSummary {
+            meshes: model.meshes.len(),
+            vertices: model.meshes.iter().map(|m| m.vertices.len()).sum(),
+            triangles: model.meshes.iter().map(|m| m.indices.len() / 3).sum(),
+            bounds_min: model.bounds_min,
+            bounds_max: model.bounds_max,
+        })
+    }
+
+    fn compare_scenes(&mut self, old: &Path, new: &Path) {
+        let result = scene_diff::compare(old, new);
+        match &result {
+            Ok(diff) => println!("Compared {} with {}: {} differences", old.display(), new.display(), diff.changes.len()),
+            Err(e) => eprintln!("Failed to compare scenes: {}", e),
+        }
+        self.scene_diff = Some(result);
+    }
+
+    fn show_scene_diff_ui(&mut self, ui: &mut egui::Ui) {
+        let action = match &self.scene_diff {
+            Some(Ok(diff)) => self.scene_diff_view.show_ui(ui, diff),
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+                None
+            }
+            None => None,
+        };
+        let (Some(action), Some(Ok(diff))) = (action, &self.scene_diff) else {
+            return;
+        };
+        let stem = naming::file_stem(&diff.new);
+        let (content, name, filter) = match action {
+            SceneDiffAction::ExportJson => (serde_json::to_string_pretty(&diff.to_json()).unwrap_or_default(), format!("{}_diff.json", stem), ("JSON", "json")),
+            SceneDiffAction::ExportMarkdown => (diff.to_markdown(), format!("{}_diff.md", stem), ("Markdown", "md")),
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export scene diff")
+            .set_file_name(name)
+            .add_filter(filter.0, &[filter.1])
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p)) else {
+            return;
+        };
+        match fs::write(long_path(&path), content) {
+            Ok(()) => println!("Wrote scene diff to {}", path.display()),
+            Err(e) => eprintln!("Failed to write {}: {}", path.display(), e),
+        }
+    }
+
+    // Archive entries are extracted copies and game files stay put while protection is on, so
+    // neither can be moved, renamed or deleted from the tree
+    fn can_modify(&self, path: &Path) -> bool {
+        !path.starts_with(&self.temp_dir) && !self.write_guard().is_protected(path)
+    }
+
+    fn refresh_tree(&mut self) {
+        if let Some(game_type) = self.state.selected_game.clone() {
+            self.start_game_scan(&game_type);
+        }
+    }
+
+    fn paste_into(&mut self, dest: &Path) {
+        let Some((sources, is_move)) = self.file_clipboard.clone() else {
+            return;
+        };
+        if sources.iter().any(|source| source.is_dir() && dest.starts_with(source)) {
+            eprintln!("Can't paste a folder into itself: {}", dest.display());
+            return;
+        }
+        let Some(dest) = self.write_guard().resolve(dest) else {
+            return;
+        };
+
+        let transfers = file_ops::plan(&sources, &dest);
+        let pending = PendingTransfer {
+            conflicts: file_ops::conflict_count(&transfers),
+            transfers,
+            is_move,
+            description: format!("{} {} item(s) to {}", if is_move { "move of" } else { "copy of" }, sources.len(), dest.display()),
+        };
+        if pending.conflicts > 0 {
+            self.pending_transfer = Some(pending);
+        } else {
+            self.run_transfer(pending, ConflictPolicy::Overwrite);
+        }
+    }
+
+    fn run_transfer(&mut self, pending: PendingTransfer, policy: ConflictPolicy) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        // Overwritten files are set aside here until the operation is undone
+        let backup_dir = self.temp_dir.join("_undo").join(now.to_string());
+        let (record, summary) = file_ops::apply(&pending.transfers, pending.is_move, policy, &backup_dir, pending.description);
+        println!("{}", summary);
+        self.undo_stack.push(record);
+        // Cut files are gone from where they were copied from
+        if pending.is_move {
+            self.file_clipboard = None;
+        }
+        self.refresh_tree();
+    }
+
+    fn show_transfer_prompt(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_transfer else {
+            return;
+        };
+
+        let mut choice = None;
+        let mut cancel = false;
+        egui::Window::new("Files already exist")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("{} of {} files already exist at the destination.", pending.conflicts, pending.transfers.len()));
+                ui.weak("Overwritten files are kept until the operation is undone.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Overwrite").clicked() {
+                        choice = Some(ConflictPolicy::Overwrite);
+                    }
+                    if ui.button("Keep both").clicked() {
+                        choice = Some(ConflictPolicy::KeepBoth);
+                    }
+                    if ui.button("Skip").clicked() {
+                        choice = Some(ConflictPolicy::Skip);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.pending_transfer = None;
+        } else if let Some(policy) = choice {
+            if let Some(pending) = self.pending_transfer.take() {
+                self.run_transfer(pending, policy);
+            }
+        }
+    }
+
+    fn is_document_modified(&self, document: Document) -> bool {
+        match document {
+            Document::Scene => self.scene_viewer.modified,
+            Document::ToyBox => self.toy_box.is_modified(),
+        }
+    }
+
+    fn document_name(&self, document: Document) -> String {
+        match document {
+            Document::Scene => self.selected_file.as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Scene".to_string()),
+            Document::ToyBox => self.toy_box.source.as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| format!("{} (Toy Box)", n.to_string_lossy()))
+                .unwrap_or_else(|| "Toy Box level".to_string()),
+        }
+    }
+
+    // Returns false when the user cancelled the dialog or the save failed
+    fn save_document_as(&mut self, document: Document) -> bool {
+        match document {
+            Document::Scene => self.save_scene_as(),
+            Document::ToyBox => self.save_toy_box_as(),
+        }
+    }
+
+    fn discard_document(&mut self, document: Document) {
+        println!("Discarding unsaved changes to {}", self.document_name(document));
+        match document {
+            Document::Scene => self.scene_viewer.modified = false,
+            Document::ToyBox => self.toy_box.discard_changes(),
+        }
+    }
+
+    // Which of `documents` have changes that haven't been written to disk
+    fn unsaved_documents(&self, documents: &[Document]) -> Vec<Document> {
+        documents.iter().copied().filter(|d| self.is_document_modified(*d)).collect()
+    }
+
+    fn has_unsaved_changes(&self) -> bool {
+        !self.unsaved_documents(&Document::ALL).is_empty()
+    }
+
+    // Holds `action` behind the unsaved changes prompt if it would close a modified document
+    fn prompt_if_unsaved(&mut self, action: PendingAction) -> bool {
+        if self.unsaved_documents(action.documents()).is_empty() {
+            return false;
+        }
+        self.unsaved_prompt = Some(action);
+        true
+    }
+
+    // Returns false when the user cancelled the dialog or the save failed
+    fn save_scene_as(&mut self) -> bool {
+        let default_name = self.selected_file.as_ref()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .unwrap_or("scene.oct")
+            .to_string();
+        let Some(save_path) = rfd::FileDialog::new()
+            .set_title("Save modified scene")
+            .set_file_name(&default_name)
+            .add_filter("Scene files", &["oct", "bent"])
+            .save_file()
+            .and_then(|p| self.write_guard().resolve(&p))
+            .and_then(|p| self.resolve_write_target(&p)) else {
+            return false;
+        };
+
+        match self.scene_viewer.save_scene_file(&save_path) {
+            Ok(()) => {
+                self.scene_viewer.modified = false;
+                true
+            }
+            Err(e) if e.downcast_ref::<std::io::Error>().map_or(false, game_lock::is_in_use_error) => {
+                eprintln!("Failed to save scene: {} is open in another program, most likely the game", save_path.display());
+                false
+            }
+            Err(e) if e.downcast_ref::<std::io::Error>().map_or(false, elevation::is_access_denied) => {
+                eprintln!("Failed to save scene: writing {} needs administrator rights", save_path.display());
+                false
+            }
+            Err(e) => {
+                eprintln!("Failed to save scene: {}", e);
+                false
+            }
+        }
+    }
+
+    fn run_pending_action(&mut self, action: PendingAction, ctx: &egui::Context) {
+        match action {
+            PendingAction::OpenFile(path, kind) => {
+                self.selected_file = Some(path.clone());
+                self.selected_files = [path.clone()].into_iter().collect();
+                self.selection_anchor = Some(path.clone());
+                self.open_file_as(&path, kind, ctx);
+            }
+            PendingAction::CloseSceneViewer => self.execute_command(AppCommand::CloseSceneViewer, ctx),
+            PendingAction::SwitchGame => self.execute_command(AppCommand::SwitchGame, ctx),
+            PendingAction::OpenToyBoxLevel(path) => self.open_toy_box_level(&path),
+            PendingAction::Exit => {
+                self.allow_close = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    fn show_unsaved_prompt(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if !self.allow_close && self.has_unsaved_changes() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                self.unsaved_prompt = Some(PendingAction::Exit);
+            } else if !self.close_with_queued_writes() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                self.allow_close = false;
+            }
+        }
+
+        let Some(action) = self.unsaved_prompt.clone() else {
+            return;
+        };
+
+        let documents = self.unsaved_documents(action.documents());
+        let mut choice = None;
+        egui::Window::new("Unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("These documents have unsaved changes:");
+                for document in &documents {
+                    ui.label(format!("● {}", self.document_name(*document)));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save...").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.unsaved_prompt = None;
+                    }
+                });
+            });
+
+        match choice {
+            // Stops at the first cancelled or failed save, leaving the prompt open for the rest
+            Some(true) if !documents.iter().all(|d| self.save_document_as(*d)) => {}
+            Some(save) => {
+                if !save {
+                    for document in documents {
+                        self.discard_document(document);
+                    }
+                }
+                self.unsaved_prompt = None;
+                self.run_pending_action(action, ctx);
+            }
+            None => {}
+        }
+    }
+
+    fn show_command_palette(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::P)) {
+            self.command_palette.toggle();
+        }
+
+        let items: Vec<PaletteItem<AppCommand>> = AppCommand::ALL
+            .iter()
+            .map(|&command| PaletteItem {
+                command,
+                label: command.label(),
+                enabled: self.command_enabled(command),
+            })
+            .collect();
+        if let Some(command) = self.command_palette.show(ctx, &items) {
+            self.execute_command(command, ctx);
+        }
+    }
+
+    fn copy_selection_to_folder(&mut self, keep_structure: bool, ctx: &egui::Context) {
+        let Some(output_dir) = rfd::FileDialog::new()
+            .set_title("Select output folder")
+            .pick_folder()
+            .and_then(|p| self.write_guard().resolve(&p))
+        else {
+            return;
+        };
+
+        // Folders and unextracted archive entries stay in so the result can say they were skipped
+        let mut files: Vec<PathBuf> = self.selected_files.iter().cloned().collect();
+        files.sort();
+        self.run_operation(Operation::CopyFiles { files, output_dir, keep_structure }, ctx);
+    }
+
+    // Where each file lands in `output_dir`
+    fn copy_targets(files: &[PathBuf], output_dir: &Path, keep_structure: bool) -> Vec<PathBuf> {
+        // Keep paths relative to the deepest folder shared by the whole selection
+        let common_root = if keep_structure {
+            files.iter()
+                .filter_map(|p| p.parent())
+                .fold(None, |root: Option<PathBuf>, parent| match root {
+                    None => Some(parent.to_path_buf()),
+                    Some(root) => Some(root.ancestors()
+                        .find(|a| parent.starts_with(a))
+                        .map(|a| a.to_path_buf())
+                        .unwrap_or_default()),
+                })
+        } else {
+            None
+        };
+
+        files.iter()
+            .map(|file| {
+                let relative = match &common_root {
+                    Some(root) => file.strip_prefix(root).unwrap_or(file.as_path()).to_path_buf(),
+                    None => PathBuf::from(file.file_name().unwrap_or_default()),
+                };
+                output_dir.join(relative)
+            })
+            .collect()
+    }
+
+    fn copy_files_to_folder(files: &[PathBuf], output_dir: &Path, keep_structure: bool) -> String {
+        let (files, skipped): (Vec<PathBuf>, Vec<PathBuf>) = files.iter().cloned().partition(|p| p.is_file());
+        for path in &skipped {
+            println!("Skipped {}, it isn't a loose file", path.display());
+        }
+        let mut copied = 0;
+        for (file, target) in files.iter().zip(Self::copy_targets(&files, output_dir, keep_structure)) {
+            if let Some(parent) = target.parent() {
+                if let Err(e) = fs::create_dir_all(parent) {
+                    eprintln!("Failed to create {}: {}", parent.display(), e);
+                    continue;
+                }
+            }
+
+            match fs::copy(file, &target) {
+                Ok(_) => copied += 1,
+                Err(e) => eprintln!("Failed to copy {}: {}", file.display(), e),
             }
         }
-        count
+
+        println!("Copied {} files to {}", copied, output_dir.display());
+        if skipped.is_empty() {
+            format!("Copied {} of {} files", copied, files.len())
+        } else {
+            format!("Copied {} of {} files, skipped {} that aren't loose files", copied, files.len(), skipped.len())
+        }
     }
 
-    fn handle_model_file_selection(&mut self, file_path: &PathBuf, ctx: &egui::Context) {
-        println!("File selected: {}", file_path.display());
-        
-        // Clear scene viewer when non-scene files are selected
-        if let Some(extension) = file_path.extension().and_then(|e| e.to_str()) {
-            if !extension.eq_ignore_ascii_case("oct") {
-                self.show_scene_viewer = false;
-                self.scene_viewer.clear();
-            } else {
-                // For .oct files, automatically try to find and load corresponding .bent file
-                let bent_path = SceneFileHandler::find_corresponding_bent_file(file_path);
-                if let Some(bent_path) = bent_path {
-                    println!("Found corresponding .bent file: {}", bent_path.display());
-                    if let Err(e) = self.scene_viewer.load_bent_file(&bent_path) {
-                        println!("Failed to load .bent file: {}", e);
-                    } else {
-                        println!("Successfully loaded animation data from .bent file");
+    fn export_for_blender(&mut self, ctx: &egui::Context) {
+        let Some((model, _)) = self.model_files.clone() else {
+            return;
+        };
+        let Some(output_dir) = rfd::FileDialog::new()
+            .set_title("Export for Blender")
+            .pick_folder()
+            .and_then(|p| self.write_guard().resolve(&p))
+        else {
+            return;
+        };
+        self.run_operation(Operation::ExportForBlender { model, output_dir }, ctx);
+    }
+
+    // Where a bundled file came from, so artists can trace it back to the game data
+    fn bundle_source_record(&self, path: &Path) -> serde_json::Value {
+        let data = fs::read(long_path(path)).unwrap_or_default();
+        let mut crc = flate2::Crc::new();
+        crc.update(&data);
+        serde_json::json!({
+            "path": path.display().to_string(),
+            // Extracted files live under temp/<archive name>/<entry>
+            "archive_entry": path.strip_prefix(&self.temp_dir).ok().map(|p| p.to_string_lossy().replace('\\', "/")),
+            "size": data.len(),
+            "crc32": format!("{:08x}", crc.sum()),
+        })
+    }
+
+    // Converts the textures of the MTB next to `ibuf_path` to PNGs named by the material texture
+    // template, relative to `output_dir` where the .gltf goes, and wires diffuse and normal maps
+    // into a glTF material
+    fn export_model_material(&mut self, ibuf_path: &Path, name: &str, output_dir: &Path) -> MaterialExport {
+        // The material is expected next to the buffers with the same name
+        let stem = ibuf_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let mtb_path = ibuf_path.parent().and_then(|dir| paths::resolve_in(dir, &format!("{}.mtb", stem)));
+        let mut textures = Vec::new();
+        if let Some(mtb_path) = &mtb_path {
+            self.configure_texture_search(mtb_path);
+            match self.mtb_viewer.resolve_material(mtb_path) {
+                Ok(resolved) => textures = resolved,
+                Err(e) => eprintln!("Failed to read material {}: {}", mtb_path.display(), e),
+            }
+        }
+
+        let naming = self.naming();
+        let mut material = GltfMaterial { name: name.to_string(), ..Default::default() };
+        let mut records = Vec::new();
+        let mut used_names: HashMap<PathBuf, usize> = HashMap::new();
+        let mut exported = 0;
+        for (index, (texture_info, source)) in textures.iter().enumerate() {
+            let slot = texture_info.slot.as_deref().map(str::to_lowercase).unwrap_or_else(|| {
+                if index == 0 { "diffuse".to_string() } else { format!("texture{}", index) }
+            });
+
+            let written = source.as_ref().ok_or_else(|| "not found in any search path".to_string()).and_then(|source| {
+                let data = fs::read(long_path(source)).map_err(|e| e.to_string())?;
+                let (png, size) = jobs::texture_png_with_size(&data).map_err(|e| e.to_string())?;
+                let relative = naming::unique(naming.material_texture(ibuf_path, &texture_info.tbody_filename, &slot, size), &mut used_names);
+                paths::write_creating_dirs(&output_dir.join(&relative), &png).map_err(|e| e.to_string())?;
+                Ok(naming::to_uri(&relative))
+            });
+            let mut record = source.as_ref()
+                .map(|source| self.bundle_source_record(source))
+                .unwrap_or_else(|| serde_json::json!({}));
+            record["name"] = serde_json::json!(texture_info.tbody_filename);
+            record["slot"] = serde_json::json!(slot);
+            match written {
+                Ok(file_name) => {
+                    exported += 1;
+                    record["png"] = serde_json::json!(file_name);
+                    if slot.contains("normal") {
+                        material.normal.get_or_insert(file_name);
+                    } else if slot.contains("diffuse") || slot.contains("albedo") || slot.contains("color") {
+                        material.base_color.get_or_insert(file_name);
                     }
-                } else {
-                    println!("No corresponding .bent file found for: {}", file_path.display());
                 }
-                // Show scene viewer for .oct files
-                self.show_scene_viewer = true;
+                Err(e) => {
+                    eprintln!("Skipped texture {}: {}", texture_info.tbody_filename, e);
+                    record["error"] = serde_json::json!(e);
+                }
             }
+            records.push(record);
         }
-        
-        if let Some(extension) = file_path.extension().and_then(|e| e.to_str()) {
-            // Handle scene files (OCT files)
-            if extension.eq_ignore_ascii_case("oct") {
-                println!("Loading scene file: {}", file_path.display());
-                match std::fs::File::open(file_path) {
-                    Ok(mut file) => {
-                        if let Err(e) = self.scene_viewer.load_scene_file(&mut file) {
-                            eprintln!("Failed to load scene file: {}", e);
-                        } else {
-                            // Extract textures for supported games
-                            if let Some(game_type) = &self.state.selected_game {
-                                // Convert main GameType to scene GameType
-                                let scene_game_type = match game_type {
-                                    GameType::ToyShit3 => SceneGameType::ToyShit3,
-                                    GameType::Cars2Arcade => SceneGameType::Cars2Arcade,
-                                    GameType::Cars2TheVideoGame => SceneGameType::Cars2TheVideoGame,
-                                    GameType::DisneyInfinity30 => SceneGameType::DisneyInfinity30,
-                                    GameType::Cars3DrivenToWinXB1 => SceneGameType::Cars3DrivenToWinXB1,
-                                };
-                                if let Err(e) = self.scene_viewer.extract_textures(&scene_game_type) {
-                                    eprintln!("Failed to extract textures: {}", e);
-                                }
-                            }
-                            self.show_scene_viewer = true;
-                            println!("Scene file loaded successfully");
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to open scene file: {}", e);
-                    }
+
+        MaterialExport {
+            material: mtb_path.is_some().then_some(material),
+            mtb_path,
+            records,
+            exported,
+            total: textures.len(),
+        }
+    }
+
+    // Writes the loaded model as glTF with its material's textures as PNGs next to it, plus
+    // <name>_metadata.json listing the source files
+    fn write_blender_bundle(&mut self, output_dir: &Path) -> String {
+        let Some((ibuf_path, vbuf_path)) = self.model_files.clone() else {
+            return "Failed: no model loaded".to_string();
+        };
+        // The template may put the model in a folder of its own, textures and metadata go with it
+        let relative = self.naming().model(&ibuf_path);
+        let output_dir = &output_dir.join(relative.parent().unwrap_or(Path::new("")));
+        let name = naming::file_stem(&relative);
+        let material = self.export_model_material(&ibuf_path, &naming::file_stem(&ibuf_path), output_dir);
+
+        let Some(model) = self.model_viewer.current_model.as_ref() else {
+            return "Failed: no model loaded".to_string();
+        };
+        let gltf_path = match gltf_export::write_gltf(model, &name, material.material.as_ref(), output_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("Failed to write glTF: {}", e);
+                return format!("Failed: {}", e);
+            }
+        };
+
+        let metadata = serde_json::json!({
+            "name": name,
+            "exported_by": concat!("Tundra ", env!("CARGO_PKG_VERSION")),
+            "gltf": paths::display_name(&gltf_path),
+            "ibuf": self.bundle_source_record(&ibuf_path),
+            "vbuf": self.bundle_source_record(&vbuf_path),
+            "material": material.mtb_path.as_ref().map(|p| self.bundle_source_record(p)),
+            "textures": material.records,
+        });
+        let metadata_path = output_dir.join(format!("{}_metadata.json", name));
+        if let Err(e) = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&metadata_path, json).map_err(|e| e.to_string()))
+        {
+            eprintln!("Failed to write {}: {}", metadata_path.display(), e);
+        }
+
+        println!("Exported {} for Blender to {}", name, output_dir.display());
+        if material.mtb_path.is_none() {
+            format!("Wrote {} without textures, no {}.mtb found", gltf_path.display(), naming::file_stem(&ibuf_path))
+        } else {
+            format!("Wrote {} with {} of {} textures", gltf_path.display(), material.exported, material.total)
+        }
+    }
+
+    fn export_scene_gltf(&mut self, ctx: &egui::Context) {
+        let Some(scene) = self.selected_file.clone() else {
+            return;
+        };
+        let Some(output_dir) = rfd::FileDialog::new()
+            .set_title("Export scene as glTF")
+            .pick_folder()
+            .and_then(|p| self.write_guard().resolve(&p))
+        else {
+            return;
+        };
+        self.run_operation(Operation::ExportSceneGltf { scene, output_dir }, ctx);
+    }
+
+    // IBUFs with a VBUF next to them, keyed by lower-cased stem. Searches the archive the scene was
+    // extracted from, or the assets folder above it for loose files.
+    fn scene_mesh_index(&self, scene_path: &Path) -> HashMap<String, PathBuf> {
+        let root = scene_path.strip_prefix(&self.temp_dir).ok()
+            .and_then(|relative| relative.components().next())
+            .map(|first| self.temp_dir.join(first))
+            .or_else(|| scene_path.parent().and_then(|p| p.parent()).map(|p| p.to_path_buf()));
+        let Some(root) = root else {
+            return HashMap::new();
+        };
+
+        let mut meshes = HashMap::new();
+        for (path, _) in jobs::matching_files(&root, "**/*.ibuf") {
+            if path.with_extension("vbuf").exists() {
+                meshes.entry(scene_export::reference_stem(&path.to_string_lossy())).or_insert(path);
+            }
+        }
+        meshes
+    }
+
+    // Assembles the scene's mesh instances with their transforms into one glTF, converting each
+    // model's textures once
+    fn write_scene_gltf(&mut self, output_dir: &Path) -> String {
+        let (Some(scene_path), Some(scene)) = (self.selected_file.clone(), self.scene_viewer.current_scene.as_ref()) else {
+            return "Failed: no scene loaded".to_string();
+        };
+        let meshes = self.scene_mesh_index(&scene_path);
+        let instances = scene_export::collect_instances(scene, &meshes);
+        if instances.is_empty() {
+            return format!("No mesh instances found ({} models searched)", meshes.len());
+        }
+        let relative = self.naming().scene(&scene_path);
+        let output_dir = &output_dir.join(relative.parent().unwrap_or(Path::new("")));
+
+        let mut model_indices: HashMap<PathBuf, Option<usize>> = HashMap::new();
+        let mut loaded = Vec::new();
+        let mut failed = 0;
+        for instance in &instances {
+            if model_indices.contains_key(&instance.ibuf) {
+                continue;
+            }
+            let vbuf = instance.ibuf.with_extension("vbuf");
+            let mut viewer = ViewModel::ModelViewer::new();
+            viewer.layout = self.buffer_layout();
+            let index = match viewer.load_model_from_files(&instance.ibuf, &vbuf) {
+                Ok(()) => viewer.current_model.take().map(|model| {
+                    let name = instance.ibuf.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                    let material = self.export_model_material(&instance.ibuf, &name, output_dir);
+                    loaded.push((name, model, material.material));
+                    loaded.len() - 1
+                }),
+                Err(e) => {
+                    eprintln!("Failed to load {}: {}", instance.ibuf.display(), e);
+                    failed += 1;
+                    None
                 }
-                return;
+            };
+            model_indices.insert(instance.ibuf.clone(), index);
+        }
+
+        let models: Vec<GltfModel> = loaded.iter()
+            .map(|(name, model, material)| GltfModel { name: name.clone(), model, material: material.clone() })
+            .collect();
+        let nodes: Vec<GltfNode> = instances.iter()
+            .filter_map(|instance| {
+                let model = model_indices.get(&instance.ibuf).copied().flatten()?;
+                Some(GltfNode { name: instance.name.clone(), model, matrix: Some(instance.matrix) })
+            })
+            .collect();
+        let name = naming::file_stem(&relative);
+        match gltf_export::write_gltf_scene(&name, &models, &nodes, output_dir) {
+            Ok(gltf_path) => {
+                println!("Exported scene {} to {}", name, gltf_path.display());
+                format!("Wrote {} with {} instances of {} models ({} failed to load)", gltf_path.display(), nodes.len(), models.len(), failed)
             }
-                
-            // Handle model files
-            if extension.eq_ignore_ascii_case("ibuf") || extension.eq_ignore_ascii_case("vbuf") {
-                // Find the corresponding file
-                let base_name = file_path.with_extension("");
-                let other_extension = if extension.eq_ignore_ascii_case("ibuf") { "vbuf" } else { "ibuf" };
-                let other_file = base_name.with_extension(other_extension);
-                
-                println!("Looking for corresponding file: {}", other_file.display());
-                
-                if other_file.exists() {
-                    let (ibuf_path, vbuf_path) = if extension.eq_ignore_ascii_case("ibuf") {
-                        (file_path.clone(), other_file)
-                    } else {
-                        (other_file, file_path.clone())
-                    };
-                    
-                    println!("Loading model from:\n  IBUF: {}\n  VBUF: {}", 
-                        ibuf_path.display(), vbuf_path.display());
-                    
-                    match self.model_viewer.load_model_from_files(&ibuf_path, &vbuf_path) {
-                        Ok(_) => {
-                            println!("Successfully loaded model from {} and {}", 
-                                ibuf_path.display(), vbuf_path.display());
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to load model: {}", e);
-                        }
-                    }
-                } else {
-                    println!("Corresponding {} file not found: {}", other_extension, other_file.display());
-                    self.model_viewer.clear_model();
+            Err(e) => {
+                eprintln!("Failed to write glTF: {}", e);
+                format!("Failed: {}", e)
+            }
+        }
+    }
+
+    fn record_operation(&mut self, operation: Operation, result: String) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.history.record(format_timestamp(now), operation, result);
+    }
+
+    // Lists what a batch operation would write instead of running it. Single-file exports from the
+    // open viewers aren't planned and run as usual; returns whether the operation was handled.
+    fn plan_operation(&mut self, operation: &Operation) -> bool {
+        let mut report = DryRunReport::new(operation.describe());
+        match operation {
+            Operation::CopyFiles { files, output_dir, keep_structure } => {
+                let (files, skipped): (Vec<PathBuf>, Vec<PathBuf>) = files.iter().cloned().partition(|p| p.is_file());
+                for (file, target) in files.iter().zip(Self::copy_targets(&files, output_dir, *keep_structure)) {
+                    report.add(target, file.display().to_string());
+                }
+                for path in skipped {
+                    report.errors.push(format!("{}: skipped, it isn't a loose file", path.display()));
                 }
-                return;
             }
-            
-            // Handle MTB and TBODY files for Disney Infinity 3.0
-            if let Some(game_type) = &self.state.selected_game {
-                if matches!(game_type, GameType::DisneyInfinity30) {
-                    if extension.eq_ignore_ascii_case("mtb") {
-                        println!("Loading MTB file: {}", file_path.display());
-                        if let Err(e) = self.mtb_viewer.load_mtb_file(file_path, ctx) {
-                            eprintln!("Failed to load MTB file: {}", e);
-                        }
-                        return;
-                    } else if extension.eq_ignore_ascii_case("tbody") {
-                        println!("Loading TBODY file: {}", file_path.display());
-                        if let Err(e) = self.mtb_viewer.load_tbody_file(file_path, ctx) {
-                            eprintln!("Failed to load TBODY file: {}", e);
+            Operation::DumpDecryptedArchive { archive, output } => report.add(output.clone(), archive.display().to_string()),
+            Operation::ExportRawEntry { archive, entry, output } => {
+                report.add(output.clone(), format!("{} in {}", entry, archive.display()));
+                report.add(raw_entry::sidecar_path(output), "header fields");
+            }
+            Operation::ConvertEndianness { source, output, .. } => report.add(output.clone(), source.display().to_string()),
+            Operation::SchemaReport { report_path } => report.add(report_path.clone(), "scanned OCT/BENT files"),
+            Operation::CreateArchive { folder, output, .. } => report.add(output.clone(), folder.display().to_string()),
+            Operation::BatchSceneDump { output_dir } => {
+                self.spawn_scene_dump_plan(report, output_dir.clone());
+                return true;
+            }
+            Operation::ExportTextureSet { .. } | Operation::ExportForBlender { .. } | Operation::ExportSceneGltf { .. } => return false,
+        }
+        self.dry_run_results.push(report);
+        self.show_dry_run = true;
+        true
+    }
+
+    // Archives have to be listed to know their scenes, so this runs as a task like the dump itself
+    fn spawn_scene_dump_plan(&mut self, mut report: DryRunReport, output_dir: PathBuf) {
+        let Some(root) = self.file_tree.first().and_then(|e| e.path.parent()).map(|p| p.to_path_buf()) else {
+            return;
+        };
+        let mut files = Vec::new();
+        let mut archives = Vec::new();
+        Self::collect_scene_sources(&self.file_tree, &self.temp_dir, &mut files, &mut archives);
+        let game_type = self.state.selected_game.clone();
+        let results = self.dry_run_results.clone();
+
+        self.task_manager.spawn("Dry run: batch OCT → JSON", move |task| {
+            task.set_total(files.len() + archives.len());
+            for path in &files {
+                let relative = path.strip_prefix(&root).unwrap_or(path);
+                task.advance(relative.display().to_string());
+                report.add(PathBuf::from(format!("{}.json", output_dir.join(relative).display())), path.display().to_string());
+            }
+            for archive in &archives {
+                if task.is_cancelled() {
+                    break;
+                }
+                let relative_archive = archive.strip_prefix(&root).unwrap_or(archive);
+                task.advance(relative_archive.display().to_string());
+                match Self::list_archive_entries(game_type.as_ref(), archive) {
+                    Ok(entries) => {
+                        for entry in entries.iter().filter(|e| Self::is_scene_file_name(&e.name)) {
+                            let target = format!("{}.json", paths::archive_entry_path(&output_dir.join(relative_archive), &entry.name).display());
+                            report.add(PathBuf::from(target), format!("{} in {}", entry.name, relative_archive.display()));
                         }
-                        return;
                     }
+                    Err(e) => report.errors.push(format!("{}: {}", relative_archive.display(), e)),
                 }
             }
-        }
-        
-        // Clear both viewers if it's not a supported file type
-        self.model_viewer.clear_model();
-        self.mtb_viewer.clear();
+            let summary = report.summary();
+            results.push(report);
+            task.finish(summary);
+        });
+        self.show_tasks = true;
+        self.show_dry_run = true;
     }
 
-    fn show_file_tree_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        // Check if scan is complete
-        self.check_scan_completion();
-
-        // Show progress if scanning
-        if let Some(progress) = &self.scan_progress {
-            ui.heading("Scanning Files...");
-            ui.label(format!("Scanning: {}", progress.current_path.display()));
-            ui.label(format!("Elapsed: {:?}", progress.start_time.elapsed()));
-            ui.add(egui::Spinner::new().size(32.0));
-            ui.label("This may take a while for large directories...");
+    // Runs an operation whose inputs were already picked and records it in the history
+    fn run_operation(&mut self, operation: Operation, ctx: &egui::Context) {
+        if self.dry_run && self.plan_operation(&operation) {
             return;
         }
+        let result = match &operation {
+            Operation::CopyFiles { files, output_dir, keep_structure } => {
+                Self::copy_files_to_folder(files, output_dir, *keep_structure)
+            }
+            Operation::DumpDecryptedArchive { archive, output } => match self.resolve_write_target(output) {
+                Some(target) => Self::write_decrypted_archive(archive, &target),
+                None => format!("Cancelled: {} can't be written", output.display()),
+            },
+            Operation::ExportRawEntry { archive, entry, output } => match self.resolve_write_target(output) {
+                Some(target) => Self::write_raw_entry(self.state.selected_game.as_ref(), archive, entry, &target),
+                None => format!("Cancelled: {} can't be written", output.display()),
+            },
+            Operation::BatchSceneDump { output_dir } => self.spawn_batch_scene_dump(output_dir.clone()),
+            Operation::SchemaReport { report_path } => self.spawn_schema_report(report_path.clone()),
+            Operation::ConvertEndianness { source, output, .. } => Self::write_endian_copy(source, output),
+            Operation::CreateArchive { folder, output, settings } => match self.resolve_write_target(output) {
+                Some(target) => self.spawn_create_archive(folder.clone(), target, *settings),
+                None => format!("Cancelled: {} can't be written", output.display()),
+            },
+            Operation::ExportForBlender { model, output_dir } => {
+                if self.model_files.as_ref().map(|(ibuf, _)| ibuf) != Some(model) {
+                    self.selected_file = Some(model.clone());
+                    self.handle_model_file_selection(model, ctx);
+                }
+                if self.model_files.as_ref().map(|(ibuf, _)| ibuf) == Some(model) {
+                    self.write_blender_bundle(output_dir)
+                } else {
+                    format!("Failed: couldn't open {}", model.display())
+                }
+            }
+            Operation::ExportSceneGltf { scene, output_dir } => {
+                if self.selected_file.as_ref() != Some(scene) || !self.scene_viewer.has_scene_loaded() {
+                    self.selected_file = Some(scene.clone());
+                    self.handle_model_file_selection(scene, ctx);
+                }
+                if self.selected_file.as_ref() == Some(scene) && self.scene_viewer.has_scene_loaded() {
+                    self.write_scene_gltf(output_dir)
+                } else {
+                    format!("Failed: couldn't open {}", scene.display())
+                }
+            }
+            Operation::ExportTextureSet { mtb, output_dir } => {
+                if self.mtb_viewer.mtb_path() != Some(mtb.as_path()) {
+                    self.selected_file = Some(mtb.clone());
+                    self.handle_model_file_selection(mtb, ctx);
+                }
+                if self.mtb_viewer.mtb_path() == Some(mtb.as_path()) {
+                    // Recorded by the viewer once every texture has decoded
+                    self.mtb_viewer.export_when_ready(output_dir.clone());
+                    return;
+                }
+                format!("Failed: couldn't open {}", mtb.display())
+            }
+        };
+        self.record_operation(operation, result);
+    }
 
-        if self.file_tree.is_empty() {
-            ui.label("No files found");
+    // Repeats go through the write guard again since protection may have been turned on since
+    fn repeat_operation(&mut self, mut operation: Operation, ctx: &egui::Context) {
+        let Some(output) = self.write_guard().resolve(operation.output_mut()) else {
             return;
-        }
-
-        let mut entries_to_process = std::mem::take(&mut self.file_tree);
-        self.show_file_tree_internal(ui, &mut entries_to_process, ctx);
-        self.file_tree = entries_to_process;
+        };
+        *operation.output_mut() = output;
+        self.run_operation(operation, ctx);
     }
 
-    fn show_file_tree_internal(&mut self, ui: &mut egui::Ui, entries: &mut Vec<FileEntry>, ctx: &egui::Context) {
-        for entry in entries {
-            let display_name = entry.path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
+    fn show_favorites(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        if self.state.favorites.is_empty() {
+            return;
+        }
 
-            if entry.is_directory || entry.is_zip {
-                // Handle ZIP files
-                if entry.is_zip {
-                    let initially_open = self.expanded_folders.contains(&entry.path);
-                    
-                    // Show ZIP icon and name in a horizontal layout for ALL games
-                    ui.horizontal(|ui| {
-                        if let Some(zip_icon) = self.file_icons.get("zip") {
-                            egui::Image::new(zip_icon)
-                                .max_size(egui::Vec2::splat(16.0))
-                                .ui(ui);
-                        }
-                    
-                        // Only show dropdown for games that support ZIP browsing
-                        if let Some(game_type) = &self.state.selected_game {
-                            if game_type.supports_zip_browsing() {
-                                let response = egui::CollapsingHeader::new(&display_name)
-                                    .default_open(initially_open)
-                                    .show(ui, |ui| {
-                                        // Load ZIP contents if not already loaded
-                                        if !entry.zip_contents_loaded {
-                                            // Extract ZIP to temp directory and scan it
-                                            match self.extract_zip_to_temp(&entry.path) {
-                                                Ok(extract_dir) => {
-                                                    // Scan the extracted directory
-                                                    let cancel_flag = Arc::new(Mutex::new(false));
-                                                    let extracted_entries = Self::scan_directory_threaded(extract_dir, cancel_flag);
-                                                    
-                                                    // Add extracted entries as children
-                                                    for mut extracted_entry in extracted_entries {
-                                                        // Mark these as extracted files (not ZIPs)
-                                                        extracted_entry.is_zip = false;
-                                                        entry.children.push(extracted_entry);
-                                                    }
-                                                    
-                                                    entry.zip_contents_loaded = true;
-                                                    println!("ZIP contents loaded and extracted to temp directory");
-                                                }
-                                                Err(e) => {
-                                                    ui.colored_label(egui::Color32::RED, 
-                                                        format!("Failed to extract ZIP: {}", e));
-                                                }
-                                            }
-                                        }
-                                        
-                                        // Show ZIP contents
-                                        self.show_file_tree_internal(ui, &mut entry.children, ctx);
-                                    });
+        let mut to_remove = None;
+        egui::CollapsingHeader::new(format!("Favorites ({})", self.state.favorites.len()))
+            .id_source("favorites")
+            .show(ui, |ui| {
+                for (index, path) in self.state.favorites.clone().iter().enumerate() {
+                    let name = path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("Unknown");
+                    let is_selected = self.selected_file.as_ref() == Some(path);
+                    let response = ui.selectable_label(is_selected, name)
+                        .on_hover_text(path.display().to_string());
 
-                                if response.header_response.clicked() {
-                                    if self.expanded_folders.contains(&entry.path) {
-                                        self.expanded_folders.remove(&entry.path);
-                                    } else {
-                                        self.expanded_folders.insert(entry.path.clone());
-                                    }
-                                }
-                            } else {
-                                // For games that don't support ZIP browsing, just show the ZIP file as a regular file (non-expandable)
-                                let is_selected = self.selected_file.as_ref() == Some(&entry.path);
-                                if ui.selectable_label(is_selected, &display_name).clicked() {
-                                    self.selected_file = Some(entry.path.clone());
-                                    self.handle_model_file_selection(&entry.path, ctx);
-                                }
-                            }
+                    if response.clicked() {
+                        self.selected_file = Some(path.clone());
+                        self.handle_model_file_selection(path, ctx);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Remove from favorites").clicked() {
+                            to_remove = Some(index);
+                            ui.close_menu();
                         }
                     });
-                    continue;
                 }
+            });
 
-                // Regular directory (for all games)
-                let initially_open = self.expanded_folders.contains(&entry.path);
-                let response = egui::CollapsingHeader::new(&display_name)
-                    .default_open(initially_open)
-                    .show(ui, |ui| {
-                        self.show_file_tree_internal(ui, &mut entry.children, ctx);
-                    });
+        if let Some(index) = to_remove {
+            self.state.favorites.remove(index);
+            self.save_state();
+        }
+        ui.separator();
+    }
 
-                // Update expanded state based on user interaction
-                if response.header_response.clicked() {
-                    if self.expanded_folders.contains(&entry.path) {
-                        self.expanded_folders.remove(&entry.path);
+    // Ticked tags are on every selected file; unticking removes them from all of them
+    fn show_tag_menu(&mut self, ui: &mut egui::Ui) {
+        let selected: Vec<PathBuf> = self.selected_files.iter().cloned().collect();
+        let mut changed = false;
+        for tag in self.tags.known_tags() {
+            let mut on = selected.iter().all(|p| self.tags.has_tag(p, &tag));
+            if ui.checkbox(&mut on, &tag).changed() {
+                for path in &selected {
+                    if on {
+                        self.tags.add(path, &tag);
                     } else {
-                        self.expanded_folders.insert(entry.path.clone());
+                        self.tags.remove(path, &tag);
                     }
                 }
-            } else {
-                // File - selectable with icon
-                let is_selected = self.selected_file.as_ref() == Some(&entry.path);
-                
-                ui.horizontal(|ui| {
-                    // Show icon if available
-                    if let Some(icon) = self.get_file_icon(&entry.path) {
-                        egui::Image::new(icon)
-                            .max_size(egui::Vec2::splat(16.0))
-                            .ui(ui);
-                    } else {
-                        // Placeholder for files without icons
-                        ui.add_space(18.0);
-                    }
-                
-                    // Check if this file is from a ZIP extraction (in temp directory)
-                    let is_extracted_from_zip = entry.path.starts_with(&self.temp_dir);
-                
-                    // Files inside ZIPs or extracted from ZIPs get green text (only for games that support ZIP browsing)
-                    let should_be_green = if let Some(game_type) = &self.state.selected_game {
-                        game_type.supports_zip_browsing() && 
-                        (entry.path.components().any(|c| {
-                            if let std::path::Component::Normal(name) = c {
-                                if let Some(name_str) = name.to_str() {
-                                    return name_str.to_lowercase().ends_with(".zip");
-                                }
+                changed = true;
+            }
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut self.new_tag).hint_text("New tag").desired_width(100.0));
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("Add").clicked() || entered) && !self.new_tag.trim().is_empty() {
+                for path in &selected {
+                    self.tags.add(path, &self.new_tag);
+                }
+                self.new_tag.clear();
+                changed = true;
+            }
+        });
+        if changed {
+            self.tags.save(Path::new(tags::TAGS_PATH));
+        }
+    }
+
+    fn collect_query_matches(&self, entries: &[FileEntry], query: &Query, out: &mut Vec<PathBuf>) {
+        for entry in entries {
+            if !entry.is_directory && query.matches(&entry.display_name, entry.size, self.tags.tags(&entry.path)) {
+                out.push(entry.path.clone());
+            }
+            self.collect_query_matches(&entry.children, query, out);
+        }
+    }
+
+    // Saved queries shown as folders above the tree, filled from the scanned files and the
+    // archives opened so far
+    fn show_saved_queries(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let key = (self.tags.generation, self.tree_generation);
+        if self.query_results.as_ref().map_or(true, |(cached, results)| *cached != key || results.len() != self.tags.queries.len()) {
+            let results = self.tags.queries.iter()
+                .map(|saved| {
+                    Query::parse(&saved.query).map(|query| {
+                        let mut matches = Vec::new();
+                        self.collect_query_matches(&self.file_tree, &query, &mut matches);
+                        matches
+                    })
+                })
+                .collect();
+            self.query_results = Some((key, results));
+        }
+        // Taken out while drawing, since the rows borrow the rest of the app
+        let results = self.query_results.take().map(|(_, results)| results).unwrap_or_default();
+
+        let mut open = None;
+        let mut remove = None;
+        let mut save = false;
+        egui::CollapsingHeader::new(format!("Saved queries ({})", self.tags.queries.len()))
+            .id_source("saved_queries")
+            .show(ui, |ui| {
+                for (index, (saved, result)) in self.tags.queries.iter().zip(&results).enumerate() {
+                    let count = result.as_ref().map_or(0, Vec::len);
+                    let header = egui::CollapsingHeader::new(format!("🔍 {} ({})", saved.name, count))
+                        .id_source(("saved_query", index))
+                        .show(ui, |ui| match result {
+                            Ok(paths) if paths.is_empty() => {
+                                ui.weak("No matches");
                             }
-                            false
-                        }) || is_extracted_from_zip)
-                    } else {
-                        false
-                    };
-                
-                    if should_be_green {
-                        if ui.selectable_label(is_selected, egui::RichText::new(&display_name).color(egui::Color32::GREEN)).clicked() {
-                            self.selected_file = Some(entry.path.clone());
-                            self.handle_model_file_selection(&entry.path, ctx);
-                        }
-                    } else {
-                        if ui.selectable_label(is_selected, &display_name).clicked() {
-                            self.selected_file = Some(entry.path.clone());
-                            self.handle_model_file_selection(&entry.path, ctx);
+                            Ok(paths) => {
+                                let row_height = ui.spacing().interact_size.y;
+                                egui::ScrollArea::vertical().id_source(("saved_query_rows", index)).max_height(200.0).show_rows(ui, row_height, paths.len(), |ui, range| {
+                                    for path in &paths[range] {
+                                        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown");
+                                        let is_selected = self.selected_file.as_ref() == Some(path);
+                                        if ui.selectable_label(is_selected, name).on_hover_text(path.display().to_string()).clicked() {
+                                            open = Some(path.clone());
+                                        }
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::RED, e);
+                            }
+                        });
+                    header.header_response.on_hover_text(&saved.query).context_menu(|ui| {
+                        if ui.button("Remove query").clicked() {
+                            remove = Some(index);
+                            ui.close_menu();
                         }
-                    }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.new_query).hint_text("untagged ext:tbody size>1MB").desired_width(180.0))
+                        .on_hover_text(tags::QUERY_HELP);
+                    let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    save = ui.button("Save query").clicked() || entered;
                 });
+                if !self.new_query.trim().is_empty() {
+                    if let Err(e) = Query::parse(&self.new_query) {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                }
+            });
+        self.query_results = Some((key, results));
+
+        if save && Query::parse(&self.new_query).is_ok() {
+            let query = self.new_query.trim().to_string();
+            self.tags.queries.push(SavedQuery { name: query.clone(), query });
+            self.new_query.clear();
+            self.tags.generation += 1;
+            self.tags.save(Path::new(tags::TAGS_PATH));
+        }
+        if let Some(index) = remove {
+            self.tags.queries.remove(index);
+            self.tags.generation += 1;
+            self.tags.save(Path::new(tags::TAGS_PATH));
+        }
+        if let Some(path) = open {
+            let mut chain = Vec::new();
+            if Self::tree_ancestors(&self.file_tree, &path, &mut chain) {
+                self.reveal_in_tree(&chain);
             }
+            self.selected_file = Some(path.clone());
+            self.handle_model_file_selection(&path, ctx);
         }
+        ui.separator();
     }
 
 fn show_scene_viewer(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
@@ -1160,27 +7563,76 @@ fn show_scene_viewer(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         return;
     }
 
-    ui.heading("Scene Viewer");
+    // A dot marks unsaved changes, like most editors
+    let dirty = if self.scene_viewer.modified { " ●" } else { "" };
+    ui.heading(format!("Scene Viewer{}", dirty));
     ui.separator();
 
     // Scene tabs
     ui.horizontal(|ui| {
         ui.selectable_value(&mut self.scene_tabs, SceneTabs::SceneInfo, "Scene Info");
+        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Tree, "Tree");
         if self.scene_viewer.has_textures() {
             ui.selectable_value(&mut self.scene_tabs, SceneTabs::Textures, "Textures");
         }
-        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Animations, "Animations"); // Changed from Properties
+        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Instances, "Instances");
+        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Blobs, format!("Binary Blobs{}", dirty));
+        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Animations, "Animations"); // Changed from Properties
+        ui.selectable_value(&mut self.scene_tabs, SceneTabs::Minimap, "Minimap");
+        if self.state.selected_game == Some(GameType::DisneyInfinity30) {
+            ui.selectable_value(&mut self.scene_tabs, SceneTabs::FigureData, format!("Figure Data{}", dirty));
+        }
+        if matches!(self.state.selected_game, Some(GameType::Cars2TheVideoGame | GameType::Cars2Arcade | GameType::Cars3DrivenToWinXB1)) {
+            ui.selectable_value(&mut self.scene_tabs, SceneTabs::VehicleStats, format!("Vehicle Stats{}", dirty));
+        }
     });
 
     ui.separator();
 
     match self.scene_tabs {
         SceneTabs::SceneInfo => {
-            ui.label("Scene file loaded successfully");
-            if let Some(endian) = &self.scene_viewer.endian {
+            if self.scene_viewer.warnings.is_empty() {
+                ui.label("Scene file loaded successfully");
+            } else {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("Scene file loaded with {} warnings (some nodes may be missing)", self.scene_viewer.warnings.len()),
+                );
+                egui::CollapsingHeader::new("Warnings")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for warning in &self.scene_viewer.warnings {
+                            ui.label(warning);
+                        }
+                    });
+            }
+            if let Some(endian) = self.scene_viewer.endian {
                 ui.label(format!("Endian: {:?}", endian));
+
+                let target_name = match endian {
+                    binrw::Endian::Little => "big",
+                    binrw::Endian::Big => "little",
+                };
+                ui.horizontal(|ui| {
+                    if let Some(scene_path) = self.selected_file.clone() {
+                        if ui.button(format!("Convert to {} endian...", target_name)).clicked() {
+                            self.convert_scene_endianness(&scene_path, target_name, ctx);
+                        }
+                    }
+                    if let Some(bent_path) = self.scene_viewer.get_bent_file_path().cloned() {
+                        if ui.button("Convert .bent...").clicked() {
+                            self.convert_scene_endianness(&bent_path, target_name, ctx);
+                        }
+                    }
+                });
             }
             ui.label(format!("Extracted textures: {}", self.scene_viewer.extracted_textures.len()));
+            if ui.button(AppCommand::ExportSceneGltf.label())
+                .on_hover_text("Places the meshes the scene references at their transforms, for Blender, Godot or Unity")
+                .clicked()
+            {
+                self.execute_command(AppCommand::ExportSceneGltf, ctx);
+            }
             
             // Show supported game info
             ui.separator();
@@ -1212,15 +7664,326 @@ fn show_scene_viewer(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
                 ui.label("No textures extracted from this scene file");
             }
         }
+        SceneTabs::Tree => {
+            self.show_tree_tab(ui, ctx);
+        }
+        SceneTabs::Instances => {
+            self.show_instances_tab(ui);
+        }
+        SceneTabs::Blobs => {
+            self.show_blobs_tab(ui);
+        }
         SceneTabs::Animations => {
             self.show_animations_tab(ui, ctx);
         }
+        SceneTabs::Minimap => {
+            self.show_minimap_tab(ui, ctx);
+        }
+        SceneTabs::FigureData => {
+            if let Some(scene) = self.scene_viewer.current_scene.as_mut() {
+                if self.figure_editor.show_ui(ui, scene) {
+                    self.scene_viewer.modified = true;
+                }
+            }
+        }
+        SceneTabs::VehicleStats => {
+            if let Some(scene) = self.scene_viewer.current_scene.as_mut() {
+                if self.vehicle_stats.show_ui(ui, scene) {
+                    self.scene_viewer.modified = true;
+                }
+            }
+        }
     }
 
     ui.separator();
-    if ui.button("Close Scene Viewer").clicked() {
-        self.show_scene_viewer = false;
-        self.scene_viewer.clear();
+    if ui.button(AppCommand::CloseSceneViewer.label()).clicked() {
+        self.execute_command(AppCommand::CloseSceneViewer, ctx);
+    }
+}
+
+fn show_tree_tab(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+    let (Some(scene_path), Some(scene)) = (self.selected_file.clone(), self.scene_viewer.current_scene.as_ref()) else {
+        return;
+    };
+    let Some(action) = self.scene_tree.show_ui(ui, &scene_path, scene) else {
+        return;
+    };
+    let stem = naming::file_stem(&scene_path);
+    let (extension, filter) = match action {
+        SceneTreeAction::ExportSvg => ("svg", "SVG image"),
+        SceneTreeAction::ExportPng => ("png", "PNG image"),
+    };
+    let Some(path) = rfd::FileDialog::new()
+        .set_title("Export scene tree")
+        .set_file_name(format!("{}_tree.{}", stem, extension))
+        .add_filter(filter, &[extension])
+        .save_file()
+        .and_then(|p| self.write_guard().resolve(&p))
+    else {
+        return;
+    };
+    match action {
+        SceneTreeAction::ExportSvg => {
+            let title = scene_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let result = fs::write(long_path(&path), self.scene_tree.svg(&title, scene))
+                .map(|_| format!("Saved {}", path.display()))
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e));
+            match &result {
+                Ok(message) => println!("{}", message),
+                Err(error) => eprintln!("{}", error),
+            }
+            self.scene_tree.set_status(result);
+        }
+        SceneTreeAction::ExportPng => self.scene_tree.request_png(ctx, path),
+    }
+}
+
+// Loads every placed model in the background and flattens the scene into one top-down image
+fn start_minimap_render(&mut self, scene_path: PathBuf) {
+    let Some(scene) = self.scene_viewer.current_scene.as_ref() else {
+        return;
+    };
+    let meshes = self.scene_mesh_index(&scene_path);
+    let instances = scene_export::collect_instances(scene, &meshes);
+    let layout = self.buffer_layout();
+    let slot = self.minimap_result.clone();
+    self.minimap_rendering = true;
+    self.task_manager.spawn(format!("Minimap of {}", paths::display_name(&scene_path)), move |task| {
+        task.set_total(instances.len());
+        let mut models: HashMap<PathBuf, Option<ViewModel::Model>> = HashMap::new();
+        let mut triangles = Vec::new();
+        for instance in &instances {
+            if task.is_cancelled() {
+                break;
+            }
+            task.advance(instance.name.clone());
+            let model = models.entry(instance.ibuf.clone()).or_insert_with(|| {
+                let mut viewer = ViewModel::ModelViewer::new();
+                viewer.layout = layout;
+                match viewer.load_model_from_files(&instance.ibuf, &instance.ibuf.with_extension("vbuf")) {
+                    Ok(()) => viewer.current_model.take(),
+                    Err(e) => {
+                        task.add_error(format!("{}: {}", instance.ibuf.display(), e));
+                        None
+                    }
+                }
+            });
+            let Some(model) = model else {
+                continue;
+            };
+            for mesh in &model.meshes {
+                for corners in mesh.indices.chunks_exact(3) {
+                    let corner = |i: u16| mesh.vertices.get(i as usize).map(|v| minimap::transform(&instance.matrix, v.position));
+                    if let (Some(a), Some(b), Some(c)) = (corner(corners[0]), corner(corners[1]), corner(corners[2])) {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+        }
+        let result = if instances.is_empty() {
+            Err(format!("No mesh instances with a model were found ({} models searched)", meshes.len()))
+        } else {
+            minimap::render(&triangles, minimap::MINIMAP_SIZE).ok_or_else(|| "The placed models have no area seen from above".to_string())
+        };
+        let summary = match &result {
+            Ok(image) => format!("{}x{} from {} triangles", image.width(), image.height(), triangles.len()),
+            Err(e) => e.clone(),
+        };
+        *slot.lock().unwrap() = Some((scene_path, result));
+        task.finish(summary);
+    });
+}
+
+fn show_minimap_tab(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+    let Some(scene_path) = self.selected_file.clone() else {
+        return;
+    };
+    if let Some((path, result)) = self.minimap_result.lock().unwrap().take() {
+        self.minimap_rendering = false;
+        match result {
+            Ok(image) => {
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice());
+                let texture = ctx.load_texture("scene_minimap", color_image, Default::default());
+                self.minimap = Some((path, texture, image));
+            }
+            Err(e) => {
+                eprintln!("Minimap failed: {}", e);
+                self.minimap = None;
+            }
+        }
+    }
+
+    ui.horizontal(|ui| {
+        if self.minimap_rendering {
+            ui.spinner();
+            ui.label("Rendering...");
+        } else if ui.button("Render minimap").on_hover_text("Top-down view of the placed models, lighter where higher").clicked() {
+            self.start_minimap_render(scene_path.clone());
+        }
+        let current = self.minimap.as_ref().filter(|(path, _, _)| *path == scene_path);
+        if ui.add_enabled(current.is_some(), egui::Button::new("Export PNG...")).clicked() {
+            let default_name = format!("{}_minimap.png", naming::file_stem(&scene_path));
+            if let Some(path) = rfd::FileDialog::new()
+                .set_title("Export minimap")
+                .set_file_name(default_name)
+                .add_filter("PNG image", &["png"])
+                .save_file()
+                .and_then(|p| self.write_guard().resolve(&p))
+            {
+                if let Some((_, _, image)) = &self.minimap {
+                    match image.save(long_path(&path)) {
+                        Ok(()) => println!("Saved minimap to {}", path.display()),
+                        Err(e) => eprintln!("Failed to save {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+    });
+    ui.weak("Render geometry only; collision meshes aren't read yet.");
+    match self.minimap.as_ref().filter(|(path, _, _)| *path == scene_path) {
+        Some((_, texture, _)) => {
+            egui::ScrollArea::both().id_source("scene_minimap_scroll").show(ui, |ui| {
+                ui.add(egui::Image::new(texture).shrink_to_fit());
+            });
+        }
+        None if !self.minimap_rendering => {
+            ui.label("Render the minimap to see the scene from above.");
+        }
+        None => {}
+    }
+}
+
+// Mesh instances the scene places; clicking one reveals its model in the file tree
+fn show_instances_tab(&mut self, ui: &mut egui::Ui) {
+    let (Some(scene_path), Some(scene)) = (self.selected_file.clone(), self.scene_viewer.current_scene.as_ref()) else {
+        return;
+    };
+    if self.scene_instances.as_ref().map_or(true, |(path, _)| *path != scene_path) {
+        let meshes = self.scene_mesh_index(&scene_path);
+        self.scene_instances = Some((scene_path.clone(), scene_export::collect_instances(scene, &meshes)));
+    }
+    let Some((_, instances)) = &self.scene_instances else {
+        return;
+    };
+    if instances.is_empty() {
+        ui.label("No instances with a model in the scene's archive or assets folder were found.");
+        return;
+    }
+
+    ui.label(format!("{} mesh instances", instances.len()));
+    let mut picked = None;
+    let row_height = ui.spacing().interact_size.y;
+    egui::ScrollArea::vertical().id_source("scene_instances_scroll").show_rows(ui, row_height, instances.len(), |ui, range| {
+        for instance in &instances[range] {
+            ui.horizontal(|ui| {
+                let current = self.selection_bus.is_current(&instance.ibuf);
+                if ui.selectable_label(current, &instance.name).on_hover_text("Show the model in the file tree").clicked() {
+                    picked = Some(instance.ibuf.clone());
+                }
+                ui.weak(instance.ibuf.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+            });
+        }
+    });
+    if let Some(path) = picked {
+        self.selection_bus.publish(&path);
+    }
+}
+
+fn show_blobs_tab(&mut self, ui: &mut egui::Ui) {
+    let blobs = self.scene_viewer.binary_blobs();
+    if blobs.is_empty() {
+        ui.label("This scene has no binary payloads.");
+        return;
+    }
+
+    ui.label(format!("{} binary payloads", blobs.len()));
+    egui::ScrollArea::vertical()
+        .id_source("scene_blobs_scroll")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for (path, size) in &blobs {
+                let (kind, _) = self.scene_viewer.binary_blob(path)
+                    .map(read_scene::guess_payload_type)
+                    .unwrap_or(("Unknown", "bin"));
+                let label = format!("{} ({}, {})", read_scene::scene_path_label(path), format_size(*size as u64), kind);
+                let selected = self.selected_blob.as_ref() == Some(path);
+                if ui.selectable_label(selected, label).clicked() {
+                    self.selected_blob = Some(path.clone());
+                }
+            }
+        });
+
+    ui.separator();
+
+    let Some(path) = self.selected_blob.clone() else {
+        ui.label("Select a payload to preview it.");
+        return;
+    };
+    let Some(data) = self.scene_viewer.binary_blob(&path) else {
+        self.selected_blob = None;
+        return;
+    };
+
+    let (kind, extension) = read_scene::guess_payload_type(data);
+    ui.label(format!("{}: {} bytes, looks like {}", read_scene::scene_path_label(&path), data.len(), kind));
+
+    // Hex preview of the first 256 bytes
+    let mut preview = String::new();
+    for (row, chunk) in data.chunks(16).take(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk.iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        preview.push_str(&format!("{:08x}  {:<47}  {}\n", row * 16, hex.join(" "), ascii));
+    }
+    egui::ScrollArea::horizontal()
+        .id_source("scene_blob_hex")
+        .show(ui, |ui| {
+            ui.monospace(preview);
+        });
+
+    let mut replacement = None;
+    ui.horizontal(|ui| {
+        if ui.button("Export blob...").clicked() {
+            let default_name = format!("{}.{}", path.last().map(|(k, _)| k.as_str()).unwrap_or("blob"), extension);
+            if let Some(save_path) = rfd::FileDialog::new()
+                .set_title("Export binary payload")
+                .set_file_name(&default_name)
+                .save_file()
+                .and_then(|p| self.write_guard().resolve(&p))
+            {
+                match fs::write(&save_path, data) {
+                    Ok(()) => println!("Exported {} bytes to {}", data.len(), save_path.display()),
+                    Err(e) => eprintln!("Failed to export payload: {}", e),
+                }
+            }
+        }
+
+        if ui.button("Replace blob from file...").clicked() {
+            if let Some(open_path) = rfd::FileDialog::new()
+                .set_title("Replace binary payload")
+                .pick_file()
+            {
+                match fs::read(&open_path) {
+                    Ok(new_data) => replacement = Some(new_data),
+                    Err(e) => eprintln!("Failed to read {}: {}", open_path.display(), e),
+                }
+            }
+        }
+    });
+
+    if let Some(new_data) = replacement {
+        if let Err(e) = self.scene_viewer.replace_binary_blob(&path, new_data) {
+            eprintln!("Failed to replace payload: {}", e);
+        }
+    }
+
+    if self.scene_viewer.modified {
+        ui.colored_label(egui::Color32::YELLOW, "Scene has unsaved payload changes");
+        if ui.button("Save scene as...").clicked() {
+            self.save_scene_as();
+        }
     }
 }
 
@@ -1329,9 +8092,97 @@ fn show_animations_tab(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
             ui.label("No animation data available.");
             ui.label("Animation data is loaded from .bent files with the same name as the .oct file.");
         }
+
+        self.show_animation_curves(ui);
     });
 }
 
+fn show_animation_curves(&mut self, ui: &mut egui::Ui) {
+    let curves = self.scene_viewer.animation_curves().to_vec();
+    if curves.is_empty() {
+        return;
+    }
+
+    ui.separator();
+    egui::CollapsingHeader::new(format!("Keyframe Curves ({})", curves.len()))
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("Show all").clicked() {
+                    self.hidden_curves.clear();
+                }
+                if ui.small_button("Hide all").clicked() {
+                    self.hidden_curves = curves.iter().map(|c| c.name.clone()).collect();
+                }
+            });
+
+            egui::ScrollArea::vertical()
+                .id_source("curve_toggles_scroll")
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    for curve in &curves {
+                        let mut visible = !self.hidden_curves.contains(&curve.name);
+                        if ui.checkbox(&mut visible, format!("{} ({} keys)", curve.name, curve.points.len())).changed() {
+                            if visible {
+                                self.hidden_curves.remove(&curve.name);
+                            } else {
+                                self.hidden_curves.insert(curve.name.clone());
+                            }
+                        }
+                    }
+                });
+
+            egui_plot::Plot::new("animation_curves_plot")
+                .legend(egui_plot::Legend::default())
+                .height(250.0)
+                .x_axis_label("Time")
+                .show(ui, |plot_ui| {
+                    for curve in curves.iter().filter(|c| !self.hidden_curves.contains(&c.name)) {
+                        let points: egui_plot::PlotPoints = curve.points.clone().into();
+                        plot_ui.line(egui_plot::Line::new(points).name(&curve.name));
+                    }
+                });
+        });
+}
+
+fn convert_scene_endianness(&mut self, source_path: &Path, target_name: &str, ctx: &egui::Context) {
+    let extension = source_path.extension().and_then(|e| e.to_str()).unwrap_or("oct").to_string();
+    let default_name = format!(
+        "{}_{}.{}",
+        source_path.file_stem().and_then(|s| s.to_str()).unwrap_or("scene"),
+        target_name,
+        extension
+    );
+
+    if let Some(output_path) = rfd::FileDialog::new()
+        .set_title(&format!("Save {} endian copy", target_name))
+        .set_file_name(&default_name)
+        .add_filter("Octane file", &[extension.as_str()])
+        .save_file()
+        .and_then(|p| self.write_guard().resolve(&p))
+    {
+        let operation = Operation::ConvertEndianness {
+            source: source_path.to_path_buf(),
+            output: output_path,
+            target_name: target_name.to_string(),
+        };
+        self.run_operation(operation, ctx);
+    }
+}
+
+fn write_endian_copy(source_path: &Path, output_path: &Path) -> String {
+    match SceneFileHandler::convert_endianness(source_path, output_path) {
+        Ok(endian) => {
+            println!("Saved {:?} endian copy to {}", endian, output_path.display());
+            format!("Saved as {:?} endian", endian)
+        }
+        Err(e) => {
+            eprintln!("Failed to convert {}: {}", source_path.display(), e);
+            format!("Failed: {}", e)
+        }
+    }
+}
+
 fn load_animation_file(&mut self, filename: &str, ctx: &egui::Context) {
     println!("Attempting to load animation file: {}", filename);
     
@@ -1387,7 +8238,11 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
 
     fn show_game_selection(&mut self, ui: &mut egui::Ui) {
         ui.heading("Tundra");
-        ui.label("Select the game you want to edit:");
+        if let Some(path) = self.pending_open.first() {
+            ui.label(format!("Select the game {} is from:", paths::display_name(path)));
+        } else {
+            ui.label("Select the game you want to edit:");
+        }
 
         for game_type in GameType::all() {
             let button_text = if let Some(path) = self.get_game_path(&game_type) {
@@ -1399,19 +8254,11 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
             if ui.button(&button_text).clicked() {
                 self.state.selected_game = Some(game_type.clone());
                 
-                if let Some(path) = self.get_game_path(&game_type) {
-                    // If we already have a valid path, go directly to editor
-                    if self.validate_executable(&game_type, &path) {
-                        if game_type != GameType::Cars3DrivenToWinXB1 {
-                            self.scan_assets_folder(&path);
-                        } else {
-                            self.scan_dtw_folder(&path);
-                        }
-                        self.state.current_step = AppStep::Editor;
-                    } else {
-                        // If path exists but is invalid, go to file selection
-                        self.state.current_step = AppStep::FileSelection;
-                    }
+                // If we already have a valid path or asset folder, go directly to editor. Paths
+                // from the command line open without an install too.
+                if self.start_game_scan(&game_type) || !self.pending_open.is_empty() {
+                    self.restore_layout(&game_type);
+                    self.state.current_step = AppStep::Editor;
                 } else {
                     // Otherwise, prompt for file selection
                     self.state.current_step = AppStep::FileSelection;
@@ -1438,19 +8285,10 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
             }
         };
 
-        // Check if we already have a valid executable for this game
-        if let Some(config) = self.state.game_configs.get(&game_type) {
-            if self.validate_executable(&game_type, &config.executable_path) {
-                // If we have a valid executable, automatically switch to editor
-                let path = config.executable_path.clone();
-                if game_type != GameType::Cars3DrivenToWinXB1 {
-                    self.scan_assets_folder(&path);
-                } else {
-                    self.scan_dtw_folder(&path);
-                }
-                self.state.current_step = AppStep::Editor;
-                return;
-            }
+        // If we already have a valid executable or asset folder, automatically switch to editor
+        if self.start_game_scan(&game_type) {
+            self.state.current_step = AppStep::Editor;
+            return;
         }
 
         ui.heading("Tundra");
@@ -1460,6 +8298,16 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
         if ui.button("Browse for executable...").clicked() {
             self.open_file_dialog();
         }
+        if ui.button("Browse asset folder directly...").on_hover_text("Open an extracted asset folder without an installed game").clicked() {
+            if let Some(folder) = rfd::FileDialog::new()
+                .set_title(&format!("Select {} asset folder", game_type.as_str()))
+                .pick_folder()
+            {
+                self.state.game_configs.entry(game_type.clone()).or_default().asset_folder = Some(folder);
+                self.save_state();
+                // Opens the editor on the next frame through the check above
+            }
+        }
 
         // Check if we have a config for this game type (even if invalid)
         if let Some(config) = self.state.game_configs.get(&game_type) {
@@ -1484,7 +8332,7 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
         }
     }
 
-    fn run_game(&self) {
+    fn run_game(&mut self) {
         if let Some(game_type) = &self.state.selected_game {
             if let Some(config) = self.state.game_configs.get(game_type) {
                 let executable_path = &config.executable_path;
@@ -1492,8 +8340,9 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
                 println!("Attempting to run game: {}", executable_path.display());
                 
                 match std::process::Command::new(executable_path).spawn() {
-                    Ok(_) => {
+                    Ok(child) => {
                         println!("Successfully launched game: {}", game_type.as_str());
+                        self.game_process.track(child);
                     }
                     Err(e) => {
                         eprintln!("Failed to launch game: {}", e);
@@ -1507,37 +8356,334 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
         }
     }
 
+    fn show_naming_options(&mut self, ui: &mut egui::Ui) {
+        let hover = naming::VARIABLES.iter().map(|(name, description)| format!("{{{}}}: {}", name, description)).collect::<Vec<_>>().join("\n");
+        let mut changed = false;
+        egui::CollapsingHeader::new("Export naming").id_source("export_naming").show(ui, |ui| {
+            ui.small("Where exporters put each file, relative to the folder picked; / makes folders");
+            let templates = &mut self.state.naming;
+            egui::Grid::new("naming_grid").num_columns(2).show(ui, |ui| {
+                for (label, template) in [
+                    ("Textures", &mut templates.texture),
+                    ("Material textures", &mut templates.material_texture),
+                    ("Models", &mut templates.model),
+                    ("Scenes", &mut templates.scene),
+                ] {
+                    ui.label(label);
+                    changed |= ui.add(egui::TextEdit::singleline(template).desired_width(280.0).font(egui::TextStyle::Monospace))
+                        .on_hover_text(&hover)
+                        .lost_focus();
+                    ui.end_row();
+                }
+            });
+            let example = self.naming().material_texture(Path::new("characters/mickey.ibuf"), "0a1b2c3d.tbody", "diffuse", (1024, 1024));
+            ui.small(format!("Material texture of characters/mickey: {}", naming::to_uri(&example)));
+            if ui.button("Reset").clicked() {
+                self.state.naming = NamingTemplates::default();
+                changed = true;
+            }
+        });
+        if changed {
+            self.save_state();
+        }
+    }
+
     fn show_options_menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.heading("Options");
+        ui.horizontal(|ui| {
+            if ui.button("Export settings...").on_hover_text("Game configurations, favorites, layouts and texture bindings").clicked() {
+                self.export_settings();
+            }
+            if ui.button("Import settings...").clicked() {
+                self.import_settings(ctx);
+            }
+        });
+        self.show_profiles_ui(ui, ctx);
+        ui.horizontal(|ui| {
+            ui.label("File associations:")
+                .on_hover_text(format!("Double-clicking .{} files opens them in Tundra", associations::EXTENSIONS.join(", .")));
+            if ui.button("Register").clicked() {
+                self.association_status = Some(
+                    std::env::current_exe().map_err(|e| e.to_string()).and_then(|exe| {
+                        let workdir = std::env::current_dir().map_err(|e| e.to_string())?;
+                        associations::register(&exe, &workdir)
+                    }),
+                );
+            }
+            if ui.button("Remove").clicked() {
+                self.association_status = Some(associations::unregister());
+            }
+        });
+        match &self.association_status {
+            Some(Ok(message)) => {
+                ui.small(message);
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            None => {}
+        }
         ui.separator();
         
         ui.label("Theme:");
+        let previous_theme = self.state.theme.clone();
+        let previous_accent = self.state.accent_color;
+        ui.horizontal_wrapped(|ui| {
+            for theme in Theme::PRESETS {
+                let label = theme.label();
+                ui.radio_value(&mut self.state.theme, theme, label);
+            }
+            if let Theme::Custom(_) = &self.state.theme {
+                let label = self.state.theme.label();
+                ui.radio_value(&mut self.state.theme, previous_theme.clone(), label);
+            }
+        });
         ui.horizontal(|ui| {
-            let previous_theme = self.state.theme.clone();
-            
-            ui.radio_value(&mut self.state.theme, Theme::Dark, "Dark");
-            ui.radio_value(&mut self.state.theme, Theme::Light, "Light");
-            ui.radio_value(&mut self.state.theme, Theme::System, "System");
-            
-            // Apply theme immediately if changed
-            if self.state.theme != previous_theme {
-                match self.state.theme {
-                    Theme::Dark => {
-                        ctx.set_visuals(egui::Visuals::dark());
+            if ui.button("Load theme file...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Load theme")
+                    .add_filter("Theme", &["json"])
+                    .pick_file()
+                {
+                    self.state.theme = Theme::Custom(path);
+                }
+            }
+            if ui.button("Save theme file...").on_hover_text("Writes the current colors as a starting point for a custom theme").clicked() {
+                self.save_theme_file(ctx);
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut use_accent = self.state.accent_color.is_some();
+            ui.checkbox(&mut use_accent, "Accent color");
+            if use_accent {
+                let mut accent = self.state.accent_color.unwrap_or([38, 139, 210]);
+                egui::color_picker::color_edit_button_srgb(ui, &mut accent);
+                self.state.accent_color = Some(accent);
+            } else {
+                self.state.accent_color = None;
+            }
+        });
+
+        // Preview changes live; the color picker saves on every drag step otherwise
+        if self.state.theme != previous_theme || self.state.accent_color != previous_accent {
+            self.apply_theme(ctx);
+            if self.state.theme != previous_theme || !ctx.input(|i| i.pointer.any_down()) {
+                self.save_state();
+            }
+        }
+        
+        ui.separator();
+        if ui.checkbox(&mut self.state.protect_game_dir, "Protect game folder")
+            .on_hover_text("Ask before writing into the game install; declined writes go to the workspace folder")
+            .changed()
+        {
+            self.save_state();
+        }
+        if self.state.protect_game_dir {
+            ui.small(format!("Workspace: {}", self.write_guard().workspace.display()));
+        }
+        ui.horizontal(|ui| {
+            ui.label("Backups kept:");
+            let response = ui.add(egui::DragValue::new(&mut self.state.backup_count).clamp_range(0..=safe_write::MAX_BACKUP_COUNT))
+                .on_hover_text("Overwritten textures, scenes and archives keep this many previous versions as .bak, .bak2... next to them");
+            if response.changed() {
+                safe_write::set_backup_count(self.state.backup_count);
+            }
+            if response.drag_stopped() || response.lost_focus() {
+                self.save_state();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.state.check_for_updates, "Check for updates on startup")
+                .on_hover_text("Looks up the latest release on GitHub")
+                .changed()
+            {
+                self.save_state();
+            }
+            if ui.button("Check now").clicked() {
+                self.updater.check(ctx, None);
+            }
+        });
+        if let Some(status) = self.updater.status() {
+            ui.small(status);
+        }
+
+        ui.horizontal(|ui| {
+            let mut changed = ui.checkbox(&mut self.state.remote_api, "Remote API")
+                .on_hover_text("Local HTTP server so scripts and other tools can read the scanned files")
+                .changed();
+            ui.label("Port:");
+            let response = ui.add(egui::DragValue::new(&mut self.state.remote_api_port).clamp_range(1024..=65535));
+            changed |= response.drag_stopped() || response.lost_focus();
+            if changed {
+                self.save_state();
+            }
+        });
+        if let Some(server) = &self.remote_server {
+            ui.small(format!("Listening on http://127.0.0.1:{}/api/status", server.port));
+        } else if let Some((_, error)) = &self.remote_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Frame rate cap:");
+            let response = ui.add(egui::DragValue::new(&mut self.state.max_fps)
+                .clamp_range(0..=240)
+                .custom_formatter(|value, _| if value == 0.0 { "Off".to_string() } else { format!("{} fps", value) }))
+                .on_hover_text("Limits how often the window redraws while something is animating");
+            if response.drag_stopped() || response.lost_focus() {
+                self.save_state();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let (count, used, hits, misses) = {
+                let cache = self.entry_cache.lock().unwrap();
+                (cache.len(), cache.used_bytes(), cache.hits, cache.misses)
+            };
+            ui.label(format!("Archive entry cache: {} entries, {} ({} hits, {} misses)", count, format_size(used as u64), hits, misses));
+            if ui.small_button("Clear").clicked() {
+                self.entry_cache.lock().unwrap().clear();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Memory budget:");
+            let response = ui.add(egui::DragValue::new(&mut self.state.memory_budget_mb)
+                .clamp_range(memory_guard::MIN_BUDGET_MB..=262144)
+                .speed(64)
+                .suffix(" MB"))
+                .on_hover_text("Archive entries that would take more than this are refused or written straight to disk instead of running out of memory");
+            if response.changed() {
+                self.apply_memory_budget();
+            }
+            if response.drag_stopped() || response.lost_focus() {
+                self.save_state();
+            }
+            let refused = memory_guard::refused();
+            ui.weak(format!("{} in use", format_size(memory_guard::reserved())));
+            if refused > 0 {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("{} refused", refused));
+            }
+        });
+
+        self.show_naming_options(ui);
+
+        let mut rescan = false;
+        if let Some(game_type) = self.state.selected_game.clone() {
+            if let Some(config) = self.state.game_configs.get_mut(&game_type) {
+                ui.separator();
+                ui.label(format!("Texture search paths ({}):", game_type.as_str()));
+                ui.small("Searched before the MTB folder, assets/textures and the source archive");
+
+                let mut remove = None;
+                for (index, path) in config.texture_search_paths.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.monospace(path.display().to_string());
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                }
+
+                let mut changed = false;
+                if let Some(index) = remove {
+                    config.texture_search_paths.remove(index);
+                    changed = true;
+                }
+                if ui.button("Add texture folder...").clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select texture folder")
+                        .pick_folder()
+                    {
+                        config.texture_search_paths.push(folder);
+                        changed = true;
                     }
-                    Theme::Light => {
-                        ctx.set_visuals(egui::Visuals::light());
+                }
+                ui.separator();
+                ui.label(format!("Extra folders ({}):", game_type.as_str()));
+                ui.small("Shown in the file tree next to the game's assets. Ignore takes space-separated globs, e.g. *.bak .git");
+                let mut remove = None;
+                egui::Grid::new("extra_roots").num_columns(3).show(ui, |ui| {
+                    for (index, root) in config.extra_roots.iter_mut().enumerate() {
+                        ui.monospace(root.path.display().to_string());
+                        let ignore = ui.add(egui::TextEdit::singleline(&mut root.ignore).hint_text("Ignore").desired_width(160.0));
+                        changed |= ignore.changed();
+                        rescan |= ignore.lost_focus();
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
                     }
-                    Theme::System => {
-                        // For System theme, we'd need to re-detect the system preference
-                        // For now, we'll just use dark as fallback
-                        ctx.set_visuals(egui::Visuals::dark());
+                });
+                if let Some(index) = remove {
+                    config.extra_roots.remove(index);
+                    changed = true;
+                    rescan = true;
+                }
+                if ui.button("Add folder...").clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select folder to show in the tree")
+                        .pick_folder()
+                    {
+                        config.extra_roots.push(ExtraRoot { path: folder, ignore: String::new() });
+                        changed = true;
+                        rescan = true;
                     }
                 }
-                self.save_state();
+                ui.separator();
+                ui.label(format!("File associations ({}):", game_type.as_str()));
+                ui.small("Checked before magic bytes and extensions. Magic is hex matched at the start of the file, e.g. 29 76 01 45");
+                let mut remove = None;
+                egui::Grid::new("file_associations").num_columns(4).show(ui, |ui| {
+                    ui.strong("Extension");
+                    ui.strong("Magic");
+                    ui.strong("Open as");
+                    ui.end_row();
+                    for (index, association) in config.file_associations.iter_mut().enumerate() {
+                        changed |= ui.add(egui::TextEdit::singleline(&mut association.extension).desired_width(60.0)).changed();
+                        let magic = ui.add(egui::TextEdit::singleline(&mut association.magic).desired_width(140.0));
+                        changed |= magic.changed();
+                        if !association.magic.trim().is_empty() && sniff::parse_magic(&association.magic).is_none() {
+                            magic.on_hover_text("Not valid hex, this rule never matches");
+                        }
+                        egui::ComboBox::from_id_source(("file_association_kind", index))
+                            .selected_text(association.kind.label())
+                            .show_ui(ui, |ui| {
+                                for kind in FileKind::ALL {
+                                    changed |= ui.selectable_value(&mut association.kind, kind, kind.label()).changed();
+                                }
+                            });
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = remove {
+                    config.file_associations.remove(index);
+                    changed = true;
+                }
+                if ui.button("Add association").clicked() {
+                    config.file_associations.push(FileAssociation {
+                        extension: "bin".to_string(),
+                        magic: String::new(),
+                        kind: FileKind::Scene,
+                    });
+                    changed = true;
+                }
+                ui.weak("Built in: OCT/BENT, DDS, TEXB (MTB), PK (ZIP) and RIFF by magic; .ibuf/.vbuf, .tbody, .mtb and text extensions by name");
+
+                if changed {
+                    self.save_state();
+                }
             }
-        });
-        
+            if rescan {
+                self.start_game_scan(&game_type);
+            }
+        }
+
         ui.separator();
         if ui.button("Close").clicked() {
             self.show_options = false;
@@ -1598,13 +8744,34 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
             
             ui.label(format!("Full path: {}", selected_path.display()));
             
-            if let Ok(metadata) = fs::metadata(selected_path) {
-                let file_size = metadata.len();
-                ui.label(format!("Size: {} bytes", file_size));
+            // Size and date come from the scan, the filesystem is only asked for files outside the tree
+            let scanned = Self::find_entry(&self.file_tree, selected_path).map(|e| (e.size, e.modified));
+            let details = scanned.or_else(|| fs::metadata(selected_path).ok().map(|m| (m.len(), modified_millis(&m))));
+            if let Some((file_size, modified)) = details {
+                ui.label(format!("Size: {} bytes ({})", file_size, format_size(file_size)));
+                if modified != 0 {
+                    ui.label(format!("Modified: {} UTC", format_timestamp(modified)));
+                }
                 
                 if let Some(extension) = selected_path.extension().and_then(|e| e.to_str()) {
                     ui.label(format!("Type: {} file", extension.to_uppercase()));
                 }
+                if let Some(kind) = self.selected_kind {
+                    ui.label(format!("Detected: {}", kind.label()));
+                }
+            }
+
+            // The split view shows the hex below instead
+            if self.selected_kind == Some(FileKind::Hex) && self.file_preview.is_some() {
+                if !self.state.panels.split {
+                    ui.separator();
+                    self.show_hex_preview(ui);
+                }
+            } else if let (Some(FileKind::Text), Some(preview)) = (self.selected_kind, &self.file_preview) {
+                ui.separator();
+                egui::ScrollArea::both().id_source("file_preview").show(ui, |ui| {
+                    ui.monospace(String::from_utf8_lossy(preview));
+                });
             }
         } else {
             ui.heading("Tundra");
@@ -1612,6 +8779,97 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
         }
     }
 
+    // Files opened before the split was turned on have no preview yet
+    fn load_split_preview(&mut self) {
+        if !self.state.panels.split || self.file_preview.is_some() {
+            return;
+        }
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        self.file_preview = read_preview(&path);
+        self.hex_template = self.file_preview.as_ref().and_then(|preview| {
+            self.binary_templates.iter().position(|t| !t.magic.is_empty() && preview.starts_with(&t.magic))
+        });
+        self.apply_binary_template();
+    }
+
+    fn reload_binary_templates(&mut self) {
+        let (templates, errors) = binary_template::load_all();
+        for error in &errors {
+            eprintln!("Failed to load binary template {}", error);
+        }
+        println!("Loaded {} binary templates", templates.len());
+        // Keep the same template selected if it's still there
+        let selected = self.hex_template.and_then(|i| self.binary_templates.get(i)).map(|t| t.name.clone());
+        self.hex_template = selected.and_then(|name| templates.iter().position(|t| t.name == name));
+        self.binary_templates = templates;
+        self.apply_binary_template();
+    }
+
+    fn apply_binary_template(&mut self) {
+        self.template_fields.clear();
+        self.template_error = None;
+        self.byte_fields.clear();
+        let (Some(template), Some(preview)) = (self.hex_template.and_then(|i| self.binary_templates.get(i)), &self.file_preview) else {
+            return;
+        };
+        let (fields, error) = template.apply(preview);
+        self.byte_fields = binary_template::byte_fields(&fields, preview.len());
+        self.template_fields = fields;
+        self.template_error = error;
+    }
+
+    fn show_hex_preview(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Template:");
+            let selected = self.hex_template.and_then(|i| self.binary_templates.get(i)).map_or("None", |t| t.name.as_str());
+            let mut choice = self.hex_template;
+            egui::ComboBox::from_id_source("hex_template")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut choice, None, "None");
+                    for (index, template) in self.binary_templates.iter().enumerate() {
+                        ui.selectable_value(&mut choice, Some(index), &template.name);
+                    }
+                });
+            if choice != self.hex_template {
+                self.hex_template = choice;
+                self.apply_binary_template();
+            }
+            if ui.button("Reload").on_hover_text(format!("Reads {}/*.{} again", binary_template::TEMPLATE_DIR, binary_template::TEMPLATE_EXTENSION)).clicked() {
+                self.reload_binary_templates();
+            }
+            if ui.button("Open folder").clicked() {
+                if let Err(e) = fs::create_dir_all(binary_template::TEMPLATE_DIR) {
+                    eprintln!("Failed to create {}: {}", binary_template::TEMPLATE_DIR, e);
+                }
+                if let Err(e) = open_in_default_app(Path::new(binary_template::TEMPLATE_DIR)) {
+                    eprintln!("Failed to open {}: {}", binary_template::TEMPLATE_DIR, e);
+                }
+            }
+        });
+        if let Some(error) = &self.template_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if !self.template_fields.is_empty() {
+            egui::CollapsingHeader::new(format!("Fields ({})", self.template_fields.len()))
+                .default_open(true)
+                .show(ui, |ui| binary_template::show_fields(ui, &self.template_fields));
+        }
+
+        let Some(preview) = &self.file_preview else {
+            return;
+        };
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let rows = (preview.len() + 15) / 16;
+        egui::ScrollArea::both().id_source("file_preview").show_rows(ui, row_height, rows, |ui, range| {
+            for row in range {
+                ui.label(binary_template::hex_row(ui, preview, row, &self.byte_fields));
+            }
+        });
+    }
+
     fn show_editor(&mut self, ctx: &egui::Context) {
         // Check scan completion
         self.check_scan_completion();
@@ -1634,20 +8892,33 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
         }
 
         // Use SidePanel for the file list to ensure it takes full height
-        egui::SidePanel::left("file_panel")
-            .resizable(false)
-            .default_width(300.0)
+        let file_panel = egui::SidePanel::left("file_panel")
+            .resizable(true)
+            .default_width(self.state.panels.file_panel_width)
+            .width_range(200.0..=800.0)
             .show(ctx, |ui| {
-                ui.heading("File System");
+                ui.horizontal(|ui| {
+                    ui.heading("File System");
+                    if self.selected_files.len() > 1 {
+                        ui.label(format!("({} selected)", self.selected_files.len()));
+                        if ui.small_button("Clear").clicked() {
+                            self.selected_files.clear();
+                            self.selection_anchor = None;
+                        }
+                    }
+                });
                 
                 // Show current game info
                 if let Some(game_type) = &self.state.selected_game {
                     if let Some(config) = self.state.game_configs.get(game_type) {
                         ui.label(format!("Game: {}", game_type.as_str()));
-                        if let Some(parent_dir) = config.executable_path.parent() {
+                        if let Some(folder) = &config.asset_folder {
+                            ui.label(format!("Asset folder: {}", folder.display()));
+                        } else if let Some(parent_dir) = config.executable_path.parent() {
                             if game_type != &GameType::Cars3DrivenToWinXB1 {
-                                let assets_dir = parent_dir.join("assets");
-                                ui.label(format!("Assets: {}", assets_dir.display()));
+                                for root in &self.asset_roots {
+                                    ui.label(format!("Assets: {}", root.display()));
+                                }
                             } else {
                                 ui.label(format!("Directory: {}", parent_dir.display()));
                             }
@@ -1658,10 +8929,27 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
                 // Show file count if scan is complete
                 if self.scan_progress.is_none() && !self.file_tree.is_empty() {
                     let total_files = self.count_files(&self.file_tree);
-                    ui.label(format!("Total files: {}", total_files));
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Total files: {}", total_files));
+                        if ui.small_button(AppCommand::Rescan.label()).on_hover_text("Ignore the scan cache and rescan every folder").clicked() {
+                            self.execute_command(AppCommand::Rescan, ctx);
+                        }
+                    });
                 }
                 
                 ui.separator();
+
+                match self.ab_slots.show_ui(ui) {
+                    Some(AbAction::Compare) => self.compare_pinned(ctx),
+                    Some(AbAction::Open(path)) => {
+                        self.selected_file = Some(path.clone());
+                        self.handle_model_file_selection(&path, ctx);
+                    }
+                    None => {}
+                }
+
+                self.show_favorites(ui, ctx);
+                self.show_saved_queries(ui, ctx);
                 
                 if self.file_tree.is_empty() && self.scan_progress.is_none() {
                     ui.label("No files found");
@@ -1670,23 +8958,283 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
                             ui.label("Make sure there's an 'assets' folder next to the executable");
                         }
                     }
+                    if ui.button("Show setup check").clicked() {
+                        self.show_health = true;
+                    }
                 } else {
-                    egui::ScrollArea::vertical()
-                        .auto_shrink([false; 2])
-                        .show(ui, |ui| {
-                            self.show_file_tree_ui(ui, ctx);
-                        });
+                    self.show_file_tree_ui(ui, ctx);
                 }
             });
+        self.state.panels.file_panel_width = file_panel.response.rect.width();
 
         // Scene viewer panel (right side) - only show if a scene file is loaded
         if self.show_scene_viewer {
-            egui::SidePanel::right("scene_panel")
+            let scene_panel = egui::SidePanel::right("scene_panel")
                 .resizable(true)
-                .default_width(400.0)
+                .default_width(self.state.panels.scene_panel_width)
                 .show(ctx, |ui| {
                     self.show_scene_viewer(ui, ctx);
                 });
+            self.state.panels.scene_panel_width = scene_panel.response.rect.width();
+        }
+
+        if self.show_storage_analyzer {
+            let mut open = true;
+            egui::Window::new("Storage Analyzer")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(600.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    if ui.button("Refresh").clicked() && !self.storage_analyzer.is_running() {
+                        self.start_storage_analysis();
+                    }
+                    ui.separator();
+                    self.storage_analyzer.show_ui(ui);
+                });
+            if !open {
+                self.show_storage_analyzer = false;
+                self.storage_analyzer.clear();
+            }
+        }
+
+        self.show_archive_properties(ctx);
+
+        if self.show_jobs {
+            let mut open = true;
+            egui::Window::new("Jobs")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    self.show_jobs_ui(ui);
+                });
+            self.show_jobs = open;
+        }
+
+        if self.show_deploy {
+            let mut open = true;
+            egui::Window::new("Deploy workspace")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(700.0)
+                .show(ctx, |ui| {
+                    self.show_deploy_ui(ui);
+                });
+            self.show_deploy = open;
+        }
+
+        if self.show_diff {
+            let mut open = true;
+            egui::Window::new("Compare versions")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(800.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    self.show_diff_ui(ui, ctx);
+                });
+            self.show_diff = open;
+        }
+
+        if self.scene_diff.is_some() {
+            let mut open = true;
+            egui::Window::new("Compare scenes")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(900.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    self.show_scene_diff_ui(ui);
+                });
+            if !open {
+                self.scene_diff = None;
+            }
+        }
+
+        if self.show_text_browser {
+            let mut open = true;
+            egui::Window::new("Text browser")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(800.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    self.show_text_browser_ui(ui);
+                });
+            self.show_text_browser = open;
+        }
+
+        if self.show_toy_box {
+            let mut open = true;
+            egui::Window::new("Toy Box levels")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(600.0)
+                .show(ctx, |ui| {
+                    self.show_toy_box_ui(ui);
+                });
+            self.show_toy_box = open;
+        }
+
+        if self.show_tracks {
+            let mut open = true;
+            egui::Window::new("Tracks and levels")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(600.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    self.show_tracks_ui(ui, ctx);
+                });
+            self.show_tracks = open;
+        }
+
+        if self.show_usage {
+            let mut open = true;
+            egui::Window::new("Asset usage")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(700.0)
+                .show(ctx, |ui| {
+                    self.show_usage_ui(ui);
+                });
+            self.show_usage = open;
+        }
+
+        if self.mod_export.is_some() {
+            self.show_mod_export(ctx);
+        }
+
+        if self.show_mod_browser {
+            let mut open = true;
+            let mut action = None;
+            let game = self.state.selected_game.as_ref().map_or("", |g| g.as_str());
+            egui::Window::new("Mod browser")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(760.0)
+                .show(ctx, |ui| {
+                    action = self.mod_browser.show_ui(ui, &mut self.state.mod_index_url, game);
+                });
+            match action {
+                Some(BrowserAction::SaveUrl) => self.save_state(),
+                Some(BrowserAction::Install(entry)) => self.spawn_mod_download(entry),
+                None => {}
+            }
+            self.show_mod_browser = open;
+        }
+
+        if self.show_health {
+            let mut open = true;
+            let mut fix = None;
+            egui::Window::new("Game setup check")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(600.0)
+                .show(ctx, |ui| match &self.health_report {
+                    Some(report) => {
+                        fix = report.show_ui(ui);
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            if ui.button("Check again").clicked() {
+                                self.start_health_check();
+                            }
+                            if ui.button("Dismiss").clicked() {
+                                self.show_health = false;
+                            }
+                        });
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Checking...");
+                        });
+                    }
+                });
+            self.show_health &= open;
+            match fix {
+                Some(HealthFix::ChangeExecutable) => self.execute_command(AppCommand::ChangeExecutable, ctx),
+                Some(HealthFix::Options) => self.execute_command(AppCommand::Options, ctx),
+                None => {}
+            }
+        }
+
+        if self.ab_slots.result.is_some() {
+            let mut open = true;
+            egui::Window::new("Compare A and B")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(800.0)
+                .default_height(500.0)
+                .show(ctx, |ui| {
+                    self.ab_slots.show_result_ui(ui);
+                });
+            if !open {
+                self.ab_slots.result = None;
+            }
+        }
+
+        self.detached.show(ctx);
+
+        if self.show_dry_run {
+            let mut open = true;
+            egui::Window::new("Dry run results")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(700.0)
+                .show(ctx, |ui| {
+                    self.dry_run_results.show_ui(ui);
+                });
+            self.show_dry_run = open;
+        }
+
+        if self.show_analysis {
+            let mut open = true;
+            egui::Window::new("Analyze")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(700.0)
+                .show(ctx, |ui| {
+                    self.show_analysis_ui(ui);
+                });
+            self.show_analysis = open;
+        }
+
+        if self.show_history {
+            let mut open = true;
+            let mut repeat = None;
+            egui::Window::new("Operation history")
+                .open(&mut open)
+                .resizable(true)
+                .default_width(600.0)
+                .show(ctx, |ui| {
+                    repeat = self.history.show_ui(ui);
+                });
+            self.show_history = open;
+            if let Some(operation) = repeat {
+                self.repeat_operation(operation, ctx);
+            }
+        }
+
+        if self.show_diagnostics {
+            egui::Window::new("Diagnostics")
+                .open(&mut self.show_diagnostics)
+                .resizable(true)
+                .default_width(550.0)
+                .show(ctx, |ui| {
+                    self.diagnostics.show_ui(ui);
+                });
+        }
+
+        if self.show_tasks {
+            egui::Window::new("Tasks")
+                .open(&mut self.show_tasks)
+                .resizable(true)
+                .default_width(450.0)
+                .show(ctx, |ui| {
+                    self.task_manager.show_ui(ui);
+                });
         }
 
         // Show options window if needed
@@ -1702,26 +9250,53 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
 
         // The rest of the space is for the main area
         egui::CentralPanel::default().show(ctx, |ui| {
-            // Check if we're viewing a Disney Infinity model or textures
-            if let Some(game_type) = &self.state.selected_game {
-                if matches!(game_type, GameType::DisneyInfinity30) {
-                    // Check what type of content we should show
-                    if self.model_viewer.has_model() {
-                        // Show model viewer
-                        let available_size = ui.available_size();
-                        self.model_viewer.show_ui(ui, available_size);
-                    } else if self.mtb_viewer.has_content() {
-                        // Show MTB/TBODY viewer
-                        let available_size = ui.available_size();
-                        self.mtb_viewer.show_ui(ui, available_size, ctx);
-                    } else {
-                        // Show regular file info
-                        egui::ScrollArea::vertical().show(ui, |ui| {
-                            self.show_regular_file_info(ui);
-                        });
+            self.show_breadcrumbs(ui);
+
+            if self.state.panels.split && self.file_preview.is_some() {
+                let hex_panel = egui::TopBottomPanel::bottom("hex_split")
+                    .resizable(true)
+                    .default_height(self.state.panels.split_height)
+                    .height_range(80.0..=(ui.available_height() - 80.0).max(80.0))
+                    .show_inside(ui, |ui| {
+                        ui.add_space(4.0);
+                        self.show_hex_preview(ui);
+                    });
+                self.state.panels.split_height = hex_panel.response.rect.height();
+            }
+
+            // Check if we're viewing a model or textures
+            if self.state.selected_game.is_some() {
+                // Check what type of content we should show
+                if self.model_viewer.has_model() {
+                    // Show model viewer
+                    let available_size = ui.available_size();
+                    self.model_viewer.show_ui(ui, available_size);
+                } else if self.mtb_viewer.has_content() {
+                    // Show MTB/TBODY viewer
+                    let available_size = ui.available_size();
+                    self.mtb_viewer.write_guard = self.write_guard();
+                    self.mtb_viewer.naming = self.naming();
+                    self.mtb_viewer.selection = self.selection_bus.clone();
+                    self.mtb_viewer.show_ui(ui, available_size, ctx);
+                    if let Some(texture) = self.mtb_viewer.take_detach_request() {
+                        self.detached.open(ctx, texture);
+                    }
+                    if let Some((mtb, output_dir, result)) = self.mtb_viewer.take_finished_export() {
+                        self.record_operation(Operation::ExportTextureSet { mtb, output_dir }, result);
+                    }
+                    if let Some(tbody_filename) = self.mtb_viewer.match_request.take() {
+                        self.start_texture_match_search(tbody_filename);
+                    }
+                    if let Some((tbody_filename, path)) = self.mtb_viewer.take_bind_request() {
+                        if self.bind_mtb_texture(&tbody_filename, &path, ctx) {
+                            if let Some(config) = self.state.selected_game.clone().and_then(|g| self.state.game_configs.get_mut(&g)) {
+                                config.texture_bindings.insert(tbody_filename, path);
+                            }
+                            self.save_state();
+                        }
                     }
                 } else {
-                    // For other games, show regular file info
+                    // Show regular file info
                     egui::ScrollArea::vertical().show(ui, |ui| {
                         self.show_regular_file_info(ui);
                     });
@@ -1735,17 +9310,67 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
             
             // "Run Game", "Options", and "Change Game" buttons in bottom right - show them OVER the model viewer
             ui.with_layout(egui::Layout::bottom_up(egui::Align::RIGHT), |ui| {
-                if ui.button("Change Game").clicked() {
-                    self.state.current_step = AppStep::GameSelection;
-                    self.save_state();
+                if ui.button(AppCommand::SwitchGame.label()).clicked() {
+                    self.execute_command(AppCommand::SwitchGame, ctx);
                 }
                 
-                if ui.button("Options").clicked() {
-                    self.show_options = true;
+                if ui.button(AppCommand::Options.label()).clicked() {
+                    self.execute_command(AppCommand::Options, ctx);
                 }
+
+                ui.menu_button("Tools", |ui| {
+                    for command in [
+                        AppCommand::StorageAnalyzer,
+                        AppCommand::BatchSceneDump,
+                        AppCommand::SchemaReport,
+                        AppCommand::ExportForBlender,
+                        AppCommand::ShowTasks,
+                        AppCommand::Diagnostics,
+                        AppCommand::History,
+                        AppCommand::Jobs,
+                        AppCommand::Deploy,
+                        AppCommand::CreateArchive,
+                        AppCommand::GenerateReleaseManifest,
+                        AppCommand::VerifyManifest,
+                        AppCommand::CompareVersions,
+                        AppCommand::CompareScenes,
+                        AppCommand::GenerateDocs,
+                        AppCommand::ComparePinned,
+                        AppCommand::HealthCheck,
+                        AppCommand::TextBrowser,
+                        AppCommand::ToyBox,
+                        AppCommand::Tracks,
+                        AppCommand::UsageStats,
+                        AppCommand::ExportModPackage,
+                        AppCommand::InstallModPackage,
+                        AppCommand::ModBrowser,
+                    ] {
+                        if ui.add_enabled(self.command_enabled(command), egui::Button::new(command.label())).clicked() {
+                            ui.close_menu();
+                            self.execute_command(command, ctx);
+                        }
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.dry_run, "Dry run")
+                        .on_hover_text("Batch operations and deploys list the files they'd write instead of writing them");
+                    if ui.checkbox(&mut self.state.panels.split, "Hex view below the viewer").changed() {
+                        self.load_split_preview();
+                    }
+                    if ui.button("Dry run results").clicked() {
+                        ui.close_menu();
+                        self.show_dry_run = true;
+                    }
+                    ui.separator();
+                    ui.weak("Ctrl+Shift+P: all commands");
+                });
                 
-                if ui.button("Run Game").clicked() {
-                    self.run_game();
+                if ui.button(AppCommand::RunGame.label()).clicked() {
+                    self.execute_command(AppCommand::RunGame, ctx);
+                }
+                if !self.write_queue.items.is_empty() {
+                    let targets = self.write_queue.items.iter().map(|item| item.target.display().to_string()).collect::<Vec<_>>().join("\n");
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("{} writes waiting for the game to exit", self.write_queue.items.len()))
+                        .on_hover_text(targets);
                 }
             });
         });
@@ -1754,9 +9379,34 @@ fn search_file_tree(&self, entries: &[FileEntry], target_filename: &str) -> Opti
 
 impl eframe::App for TundraEditor {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.state.max_fps > 0 {
+            let frame_time = std::time::Duration::from_secs_f64(1.0 / self.state.max_fps as f64);
+            if let Some(remaining) = frame_time.checked_sub(self.last_frame.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+        self.last_frame = Instant::now();
+
         // Handle file dialog on the main thread
         self.handle_file_dialog(ctx);
 
+        // Textures exported for external editing are written back as they change
+        self.mtb_viewer.poll_watches(ctx);
+        self.start_texture_decoding(ctx);
+        self.update_remote_server(ctx);
+        self.sync_selection();
+        self.poll_health_check();
+        self.poll_instance_handoffs(ctx);
+        self.poll_write_queue(ctx);
+        self.poll_mod_install();
+
+        // Follow the OS switching between light and dark
+        let follows_system = self.state.theme == Theme::System;
+        self.system_theme.set_active(follows_system);
+        if self.system_theme.take_change() && follows_system {
+            self.apply_theme(ctx);
+        }
+
         // Check if we should exit the application
         if self.should_exit {
             println!("TS3 modding will never exist");
@@ -1776,12 +9426,28 @@ impl eframe::App for TundraEditor {
             }
             AppStep::Editor => {
                 self.show_editor(ctx);
+                self.show_command_palette(ctx);
             }
         }
+
+        // Also catches the window being closed with unsaved changes
+        self.show_unsaved_prompt(ctx);
+        self.show_transfer_prompt(ctx);
+        self.show_rename_prompt(ctx);
+        self.show_archive_prompt(ctx);
+
+        if let Some(skipped) = self.updater.show_ui(ctx) {
+            self.state.skipped_update = Some(skipped);
+            self.save_state();
+        }
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         // Save to JSON file
+        self.state.viewport = self.model_viewer.settings.clone();
+        if self.state.current_step == AppStep::Editor {
+            self.capture_layout();
+        }
         self.save_state();
         
         // Also save to eframe storage for compatibility
@@ -1792,9 +9458,16 @@ impl eframe::App for TundraEditor {
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         println!("Tundra editor is shutting down");
+
+        // Viewport settings are written back with the periodic save() and here, not on every slider drag
+        self.state.viewport = self.model_viewer.settings.clone();
+        if self.state.current_step == AppStep::Editor {
+            self.capture_layout();
+        }
+        self.save_state();
         
         // Clean up temp directory
-        if let Err(e) = fs::remove_dir_all(&self.temp_dir) {
+        if let Err(e) = fs::remove_dir_all(long_path(&self.temp_dir)) {
             eprintln!("Failed to clean up temp directory: {}", e);
         } else {
             println!("Cleaned up temp directory: {}", self.temp_dir.display());
@@ -1802,7 +9475,110 @@ impl eframe::App for TundraEditor {
     }
 }
 
+// `tundra --job <file>` runs a job file without opening the window
+fn run_job_from_command_line(path: &Path, config_path: &Path) -> i32 {
+    let job = match Job::load(path) {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("Failed to load job {}: {}", path.display(), e);
+            return 2;
+        }
+    };
+    let game_type = job.game.as_deref().and_then(TundraEditor::parse_game_type);
+    if let (Some(name), None) = (&job.game, &game_type) {
+        eprintln!("Unknown game in job file: {}", name);
+        return 2;
+    }
+
+    // Exports are named by the templates saved in Options, same as in the window
+    let templates = fs::read_to_string(config_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<AppState>(&content).ok())
+        .map(|state| state.naming)
+        .unwrap_or_default();
+    let naming = Naming { templates, game: game_type.as_ref().map(|g| format!("{:?}", g)).unwrap_or_default() };
+
+    let task = TaskContext::detached();
+    let summary = TundraEditor::run_job(&task, &job, game_type.as_ref(), &naming);
+    let errors = task.progress().errors;
+    for error in &errors {
+        eprintln!("  {}", error);
+    }
+    println!("{}", summary);
+    if errors.is_empty() { 0 } else { 1 }
+}
+
+// What the command line asked of the window
+struct LaunchOptions {
+    // Set by --remote-api
+    remote_api_session: bool,
+    config_path: PathBuf,
+    instance_lock: Option<InstanceLock>,
+    // `tundra <path>...`: files to open and folders to add as roots
+    open: Vec<PathBuf>,
+}
+
+// Arguments that aren't flags or flag values: files and folders to open
+fn path_arguments(args: &[String]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--config" || arg == "--job" || arg == "--workdir" {
+            iter.next();
+        } else if !arg.starts_with("--") {
+            paths.push(instance::absolute(Path::new(arg)));
+        }
+    }
+    paths
+}
+
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    // `--config <file>` uses another config in place of tundra_config.json, e.g. one per project
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(index) => match args.get(index + 1) {
+            Some(path) => instance::absolute(Path::new(path)),
+            None => {
+                eprintln!("Usage: tundra --config <config file>");
+                std::process::exit(2);
+            }
+        },
+        None => PathBuf::from(CONFIG_PATH),
+    };
+    if let Some(index) = args.iter().position(|arg| arg == "--job") {
+        let Some(job_path) = args.get(index + 1) else {
+            eprintln!("Usage: tundra --job <job file>");
+            std::process::exit(2);
+        };
+        std::process::exit(run_job_from_command_line(Path::new(job_path), &config_path));
+    }
+    let remote_api_session = args.iter().any(|arg| arg == "--remote-api");
+    let open = path_arguments(&args);
+    // Set by file associations: the OS starts Tundra in the opened file's folder, but the config,
+    // icons and temp/ are found relative to where Tundra lives. Paths above are resolved first.
+    if let Some(workdir) = args.iter().position(|arg| arg == "--workdir").and_then(|index| args.get(index + 1)) {
+        if let Err(e) = std::env::set_current_dir(workdir) {
+            eprintln!("Failed to change to {}: {}", workdir, e);
+        }
+    }
+
+    // One instance at a time shares temp/ and the config; later launches hand their paths to it.
+    // --multi-instance opts out for runs that are kept apart on purpose.
+    let instance_lock = if args.iter().any(|arg| arg == "--multi-instance") {
+        println!("Running without the instance lock; other instances share temp/ and the config");
+        None
+    } else {
+        match instance::acquire(&open) {
+            Ok(Acquired::Forwarded) => std::process::exit(0),
+            Ok(Acquired::Primary(lock)) => Some(lock),
+            Err(e) => {
+                eprintln!("Failed to take the instance lock, running without it: {}", e);
+                None
+            }
+        }
+    };
+    let launch = LaunchOptions { remote_api_session, config_path, instance_lock, open };
+
     // Load icon
     let icon = load_icon("src/art/icon.ico").expect("Failed to load app icon");
     
@@ -1817,7 +9593,7 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Tundra",
         options,
-        Box::new(|cc| Box::new(TundraEditor::new(cc))),
+        Box::new(move |cc| Box::new(TundraEditor::new(cc, launch))),
     )
 }
 